@@ -5,9 +5,16 @@ void require(int cond);
 #endif
 
 unsigned long long wasm_input(int);
+void wasm_private_input_bytes(void *dst, int byte_length);
 
 /* Convert list of u64 into bytes */
 static __inline__ void read_bytes_from_u64(void *dst, int byte_length, bool is_public) {
+    /* Private inputs are loaded by a single host call */
+    if (!is_public) {
+        wasm_private_input_bytes(dst, byte_length);
+        return;
+    }
+
     uint64_t *dst64 = (uint64_t*) dst;
     #pragma clang loop unroll(full)
     for (int i = 0; i * 8 < byte_length; i++) {
@@ -0,0 +1,19 @@
+#include <stdint.h>
+#include "secp256k1.h"
+
+unsigned long long wasm_input(int);
+
+/* Verify a signature given by the public inputs. */
+__attribute__((visibility("default")))
+int zkmain() {
+    uint64_t limbs[5][4];
+
+    for (int i = 0; i < 5; i++) {
+        for (int j = 0; j < 4; j++) {
+            limbs[i][j] = wasm_input(1);
+        }
+    }
+
+    secp256k1_verify(0, limbs[0], limbs[1], limbs[2], limbs[3], limbs[4]);
+    return 0;
+}
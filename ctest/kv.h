@@ -0,0 +1,24 @@
+#ifndef ZKWASM_KV_H
+#define ZKWASM_KV_H
+
+#include <stdint.h>
+
+/*
+ * The kv plugin serves a key-value store committed by the roots of a sparse
+ * Merkle tree. The first argument of every call is its index in the sequence
+ * of kv calls, a key never set reads 0.
+ */
+uint64_t zkwasm_kv_get(int index, uint64_t key);
+void zkwasm_kv_set(int index, uint64_t key, uint64_t value);
+
+static int kv_calls = 0;
+
+static __inline__ uint64_t kv_get(uint64_t key) {
+    return zkwasm_kv_get(kv_calls++, key);
+}
+
+static __inline__ void kv_set(uint64_t key, uint64_t value) {
+    zkwasm_kv_set(kv_calls++, key, value);
+}
+
+#endif
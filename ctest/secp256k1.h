@@ -0,0 +1,37 @@
+#ifndef ZKWASM_SECP256K1_H
+#define ZKWASM_SECP256K1_H
+
+#include <stdint.h>
+
+/*
+ * The secp256k1 plugin verifies ECDSA signatures. The k-th verification takes
+ * the little endian u64 limbs of the hash, r, s and the x and y of the public
+ * key at the indices k * 20 + 0..20, then zkwasm_secp256k1_verify(k) traps
+ * unless the signature is valid.
+ */
+void zkwasm_secp256k1_input(int index, uint64_t limb);
+void zkwasm_secp256k1_verify(int index);
+
+/* Verify the k-th signature, the hash is the big endian integer of the digest. */
+static __inline__ void secp256k1_verify(
+    int k,
+    const uint64_t hash[4],
+    const uint64_t r[4],
+    const uint64_t s[4],
+    const uint64_t pk_x[4],
+    const uint64_t pk_y[4]
+) {
+    const uint64_t *inputs[5] = {hash, r, s, pk_x, pk_y};
+
+    #pragma clang loop unroll(full)
+    for (int i = 0; i < 5; i++) {
+        #pragma clang loop unroll(full)
+        for (int j = 0; j < 4; j++) {
+            zkwasm_secp256k1_input(k * 20 + i * 4 + j, inputs[i][j]);
+        }
+    }
+
+    zkwasm_secp256k1_verify(k);
+}
+
+#endif
@@ -0,0 +1,55 @@
+#ifndef ZKWASM_U256_H
+#define ZKWASM_U256_H
+
+#include <stdint.h>
+
+/*
+ * The u256 plugin runs 256-bit operations on little endian u64 limbs. The k-th
+ * operation takes the limbs of a, b and m at the indices k * 12 + 0..12, its
+ * output limbs are read at the indices k * 8 + 0..8 by the function of the
+ * operation.
+ */
+void zkwasm_u256_input(int index, uint64_t limb);
+uint64_t zkwasm_u256_add(int index);
+uint64_t zkwasm_u256_sub(int index);
+uint64_t zkwasm_u256_mul(int index);
+uint64_t zkwasm_u256_divmod(int index);
+uint64_t zkwasm_u256_mulmod(int index);
+
+static __inline__ void u256_input(int k, int operand, const uint64_t x[4]) {
+    #pragma clang loop unroll(full)
+    for (int i = 0; i < 4; i++) {
+        zkwasm_u256_input(k * 12 + operand * 4 + i, x[i]);
+    }
+}
+
+/* r = a * b, the 512 bits of the product. */
+static __inline__ void u256_mul(int k, uint64_t r[8], const uint64_t a[4], const uint64_t b[4]) {
+    u256_input(k, 0, a);
+    u256_input(k, 1, b);
+
+    #pragma clang loop unroll(full)
+    for (int i = 0; i < 8; i++) {
+        r[i] = zkwasm_u256_mul(k * 8 + i);
+    }
+}
+
+/* r = a * b mod m, traps if m is 0. */
+static __inline__ void u256_mulmod(
+    int k,
+    uint64_t r[4],
+    const uint64_t a[4],
+    const uint64_t b[4],
+    const uint64_t m[4]
+) {
+    u256_input(k, 0, a);
+    u256_input(k, 1, b);
+    u256_input(k, 2, m);
+
+    #pragma clang loop unroll(full)
+    for (int i = 0; i < 4; i++) {
+        r[i] = zkwasm_u256_mulmod(k * 8 + i);
+    }
+}
+
+#endif
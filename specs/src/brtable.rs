@@ -9,6 +9,7 @@ pub struct BrTableEntry {
     pub index: u32,
     pub drop: u32,
     pub keep: u32,
+    pub keep_types: u32,
     pub dst_pc: u32,
 }
 
@@ -46,7 +47,29 @@ impl ElemTable {
     }
 }
 
+/*
+ * Tables are laid out back to back in LocationType::Table. The slot at `base`
+ * holds the current size of the table, followed by `capacity` element slots.
+ * The capacity is the maximum size of the table if declared, otherwise its
+ * initial size, so the table can't grow beyond it.
+ * A funcref is kept as encode_func_ref(type_idx, func_idx), and null as 0.
+ */
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct TableLayout {
+    pub table_idx: u32,
+    pub base: u32,
+    pub capacity: u32,
+}
+
+impl TableLayout {
+    pub fn elem_offset(&self, index: u32) -> u32 {
+        assert!(index < self.capacity);
+
+        self.base + 1 + index
+    }
+}
+
 pub enum IndirectClass {
     BrTable,
-    CallIndirect,
+    Table,
 }
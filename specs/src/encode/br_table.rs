@@ -1,14 +1,14 @@
 use num_bigint::BigUint;
 
 use crate::{
-    brtable::{BrTableEntry, ElemEntry, IndirectClass},
+    brtable::{BrTableEntry, IndirectClass, TableLayout},
     encode::COMMON_RANGE_OFFSET,
 };
 
 use super::FromBn;
 
 lazy_static! {
-    static ref INDIRECT_CLASS_SHIFT: BigUint = BigUint::from(1u64) << 192;
+    static ref INDIRECT_CLASS_SHIFT: BigUint = BigUint::from(1u64) << 224;
 }
 
 pub fn encode_br_table_entry<T: FromBn>(
@@ -17,13 +17,15 @@ pub fn encode_br_table_entry<T: FromBn>(
     index: T,
     drop: T,
     keep: T,
+    keep_types: T,
     dst_pc: T,
 ) -> T {
     const FID_SHIFT: u32 = IID_SHIFT + COMMON_RANGE_OFFSET;
     const IID_SHIFT: u32 = INDEX_SHIFT + COMMON_RANGE_OFFSET;
     const INDEX_SHIFT: u32 = DROP_SHIFT + COMMON_RANGE_OFFSET;
     const DROP_SHIFT: u32 = KEEP_SHIFT + COMMON_RANGE_OFFSET;
-    const KEEP_SHIFT: u32 = DST_PC_SHIFT + COMMON_RANGE_OFFSET;
+    const KEEP_SHIFT: u32 = KEEP_TYPES_SHIFT + COMMON_RANGE_OFFSET;
+    const KEEP_TYPES_SHIFT: u32 = DST_PC_SHIFT + COMMON_RANGE_OFFSET;
     const DST_PC_SHIFT: u32 = 0;
 
     T::from_bn(&(BigUint::from(IndirectClass::BrTable as u64))) * T::from_bn(&INDIRECT_CLASS_SHIFT)
@@ -32,20 +34,27 @@ pub fn encode_br_table_entry<T: FromBn>(
         + index * T::from_bn(&(BigUint::from(1u64) << INDEX_SHIFT))
         + drop * T::from_bn(&(BigUint::from(1u64) << DROP_SHIFT))
         + keep * T::from_bn(&(BigUint::from(1u64) << KEEP_SHIFT))
+        + keep_types * T::from_bn(&(BigUint::from(1u64) << KEEP_TYPES_SHIFT))
         + dst_pc
 }
 
-pub fn encode_elem_entry<T: FromBn>(table_idx: T, type_idx: T, offset: T, func_idx: T) -> T {
-    const TABLE_INDEX_SHIFT: u32 = TYPE_INDEX_SHIFT + COMMON_RANGE_OFFSET;
-    const TYPE_INDEX_SHIFT: u32 = OFFSET_SHIFT + COMMON_RANGE_OFFSET;
-    const OFFSET_SHIFT: u32 = FUNC_INDEX + COMMON_RANGE_OFFSET;
-    const FUNC_INDEX: u32 = 0;
+pub fn encode_table_layout<T: FromBn>(table_idx: T, base: T, capacity: T) -> T {
+    const TABLE_INDEX_SHIFT: u32 = BASE_SHIFT + COMMON_RANGE_OFFSET;
+    const BASE_SHIFT: u32 = CAPACITY_SHIFT + COMMON_RANGE_OFFSET;
+    const CAPACITY_SHIFT: u32 = 0;
 
-    T::from_bn(&(BigUint::from(IndirectClass::CallIndirect as u64)))
-        * T::from_bn(&INDIRECT_CLASS_SHIFT)
+    T::from_bn(&(BigUint::from(IndirectClass::Table as u64))) * T::from_bn(&INDIRECT_CLASS_SHIFT)
         + table_idx * T::from_bn(&(BigUint::from(1u64) << TABLE_INDEX_SHIFT))
-        + type_idx * T::from_bn(&(BigUint::from(1u64) << TYPE_INDEX_SHIFT))
-        + offset * T::from_bn(&(BigUint::from(1u64) << OFFSET_SHIFT))
+        + base * T::from_bn(&(BigUint::from(1u64) << BASE_SHIFT))
+        + capacity
+}
+
+/*
+ * The type is part of the reference so that call_indirect checks the signature
+ * with the element read, a null reference never matches since it is 0.
+ */
+pub fn encode_func_ref<T: FromBn>(type_idx: T, func_idx: T) -> T {
+    (type_idx + T::from_bn(&BigUint::from(1u64))) * T::from_bn(&(BigUint::from(1u64) << 32))
         + func_idx
 }
 
@@ -57,18 +66,18 @@ impl BrTableEntry {
             BigUint::from(self.index),
             BigUint::from(self.drop),
             BigUint::from(self.keep),
+            BigUint::from(self.keep_types),
             BigUint::from(self.dst_pc),
         )
     }
 }
 
-impl ElemEntry {
+impl TableLayout {
     pub fn encode(&self) -> BigUint {
-        encode_elem_entry(
+        encode_table_layout(
             BigUint::from(self.table_idx),
-            BigUint::from(self.type_idx),
-            BigUint::from(self.offset),
-            BigUint::from(self.func_idx),
+            BigUint::from(self.base),
+            BigUint::from(self.capacity),
         )
     }
 }
@@ -1,8 +1,13 @@
 use num_bigint::BigUint;
 
-use crate::itable::{
-    ConversionOp, OpcodeClass, OPCODE_ARG0_SHIFT as OPCODE_ARG0, OPCODE_ARG1_SHIFT as OPCODE_ARG1,
-    OPCODE_CLASS_SHIFT as OPCODE_CLASS,
+use crate::{
+    itable::{
+        ConversionOp, OpcodeClass, OPCODE_ARG0_SHIFT as OPCODE_ARG0,
+        OPCODE_ARG1_SHIFT as OPCODE_ARG1, OPCODE_ARG2_SHIFT as OPCODE_ARG2,
+        OPCODE_CLASS_SHIFT as OPCODE_CLASS, OPCODE_KEEP_TYPES_SHIFT as OPCODE_KEEP_TYPES,
+    },
+    mtable::VarType,
+    types::ValueType,
 };
 
 use super::FromBn;
@@ -11,6 +16,22 @@ lazy_static! {
     static ref OPCODE_CLASS_SHIFT: BigUint = BigUint::from(1u64) << OPCODE_CLASS;
     static ref OPCODE_ARG0_SHIFT: BigUint = BigUint::from(1u64) << OPCODE_ARG0;
     static ref OPCODE_ARG1_SHIFT: BigUint = BigUint::from(1u64) << OPCODE_ARG1;
+    static ref OPCODE_ARG2_SHIFT: BigUint = BigUint::from(1u64) << OPCODE_ARG2;
+    static ref OPCODE_KEEP_TYPES_SHIFT: BigUint = BigUint::from(1u64) << OPCODE_KEEP_TYPES;
+}
+
+/*
+ * Bit i is set if the i-th kept value is 64-bit, the kept values are
+ * ordered from the top of the stack. Only the values kept in the step of the
+ * class are encoded, see OpcodeClass::max_keep.
+ */
+pub fn encode_keep_types(class: OpcodeClass, keep: &[ValueType]) -> u64 {
+    keep.iter()
+        .take(class.max_keep())
+        .enumerate()
+        .fold(0, |acc, (i, vtype)| {
+            acc | (((VarType::from(*vtype) == VarType::I64) as u64) << i)
+        })
 }
 
 pub fn encode_global_get<T: FromBn>(globalidx: T) -> T {
@@ -28,54 +49,86 @@ pub fn encode_call<T: FromBn>(function_index: T) -> T {
         + function_index * T::from_bn(&OPCODE_ARG0_SHIFT)
 }
 
-pub fn encode_call_indirect<T: FromBn>(type_index: T) -> T {
+pub fn encode_call_indirect<T: FromBn>(type_index: T, table_index: T) -> T {
     T::from_bn(&(BigUint::from(OpcodeClass::CallIndirect as u64))) * T::from_bn(&OPCODE_CLASS_SHIFT)
+        + table_index * T::from_bn(&OPCODE_ARG2_SHIFT)
         + type_index * T::from_bn(&OPCODE_ARG0_SHIFT)
 }
 
+pub fn encode_return_call<T: FromBn>(drop: T, keep: T, keep_types: T, function_index: T) -> T {
+    T::from_bn(&(BigUint::from(OpcodeClass::ReturnCall as u64))) * T::from_bn(&OPCODE_CLASS_SHIFT)
+        + drop * T::from_bn(&OPCODE_ARG0_SHIFT)
+        + keep * T::from_bn(&OPCODE_ARG1_SHIFT)
+        + keep_types * T::from_bn(&OPCODE_KEEP_TYPES_SHIFT)
+        + function_index
+}
+
+pub fn encode_return_call_indirect<T: FromBn>(
+    drop: T,
+    keep: T,
+    keep_types: T,
+    type_index: T,
+    table_index: T,
+) -> T {
+    T::from_bn(&(BigUint::from(OpcodeClass::ReturnCallIndirect as u64)))
+        * T::from_bn(&OPCODE_CLASS_SHIFT)
+        + table_index * T::from_bn(&OPCODE_ARG2_SHIFT)
+        + drop * T::from_bn(&OPCODE_ARG0_SHIFT)
+        + keep * T::from_bn(&OPCODE_ARG1_SHIFT)
+        + keep_types * T::from_bn(&OPCODE_KEEP_TYPES_SHIFT)
+        + type_index
+}
+
+/*
+ * table.get, table.set, table.size and table.grow.
+ */
+pub fn encode_table_op<T: FromBn>(class: OpcodeClass, table_index: T) -> T {
+    T::from_bn(&(BigUint::from(class as u64))) * T::from_bn(&OPCODE_CLASS_SHIFT)
+        + table_index * T::from_bn(&OPCODE_ARG2_SHIFT)
+}
+
 pub fn encode_call_host<T: FromBn>(op: T, is_ret: T) -> T {
     T::from_bn(&(BigUint::from(OpcodeClass::CallHost as u64))) * T::from_bn(&OPCODE_CLASS_SHIFT)
         + op * T::from_bn(&OPCODE_ARG0_SHIFT)
         + is_ret * T::from_bn(&OPCODE_ARG1_SHIFT)
 }
 
-pub fn encode_br<T: FromBn>(drop: T, keep: T, dst_pc: T) -> T {
+pub fn encode_br<T: FromBn>(drop: T, keep: T, keep_types: T, dst_pc: T) -> T {
     T::from_bn(&(BigUint::from(OpcodeClass::Br as u64))) * T::from_bn(&OPCODE_CLASS_SHIFT)
         + drop * T::from_bn(&OPCODE_ARG0_SHIFT)
         + keep * T::from_bn(&OPCODE_ARG1_SHIFT)
+        + keep_types * T::from_bn(&OPCODE_KEEP_TYPES_SHIFT)
+        + dst_pc
+}
+
+pub fn encode_br_if<T: FromBn>(drop: T, keep: T, keep_types: T, dst_pc: T) -> T {
+    T::from_bn(&(BigUint::from(OpcodeClass::BrIf as u64))) * T::from_bn(&OPCODE_CLASS_SHIFT)
+        + drop * T::from_bn(&OPCODE_ARG0_SHIFT)
+        + keep * T::from_bn(&OPCODE_ARG1_SHIFT)
+        + keep_types * T::from_bn(&OPCODE_KEEP_TYPES_SHIFT)
         + dst_pc
 }
 
-pub fn encode_br_if_eqz<T: FromBn>(drop: T, keep: T, dst_pc: T) -> T {
+pub fn encode_br_if_eqz<T: FromBn>(drop: T, keep: T, keep_types: T, dst_pc: T) -> T {
     T::from_bn(&(BigUint::from(OpcodeClass::BrIfEqz as u64))) * T::from_bn(&OPCODE_CLASS_SHIFT)
         + drop * T::from_bn(&OPCODE_ARG0_SHIFT)
         + keep * T::from_bn(&OPCODE_ARG1_SHIFT)
+        + keep_types * T::from_bn(&OPCODE_KEEP_TYPES_SHIFT)
         + dst_pc
 }
 
+pub fn encode_return<T: FromBn>(drop: T, keep: T, keep_types: T) -> T {
+    T::from_bn(&(BigUint::from(OpcodeClass::Return as u64))) * T::from_bn(&OPCODE_CLASS_SHIFT)
+        + drop * T::from_bn(&OPCODE_ARG0_SHIFT)
+        + keep * T::from_bn(&OPCODE_ARG1_SHIFT)
+        + keep_types * T::from_bn(&OPCODE_KEEP_TYPES_SHIFT)
+}
+
 pub fn encode_br_table<T: FromBn>(len: T) -> T {
     T::from_bn(&BigUint::from(OpcodeClass::BrTable as u64)) + len
 }
 
 pub fn encode_conversion<T: FromBn>(op: ConversionOp) -> T {
-    match op {
-        ConversionOp::I32WrapI64 => {
-            T::from_bn(&(BigUint::from(OpcodeClass::Conversion as u64)))
-                * T::from_bn(&OPCODE_CLASS_SHIFT)
-                + T::from_bn(&(BigUint::from(ConversionOp::I32WrapI64 as u64)))
-                    * T::from_bn(&OPCODE_ARG0_SHIFT)
-        }
-        ConversionOp::I64ExtendI32s => {
-            T::from_bn(&(BigUint::from(OpcodeClass::Conversion as u64)))
-                * T::from_bn(&OPCODE_CLASS_SHIFT)
-                + T::from_bn(&(BigUint::from(ConversionOp::I64ExtendI32s as u64)))
-                    * T::from_bn(&OPCODE_ARG0_SHIFT)
-        }
-        ConversionOp::I64ExtendI32u => {
-            T::from_bn(&(BigUint::from(OpcodeClass::Conversion as u64)))
-                * T::from_bn(&OPCODE_CLASS_SHIFT)
-                + T::from_bn(&(BigUint::from(ConversionOp::I64ExtendI32u as u64)))
-                    * T::from_bn(&OPCODE_ARG0_SHIFT)
-        }
-    }
+    T::from_bn(&(BigUint::from(OpcodeClass::Conversion as u64))) * T::from_bn(&OPCODE_CLASS_SHIFT)
+        + T::from_bn(&(BigUint::from(op as u64))) * T::from_bn(&OPCODE_ARG0_SHIFT)
 }
@@ -1,8 +1,9 @@
+use num_bigint::BigUint;
 use serde::Serialize;
 
 use crate::{host_function::HostPlugin, step::StepInfo};
 
-use super::itable::InstructionTableEntry;
+use super::{itable::InstructionTableEntry, jtable::StaticFrameEntry};
 
 #[derive(Clone, Debug, Serialize)]
 pub struct EventTableEntry {
@@ -64,4 +65,46 @@ impl EventTable {
             })
             .collect::<Vec<_>>()
     }
+
+    /*
+     * The encoded instruction table entry of the trapping step, if the
+     * execution trapped. It is the instance of a proof in trap mode.
+     */
+    pub fn trap_point(&self) -> Option<BigUint> {
+        self.0
+            .last()
+            .filter(|entry| entry.step_info.is_trap())
+            .map(|entry| entry.inst.encode())
+    }
+
+    /*
+     * The callee fid of the frame of each step, the frame entered by the last
+     * call not returned yet. The static frames are entered first, a tail call
+     * runs the callee in the frame it replaces.
+     */
+    pub fn callee_fids(&self, static_jtable: &[StaticFrameEntry]) -> Vec<u32> {
+        let mut frames = static_jtable
+            .iter()
+            .rev()
+            .map(|entry| entry.callee_fid)
+            .collect::<Vec<_>>();
+
+        self.0
+            .iter()
+            .map(|entry| {
+                let callee_fid = frames.last().cloned().unwrap_or_default();
+
+                match &entry.step_info {
+                    StepInfo::Call { index } => frames.push(*index),
+                    StepInfo::CallIndirect { func_index, .. } => frames.push(*func_index),
+                    StepInfo::Return { .. } => {
+                        frames.pop();
+                    }
+                    _ => {}
+                }
+
+                callee_fid
+            })
+            .collect()
+    }
 }
@@ -2,9 +2,21 @@ use num_bigint::BigUint;
 
 use crate::encode::FromBn;
 
-pub fn encode_host_call_entry<T: FromBn>(host_call_idx: T, op: T, is_ret: T, arg: T) -> T {
+pub fn encode_host_call_entry<T: FromBn>(
+    host_call_idx: T,
+    op: T,
+    is_ret: T,
+    is_public: T,
+    arg: T,
+) -> T {
     host_call_idx * T::from_bn(&(BigUint::from(1u64) << 96))
         + op * T::from_bn(&(BigUint::from(1u64) << 80))
+        + is_public * T::from_bn(&(BigUint::from(1u64) << 72))
         + is_ret * T::from_bn(&(BigUint::from(1u64) << 64))
         + arg
 }
+
+// Offset by one, so that the zero rows of the table never pass for an op.
+pub fn encode_host_call_visibility<T: FromBn>(op: T, is_public: T) -> T {
+    (op + T::from_bn(&BigUint::from(1u64))) * T::from_bn(&BigUint::from(2u64)) + is_public
+}
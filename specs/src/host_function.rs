@@ -1,6 +1,11 @@
+use std::collections::BTreeMap;
+
 use serde::Serialize;
+use strum_macros::EnumIter;
 
-use crate::{external_host_call_table::ExternalHostCallSignature, types::ValueType};
+use crate::{
+    external_host_call_table::ExternalHostCallSignature, mtable::AccessType, types::ValueType,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Signature {
@@ -27,9 +32,65 @@ pub enum HostFunctionDesc {
     },
 }
 
-#[derive(Clone, Debug, Serialize, Copy, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, Serialize, Copy, PartialEq, Eq, Hash, EnumIter)]
 pub enum HostPlugin {
-    HostInput = 0,
+    HostInput,
     Sha256,
     Require,
+    Keccak,
+    Poseidon,
+    Sha256Compress,
+    Secp256k1,
+    U256,
+    Kv,
+    // A plugin out of the crate, by the index allocated in the plugin registry.
+    Registered(usize),
+}
+
+impl HostPlugin {
+    // The ids of the registered plugins follow the builtin plugins.
+    pub fn id(&self) -> usize {
+        match self {
+            HostPlugin::HostInput => 0,
+            HostPlugin::Sha256 => 1,
+            HostPlugin::Require => 2,
+            HostPlugin::Keccak => 3,
+            HostPlugin::Poseidon => 4,
+            HostPlugin::Sha256Compress => 5,
+            HostPlugin::Secp256k1 => 6,
+            HostPlugin::U256 => 7,
+            HostPlugin::Kv => 8,
+            HostPlugin::Registered(index) => 9 + index,
+        }
+    }
+}
+
+/*
+ * An access of a host function on a u64 block of the guest memory, the offset
+ * is the index of the block. A write is preceded by a read of the block, so
+ * that the bytes out of the written range are kept.
+ */
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct HostMemoryAccess {
+    pub offset: u32,
+    pub atype: AccessType,
+    pub value: u64,
+}
+
+/*
+ * The guest memory accesses of the host calls by eid, in order. The tracer
+ * only sees the arguments of a host call, the accesses are recorded by the
+ * host env beside the trace.
+ */
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct HostMemoryAccessTable(BTreeMap<u32, Vec<HostMemoryAccess>>);
+
+impl HostMemoryAccessTable {
+    pub fn new(accesses: BTreeMap<u32, Vec<HostMemoryAccess>>) -> Self {
+        HostMemoryAccessTable(accesses)
+    }
+
+    pub fn accesses(&self, eid: u32) -> &[HostMemoryAccess] {
+        self.0.get(&eid).map_or(&[], |accesses| accesses.as_slice())
+    }
 }
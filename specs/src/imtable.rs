@@ -1,4 +1,10 @@
-use crate::mtable::{LocationType, VarType};
+use num_bigint::BigUint;
+
+use crate::{
+    brtable::{ElemTable, TableLayout},
+    encode::br_table::encode_func_ref,
+    mtable::{LocationType, VarType},
+};
 use serde::Serialize;
 
 #[derive(Serialize, Debug, Clone)]
@@ -51,3 +57,138 @@ impl InitMemoryTable {
         self.0.last().map_or(0, |entry| entry.offset + 1)
     }
 }
+
+/// The initial size and the capacity of each table of the wasm module, in the
+/// order of the table index space: imported tables first. The capacity is the
+/// maximum if declared, the initial size otherwise.
+pub fn table_limits(wasm: &[u8]) -> Result<Vec<(u32, u32)>, parity_wasm::elements::Error> {
+    let module = parity_wasm::deserialize_buffer::<parity_wasm::elements::Module>(wasm)?;
+
+    let imported = module.import_section().map_or(vec![], |section| {
+        section
+            .entries()
+            .iter()
+            .filter_map(|entry| match entry.external() {
+                parity_wasm::elements::External::Table(table) => Some(*table.limits()),
+                _ => None,
+            })
+            .collect()
+    });
+    let declared = module.table_section().map_or(vec![], |section| {
+        section
+            .entries()
+            .iter()
+            .map(|table| *table.limits())
+            .collect()
+    });
+
+    Ok(imported
+        .into_iter()
+        .chain(declared)
+        .map(|limits| {
+            (
+                limits.initial(),
+                limits.maximum().unwrap_or(limits.initial()),
+            )
+        })
+        .collect())
+}
+
+/// Lays out the tables in LocationType::Table, see TableLayout. `limits` lists the
+/// initial size and the capacity of each table, `elem_table` holds the initial elements.
+/// Returns the init entries and the layout of each table.
+pub fn tables_entries(
+    limits: &[(u32, u32)],
+    elem_table: &ElemTable,
+) -> (Vec<InitMemoryTableEntry>, Vec<TableLayout>) {
+    let mut entries = vec![];
+    let mut layouts = vec![];
+    let mut base = 0u32;
+
+    for (table_idx, (initial_size, capacity)) in limits.iter().enumerate() {
+        assert!(initial_size <= capacity);
+
+        let layout = TableLayout {
+            table_idx: table_idx as u32,
+            base,
+            capacity: *capacity,
+        };
+
+        entries.push(InitMemoryTableEntry {
+            ltype: LocationType::Table,
+            is_mutable: true,
+            offset: base,
+            vtype: VarType::I64,
+            value: *initial_size as u64,
+        });
+
+        for index in 0..*capacity {
+            entries.push(InitMemoryTableEntry {
+                ltype: LocationType::Table,
+                is_mutable: true,
+                offset: layout.elem_offset(index),
+                vtype: VarType::I64,
+                value: 0,
+            });
+        }
+
+        layouts.push(layout);
+        base += capacity + 1;
+    }
+
+    for elem in elem_table.entries() {
+        let offset = layouts[elem.table_idx as usize].elem_offset(elem.offset) as usize;
+
+        // The slots are consecutive from 0, so the entries are indexed by offset
+        entries[offset].value = u64::try_from(encode_func_ref(
+            BigUint::from(elem.type_idx),
+            BigUint::from(elem.func_idx),
+        ))
+        .unwrap();
+    }
+
+    (entries, layouts)
+}
+
+/// Lays out passive data segments back to back in LocationType::Data, each segment
+/// takes a mutable block holding its length, cleared by data.drop, followed by the
+/// immutable blocks of its bytes. Returns the init entries and the block of the length
+/// of each segment, which is the `segment_offset` of `Opcode::MemoryInit`.
+pub fn passive_data_segments_entries(
+    segments: &[Vec<u8>],
+) -> (Vec<InitMemoryTableEntry>, Vec<u32>) {
+    let mut entries = vec![];
+    let mut segment_offsets = vec![];
+    let mut block_offset = 0u32;
+
+    for segment in segments {
+        segment_offsets.push(block_offset);
+
+        entries.push(InitMemoryTableEntry {
+            ltype: LocationType::Data,
+            is_mutable: true,
+            offset: block_offset,
+            vtype: VarType::I64,
+            value: segment.len() as u64,
+        });
+
+        block_offset += 1;
+
+        for chunk in segment.chunks(8) {
+            let mut bytes = [0u8; 8];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+
+            entries.push(InitMemoryTableEntry {
+                ltype: LocationType::Data,
+                is_mutable: false,
+                offset: block_offset,
+                vtype: VarType::I64,
+                value: u64::from_le_bytes(bytes),
+            });
+
+            block_offset += 1;
+        }
+    }
+
+    (entries, segment_offsets)
+}
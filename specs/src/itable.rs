@@ -3,8 +3,10 @@ use crate::{
     brtable::{BrTable, BrTableEntry},
     encode::{
         opcode::{
-            encode_br_if_eqz, encode_br_table, encode_call, encode_call_host, encode_call_indirect,
-            encode_global_get, encode_global_set,
+            encode_br, encode_br_if, encode_br_if_eqz, encode_br_table, encode_call,
+            encode_call_host, encode_call_indirect, encode_global_get, encode_global_set,
+            encode_keep_types, encode_return, encode_return_call, encode_return_call_indirect,
+            encode_table_op,
         },
         COMMON_RANGE_OFFSET,
     },
@@ -48,6 +50,16 @@ pub enum OpcodeClass {
     MemorySize,
     MemoryGrow,
     Conversion,
+    MemoryFill,
+    MemoryCopy,
+    MemoryInit,
+    DataDrop,
+    ReturnCall,
+    ReturnCallIndirect,
+    TableGet,
+    TableSet,
+    TableSize,
+    TableGrow,
     ForeignPluginStart,
 }
 
@@ -72,15 +84,31 @@ impl OpcodeClass {
             OpcodeClass::BrIf => 1,
             OpcodeClass::BrIfEqz => 1,
             OpcodeClass::BrTable => 1,
-            OpcodeClass::Unreachable => todo!(),
+            OpcodeClass::Unreachable => 0,
             OpcodeClass::Call => 0,
-            OpcodeClass::CallHost => 1, // Push or pop
-            OpcodeClass::CallIndirect => 1,
+            OpcodeClass::CallHost => 1,     // Push or pop
+            OpcodeClass::CallIndirect => 2, // Pop offset, read the element from the table
             OpcodeClass::Store => 4, // Load value from stack, load address from stack, read raw value, write value
             OpcodeClass::Load => 3,  // pop address, load memory, push stack
             OpcodeClass::MemorySize => 1,
             OpcodeClass::MemoryGrow => 2,
             OpcodeClass::Conversion => 2,
+            // Pop the 3 operands, accesses on memory blocks are counted in assigned_extra_mops
+            OpcodeClass::MemoryFill => 3,
+            OpcodeClass::MemoryCopy => 3,
+            // Pop the 3 operands and read the length of the segment
+            OpcodeClass::MemoryInit => 4,
+            // Clear the length of the segment
+            OpcodeClass::DataDrop => 1,
+            // Kept args are counted in assigned_extra_mops
+            OpcodeClass::ReturnCall => 0,
+            OpcodeClass::ReturnCallIndirect => 2,
+            // Accesses on the size slot of the table are counted in the opcode class
+            OpcodeClass::TableGet => 4,
+            OpcodeClass::TableSet => 4,
+            OpcodeClass::TableSize => 2,
+            // The write on the size only happens if the table grows, see assigned_extra_mops
+            OpcodeClass::TableGrow => 4,
             OpcodeClass::ForeignPluginStart => 0,
         }
     }
@@ -93,6 +121,25 @@ impl OpcodeClass {
             _ => 0,
         }
     }
+
+    /*
+     * The number of values kept in a step of the class, each of them takes two
+     * of the mtable lookups of the step. The values kept beyond them are moved
+     * in the keep table.
+     */
+    pub const fn max_keep(&self) -> usize {
+        match self {
+            OpcodeClass::Br => 3,
+            OpcodeClass::Return => 3,
+            OpcodeClass::ReturnCall => 3,
+            // The condition or the index takes a lookup
+            OpcodeClass::BrIf => 2,
+            OpcodeClass::BrIfEqz => 2,
+            OpcodeClass::BrTable => 2,
+            OpcodeClass::ReturnCallIndirect => 2,
+            _ => 0,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord, Hash)]
@@ -166,6 +213,11 @@ pub enum ConversionOp {
     I32WrapI64,
     I64ExtendI32s,
     I64ExtendI32u,
+    I32Extend8s,
+    I32Extend16s,
+    I64Extend8s,
+    I64Extend16s,
+    I64Extend32s,
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -253,9 +305,44 @@ pub enum Opcode {
     Call {
         index: u32,
     },
+    /*
+     * call_indirect can only name the table 0, it is encoded with the table
+     * index of return_call_indirect.
+     */
     CallIndirect {
         type_idx: u32,
     },
+    /*
+     * Tail calls reuse the current frame: the args of the callee are kept
+     * and the rest of the current frame is dropped, like a return.
+     */
+    ReturnCall {
+        drop: u32,
+        keep: Vec<ValueType>,
+        index: u32,
+    },
+    ReturnCallIndirect {
+        drop: u32,
+        keep: Vec<ValueType>,
+        type_idx: u32,
+        table_idx: u32,
+    },
+    /*
+     * References are kept on the stack as I64, ref.null and ref.func are
+     * compiled to Const, see brtable::TableLayout for the encoding.
+     */
+    TableGet {
+        table_idx: u32,
+    },
+    TableSet {
+        table_idx: u32,
+    },
+    TableSize {
+        table_idx: u32,
+    },
+    TableGrow {
+        table_idx: u32,
+    },
     InternalHostCall {
         plugin: HostPlugin,
         function_index: usize,
@@ -279,6 +366,21 @@ pub enum Opcode {
     Conversion {
         class: ConversionOp,
     },
+    MemoryFill,
+    MemoryCopy,
+    /*
+     * segment_offset is the block of the passive segment in LocationType::Data,
+     * it holds the length of the segment and is followed by its bytes, see
+     * passive_data_segments_entries.
+     */
+    MemoryInit {
+        segment: u32,
+        segment_offset: u32,
+    },
+    DataDrop {
+        segment: u32,
+        segment_offset: u32,
+    },
 }
 
 impl Opcode {
@@ -303,9 +405,13 @@ impl Opcode {
 }
 
 pub const OPCODE_SHIFT: u32 = OPCODE_CLASS_SHIFT + 16;
-pub const OPCODE_CLASS_SHIFT: u32 = OPCODE_ARG0_SHIFT + COMMON_RANGE_OFFSET;
+pub const OPCODE_CLASS_SHIFT: u32 = OPCODE_ARG2_SHIFT + COMMON_RANGE_OFFSET;
+// Index of the table accessed by the table opcodes and the indirect calls.
+pub const OPCODE_ARG2_SHIFT: u32 = OPCODE_ARG0_SHIFT + COMMON_RANGE_OFFSET;
 pub const OPCODE_ARG0_SHIFT: u32 = OPCODE_ARG1_SHIFT + COMMON_RANGE_OFFSET;
 pub const OPCODE_ARG1_SHIFT: u32 = 64;
+// Types of the kept values of branch and return, packed above the branch target.
+pub const OPCODE_KEEP_TYPES_SHIFT: u32 = 32;
 pub const OPCODE_CELL: usize = 4;
 
 impl Into<BigUint> for Opcode {
@@ -335,12 +441,11 @@ impl Into<BigUint> for Opcode {
             }
             Opcode::Drop => BigUint::from(OpcodeClass::Drop as u64) << OPCODE_CLASS_SHIFT,
             Opcode::Select => BigUint::from(OpcodeClass::Select as u64) << OPCODE_CLASS_SHIFT,
-            Opcode::Return { drop, keep } => {
-                (BigUint::from(OpcodeClass::Return as u64) << OPCODE_CLASS_SHIFT)
-                    + (BigUint::from(drop as u64) << OPCODE_ARG0_SHIFT)
-                    + (BigUint::from(keep.len() as u64) << OPCODE_ARG1_SHIFT)
-                    + keep.first().map_or(0u64, |x| VarType::from(*x) as u64)
-            }
+            Opcode::Return { drop, keep } => encode_return(
+                BigUint::from(drop as u64),
+                BigUint::from(keep.len() as u64),
+                BigUint::from(encode_keep_types(OpcodeClass::Return, &keep)),
+            ),
             Opcode::Bin { class, vtype } => {
                 (BigUint::from(OpcodeClass::Bin as u64) << OPCODE_CLASS_SHIFT)
                     + (BigUint::from(class as u64) << OPCODE_ARG0_SHIFT)
@@ -371,23 +476,22 @@ impl Into<BigUint> for Opcode {
                     + (BigUint::from(class as u64) << OPCODE_ARG0_SHIFT)
                     + (BigUint::from(vtype as u64) << OPCODE_ARG1_SHIFT)
             }
-            Opcode::Br { drop, keep, dst_pc } => {
-                // TODO: should encode type of keep values?
-                (BigUint::from(OpcodeClass::Br as u64) << OPCODE_CLASS_SHIFT)
-                    + (BigUint::from(drop as u64) << OPCODE_ARG0_SHIFT)
-                    + (BigUint::from(keep.len() as u64) << OPCODE_ARG1_SHIFT)
-                    + dst_pc
-            }
-            Opcode::BrIf { drop, keep, dst_pc } => {
-                // TODO: should encode type of keep values?
-                (BigUint::from(OpcodeClass::BrIf as u64) << OPCODE_CLASS_SHIFT)
-                    + (BigUint::from(drop as u64) << OPCODE_ARG0_SHIFT)
-                    + (BigUint::from(keep.len() as u64) << OPCODE_ARG1_SHIFT)
-                    + dst_pc
-            }
+            Opcode::Br { drop, keep, dst_pc } => encode_br(
+                BigUint::from(drop as u64),
+                BigUint::from(keep.len() as u64),
+                BigUint::from(encode_keep_types(OpcodeClass::Br, &keep)),
+                BigUint::from(dst_pc),
+            ),
+            Opcode::BrIf { drop, keep, dst_pc } => encode_br_if(
+                BigUint::from(drop as u64),
+                BigUint::from(keep.len() as u64),
+                BigUint::from(encode_keep_types(OpcodeClass::BrIf, &keep)),
+                BigUint::from(dst_pc),
+            ),
             Opcode::BrIfEqz { drop, keep, dst_pc } => encode_br_if_eqz(
                 BigUint::from(drop as u64),
                 BigUint::from(keep.len() as u64),
+                BigUint::from(encode_keep_types(OpcodeClass::BrIfEqz, &keep)),
                 BigUint::from(dst_pc),
             ),
             Opcode::BrTable { targets } => encode_br_table(BigUint::from(targets.len())),
@@ -396,7 +500,37 @@ impl Into<BigUint> for Opcode {
             }
             Opcode::Call { index } => encode_call(BigUint::from(index as u64)),
             Opcode::CallIndirect { type_idx } => {
-                encode_call_indirect(BigUint::from(type_idx as u64))
+                encode_call_indirect(BigUint::from(type_idx as u64), BigUint::from(0u64))
+            }
+            Opcode::ReturnCall { drop, keep, index } => encode_return_call(
+                BigUint::from(drop as u64),
+                BigUint::from(keep.len() as u64),
+                BigUint::from(encode_keep_types(OpcodeClass::ReturnCall, &keep)),
+                BigUint::from(index as u64),
+            ),
+            Opcode::ReturnCallIndirect {
+                drop,
+                keep,
+                type_idx,
+                table_idx,
+            } => encode_return_call_indirect(
+                BigUint::from(drop as u64),
+                BigUint::from(keep.len() as u64),
+                BigUint::from(encode_keep_types(OpcodeClass::ReturnCallIndirect, &keep)),
+                BigUint::from(type_idx as u64),
+                BigUint::from(table_idx as u64),
+            ),
+            Opcode::TableGet { table_idx } => {
+                encode_table_op(OpcodeClass::TableGet, BigUint::from(table_idx as u64))
+            }
+            Opcode::TableSet { table_idx } => {
+                encode_table_op(OpcodeClass::TableSet, BigUint::from(table_idx as u64))
+            }
+            Opcode::TableSize { table_idx } => {
+                encode_table_op(OpcodeClass::TableSize, BigUint::from(table_idx as u64))
+            }
+            Opcode::TableGrow { table_idx } => {
+                encode_table_op(OpcodeClass::TableGrow, BigUint::from(table_idx as u64))
             }
             Opcode::InternalHostCall {
                 op_index_in_plugin, ..
@@ -440,6 +574,28 @@ impl Into<BigUint> for Opcode {
                 (BigUint::from(OpcodeClass::Conversion as u64) << OPCODE_CLASS_SHIFT)
                     + (BigUint::from(class as u64) << OPCODE_ARG0_SHIFT)
             }
+            Opcode::MemoryFill => {
+                BigUint::from(OpcodeClass::MemoryFill as u64) << OPCODE_CLASS_SHIFT
+            }
+            Opcode::MemoryCopy => {
+                BigUint::from(OpcodeClass::MemoryCopy as u64) << OPCODE_CLASS_SHIFT
+            }
+            Opcode::MemoryInit {
+                segment,
+                segment_offset,
+            } => {
+                (BigUint::from(OpcodeClass::MemoryInit as u64) << OPCODE_CLASS_SHIFT)
+                    + (BigUint::from(segment as u64) << OPCODE_ARG0_SHIFT)
+                    + (BigUint::from(segment_offset as u64) << OPCODE_ARG1_SHIFT)
+            }
+            Opcode::DataDrop {
+                segment,
+                segment_offset,
+            } => {
+                (BigUint::from(OpcodeClass::DataDrop as u64) << OPCODE_CLASS_SHIFT)
+                    + (BigUint::from(segment as u64) << OPCODE_ARG0_SHIFT)
+                    + (BigUint::from(segment_offset as u64) << OPCODE_ARG1_SHIFT)
+            }
         };
         assert!(bn < BigUint::from(1u64) << OPCODE_SHIFT);
         bn
@@ -471,6 +627,12 @@ impl Into<OpcodeClass> for Opcode {
             Opcode::Unreachable => OpcodeClass::Unreachable,
             Opcode::Call { .. } => OpcodeClass::Call,
             Opcode::CallIndirect { .. } => OpcodeClass::CallIndirect,
+            Opcode::ReturnCall { .. } => OpcodeClass::ReturnCall,
+            Opcode::ReturnCallIndirect { .. } => OpcodeClass::ReturnCallIndirect,
+            Opcode::TableGet { .. } => OpcodeClass::TableGet,
+            Opcode::TableSet { .. } => OpcodeClass::TableSet,
+            Opcode::TableSize { .. } => OpcodeClass::TableSize,
+            Opcode::TableGrow { .. } => OpcodeClass::TableGrow,
             Opcode::InternalHostCall { .. } => OpcodeClass::ForeignPluginStart,
             Opcode::ExternalHostCall { .. } => OpcodeClass::CallHost,
             Opcode::Load { .. } => OpcodeClass::Load,
@@ -478,6 +640,10 @@ impl Into<OpcodeClass> for Opcode {
             Opcode::MemorySize => OpcodeClass::MemorySize,
             Opcode::MemoryGrow => OpcodeClass::MemoryGrow,
             Opcode::Conversion { .. } => OpcodeClass::Conversion,
+            Opcode::MemoryFill => OpcodeClass::MemoryFill,
+            Opcode::MemoryCopy => OpcodeClass::MemoryCopy,
+            Opcode::MemoryInit { .. } => OpcodeClass::MemoryInit,
+            Opcode::DataDrop { .. } => OpcodeClass::DataDrop,
         }
     }
 }
@@ -487,7 +653,7 @@ impl Into<OpcodeClassPlain> for Opcode {
         let class: OpcodeClass = self.clone().into();
 
         if let Opcode::InternalHostCall { plugin, .. } = self {
-            OpcodeClassPlain(class as usize + plugin as usize)
+            OpcodeClassPlain(class as usize + plugin.id())
         } else {
             OpcodeClassPlain(class as usize)
         }
@@ -540,6 +706,7 @@ impl InstructionTable {
                         index: index as u32,
                         drop: target.drop,
                         keep: target.keep.len() as u32,
+                        keep_types: encode_keep_types(OpcodeClass::BrTable, &target.keep) as u32,
                         dst_pc: target.dst_pc,
                     })
                     .collect(),
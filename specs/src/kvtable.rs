@@ -0,0 +1,27 @@
+use num_bigint::BigUint;
+
+/// The depth of the sparse Merkle tree of the key-value store, the bits of a key
+/// from the lowest one are the path of its leaf from the leaf up.
+pub const KV_TREE_DEPTH: usize = 64;
+
+/// An access of the key-value store. The leaf of the key holds the value, 0 if the
+/// key was never set, a get leaves the value unchanged.
+#[derive(Clone, Debug)]
+pub struct KvAccess {
+    pub is_set: bool,
+    pub key: u64,
+    pub old_value: u64,
+    pub new_value: u64,
+    /// The siblings of the path of the key from the leaf up, they are shared by the
+    /// tree before and after the access.
+    pub siblings: Vec<BigUint>,
+}
+
+/// The accesses of the key-value store in the order of the trace, with the roots
+/// of the tree before the first access and after the last one.
+#[derive(Default, Clone, Debug)]
+pub struct KvTable {
+    pub old_root: BigUint,
+    pub new_root: BigUint,
+    pub accesses: Vec<KvAccess>,
+}
@@ -4,12 +4,14 @@
 
 use std::{env, io::Write, path::PathBuf};
 
-use brtable::ElemTable;
+use brtable::{ElemTable, TableLayout};
 use configure_table::ConfigureTable;
 use etable::EventTable;
+use host_function::HostMemoryAccessTable;
 use imtable::InitMemoryTable;
 use itable::InstructionTable;
 use jtable::{JumpTable, StaticFrameEntry};
+use kvtable::KvTable;
 use mtable::MTable;
 use serde::Serialize;
 
@@ -25,7 +27,9 @@ pub mod host_function;
 pub mod imtable;
 pub mod itable;
 pub mod jtable;
+pub mod kvtable;
 pub mod mtable;
+pub mod poseidon;
 pub mod step;
 pub mod types;
 
@@ -34,6 +38,7 @@ pub struct CompilationTable {
     pub itable: InstructionTable,
     pub imtable: InitMemoryTable,
     pub elem_table: ElemTable,
+    pub table_layouts: Vec<TableLayout>,
     pub configure_table: ConfigureTable,
     pub static_jtable: Vec<StaticFrameEntry>,
 }
@@ -43,6 +48,10 @@ pub struct ExecutionTable {
     pub etable: EventTable,
     pub mtable: MTable,
     pub jtable: JumpTable,
+    pub host_memory_accesses: HostMemoryAccessTable,
+    // The witness of the key-value store, it is not part of the trace.
+    #[serde(skip)]
+    pub kvtable: KvTable,
 }
 
 #[derive(Default, Clone)]
@@ -10,6 +10,15 @@ pub enum LocationType {
     Stack = 1,
     Heap = 2,
     Global = 3,
+    /*
+     * Passive data segments, laid out back to back, each of them is the block
+     * of its length followed by its bytes, offset is the index of the u64 block.
+     */
+    Data = 4,
+    /*
+     * Tables, laid out as described in brtable::TableLayout. All slots are I64.
+     */
+    Table = 5,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Hash, Eq)]
@@ -198,7 +207,7 @@ impl MTable {
         let mut set = HashSet::<MemoryTableEntry>::default();
 
         self.0.iter().for_each(|entry| {
-            if entry.ltype == LocationType::Heap || entry.ltype == LocationType::Global {
+            if entry.ltype != LocationType::Stack {
                 let (init_type, value) = match imtable.try_find(entry.ltype, entry.offset) {
                     Some(value) => (InitType::Positive, value),
                     None => (InitType::Lazy, 0),
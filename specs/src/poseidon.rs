@@ -0,0 +1,187 @@
+use ::poseidon::Spec;
+use halo2_proofs::arithmetic::FieldExt;
+
+pub const POSEIDON_T: usize = 9;
+pub const POSEIDON_RATE: usize = POSEIDON_T - 1;
+pub const POSEIDON_FULL_ROUNDS: usize = 8;
+pub const POSEIDON_PARTIAL_ROUNDS: usize = 63;
+pub const POSEIDON_ROUNDS: usize = POSEIDON_FULL_ROUNDS + POSEIDON_PARTIAL_ROUNDS;
+
+/// The kind of a round of the optimized permutation, see PoseidonSpec::round.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PoseidonRound {
+    /// The sbox on every element followed by the MDS matrix.
+    Full,
+    /// The last full round before the partial rounds, it multiplies by the
+    /// pre-sparse matrix instead of the MDS matrix.
+    PreSparse,
+    /// The sbox on the first element followed by the sparse matrix of the round.
+    Partial,
+}
+
+/// The Poseidon permutation and sponge of the `poseidon` crate, the one of the
+/// proof transcript, with T = 9, RATE = 8, 8 full rounds and 63 partial rounds.
+///
+/// The permutation is split in rounds computing `M * (sbox(state) + C)`, with the
+/// optimized constants and matrices of `poseidon::Spec`, so that circuits can
+/// constrain it a round per row.
+#[derive(Clone)]
+pub struct PoseidonSpec<F: FieldExt> {
+    spec: Spec<F, POSEIDON_T, POSEIDON_RATE>,
+}
+
+impl<F: FieldExt> PoseidonSpec<F> {
+    pub fn new() -> Self {
+        PoseidonSpec {
+            spec: Spec::new(POSEIDON_FULL_ROUNDS, POSEIDON_PARTIAL_ROUNDS),
+        }
+    }
+
+    /// The state of a new sponge, the capacity element is 2^64.
+    pub fn initial_state() -> [F; POSEIDON_T] {
+        let mut state = [F::zero(); POSEIDON_T];
+        state[0] = F::from_u128(1 << 64);
+        state
+    }
+
+    /// The constants added to the absorbed state before the first round.
+    pub fn absorb_constants(&self) -> [F; POSEIDON_T] {
+        self.spec.constants().start()[0]
+    }
+
+    pub fn round_kind(round: usize) -> PoseidonRound {
+        let half = POSEIDON_FULL_ROUNDS / 2;
+
+        if round + 1 == half {
+            PoseidonRound::PreSparse
+        } else if round < half || round >= half + POSEIDON_PARTIAL_ROUNDS {
+            PoseidonRound::Full
+        } else {
+            PoseidonRound::Partial
+        }
+    }
+
+    /// The constants added after the sbox of the round, the partial rounds only
+    /// add to the first element and the last round adds nothing.
+    pub fn round_constants(&self, round: usize) -> [F; POSEIDON_T] {
+        let half = POSEIDON_FULL_ROUNDS / 2;
+        let constants = self.spec.constants();
+
+        if round < half {
+            constants.start()[round + 1]
+        } else if round < half + POSEIDON_PARTIAL_ROUNDS {
+            let mut partial = [F::zero(); POSEIDON_T];
+            partial[0] = constants.partial()[round - half];
+            partial
+        } else {
+            constants
+                .end()
+                .get(round - half - POSEIDON_PARTIAL_ROUNDS)
+                .cloned()
+                .unwrap_or([F::zero(); POSEIDON_T])
+        }
+    }
+
+    pub fn mds(&self) -> [[F; POSEIDON_T]; POSEIDON_T] {
+        self.spec.mds_matrices().mds().rows()
+    }
+
+    pub fn pre_sparse_mds(&self) -> [[F; POSEIDON_T]; POSEIDON_T] {
+        self.spec.mds_matrices().pre_sparse_mds().rows()
+    }
+
+    /// The first row and the rest of the first column of the sparse matrix of a
+    /// partial round, the rest of the matrix is the identity.
+    pub fn sparse_mds(&self, round: usize) -> ([F; POSEIDON_T], [F; POSEIDON_RATE]) {
+        let sparse = &self.spec.mds_matrices().sparse_matrices()[round - POSEIDON_FULL_ROUNDS / 2];
+
+        (*sparse.row(), *sparse.col_hat())
+    }
+
+    pub fn sbox(x: F) -> F {
+        x.square().square() * x
+    }
+
+    /// The state after the given round.
+    pub fn round(&self, round: usize, state: &[F; POSEIDON_T]) -> [F; POSEIDON_T] {
+        let kind = Self::round_kind(round);
+        let constants = self.round_constants(round);
+
+        let mut sboxed = [F::zero(); POSEIDON_T];
+        for i in 0..POSEIDON_T {
+            sboxed[i] = if i == 0 || kind != PoseidonRound::Partial {
+                Self::sbox(state[i])
+            } else {
+                state[i]
+            } + constants[i];
+        }
+
+        let mut next = [F::zero(); POSEIDON_T];
+        match kind {
+            PoseidonRound::Full | PoseidonRound::PreSparse => {
+                let mds = if kind == PoseidonRound::Full {
+                    self.mds()
+                } else {
+                    self.pre_sparse_mds()
+                };
+
+                for i in 0..POSEIDON_T {
+                    for j in 0..POSEIDON_T {
+                        next[i] = next[i] + mds[i][j] * sboxed[j];
+                    }
+                }
+            }
+            PoseidonRound::Partial => {
+                let (row, col_hat) = self.sparse_mds(round);
+
+                for j in 0..POSEIDON_T {
+                    next[0] = next[0] + row[j] * sboxed[j];
+                }
+                for i in 1..POSEIDON_T {
+                    next[i] = col_hat[i - 1] * sboxed[0] + sboxed[i];
+                }
+            }
+        }
+
+        next
+    }
+
+    pub fn permute(&self, state: &mut [F; POSEIDON_T]) {
+        for (word, constant) in state.iter_mut().zip(self.absorb_constants().iter()) {
+            *word = *word + *constant;
+        }
+
+        for round in 0..POSEIDON_ROUNDS {
+            *state = self.round(round, state);
+        }
+    }
+
+    /// Absorbs a chunk into the rate elements of the state and permutes it.
+    pub fn absorb(&self, state: &mut [F; POSEIDON_T], chunk: &[F]) {
+        assert!(chunk.len() <= POSEIDON_RATE);
+
+        for (i, input) in chunk.iter().enumerate() {
+            state[i + 1] = state[i + 1] + *input;
+        }
+
+        self.permute(state);
+    }
+
+    /// The digest of `poseidon::Poseidon` hashing the inputs: the full chunks are
+    /// absorbed in order, then the remaining inputs followed by a one. The digest
+    /// is the first rate element of the final state.
+    pub fn hash(&self, inputs: &[F]) -> F {
+        let mut state = Self::initial_state();
+        let full_chunks = inputs.len() / POSEIDON_RATE * POSEIDON_RATE;
+
+        for chunk in inputs[..full_chunks].chunks(POSEIDON_RATE) {
+            self.absorb(&mut state, chunk);
+        }
+
+        let mut last = inputs[full_chunks..].to_vec();
+        last.push(F::one());
+        self.absorb(&mut state, &last);
+
+        state[1]
+    }
+}
@@ -9,6 +9,10 @@ use serde::Serialize;
 
 #[derive(Debug, Clone, Serialize)]
 pub enum StepInfo {
+    /*
+     * keep and keep_values of branches and return are listed from the top of
+     * the stack, after the condition or index has been popped.
+     */
     Br {
         dst_pc: u32,
         drop: u32,
@@ -61,6 +65,56 @@ pub enum StepInfo {
         offset: u32,
         func_index: u32,
     },
+    /*
+     * Tail calls, the args of the callee are kept in place of the current frame.
+     * The callee runs in the current frame, a tail call has no jtable entry and
+     * the return of the callee leaves the frame.
+     *
+     * Circuit-only: parity-wasm 0.42 doesn't decode the tail call opcodes and
+     * the wasmi tracer does not emit these steps yet.
+     */
+    ReturnCall {
+        index: u32,
+        drop: u32,
+        keep: Vec<ValueType>,
+        drop_values: Vec<u64>,
+        keep_values: Vec<u64>,
+    },
+    ReturnCallIndirect {
+        table_index: u32,
+        type_index: u32,
+        offset: u32,
+        func_index: u32,
+        drop: u32,
+        keep: Vec<ValueType>,
+        drop_values: Vec<u64>,
+        keep_values: Vec<u64>,
+    },
+    // Sizes are the ones before the step
+    TableGet {
+        table_index: u32,
+        index: u32,
+        size: u32,
+        value: u64,
+    },
+    TableSet {
+        table_index: u32,
+        index: u32,
+        size: u32,
+        value: u64,
+    },
+    TableSize {
+        table_index: u32,
+        size: u32,
+    },
+    // result is the previous size, or -1 if the table can't grow
+    TableGrow {
+        table_index: u32,
+        delta: u32,
+        init: u64,
+        size: u32,
+        result: i32,
+    },
     CallHost {
         plugin: HostPlugin,
         host_function_idx: usize,
@@ -213,4 +267,99 @@ pub enum StepInfo {
         result: i64,
         sign: bool,
     },
+    /*
+     * Sign-extension of the low `bits` bits of a `vtype` value,
+     * i.e. i32.extend8_s, i32.extend16_s and i64.extend{8,16,32}_s.
+     *
+     * Circuit-only: the wasmi tracer does not emit these steps yet.
+     */
+    SignExtend {
+        vtype: VarType,
+        bits: u32,
+        value: u64,
+        result: u64,
+    },
+
+    /*
+     * Block values of bulk memory operations are listed in ascending order of
+     * the u64 blocks touched by the operation, they are empty if len is 0.
+     *
+     * Circuit-only: the wasmi tracer does not emit the bulk memory steps yet.
+     */
+    MemoryFill {
+        offset: u32,
+        value: u32,
+        len: u32,
+        pre_block_values: Vec<u64>,
+        updated_block_values: Vec<u64>,
+    },
+    MemoryCopy {
+        dst: u32,
+        src: u32,
+        len: u32,
+        src_block_values: Vec<u64>,
+        pre_block_values: Vec<u64>,
+        updated_block_values: Vec<u64>,
+    },
+    /*
+     * segment_len is the length of the segment when the step runs, it is 0
+     * once the segment is dropped.
+     */
+    MemoryInit {
+        segment: u32,
+        segment_len: u32,
+        dst: u32,
+        src: u32,
+        len: u32,
+        data_block_values: Vec<u64>,
+        pre_block_values: Vec<u64>,
+        updated_block_values: Vec<u64>,
+    },
+    DataDrop {
+        segment: u32,
+    },
+
+    /*
+     * Trapping steps, each of them is the last step of the execution.
+     *
+     * Circuit-only: the wasmi tracer does not emit the trapping steps yet.
+     */
+    Unreachable,
+    LoadOutOfBounds {
+        vtype: VarType,
+        load_size: MemoryReadSize,
+        offset: u32,
+        raw_address: u32,
+    },
+    StoreOutOfBounds {
+        vtype: VarType,
+        store_size: MemoryStoreSize,
+        offset: u32,
+        raw_address: u32,
+        value: u64,
+    },
+    // Division by zero, or overflow of the signed division
+    I32BinOpTrap {
+        class: BinOp,
+        left: i32,
+        right: i32,
+    },
+    I64BinOpTrap {
+        class: BinOp,
+        left: i64,
+        right: i64,
+    },
+}
+
+impl StepInfo {
+    pub fn is_trap(&self) -> bool {
+        match self {
+            StepInfo::Unreachable
+            | StepInfo::LoadOutOfBounds { .. }
+            | StepInfo::StoreOutOfBounds { .. }
+            | StepInfo::I32BinOpTrap { .. }
+            | StepInfo::I64BinOpTrap { .. } => true,
+            _ => false,
+        }
+    }
 }
@@ -1,49 +1,42 @@
-use halo2_proofs::{arithmetic::FieldExt, circuit::Layouter, plonk::Error};
-use specs::brtable::{BrTable, ElemTable};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Cell, Layouter},
+    plonk::Error,
+};
+use specs::brtable::{BrTable, TableLayout};
 
 use super::BrTableChip;
 use crate::circuits::utils::bn_to_field;
 
 impl<F: FieldExt> BrTableChip<F> {
+    /*
+     * The values of the brtable column: the zero row for disabled lookups, the
+     * br entries and then the table layouts.
+     */
+    pub fn column(br_table_init: &BrTable, table_layouts: &[TableLayout]) -> Vec<F> {
+        let mut column = vec![F::zero()];
+
+        for e in br_table_init.entries() {
+            column.push(bn_to_field::<F>(&e.encode()));
+        }
+
+        for e in table_layouts {
+            column.push(bn_to_field::<F>(&e.encode()));
+        }
+
+        column
+    }
+
     pub(in crate::circuits) fn assign(
         self,
         layouter: &mut impl Layouter<F>,
         br_table_init: &BrTable,
-        elem_table: &ElemTable,
-    ) -> Result<(), Error> {
-        layouter.assign_table(
-            || "minit",
-            |mut table| {
-                table.assign_cell(|| "brtable init", self.config.col, 0, || Ok(F::zero()))?;
-
-                let mut offset = 1;
-
-                for e in br_table_init.entries() {
-                    table.assign_cell(
-                        || "brtable init",
-                        self.config.col,
-                        offset,
-                        || Ok(bn_to_field::<F>(&e.encode())),
-                    )?;
-
-                    offset += 1;
-                }
-
-                for e in elem_table.entries() {
-                    table.assign_cell(
-                        || "call indirect init",
-                        self.config.col,
-                        offset,
-                        || Ok(bn_to_field::<F>(&e.encode())),
-                    )?;
-
-                    offset += 1;
-                }
-
-                Ok(())
-            },
-        )?;
-
-        Ok(())
+        table_layouts: &[TableLayout],
+    ) -> Result<Vec<Cell>, Error> {
+        self.config.col.assign(
+            layouter,
+            "brtable init",
+            Self::column(br_table_init, table_layouts),
+        )
     }
 }
@@ -1,14 +1,14 @@
 use halo2_proofs::{
     arithmetic::FieldExt,
-    plonk::{ConstraintSystem, Expression, TableColumn, VirtualCells},
+    plonk::{ConstraintSystem, Expression, VirtualCells},
 };
 use std::marker::PhantomData;
 
 use super::BrTableConfig;
-use crate::circuits::traits::ConfigureLookupTable;
+use crate::circuits::{image_table::ImageTableColumn, traits::ConfigureLookupTable};
 
 impl<F: FieldExt> BrTableConfig<F> {
-    pub(in crate::circuits) fn configure(col: TableColumn) -> Self {
+    pub(in crate::circuits) fn configure(col: ImageTableColumn) -> Self {
         Self {
             col,
             _mark: PhantomData,
@@ -23,6 +23,6 @@ impl<F: FieldExt> ConfigureLookupTable<F> for BrTableConfig<F> {
         key: &'static str,
         expr: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
     ) {
-        meta.lookup(key, |meta| vec![(expr(meta), self.col)]);
+        self.col.configure_in_table(meta, key, expr);
     }
 }
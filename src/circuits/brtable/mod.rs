@@ -1,4 +1,5 @@
-use halo2_proofs::{arithmetic::FieldExt, plonk::TableColumn};
+use super::image_table::ImageTableColumn;
+use halo2_proofs::arithmetic::FieldExt;
 use std::marker::PhantomData;
 
 mod assign;
@@ -6,7 +7,7 @@ mod configure;
 
 #[derive(Clone)]
 pub struct BrTableConfig<F: FieldExt> {
-    pub(self) col: TableColumn,
+    pub(self) col: ImageTableColumn,
     _mark: PhantomData<F>,
 }
 
@@ -0,0 +1,377 @@
+use halo2_proofs::{arithmetic::FieldExt, circuit::Layouter, plonk::Error};
+use specs::{
+    etable::EventTable,
+    host_function::{HostMemoryAccessTable, HostPlugin},
+    itable::Opcode,
+    mtable::AccessType,
+    step::StepInfo,
+};
+
+use crate::{circuits::traits::TableSize, foreign::wasm_input_helper::WasmInputOp};
+
+use super::{
+    BulkMemoryOpKind, BulkMemoryTableChip, RotationOfAuxColumn, RotationOfBitColumn, STEP_SIZE,
+};
+
+/*
+ * A bulk memory operation with a non-zero length, src is the address in
+ * LocationType::Data for memory.init, dst for memory.fill and 8 times the
+ * index of the first private input for wasm_private_input_bytes.
+ */
+pub(crate) struct BulkMemoryOp {
+    eid: u32,
+    kind: BulkMemoryOpKind,
+    dst: u32,
+    src: u32,
+    len: u32,
+    fill: u8,
+    src_block_values: Vec<u64>,
+    pre_block_values: Vec<u64>,
+    updated_block_values: Vec<u64>,
+}
+
+impl BulkMemoryOp {
+    pub(crate) fn steps(&self) -> usize {
+        self.pre_block_values.len()
+    }
+}
+
+/*
+ * The private inputs are only known in the private input commitment mode, the
+ * ones read by wasm_private_input_bytes are otherwise recovered from the
+ * written bytes with the padding bytes of the last one left zero.
+ */
+pub(crate) fn bulk_memory_ops(
+    etable: &EventTable,
+    host_memory_accesses: &HostMemoryAccessTable,
+    private_inputs: &[u64],
+) -> Vec<BulkMemoryOp> {
+    let mut ops = vec![];
+    let mut private_input_index = 0u32;
+
+    for entry in etable.entries() {
+        let op = match &entry.step_info {
+            StepInfo::MemoryFill {
+                offset,
+                value,
+                len,
+                pre_block_values,
+                updated_block_values,
+            } => BulkMemoryOp {
+                eid: entry.eid,
+                kind: BulkMemoryOpKind::Fill,
+                dst: *offset,
+                src: *offset,
+                len: *len,
+                fill: *value as u8,
+                src_block_values: vec![],
+                pre_block_values: pre_block_values.clone(),
+                updated_block_values: updated_block_values.clone(),
+            },
+            StepInfo::MemoryCopy {
+                dst,
+                src,
+                len,
+                src_block_values,
+                pre_block_values,
+                updated_block_values,
+            } => BulkMemoryOp {
+                eid: entry.eid,
+                kind: BulkMemoryOpKind::Copy,
+                dst: *dst,
+                src: *src,
+                len: *len,
+                fill: 0,
+                src_block_values: src_block_values.clone(),
+                pre_block_values: pre_block_values.clone(),
+                updated_block_values: updated_block_values.clone(),
+            },
+            StepInfo::MemoryInit {
+                dst,
+                src,
+                len,
+                data_block_values,
+                pre_block_values,
+                updated_block_values,
+                ..
+            } => {
+                let segment_offset = match entry.inst.opcode {
+                    Opcode::MemoryInit { segment_offset, .. } => segment_offset,
+                    _ => unreachable!(),
+                };
+
+                BulkMemoryOp {
+                    eid: entry.eid,
+                    kind: BulkMemoryOpKind::Init,
+                    dst: *dst,
+                    src: (segment_offset + 1) * 8 + *src,
+                    len: *len,
+                    fill: 0,
+                    src_block_values: data_block_values.clone(),
+                    pre_block_values: pre_block_values.clone(),
+                    updated_block_values: updated_block_values.clone(),
+                }
+            }
+            StepInfo::CallHost {
+                plugin: HostPlugin::HostInput,
+                op_index_in_plugin,
+                args,
+                ..
+            } => {
+                if *op_index_in_plugin == WasmInputOp::Input as usize && args[0] == 0 {
+                    private_input_index += 1;
+                }
+
+                if *op_index_in_plugin != WasmInputOp::PrivateInputBytes as usize {
+                    continue;
+                }
+
+                let (dst, len) = (args[0] as u32, args[1] as u32);
+                let limbs = (len + 7) / 8;
+
+                let (pre_block_values, updated_block_values): (Vec<_>, Vec<_>) =
+                    host_memory_accesses
+                        .accesses(entry.eid)
+                        .chunks(2)
+                        .map(|accesses| {
+                            assert_eq!(accesses[0].atype, AccessType::Read);
+                            assert_eq!(accesses[1].atype, AccessType::Write);
+
+                            (accesses[0].value, accesses[1].value)
+                        })
+                        .unzip();
+
+                let mut bytes = updated_block_values
+                    .iter()
+                    .flat_map(|value| value.to_le_bytes())
+                    .skip(dst as usize % 8)
+                    .take(len as usize)
+                    .collect::<Vec<_>>();
+                bytes.resize(limbs as usize * 8, 0);
+
+                let op = BulkMemoryOp {
+                    eid: entry.eid,
+                    kind: BulkMemoryOpKind::PrivateInput,
+                    dst,
+                    src: private_input_index * 8,
+                    len,
+                    fill: 0,
+                    src_block_values: bytes
+                        .chunks(8)
+                        .enumerate()
+                        .map(|(i, limb)| {
+                            private_inputs
+                                .get(private_input_index as usize + i)
+                                .cloned()
+                                .unwrap_or_else(|| u64::from_le_bytes(limb.try_into().unwrap()))
+                        })
+                        .collect(),
+                    pre_block_values,
+                    updated_block_values,
+                };
+
+                private_input_index += limbs;
+
+                op
+            }
+            _ => continue,
+        };
+
+        if op.len != 0 {
+            ops.push(op);
+        }
+    }
+
+    ops
+}
+
+impl<F: FieldExt> BulkMemoryTableChip<F> {
+    pub(in crate::circuits) fn assign(
+        self,
+        layouter: &mut impl Layouter<F>,
+        ops: &Vec<BulkMemoryOp>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "bulk memory table",
+            |mut region| {
+                for offset in 0..self.max_available_size() {
+                    region.assign_fixed(
+                        || "bulk memory table sel",
+                        self.config.sel,
+                        offset,
+                        || Ok(F::one()),
+                    )?;
+
+                    if offset % STEP_SIZE == 0 {
+                        region.assign_fixed(
+                            || "bulk memory table step sel",
+                            self.config.step_sel,
+                            offset,
+                            || Ok(F::one()),
+                        )?;
+                    }
+                }
+
+                let mut offset = 0;
+
+                for op in ops {
+                    let dst_start_block = op.dst / 8;
+                    let dst_end = op.dst + op.len - 1;
+                    let shift = (op.src as i64 - op.dst as i64).rem_euclid(8) as u32;
+                    let lo_skip = op.dst % 8 + shift >= 8;
+                    let hi_need = dst_end % 8 + shift >= 8;
+                    let src_blocks = op.steps() as u32 + 1 - lo_skip as u32 - (!hi_need) as u32;
+                    let src_start_block = op.src as i64 / 8;
+
+                    if op.kind != BulkMemoryOpKind::Fill {
+                        assert_eq!(src_blocks as usize, op.src_block_values.len());
+                    }
+
+                    for step in 0..op.steps() {
+                        assert!(offset + STEP_SIZE <= self.max_available_size());
+
+                        let block = dst_start_block + step as u32;
+                        let is_first = step == 0;
+                        let is_last = step == op.steps() - 1;
+                        let hi_block =
+                            (block as i64 + 1 + (op.src as i64 - op.dst as i64 - shift as i64) / 8)
+                                as u32;
+
+                        let bits = [
+                            (RotationOfBitColumn::Enable, true),
+                            (RotationOfBitColumn::IsFirst, is_first),
+                            (RotationOfBitColumn::IsLast, is_last),
+                            (
+                                RotationOfBitColumn::IsFill,
+                                op.kind == BulkMemoryOpKind::Fill,
+                            ),
+                            (
+                                RotationOfBitColumn::IsCopy,
+                                op.kind == BulkMemoryOpKind::Copy,
+                            ),
+                            (
+                                RotationOfBitColumn::IsInit,
+                                op.kind == BulkMemoryOpKind::Init,
+                            ),
+                            (
+                                RotationOfBitColumn::IsPrivateInput,
+                                op.kind == BulkMemoryOpKind::PrivateInput,
+                            ),
+                        ];
+
+                        for (rot, value) in bits {
+                            region.assign_advice(
+                                || "bulk memory table bit",
+                                self.config.bit,
+                                offset + rot as usize,
+                                || Ok(F::from(value as u64)),
+                            )?;
+                        }
+
+                        region.assign_advice(
+                            || "bulk memory table shift",
+                            self.config.bit,
+                            offset + RotationOfBitColumn::ShiftStart as usize + shift as usize,
+                            || Ok(F::one()),
+                        )?;
+
+                        let aux = [
+                            (RotationOfAuxColumn::Eid, op.eid as u64),
+                            (RotationOfAuxColumn::Dst, op.dst as u64),
+                            (RotationOfAuxColumn::Src, op.src as u64),
+                            (RotationOfAuxColumn::Len, op.len as u64),
+                            (RotationOfAuxColumn::Fill, op.fill as u64),
+                            (RotationOfAuxColumn::DstStartBlock, dst_start_block as u64),
+                            (RotationOfAuxColumn::SrcBlocks, src_blocks as u64),
+                            (RotationOfAuxColumn::LoSkip, lo_skip as u64),
+                            (RotationOfAuxColumn::Block, block as u64),
+                            (RotationOfAuxColumn::HiBlock, hi_block as u64),
+                            (RotationOfAuxColumn::LoIn, !(is_first && lo_skip) as u64),
+                            (RotationOfAuxColumn::HiIn, !(is_last && !hi_need) as u64),
+                        ];
+
+                        for (rot, value) in aux {
+                            region.assign_advice(
+                                || "bulk memory table aux",
+                                self.config.aux,
+                                offset + rot as usize,
+                                || Ok(F::from(value)),
+                            )?;
+                        }
+
+                        for i in 0..8u32 {
+                            let address = block * 8 + i;
+
+                            region.assign_advice(
+                                || "bulk memory table after dst",
+                                self.config.mask,
+                                offset + i as usize,
+                                || Ok(F::from((address >= op.dst) as u64)),
+                            )?;
+
+                            region.assign_advice(
+                                || "bulk memory table before dst + len",
+                                self.config.mask,
+                                offset + 8 + i as usize,
+                                || Ok(F::from((address <= dst_end) as u64)),
+                            )?;
+                        }
+
+                        let pre_bytes = op.pre_block_values[step].to_le_bytes();
+                        let post_bytes = op.updated_block_values[step].to_le_bytes();
+
+                        for i in 0..8 {
+                            region.assign_advice(
+                                || "bulk memory table pre byte",
+                                self.config.block,
+                                offset + i,
+                                || Ok(F::from(pre_bytes[i] as u64)),
+                            )?;
+
+                            region.assign_advice(
+                                || "bulk memory table post byte",
+                                self.config.block,
+                                offset + 8 + i,
+                                || Ok(F::from(post_bytes[i] as u64)),
+                            )?;
+                        }
+
+                        /*
+                         * The source blocks out of the operation are never read
+                         * and left zero.
+                         */
+                        let source_block = |block: i64| -> [u8; 8] {
+                            if op.kind == BulkMemoryOpKind::Fill {
+                                [op.fill; 8]
+                            } else {
+                                usize::try_from(block - src_start_block)
+                                    .ok()
+                                    .and_then(|index| op.src_block_values.get(index))
+                                    .map_or([0; 8], |value| value.to_le_bytes())
+                            }
+                        };
+
+                        let window = [
+                            source_block(hi_block as i64 - 1),
+                            source_block(hi_block as i64),
+                        ]
+                        .concat();
+
+                        for (i, byte) in window.into_iter().enumerate() {
+                            region.assign_advice(
+                                || "bulk memory table src byte",
+                                self.config.src,
+                                offset + i,
+                                || Ok(F::from(byte as u64)),
+                            )?;
+                        }
+
+                        offset += STEP_SIZE;
+                    }
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
@@ -0,0 +1,393 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{ConstraintSystem, Expression, VirtualCells},
+};
+use specs::mtable::VarType;
+use std::marker::PhantomData;
+
+use crate::{
+    circuits::{
+        mtable_compact::{encode::MemoryTableLookupEncode, MemoryTableConfig},
+        rtable::RangeTableConfig,
+        Lookup,
+    },
+    constant_from, curr,
+    foreign::{wasm_input_helper::circuits::InputTableEncode, ForeignTableConfig},
+    nextn,
+};
+
+use super::{
+    BulkMemoryTableConfig, BulkMemoryTableEncode, RotationOfAuxColumn, RotationOfBitColumn,
+    STEP_SIZE,
+};
+
+impl<F: FieldExt> BulkMemoryTableConfig<F> {
+    pub(in crate::circuits) fn configure(
+        meta: &mut ConstraintSystem<F>,
+        rtable: &RangeTableConfig<F>,
+        mtable: &MemoryTableConfig<F>,
+    ) -> Self {
+        let config = Self {
+            sel: meta.fixed_column(),
+            step_sel: meta.fixed_column(),
+            bit: meta.advice_column(),
+            aux: meta.advice_column(),
+            mask: meta.advice_column(),
+            block: meta.advice_column(),
+            src: meta.advice_column(),
+            _mark: PhantomData,
+        };
+
+        config.configure_bytes(meta, rtable);
+        config.configure_operation(meta);
+        config.configure_next_step(meta);
+        config.configure_mtable_lookup(meta, mtable);
+
+        config
+    }
+
+    fn configure_bytes(&self, meta: &mut ConstraintSystem<F>, rtable: &RangeTableConfig<F>) {
+        rtable.configure_in_u8_range(meta, "bulk memory table block bytes", |meta| {
+            curr!(meta, self.block) * self.is_line(meta)
+        });
+
+        rtable.configure_in_u8_range(meta, "bulk memory table src bytes", |meta| {
+            curr!(meta, self.src) * self.is_line(meta)
+        });
+
+        meta.create_gate("bulk memory table bits", |meta| {
+            let mut constraints = vec![];
+
+            for i in 0..STEP_SIZE as i32 {
+                let bit = nextn!(meta, self.bit, i);
+                let mask = nextn!(meta, self.mask, i);
+
+                constraints.push(bit.clone() * (constant_from!(1) - bit));
+                constraints.push(mask.clone() * (constant_from!(1) - mask));
+            }
+
+            constraints
+                .into_iter()
+                .map(|e| e * self.is_step(meta))
+                .collect::<Vec<_>>()
+        });
+
+        /*
+         * A disabled step is all zero, its bytes are kept.
+         */
+        meta.create_gate("bulk memory table written bytes", |meta| {
+            let mut constraints = vec![];
+
+            for i in 0..8 {
+                let mut window = constant_from!(0);
+                for k in 0..8 {
+                    window = window + self.shift_bit(meta, k) * self.src_byte(meta, i + k);
+                }
+
+                constraints.push(
+                    self.post_byte(meta, i)
+                        - self.pre_byte(meta, i)
+                        - self.after_start(meta, i)
+                            * self.before_end(meta, i)
+                            * (window - self.pre_byte(meta, i)),
+                );
+            }
+
+            constraints
+                .into_iter()
+                .map(|e| e * self.is_step(meta))
+                .collect::<Vec<_>>()
+        });
+    }
+
+    fn configure_operation(&self, meta: &mut ConstraintSystem<F>) {
+        meta.create_gate("bulk memory table operation", |meta| {
+            let is_first = self.bit(meta, RotationOfBitColumn::IsFirst);
+            let is_last = self.bit(meta, RotationOfBitColumn::IsLast);
+            let is_fill = self.bit(meta, RotationOfBitColumn::IsFill);
+            let block = self.aux(meta, RotationOfAuxColumn::Block);
+
+            let mut constraints = vec![
+                is_fill.clone()
+                    + self.bit(meta, RotationOfBitColumn::IsCopy)
+                    + self.bit(meta, RotationOfBitColumn::IsInit)
+                    + self.bit(meta, RotationOfBitColumn::IsPrivateInput)
+                    - constant_from!(1),
+                (0..8)
+                    .map(|k| self.shift_bit(meta, k))
+                    .fold(constant_from!(0), |acc, bit| acc + bit)
+                    - constant_from!(1),
+                /*
+                 * The window starts at the block hi_block - 1, the lookups of
+                 * the source blocks bind shift to (src - dst) mod 8.
+                 */
+                (self.aux(meta, RotationOfAuxColumn::HiBlock) - constant_from!(1) - block.clone())
+                    * constant_from!(8)
+                    - self.aux(meta, RotationOfAuxColumn::Src)
+                    + self.aux(meta, RotationOfAuxColumn::Dst)
+                    + self.shift(meta),
+                constant_from!(1) - self.after_start(meta, 7),
+                constant_from!(1) - self.before_end(meta, 0),
+                is_first.clone()
+                    * (self.aux(meta, RotationOfAuxColumn::Dst)
+                        - block.clone() * constant_from!(8)
+                        - self.dst_inner_offset(meta)),
+                is_first.clone()
+                    * (self.aux(meta, RotationOfAuxColumn::DstStartBlock) - block.clone()),
+                is_first.clone()
+                    * (self.aux(meta, RotationOfAuxColumn::LoSkip)
+                        - self.lo_skip_of_first_step(meta)),
+                is_last.clone()
+                    * (self.aux(meta, RotationOfAuxColumn::Dst)
+                        + self.aux(meta, RotationOfAuxColumn::Len)
+                        - block.clone() * constant_from!(8)
+                        - self.dst_end_bytes(meta)),
+                is_last.clone()
+                    * (self.aux(meta, RotationOfAuxColumn::SrcBlocks)
+                        - (block - self.aux(meta, RotationOfAuxColumn::DstStartBlock)
+                            + constant_from!(1)
+                            - self.aux(meta, RotationOfAuxColumn::LoSkip)
+                            + self.hi_need_of_last_step(meta))),
+            ];
+
+            for i in 0..7 {
+                constraints.push(
+                    self.after_start(meta, i) * (constant_from!(1) - self.after_start(meta, i + 1)),
+                );
+                constraints.push(
+                    self.before_end(meta, i + 1) * (constant_from!(1) - self.before_end(meta, i)),
+                );
+            }
+
+            for i in 0..8 {
+                constraints.push(
+                    (constant_from!(1) - is_first.clone())
+                        * (constant_from!(1) - self.after_start(meta, i)),
+                );
+                constraints.push(
+                    (constant_from!(1) - is_last.clone())
+                        * (constant_from!(1) - self.before_end(meta, i)),
+                );
+            }
+
+            for i in 0..STEP_SIZE {
+                constraints.push(
+                    is_fill.clone()
+                        * (self.src_byte(meta, i) - self.aux(meta, RotationOfAuxColumn::Fill)),
+                );
+            }
+
+            constraints
+                .into_iter()
+                .map(|e| e * self.bit(meta, RotationOfBitColumn::Enable) * self.is_step(meta))
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("bulk memory table source blocks in", |meta| {
+            let enable = self.bit(meta, RotationOfBitColumn::Enable);
+
+            vec![
+                self.aux(meta, RotationOfAuxColumn::LoIn)
+                    - enable.clone()
+                        * (constant_from!(1)
+                            - self.bit(meta, RotationOfBitColumn::IsFirst)
+                                * self.aux(meta, RotationOfAuxColumn::LoSkip)),
+                self.aux(meta, RotationOfAuxColumn::HiIn)
+                    - enable
+                        * (constant_from!(1)
+                            - self.bit(meta, RotationOfBitColumn::IsLast)
+                                * (constant_from!(1) - self.hi_need_of_last_step(meta))),
+            ]
+            .into_iter()
+            .map(|e| e * self.is_step(meta))
+            .collect::<Vec<_>>()
+        });
+    }
+
+    fn configure_next_step(&self, meta: &mut ConstraintSystem<F>) {
+        meta.create_gate("bulk memory table next step", |meta| {
+            let mut constraints = vec![
+                constant_from!(1)
+                    - self.next_bit(meta, RotationOfBitColumn::Enable) * self.is_next_step(meta),
+                self.next_bit(meta, RotationOfBitColumn::IsFirst),
+                self.next_aux(meta, RotationOfAuxColumn::Block)
+                    - self.aux(meta, RotationOfAuxColumn::Block)
+                    - constant_from!(1),
+            ];
+
+            for rot in [
+                RotationOfBitColumn::IsFill,
+                RotationOfBitColumn::IsCopy,
+                RotationOfBitColumn::IsInit,
+                RotationOfBitColumn::IsPrivateInput,
+            ] {
+                let rot = rot as i32;
+                constraints.push(
+                    nextn!(meta, self.bit, rot + STEP_SIZE as i32) - nextn!(meta, self.bit, rot),
+                );
+            }
+
+            for rot in [
+                RotationOfAuxColumn::Eid,
+                RotationOfAuxColumn::Dst,
+                RotationOfAuxColumn::Src,
+                RotationOfAuxColumn::Len,
+                RotationOfAuxColumn::Fill,
+                RotationOfAuxColumn::DstStartBlock,
+                RotationOfAuxColumn::SrcBlocks,
+                RotationOfAuxColumn::LoSkip,
+            ] {
+                let rot = rot as i32;
+                constraints.push(
+                    nextn!(meta, self.aux, rot + STEP_SIZE as i32) - nextn!(meta, self.aux, rot),
+                );
+            }
+
+            constraints
+                .into_iter()
+                .map(|e| {
+                    e * self.bit(meta, RotationOfBitColumn::Enable)
+                        * (constant_from!(1) - self.bit(meta, RotationOfBitColumn::IsLast))
+                        * self.is_step(meta)
+                })
+                .collect::<Vec<_>>()
+        });
+    }
+
+    fn configure_mtable_lookup(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        mtable: &MemoryTableConfig<F>,
+    ) {
+        mtable.configure_in_table(meta, "bulk memory table block read", |meta| {
+            self.bit(meta, RotationOfBitColumn::Enable)
+                * MemoryTableLookupEncode::encode_memory_load(
+                    self.aux(meta, RotationOfAuxColumn::Eid),
+                    self.dst_emid(meta),
+                    self.aux(meta, RotationOfAuxColumn::Block),
+                    constant_from!(VarType::I64),
+                    self.pre_value(meta),
+                )
+                * self.is_step(meta)
+        });
+
+        mtable.configure_in_table(meta, "bulk memory table block write", |meta| {
+            self.bit(meta, RotationOfBitColumn::Enable)
+                * MemoryTableLookupEncode::encode_memory_store(
+                    self.aux(meta, RotationOfAuxColumn::Eid),
+                    self.dst_emid(meta) + constant_from!(1),
+                    self.aux(meta, RotationOfAuxColumn::Block),
+                    constant_from!(VarType::I64),
+                    self.post_value(meta),
+                )
+                * self.is_step(meta)
+        });
+
+        mtable.configure_in_table(meta, "bulk memory table lower source block", |meta| {
+            let eid = self.aux(meta, RotationOfAuxColumn::Eid);
+            let emid = self.src_emid(meta);
+            let offset = self.aux(meta, RotationOfAuxColumn::HiBlock) - constant_from!(1);
+            let value = self.lo_value(meta);
+
+            self.aux(meta, RotationOfAuxColumn::LoIn)
+                * self.source_block_read(meta, eid, emid, offset, value)
+                * self.is_step(meta)
+        });
+
+        mtable.configure_in_table(meta, "bulk memory table higher source block", |meta| {
+            let eid = self.aux(meta, RotationOfAuxColumn::Eid);
+            let emid = self.src_emid(meta) + constant_from!(1);
+            let offset = self.aux(meta, RotationOfAuxColumn::HiBlock);
+            let value = self.hi_value(meta);
+
+            self.aux(meta, RotationOfAuxColumn::HiIn)
+                * self.source_block_read(meta, eid, emid, offset, value)
+                * self.is_step(meta)
+        });
+    }
+
+    /*
+     * The source blocks of wasm_private_input_bytes are the private inputs at
+     * the indexes hi_block - 1 and hi_block, they are looked up in the
+     * committed private inputs in the private input commitment mode.
+     */
+    pub(in crate::circuits) fn configure_private_input_lookup(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        private_input_table: &impl ForeignTableConfig<F>,
+    ) {
+        private_input_table.configure_in_table(
+            meta,
+            "bulk memory table lower private input",
+            &|meta| {
+                self.aux(meta, RotationOfAuxColumn::LoIn)
+                    * self.bit(meta, RotationOfBitColumn::IsPrivateInput)
+                    * InputTableEncode::encode_for_lookup(
+                        self.aux(meta, RotationOfAuxColumn::HiBlock) - constant_from!(1),
+                        self.lo_value(meta),
+                    )
+                    * self.is_step(meta)
+            },
+        );
+
+        private_input_table.configure_in_table(
+            meta,
+            "bulk memory table higher private input",
+            &|meta| {
+                self.aux(meta, RotationOfAuxColumn::HiIn)
+                    * self.bit(meta, RotationOfBitColumn::IsPrivateInput)
+                    * InputTableEncode::encode_for_lookup(
+                        self.aux(meta, RotationOfAuxColumn::HiBlock),
+                        self.hi_value(meta),
+                    )
+                    * self.is_step(meta)
+            },
+        );
+    }
+
+    // The source of memory.copy is the heap, the one of memory.init is LocationType::Data.
+    fn source_block_read(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        eid: Expression<F>,
+        emid: Expression<F>,
+        offset: Expression<F>,
+        value: Expression<F>,
+    ) -> Expression<F> {
+        self.bit(meta, RotationOfBitColumn::IsCopy)
+            * MemoryTableLookupEncode::encode_memory_load(
+                eid.clone(),
+                emid.clone(),
+                offset.clone(),
+                constant_from!(VarType::I64),
+                value.clone(),
+            )
+            + self.bit(meta, RotationOfBitColumn::IsInit)
+                * MemoryTableLookupEncode::encode_data_read(eid, emid, offset, value)
+    }
+}
+
+impl<F: FieldExt> ForeignTableConfig<F> for BulkMemoryTableConfig<F> {
+    fn configure_in_table(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        key: &'static str,
+        expr: &dyn Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+    ) {
+        meta.lookup_any(key, |meta| {
+            vec![(
+                expr(meta),
+                BulkMemoryTableEncode::encode_for_lookup(
+                    self.aux(meta, RotationOfAuxColumn::Eid),
+                    self.kind(meta),
+                    self.aux(meta, RotationOfAuxColumn::Dst),
+                    self.aux(meta, RotationOfAuxColumn::Src),
+                    self.aux(meta, RotationOfAuxColumn::Len),
+                    self.aux(meta, RotationOfAuxColumn::Fill),
+                ) * self.bit(meta, RotationOfBitColumn::Enable)
+                    * self.bit(meta, RotationOfBitColumn::IsFirst)
+                    * self.is_step(meta),
+            )]
+        });
+    }
+}
@@ -0,0 +1,189 @@
+use super::*;
+use crate::{constant_from, fixed_curr, nextn};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Expression, VirtualCells},
+    poly::Rotation,
+};
+
+impl<F: FieldExt> BulkMemoryTableConfig<F> {
+    pub(super) fn is_step(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        fixed_curr!(meta, self.step_sel)
+    }
+
+    pub(super) fn is_next_step(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        meta.query_fixed(self.step_sel, Rotation(STEP_SIZE as i32))
+    }
+
+    pub(super) fn is_line(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        fixed_curr!(meta, self.sel)
+    }
+
+    pub(super) fn bit(
+        &self,
+        meta: &mut VirtualCells<F>,
+        rot: RotationOfBitColumn,
+    ) -> Expression<F> {
+        nextn!(meta, self.bit, rot as i32)
+    }
+
+    pub(super) fn next_bit(
+        &self,
+        meta: &mut VirtualCells<F>,
+        rot: RotationOfBitColumn,
+    ) -> Expression<F> {
+        nextn!(meta, self.bit, rot as i32 + STEP_SIZE as i32)
+    }
+
+    pub(super) fn aux(
+        &self,
+        meta: &mut VirtualCells<F>,
+        rot: RotationOfAuxColumn,
+    ) -> Expression<F> {
+        nextn!(meta, self.aux, rot as i32)
+    }
+
+    pub(super) fn next_aux(
+        &self,
+        meta: &mut VirtualCells<F>,
+        rot: RotationOfAuxColumn,
+    ) -> Expression<F> {
+        nextn!(meta, self.aux, rot as i32 + STEP_SIZE as i32)
+    }
+
+    pub(super) fn shift_bit(&self, meta: &mut VirtualCells<F>, k: usize) -> Expression<F> {
+        nextn!(
+            meta,
+            self.bit,
+            RotationOfBitColumn::ShiftStart as i32 + k as i32
+        )
+    }
+
+    pub(super) fn shift(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        let mut acc = constant_from!(0);
+        for k in 1..8 {
+            acc = acc + self.shift_bit(meta, k) * constant_from!(k);
+        }
+        acc
+    }
+
+    pub(super) fn after_start(&self, meta: &mut VirtualCells<F>, i: usize) -> Expression<F> {
+        nextn!(meta, self.mask, i as i32)
+    }
+
+    pub(super) fn before_end(&self, meta: &mut VirtualCells<F>, i: usize) -> Expression<F> {
+        nextn!(meta, self.mask, 8 + i as i32)
+    }
+
+    pub(super) fn pre_byte(&self, meta: &mut VirtualCells<F>, i: usize) -> Expression<F> {
+        nextn!(meta, self.block, i as i32)
+    }
+
+    pub(super) fn post_byte(&self, meta: &mut VirtualCells<F>, i: usize) -> Expression<F> {
+        nextn!(meta, self.block, 8 + i as i32)
+    }
+
+    pub(super) fn src_byte(&self, meta: &mut VirtualCells<F>, i: usize) -> Expression<F> {
+        nextn!(meta, self.src, i as i32)
+    }
+
+    fn compose(&self, bytes: Vec<Expression<F>>) -> Expression<F> {
+        let mut acc = constant_from!(0);
+        for (i, byte) in bytes.into_iter().enumerate() {
+            acc = acc + byte * constant_from!(1u64 << (8 * i));
+        }
+        acc
+    }
+
+    pub(super) fn pre_value(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        let bytes = (0..8).map(|i| self.pre_byte(meta, i)).collect();
+        self.compose(bytes)
+    }
+
+    pub(super) fn post_value(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        let bytes = (0..8).map(|i| self.post_byte(meta, i)).collect();
+        self.compose(bytes)
+    }
+
+    pub(super) fn lo_value(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        let bytes = (0..8).map(|i| self.src_byte(meta, i)).collect();
+        self.compose(bytes)
+    }
+
+    pub(super) fn hi_value(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        let bytes = (8..16).map(|i| self.src_byte(meta, i)).collect();
+        self.compose(bytes)
+    }
+
+    // The offset of the first byte of the block from dst, on the first step.
+    pub(super) fn dst_inner_offset(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        let mut acc = constant_from!(0);
+        for i in 0..8 {
+            acc = acc + constant_from!(1) - self.after_start(meta, i);
+        }
+        acc
+    }
+
+    // The number of bytes of the block before dst + len, on the last step.
+    pub(super) fn dst_end_bytes(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        let mut acc = constant_from!(0);
+        for i in 0..8 {
+            acc = acc + self.before_end(meta, i);
+        }
+        acc
+    }
+
+    /*
+     * On the first step, the first byte dst_inner_offset is written with byte
+     * dst_inner_offset + shift of the window, which is out of the lower source
+     * block iff after_start[7 - shift] is not set.
+     */
+    pub(super) fn lo_skip_of_first_step(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        let mut acc = constant_from!(0);
+        for k in 1..8 {
+            acc =
+                acc + self.shift_bit(meta, k) * (constant_from!(1) - self.after_start(meta, 7 - k));
+        }
+        acc
+    }
+
+    /*
+     * On the last step, the last byte is written from the higher source block
+     * iff before_end[8 - shift] is set.
+     */
+    pub(super) fn hi_need_of_last_step(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        let mut acc = constant_from!(0);
+        for k in 1..8 {
+            acc = acc + self.shift_bit(meta, k) * self.before_end(meta, 8 - k);
+        }
+        acc
+    }
+
+    pub(super) fn src_emid(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        constant_from!(4)
+            + self.bit(meta, RotationOfBitColumn::IsInit)
+            + self.aux(meta, RotationOfAuxColumn::Block)
+            - self.aux(meta, RotationOfAuxColumn::DstStartBlock)
+            - self.aux(meta, RotationOfAuxColumn::LoSkip)
+    }
+
+    // wasm_private_input_bytes pops 2 operands, the others pop 3.
+    pub(super) fn dst_emid(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        constant_from!(4) + self.bit(meta, RotationOfBitColumn::IsInit)
+            - self.bit(meta, RotationOfBitColumn::IsPrivateInput)
+            + (self.bit(meta, RotationOfBitColumn::IsCopy)
+                + self.bit(meta, RotationOfBitColumn::IsInit))
+                * self.aux(meta, RotationOfAuxColumn::SrcBlocks)
+            + (self.aux(meta, RotationOfAuxColumn::Block)
+                - self.aux(meta, RotationOfAuxColumn::DstStartBlock))
+                * constant_from!(2)
+    }
+
+    pub(super) fn kind(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        self.bit(meta, RotationOfBitColumn::IsFill) * constant_from!(BulkMemoryOpKind::Fill)
+            + self.bit(meta, RotationOfBitColumn::IsCopy) * constant_from!(BulkMemoryOpKind::Copy)
+            + self.bit(meta, RotationOfBitColumn::IsInit) * constant_from!(BulkMemoryOpKind::Init)
+            + self.bit(meta, RotationOfBitColumn::IsPrivateInput)
+                * constant_from!(BulkMemoryOpKind::PrivateInput)
+    }
+}
@@ -0,0 +1,140 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Advice, Column, Fixed},
+};
+use num_bigint::BigUint;
+use specs::encode::FromBn;
+use std::marker::PhantomData;
+
+use super::traits::TableSize;
+
+pub(crate) mod assign;
+mod configure;
+mod expression;
+
+pub const BULK_MEMORY_TABLE_KEY: &'static str = "bulk-memory-table";
+
+pub const STEP_SIZE: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum BulkMemoryOpKind {
+    Fill = 1,
+    Copy,
+    Init,
+    PrivateInput,
+}
+
+pub(crate) enum RotationOfBitColumn {
+    Enable = 0,
+    IsFirst,
+    IsLast,
+    IsFill,
+    IsCopy,
+    IsInit,
+    IsPrivateInput,
+    // ShiftStart + k is set if the source is k bytes ahead of the block.
+    ShiftStart,
+}
+
+/*
+ * The operands are the same on all the steps of an operation, the source
+ * address of memory.init is the address in LocationType::Data, the one of
+ * wasm_private_input_bytes is 8 times the index of its first private input.
+ */
+pub(crate) enum RotationOfAuxColumn {
+    Eid = 0,
+    Dst,
+    Src,
+    Len,
+    Fill,
+    DstStartBlock,
+    SrcBlocks,
+    // Whether the lower source block of the first step is out of the source.
+    LoSkip,
+    Block,
+    HiBlock,
+    // Whether the lower and higher source blocks of the step are read.
+    LoIn,
+    HiIn,
+}
+
+/*
+ * A bulk memory operation takes a step of STEP_SIZE rows for each u64 block
+ * it writes, from the block of dst to the block of dst + len - 1. The etable
+ * looks up the first step of each operation with a non-zero length.
+ *
+ * Byte i of the block is in the operation if mask[i] (after dst) and
+ * mask[8 + i] (before dst + len) are set, it is then written with byte
+ * i + shift of the source window, which holds the source blocks
+ * hi_block - 1 and hi_block, or the fill value.
+ *
+ * The block values and the source blocks are looked up in the mtable at the
+ * emids of the accesses listed by the runtime: the pops, the length of the
+ * segment for memory.init, the source blocks, then a read and a write for
+ * each block. The source blocks of wasm_private_input_bytes are the private
+ * inputs, they are not memory accesses.
+ */
+#[derive(Clone)]
+pub struct BulkMemoryTableConfig<F: FieldExt> {
+    sel: Column<Fixed>,
+    step_sel: Column<Fixed>,
+
+    // See enum RotationOfBitColumn
+    bit: Column<Advice>,
+    // See enum RotationOfAuxColumn
+    aux: Column<Advice>,
+
+    // Rotation:
+    // 0..8 after dst
+    // 8..16 before dst + len
+    mask: Column<Advice>,
+    // Rotation:
+    // 0..8 bytes before the operation
+    // 8..16 bytes after the operation
+    block: Column<Advice>,
+    // Rotation:
+    // 0..16 source window
+    src: Column<Advice>,
+
+    _mark: PhantomData<F>,
+}
+
+pub struct BulkMemoryTableChip<F: FieldExt> {
+    config: BulkMemoryTableConfig<F>,
+    max_available_rows: usize,
+}
+
+impl<F: FieldExt> TableSize for BulkMemoryTableChip<F> {
+    fn max_available_size(&self) -> usize {
+        self.max_available_rows
+    }
+}
+
+impl<F: FieldExt> BulkMemoryTableChip<F> {
+    pub fn new(config: BulkMemoryTableConfig<F>, max_available_rows: usize) -> Self {
+        Self {
+            config,
+            max_available_rows: max_available_rows / STEP_SIZE * STEP_SIZE,
+        }
+    }
+}
+
+pub(crate) struct BulkMemoryTableEncode;
+
+impl BulkMemoryTableEncode {
+    pub(crate) fn encode_for_lookup<T: FromBn>(
+        eid: T,
+        kind: T,
+        dst: T,
+        src: T,
+        len: T,
+        fill: T,
+    ) -> T {
+        eid * T::from_bn(&(BigUint::from(1u64) << 136))
+            + kind * T::from_bn(&(BigUint::from(1u64) << 128))
+            + dst * T::from_bn(&(BigUint::from(1u64) << 96))
+            + src * T::from_bn(&(BigUint::from(1u64) << 64))
+            + len * T::from_bn(&(BigUint::from(1u64) << 32))
+            + fill
+    }
+}
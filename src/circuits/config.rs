@@ -1,60 +1,208 @@
-use std::{env, sync::Mutex};
+use anyhow::{ensure, Result};
+use serde::{Deserialize, Serialize};
+use std::{fs::File, io::BufReader, path::PathBuf};
 
-pub const VAR_COLUMNS: usize = 20;
 pub const IMTABLE_COLOMNS: usize = 2;
 
 pub const POW_TABLE_LIMIT: u64 = 128;
 
 pub const MIN_K: u32 = 18;
 
-lazy_static! {
-    static ref ZKWASM_K: Mutex<u32> =
-        Mutex::new(env::var("ZKWASM_K").map_or(MIN_K, |k| k.parse().unwrap()));
-    pub(super) static ref ZKWASM_TABLE_DENOMINATOR: u32 =
-        env::var("ZKWASM_TABLE_DENOMINATOR").map_or(8, |k| k.parse().unwrap());
-    static ref ZKWASM_ETABLE_RATIO: u32 =
-        env::var("ZKWASM_ETABLE_RATIO").map_or(6, |k| k.parse().unwrap());
-    static ref ZKWASM_MTABLE_RATIO: u32 =
-        env::var("ZKWASM_MTABLE_RATIO").map_or(7, |k| k.parse().unwrap());
-    static ref ZKWASM_JTABLE_RATIO: u32 =
-        env::var("ZKWASM_JTABLE_RATIO").map_or(6, |k| k.parse().unwrap());
-    pub(super) static ref ZKWASM_FOREIGN_CALL_TABLE_RATIO: u32 =
-        env::var("ZKWASM_JTABLE_RATIO").map_or(6, |k| k.parse().unwrap());
-    static ref ZKWASM_SHA256_RATIO: u32 =
-        env::var("ZKWASM_SHA256_RATIO").map_or(6, |k| k.parse().unwrap());
+/*
+ * The size K of the circuit and the share of the 2^K rows available to each
+ * table: a table gets (2^K / table_denominator * ratio) rows.
+ *
+ * The layout is part of the circuit, the one used to generate the vkey must be
+ * used to create and verify proofs. Every ratio must be set, a layout written
+ * before a table was added fails to load rather than leaving no room for it.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CircuitLayout {
+    pub k: u32,
+    pub table_denominator: u32,
+    pub etable_ratio: u32,
+    pub mtable_ratio: u32,
+    pub jtable_ratio: u32,
+    pub foreign_call_table_ratio: u32,
+    pub sha256_ratio: u32,
+    pub keccak_ratio: u32,
+    pub poseidon_ratio: u32,
+    pub sha256_compress_ratio: u32,
+    pub secp256k1_ratio: u32,
+    pub u256_ratio: u32,
+    pub kv_ratio: u32,
+    pub plugin_ratio: u32,
+    pub bulk_memory_ratio: u32,
+    pub keep_ratio: u32,
+    /*
+     * The capacities of the image tables of the universal circuit, whose image
+     * is committed in the proof instead of being fixed in the vkey.
+     */
+    #[serde(default)]
+    pub image_table: Option<ImageTableLayout>,
 }
 
-pub fn set_zkwasm_k(k: u32) {
-    assert!(k >= MIN_K);
+/*
+ * The rows of the image tables available to the universal circuit, including
+ * the leading zero row of each table.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageTableLayout {
+    pub itable_rows: u32,
+    pub brtable_rows: u32,
+    pub imtable_rows: u32,
+}
+
+impl ImageTableLayout {
+    pub fn new(k: u32) -> Self {
+        assert!(k >= MIN_K);
 
-    let mut zkwasm_k = (*ZKWASM_K).lock().unwrap();
-    *zkwasm_k = k;
+        let scale = 1 << (k - MIN_K);
+
+        Self {
+            itable_rows: 4096 * scale,
+            brtable_rows: 1024 * scale,
+            imtable_rows: 8192 * scale,
+        }
+    }
 }
 
-pub fn zkwasm_k() -> u32 {
-    *ZKWASM_K.lock().unwrap()
+impl Default for CircuitLayout {
+    fn default() -> Self {
+        Self::new(MIN_K)
+    }
 }
 
-pub(crate) fn max_etable_rows() -> u32 {
-    assert!(*ZKWASM_ETABLE_RATIO < *ZKWASM_TABLE_DENOMINATOR);
+impl CircuitLayout {
+    pub fn new(k: u32) -> Self {
+        assert!(k >= MIN_K);
 
-    (1 << zkwasm_k()) / *ZKWASM_TABLE_DENOMINATOR * *ZKWASM_ETABLE_RATIO
-}
+        Self {
+            k,
+            table_denominator: 8,
+            etable_ratio: 6,
+            mtable_ratio: 7,
+            jtable_ratio: 6,
+            foreign_call_table_ratio: 6,
+            sha256_ratio: 6,
+            keccak_ratio: 1,
+            poseidon_ratio: 1,
+            sha256_compress_ratio: 1,
+            // A signature verification takes more than a unit of rows with MIN_K.
+            secp256k1_ratio: 2,
+            u256_ratio: 1,
+            // A kv access takes about a third of a unit of rows with MIN_K.
+            kv_ratio: 1,
+            plugin_ratio: 1,
+            bulk_memory_ratio: 1,
+            keep_ratio: 1,
+            image_table: None,
+        }
+    }
 
-pub(crate) fn max_mtable_rows() -> u32 {
-    assert!(*ZKWASM_MTABLE_RATIO < *ZKWASM_TABLE_DENOMINATOR);
+    /*
+     * The layout of the universal circuit, one vkey serves every image fitting
+     * in the capacities of its image tables.
+     */
+    pub fn new_universal(k: u32) -> Self {
+        Self {
+            image_table: Some(ImageTableLayout::new(k)),
+            ..Self::new(k)
+        }
+    }
 
-    (1 << zkwasm_k()) / *ZKWASM_TABLE_DENOMINATOR * *ZKWASM_MTABLE_RATIO
-}
+    pub fn is_universal(&self) -> bool {
+        self.image_table.is_some()
+    }
 
-pub(crate) fn max_jtable_rows() -> u32 {
-    assert!(*ZKWASM_JTABLE_RATIO < *ZKWASM_TABLE_DENOMINATOR);
+    fn rows_of_ratio(&self, ratio: u32) -> u32 {
+        assert!(ratio < self.table_denominator);
 
-    (1 << zkwasm_k()) / *ZKWASM_TABLE_DENOMINATOR * *ZKWASM_JTABLE_RATIO
-}
+        (1 << self.k) / self.table_denominator * ratio
+    }
+
+    /*
+     * The smallest ratio whose rows hold the required rows.
+     */
+    pub(crate) fn fit_ratio(&self, required_rows: usize) -> Result<u32> {
+        let unit = ((1 << self.k) / self.table_denominator) as usize;
+        let ratio = usize::max((required_rows + unit - 1) / unit, 1) as u32;
+
+        ensure!(
+            ratio < self.table_denominator,
+            "{} rows do not fit in the circuit with K = {}",
+            required_rows,
+            self.k
+        );
+
+        Ok(ratio)
+    }
+
+    pub(crate) fn max_etable_rows(&self) -> u32 {
+        self.rows_of_ratio(self.etable_ratio)
+    }
+
+    pub(crate) fn max_mtable_rows(&self) -> u32 {
+        self.rows_of_ratio(self.mtable_ratio)
+    }
+
+    pub(crate) fn max_jtable_rows(&self) -> u32 {
+        self.rows_of_ratio(self.jtable_ratio)
+    }
+
+    pub(crate) fn max_foreign_call_table_rows(&self) -> u32 {
+        self.rows_of_ratio(self.foreign_call_table_ratio)
+    }
+
+    pub(crate) fn max_sha256_rows(&self) -> u32 {
+        self.rows_of_ratio(self.sha256_ratio)
+    }
+
+    pub(crate) fn max_keccak_rows(&self) -> u32 {
+        self.rows_of_ratio(self.keccak_ratio)
+    }
+
+    pub(crate) fn max_poseidon_rows(&self) -> u32 {
+        self.rows_of_ratio(self.poseidon_ratio)
+    }
+
+    pub(crate) fn max_sha256_compress_rows(&self) -> u32 {
+        self.rows_of_ratio(self.sha256_compress_ratio)
+    }
+
+    pub(crate) fn max_secp256k1_rows(&self) -> u32 {
+        self.rows_of_ratio(self.secp256k1_ratio)
+    }
+
+    pub(crate) fn max_u256_rows(&self) -> u32 {
+        self.rows_of_ratio(self.u256_ratio)
+    }
+
+    pub(crate) fn max_kv_rows(&self) -> u32 {
+        self.rows_of_ratio(self.kv_ratio)
+    }
+
+    pub(crate) fn max_plugin_rows(&self) -> u32 {
+        self.rows_of_ratio(self.plugin_ratio)
+    }
+
+    pub(crate) fn max_bulk_memory_rows(&self) -> u32 {
+        self.rows_of_ratio(self.bulk_memory_ratio)
+    }
+
+    pub(crate) fn max_keep_rows(&self) -> u32 {
+        self.rows_of_ratio(self.keep_ratio)
+    }
+
+    pub fn read(path: &PathBuf) -> Result<Self> {
+        let fd = File::open(path)?;
+
+        Ok(serde_json::from_reader(BufReader::new(fd))?)
+    }
 
-pub(crate) fn max_sha256_rows() -> u32 {
-    assert!(*ZKWASM_SHA256_RATIO < *ZKWASM_TABLE_DENOMINATOR);
+    pub fn write(&self, path: &PathBuf) {
+        let fd = File::create(path).unwrap();
 
-    (1 << zkwasm_k()) / *ZKWASM_TABLE_DENOMINATOR * *ZKWASM_SHA256_RATIO
+        serde_json::to_writer_pretty(fd, self).unwrap()
+    }
 }
@@ -1,7 +1,5 @@
 use specs::step::StepInfo;
 
-use crate::circuits::config::max_etable_rows;
-
 use super::*;
 
 impl<F: FieldExt> EventTableCommonConfig<F> {
@@ -9,20 +7,24 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
         &self,
         ctx: &mut Context<'_, F>,
         op_configs: &BTreeMap<OpcodeClassPlain, Rc<Box<dyn EventTableOpcodeConfig<F>>>>,
+        op_bitmaps: &BTreeMap<OpcodeClassPlain, (i32, i32)>,
         etable: &EventTable,
         configure: ConfigureTable,
-    ) -> Result<(Option<Cell>, Option<Cell>), Error> {
+        table_layouts: &[TableLayout],
+        callee_fids: &[u32],
+        max_available_rows: usize,
+    ) -> Result<(Option<Cell>, Option<Cell>, Option<Cell>), Error> {
         let mut status_entries = Vec::with_capacity(etable.entries().len() + 1);
 
         // Ensure capability, at least reserve one row for disabled entries
-        assert!((etable.entries().len() + 1) * ETABLE_STEP_SIZE <= max_etable_rows() as usize);
+        assert!((etable.entries().len() + 1) * self.step_size <= max_available_rows);
 
         // Step 1: fill fixed columns
-        for i in 0..max_etable_rows() as usize {
+        for i in 0..max_available_rows {
             ctx.region
                 .assign_fixed(|| "etable common sel", self.sel, i, || Ok(F::one()))?;
 
-            if i % ETABLE_STEP_SIZE == EventTableBitColumnRotation::Enable as usize {
+            if i % self.step_size == EventTableBitColumnRotation::Enable as usize {
                 ctx.region.assign_fixed(
                     || "etable common block first line sel",
                     self.block_first_line_sel,
@@ -31,7 +33,7 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
                 )?;
             }
 
-            if i % ETABLE_STEP_SIZE == EventTableUnlimitColumnRotation::ITableLookup as usize {
+            if i % self.step_size == EventTableUnlimitColumnRotation::ITableLookup as usize {
                 ctx.region.assign_fixed(
                     || "itable lookup",
                     self.itable_lookup,
@@ -40,7 +42,7 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
                 )?;
             }
 
-            if i % ETABLE_STEP_SIZE == EventTableUnlimitColumnRotation::BrTableLookup as usize {
+            if i % self.step_size == EventTableUnlimitColumnRotation::BrTableLookup as usize {
                 ctx.region.assign_fixed(
                     || "brtable lookup",
                     self.brtable_lookup,
@@ -49,7 +51,7 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
                 )?;
             }
 
-            if i % ETABLE_STEP_SIZE
+            if i % self.step_size
                 == EventTableUnlimitColumnRotation::ExternalHostCallLookup as usize
             {
                 ctx.region.assign_fixed(
@@ -60,7 +62,7 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
                 )?;
             }
 
-            if i % ETABLE_STEP_SIZE == EventTableUnlimitColumnRotation::JTableLookup as usize {
+            if i % self.step_size == EventTableUnlimitColumnRotation::JTableLookup as usize {
                 ctx.region.assign_fixed(
                     || "jtable lookup",
                     self.jtable_lookup,
@@ -69,7 +71,7 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
                 )?;
             }
 
-            if i % ETABLE_STEP_SIZE == EventTableUnlimitColumnRotation::PowTableLookup as usize {
+            if i % self.step_size == EventTableUnlimitColumnRotation::PowTableLookup as usize {
                 ctx.region.assign_fixed(
                     || "pow table lookup",
                     self.pow_table_lookup,
@@ -78,7 +80,7 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
                 )?;
             }
 
-            if i % ETABLE_STEP_SIZE
+            if i % self.step_size
                 == EventTableUnlimitColumnRotation::OffsetLenBitsTableLookup as usize
             {
                 ctx.region.assign_fixed(
@@ -89,8 +91,8 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
                 )?;
             }
 
-            if i % ETABLE_STEP_SIZE >= EventTableUnlimitColumnRotation::MTableLookupStart as usize
-                && i % ETABLE_STEP_SIZE < EventTableUnlimitColumnRotation::U64Start as usize
+            if i % self.step_size >= EventTableUnlimitColumnRotation::MTableLookupStart as usize
+                && i % self.step_size < EventTableUnlimitColumnRotation::U64Start as usize
             {
                 ctx.region.assign_fixed(
                     || "mtable lookup",
@@ -114,16 +116,41 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
             || Ok(F::from(0u64)),
         )?;
 
-        ctx.region.assign_advice_from_constant(
+        /*
+         * The universal circuit binds the initial memory pages to the image
+         * commitment rather than a constant.
+         */
+        let init_memory_pages_cell = if self.circuit_configure.image_table.is_some() {
+            let cell = ctx.region.assign_advice(
+                || "init current memory",
+                self.state,
+                EventTableCommonRangeColumnRotation::AllocatedMemoryPages as usize,
+                || Ok(F::from(configure.init_memory_pages as u64)),
+            )?;
+
+            Some(cell.cell())
+        } else {
+            ctx.region.assign_advice_from_constant(
+                || "init current memory",
+                self.state,
+                EventTableCommonRangeColumnRotation::AllocatedMemoryPages as usize,
+                F::from(configure.init_memory_pages as u64),
+            )?;
+
+            None
+        };
+        ctx.region.assign_advice(
             || "init current memory",
-            self.state,
+            self.state_high,
             EventTableCommonRangeColumnRotation::AllocatedMemoryPages as usize,
-            F::from(configure.init_memory_pages as u64),
+            || Ok(F::from(configure.init_memory_pages as u64 >> 16)),
         )?;
 
         let mut mops = vec![];
         let mut jops = vec![];
         let mut host_public_inputs = 0u64;
+        let mut host_public_outputs = 0u64;
+        let mut host_private_inputs = 0u64;
         let mut external_host_call_call_index = 1usize;
 
         macro_rules! assign_advice {
@@ -133,6 +160,16 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
             };
         }
 
+        macro_rules! assign_state {
+            ($o:expr, $k:expr, $v:expr) => {{
+                let v: u64 = $v;
+
+                let cell = assign_advice!(self.state, $o, $k, v);
+                assign_advice!(self.state_high, $o, $k, v >> 16);
+                cell
+            }};
+        }
+
         macro_rules! assign_constant {
             ($c:expr, $o:expr, $k:expr, $v:expr) => {
                 ctx.region.assign_advice_from_constant(
@@ -144,7 +181,7 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
             };
         }
 
-        for entry in etable.entries().iter() {
+        for (entry, callee_fid) in etable.entries().iter().zip(callee_fids.iter()) {
             status_entries.push(Status {
                 eid: entry.eid,
                 fid: entry.inst.fid,
@@ -152,6 +189,7 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
                 sp: entry.sp,
                 last_jump_eid: entry.last_jump_eid,
                 allocated_memory_pages: entry.allocated_memory_pages,
+                callee_fid: *callee_fid,
             });
         }
 
@@ -162,6 +200,7 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
             sp: 0,
             last_jump_eid: 0,
             allocated_memory_pages: 0,
+            callee_fid: 0,
         });
 
         let mut mops_in_total = 0;
@@ -174,6 +213,20 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
             F::zero()
         );
 
+        assign_constant!(
+            self.state,
+            EventTableCommonRangeColumnRotation::OutputIndex,
+            "output index",
+            F::zero()
+        );
+
+        assign_constant!(
+            self.state,
+            EventTableCommonRangeColumnRotation::PrivateInputIndex,
+            "private input index",
+            F::zero()
+        );
+
         assign_constant!(
             self.state,
             EventTableCommonRangeColumnRotation::ExternalHostCallIndex,
@@ -189,30 +242,46 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
                 next: &status_entries[index + 1],
                 current_external_host_call_index: external_host_call_call_index,
                 configure,
+                table_layouts,
             };
 
             let config = op_configs.get(&opcode).unwrap();
 
             config.assign(ctx, &step_status, entry)?;
 
-            mops.push(
-                config.assigned_extra_mops(ctx, &step_status, entry) + entry.inst.opcode.mops(),
-            );
+            // A trapping step doesn't perform the accesses of its opcode class.
+            if entry.step_info.is_trap() {
+                mops.push(config.assigned_extra_mops(ctx, &step_status, entry));
+            } else {
+                mops.push(
+                    config.assigned_extra_mops(ctx, &step_status, entry) + entry.inst.opcode.mops(),
+                );
+            }
             jops.push(entry.inst.opcode.jops());
 
             mops_in_total += mops.last().unwrap();
             jops_in_total += jops.last().unwrap();
 
             if index != 0 {
-                assign_advice!(
-                    self.state,
+                assign_state!(
                     EventTableCommonRangeColumnRotation::InputIndex,
                     "input index",
                     host_public_inputs
                 );
 
-                assign_advice!(
-                    self.state,
+                assign_state!(
+                    EventTableCommonRangeColumnRotation::OutputIndex,
+                    "output index",
+                    host_public_outputs
+                );
+
+                assign_state!(
+                    EventTableCommonRangeColumnRotation::PrivateInputIndex,
+                    "private input index",
+                    host_private_inputs
+                );
+
+                assign_state!(
                     EventTableCommonRangeColumnRotation::ExternalHostCallIndex,
                     "external host call index",
                     external_host_call_call_index as u64
@@ -222,11 +291,15 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
             if config.is_host_public_input(&step_status, entry) {
                 host_public_inputs += 1;
             }
+            if config.is_host_public_output(&step_status, entry) {
+                host_public_outputs += 1;
+            }
+            host_private_inputs += config.host_private_inputs(&step_status, entry);
             if let StepInfo::ExternalHostCall { .. } = entry.step_info {
                 external_host_call_call_index += 1;
             }
 
-            for _ in 0..ETABLE_STEP_SIZE {
+            for _ in 0..self.step_size {
                 ctx.next();
             }
         }
@@ -248,79 +321,95 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
         let mut rest_mops = mops.into_iter();
         let mut rest_jops = jops.into_iter();
 
-        // Step: fill Status for each eentry
-
-        for (index, entry) in etable.entries().iter().enumerate() {
-            let opcode: OpcodeClassPlain = entry.inst.opcode.clone().into();
-
-            assign_advice!(
+        // In trap mode, the trapping step must exist, so the first step is always enabled.
+        if self.circuit_configure.trap_mode {
+            assign_constant!(
                 self.shared_bits[0],
                 EventTableBitColumnRotation::Enable,
                 "shared_bits",
                 1
             );
+        }
+
+        // Step: fill Status for each eentry
+
+        for (index, entry) in etable.entries().iter().enumerate() {
+            let opcode: OpcodeClassPlain = entry.inst.opcode.clone().into();
+
+            if index != 0 || !self.circuit_configure.trap_mode {
+                assign_advice!(
+                    self.shared_bits[0],
+                    EventTableBitColumnRotation::Enable,
+                    "shared_bits",
+                    1
+                );
+            }
 
             {
-                let (op_lvl1, op_lvl2) = opclass_to_two_level(opcode);
+                let (op_lvl1, op_lvl2) = *op_bitmaps.get(&opcode).unwrap();
 
                 assign_advice!(self.opcode_bits, op_lvl1, "opcode level 1", 1);
                 assign_advice!(self.opcode_bits, op_lvl2, "opcode level 2", 1);
             }
 
-            assign_advice!(
-                self.state,
+            assign_state!(
                 EventTableCommonRangeColumnRotation::RestMOps,
                 "rest mops",
                 rest_mops.next().unwrap()
             );
 
-            assign_advice!(
-                self.state,
+            assign_state!(
                 EventTableCommonRangeColumnRotation::RestJOps,
                 "rest jops",
                 rest_jops.next().unwrap()
             );
 
-            assign_advice!(
-                self.state,
+            assign_state!(
                 EventTableCommonRangeColumnRotation::EID,
                 "eid",
                 entry.eid as u64
             );
 
-            assign_advice!(
-                self.state,
+            let fid_cell = assign_state!(
                 EventTableCommonRangeColumnRotation::FID,
                 "fid",
                 entry.inst.fid as u64
             );
 
-            assign_advice!(
-                self.state,
+            assign_state!(
                 EventTableCommonRangeColumnRotation::IID,
                 "iid",
                 entry.inst.iid as u64
             );
 
-            assign_advice!(
-                self.state,
+            assign_state!(
                 EventTableCommonRangeColumnRotation::SP,
                 "sp",
                 entry.sp as u64
             );
 
-            assign_advice!(
-                self.state,
+            assign_state!(
                 EventTableCommonRangeColumnRotation::LastJumpEid,
                 "last jump eid",
                 entry.last_jump_eid as u64
             );
 
+            let callee_fid_cell = assign_state!(
+                EventTableCommonRangeColumnRotation::CalleeFid,
+                "callee fid",
+                callee_fids[index] as u64
+            );
+
+            // The first step runs in the frame entered by its function.
+            if index == 0 {
+                ctx.region
+                    .constrain_equal(fid_cell.cell(), callee_fid_cell.cell())?;
+            }
+
             if index == 0 {
                 assert_eq!(entry.allocated_memory_pages, configure.init_memory_pages);
             } else {
-                assign_advice!(
-                    self.state,
+                assign_state!(
                     EventTableCommonRangeColumnRotation::AllocatedMemoryPages,
                     "current memory",
                     entry.allocated_memory_pages as u64
@@ -334,14 +423,14 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
                 || Ok(bn_to_field(&entry.inst.encode())),
             )?;
 
-            for _ in 0..ETABLE_STEP_SIZE {
+            for _ in 0..self.step_size {
                 ctx.next();
             }
         }
 
         // Step 3: fill the first disabled row
 
-        {
+        if !(self.circuit_configure.trap_mode && etable.entries().is_empty()) {
             assign_advice!(
                 self.shared_bits[0],
                 EventTableBitColumnRotation::Enable,
@@ -350,6 +439,10 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
             );
         }
 
-        Ok((Some(rest_mops_cell.cell()), Some(rest_jops_cell.cell())))
+        Ok((
+            Some(rest_mops_cell.cell()),
+            Some(rest_jops_cell.cell()),
+            init_memory_pages_cell,
+        ))
     }
 }
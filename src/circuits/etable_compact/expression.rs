@@ -21,7 +21,7 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
         nextn!(
             meta,
             self.shared_bits[0],
-            EventTableBitColumnRotation::Enable as i32 + ETABLE_STEP_SIZE as i32
+            EventTableBitColumnRotation::Enable as i32 + self.step_size as i32
         )
     }
 
@@ -41,7 +41,7 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
         nextn!(
             meta,
             self.state,
-            EventTableCommonRangeColumnRotation::RestMOps as i32 + ETABLE_STEP_SIZE as i32
+            EventTableCommonRangeColumnRotation::RestMOps as i32 + self.step_size as i32
         )
     }
 
@@ -57,7 +57,7 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
         nextn!(
             meta,
             self.state,
-            EventTableCommonRangeColumnRotation::RestJOps as i32 + ETABLE_STEP_SIZE as i32
+            EventTableCommonRangeColumnRotation::RestJOps as i32 + self.step_size as i32
         )
     }
 
@@ -73,7 +73,7 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
         nextn!(
             meta,
             self.state,
-            EventTableCommonRangeColumnRotation::InputIndex as i32 + ETABLE_STEP_SIZE as i32
+            EventTableCommonRangeColumnRotation::InputIndex as i32 + self.step_size as i32
         )
     }
 
@@ -89,7 +89,7 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
         nextn!(
             meta,
             self.state,
-            EventTableCommonRangeColumnRotation::EID as i32 + ETABLE_STEP_SIZE as i32
+            EventTableCommonRangeColumnRotation::EID as i32 + self.step_size as i32
         )
     }
 
@@ -105,7 +105,7 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
         nextn!(
             meta,
             self.state,
-            EventTableCommonRangeColumnRotation::FID as i32 + ETABLE_STEP_SIZE as i32
+            EventTableCommonRangeColumnRotation::FID as i32 + self.step_size as i32
         )
     }
 
@@ -121,7 +121,7 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
         nextn!(
             meta,
             self.state,
-            EventTableCommonRangeColumnRotation::IID as i32 + ETABLE_STEP_SIZE as i32
+            EventTableCommonRangeColumnRotation::IID as i32 + self.step_size as i32
         )
     }
 
@@ -137,7 +137,7 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
         nextn!(
             meta,
             self.state,
-            EventTableCommonRangeColumnRotation::SP as i32 + ETABLE_STEP_SIZE as i32
+            EventTableCommonRangeColumnRotation::SP as i32 + self.step_size as i32
         )
     }
 
@@ -153,7 +153,28 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
         nextn!(
             meta,
             self.state,
-            EventTableCommonRangeColumnRotation::LastJumpEid as i32 + ETABLE_STEP_SIZE as i32
+            EventTableCommonRangeColumnRotation::LastJumpEid as i32 + self.step_size as i32
+        )
+    }
+
+    /*
+     * The callee fid of the jtable entry of the current frame. A call enters a
+     * frame with its callee, a return restores the one of the frame returned
+     * to, a tail call runs its callee in the current frame.
+     */
+    pub fn callee_fid(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        nextn!(
+            meta,
+            self.state,
+            EventTableCommonRangeColumnRotation::CalleeFid as i32
+        )
+    }
+
+    pub fn next_callee_fid(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        nextn!(
+            meta,
+            self.state,
+            EventTableCommonRangeColumnRotation::CalleeFid as i32 + self.step_size as i32
         )
     }
 
@@ -170,7 +191,7 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
             meta,
             self.state,
             EventTableCommonRangeColumnRotation::AllocatedMemoryPages as i32
-                + ETABLE_STEP_SIZE as i32
+                + self.step_size as i32
         )
     }
 
@@ -187,7 +208,39 @@ impl<F: FieldExt> EventTableCommonConfig<F> {
             meta,
             self.state,
             EventTableCommonRangeColumnRotation::ExternalHostCallIndex as i32
-                + ETABLE_STEP_SIZE as i32
+                + self.step_size as i32
+        )
+    }
+
+    pub fn output_index(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        nextn!(
+            meta,
+            self.state,
+            EventTableCommonRangeColumnRotation::OutputIndex as i32
+        )
+    }
+
+    pub fn next_output_index(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        nextn!(
+            meta,
+            self.state,
+            EventTableCommonRangeColumnRotation::OutputIndex as i32 + self.step_size as i32
+        )
+    }
+
+    pub fn private_input_index(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        nextn!(
+            meta,
+            self.state,
+            EventTableCommonRangeColumnRotation::PrivateInputIndex as i32
+        )
+    }
+
+    pub fn next_private_input_index(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        nextn!(
+            meta,
+            self.state,
+            EventTableCommonRangeColumnRotation::PrivateInputIndex as i32 + self.step_size as i32
         )
     }
 
@@ -8,33 +8,56 @@ use crate::circuits::{
         op_call::CallConfigBuilder,
         op_call_host_foreign_circuit::ExternalCallHostCircuitConfigBuilder,
         op_call_indirect::CallIndirectConfigBuilder, op_const::ConstConfigBuilder,
-        op_conversion::ConversionConfigBuilder, op_drop::DropConfigBuilder,
-        op_global_get::GlobalGetConfigBuilder, op_global_set::GlobalSetConfigBuilder,
-        op_load::LoadConfigBuilder, op_local_get::LocalGetConfigBuilder,
-        op_local_set::LocalSetConfigBuilder, op_local_tee::LocalTeeConfigBuilder,
-        op_memory_grow::MemoryGrowConfigBuilder, op_memory_size::MemorySizeConfigBuilder,
-        op_rel::RelConfigBuilder, op_return::ReturnConfigBuilder, op_select::SelectConfigBuilder,
-        op_store::StoreConfigBuilder, op_test::TestConfigBuilder, op_unary::UnaryConfigBuilder,
-        ConstraintBuilder, EventTableCellAllocator, EventTableOpcodeConfigBuilder,
+        op_conversion::ConversionConfigBuilder, op_data_drop::DataDropConfigBuilder,
+        op_drop::DropConfigBuilder, op_global_get::GlobalGetConfigBuilder,
+        op_global_set::GlobalSetConfigBuilder, op_load::LoadConfigBuilder,
+        op_local_get::LocalGetConfigBuilder, op_local_set::LocalSetConfigBuilder,
+        op_local_tee::LocalTeeConfigBuilder, op_memory_copy::MemoryCopyConfigBuilder,
+        op_memory_fill::MemoryFillConfigBuilder, op_memory_grow::MemoryGrowConfigBuilder,
+        op_memory_init::MemoryInitConfigBuilder, op_memory_size::MemorySizeConfigBuilder,
+        op_rel::RelConfigBuilder, op_return::ReturnConfigBuilder,
+        op_return_call::ReturnCallConfigBuilder,
+        op_return_call_indirect::ReturnCallIndirectConfigBuilder, op_select::SelectConfigBuilder,
+        op_store::StoreConfigBuilder, op_table_get::TableGetConfigBuilder,
+        op_table_grow::TableGrowConfigBuilder, op_table_set::TableSetConfigBuilder,
+        op_table_size::TableSizeConfigBuilder, op_test::TestConfigBuilder,
+        op_unary::UnaryConfigBuilder, op_unreachable::UnreachableConfigBuilder, ConstraintBuilder,
+        EventTableCellAllocator, EventTableOpcodeConfigBuilder,
     },
+    image_table::ImageConfigureConfig,
     traits::ConfigureLookupTable,
     utils::bn_to_field,
 };
 use crate::{
     constant_from, curr, fixed_curr,
     foreign::{
+        keccak_helper::etable_op_configure::{
+            ETableKeccakHelperTableConfigBuilder, KeccakForeignCallInfo,
+        },
+        kv_helper::etable_op_configure::{ETableKvHelperTableConfigBuilder, KvForeignCallInfo},
+        poseidon_helper::etable_op_configure::{
+            ETablePoseidonHelperTableConfigBuilder, PoseidonForeignCallInfo,
+        },
+        registry::PluginRegistry,
         require_helper::etable_op_configure::{
             ETableRequireHelperTableConfigBuilder, RequireForeignCallInfo,
         },
+        secp256k1_helper::etable_op_configure::{
+            ETableSecp256k1HelperTableConfigBuilder, Secp256k1ForeignCallInfo,
+        },
+        sha256_compress_helper::etable_op_configure::{
+            ETableSha256CompressHelperTableConfigBuilder, Sha256CompressForeignCallInfo,
+        },
         sha256_helper::etable_op_configure::{
             ETableSha256HelperTableConfigBuilder, Sha256ForeignCallInfo,
         },
-        wasm_input_helper::etable_op_configure::{
-            ETableWasmInputHelperTableConfigBuilder, WasmInputForeignCallInfo,
+        u256_helper::etable_op_configure::{
+            ETableU256HelperTableConfigBuilder, U256ForeignCallInfo,
         },
+        wasm_input_helper::etable_op_configure::ETableWasmInputHelperTableConfigBuilder,
         EventTableForeignCallConfigBuilder, ForeignTableConfig,
     },
-    nextn,
+    instance_curr, nextn,
 };
 use halo2_proofs::{
     arithmetic::FieldExt,
@@ -42,6 +65,7 @@ use halo2_proofs::{
     plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, VirtualCells},
 };
 use specs::{
+    brtable::TableLayout,
     configure_table::ConfigureTable,
     encode::instruction_table::encode_instruction_table_entry,
     etable::{EventTable, EventTableEntry},
@@ -58,27 +82,31 @@ pub mod expression;
 pub mod op_configure;
 
 // TODO:
-// 1. add constraints for termination
+// 1. add constraints for termination of a successful execution
 // 2. add input output for circuits
 
-const ETABLE_STEP_SIZE: usize = 20usize;
-const U4_COLUMNS: usize = 3usize;
-const U8_COLUMNS: usize = 2usize;
-const BITS_COLUMNS: usize = 2usize;
+/*
+ * The upper bound of the rows of a step, the opcode configs are dry run with it
+ * and the step is then sized by their allocation, see EventTableColumnUsage::step_size.
+ */
+const MAX_ETABLE_STEP_SIZE: usize = 20usize;
+// Upper bounds of the shared columns, the u64 values of U4 and U8 columns fill the rest of aux.
+const MAX_U4_COLUMNS: usize = 3usize;
+const MAX_U8_COLUMNS: usize = 2usize;
+const MAX_BITS_COLUMNS: usize = 2usize;
 const MTABLE_LOOKUPS_SIZE: usize = 6usize;
-const MAX_OP_LVL1: i32 = (ETABLE_STEP_SIZE >> 1) as i32;
-const MAX_OP_LVL2: i32 = ETABLE_STEP_SIZE as i32;
-
-fn opclass_to_two_level(class: OpcodeClassPlain) -> (usize, usize) {
-    let mut id = class.0 as i32;
-    assert!(id <= MAX_OP_LVL1 * (MAX_OP_LVL2 - MAX_OP_LVL1));
-
-    id -= 1;
-
-    (
-        (id / MAX_OP_LVL1) as usize,
-        ((id % MAX_OP_LVL1) + MAX_OP_LVL1) as usize,
-    )
+// The rows of the u4 and u8 cells of a u64 value.
+const U64_DECOMPOSITION_ROWS: usize = 16usize;
+
+/*
+ * The enabled opcode classes are numbered in order, each one is selected by a
+ * bit of the first half of the opcode bits and a bit of the second half.
+ */
+fn op_index_to_two_level(index: usize, step_size: usize) -> (usize, usize) {
+    let lvl = step_size >> 1;
+    assert!(index < lvl * lvl);
+
+    (index / lvl, index % lvl + lvl)
 }
 
 pub(crate) enum EventTableBitColumnRotation {
@@ -95,8 +123,12 @@ pub(crate) enum EventTableCommonRangeColumnRotation {
     IID,
     SP,
     LastJumpEid,
+    CalleeFid,
     AllocatedMemoryPages,
     ExternalHostCallIndex,
+    OutputIndex,
+    PrivateInputIndex,
+    Max,
 }
 
 pub(crate) enum EventTableUnlimitColumnRotation {
@@ -141,6 +173,8 @@ pub struct Status {
     pub sp: u32,
     pub last_jump_eid: u32,
     pub allocated_memory_pages: u32,
+    // See EventTableCommonConfig::callee_fid
+    pub callee_fid: u32,
 }
 
 pub struct StepStatus<'a> {
@@ -148,6 +182,7 @@ pub struct StepStatus<'a> {
     pub next: &'a Status,
     pub current_external_host_call_index: usize,
     pub configure: ConfigureTable,
+    pub table_layouts: &'a [TableLayout],
 }
 
 impl TryFrom<u32> for MLookupItem {
@@ -167,18 +202,95 @@ impl TryFrom<u32> for MLookupItem {
 #[derive(Clone)]
 pub struct ConstantCell(pub Column<Fixed>);
 
+/*
+ * Cells of the shared columns requested by the opcode configs. The event table
+ * only allocates the columns and the rows needed by the enabled opcode classes.
+ */
+#[derive(Clone, Copy)]
+pub(crate) struct EventTableColumnUsage {
+    pub(crate) bits: usize,
+    pub(crate) common_range: usize,
+    pub(crate) unlimited: usize,
+    pub(crate) u4_bop: bool,
+    pub(crate) u64: usize,
+    pub(crate) u64_on_u8: usize,
+}
+
+impl EventTableColumnUsage {
+    fn max() -> Self {
+        Self {
+            bits: MAX_BITS_COLUMNS * MAX_ETABLE_STEP_SIZE,
+            common_range: MAX_ETABLE_STEP_SIZE,
+            unlimited: MAX_ETABLE_STEP_SIZE,
+            u4_bop: true,
+            u64: MAX_U4_COLUMNS,
+            u64_on_u8: MAX_U8_COLUMNS * 2,
+        }
+    }
+
+    fn merge(self, other: Self) -> Self {
+        Self {
+            bits: usize::max(self.bits, other.bits),
+            common_range: usize::max(self.common_range, other.common_range),
+            unlimited: usize::max(self.unlimited, other.unlimited),
+            u4_bop: self.u4_bop || other.u4_bop,
+            u64: usize::max(self.u64, other.u64),
+            u64_on_u8: usize::max(self.u64_on_u8, other.u64_on_u8),
+        }
+    }
+
+    /*
+     * The rows of a step holding the common states, the allocated cells and
+     * the opcode bits of the enabled opcode classes.
+     */
+    fn step_size(&self, ops: usize) -> usize {
+        let mut step_size = [
+            EventTableCommonRangeColumnRotation::Max as usize,
+            self.common_range,
+            self.unlimited,
+            EventTableUnlimitColumnRotation::U64Start as usize + self.u64 + self.u64_on_u8,
+            if self.u64 + self.u64_on_u8 > 0 {
+                U64_DECOMPOSITION_ROWS
+            } else {
+                0
+            },
+        ]
+        .into_iter()
+        .max()
+        .unwrap();
+
+        while (step_size >> 1) * (step_size >> 1) < ops {
+            step_size += 1;
+        }
+
+        assert!(step_size <= MAX_ETABLE_STEP_SIZE);
+
+        step_size
+    }
+}
+
+type EventTableOpcodeConfigBuilderFn<F> = Box<
+    dyn Fn(
+        &mut EventTableCellAllocator<F>,
+        &mut ConstraintBuilder<F>,
+    ) -> Box<dyn EventTableOpcodeConfig<F>>,
+>;
+
 #[derive(Clone)]
 pub struct EventTableCommonConfig<F> {
     pub sel: Column<Fixed>,
     pub block_first_line_sel: Column<Fixed>,
 
-    pub shared_bits: [Column<Advice>; BITS_COLUMNS],
+    pub shared_bits: Vec<Column<Advice>>,
     pub opcode_bits: Column<Advice>,
 
     pub state: Column<Advice>,
     pub common_range: Column<Advice>,
+    // High u16 limbs of state and common_range, see RangeTableConfig::configure_in_common_range
+    pub state_high: Column<Advice>,
+    pub common_range_high: Column<Advice>,
 
-    pub unlimited: Column<Advice>,
+    pub unlimited: Option<Column<Advice>>,
 
     pub itable_lookup: Column<Fixed>,
     pub brtable_lookup: Column<Fixed>,
@@ -190,26 +302,96 @@ pub struct EventTableCommonConfig<F> {
 
     pub aux: Column<Advice>,
 
-    pub u4_bop: Column<Advice>,
-    pub u4_shared: [Column<Advice>; U4_COLUMNS],
-    pub u8_shared: [Column<Advice>; U8_COLUMNS],
+    pub u4_bop: Option<Column<Advice>>,
+    pub u4_shared: Vec<Column<Advice>>,
+    pub u8_shared: Vec<Column<Advice>>,
 
     pub circuit_configure: CircuitConfigure,
+    pub image_configure: ImageConfigureConfig<F>,
+
+    // The rows of a step, see EventTableColumnUsage::step_size.
+    pub step_size: usize,
 
     _mark: PhantomData<F>,
 }
 
+impl<F: FieldExt> EventTableCommonConfig<F> {
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        circuit_configure: &CircuitConfigure,
+        image_configure: &ImageConfigureConfig<F>,
+        usage: &EventTableColumnUsage,
+        step_size: usize,
+    ) -> Self {
+        let sel = meta.fixed_column();
+        let block_first_line_sel = meta.fixed_column();
+        let shared_bits = (0..(usage.bits + step_size - 1) / step_size)
+            .map(|_| meta.advice_column())
+            .collect();
+        let opcode_bits = meta.advice_column();
+
+        let state = meta.advice_column();
+        let common_range = meta.advice_column();
+        let state_high = meta.advice_column();
+        let common_range_high = meta.advice_column();
+        let aux = meta.advice_column();
+        let unlimited = (usage.unlimited > 0).then(|| meta.advice_column());
+
+        let itable_lookup = meta.fixed_column();
+        let brtable_lookup = meta.fixed_column();
+        let external_host_call_table_lookup = meta.fixed_column();
+        let jtable_lookup = meta.fixed_column();
+        let mtable_lookup = meta.fixed_column();
+        let pow_table_lookup = meta.fixed_column();
+        let offset_len_bits_table_lookup = meta.fixed_column();
+
+        let u4_shared = (0..usage.u64).map(|_| meta.advice_column()).collect();
+        let u8_shared = (0..(usage.u64_on_u8 + 1) / 2)
+            .map(|_| meta.advice_column())
+            .collect();
+        let u4_bop = usage.u4_bop.then(|| meta.advice_column());
+
+        EventTableCommonConfig {
+            sel,
+            block_first_line_sel,
+            shared_bits,
+            opcode_bits,
+            state,
+            common_range,
+            state_high,
+            common_range_high,
+            unlimited,
+            itable_lookup,
+            brtable_lookup,
+            external_host_call_table_lookup,
+            jtable_lookup,
+            mtable_lookup,
+            pow_table_lookup,
+            offset_len_bits_table_lookup,
+            aux,
+            u4_shared,
+            u8_shared,
+            u4_bop,
+            circuit_configure: circuit_configure.clone(),
+            image_configure: image_configure.clone(),
+            step_size,
+            _mark: PhantomData,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct EventTableConfig<F: FieldExt> {
     common_config: EventTableCommonConfig<F>,
     op_configs: BTreeMap<OpcodeClassPlain, Rc<Box<dyn EventTableOpcodeConfig<F>>>>,
+    op_bitmaps: BTreeMap<OpcodeClassPlain, (i32, i32)>,
 }
 
 impl<F: FieldExt> EventTableConfig<F> {
     pub fn configure(
         meta: &mut ConstraintSystem<F>,
-        cols: &mut (impl Iterator<Item = Column<Advice>> + Clone),
         circuit_configure: &CircuitConfigure,
+        image_configure: &ImageConfigureConfig<F>,
         rtable: &RangeTableConfig<F>,
         itable: &InstructionTableConfig<F>,
         mtable: &MemoryTableConfig<F>,
@@ -217,29 +399,230 @@ impl<F: FieldExt> EventTableConfig<F> {
         brtable: &BrTableConfig<F>,
         external_host_call_table: &ExternalHostCallTableConfig<F>,
         foreign_tables: &BTreeMap<&'static str, Box<dyn ForeignTableConfig<F>>>,
+        plugins: &PluginRegistry<F>,
         opcode_set: &BTreeSet<OpcodeClassPlain>,
     ) -> Self {
-        let sel = meta.fixed_column();
-        let block_first_line_sel = meta.fixed_column();
-        let shared_bits = [0; BITS_COLUMNS].map(|_| cols.next().unwrap());
-        let opcode_bits = cols.next().unwrap();
+        let mut op_builders: Vec<(OpcodeClassPlain, EventTableOpcodeConfigBuilderFn<F>)> = vec![];
+
+        macro_rules! configure [
+            ($op:expr, $x:ident) => ({
+                let op = OpcodeClassPlain($op as usize);
+                if opcode_set.contains(&op) {
+                    op_builders.push((
+                        op,
+                        Box::new(<$x as EventTableOpcodeConfigBuilder<F>>::configure),
+                    ));
+                }
+            })
+        ];
 
-        let state = cols.next().unwrap();
-        let common_range = cols.next().unwrap();
-        let aux = cols.next().unwrap();
-        let unlimited = cols.next().unwrap();
+        macro_rules! configure_foreign [
+            ($op:expr, $x:ident, $call_info:ident) => ({
+                let op = OpcodeClassPlain(OpcodeClass::ForeignPluginStart as usize + $op.id());
+                if opcode_set.contains(&op) {
+                    op_builders.push((
+                        op,
+                        Box::new(
+                            |allocator: &mut EventTableCellAllocator<F>,
+                             constraint_builder: &mut ConstraintBuilder<F>| {
+                                $x::configure(allocator, constraint_builder, &$call_info {})
+                            },
+                        ),
+                    ));
+                }
+            })
+        ];
 
-        let itable_lookup = meta.fixed_column();
-        let brtable_lookup = meta.fixed_column();
-        let external_host_call_table_lookup = meta.fixed_column();
-        let jtable_lookup = meta.fixed_column();
-        let mtable_lookup = meta.fixed_column();
-        let pow_table_lookup = meta.fixed_column();
-        let offset_len_bits_table_lookup = meta.fixed_column();
+        configure!(OpcodeClass::Return, ReturnConfigBuilder);
+        configure!(OpcodeClass::Br, BrConfigBuilder);
+        configure!(OpcodeClass::BrIfEqz, BrIfEqzConfigBuilder);
+        configure!(OpcodeClass::BrTable, BrTableConfigBuilder);
+        configure!(OpcodeClass::Call, CallConfigBuilder);
+        configure!(OpcodeClass::CallIndirect, CallIndirectConfigBuilder);
+        configure!(OpcodeClass::ReturnCall, ReturnCallConfigBuilder);
+        configure!(
+            OpcodeClass::ReturnCallIndirect,
+            ReturnCallIndirectConfigBuilder
+        );
+        {
+            let op = OpcodeClassPlain(OpcodeClass::CallHost as usize);
+            if opcode_set.contains(&op) {
+                let visibility = circuit_configure.external_host_call_visibility.clone();
+
+                op_builders.push((
+                    op,
+                    Box::new(
+                        move |allocator: &mut EventTableCellAllocator<F>,
+                              constraint_builder: &mut ConstraintBuilder<F>| {
+                            ExternalCallHostCircuitConfigBuilder::configure(
+                                allocator,
+                                constraint_builder,
+                                &visibility,
+                            )
+                        },
+                    ),
+                ));
+            }
+        }
+        configure!(OpcodeClass::Const, ConstConfigBuilder);
+        configure!(OpcodeClass::Drop, DropConfigBuilder);
+        configure!(OpcodeClass::LocalGet, LocalGetConfigBuilder);
+        configure!(OpcodeClass::LocalSet, LocalSetConfigBuilder);
+        configure!(OpcodeClass::LocalTee, LocalTeeConfigBuilder);
+        configure!(OpcodeClass::GlobalGet, GlobalGetConfigBuilder);
+        configure!(OpcodeClass::GlobalSet, GlobalSetConfigBuilder);
+        configure!(OpcodeClass::Bin, BinConfigBuilder);
+        configure!(OpcodeClass::BinBit, BinBitConfigBuilder);
+        configure!(OpcodeClass::BinShift, BinShiftConfigBuilder);
+        configure!(OpcodeClass::Unary, UnaryConfigBuilder);
+        configure!(OpcodeClass::BrIf, BrIfConfigBuilder);
+        configure!(OpcodeClass::Load, LoadConfigBuilder);
+        configure!(OpcodeClass::Store, StoreConfigBuilder);
+        configure!(OpcodeClass::MemorySize, MemorySizeConfigBuilder);
+        configure!(OpcodeClass::MemoryGrow, MemoryGrowConfigBuilder);
+        configure!(OpcodeClass::Rel, RelConfigBuilder);
+        configure!(OpcodeClass::Select, SelectConfigBuilder);
+        configure!(OpcodeClass::Test, TestConfigBuilder);
+        configure!(OpcodeClass::Conversion, ConversionConfigBuilder);
+        configure!(OpcodeClass::MemoryFill, MemoryFillConfigBuilder);
+        configure!(OpcodeClass::MemoryCopy, MemoryCopyConfigBuilder);
+        configure!(OpcodeClass::MemoryInit, MemoryInitConfigBuilder);
+        configure!(OpcodeClass::DataDrop, DataDropConfigBuilder);
+        configure!(OpcodeClass::Unreachable, UnreachableConfigBuilder);
+        configure!(OpcodeClass::TableGet, TableGetConfigBuilder);
+        configure!(OpcodeClass::TableSet, TableSetConfigBuilder);
+        configure!(OpcodeClass::TableSize, TableSizeConfigBuilder);
+        configure!(OpcodeClass::TableGrow, TableGrowConfigBuilder);
+        {
+            let op = OpcodeClassPlain(
+                OpcodeClass::ForeignPluginStart as usize + HostPlugin::HostInput.id(),
+            );
+            if opcode_set.contains(&op) {
+                let commits_private_inputs = circuit_configure.private_input_commitment.is_some();
+
+                op_builders.push((
+                    op,
+                    Box::new(
+                        move |allocator: &mut EventTableCellAllocator<F>,
+                              constraint_builder: &mut ConstraintBuilder<F>| {
+                            ETableWasmInputHelperTableConfigBuilder::configure(
+                                allocator,
+                                constraint_builder,
+                                commits_private_inputs,
+                            )
+                        },
+                    ),
+                ));
+            }
+        }
+        configure_foreign!(
+            HostPlugin::Sha256,
+            ETableSha256HelperTableConfigBuilder,
+            Sha256ForeignCallInfo
+        );
+        configure_foreign!(
+            HostPlugin::Require,
+            ETableRequireHelperTableConfigBuilder,
+            RequireForeignCallInfo
+        );
+        configure_foreign!(
+            HostPlugin::Keccak,
+            ETableKeccakHelperTableConfigBuilder,
+            KeccakForeignCallInfo
+        );
+        configure_foreign!(
+            HostPlugin::Poseidon,
+            ETablePoseidonHelperTableConfigBuilder,
+            PoseidonForeignCallInfo
+        );
+        configure_foreign!(
+            HostPlugin::Sha256Compress,
+            ETableSha256CompressHelperTableConfigBuilder,
+            Sha256CompressForeignCallInfo
+        );
+        configure_foreign!(
+            HostPlugin::Secp256k1,
+            ETableSecp256k1HelperTableConfigBuilder,
+            Secp256k1ForeignCallInfo
+        );
+        configure_foreign!(
+            HostPlugin::U256,
+            ETableU256HelperTableConfigBuilder,
+            U256ForeignCallInfo
+        );
+        configure_foreign!(
+            HostPlugin::Kv,
+            ETableKvHelperTableConfigBuilder,
+            KvForeignCallInfo
+        );
+        for (plugin, registered) in plugins.plugins() {
+            let op = OpcodeClassPlain(OpcodeClass::ForeignPluginStart as usize + plugin.id());
+            if opcode_set.contains(&op) {
+                let registered = registered.clone();
+
+                op_builders.push((
+                    op,
+                    Box::new(
+                        move |allocator: &mut EventTableCellAllocator<F>,
+                              constraint_builder: &mut ConstraintBuilder<F>| {
+                            registered.configure_etable(allocator, constraint_builder, plugin)
+                        },
+                    ),
+                ));
+            }
+        }
+
+        /*
+         * Dry run the opcode configs on a scratch constraint system to find out
+         * how many shared cells they need, so that unused opcode classes cost
+         * no columns nor rows.
+         */
+        let usage = {
+            let mut meta = ConstraintSystem::default();
+            let common_config = EventTableCommonConfig::new(
+                &mut meta,
+                circuit_configure,
+                image_configure,
+                &EventTableColumnUsage::max(),
+                MAX_ETABLE_STEP_SIZE,
+            );
+
+            op_builders.iter().fold(
+                EventTableCellAllocator::new(&common_config).usage(),
+                |usage, (_, builder)| {
+                    let mut allocator = EventTableCellAllocator::new(&common_config);
+                    builder(&mut allocator, &mut ConstraintBuilder::new(&mut meta));
+                    usage.merge(allocator.usage())
+                },
+            )
+        };
 
-        let u4_shared = [0; U4_COLUMNS].map(|_| cols.next().unwrap());
-        let u8_shared = [0; U8_COLUMNS].map(|_| cols.next().unwrap());
-        let u4_bop = cols.next().unwrap();
+        let step_size = usage.step_size(op_builders.len());
+        let common_config = EventTableCommonConfig::new(
+            meta,
+            circuit_configure,
+            image_configure,
+            &usage,
+            step_size,
+        );
+        let shared_bits = common_config.shared_bits.clone();
+        let opcode_bits = common_config.opcode_bits;
+        let state = common_config.state;
+        let common_range = common_config.common_range;
+        let state_high = common_config.state_high;
+        let common_range_high = common_config.common_range_high;
+        let aux = common_config.aux;
+        let sel = common_config.sel;
+        let block_first_line_sel = common_config.block_first_line_sel;
+        let itable_lookup = common_config.itable_lookup;
+        let brtable_lookup = common_config.brtable_lookup;
+        let external_host_call_table_lookup = common_config.external_host_call_table_lookup;
+        let jtable_lookup = common_config.jtable_lookup;
+        let mtable_lookup = common_config.mtable_lookup;
+        let pow_table_lookup = common_config.pow_table_lookup;
+        let offset_len_bits_table_lookup = common_config.offset_len_bits_table_lookup;
+        let u4_shared = common_config.u4_shared.clone();
+        let u8_shared = common_config.u8_shared.clone();
 
         meta.enable_equality(state);
         meta.create_gate("etable opcode bits", |meta| {
@@ -258,33 +641,43 @@ impl<F: FieldExt> EventTableConfig<F> {
                 .collect::<Vec<_>>()
         });
 
-        rtable.configure_in_u4_bop_set(meta, "etable u4 bop", |meta| {
-            curr!(meta, u4_bop) * fixed_curr!(meta, sel)
-        });
+        if let Some(u4_bop) = common_config.u4_bop {
+            assert!(u4_shared.len() >= 3);
 
-        rtable.configure_in_u4_bop_calc_set(meta, "etable u4 bop calc", |meta| {
-            (
-                curr!(meta, u4_shared[0]),
-                curr!(meta, u4_shared[1]),
-                curr!(meta, u4_shared[2]),
-                curr!(meta, u4_bop) * fixed_curr!(meta, sel),
-            )
-        });
+            rtable.configure_in_u4_bop_set(meta, "etable u4 bop", |meta| {
+                curr!(meta, u4_bop) * fixed_curr!(meta, sel)
+            });
+
+            rtable.configure_in_u4_bop_calc_set(meta, "etable u4 bop calc", |meta| {
+                (
+                    curr!(meta, u4_shared[0]),
+                    curr!(meta, u4_shared[1]),
+                    curr!(meta, u4_shared[2]),
+                    curr!(meta, u4_bop) * fixed_curr!(meta, sel),
+                )
+            });
+        }
 
         rtable.configure_in_common_range(meta, "etable state in common", |meta| {
-            curr!(meta, state) * fixed_curr!(meta, sel)
+            (
+                curr!(meta, state) * fixed_curr!(meta, sel),
+                curr!(meta, state_high) * fixed_curr!(meta, sel),
+            )
         });
         rtable.configure_in_common_range(meta, "etable common range", |meta| {
-            curr!(meta, common_range) * fixed_curr!(meta, sel)
+            (
+                curr!(meta, common_range) * fixed_curr!(meta, sel),
+                curr!(meta, common_range_high) * fixed_curr!(meta, sel),
+            )
         });
 
-        for i in 0..U4_COLUMNS {
+        for i in 0..u4_shared.len() {
             rtable.configure_in_u4_range(meta, "etable u4", |meta| {
                 curr!(meta, u4_shared[i]) * fixed_curr!(meta, sel)
             });
         }
 
-        for i in 0..U8_COLUMNS {
+        for i in 0..u8_shared.len() {
             rtable.configure_in_u8_range(meta, "etable u8", |meta| {
                 curr!(meta, u8_shared[i]) * fixed_curr!(meta, sel)
             });
@@ -320,7 +713,7 @@ impl<F: FieldExt> EventTableConfig<F> {
             curr!(meta, aux) * fixed_curr!(meta, offset_len_bits_table_lookup)
         });
 
-        for i in 0..U4_COLUMNS {
+        for i in 0..u4_shared.len() {
             meta.create_gate("etable u64 on u4", |meta| {
                 let mut acc = nextn!(
                     meta,
@@ -337,13 +730,13 @@ impl<F: FieldExt> EventTableConfig<F> {
             });
         }
 
-        for i in 0..U8_COLUMNS {
+        for i in 0..u8_shared.len() {
             meta.create_gate("etable u64 on u8", |meta| {
                 let mut acc1 = nextn!(
                     meta,
                     aux,
                     EventTableUnlimitColumnRotation::U64Start as i32
-                        + U4_COLUMNS as i32
+                        + u4_shared.len() as i32
                         + i as i32 * 2
                 );
                 let mut base = 1u64;
@@ -356,7 +749,7 @@ impl<F: FieldExt> EventTableConfig<F> {
                     meta,
                     aux,
                     EventTableUnlimitColumnRotation::U64Start as i32
-                        + U4_COLUMNS as i32
+                        + u4_shared.len() as i32
                         + i as i32 * 2
                         + 1
                 );
@@ -373,126 +766,25 @@ impl<F: FieldExt> EventTableConfig<F> {
             });
         }
 
-        let common_config = EventTableCommonConfig {
-            sel,
-            block_first_line_sel,
-            shared_bits,
-            opcode_bits,
-            state,
-            common_range,
-            unlimited,
-            itable_lookup,
-            brtable_lookup,
-            external_host_call_table_lookup,
-            jtable_lookup,
-            mtable_lookup,
-            pow_table_lookup,
-            offset_len_bits_table_lookup,
-            aux,
-            u4_shared,
-            u8_shared,
-            u4_bop,
-            circuit_configure: circuit_configure.clone(),
-            _mark: PhantomData,
-        };
-
         let mut op_bitmaps: BTreeMap<OpcodeClassPlain, (i32, i32)> = BTreeMap::new();
         let mut op_configs: BTreeMap<OpcodeClassPlain, Rc<Box<dyn EventTableOpcodeConfig<F>>>> =
             BTreeMap::new();
 
-        macro_rules! configure [
-            ($op:expr, $x:ident) => ({
-                let op = OpcodeClassPlain($op as usize);
-                if opcode_set.contains(&op) {
-                    let (op_lvl1, op_lvl2) = opclass_to_two_level(op);
-                    let mut allocator = EventTableCellAllocator::new(&common_config);
-                    let mut constraint_builder = ConstraintBuilder::new(meta);
-
-                    let config = $x::configure(
-                        &mut allocator,
-                        &mut constraint_builder,
-                    );
-
-                    constraint_builder.finalize(foreign_tables, |meta|
-                        fixed_curr!(meta, common_config.block_first_line_sel) *
-                            common_config.op_enabled(meta, op_lvl1 as i32, op_lvl2 as i32)
-                    );
+        for (index, (op, builder)) in op_builders.into_iter().enumerate() {
+            let (op_lvl1, op_lvl2) = op_index_to_two_level(index, step_size);
+            let mut allocator = EventTableCellAllocator::new(&common_config);
+            let mut constraint_builder = ConstraintBuilder::new(meta);
 
-                    op_bitmaps.insert(op, (op_lvl1 as i32, op_lvl2 as i32));
-                    op_configs.insert(op, Rc::new(config));
-                }
-    })
-        ];
-
-        macro_rules! configure_foreign [
-            ($op:expr, $x:ident, $call_info:ident) => ({
-                let op = OpcodeClassPlain(OpcodeClass::ForeignPluginStart as usize + $op as usize);
-
-                if opcode_set.contains(&op) {
-                    let (op_lvl1, op_lvl2) = opclass_to_two_level(op);
-                    let mut allocator = EventTableCellAllocator::new(&common_config);
-                    let mut constraint_builder = ConstraintBuilder::new(meta);
-
-                    let config = $x::configure(
-                        &mut allocator,
-                        &mut constraint_builder,
-                        &$call_info{},
-                    );
+            let config = builder(&mut allocator, &mut constraint_builder);
 
-                    constraint_builder.finalize(foreign_tables, |meta|
-                        fixed_curr!(meta, common_config.block_first_line_sel) *
-                            common_config.op_enabled(meta, op_lvl1 as i32, op_lvl2 as i32)
-                    );
-
-                    op_bitmaps.insert(op, (op_lvl1 as i32, op_lvl2 as i32));
-                    op_configs.insert(op, Rc::new(config));
-                }
-            })
-        ];
+            constraint_builder.finalize(foreign_tables, |meta| {
+                fixed_curr!(meta, common_config.block_first_line_sel)
+                    * common_config.op_enabled(meta, op_lvl1 as i32, op_lvl2 as i32)
+            });
 
-        configure!(OpcodeClass::Return, ReturnConfigBuilder);
-        configure!(OpcodeClass::Br, BrConfigBuilder);
-        configure!(OpcodeClass::BrIfEqz, BrIfEqzConfigBuilder);
-        configure!(OpcodeClass::BrTable, BrTableConfigBuilder);
-        configure!(OpcodeClass::Call, CallConfigBuilder);
-        configure!(OpcodeClass::CallIndirect, CallIndirectConfigBuilder);
-        configure!(OpcodeClass::CallHost, ExternalCallHostCircuitConfigBuilder);
-        configure!(OpcodeClass::Const, ConstConfigBuilder);
-        configure!(OpcodeClass::Drop, DropConfigBuilder);
-        configure!(OpcodeClass::LocalGet, LocalGetConfigBuilder);
-        configure!(OpcodeClass::LocalSet, LocalSetConfigBuilder);
-        configure!(OpcodeClass::LocalTee, LocalTeeConfigBuilder);
-        configure!(OpcodeClass::GlobalGet, GlobalGetConfigBuilder);
-        configure!(OpcodeClass::GlobalSet, GlobalSetConfigBuilder);
-        configure!(OpcodeClass::Bin, BinConfigBuilder);
-        configure!(OpcodeClass::BinBit, BinBitConfigBuilder);
-        configure!(OpcodeClass::BinShift, BinShiftConfigBuilder);
-        configure!(OpcodeClass::Unary, UnaryConfigBuilder);
-        configure!(OpcodeClass::BrIf, BrIfConfigBuilder);
-        configure!(OpcodeClass::Load, LoadConfigBuilder);
-        configure!(OpcodeClass::Store, StoreConfigBuilder);
-        configure!(OpcodeClass::MemorySize, MemorySizeConfigBuilder);
-        configure!(OpcodeClass::MemoryGrow, MemoryGrowConfigBuilder);
-        configure!(OpcodeClass::Rel, RelConfigBuilder);
-        configure!(OpcodeClass::Select, SelectConfigBuilder);
-        configure!(OpcodeClass::Test, TestConfigBuilder);
-        configure!(OpcodeClass::Conversion, ConversionConfigBuilder);
-        // TODO: dynamically register plugins
-        configure_foreign!(
-            HostPlugin::HostInput,
-            ETableWasmInputHelperTableConfigBuilder,
-            WasmInputForeignCallInfo
-        );
-        configure_foreign!(
-            HostPlugin::Sha256,
-            ETableSha256HelperTableConfigBuilder,
-            Sha256ForeignCallInfo
-        );
-        configure_foreign!(
-            HostPlugin::Require,
-            ETableRequireHelperTableConfigBuilder,
-            RequireForeignCallInfo
-        );
+            op_bitmaps.insert(op, (op_lvl1 as i32, op_lvl2 as i32));
+            op_configs.insert(op, Rc::new(config));
+        }
 
         meta.create_gate("enable seq", |meta| {
             vec![
@@ -502,6 +794,57 @@ impl<F: FieldExt> EventTableConfig<F> {
             ]
         });
 
+        {
+            let trapped = |meta: &mut VirtualCells<'_, F>| {
+                let mut acc = constant_from!(0);
+
+                for (op, (lvl1, lvl2)) in op_bitmaps.iter() {
+                    let config = op_configs.get(op).unwrap();
+                    match config.is_trap(meta) {
+                        Some(e) => acc = acc + e * common_config.op_enabled(meta, *lvl1, *lvl2),
+                        _ => {}
+                    }
+                }
+
+                acc
+            };
+
+            meta.create_gate("etable trap is the last step", |meta| {
+                vec![
+                    trapped(meta)
+                        * common_config.next_enable(meta)
+                        * common_config.enabled_block(meta),
+                ]
+            });
+
+            if circuit_configure.trap_mode {
+                let trap_point = meta.instance_column();
+
+                meta.enable_equality(common_config.shared_bits[0]);
+
+                meta.create_gate("etable trap termination", |meta| {
+                    vec![
+                        (constant_from!(1) - common_config.next_enable(meta))
+                            * (constant_from!(1) - trapped(meta))
+                            * common_config.enabled_block(meta),
+                    ]
+                });
+
+                meta.lookup_any("etable trap point", |meta| {
+                    vec![(
+                        (constant_from!(1) - common_config.next_enable(meta))
+                            * common_config.itable_lookup(meta)
+                            * common_config.enabled_block(meta),
+                        instance_curr!(meta, trap_point),
+                    )]
+                });
+            } else {
+                meta.create_gate("etable no trap", |meta| {
+                    vec![trapped(meta) * common_config.enabled_block(meta)]
+                });
+            }
+        }
+
         meta.create_gate("etable common change", |meta| {
             let mut rest_mops_acc =
                 common_config.next_rest_mops(meta) - common_config.rest_mops(meta);
@@ -509,6 +852,10 @@ impl<F: FieldExt> EventTableConfig<F> {
                 common_config.next_rest_jops(meta) - common_config.rest_jops(meta);
             let mut input_index_acc =
                 common_config.input_index(meta) - common_config.next_input_index(meta);
+            let mut output_index_acc =
+                common_config.output_index(meta) - common_config.next_output_index(meta);
+            let mut private_input_index_acc = common_config.private_input_index(meta)
+                - common_config.next_private_input_index(meta);
             let mut external_host_call_index_acc = common_config.external_host_call_index(meta)
                 - common_config.next_external_host_call_index(meta);
             let mut fid_acc = common_config.next_fid(meta) - common_config.fid(meta);
@@ -517,6 +864,8 @@ impl<F: FieldExt> EventTableConfig<F> {
             let mut sp_acc = common_config.next_sp(meta) - common_config.sp(meta);
             let mut last_jump_eid_acc =
                 common_config.next_last_jump_eid(meta) - common_config.last_jump_eid(meta);
+            let mut callee_fid_acc =
+                common_config.next_callee_fid(meta) - common_config.callee_fid(meta);
             let mut allocated_memory_pages_acc = common_config.next_allocated_memory_pages(meta)
                 - common_config.allocated_memory_pages(meta);
 
@@ -559,6 +908,15 @@ impl<F: FieldExt> EventTableConfig<F> {
                     _ => {}
                 }
 
+                match config.next_callee_fid(meta, &common_config) {
+                    Some(e) => {
+                        callee_fid_acc = callee_fid_acc
+                            - (e - common_config.callee_fid(meta))
+                                * common_config.op_enabled(meta, *lvl1, *lvl2)
+                    }
+                    _ => {}
+                }
+
                 match config.next_fid(meta, &common_config) {
                     Some(e) => {
                         fid_acc = fid_acc
@@ -621,6 +979,22 @@ impl<F: FieldExt> EventTableConfig<F> {
                     _ => {}
                 }
 
+                match config.output_index_increase(meta, &common_config) {
+                    Some(e) => {
+                        output_index_acc =
+                            output_index_acc + common_config.op_enabled(meta, *lvl1, *lvl2) * e
+                    }
+                    _ => {}
+                }
+
+                match config.private_input_index_increase(meta, &common_config) {
+                    Some(e) => {
+                        private_input_index_acc = private_input_index_acc
+                            + common_config.op_enabled(meta, *lvl1, *lvl2) * e
+                    }
+                    _ => {}
+                }
+
                 if config.external_host_call_index_increase(meta, &common_config) {
                     external_host_call_index_acc =
                         external_host_call_index_acc + common_config.op_enabled(meta, *lvl1, *lvl2)
@@ -648,11 +1022,14 @@ impl<F: FieldExt> EventTableConfig<F> {
                     iid_acc * common_config.next_enable(meta),
                     sp_acc * common_config.next_enable(meta),
                     last_jump_eid_acc,
+                    callee_fid_acc * common_config.next_enable(meta),
                     allocated_memory_pages_acc * common_config.next_enable(meta),
                     itable_lookup,
                     brtable_lookup,
                     jtable_lookup,
                     input_index_acc * common_config.next_enable(meta),
+                    output_index_acc * common_config.next_enable(meta),
+                    private_input_index_acc * common_config.next_enable(meta),
                     external_host_call_index_acc * common_config.next_enable(meta),
                 ],
                 mtable_lookup,
@@ -667,11 +1044,13 @@ impl<F: FieldExt> EventTableConfig<F> {
             let mut acc_lvl1 = constant_from!(1);
             let mut acc_lvl2 = constant_from!(1);
 
-            for i in 0..MAX_OP_LVL1 {
+            let lvl = (step_size >> 1) as i32;
+
+            for i in 0..lvl {
                 acc_lvl1 = acc_lvl1 - nextn!(meta, common_config.opcode_bits, i);
             }
 
-            for i in MAX_OP_LVL1..ETABLE_STEP_SIZE as i32 {
+            for i in lvl..lvl * 2 {
                 acc_lvl2 = acc_lvl2 - nextn!(meta, common_config.opcode_bits, i);
             }
 
@@ -684,17 +1063,27 @@ impl<F: FieldExt> EventTableConfig<F> {
         Self {
             common_config,
             op_configs,
+            op_bitmaps,
         }
     }
+
+    // The rows of a step, see EventTableColumnUsage::step_size.
+    pub fn step_size(&self) -> usize {
+        self.common_config.step_size
+    }
 }
 
 pub struct EventTableChip<F: FieldExt> {
     config: EventTableConfig<F>,
+    max_available_rows: usize,
 }
 
 impl<F: FieldExt> EventTableChip<F> {
-    pub fn new(config: EventTableConfig<F>) -> Self {
-        EventTableChip { config }
+    pub fn new(config: EventTableConfig<F>, max_available_rows: usize) -> Self {
+        EventTableChip {
+            config,
+            max_available_rows,
+        }
     }
 
     pub(super) fn assign(
@@ -702,9 +1091,18 @@ impl<F: FieldExt> EventTableChip<F> {
         ctx: &mut Context<'_, F>,
         etable: &EventTable,
         configure: ConfigureTable,
-    ) -> Result<(Option<Cell>, Option<Cell>), Error> {
-        self.config
-            .common_config
-            .assign(ctx, &self.config.op_configs, etable, configure)
+        table_layouts: &[TableLayout],
+        callee_fids: &[u32],
+    ) -> Result<(Option<Cell>, Option<Cell>, Option<Cell>), Error> {
+        self.config.common_config.assign(
+            ctx,
+            &self.config.op_configs,
+            &self.config.op_bitmaps,
+            etable,
+            configure,
+            table_layouts,
+            callee_fids,
+            self.max_available_rows,
+        )
     }
 }
@@ -0,0 +1,87 @@
+use super::*;
+use crate::circuits::utils::Context;
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Error, Expression, VirtualCells},
+};
+
+/*
+ * The u64 blocks touched by the bytes [start, start + len), i.e.
+ *     start_block * 8 + start_inner_offset = start
+ *     end_block * 8 + end_inner_offset = start + len - 1
+ * end_* are only constrained if len is not zero.
+ */
+#[derive(Clone, Copy)]
+pub(crate) struct BlockRangeCell {
+    start_block: CommonRangeCell,
+    start_inner_offset: CommonRangeCell,
+    start_inner_offset_helper: CommonRangeCell,
+    end_block: CommonRangeCell,
+    end_inner_offset: CommonRangeCell,
+    end_inner_offset_helper: CommonRangeCell,
+}
+
+impl BlockRangeCell {
+    pub(crate) fn alloc<F: FieldExt>(common: &mut EventTableCellAllocator<F>) -> Self {
+        Self {
+            start_block: common.alloc_common_range_value(),
+            start_inner_offset: common.alloc_common_range_value(),
+            start_inner_offset_helper: common.alloc_common_range_value(),
+            end_block: common.alloc_common_range_value(),
+            end_inner_offset: common.alloc_common_range_value(),
+            end_inner_offset_helper: common.alloc_common_range_value(),
+        }
+    }
+
+    pub(crate) fn constraints<F: FieldExt>(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        start: Expression<F>,
+        len: Expression<F>,
+        is_not_empty: Expression<F>,
+    ) -> Vec<Expression<F>> {
+        vec![
+            self.start_inner_offset.expr(meta) + self.start_inner_offset_helper.expr(meta)
+                - constant_from!(7),
+            self.end_inner_offset.expr(meta) + self.end_inner_offset_helper.expr(meta)
+                - constant_from!(7),
+            self.start_block.expr(meta) * constant_from!(8) + self.start_inner_offset.expr(meta)
+                - start.clone(),
+            is_not_empty
+                * (self.end_block.expr(meta) * constant_from!(8)
+                    + self.end_inner_offset.expr(meta)
+                    + constant_from!(1)
+                    - start
+                    - len),
+        ]
+    }
+
+    pub(crate) fn blocks<F: FieldExt>(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        is_not_empty: Expression<F>,
+    ) -> Expression<F> {
+        is_not_empty * (self.end_block.expr(meta) - self.start_block.expr(meta) + constant_from!(1))
+    }
+
+    pub(crate) fn assign<F: FieldExt>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        start: u64,
+        len: u64,
+    ) -> Result<(), Error> {
+        self.start_block.assign(ctx, F::from(start / 8))?;
+        self.start_inner_offset.assign(ctx, F::from(start % 8))?;
+        self.start_inner_offset_helper
+            .assign(ctx, F::from(7 - start % 8))?;
+
+        let end = if len == 0 { start } else { start + len - 1 };
+
+        self.end_block.assign(ctx, F::from(end / 8))?;
+        self.end_inner_offset.assign(ctx, F::from(end % 8))?;
+        self.end_inner_offset_helper
+            .assign(ctx, F::from(7 - end % 8))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,312 @@
+use super::*;
+use crate::circuits::{
+    keep_table::{KeepTableEncode, KEEP_TABLE_KEY},
+    mtable_compact::encode::MemoryTableLookupEncode,
+    utils::Context,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Error, Expression, VirtualCells},
+};
+use specs::{etable::EventTableEntry, mtable::VarType, step::StepInfo};
+
+/*
+ * Values kept by a branch, return or tail call, listed from the top of the stack.
+ * `start` is the number of operands popped before them plus one, so the i-th
+ * kept value is read at sp + start + i with emid start + i, and is written at
+ * sp + start + drop + i after all the reads.
+ * Each kept value of the step costs two mtable lookups, which bounds max_keep,
+ * see OpcodeClass::max_keep. The values kept beyond the slots of the step are
+ * moved in the keep table.
+ */
+pub(super) struct KeepValuesCell {
+    is_keep: Vec<BitCell>,
+    is_i64: Vec<BitCell>,
+    values: Vec<UnlimitedCell>,
+    // The number of values moved in the keep table.
+    moved: CommonRangeCell,
+    is_moved: BitCell,
+    lookup_stack_read: Vec<MTableLookupCell>,
+    lookup_stack_write: Vec<MTableLookupCell>,
+}
+
+impl KeepValuesCell {
+    pub(super) fn configure<F: FieldExt>(
+        common: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+        max_keep: usize,
+        start: u64,
+        drop: CommonRangeCell,
+        enable: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F> + 'static,
+    ) -> Self {
+        let is_keep = (0..max_keep)
+            .map(|_| common.alloc_bit_value())
+            .collect::<Vec<_>>();
+        let is_i64 = (0..max_keep)
+            .map(|_| common.alloc_bit_value())
+            .collect::<Vec<_>>();
+        let values = (0..max_keep)
+            .map(|_| common.alloc_unlimited_value())
+            .collect::<Vec<_>>();
+        let moved = common.alloc_common_range_value();
+        let is_moved = common.alloc_bit_value();
+        let lookup_stack_read = (0..max_keep)
+            .map(|_| common.alloc_mtable_lookup())
+            .collect::<Vec<_>>();
+        let lookup_stack_write = (0..max_keep)
+            .map(|_| common.alloc_mtable_lookup())
+            .collect::<Vec<_>>();
+
+        {
+            let is_keep = is_keep.clone();
+            let is_i64 = is_i64.clone();
+
+            constraint_builder.push(
+                "keep values",
+                Box::new(move |meta| {
+                    let mut constraints = vec![];
+
+                    for i in 0..max_keep {
+                        // The kept values are a prefix of the slots.
+                        if i + 1 < max_keep {
+                            constraints.push(
+                                is_keep[i + 1].expr(meta)
+                                    * (constant_from!(1) - is_keep[i].expr(meta)),
+                            );
+                        }
+
+                        constraints.push(
+                            is_i64[i].expr(meta) * (constant_from!(1) - is_keep[i].expr(meta)),
+                        );
+                    }
+
+                    // The values are only moved once the slots are full.
+                    constraints.push(moved.expr(meta) * (constant_from!(1) - is_moved.expr(meta)));
+                    constraints.push(
+                        is_moved.expr(meta)
+                            * (constant_from!(1) - is_keep[max_keep - 1].expr(meta)),
+                    );
+
+                    constraints
+                }),
+            );
+        }
+
+        {
+            let eid = common.eid_cell();
+            let sp = common.sp_cell();
+            let is_keep = is_keep.clone();
+
+            constraint_builder.lookup(
+                KEEP_TABLE_KEY,
+                "keep values: keep table",
+                Box::new(move |meta| {
+                    let keep = is_keep
+                        .iter()
+                        .fold(moved.expr(meta), |acc, is_keep| acc + is_keep.expr(meta));
+
+                    enable(meta)
+                        * is_moved.expr(meta)
+                        * KeepTableEncode::encode_for_lookup(
+                            eid.expr(meta),
+                            constant_from!(start + max_keep as u64),
+                            sp.expr(meta) + constant_from!(start + max_keep as u64),
+                            drop.expr(meta),
+                            keep,
+                            moved.expr(meta),
+                        )
+                }),
+            );
+        }
+
+        KeepValuesCell {
+            is_keep,
+            is_i64,
+            values,
+            moved,
+            is_moved,
+            lookup_stack_read,
+            lookup_stack_write,
+        }
+    }
+
+    pub(super) fn keep<F: FieldExt>(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        self.is_keep
+            .iter()
+            .fold(self.moved.expr(meta), |acc, is_keep| {
+                acc + is_keep.expr(meta)
+            })
+    }
+
+    /*
+     * Matches specs::encode::opcode::encode_keep_types, the types of the moved
+     * values are not encoded.
+     */
+    pub(super) fn keep_types<F: FieldExt>(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        self.is_i64
+            .iter()
+            .enumerate()
+            .fold(constant_from!(0), |acc, (i, is_i64)| {
+                acc + is_i64.expr(meta) * constant_from!(1u64 << i)
+            })
+    }
+
+    pub(super) fn mops<F: FieldExt>(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        constant_from!(2) * self.keep(meta)
+    }
+
+    fn vtype<F: FieldExt>(&self, meta: &mut VirtualCells<'_, F>, i: usize) -> Expression<F> {
+        constant_from!(VarType::I32 as u64) + self.is_i64[i].expr(meta)
+    }
+
+    /*
+     * The first max_keep lookup indexes are the reads, the next max_keep are the writes.
+     */
+    pub(super) fn mtable_lookup<F: FieldExt>(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        index: usize,
+        common_config: &EventTableCommonConfig<F>,
+        enable: Expression<F>,
+        start: u64,
+        drop: Expression<F>,
+    ) -> Option<Expression<F>> {
+        let max_keep = self.is_keep.len();
+
+        if index < max_keep {
+            let i = index as u64;
+
+            Some(
+                enable
+                    * self.is_keep[index].expr(meta)
+                    * MemoryTableLookupEncode::encode_stack_read(
+                        common_config.eid(meta),
+                        constant_from!(start + i),
+                        common_config.sp(meta) + constant_from!(start + i),
+                        self.vtype(meta, index),
+                        self.values[index].expr(meta),
+                    ),
+            )
+        } else if index < 2 * max_keep {
+            let i = (index - max_keep) as u64;
+
+            Some(
+                enable
+                    * self.is_keep[index - max_keep].expr(meta)
+                    * MemoryTableLookupEncode::encode_stack_write(
+                        common_config.eid(meta),
+                        constant_from!(start + i) + self.keep(meta),
+                        common_config.sp(meta) + constant_from!(start + i) + drop,
+                        self.vtype(meta, index - max_keep),
+                        self.values[index - max_keep].expr(meta),
+                    ),
+            )
+        } else {
+            None
+        }
+    }
+
+    pub(super) fn assign<F: FieldExt>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        entry: &EventTableEntry,
+        enable: bool,
+        start: u64,
+    ) -> Result<(), Error> {
+        let (drop, keep, keep_values) = match &entry.step_info {
+            StepInfo::Br {
+                drop,
+                keep,
+                keep_values,
+                ..
+            }
+            | StepInfo::BrIfEqz {
+                drop,
+                keep,
+                keep_values,
+                ..
+            }
+            | StepInfo::BrIfNez {
+                drop,
+                keep,
+                keep_values,
+                ..
+            }
+            | StepInfo::BrTable {
+                drop,
+                keep,
+                keep_values,
+                ..
+            }
+            | StepInfo::Return {
+                drop,
+                keep,
+                keep_values,
+                ..
+            }
+            | StepInfo::ReturnCall {
+                drop,
+                keep,
+                keep_values,
+                ..
+            }
+            | StepInfo::ReturnCallIndirect {
+                drop,
+                keep,
+                keep_values,
+                ..
+            } => (*drop as u64, keep, keep_values),
+            _ => unreachable!(),
+        };
+
+        assert_eq!(keep.len(), keep_values.len());
+
+        let max_keep = self.is_keep.len();
+        let moved = keep.len().saturating_sub(max_keep);
+
+        self.moved.assign(ctx, F::from(moved as u64))?;
+        self.is_moved.assign(ctx, moved > 0)?;
+
+        for (i, (vtype, value)) in keep
+            .iter()
+            .zip(keep_values.iter())
+            .take(max_keep)
+            .enumerate()
+        {
+            let vtype = VarType::from(*vtype);
+
+            self.is_keep[i].assign(ctx, true)?;
+            self.is_i64[i].assign(ctx, vtype == VarType::I64)?;
+            self.values[i].assign(ctx, F::from(*value))?;
+
+            if enable {
+                let emid = start + i as u64;
+                let offset = entry.sp as u64 + start + i as u64;
+
+                self.lookup_stack_read[i].assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_stack_read(
+                        BigUint::from(entry.eid),
+                        BigUint::from(emid),
+                        BigUint::from(offset),
+                        BigUint::from(vtype as u16),
+                        BigUint::from(*value),
+                    ),
+                )?;
+
+                self.lookup_stack_write[i].assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_stack_write(
+                        BigUint::from(entry.eid),
+                        BigUint::from(emid + keep.len() as u64),
+                        BigUint::from(offset + drop),
+                        BigUint::from(vtype as u16),
+                        BigUint::from(*value),
+                    ),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,8 +1,13 @@
-use crate::circuits::{config::POW_TABLE_LIMIT, rtable::offset_len_bits_encode};
+use crate::circuits::{
+    config::POW_TABLE_LIMIT,
+    rtable::{common_range_high_limb, offset_len_bits_encode},
+};
 
 use super::*;
 use halo2_proofs::{arithmetic::FieldExt, plonk::ConstraintSystem};
 
+pub(crate) mod bulk_memory;
+pub(super) mod keep_values;
 pub(super) mod op_bin;
 pub(super) mod op_bin_bit;
 pub(super) mod op_bin_shift;
@@ -15,6 +20,7 @@ pub(super) mod op_call_host_foreign_circuit;
 pub(super) mod op_call_indirect;
 pub(super) mod op_const;
 pub(super) mod op_conversion;
+pub(super) mod op_data_drop;
 pub(super) mod op_drop;
 pub(super) mod op_global_get;
 pub(super) mod op_global_set;
@@ -22,14 +28,25 @@ pub(super) mod op_load;
 pub(super) mod op_local_get;
 pub(super) mod op_local_set;
 pub(super) mod op_local_tee;
+pub(super) mod op_memory_copy;
+pub(super) mod op_memory_fill;
 pub(super) mod op_memory_grow;
+pub(super) mod op_memory_init;
 pub(super) mod op_memory_size;
 pub(super) mod op_rel;
 pub(super) mod op_return;
+pub(super) mod op_return_call;
+pub(super) mod op_return_call_indirect;
 pub(crate) mod op_select;
 pub(super) mod op_store;
+pub(super) mod op_table_get;
+pub(super) mod op_table_grow;
+pub(super) mod op_table_set;
+pub(super) mod op_table_size;
 pub(super) mod op_test;
 pub(crate) mod op_unary;
+pub(super) mod op_unreachable;
+pub(super) mod table;
 
 // TODO: replace repeated code with macro
 
@@ -236,6 +253,7 @@ impl BitCell {
 #[derive(Clone, Copy)]
 pub struct CommonRangeCell {
     pub col: Column<Advice>,
+    pub high_col: Column<Advice>,
     pub rot: i32,
 }
 
@@ -247,6 +265,12 @@ impl CommonRangeCell {
             (ctx.offset as i32 + self.rot) as usize,
             || Ok(value),
         )?;
+        ctx.region.assign_advice(
+            || "common range cell high limb",
+            self.high_col,
+            (ctx.offset as i32 + self.rot) as usize,
+            || Ok(common_range_high_limb(&value)),
+        )?;
         Ok(())
     }
 
@@ -418,32 +442,44 @@ impl<'a, F: FieldExt> EventTableCellAllocator<'a, F> {
         }
     }
 
+    pub(super) fn usage(&self) -> EventTableColumnUsage {
+        EventTableColumnUsage {
+            bits: self.bit_index as usize,
+            common_range: self.common_range_index as usize,
+            unlimited: self.unlimited_index as usize,
+            u4_bop: self.u4_bop_index > 0,
+            u64: self.u64_index as usize,
+            u64_on_u8: self.u64_on_u8_index as usize,
+        }
+    }
+
     pub fn alloc_bit_value(&mut self) -> BitCell {
-        assert!(self.bit_index < BITS_COLUMNS as i32 * ETABLE_STEP_SIZE as i32);
+        assert!(self.bit_index < (self.config.shared_bits.len() * self.config.step_size) as i32);
         let allocated_index = self.bit_index;
         self.bit_index += 1;
         BitCell {
-            col: self.config.shared_bits[allocated_index as usize / ETABLE_STEP_SIZE as usize],
-            rot: allocated_index % ETABLE_STEP_SIZE as i32,
+            col: self.config.shared_bits[allocated_index as usize / self.config.step_size],
+            rot: allocated_index % self.config.step_size as i32,
         }
     }
 
     pub fn alloc_common_range_value(&mut self) -> CommonRangeCell {
-        assert!(self.common_range_index < ETABLE_STEP_SIZE as i32);
+        assert!(self.common_range_index < self.config.step_size as i32);
         let allocated_index = self.common_range_index;
         self.common_range_index += 1;
         CommonRangeCell {
             col: self.config.common_range,
+            high_col: self.config.common_range_high,
             rot: allocated_index,
         }
     }
 
     pub fn alloc_unlimited_value(&mut self) -> UnlimitedCell {
-        assert!(self.unlimited_index < ETABLE_STEP_SIZE as i32);
+        assert!(self.unlimited_index < self.config.step_size as i32);
         let allocated_index = self.unlimited_index;
         self.unlimited_index += 1;
         UnlimitedCell {
-            col: self.config.unlimited,
+            col: self.config.unlimited.unwrap(),
             rot: allocated_index,
         }
     }
@@ -452,12 +488,12 @@ impl<'a, F: FieldExt> EventTableCellAllocator<'a, F> {
         assert!(self.u4_bop_index < 1 as i32);
         self.u4_bop_index += 1;
         U4BopCell {
-            col: self.config.u4_bop,
+            col: self.config.u4_bop.unwrap(),
         }
     }
 
     pub fn alloc_u64(&mut self) -> U64Cell {
-        assert!(self.u64_index < U4_COLUMNS as i32);
+        assert!(self.u64_index < self.config.u4_shared.len() as i32);
         let allocated_index = self.u64_index;
         self.u64_index += 1;
         U64Cell {
@@ -468,14 +504,14 @@ impl<'a, F: FieldExt> EventTableCellAllocator<'a, F> {
     }
 
     pub fn alloc_u64_on_u8(&mut self) -> U64OnU8Cell {
-        assert!(self.u64_on_u8_index < U8_COLUMNS as i32 * 2);
+        assert!(self.u64_on_u8_index < self.config.u8_shared.len() as i32 * 2);
         let allocated_index = self.u64_on_u8_index;
         self.u64_on_u8_index += 1;
         U64OnU8Cell {
             value_col: self.config.aux,
             value_rot: allocated_index
                 + EventTableUnlimitColumnRotation::U64Start as i32
-                + U4_COLUMNS as i32,
+                + self.config.u4_shared.len() as i32,
             u8_col: self.config.u8_shared[allocated_index as usize / 2],
             u8_rot: (allocated_index % 2) * 8,
         }
@@ -556,20 +592,55 @@ impl<'a, F: FieldExt> EventTableCellAllocator<'a, F> {
     pub fn input_index_cell(&self) -> CommonRangeCell {
         CommonRangeCell {
             col: self.config.state.clone(),
+            high_col: self.config.state_high.clone(),
             rot: EventTableCommonRangeColumnRotation::InputIndex as i32,
         }
     }
 
+    pub fn output_index_cell(&self) -> CommonRangeCell {
+        CommonRangeCell {
+            col: self.config.state.clone(),
+            high_col: self.config.state_high.clone(),
+            rot: EventTableCommonRangeColumnRotation::OutputIndex as i32,
+        }
+    }
+
+    pub fn private_input_index_cell(&self) -> CommonRangeCell {
+        CommonRangeCell {
+            col: self.config.state.clone(),
+            high_col: self.config.state_high.clone(),
+            rot: EventTableCommonRangeColumnRotation::PrivateInputIndex as i32,
+        }
+    }
+
     pub fn external_host_index_cell(&self) -> CommonRangeCell {
         CommonRangeCell {
             col: self.config.state.clone(),
+            high_col: self.config.state_high.clone(),
             rot: EventTableCommonRangeColumnRotation::ExternalHostCallIndex as i32,
         }
     }
 
+    pub fn eid_cell(&self) -> CommonRangeCell {
+        CommonRangeCell {
+            col: self.config.state.clone(),
+            high_col: self.config.state_high.clone(),
+            rot: EventTableCommonRangeColumnRotation::EID as i32,
+        }
+    }
+
+    pub fn sp_cell(&self) -> CommonRangeCell {
+        CommonRangeCell {
+            col: self.config.state.clone(),
+            high_col: self.config.state_high.clone(),
+            rot: EventTableCommonRangeColumnRotation::SP as i32,
+        }
+    }
+
     pub fn allocated_memory_pages_cell(&self) -> CommonRangeCell {
         CommonRangeCell {
             col: self.config.state.clone(),
+            high_col: self.config.state_high.clone(),
             rot: EventTableCommonRangeColumnRotation::AllocatedMemoryPages as i32,
         }
     }
@@ -701,6 +772,13 @@ pub trait EventTableOpcodeConfig<F: FieldExt> {
     ) -> Option<Expression<F>> {
         None
     }
+    fn next_callee_fid(
+        &self,
+        _meta: &mut VirtualCells<'_, F>,
+        _common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        None
+    }
     fn next_fid(
         &self,
         _meta: &mut VirtualCells<'_, F>,
@@ -754,6 +832,27 @@ pub trait EventTableOpcodeConfig<F: FieldExt> {
     fn is_host_public_input(&self, _step: &StepStatus, _entry: &EventTableEntry) -> bool {
         false
     }
+    fn output_index_increase(
+        &self,
+        _meta: &mut VirtualCells<'_, F>,
+        _common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        None
+    }
+    fn is_host_public_output(&self, _step: &StepStatus, _entry: &EventTableEntry) -> bool {
+        false
+    }
+    fn private_input_index_increase(
+        &self,
+        _meta: &mut VirtualCells<'_, F>,
+        _common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        None
+    }
+    // The number of private inputs consumed by the step.
+    fn host_private_inputs(&self, _step: &StepStatus, _entry: &EventTableEntry) -> u64 {
+        0
+    }
 
     fn external_host_call_index_increase(
         &self,
@@ -762,4 +861,12 @@ pub trait EventTableOpcodeConfig<F: FieldExt> {
     ) -> bool {
         false
     }
+
+    /*
+     * Whether the step traps. A trapping step must be the last step, its accesses
+     * are counted by assigned_extra_mops only.
+     */
+    fn is_trap(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        None
+    }
 }
@@ -50,6 +50,12 @@ pub struct BinConfig {
     is_rem_s: BitCell,
     is_64bits: BitCell,
 
+    is_trap: BitCell,
+    rhs_is_zero: BitCell,
+    rhs_inv: UnlimitedCell,
+    div_s_overflow: BitCell,
+    div_s_overflow_inv: UnlimitedCell,
+
     lookup_stack_read_lhs: MTableLookupCell,
     lookup_stack_read_rhs: MTableLookupCell,
     lookup_stack_write: MTableLookupCell,
@@ -92,6 +98,12 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BinConfigBuilder {
 
         let is_64bits = common.alloc_bit_value();
 
+        let is_trap = common.alloc_bit_value();
+        let rhs_is_zero = common.alloc_bit_value();
+        let rhs_inv = common.alloc_unlimited_value();
+        let div_s_overflow = common.alloc_bit_value();
+        let div_s_overflow_inv = common.alloc_unlimited_value();
+
         constraint_builder.push(
             "binop: selector",
             Box::new(move |meta| {
@@ -157,6 +169,9 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BinConfigBuilder {
                     (res.expr(meta) - aux1.expr(meta)) * is_div_u.expr(meta),
                     (res.expr(meta) - aux2.expr(meta)) * is_rem_u.expr(meta),
                 ]
+                .into_iter()
+                .map(|x| x * (constant_from!(1) - is_trap.expr(meta)))
+                .collect()
             }),
         );
 
@@ -202,7 +217,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BinConfigBuilder {
                     aux1.expr(meta) + aux2.expr(meta) + constant_from!(1) - normalized_rhs,
                 ]
                 .into_iter()
-                .map(|x| x * enable.clone())
+                .map(|x| x * enable.clone() * (constant_from!(1) - is_trap.expr(meta)))
                 .collect()
             }),
         );
@@ -226,6 +241,9 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BinConfigBuilder {
                         * res_flag.clone()
                         * is_div_s.expr(meta),
                 ]
+                .into_iter()
+                .map(|x| x * (constant_from!(1) - is_trap.expr(meta)))
+                .collect()
             }),
         );
 
@@ -245,6 +263,59 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BinConfigBuilder {
                         * lhs_flag.expr(meta)
                         * is_rem_s.expr(meta),
                 ]
+                .into_iter()
+                .map(|x| x * (constant_from!(1) - is_trap.expr(meta)))
+                .collect()
+            }),
+        );
+
+        constraint_builder.push(
+            "binop: rhs is zero",
+            Box::new(move |meta| {
+                vec![
+                    rhs_is_zero.expr(meta) * rhs.expr(meta),
+                    rhs.expr(meta) * rhs_inv.expr(meta) - constant_from!(1)
+                        + rhs_is_zero.expr(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "binop: div_s overflow",
+            Box::new(move |meta| {
+                let modulus = constant!(bn_to_field(&(BigUint::from(1u64) << 32usize)))
+                    + constant!(bn_to_field(&(BigUint::from((u32::MAX as u64) << 32usize))))
+                        * is_64bits.expr(meta);
+                let min = constant!(bn_to_field(&(BigUint::from(1u64) << 31usize)))
+                    + constant!(bn_to_field(
+                        &((BigUint::from(1u64) << 63usize) - (BigUint::from(1u64) << 31usize))
+                    )) * is_64bits.expr(meta);
+
+                // Zero iff lhs is the minimal signed value and rhs is -1
+                let diff = lhs.expr(meta) - min
+                    + (modulus - constant_from!(1) - rhs.expr(meta))
+                        * constant!(bn_to_field(&(BigUint::from(1u64) << 65usize)));
+
+                vec![
+                    div_s_overflow.expr(meta) * diff.clone(),
+                    diff * div_s_overflow_inv.expr(meta) - constant_from!(1)
+                        + div_s_overflow.expr(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "binop: trap",
+            Box::new(move |meta| {
+                vec![
+                    is_trap.expr(meta)
+                        - (is_div_u.expr(meta)
+                            + is_rem_u.expr(meta)
+                            + is_div_s.expr(meta)
+                            + is_rem_s.expr(meta))
+                            * rhs_is_zero.expr(meta)
+                        - is_div_s.expr(meta) * div_s_overflow.expr(meta),
+                ]
             }),
         );
 
@@ -276,6 +347,11 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BinConfigBuilder {
             is_div_s,
             is_rem_s,
             is_64bits,
+            is_trap,
+            rhs_is_zero,
+            rhs_inv,
+            div_s_overflow,
+            div_s_overflow_inv,
             lookup_stack_read_lhs,
             lookup_stack_read_rhs,
             lookup_stack_write,
@@ -337,7 +413,15 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BinConfig {
                 let right = *right as u32 as u64;
                 let value = *value as u32 as u64;
 
-                (class, vtype, 32, left, right, value)
+                (class, vtype, 32, left, right, Some(value))
+            }
+
+            StepInfo::I32BinOpTrap { class, left, right } => {
+                let vtype = VarType::I32;
+                let left = *left as u32 as u64;
+                let right = *right as u32 as u64;
+
+                (class, vtype, 32, left, right, None)
             }
 
             StepInfo::I64BinOp {
@@ -351,7 +435,15 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BinConfig {
                 let right = *right as u64;
                 let value = *value as u64;
 
-                (class, vtype, 64, left, right, value)
+                (class, vtype, 64, left, right, Some(value))
+            }
+
+            StepInfo::I64BinOpTrap { class, left, right } => {
+                let vtype = VarType::I64;
+                let left = *left as u64;
+                let right = *right as u64;
+
+                (class, vtype, 64, left, right, None)
             }
 
             _ => unreachable!(),
@@ -359,6 +451,66 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BinConfig {
 
         self.lhs.assign(ctx, left)?;
         self.rhs.assign(ctx, right)?;
+
+        self.rhs_is_zero.assign(ctx, right == 0)?;
+        self.rhs_inv
+            .assign(ctx, F::from(right).invert().unwrap_or(F::zero()))?;
+
+        {
+            let modulus = 1u128 << shift;
+            let min = 1u64 << (shift - 1);
+            let diff = F::from(left) - F::from(min)
+                + (bn_to_field::<F>(&BigUint::from(modulus - 1)) - F::from(right))
+                    * bn_to_field::<F>(&(BigUint::from(1u64) << 65usize));
+
+            self.div_s_overflow.assign(ctx, diff == F::zero())?;
+            self.div_s_overflow_inv
+                .assign(ctx, diff.invert().unwrap_or(F::zero()))?;
+        }
+
+        match vtype {
+            VarType::I64 => self.is_64bits.assign(ctx, true)?,
+            _ => {}
+        };
+
+        self.lookup_stack_read_lhs.assign(
+            ctx,
+            &MemoryTableLookupEncode::encode_stack_read(
+                BigUint::from(step_info.current.eid),
+                BigUint::from(1 as u64),
+                BigUint::from(step_info.current.sp + 1),
+                BigUint::from(vtype as u16),
+                BigUint::from(right),
+            ),
+        )?;
+
+        self.lookup_stack_read_rhs.assign(
+            ctx,
+            &MemoryTableLookupEncode::encode_stack_read(
+                BigUint::from(step_info.current.eid),
+                BigUint::from(2 as u64),
+                BigUint::from(step_info.current.sp + 2),
+                BigUint::from(vtype as u16),
+                BigUint::from(left),
+            ),
+        )?;
+
+        let value = match value {
+            Some(value) => value,
+            None => {
+                match class {
+                    BinOp::UnsignedDiv => self.is_div_u.assign(ctx, true)?,
+                    BinOp::UnsignedRem => self.is_rem_u.assign(ctx, true)?,
+                    BinOp::SignedDiv => self.is_div_s.assign(ctx, true)?,
+                    BinOp::SignedRem => self.is_rem_s.assign(ctx, true)?,
+                    _ => unreachable!(),
+                }
+                self.is_trap.assign(ctx, true)?;
+
+                return Ok(());
+            }
+        };
+
         self.res.assign(ctx, value)?;
 
         match class {
@@ -445,33 +597,6 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BinConfig {
             _ => {}
         }
 
-        match vtype {
-            VarType::I64 => self.is_64bits.assign(ctx, true)?,
-            _ => {}
-        };
-
-        self.lookup_stack_read_lhs.assign(
-            ctx,
-            &MemoryTableLookupEncode::encode_stack_read(
-                BigUint::from(step_info.current.eid),
-                BigUint::from(1 as u64),
-                BigUint::from(step_info.current.sp + 1),
-                BigUint::from(vtype as u16),
-                BigUint::from(right),
-            ),
-        )?;
-
-        self.lookup_stack_read_rhs.assign(
-            ctx,
-            &MemoryTableLookupEncode::encode_stack_read(
-                BigUint::from(step_info.current.eid),
-                BigUint::from(2 as u64),
-                BigUint::from(step_info.current.sp + 2),
-                BigUint::from(vtype as u16),
-                BigUint::from(left),
-            ),
-        )?;
-
         self.lookup_stack_write.assign(
             ctx,
             &MemoryTableLookupEncode::encode_stack_write(
@@ -486,8 +611,21 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BinConfig {
         Ok(())
     }
 
-    fn mops(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(constant_from!(3))
+    fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(3) - self.is_trap.expr(meta))
+    }
+
+    fn assigned_extra_mops(
+        &self,
+        _ctx: &mut Context<'_, F>,
+        _step: &StepStatus,
+        entry: &EventTableEntry,
+    ) -> u64 {
+        match &entry.step_info {
+            // Only the operands are read
+            StepInfo::I32BinOpTrap { .. } | StepInfo::I64BinOpTrap { .. } => 2,
+            _ => 0,
+        }
     }
 
     fn mtable_lookup(
@@ -512,13 +650,15 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BinConfig {
                 vtype.clone(),
                 self.lhs.expr(meta),
             )),
-            MLookupItem::Third => Some(MemoryTableLookupEncode::encode_stack_write(
-                common_config.eid(meta),
-                constant_from!(3),
-                common_config.sp(meta) + constant_from!(2),
-                vtype.clone(),
-                self.res.expr(meta),
-            )),
+            MLookupItem::Third => Some(
+                MemoryTableLookupEncode::encode_stack_write(
+                    common_config.eid(meta),
+                    constant_from!(3),
+                    common_config.sp(meta) + constant_from!(2),
+                    vtype.clone(),
+                    self.res.expr(meta),
+                ) * (constant_from!(1) - self.is_trap.expr(meta)),
+            ),
             _ => None,
         }
     }
@@ -526,11 +666,54 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BinConfig {
     fn sp_diff(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
         Some(constant!(F::one()))
     }
+
+    fn is_trap(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(self.is_trap.expr(meta))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::test::test_circuit_noexternal;
+    use crate::test::{test_circuit_noexternal, test_circuit_noexternal_patched, StepPatch};
+    use specs::{
+        itable::{BinOp, Opcode},
+        mtable::VarType,
+        step::StepInfo,
+    };
+
+    // The wasmi tracer does not emit the trapping division, add stands in.
+    fn test_circuit_bin_trap(textual_repr: &str, instruction: &str, class: BinOp, vtype: VarType) {
+        let stand_in = match vtype {
+            VarType::I32 => "(i32.add)",
+            VarType::I64 => "(i64.add)",
+        };
+
+        test_circuit_noexternal_patched(
+            textual_repr,
+            &[(instruction, stand_in)],
+            &[],
+            |_, entry| {
+                let step_info = match entry.step_info {
+                    StepInfo::I32BinOp {
+                        class: BinOp::Add,
+                        left,
+                        right,
+                        ..
+                    } => StepInfo::I32BinOpTrap { class, left, right },
+                    StepInfo::I64BinOp {
+                        class: BinOp::Add,
+                        left,
+                        right,
+                        ..
+                    } => StepInfo::I64BinOpTrap { class, left, right },
+                    _ => return StepPatch::Keep,
+                };
+
+                StepPatch::Replace(Opcode::Bin { class, vtype }, step_info)
+            },
+        )
+        .unwrap()
+    }
 
     #[test]
     fn test_bin_add() {
@@ -798,4 +981,61 @@ mod tests {
 
         test_circuit_noexternal(textual_repr).unwrap()
     }
+
+    #[test]
+    fn test_bin_div_by_zero_trap() {
+        let textual_repr = r#"
+                (module
+                    (func (export "test")
+                        (i32.const 4)
+                        (i32.const 0)
+                        (i32.div_u)
+                        (drop)
+                    )
+                   )
+                "#;
+
+        test_circuit_bin_trap(
+            textual_repr,
+            "(i32.div_u)",
+            BinOp::UnsignedDiv,
+            VarType::I32,
+        )
+    }
+
+    #[test]
+    fn test_bin_rem_by_zero_trap() {
+        let textual_repr = r#"
+                (module
+                    (func (export "test")
+                        (i64.const -4)
+                        (i64.const 0)
+                        (i64.rem_s)
+                        (drop)
+                    )
+                   )
+                "#;
+
+        test_circuit_bin_trap(textual_repr, "(i64.rem_s)", BinOp::SignedRem, VarType::I64)
+    }
+
+    #[test]
+    fn test_bin_div_s_overflow_trap() {
+        let textual_repr = r#"
+                (module
+                    (func (export "test")
+                        (i32.const 0x80000000)
+                        (i32.const -1)
+                        (i32.rem_s)
+                        (drop)
+                        (i32.const 0x80000000)
+                        (i32.const -1)
+                        (i32.div_s)
+                        (drop)
+                    )
+                   )
+                "#;
+
+        test_circuit_bin_trap(textual_repr, "(i32.div_s)", BinOp::SignedDiv, VarType::I32)
+    }
 }
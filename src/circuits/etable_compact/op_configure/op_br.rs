@@ -1,21 +1,20 @@
+use super::keep_values::KeepValuesCell;
 use super::*;
-use crate::circuits::{mtable_compact::encode::MemoryTableLookupEncode, utils::Context};
+use crate::circuits::utils::Context;
 use halo2_proofs::{
     arithmetic::FieldExt,
     plonk::{Error, Expression, VirtualCells},
 };
+use specs::encode::opcode::encode_br;
 use specs::etable::EventTableEntry;
 use specs::step::StepInfo;
-use specs::{encode::opcode::encode_br, mtable::VarType};
+
+const MAX_KEEP: usize = OpcodeClass::Br.max_keep();
 
 pub struct BrConfig {
-    keep: BitCell,
-    keep_value: U64Cell,
-    keep_type: CommonRangeCell,
+    keep: KeepValuesCell,
     drop: CommonRangeCell,
     dst_pc: CommonRangeCell,
-    lookup_stack_read_return_value: MTableLookupCell,
-    lookup_stack_write_return_value: MTableLookupCell,
 }
 
 pub struct BrConfigBuilder {}
@@ -23,25 +22,15 @@ pub struct BrConfigBuilder {}
 impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrConfigBuilder {
     fn configure(
         common: &mut EventTableCellAllocator<F>,
-        _constraint_builder: &mut ConstraintBuilder<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
     ) -> Box<dyn EventTableOpcodeConfig<F>> {
-        let keep = common.alloc_bit_value();
-        let keep_value = common.alloc_u64();
-        let keep_type = common.alloc_common_range_value();
         let drop = common.alloc_common_range_value();
+        let keep = KeepValuesCell::configure(common, constraint_builder, MAX_KEEP, 1, drop, |_| {
+            constant_from!(1)
+        });
         let dst_pc = common.alloc_common_range_value();
-        let lookup_stack_read_return_value = common.alloc_mtable_lookup();
-        let lookup_stack_write_return_value = common.alloc_mtable_lookup();
 
-        Box::new(BrConfig {
-            keep,
-            keep_value,
-            keep_type,
-            drop,
-            dst_pc,
-            lookup_stack_read_return_value,
-            lookup_stack_write_return_value,
-        })
+        Box::new(BrConfig { keep, drop, dst_pc })
     }
 }
 
@@ -49,7 +38,8 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrConfig {
     fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
         encode_br(
             self.drop.expr(meta),
-            self.keep.expr(meta),
+            self.keep.keep(meta),
+            self.keep.keep_types(meta),
             self.dst_pc.expr(meta),
         )
     }
@@ -57,50 +47,13 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrConfig {
     fn assign(
         &self,
         ctx: &mut Context<'_, F>,
-        step_info: &StepStatus,
+        _step_info: &StepStatus,
         entry: &EventTableEntry,
     ) -> Result<(), Error> {
         match &entry.step_info {
-            StepInfo::Br {
-                dst_pc,
-                drop,
-                keep,
-                keep_values,
-            } => {
-                assert!(keep.len() <= 1);
-
+            StepInfo::Br { dst_pc, drop, .. } => {
                 self.drop.assign(ctx, F::from(*drop as u64))?;
-
-                if keep.len() > 0 {
-                    let keep_type: VarType = keep[0].into();
-
-                    self.keep.assign(ctx, true)?;
-                    self.keep_value.assign(ctx, keep_values[0])?;
-                    self.keep_type.assign(ctx, F::from(keep_type as u64))?;
-
-                    self.lookup_stack_read_return_value.assign(
-                        ctx,
-                        &MemoryTableLookupEncode::encode_stack_read(
-                            BigUint::from(entry.eid),
-                            BigUint::from(1 as u64),
-                            BigUint::from(entry.sp + 1),
-                            BigUint::from(keep_type as u16),
-                            BigUint::from(keep_values[0]),
-                        ),
-                    )?;
-
-                    self.lookup_stack_write_return_value.assign(
-                        ctx,
-                        &MemoryTableLookupEncode::encode_stack_write(
-                            BigUint::from(step_info.current.eid),
-                            BigUint::from(2 as u64),
-                            BigUint::from(step_info.current.sp + 1 + drop),
-                            BigUint::from(keep_type as u16),
-                            BigUint::from(keep_values[0]),
-                        ),
-                    )?;
-                }
-
+                self.keep.assign(ctx, entry, true, 1)?;
                 self.dst_pc.assign(ctx, F::from((*dst_pc) as u64))?;
             }
             _ => unreachable!(),
@@ -110,7 +63,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrConfig {
     }
 
     fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(constant_from!(2) * self.keep.expr(meta))
+        Some(self.keep.mops(meta))
     }
 
     fn assigned_extra_mops(
@@ -120,14 +73,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrConfig {
         entry: &EventTableEntry,
     ) -> u64 {
         match &entry.step_info {
-            StepInfo::Br { keep, .. } => {
-                if keep.len() > 0 {
-                    assert!(keep.len() == 1);
-                    2
-                } else {
-                    0
-                }
-            }
+            StepInfo::Br { keep, .. } => 2 * keep.len() as u64,
             _ => unreachable!(),
         }
     }
@@ -138,31 +84,16 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrConfig {
         item: MLookupItem,
         common_config: &EventTableCommonConfig<F>,
     ) -> Option<Expression<F>> {
-        match item {
-            MLookupItem::First => Some(
-                self.keep.expr(meta)
-                    * MemoryTableLookupEncode::encode_stack_read(
-                        common_config.eid(meta),
-                        constant_from!(1),
-                        common_config.sp(meta) + constant_from!(1),
-                        self.keep_type.expr(meta),
-                        self.keep_value.expr(meta),
-                    ),
-            ),
-
-            MLookupItem::Second => Some(
-                self.keep.expr(meta)
-                    * MemoryTableLookupEncode::encode_stack_write(
-                        common_config.eid(meta),
-                        constant_from!(2),
-                        common_config.sp(meta) + constant_from!(1) + self.drop.expr(meta),
-                        self.keep_type.expr(meta),
-                        self.keep_value.expr(meta),
-                    ),
-            ),
-
-            _ => None,
-        }
+        let drop = self.drop.expr(meta);
+
+        self.keep.mtable_lookup(
+            meta,
+            item as usize,
+            common_config,
+            constant_from!(1),
+            1,
+            drop,
+        )
     }
 
     fn sp_diff(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
@@ -212,4 +143,26 @@ mod tests {
 
         test_circuit_noexternal(textual_repr).unwrap();
     }
+
+    #[test]
+    fn test_br_multi_value_ok() {
+        let textual_repr = r#"
+        (module
+            (func (export "test")
+              (block (result i32 i64 i32)
+                (i32.const 0)
+                (i32.const 1)
+                (i64.const 2)
+                (i32.const 3)
+                br 0
+              )
+              drop
+              drop
+              drop
+            )
+           )
+        "#;
+
+        test_circuit_noexternal(textual_repr).unwrap();
+    }
 }
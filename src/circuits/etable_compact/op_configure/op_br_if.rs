@@ -1,35 +1,25 @@
+use super::keep_values::KeepValuesCell;
 use super::*;
-use crate::{
-    circuits::{
-        mtable_compact::encode::MemoryTableLookupEncode,
-        utils::{bn_to_field, Context},
-    },
-    constant,
-};
+use crate::circuits::{mtable_compact::encode::MemoryTableLookupEncode, utils::Context};
 use halo2_proofs::{
     arithmetic::FieldExt,
     plonk::{Error, Expression, VirtualCells},
 };
-use specs::itable::OPCODE_ARG1_SHIFT;
+use specs::encode::opcode::encode_br_if;
+use specs::etable::EventTableEntry;
 use specs::mtable::VarType;
 use specs::step::StepInfo;
-use specs::{
-    etable::EventTableEntry,
-    itable::{OPCODE_ARG0_SHIFT, OPCODE_CLASS_SHIFT},
-};
+
+const MAX_KEEP: usize = OpcodeClass::BrIf.max_keep();
 
 pub struct BrIfConfig {
     cond: U64Cell,
     cond_inv: UnlimitedCell,
     cond_is_zero: BitCell,
-    keep: BitCell,
-    keep_value: U64Cell,
-    keep_type: CommonRangeCell,
+    keep: KeepValuesCell,
     drop: CommonRangeCell,
     dst_pc: CommonRangeCell,
     lookup_stack_read_cond: MTableLookupCell,
-    lookup_stack_read_return_value: MTableLookupCell,
-    lookup_stack_write_return_value: MTableLookupCell,
 }
 
 pub struct BrIfConfigBuilder {}
@@ -42,14 +32,13 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrIfConfigBuilder {
         let cond = common.alloc_u64();
         let cond_inv = common.alloc_unlimited_value();
         let cond_is_zero = common.alloc_bit_value();
-        let keep = common.alloc_bit_value();
-        let keep_value = common.alloc_u64();
-        let keep_type = common.alloc_common_range_value();
         let drop = common.alloc_common_range_value();
         let dst_pc = common.alloc_common_range_value();
         let lookup_stack_read_cond = common.alloc_mtable_lookup();
-        let lookup_stack_read_return_value = common.alloc_mtable_lookup();
-        let lookup_stack_write_return_value = common.alloc_mtable_lookup();
+        let keep =
+            KeepValuesCell::configure(common, constraint_builder, MAX_KEEP, 2, drop, move |meta| {
+                constant_from!(1) - cond_is_zero.expr(meta)
+            });
 
         constraint_builder.push(
             "op_br_if cond bit",
@@ -66,13 +55,9 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrIfConfigBuilder {
             cond,
             cond_inv,
             keep,
-            keep_value,
-            keep_type,
             drop,
             dst_pc,
             lookup_stack_read_cond,
-            lookup_stack_read_return_value,
-            lookup_stack_write_return_value,
             cond_is_zero,
         })
     }
@@ -80,19 +65,18 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrIfConfigBuilder {
 
 impl<F: FieldExt> EventTableOpcodeConfig<F> for BrIfConfig {
     fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
-        constant!(bn_to_field(
-            &(BigUint::from(OpcodeClass::BrIf as u64) << OPCODE_CLASS_SHIFT)
-        )) + self.drop.expr(meta)
-            * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG0_SHIFT)))
-            + self.keep.expr(meta)
-                * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG1_SHIFT)))
-            + self.dst_pc.expr(meta)
+        encode_br_if(
+            self.drop.expr(meta),
+            self.keep.keep(meta),
+            self.keep.keep_types(meta),
+            self.dst_pc.expr(meta),
+        )
     }
 
     fn assign(
         &self,
         ctx: &mut Context<'_, F>,
-        step_info: &StepStatus,
+        _step_info: &StepStatus,
         entry: &EventTableEntry,
     ) -> Result<(), Error> {
         match &entry.step_info {
@@ -100,11 +84,8 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrIfConfig {
                 condition,
                 dst_pc,
                 drop,
-                keep,
-                keep_values,
+                ..
             } => {
-                assert!(keep.len() <= 1);
-
                 let cond = *condition as u32 as u64;
 
                 self.lookup_stack_read_cond.assign(
@@ -120,37 +101,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrIfConfig {
 
                 self.drop.assign(ctx, F::from(*drop as u64))?;
 
-                if keep.len() > 0 {
-                    let keep_type: VarType = keep[0].into();
-
-                    self.keep.assign(ctx, true)?;
-                    self.keep_value.assign(ctx, keep_values[0])?;
-                    self.keep_type.assign(ctx, F::from(keep_type as u64))?;
-
-                    if *condition != 0 {
-                        self.lookup_stack_read_return_value.assign(
-                            ctx,
-                            &MemoryTableLookupEncode::encode_stack_read(
-                                BigUint::from(entry.eid),
-                                BigUint::from(2 as u64),
-                                BigUint::from(entry.sp + 2),
-                                BigUint::from(keep_type as u16),
-                                BigUint::from(keep_values[0]),
-                            ),
-                        )?;
-
-                        self.lookup_stack_write_return_value.assign(
-                            ctx,
-                            &MemoryTableLookupEncode::encode_stack_write(
-                                BigUint::from(step_info.current.eid),
-                                BigUint::from(3 as u64),
-                                BigUint::from(step_info.current.sp + 2 + drop),
-                                BigUint::from(keep_type as u16),
-                                BigUint::from(keep_values[0]),
-                            ),
-                        )?;
-                    }
-                }
+                self.keep.assign(ctx, entry, *condition != 0, 2)?;
 
                 self.cond.assign(ctx, cond)?;
                 self.cond_inv
@@ -175,9 +126,8 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrIfConfig {
             StepInfo::BrIfNez {
                 condition, keep, ..
             } => {
-                if *condition != 0 && keep.len() > 0 {
-                    assert!(keep.len() == 1);
-                    2
+                if *condition != 0 {
+                    2 * keep.len() as u64
                 } else {
                     0
                 }
@@ -189,9 +139,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrIfConfig {
     fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
         Some(
             constant_from!(1)
-                + constant_from!(2)
-                    * (constant_from!(1) - self.cond_is_zero.expr(meta))
-                    * self.keep.expr(meta),
+                + (constant_from!(1) - self.cond_is_zero.expr(meta)) * self.keep.mops(meta),
         )
     }
 
@@ -210,31 +158,13 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrIfConfig {
                 self.cond.expr(meta),
             )),
 
-            MLookupItem::Second => Some(
-                (constant_from!(1) - self.cond_is_zero.expr(meta))
-                    * self.keep.expr(meta)
-                    * MemoryTableLookupEncode::encode_stack_read(
-                        common_config.eid(meta),
-                        constant_from!(2),
-                        common_config.sp(meta) + constant_from!(2),
-                        self.keep_type.expr(meta),
-                        self.keep_value.expr(meta),
-                    ),
-            ),
-
-            MLookupItem::Third => Some(
-                (constant_from!(1) - self.cond_is_zero.expr(meta))
-                    * self.keep.expr(meta)
-                    * MemoryTableLookupEncode::encode_stack_write(
-                        common_config.eid(meta),
-                        constant_from!(3),
-                        common_config.sp(meta) + constant_from!(2) + self.drop.expr(meta),
-                        self.keep_type.expr(meta),
-                        self.keep_value.expr(meta),
-                    ),
-            ),
+            _ => {
+                let enable = (constant_from!(1) - self.cond_is_zero.expr(meta));
+                let drop = self.drop.expr(meta);
 
-            _ => None,
+                self.keep
+                    .mtable_lookup(meta, item as usize - 1, common_config, enable, 2, drop)
+            }
         }
     }
 
@@ -374,4 +304,62 @@ mod tests {
 
         test_circuit_noexternal(textual_repr).unwrap();
     }
+
+    #[test]
+    fn test_br_if_multi_value_ok() {
+        let textual_repr = r#"
+        (module
+            (func (export "test")
+              (block (result i64 i32)
+                (i64.const 0)
+                (i32.const 1)
+                (i32.const 0)
+                br_if 0
+                drop
+                drop
+                (i32.const 2)
+                (i64.const 3)
+                (i32.const 4)
+                (i32.const 1)
+                br_if 0
+                drop
+                drop
+                drop
+                (i64.const 5)
+                (i32.const 6)
+              )
+              drop
+              drop
+            )
+           )
+        "#;
+
+        test_circuit_noexternal(textual_repr).unwrap();
+    }
+
+    #[test]
+    fn test_br_if_keep_moved_ok() {
+        let textual_repr = r#"
+        (module
+            (func (export "test")
+              (block (result i32 i64 i32 i64)
+                (i32.const 0)
+                (i64.const 1)
+                (i32.const 2)
+                (i64.const 3)
+                (i32.const 0)
+                br_if 0
+                (i32.const 1)
+                br_if 0
+              )
+              drop
+              drop
+              drop
+              drop
+            )
+           )
+        "#;
+
+        test_circuit_noexternal(textual_repr).unwrap();
+    }
 }
@@ -1,3 +1,4 @@
+use super::keep_values::KeepValuesCell;
 use super::*;
 use crate::circuits::{mtable_compact::encode::MemoryTableLookupEncode, utils::Context};
 use halo2_proofs::{
@@ -9,18 +10,16 @@ use specs::etable::EventTableEntry;
 use specs::mtable::VarType;
 use specs::step::StepInfo;
 
+const MAX_KEEP: usize = OpcodeClass::BrIfEqz.max_keep();
+
 pub struct BrIfEqzConfig {
     cond: U64Cell,
     cond_inv: UnlimitedCell,
     cond_is_zero: BitCell,
-    keep: BitCell,
-    keep_value: U64Cell,
-    keep_type: CommonRangeCell,
+    keep: KeepValuesCell,
     drop: CommonRangeCell,
     dst_pc: CommonRangeCell,
     lookup_stack_read_cond: MTableLookupCell,
-    lookup_stack_read_return_value: MTableLookupCell,
-    lookup_stack_write_return_value: MTableLookupCell,
 }
 
 pub struct BrIfEqzConfigBuilder {}
@@ -33,14 +32,13 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrIfEqzConfigBuilder {
         let cond = common.alloc_u64();
         let cond_inv = common.alloc_unlimited_value();
         let cond_is_zero = common.alloc_bit_value();
-        let keep = common.alloc_bit_value();
-        let keep_value = common.alloc_u64();
-        let keep_type = common.alloc_common_range_value();
         let drop = common.alloc_common_range_value();
         let dst_pc = common.alloc_common_range_value();
         let lookup_stack_read_cond = common.alloc_mtable_lookup();
-        let lookup_stack_read_return_value = common.alloc_mtable_lookup();
-        let lookup_stack_write_return_value = common.alloc_mtable_lookup();
+        let keep =
+            KeepValuesCell::configure(common, constraint_builder, MAX_KEEP, 2, drop, move |meta| {
+                cond_is_zero.expr(meta)
+            });
 
         constraint_builder.push(
             "op_br_if_eqz cond bit",
@@ -57,13 +55,9 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrIfEqzConfigBuilder {
             cond,
             cond_inv,
             keep,
-            keep_value,
-            keep_type,
             drop,
             dst_pc,
             lookup_stack_read_cond,
-            lookup_stack_read_return_value,
-            lookup_stack_write_return_value,
             cond_is_zero,
         })
     }
@@ -73,7 +67,8 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrIfEqzConfig {
     fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
         encode_br_if_eqz(
             self.drop.expr(meta),
-            self.keep.expr(meta),
+            self.keep.keep(meta),
+            self.keep.keep_types(meta),
             self.dst_pc.expr(meta),
         )
     }
@@ -81,7 +76,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrIfEqzConfig {
     fn assign(
         &self,
         ctx: &mut Context<'_, F>,
-        step_info: &StepStatus,
+        _step_info: &StepStatus,
         entry: &EventTableEntry,
     ) -> Result<(), Error> {
         match &entry.step_info {
@@ -89,11 +84,8 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrIfEqzConfig {
                 condition,
                 dst_pc,
                 drop,
-                keep,
-                keep_values,
+                ..
             } => {
-                assert!(keep.len() <= 1);
-
                 let cond = *condition as u32 as u64;
 
                 self.lookup_stack_read_cond.assign(
@@ -109,37 +101,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrIfEqzConfig {
 
                 self.drop.assign(ctx, F::from(*drop as u64))?;
 
-                if keep.len() > 0 {
-                    let keep_type: VarType = keep[0].into();
-
-                    self.keep.assign(ctx, true)?;
-                    self.keep_value.assign(ctx, keep_values[0])?;
-                    self.keep_type.assign(ctx, F::from(keep_type as u64))?;
-
-                    if *condition == 0 {
-                        self.lookup_stack_read_return_value.assign(
-                            ctx,
-                            &MemoryTableLookupEncode::encode_stack_read(
-                                BigUint::from(entry.eid),
-                                BigUint::from(2 as u64),
-                                BigUint::from(entry.sp + 2),
-                                BigUint::from(keep_type as u16),
-                                BigUint::from(keep_values[0]),
-                            ),
-                        )?;
-
-                        self.lookup_stack_write_return_value.assign(
-                            ctx,
-                            &MemoryTableLookupEncode::encode_stack_write(
-                                BigUint::from(step_info.current.eid),
-                                BigUint::from(3 as u64),
-                                BigUint::from(step_info.current.sp + 2 + drop),
-                                BigUint::from(keep_type as u16),
-                                BigUint::from(keep_values[0]),
-                            ),
-                        )?;
-                    }
-                }
+                self.keep.assign(ctx, entry, *condition == 0, 2)?;
 
                 self.cond.assign(ctx, cond)?;
                 self.cond_inv
@@ -155,10 +117,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrIfEqzConfig {
     }
 
     fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(
-            constant_from!(1)
-                + constant_from!(2) * self.cond_is_zero.expr(meta) * self.keep.expr(meta),
-        )
+        Some(constant_from!(1) + self.cond_is_zero.expr(meta) * self.keep.mops(meta))
     }
 
     fn assigned_extra_mops(
@@ -171,9 +130,8 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrIfEqzConfig {
             StepInfo::BrIfEqz {
                 condition, keep, ..
             } => {
-                if *condition == 0 && keep.len() > 0 {
-                    assert!(keep.len() == 1);
-                    2
+                if *condition == 0 {
+                    2 * keep.len() as u64
                 } else {
                     0
                 }
@@ -197,31 +155,13 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrIfEqzConfig {
                 self.cond.expr(meta),
             )),
 
-            MLookupItem::Second => Some(
-                self.cond_is_zero.expr(meta)
-                    * self.keep.expr(meta)
-                    * MemoryTableLookupEncode::encode_stack_read(
-                        common_config.eid(meta),
-                        constant_from!(2),
-                        common_config.sp(meta) + constant_from!(2),
-                        self.keep_type.expr(meta),
-                        self.keep_value.expr(meta),
-                    ),
-            ),
-
-            MLookupItem::Third => Some(
-                self.cond_is_zero.expr(meta)
-                    * self.keep.expr(meta)
-                    * MemoryTableLookupEncode::encode_stack_write(
-                        common_config.eid(meta),
-                        constant_from!(3),
-                        common_config.sp(meta) + constant_from!(2) + self.drop.expr(meta),
-                        self.keep_type.expr(meta),
-                        self.keep_value.expr(meta),
-                    ),
-            ),
+            _ => {
+                let enable = self.cond_is_zero.expr(meta);
+                let drop = self.drop.expr(meta);
 
-            _ => None,
+                self.keep
+                    .mtable_lookup(meta, item as usize - 1, common_config, enable, 2, drop)
+            }
         }
     }
 
@@ -1,18 +1,22 @@
+use super::keep_values::KeepValuesCell;
 use super::*;
 use crate::circuits::{mtable_compact::encode::MemoryTableLookupEncode, utils::Context};
 use halo2_proofs::{
     arithmetic::FieldExt,
     plonk::{Error, Expression, VirtualCells},
 };
-use specs::encode::{br_table::encode_br_table_entry, opcode::encode_br_table};
+use specs::encode::{
+    br_table::encode_br_table_entry,
+    opcode::{encode_br_table, encode_keep_types},
+};
 use specs::etable::EventTableEntry;
 use specs::mtable::VarType;
 use specs::step::StepInfo;
 
+const MAX_KEEP: usize = OpcodeClass::BrTable.max_keep();
+
 pub struct BrTableConfig {
-    keep: BitCell,
-    keep_value: U64Cell,
-    keep_type: CommonRangeCell,
+    keep: KeepValuesCell,
     drop: CommonRangeCell,
     dst_iid: CommonRangeCell,
     expect_index: CommonRangeCell,
@@ -22,8 +26,6 @@ pub struct BrTableConfig {
     br_table_lookup: BrTableLookupCell,
     diff: U64Cell,
     lookup_stack_read_index: MTableLookupCell,
-    lookup_stack_read_return_value: MTableLookupCell,
-    lookup_stack_write_return_value: MTableLookupCell,
 }
 
 pub struct BrTableConfigBuilder {}
@@ -33,9 +35,6 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrTableConfigBuilder {
         common: &mut EventTableCellAllocator<F>,
         constraint_builder: &mut ConstraintBuilder<F>,
     ) -> Box<dyn EventTableOpcodeConfig<F>> {
-        let keep = common.alloc_bit_value();
-        let keep_value = common.alloc_u64();
-        let keep_type = common.alloc_common_range_value();
         let drop = common.alloc_common_range_value();
         let dst_iid = common.alloc_common_range_value();
 
@@ -47,8 +46,9 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrTableConfigBuilder {
 
         let br_table_lookup = common.alloc_brtable_lookup();
         let lookup_stack_read_index = common.alloc_mtable_lookup();
-        let lookup_stack_read_return_value = common.alloc_mtable_lookup();
-        let lookup_stack_write_return_value = common.alloc_mtable_lookup();
+        let keep = KeepValuesCell::configure(common, constraint_builder, MAX_KEEP, 2, drop, |_| {
+            constant_from!(1)
+        });
 
         constraint_builder.push(
             "op_brtable oob",
@@ -83,8 +83,6 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrTableConfigBuilder {
 
         Box::new(BrTableConfig {
             keep,
-            keep_value,
-            keep_type,
             drop,
             dst_iid,
             expect_index,
@@ -94,8 +92,6 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrTableConfigBuilder {
             diff,
             br_table_lookup,
             lookup_stack_read_index,
-            lookup_stack_read_return_value,
-            lookup_stack_write_return_value,
         })
     }
 }
@@ -115,7 +111,8 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrTableConfig {
             common_config.iid(meta),
             self.effective_index.expr(meta),
             self.drop.expr(meta),
-            self.keep.expr(meta),
+            self.keep.keep(meta),
+            self.keep.keep_types(meta),
             self.dst_iid.expr(meta),
         ))
     }
@@ -132,10 +129,8 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrTableConfig {
                 dst_pc,
                 drop,
                 keep,
-                keep_values,
+                ..
             } => {
-                assert!(keep.len() <= 1);
-
                 self.drop.assign(ctx, F::from(*drop as u64))?;
                 self.dst_iid.assign(ctx, F::from(*dst_pc as u64))?;
                 let index = *index as u32 as u64;
@@ -151,35 +146,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrTableConfig {
                     ),
                 )?;
 
-                if keep.len() > 0 {
-                    let keep_type: VarType = keep[0].into();
-
-                    self.keep.assign(ctx, true)?;
-                    self.keep_value.assign(ctx, keep_values[0])?;
-                    self.keep_type.assign(ctx, F::from(keep_type as u64))?;
-
-                    self.lookup_stack_read_return_value.assign(
-                        ctx,
-                        &MemoryTableLookupEncode::encode_stack_read(
-                            BigUint::from(entry.eid),
-                            BigUint::from(2 as u64),
-                            BigUint::from(entry.sp + 2),
-                            BigUint::from(keep_type as u16),
-                            BigUint::from(keep_values[0]),
-                        ),
-                    )?;
-
-                    self.lookup_stack_write_return_value.assign(
-                        ctx,
-                        &MemoryTableLookupEncode::encode_stack_write(
-                            BigUint::from(step_info.current.eid),
-                            BigUint::from(3 as u64),
-                            BigUint::from(step_info.current.sp + 2 + drop),
-                            BigUint::from(keep_type as u16),
-                            BigUint::from(keep_values[0]),
-                        ),
-                    )?;
-                }
+                self.keep.assign(ctx, entry, true, 2)?;
 
                 let targets = match &entry.inst.opcode {
                     specs::itable::Opcode::BrTable { targets } => targets.clone(),
@@ -214,6 +181,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrTableConfig {
                         BigUint::from(effective_index),
                         BigUint::from(*drop),
                         BigUint::from(keep.len()),
+                        BigUint::from(encode_keep_types(OpcodeClass::BrTable, keep)),
                         BigUint::from(*dst_pc),
                     ),
                 )?;
@@ -231,20 +199,13 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrTableConfig {
         entry: &EventTableEntry,
     ) -> u64 {
         match &entry.step_info {
-            StepInfo::BrTable { keep, .. } => {
-                if keep.len() > 0 {
-                    assert!(keep.len() == 1);
-                    2
-                } else {
-                    0
-                }
-            }
+            StepInfo::BrTable { keep, .. } => 2 * keep.len() as u64,
             _ => unreachable!(),
         }
     }
 
     fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(constant_from!(1) + constant_from!(2) * self.keep.expr(meta))
+        Some(constant_from!(1) + self.keep.mops(meta))
     }
 
     fn mtable_lookup(
@@ -261,27 +222,18 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrTableConfig {
                 constant_from!(VarType::I32 as u32 as u64),
                 self.expect_index.expr(meta),
             )),
-            MLookupItem::Second => Some(
-                self.keep.expr(meta)
-                    * MemoryTableLookupEncode::encode_stack_read(
-                        common_config.eid(meta),
-                        constant_from!(2),
-                        common_config.sp(meta) + constant_from!(2),
-                        self.keep_type.expr(meta),
-                        self.keep_value.expr(meta),
-                    ),
-            ),
-            MLookupItem::Third => Some(
-                self.keep.expr(meta)
-                    * MemoryTableLookupEncode::encode_stack_write(
-                        common_config.eid(meta),
-                        constant_from!(3),
-                        common_config.sp(meta) + constant_from!(2) + self.drop.expr(meta),
-                        self.keep_type.expr(meta),
-                        self.keep_value.expr(meta),
-                    ),
-            ),
-            _ => None,
+            _ => {
+                let drop = self.drop.expr(meta);
+
+                self.keep.mtable_lookup(
+                    meta,
+                    item as usize - 1,
+                    common_config,
+                    constant_from!(1),
+                    2,
+                    drop,
+                )
+            }
         }
     }
 
@@ -417,4 +369,27 @@ mod tests {
 
         test_circuit_noexternal(textual_repr).unwrap();
     }
+
+    #[test]
+    fn test_br_table_multi_value_ok() {
+        let textual_repr = r#"
+            (module
+              (func (export "test") (result i64 i32)
+                (block (result i64 i32)
+                  (block (result i64 i32)
+                    (i64.const 1)
+                    (i32.const 2)
+                    (br_table 0 1 (i32.const 1))
+                  )
+                  drop
+                  drop
+                  (i64.const 3)
+                  (i32.const 4)
+                )
+              )
+            )
+        "#;
+
+        test_circuit_noexternal(textual_repr).unwrap();
+    }
 }
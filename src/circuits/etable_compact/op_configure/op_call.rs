@@ -51,6 +51,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for CallConfig {
                         step_info.current.eid.to_biguint().unwrap(),
                         step_info.current.last_jump_eid.to_biguint().unwrap(),
                         (*index).to_biguint().unwrap(),
+                        step_info.current.callee_fid.to_biguint().unwrap(),
                         step_info.current.fid.to_biguint().unwrap(),
                         (step_info.current.iid + 1).to_biguint().unwrap(),
                     ),
@@ -78,6 +79,14 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for CallConfig {
         Some(common_config.eid(meta))
     }
 
+    fn next_callee_fid(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        _common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        Some(self.index.expr(meta))
+    }
+
     fn next_fid(
         &self,
         meta: &mut VirtualCells<'_, F>,
@@ -103,6 +112,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for CallConfig {
             common_config.eid(meta),
             common_config.last_jump_eid(meta),
             self.index.expr(meta),
+            common_config.callee_fid(meta),
             common_config.fid(meta),
             common_config.iid(meta) + constant_from!(1),
         ))
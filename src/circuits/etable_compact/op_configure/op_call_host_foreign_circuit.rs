@@ -1,6 +1,9 @@
 use super::*;
 use crate::{
-    circuits::{mtable_compact::encode::MemoryTableLookupEncode, utils::Context},
+    circuits::{
+        external_host_call_table::EXTERNAL_HOST_CALL_VISIBILITY_TABLE_KEY,
+        mtable_compact::encode::MemoryTableLookupEncode, utils::Context,
+    },
     constant,
 };
 
@@ -11,30 +14,42 @@ use halo2_proofs::{
 use specs::{
     encode::opcode::encode_call_host,
     etable::EventTableEntry,
-    external_host_call_table::{encode::encode_host_call_entry, ExternalHostCallSignature},
+    external_host_call_table::{
+        encode::{encode_host_call_entry, encode_host_call_visibility},
+        ExternalHostCallSignature,
+    },
     mtable::VarType,
     step::StepInfo,
 };
+use std::collections::BTreeMap;
 
 pub struct ExternalCallHostCircuitConfig {
     op: CommonRangeCell,
     value: U64Cell,
     value_is_ret: BitCell,
+    is_public: BitCell,
+    external_host_call_visibility: BTreeMap<usize, bool>,
     stack_rw_lookup: MTableLookupCell,
     external_host_call_lookup: ExternalHostCallTableLookupCell,
 }
 
 pub struct ExternalCallHostCircuitConfigBuilder {}
 
-impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ExternalCallHostCircuitConfigBuilder {
-    fn configure(
+impl ExternalCallHostCircuitConfigBuilder {
+    /*
+     * A call takes the visibility of its function from the visibility table,
+     * all calls are private if no function is public.
+     */
+    pub(in crate::circuits) fn configure<F: FieldExt>(
         common: &mut EventTableCellAllocator<F>,
         constraint_builder: &mut ConstraintBuilder<F>,
+        external_host_call_visibility: &BTreeMap<usize, bool>,
     ) -> Box<dyn EventTableOpcodeConfig<F>> {
         let index = common.external_host_index_cell();
         let op = common.alloc_common_range_value();
         let value = common.alloc_u64();
         let value_is_ret = common.alloc_bit_value();
+        let is_public = common.alloc_bit_value();
 
         let stack_rw_lookup = common.alloc_mtable_lookup();
         let external_host_call_lookup = common.alloc_external_host_call_table_lookup();
@@ -48,16 +63,37 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ExternalCallHostCircuitCo
                             index.expr(meta),
                             op.expr(meta),
                             value_is_ret.expr(meta),
+                            is_public.expr(meta),
                             value.expr(meta),
                         ),
                 ]
             }),
         );
 
+        if external_host_call_visibility
+            .values()
+            .any(|is_public| *is_public)
+        {
+            constraint_builder.lookup(
+                EXTERNAL_HOST_CALL_VISIBILITY_TABLE_KEY,
+                "external host call visibility lookup",
+                Box::new(move |meta| {
+                    encode_host_call_visibility(op.expr(meta), is_public.expr(meta))
+                }),
+            );
+        } else {
+            constraint_builder.push(
+                "external host call is private",
+                Box::new(move |meta| vec![is_public.expr(meta)]),
+            );
+        }
+
         Box::new(ExternalCallHostCircuitConfig {
             op,
             value,
             value_is_ret,
+            is_public,
+            external_host_call_visibility: external_host_call_visibility.clone(),
             stack_rw_lookup,
             external_host_call_lookup,
         })
@@ -80,12 +116,21 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ExternalCallHostCircuitConfig {
                 self.op.assign(ctx, F::from(*op as u64))?;
                 self.value.assign(ctx, value.unwrap())?;
                 self.value_is_ret.assign(ctx, sig.is_ret())?;
+
+                let is_public = self
+                    .external_host_call_visibility
+                    .get(op)
+                    .cloned()
+                    .unwrap_or(false);
+                self.is_public.assign(ctx, is_public)?;
+
                 self.external_host_call_lookup.assign(
                     ctx,
                     &encode_host_call_entry(
                         BigUint::from(step_info.current_external_host_call_index),
                         BigUint::from(*op as u64),
                         BigUint::from(sig.is_ret() as u64),
+                        BigUint::from(is_public as u64),
                         BigUint::from(value.unwrap()),
                     ),
                 )?;
@@ -190,9 +235,7 @@ mod tests {
     }
     impl ForeignContext for Context {}
 
-    #[test]
-    fn test_call_host() {
-        let textual_repr = r#"
+    const FOREIGN_PLAYGROUND_WAT: &'static str = r#"
         (module
             (type (;0;) (func (result i64)))
             (type (;1;) (func (param i64)))
@@ -211,48 +254,70 @@ mod tests {
             (export "test" (func 2)))
         "#;
 
-        let env = {
-            let mut env = HostEnv::new();
+    fn foreign_playground_env(public_push: bool) -> HostEnv {
+        let mut env = HostEnv::new();
 
-            let foreign_playground_plugin = env
-                .external_env
-                .register_plugin("foreign_playground", Box::new(Context::default()));
-            env.external_env.register_function(
+        let foreign_playground_plugin = env
+            .external_env
+            .register_plugin("foreign_playground", Box::new(Context::default()));
+
+        let push: Rc<
+            dyn Fn(&mut dyn ForeignContext, wasmi::RuntimeArgs) -> Option<wasmi::RuntimeValue>,
+        > = Rc::new(
+            |context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
+                let context = context.downcast_mut::<Context>().unwrap();
+
+                let value: u64 = args.nth(0);
+                context.acc += value;
+
+                None
+            },
+        );
+        if public_push {
+            env.external_env.register_public_function(
                 "foreign_push",
                 0,
                 ExternalHostCallSignature::Argument,
                 foreign_playground_plugin.clone(),
-                Rc::new(
-                    |context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
-                        let context = context.downcast_mut::<Context>().unwrap();
-
-                        let value: u64 = args.nth(0);
-                        context.acc += value;
-
-                        None
-                    },
-                ),
+                push,
             );
+        } else {
             env.external_env.register_function(
-                "foreign_pop",
-                1,
-                ExternalHostCallSignature::Return,
-                foreign_playground_plugin,
-                Rc::new(
-                    |context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
-                        let context = context.downcast_mut::<Context>().unwrap();
-
-                        Some(wasmi::RuntimeValue::I64(context.acc as i64))
-                    },
-                ),
+                "foreign_push",
+                0,
+                ExternalHostCallSignature::Argument,
+                foreign_playground_plugin.clone(),
+                push,
             );
+        }
+        env.external_env.register_function(
+            "foreign_pop",
+            1,
+            ExternalHostCallSignature::Return,
+            foreign_playground_plugin,
+            Rc::new(
+                |context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
+                    let context = context.downcast_mut::<Context>().unwrap();
+
+                    Some(wasmi::RuntimeValue::I64(context.acc as i64))
+                },
+            ),
+        );
 
-            env.finalize();
+        env.finalize();
 
-            env
-        };
+        env
+    }
 
-        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
-        test_circuit_with_env(env, wasm, "test", vec![]).unwrap();
+    #[test]
+    fn test_call_host() {
+        let wasm = wabt::wat2wasm(FOREIGN_PLAYGROUND_WAT).expect("failed to parse wat");
+        test_circuit_with_env(foreign_playground_env(false), wasm, "test", vec![]).unwrap();
+    }
+
+    #[test]
+    fn test_call_host_public() {
+        let wasm = wabt::wat2wasm(FOREIGN_PLAYGROUND_WAT).expect("failed to parse wat");
+        test_circuit_with_env(foreign_playground_env(true), wasm, "test", vec![]).unwrap();
     }
 }
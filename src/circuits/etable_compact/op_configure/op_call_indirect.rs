@@ -1,3 +1,4 @@
+use super::table::TableLayoutCell;
 use super::*;
 use crate::{
     circuits::{mtable_compact::encode::MemoryTableLookupEncode, utils::Context},
@@ -11,7 +12,7 @@ use halo2_proofs::{
 use num_bigint::ToBigUint;
 use specs::{encode::opcode::encode_call_indirect, mtable::VarType, step::StepInfo};
 use specs::{
-    encode::{br_table::encode_elem_entry, frame_table::encode_frame_table_entry},
+    encode::{br_table::encode_func_ref, frame_table::encode_frame_table_entry},
     etable::EventTableEntry,
 };
 
@@ -19,9 +20,10 @@ pub struct CallIndirectConfig {
     type_index: CommonRangeCell,
     func_index: CommonRangeCell,
     offset: CommonRangeCell,
-    table_index: CommonRangeCell,
+    offset_helper: CommonRangeCell,
+    table: TableLayoutCell,
     stack_read_lookup: MTableLookupCell,
-    elem_lookup: BrTableLookupCell,
+    elem_read_lookup: MTableLookupCell,
     frame_table_lookup: JTableLookupCell,
 }
 
@@ -33,27 +35,39 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for CallIndirectConfigBuilder
         constraint_builder: &mut ConstraintBuilder<F>,
     ) -> Box<dyn EventTableOpcodeConfig<F>> {
         let type_index = common.alloc_common_range_value();
-        let table_index = common.alloc_common_range_value();
         let offset = common.alloc_common_range_value();
+        let offset_helper = common.alloc_common_range_value();
         let func_index = common.alloc_common_range_value();
+        let table = TableLayoutCell::alloc(common);
 
-        let elem_lookup = common.alloc_brtable_lookup();
         let stack_read_lookup = common.alloc_mtable_lookup();
+        let elem_read_lookup = common.alloc_mtable_lookup();
         let frame_table_lookup = common.alloc_jtable_lookup();
 
-        // Wasmi only support one table.
+        let capacity = table.capacity;
+
+        /*
+         * Slots beyond the size of the table are null, so the element read fails
+         * for them without reading the size.
+         */
         constraint_builder.push(
-            "table_index",
-            Box::new(move |meta| vec![table_index.expr(meta)]),
+            "call_indirect: offset < capacity",
+            Box::new(move |meta| {
+                vec![
+                    offset.expr(meta) + offset_helper.expr(meta) + constant_from!(1)
+                        - capacity.expr(meta),
+                ]
+            }),
         );
 
         Box::new(CallIndirectConfig {
             type_index,
             func_index,
             offset,
-            table_index,
-            elem_lookup,
+            offset_helper,
+            table,
             stack_read_lookup,
+            elem_read_lookup,
             frame_table_lookup,
         })
     }
@@ -61,7 +75,8 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for CallIndirectConfigBuilder
 
 impl<F: FieldExt> EventTableOpcodeConfig<F> for CallIndirectConfig {
     fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
-        encode_call_indirect(self.type_index.expr(meta))
+        // The itable binds the table to 0, see Opcode::CallIndirect
+        encode_call_indirect(self.type_index.expr(meta), self.table.table_idx.expr(meta))
     }
 
     fn assign(
@@ -77,21 +92,15 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for CallIndirectConfig {
                 offset,
                 func_index,
             } => {
-                self.table_index.assign(ctx, F::from(*table_index as u64))?;
+                let layout = &step_info.table_layouts[*table_index as usize];
+
+                self.table.assign(ctx, layout)?;
                 self.type_index.assign(ctx, F::from(*type_index as u64))?;
                 self.offset.assign(ctx, F::from(*offset as u64))?;
+                self.offset_helper
+                    .assign(ctx, F::from((layout.capacity - 1 - *offset) as u64))?;
                 self.func_index.assign(ctx, F::from(*func_index as u64))?;
 
-                self.elem_lookup.assign(
-                    ctx,
-                    &encode_elem_entry(
-                        BigUint::from(*table_index),
-                        BigUint::from(*type_index),
-                        BigUint::from(*offset),
-                        BigUint::from(*func_index),
-                    ),
-                )?;
-
                 self.stack_read_lookup.assign(
                     ctx,
                     &MemoryTableLookupEncode::encode_stack_read(
@@ -103,12 +112,23 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for CallIndirectConfig {
                     ),
                 )?;
 
+                self.elem_read_lookup.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_table_read(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(2 as u64),
+                        BigUint::from(layout.elem_offset(*offset)),
+                        encode_func_ref(BigUint::from(*type_index), BigUint::from(*func_index)),
+                    ),
+                )?;
+
                 self.frame_table_lookup.assign(
                     ctx,
                     &encode_frame_table_entry(
                         step_info.current.eid.to_biguint().unwrap(),
                         step_info.current.last_jump_eid.to_biguint().unwrap(),
                         (*func_index).to_biguint().unwrap(),
+                        step_info.current.callee_fid.to_biguint().unwrap(),
                         step_info.current.fid.to_biguint().unwrap(),
                         (step_info.current.iid + 1).to_biguint().unwrap(),
                     ),
@@ -126,7 +146,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for CallIndirectConfig {
     }
 
     fn mops(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(constant_from!(1))
+        Some(constant_from!(2))
     }
 
     fn mtable_lookup(
@@ -143,6 +163,16 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for CallIndirectConfig {
                 constant_from!(VarType::I32),
                 self.offset.expr(meta),
             )),
+            MLookupItem::Second => {
+                let offset = self.offset.expr(meta);
+
+                Some(MemoryTableLookupEncode::encode_table_read(
+                    common_config.eid(meta),
+                    constant_from!(2),
+                    self.table.elem_offset(meta, offset),
+                    encode_func_ref(self.type_index.expr(meta), self.func_index.expr(meta)),
+                ))
+            }
             _ => None,
         }
     }
@@ -152,12 +182,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for CallIndirectConfig {
         meta: &mut VirtualCells<'_, F>,
         _common_config: &EventTableCommonConfig<F>,
     ) -> Option<Expression<F>> {
-        Some(encode_elem_entry(
-            self.table_index.expr(meta),
-            self.type_index.expr(meta),
-            self.offset.expr(meta),
-            self.func_index.expr(meta),
-        ))
+        Some(self.table.encode(meta))
     }
 
     fn jops(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
@@ -172,6 +197,14 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for CallIndirectConfig {
         Some(common_config.next_last_jump_eid(meta))
     }
 
+    fn next_callee_fid(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        _common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        Some(self.func_index.expr(meta))
+    }
+
     fn next_fid(
         &self,
         meta: &mut VirtualCells<'_, F>,
@@ -197,6 +230,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for CallIndirectConfig {
             common_config.eid(meta),
             common_config.last_jump_eid(meta),
             self.func_index.expr(meta),
+            common_config.callee_fid(meta),
             common_config.fid(meta),
             common_config.iid(meta) + constant_from!(1),
         ))
@@ -234,4 +268,33 @@ mod tests {
 
         test_circuit_noexternal(textual_repr).unwrap()
     }
+
+    #[test]
+    fn test_call_indirect_with_two_tables() {
+        let textual_repr = r#"
+            (module
+                (type (;0;) (func (param i32 i32) (result i32)))
+                (func (;0;) (type 0) (param i32 i32) (result i32)
+                    local.get 0
+                    local.get 1
+                    i32.add)
+                (func (;1;) (type 0) (param i32 i32) (result i32)
+                    local.get 0
+                    local.get 1
+                    i32.sub)
+                (func (;2;) (result i32)
+                    i32.const 1
+                    i32.const 2
+                    i32.const 1
+                    call_indirect (type 0))
+                (table (;0;) 2 2 funcref)
+                (table (;1;) 3 4 funcref)
+                (export "test" (func 2))
+                (elem (;0;) (table 0) (i32.const 0) func 0 1)
+                (elem (;1;) (table 1) (i32.const 1) func 1 0)
+            )
+        "#;
+
+        test_circuit_noexternal(textual_repr).unwrap()
+    }
 }
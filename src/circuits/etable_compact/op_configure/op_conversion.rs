@@ -21,6 +21,11 @@ pub struct ConversionConfig {
     is_i32_wrap_i64: BitCell,
     is_i64_extend_i32_u: BitCell,
     is_i64_extend_i32_s: BitCell,
+    is_i32_extend_8s: BitCell,
+    is_i32_extend_16s: BitCell,
+    is_i64_extend_8s: BitCell,
+    is_i64_extend_16s: BitCell,
+    is_i64_extend_32s: BitCell,
 
     lookup_stack_read: MTableLookupCell,
     lookup_stack_write: MTableLookupCell,
@@ -46,6 +51,11 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ConversionConfigBuilder {
         let is_i32_wrap_i64 = common.alloc_bit_value();
         let is_i64_extend_i32_u = common.alloc_bit_value();
         let is_i64_extend_i32_s = common.alloc_bit_value();
+        let is_i32_extend_8s = common.alloc_bit_value();
+        let is_i32_extend_16s = common.alloc_bit_value();
+        let is_i64_extend_8s = common.alloc_bit_value();
+        let is_i64_extend_16s = common.alloc_bit_value();
+        let is_i64_extend_32s = common.alloc_bit_value();
 
         let lookup_stack_read = common.alloc_mtable_lookup();
         let lookup_stack_write = common.alloc_mtable_lookup();
@@ -57,6 +67,11 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ConversionConfigBuilder {
                     is_i32_wrap_i64.expr(meta)
                         + is_i64_extend_i32_u.expr(meta)
                         + is_i64_extend_i32_s.expr(meta)
+                        + is_i32_extend_8s.expr(meta)
+                        + is_i32_extend_16s.expr(meta)
+                        + is_i64_extend_8s.expr(meta)
+                        + is_i64_extend_16s.expr(meta)
+                        + is_i64_extend_32s.expr(meta)
                         - constant_from!(1),
                 ]
             }),
@@ -74,6 +89,18 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ConversionConfigBuilder {
                         * (value_type.expr(meta) - constant_from!(VarType::I32)),
                     (is_i64_extend_i32_s.expr(meta) + is_i64_extend_i32_u.expr(meta))
                         * (res_type.expr(meta) - constant_from!(VarType::I64)),
+                    (is_i32_extend_8s.expr(meta) + is_i32_extend_16s.expr(meta))
+                        * (value_type.expr(meta) - constant_from!(VarType::I32)),
+                    (is_i64_extend_8s.expr(meta)
+                        + is_i64_extend_16s.expr(meta)
+                        + is_i64_extend_32s.expr(meta))
+                        * (value_type.expr(meta) - constant_from!(VarType::I64)),
+                    (is_i32_extend_8s.expr(meta)
+                        + is_i32_extend_16s.expr(meta)
+                        + is_i64_extend_8s.expr(meta)
+                        + is_i64_extend_16s.expr(meta)
+                        + is_i64_extend_32s.expr(meta))
+                        * (res_type.expr(meta) - value_type.expr(meta)),
                 ]
             }),
         );
@@ -94,12 +121,21 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ConversionConfigBuilder {
         constraint_builder.push(
             "extend op flag bit",
             Box::new(move |meta| {
-                let flag_u8 = value.u8_expr(meta, 3);
+                // flag_u8 is the most significant byte of the extended part
+                let is_extend_from_u8 = is_i32_extend_8s.expr(meta) + is_i64_extend_8s.expr(meta);
+                let is_extend_from_u16 =
+                    is_i32_extend_16s.expr(meta) + is_i64_extend_16s.expr(meta);
+                let is_extend_from_u32 = is_i64_extend_i32_s.expr(meta)
+                    + is_i64_extend_i32_u.expr(meta)
+                    + is_i64_extend_32s.expr(meta);
+
+                let flag = flag_bit.expr(meta) * constant_from!(128) + flag_u8_rem.expr(meta);
+
                 vec![
-                    (is_i64_extend_i32_s.expr(meta) + is_i64_extend_i32_u.expr(meta))
-                        * (flag_bit.expr(meta) * constant_from!(128) + flag_u8_rem.expr(meta)
-                            - flag_u8),
-                    (is_i64_extend_i32_s.expr(meta) + is_i64_extend_i32_u.expr(meta))
+                    is_extend_from_u8.clone() * (flag.clone() - value.u8_expr(meta, 0)),
+                    is_extend_from_u16.clone() * (flag.clone() - value.u8_expr(meta, 1)),
+                    is_extend_from_u32.clone() * (flag - value.u8_expr(meta, 3)),
+                    (is_extend_from_u8 + is_extend_from_u16 + is_extend_from_u32)
                         * (flag_u8_rem.expr(meta) + flag_u8_rem_diff.expr(meta)
                             - constant_from!(127)),
                 ]
@@ -122,6 +158,37 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ConversionConfigBuilder {
             }),
         );
 
+        constraint_builder.push(
+            "sign extend",
+            Box::new(move |meta| {
+                let low_bytes = |meta: &mut VirtualCells<'_, F>, bytes: i32| {
+                    let mut acc = constant_from!(0);
+
+                    for i in 0..bytes {
+                        acc = acc + value.u8_expr(meta, i) * constant_from!(1u64 << (i * 8));
+                    }
+
+                    acc
+                };
+
+                macro_rules! sign_extend {
+                    ($op: ident, $bytes: expr, $max: expr) => {{
+                        let pad = flag_bit.expr(meta)
+                            * constant_from!($max - ((1u64 << ($bytes * 8)) - 1));
+                        $op.expr(meta) * (pad + low_bytes(meta, $bytes) - res.expr(meta))
+                    }};
+                }
+
+                vec![
+                    sign_extend!(is_i32_extend_8s, 1, u32::MAX as u64),
+                    sign_extend!(is_i32_extend_16s, 2, u32::MAX as u64),
+                    sign_extend!(is_i64_extend_8s, 1, u64::MAX),
+                    sign_extend!(is_i64_extend_16s, 2, u64::MAX),
+                    sign_extend!(is_i64_extend_32s, 4, u64::MAX),
+                ]
+            }),
+        );
+
         Box::new(ConversionConfig {
             value,
             value_type,
@@ -133,6 +200,11 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ConversionConfigBuilder {
             is_i32_wrap_i64,
             is_i64_extend_i32_u,
             is_i64_extend_i32_s,
+            is_i32_extend_8s,
+            is_i32_extend_16s,
+            is_i64_extend_8s,
+            is_i64_extend_16s,
+            is_i64_extend_32s,
             lookup_stack_read,
             lookup_stack_write,
         })
@@ -147,6 +219,16 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ConversionConfig {
                 * encode_conversion::<Expression<F>>(ConversionOp::I64ExtendI32s)
             + self.is_i64_extend_i32_u.expr(meta)
                 * encode_conversion::<Expression<F>>(ConversionOp::I64ExtendI32u)
+            + self.is_i32_extend_8s.expr(meta)
+                * encode_conversion::<Expression<F>>(ConversionOp::I32Extend8s)
+            + self.is_i32_extend_16s.expr(meta)
+                * encode_conversion::<Expression<F>>(ConversionOp::I32Extend16s)
+            + self.is_i64_extend_8s.expr(meta)
+                * encode_conversion::<Expression<F>>(ConversionOp::I64Extend8s)
+            + self.is_i64_extend_16s.expr(meta)
+                * encode_conversion::<Expression<F>>(ConversionOp::I64Extend16s)
+            + self.is_i64_extend_32s.expr(meta)
+                * encode_conversion::<Expression<F>>(ConversionOp::I64Extend32s)
     }
 
     fn assign(
@@ -191,6 +273,33 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ConversionConfig {
                     VarType::I64,
                 )
             }
+            StepInfo::SignExtend {
+                vtype,
+                bits,
+                value,
+                result,
+            } => {
+                match (vtype, bits) {
+                    (VarType::I32, 8) => self.is_i32_extend_8s.assign(ctx, true)?,
+                    (VarType::I32, 16) => self.is_i32_extend_16s.assign(ctx, true)?,
+                    (VarType::I64, 8) => self.is_i64_extend_8s.assign(ctx, true)?,
+                    (VarType::I64, 16) => self.is_i64_extend_16s.assign(ctx, true)?,
+                    (VarType::I64, 32) => self.is_i64_extend_32s.assign(ctx, true)?,
+                    _ => unreachable!(),
+                }
+
+                let flag_u8 = (value >> (bits - 8)) & 0xff;
+                let flag_bit = flag_u8 >> 7;
+                let flag_u8_rem = flag_u8 & 0x7f;
+                let flag_u8_rem_diff = 0x7f - flag_u8_rem;
+
+                self.flag_bit.assign(ctx, flag_bit == 1)?;
+                self.flag_u8_rem.assign(ctx, F::from(flag_u8_rem))?;
+                self.flag_u8_rem_diff
+                    .assign(ctx, F::from(flag_u8_rem_diff))?;
+
+                (value, vtype, result, vtype)
+            }
             _ => unreachable!(),
         };
 
@@ -258,7 +367,70 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ConversionConfig {
 
 #[cfg(test)]
 mod tests {
-    use crate::test::test_circuit_noexternal;
+    use crate::test::{test_circuit_noexternal, test_circuit_noexternal_patched, StepPatch};
+    use specs::{
+        itable::{ConversionOp, Opcode, UnaryOp},
+        mtable::VarType,
+        step::StepInfo,
+    };
+
+    /*
+     * The wasmi tracer does not emit the sign-extension steps, clz, ctz and
+     * popcnt stand in for the sign-extensions of 8, 16 and 32 bits.
+     */
+    fn test_circuit_sign_extend(textual_repr: &str) {
+        test_circuit_noexternal_patched(
+            textual_repr,
+            &[
+                ("i32.extend8_s", "i32.clz"),
+                ("i32.extend16_s", "i32.ctz"),
+                ("i64.extend8_s", "i64.clz"),
+                ("i64.extend16_s", "i64.ctz"),
+                ("i64.extend32_s", "i64.popcnt"),
+            ],
+            &[],
+            |_, entry| match entry.step_info {
+                StepInfo::UnaryOp {
+                    class,
+                    vtype,
+                    operand,
+                    ..
+                } => {
+                    let bits = match class {
+                        UnaryOp::Clz => 8,
+                        UnaryOp::Ctz => 16,
+                        UnaryOp::Popcnt => 32,
+                    };
+                    let class = match (vtype, bits) {
+                        (VarType::I32, 8) => ConversionOp::I32Extend8s,
+                        (VarType::I32, 16) => ConversionOp::I32Extend16s,
+                        (VarType::I64, 8) => ConversionOp::I64Extend8s,
+                        (VarType::I64, 16) => ConversionOp::I64Extend16s,
+                        (VarType::I64, 32) => ConversionOp::I64Extend32s,
+                        _ => unreachable!(),
+                    };
+
+                    let result = ((operand << (64 - bits)) as i64 >> (64 - bits)) as u64;
+                    let result = match vtype {
+                        VarType::I32 => result as u32 as u64,
+                        VarType::I64 => result,
+                    };
+
+                    StepPatch::Replace(
+                        Opcode::Conversion { class },
+                        StepInfo::SignExtend {
+                            vtype,
+                            bits,
+                            value: operand,
+                            result,
+                        },
+                    )
+                }
+                _ => StepPatch::Keep,
+            },
+        )
+        .unwrap()
+    }
 
     #[test]
     fn test_i32_wrap_i64_ok() {
@@ -325,4 +497,86 @@ mod tests {
 
         test_circuit_noexternal(textual_repr).unwrap()
     }
+
+    #[test]
+    fn test_i32_sign_extend_ok() {
+        let textual_repr = r#"
+                (module
+                    (func (export "test")
+                      (i32.const 0)
+                      (i32.extend8_s)
+                      (drop)
+
+                      (i32.const 0x7f)
+                      (i32.extend8_s)
+                      (drop)
+
+                      (i32.const 0x80)
+                      (i32.extend8_s)
+                      (drop)
+
+                      (i32.const 0xffffff00)
+                      (i32.extend8_s)
+                      (drop)
+
+                      (i32.const 0x7fff)
+                      (i32.extend16_s)
+                      (drop)
+
+                      (i32.const 0x8000)
+                      (i32.extend16_s)
+                      (drop)
+
+                      (i32.const -1)
+                      (i32.extend16_s)
+                      (drop)
+                    )
+                   )
+                "#;
+
+        test_circuit_sign_extend(textual_repr)
+    }
+
+    #[test]
+    fn test_i64_sign_extend_ok() {
+        let textual_repr = r#"
+                (module
+                    (func (export "test")
+                      (i64.const 0x7f)
+                      (i64.extend8_s)
+                      (drop)
+
+                      (i64.const 0x80)
+                      (i64.extend8_s)
+                      (drop)
+
+                      (i64.const 0x7fff)
+                      (i64.extend16_s)
+                      (drop)
+
+                      (i64.const 0xffffffffffff8000)
+                      (i64.extend16_s)
+                      (drop)
+
+                      (i64.const 0)
+                      (i64.extend32_s)
+                      (drop)
+
+                      (i64.const 0x7fffffff)
+                      (i64.extend32_s)
+                      (drop)
+
+                      (i64.const 0x80000000)
+                      (i64.extend32_s)
+                      (drop)
+
+                      (i64.const -1)
+                      (i64.extend32_s)
+                      (drop)
+                    )
+                   )
+                "#;
+
+        test_circuit_sign_extend(textual_repr)
+    }
 }
@@ -0,0 +1,134 @@
+use super::*;
+use crate::{
+    circuits::{
+        mtable_compact::encode::MemoryTableLookupEncode,
+        utils::{bn_to_field, Context},
+    },
+    constant,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Error, Expression, VirtualCells},
+};
+use specs::{
+    etable::EventTableEntry,
+    itable::{Opcode, OPCODE_ARG0_SHIFT, OPCODE_ARG1_SHIFT, OPCODE_CLASS_SHIFT},
+    step::StepInfo,
+};
+
+pub struct DataDropConfig {
+    segment: CommonRangeCell,
+    segment_offset: CommonRangeCell,
+
+    lookup_data_write_segment_len: MTableLookupCell,
+}
+
+pub struct DataDropConfigBuilder {}
+
+impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for DataDropConfigBuilder {
+    fn configure(
+        common: &mut EventTableCellAllocator<F>,
+        _constraint_builder: &mut ConstraintBuilder<F>,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let segment = common.alloc_common_range_value();
+        let segment_offset = common.alloc_common_range_value();
+
+        let lookup_data_write_segment_len = common.alloc_mtable_lookup();
+
+        Box::new(DataDropConfig {
+            segment,
+            segment_offset,
+            lookup_data_write_segment_len,
+        })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for DataDropConfig {
+    fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        constant!(bn_to_field(
+            &(BigUint::from(OpcodeClass::DataDrop as u64) << OPCODE_CLASS_SHIFT)
+        )) + self.segment.expr(meta)
+            * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG0_SHIFT)))
+            + self.segment_offset.expr(meta)
+                * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG1_SHIFT)))
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step_info: &StepStatus,
+        entry: &EventTableEntry,
+    ) -> Result<(), Error> {
+        match &entry.step_info {
+            StepInfo::DataDrop { segment } => {
+                let segment_offset = match entry.inst.opcode {
+                    Opcode::DataDrop { segment_offset, .. } => segment_offset,
+                    _ => unreachable!(),
+                };
+
+                self.segment.assign(ctx, F::from(*segment as u64))?;
+                self.segment_offset
+                    .assign(ctx, F::from(segment_offset as u64))?;
+
+                self.lookup_data_write_segment_len.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_data_write(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(1 as u64),
+                        BigUint::from(segment_offset),
+                        BigUint::from(0 as u64),
+                    ),
+                )?;
+
+                Ok(())
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn mops(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(1))
+    }
+
+    // Clear the length of the segment, memory.init on a dropped segment is then empty.
+    fn mtable_lookup(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        item: MLookupItem,
+        common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        match item {
+            MLookupItem::First => Some(MemoryTableLookupEncode::encode_data_write(
+                common_config.eid(meta),
+                constant_from!(1),
+                self.segment_offset.expr(meta),
+                constant_from!(0),
+            )),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::test_circuit_bulk_memory;
+
+    #[test]
+    fn test_data_drop_ok() {
+        let textual_repr = r#"
+                (module
+                    (memory $0 1)
+                    (data $d0 "\01\02\03")
+                    (data $d1 "\04\05\06")
+                    (func (export "test")
+                      (data.drop $d1)
+                      (data.drop $d0)
+                      (data.drop $d0)
+                      (i32.const 0) (i32.const 0) (i32.const 0) (memory.init $d0)
+                    )
+                   )
+                "#;
+
+        test_circuit_bulk_memory(textual_repr).unwrap()
+    }
+}
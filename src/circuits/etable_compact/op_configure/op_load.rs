@@ -58,6 +58,9 @@ pub struct LoadConfig {
     lookup_pow: PowTableLookupCell,
 
     address_within_allocated_pages_helper: CommonRangeCell,
+
+    is_trap: BitCell,
+    out_of_bounds_helper: U64OnU8Cell,
 }
 
 pub struct LoadConfigBuilder {}
@@ -106,6 +109,9 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for LoadConfigBuilder {
         let current_memory_page_size = common.allocated_memory_pages_cell();
         let address_within_allocated_pages_helper = common.alloc_common_range_value();
 
+        let is_trap = common.alloc_bit_value();
+        let out_of_bounds_helper = common.alloc_u64_on_u8();
+
         constraint_builder.push(
             "op_load start end offset <= 7",
             Box::new(move |meta| {
@@ -142,9 +148,10 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for LoadConfigBuilder {
             "op_load start load_base",
             Box::new(move |meta| {
                 vec![
-                    load_base.expr(meta) + opcode_load_offset.expr(meta)
+                    (load_base.expr(meta) + opcode_load_offset.expr(meta)
                         - load_start_block_index.expr(meta) * constant_from!(8)
-                        - load_start_block_inner_offset.expr(meta),
+                        - load_start_block_inner_offset.expr(meta))
+                        * (constant_from!(1) - is_trap.expr(meta)),
                 ]
             }),
         );
@@ -263,7 +270,27 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for LoadConfigBuilder {
                         + opcode_load_offset.expr(meta)
                         + len
                         + address_within_allocated_pages_helper.expr(meta)
-                        - current_memory_page_size.expr(meta) * constant_from!(WASM_PAGE_SIZE)),
+                        - current_memory_page_size.expr(meta) * constant_from!(WASM_PAGE_SIZE))
+                        * (constant_from!(1) - is_trap.expr(meta)),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_load out of bounds",
+            Box::new(move |meta| {
+                let len = constant_from!(1)
+                    + is_two_bytes.expr(meta) * constant_from!(1)
+                    + is_four_bytes.expr(meta) * constant_from!(3)
+                    + is_eight_bytes.expr(meta) * constant_from!(7);
+
+                // The last byte is at or after the end of the allocated memory
+                vec![
+                    (load_base.expr(meta) + opcode_load_offset.expr(meta) + len
+                        - constant_from!(1)
+                        - out_of_bounds_helper.expr(meta)
+                        - current_memory_page_size.expr(meta) * constant_from!(WASM_PAGE_SIZE))
+                        * is_trap.expr(meta),
                 ]
             }),
         );
@@ -298,6 +325,8 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for LoadConfigBuilder {
             lookup_offset_len_bits,
             lookup_pow,
             address_within_allocated_pages_helper,
+            is_trap,
+            out_of_bounds_helper,
         })
     }
 }
@@ -453,14 +482,75 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for LoadConfig {
                 Ok(())
             }
 
+            StepInfo::LoadOutOfBounds {
+                vtype,
+                load_size,
+                offset,
+                raw_address,
+            } => {
+                self.opcode_load_offset
+                    .assign(ctx, F::from(offset as u64))?;
+
+                // No memory block is accessed, the block cells describe [0, len).
+                let len = load_size.byte_size();
+
+                self.load_start_block_inner_offset_helper
+                    .assign(ctx, F::from(7))?;
+                self.load_end_block_inner_offset
+                    .assign(ctx, F::from(len - 1))?;
+                self.load_end_block_inner_offset_helper
+                    .assign(ctx, F::from(8 - len))?;
+
+                let bits = bits_of_offset_len(0, len);
+                for i in 0..16 {
+                    self.mask_bits[i].assign(ctx, (bits >> i) & 1 == 1)?;
+                }
+                self.offset_modulus.assign(ctx, 1)?;
+                self.load_base.assign(ctx, raw_address.into())?;
+
+                self.is_one_byte.assign(ctx, len == 1)?;
+                self.is_two_bytes.assign(ctx, len == 2)?;
+                self.is_four_bytes.assign(ctx, len == 4)?;
+                self.is_eight_bytes.assign(ctx, len == 8)?;
+                self.is_sign.assign(ctx, load_size.is_sign())?;
+                self.is_i64.assign(ctx, vtype == VarType::I64)?;
+                self.vtype.assign(ctx, F::from(vtype as u64))?;
+
+                self.lookup_stack_read.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_stack_read(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(1 as u64),
+                        BigUint::from(step_info.current.sp + 1),
+                        BigUint::from(VarType::I32 as u16),
+                        BigUint::from(raw_address),
+                    ),
+                )?;
+
+                self.is_trap.assign(ctx, true)?;
+                self.out_of_bounds_helper.assign(
+                    ctx,
+                    raw_address as u64 + offset as u64 + len
+                        - 1
+                        - step_info.current.allocated_memory_pages as u64 * WASM_PAGE_SIZE,
+                )?;
+
+                self.lookup_offset_len_bits.assign(ctx, 0, len)?;
+                self.lookup_pow.assign(ctx, 0)?;
+
+                Ok(())
+            }
+
             _ => unreachable!(),
         }
     }
 
     fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
         Some(
-            constant_from!(3) + self.load_end_block_index.expr(meta)
-                - self.load_start_block_index.expr(meta),
+            (constant_from!(3) + self.load_end_block_index.expr(meta)
+                - self.load_start_block_index.expr(meta))
+                * (constant_from!(1) - self.is_trap.expr(meta))
+                + self.is_trap.expr(meta),
         )
     }
 
@@ -484,6 +574,8 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for LoadConfig {
                     0
                 }
             }
+            // Only the address is read
+            StepInfo::LoadOutOfBounds { .. } => 1,
             _ => unreachable!(),
         }
     }
@@ -496,6 +588,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for LoadConfig {
     ) -> Option<Expression<F>> {
         let cross_load =
             self.load_end_block_index.expr(meta) - self.load_start_block_index.expr(meta);
+        let no_trap = constant_from!(1) - self.is_trap.expr(meta);
 
         match item {
             MLookupItem::First => Some(MemoryTableLookupEncode::encode_stack_read(
@@ -505,13 +598,15 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for LoadConfig {
                 constant_from!(VarType::I32),
                 self.load_base.expr(meta),
             )),
-            MLookupItem::Second => Some(MemoryTableLookupEncode::encode_memory_load(
-                common_config.eid(meta),
-                constant_from!(2),
-                self.load_start_block_index.expr(meta),
-                constant_from!(VarType::I64),
-                self.load_value1.expr(meta),
-            )),
+            MLookupItem::Second => Some(
+                MemoryTableLookupEncode::encode_memory_load(
+                    common_config.eid(meta),
+                    constant_from!(2),
+                    self.load_start_block_index.expr(meta),
+                    constant_from!(VarType::I64),
+                    self.load_value1.expr(meta),
+                ) * no_trap.clone(),
+            ),
             MLookupItem::Third => Some(
                 MemoryTableLookupEncode::encode_memory_load(
                     common_config.eid(meta),
@@ -519,23 +614,64 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for LoadConfig {
                     self.load_end_block_index.expr(meta),
                     constant_from!(VarType::I64),
                     self.load_value2.expr(meta),
-                ) * cross_load.clone(),
+                ) * cross_load.clone()
+                    * no_trap.clone(),
+            ),
+            MLookupItem::Fourth => Some(
+                MemoryTableLookupEncode::encode_stack_write(
+                    common_config.eid(meta),
+                    constant_from!(3) + cross_load,
+                    common_config.sp(meta) + constant_from!(1),
+                    self.vtype.expr(meta),
+                    self.res.expr(meta),
+                ) * no_trap,
             ),
-            MLookupItem::Fourth => Some(MemoryTableLookupEncode::encode_stack_write(
-                common_config.eid(meta),
-                constant_from!(3) + cross_load,
-                common_config.sp(meta) + constant_from!(1),
-                self.vtype.expr(meta),
-                self.res.expr(meta),
-            )),
             _ => None,
         }
     }
+
+    fn is_trap(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(self.is_trap.expr(meta))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::test::test_circuit_noexternal;
+    use crate::test::{test_circuit_noexternal, test_circuit_noexternal_patched, StepPatch};
+    use specs::{
+        itable::Opcode,
+        mtable::{MemoryReadSize, VarType},
+        step::StepInfo,
+    };
+
+    // The wasmi tracer does not emit the trapping load, i32.eqz stands in.
+    fn test_circuit_load_trap(textual_repr: &str, instruction: &str, opcode: Opcode) {
+        test_circuit_noexternal_patched(
+            textual_repr,
+            &[(instruction, "(i32.eqz)")],
+            &[],
+            |_, entry| match (&opcode, &entry.step_info) {
+                (
+                    Opcode::Load {
+                        offset,
+                        vtype,
+                        size,
+                    },
+                    StepInfo::Test { value, .. },
+                ) => StepPatch::Replace(
+                    opcode.clone(),
+                    StepInfo::LoadOutOfBounds {
+                        vtype: *vtype,
+                        load_size: *size,
+                        offset: *offset,
+                        raw_address: *value as u32,
+                    },
+                ),
+                _ => StepPatch::Keep,
+            },
+        )
+        .unwrap()
+    }
 
     #[test]
     fn test_load_cross() {
@@ -623,4 +759,52 @@ mod tests {
 
         test_circuit_noexternal(textual_repr).unwrap();
     }
+
+    #[test]
+    fn test_load_out_of_bounds_trap() {
+        let textual_repr = r#"
+                (module
+                    (memory $0 1)
+                    (func (export "test")
+                        (i32.const 65532)
+                        (i64.load offset=0)
+                        (drop)
+                    )
+                )
+                "#;
+
+        test_circuit_load_trap(
+            textual_repr,
+            "(i64.load offset=0)",
+            Opcode::Load {
+                offset: 0,
+                vtype: VarType::I64,
+                size: MemoryReadSize::I64,
+            },
+        );
+    }
+
+    #[test]
+    fn test_load_out_of_bounds_offset_trap() {
+        let textual_repr = r#"
+                (module
+                    (memory $0 1)
+                    (func (export "test")
+                        (i32.const 65535)
+                        (i32.load8_u offset=1)
+                        (drop)
+                    )
+                )
+                "#;
+
+        test_circuit_load_trap(
+            textual_repr,
+            "(i32.load8_u offset=1)",
+            Opcode::Load {
+                offset: 1,
+                vtype: VarType::I32,
+                size: MemoryReadSize::U8,
+            },
+        );
+    }
 }
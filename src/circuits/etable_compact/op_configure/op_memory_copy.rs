@@ -0,0 +1,295 @@
+use super::bulk_memory::BlockRangeCell;
+use super::*;
+use crate::{
+    circuits::{
+        bulk_memory_table::{BulkMemoryOpKind, BulkMemoryTableEncode, BULK_MEMORY_TABLE_KEY},
+        mtable_compact::encode::MemoryTableLookupEncode,
+        utils::{bn_to_field, Context},
+    },
+    constant,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Error, Expression, VirtualCells},
+};
+use specs::{
+    configure_table::WASM_PAGE_SIZE,
+    etable::EventTableEntry,
+    itable::{OpcodeClass, OPCODE_CLASS_SHIFT},
+};
+use specs::{mtable::VarType, step::StepInfo};
+
+pub struct MemoryCopyConfig {
+    dst: U64Cell,
+    src: U64Cell,
+    len: U64Cell,
+    len_inv: UnlimitedCell,
+    is_empty: BitCell,
+
+    dst_blocks: BlockRangeCell,
+    src_blocks: BlockRangeCell,
+    dst_within_allocated_pages_helper: CommonRangeCell,
+    src_within_allocated_pages_helper: CommonRangeCell,
+
+    lookup_stack_read_len: MTableLookupCell,
+    lookup_stack_read_src: MTableLookupCell,
+    lookup_stack_read_dst: MTableLookupCell,
+}
+
+pub struct MemoryCopyConfigBuilder {}
+
+impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for MemoryCopyConfigBuilder {
+    fn configure(
+        common: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let dst = common.alloc_u64();
+        let src = common.alloc_u64();
+        let len = common.alloc_u64();
+        let len_inv = common.alloc_unlimited_value();
+        let is_empty = common.alloc_bit_value();
+
+        let dst_blocks = BlockRangeCell::alloc(common);
+        let src_blocks = BlockRangeCell::alloc(common);
+
+        let eid = common.eid_cell();
+        let current_memory_page_size = common.allocated_memory_pages_cell();
+        let dst_within_allocated_pages_helper = common.alloc_common_range_value();
+        let src_within_allocated_pages_helper = common.alloc_common_range_value();
+
+        let lookup_stack_read_len = common.alloc_mtable_lookup();
+        let lookup_stack_read_src = common.alloc_mtable_lookup();
+        let lookup_stack_read_dst = common.alloc_mtable_lookup();
+
+        constraint_builder.push(
+            "memory_copy: is empty",
+            Box::new(move |meta| {
+                vec![
+                    is_empty.expr(meta) * len.expr(meta),
+                    len.expr(meta) * len_inv.expr(meta) - constant_from!(1) + is_empty.expr(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "memory_copy: dst blocks",
+            Box::new(move |meta| {
+                let is_not_empty = constant_from!(1) - is_empty.expr(meta);
+                dst_blocks.constraints(meta, dst.expr(meta), len.expr(meta), is_not_empty)
+            }),
+        );
+
+        constraint_builder.push(
+            "memory_copy: src blocks",
+            Box::new(move |meta| {
+                let is_not_empty = constant_from!(1) - is_empty.expr(meta);
+                src_blocks.constraints(meta, src.expr(meta), len.expr(meta), is_not_empty)
+            }),
+        );
+
+        constraint_builder.push(
+            "memory_copy: allocated address",
+            Box::new(move |meta| {
+                let memory_size =
+                    current_memory_page_size.expr(meta) * constant_from!(WASM_PAGE_SIZE);
+
+                vec![
+                    dst.expr(meta) + len.expr(meta) + dst_within_allocated_pages_helper.expr(meta)
+                        - memory_size.clone(),
+                    src.expr(meta) + len.expr(meta) + src_within_allocated_pages_helper.expr(meta)
+                        - memory_size,
+                ]
+            }),
+        );
+
+        // The block values are constrained in the bulk memory table.
+        constraint_builder.lookup(
+            BULK_MEMORY_TABLE_KEY,
+            "memory_copy: bulk memory table",
+            Box::new(move |meta| {
+                (constant_from!(1) - is_empty.expr(meta))
+                    * BulkMemoryTableEncode::encode_for_lookup(
+                        eid.expr(meta),
+                        constant_from!(BulkMemoryOpKind::Copy),
+                        dst.expr(meta),
+                        src.expr(meta),
+                        len.expr(meta),
+                        constant_from!(0),
+                    )
+            }),
+        );
+
+        Box::new(MemoryCopyConfig {
+            dst,
+            src,
+            len,
+            len_inv,
+            is_empty,
+            dst_blocks,
+            src_blocks,
+            dst_within_allocated_pages_helper,
+            src_within_allocated_pages_helper,
+            lookup_stack_read_len,
+            lookup_stack_read_src,
+            lookup_stack_read_dst,
+        })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for MemoryCopyConfig {
+    fn opcode(&self, _meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        constant!(bn_to_field(
+            &(BigUint::from(OpcodeClass::MemoryCopy as u64) << OPCODE_CLASS_SHIFT)
+        ))
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step_info: &StepStatus,
+        entry: &EventTableEntry,
+    ) -> Result<(), Error> {
+        match &entry.step_info {
+            StepInfo::MemoryCopy { dst, src, len, .. } => {
+                self.dst.assign(ctx, *dst as u64)?;
+                self.src.assign(ctx, *src as u64)?;
+                self.len.assign(ctx, *len as u64)?;
+                self.len_inv
+                    .assign(ctx, F::from(*len as u64).invert().unwrap_or(F::zero()))?;
+                self.is_empty.assign(ctx, *len == 0)?;
+
+                self.dst_blocks.assign(ctx, *dst as u64, *len as u64)?;
+                self.src_blocks.assign(ctx, *src as u64, *len as u64)?;
+
+                let memory_size = step_info.current.allocated_memory_pages as u64 * WASM_PAGE_SIZE;
+                self.dst_within_allocated_pages_helper
+                    .assign(ctx, F::from(memory_size - (*dst as u64 + *len as u64)))?;
+                self.src_within_allocated_pages_helper
+                    .assign(ctx, F::from(memory_size - (*src as u64 + *len as u64)))?;
+
+                self.lookup_stack_read_len.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_stack_read(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(1 as u64),
+                        BigUint::from(step_info.current.sp + 1),
+                        BigUint::from(VarType::I32 as u16),
+                        BigUint::from(*len),
+                    ),
+                )?;
+
+                self.lookup_stack_read_src.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_stack_read(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(2 as u64),
+                        BigUint::from(step_info.current.sp + 2),
+                        BigUint::from(VarType::I32 as u16),
+                        BigUint::from(*src),
+                    ),
+                )?;
+
+                self.lookup_stack_read_dst.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_stack_read(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(3 as u64),
+                        BigUint::from(step_info.current.sp + 3),
+                        BigUint::from(VarType::I32 as u16),
+                        BigUint::from(*dst),
+                    ),
+                )?;
+
+                Ok(())
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        let is_not_empty = constant_from!(1) - self.is_empty.expr(meta);
+
+        Some(
+            constant_from!(3)
+                + self.src_blocks.blocks(meta, is_not_empty.clone())
+                + constant_from!(2) * self.dst_blocks.blocks(meta, is_not_empty),
+        )
+    }
+
+    fn assigned_extra_mops(
+        &self,
+        _ctx: &mut Context<'_, F>,
+        _step: &StepStatus,
+        entry: &EventTableEntry,
+    ) -> u64 {
+        match &entry.step_info {
+            StepInfo::MemoryCopy {
+                src_block_values,
+                pre_block_values,
+                ..
+            } => src_block_values.len() as u64 + 2 * pre_block_values.len() as u64,
+            _ => unreachable!(),
+        }
+    }
+
+    fn sp_diff(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(3))
+    }
+
+    fn mtable_lookup(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        item: MLookupItem,
+        common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        match item {
+            MLookupItem::First => Some(MemoryTableLookupEncode::encode_stack_read(
+                common_config.eid(meta),
+                constant_from!(1),
+                common_config.sp(meta) + constant_from!(1),
+                constant_from!(VarType::I32 as u64),
+                self.len.expr(meta),
+            )),
+            MLookupItem::Second => Some(MemoryTableLookupEncode::encode_stack_read(
+                common_config.eid(meta),
+                constant_from!(2),
+                common_config.sp(meta) + constant_from!(2),
+                constant_from!(VarType::I32 as u64),
+                self.src.expr(meta),
+            )),
+            MLookupItem::Third => Some(MemoryTableLookupEncode::encode_stack_read(
+                common_config.eid(meta),
+                constant_from!(3),
+                common_config.sp(meta) + constant_from!(3),
+                constant_from!(VarType::I32 as u64),
+                self.dst.expr(meta),
+            )),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::test_circuit_bulk_memory;
+
+    #[test]
+    fn test_memory_copy_ok() {
+        let textual_repr = r#"
+                (module
+                    (memory $0 1)
+                    (data (i32.const 0) "\01\02\03\04\05\06\07\08\09\0a\0b\0c\0d\0e\0f\10")
+                    (func (export "test")
+                      (i32.const 32) (i32.const 0) (i32.const 0) (memory.copy)
+                      (i32.const 32) (i32.const 0) (i32.const 16) (memory.copy)
+                      (i32.const 3) (i32.const 1) (i32.const 13) (memory.copy)
+                      (i32.const 1) (i32.const 3) (i32.const 13) (memory.copy)
+                      (i32.const 65535) (i32.const 0) (i32.const 1) (memory.copy)
+                    )
+                   )
+                "#;
+
+        test_circuit_bulk_memory(textual_repr).unwrap()
+    }
+}
@@ -0,0 +1,276 @@
+use super::bulk_memory::BlockRangeCell;
+use super::*;
+use crate::{
+    circuits::{
+        bulk_memory_table::{BulkMemoryOpKind, BulkMemoryTableEncode, BULK_MEMORY_TABLE_KEY},
+        mtable_compact::encode::MemoryTableLookupEncode,
+        utils::{bn_to_field, Context},
+    },
+    constant,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Error, Expression, VirtualCells},
+};
+use specs::{
+    configure_table::WASM_PAGE_SIZE,
+    etable::EventTableEntry,
+    itable::{OpcodeClass, OPCODE_CLASS_SHIFT},
+};
+use specs::{mtable::VarType, step::StepInfo};
+
+pub struct MemoryFillConfig {
+    offset: U64Cell,
+    value: U64Cell,
+    len: U64Cell,
+    len_inv: UnlimitedCell,
+    is_empty: BitCell,
+
+    dst_blocks: BlockRangeCell,
+    address_within_allocated_pages_helper: CommonRangeCell,
+
+    lookup_stack_read_len: MTableLookupCell,
+    lookup_stack_read_value: MTableLookupCell,
+    lookup_stack_read_offset: MTableLookupCell,
+}
+
+pub struct MemoryFillConfigBuilder {}
+
+impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for MemoryFillConfigBuilder {
+    fn configure(
+        common: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let offset = common.alloc_u64();
+        let value = common.alloc_u64();
+        let len = common.alloc_u64();
+        let len_inv = common.alloc_unlimited_value();
+        let is_empty = common.alloc_bit_value();
+
+        let dst_blocks = BlockRangeCell::alloc(common);
+
+        let eid = common.eid_cell();
+        let current_memory_page_size = common.allocated_memory_pages_cell();
+        let address_within_allocated_pages_helper = common.alloc_common_range_value();
+
+        let lookup_stack_read_len = common.alloc_mtable_lookup();
+        let lookup_stack_read_value = common.alloc_mtable_lookup();
+        let lookup_stack_read_offset = common.alloc_mtable_lookup();
+
+        constraint_builder.push(
+            "memory_fill: is empty",
+            Box::new(move |meta| {
+                vec![
+                    is_empty.expr(meta) * len.expr(meta),
+                    len.expr(meta) * len_inv.expr(meta) - constant_from!(1) + is_empty.expr(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "memory_fill: dst blocks",
+            Box::new(move |meta| {
+                let is_not_empty = constant_from!(1) - is_empty.expr(meta);
+                dst_blocks.constraints(meta, offset.expr(meta), len.expr(meta), is_not_empty)
+            }),
+        );
+
+        constraint_builder.push(
+            "memory_fill: allocated address",
+            Box::new(move |meta| {
+                vec![
+                    offset.expr(meta)
+                        + len.expr(meta)
+                        + address_within_allocated_pages_helper.expr(meta)
+                        - current_memory_page_size.expr(meta) * constant_from!(WASM_PAGE_SIZE),
+                ]
+            }),
+        );
+
+        /*
+         * The block values are constrained in the bulk memory table, the low
+         * byte of value is the one written.
+         */
+        constraint_builder.lookup(
+            BULK_MEMORY_TABLE_KEY,
+            "memory_fill: bulk memory table",
+            Box::new(move |meta| {
+                (constant_from!(1) - is_empty.expr(meta))
+                    * BulkMemoryTableEncode::encode_for_lookup(
+                        eid.expr(meta),
+                        constant_from!(BulkMemoryOpKind::Fill),
+                        offset.expr(meta),
+                        offset.expr(meta),
+                        len.expr(meta),
+                        value.u4_expr(meta, 0) + value.u4_expr(meta, 1) * constant_from!(16),
+                    )
+            }),
+        );
+
+        Box::new(MemoryFillConfig {
+            offset,
+            value,
+            len,
+            len_inv,
+            is_empty,
+            dst_blocks,
+            address_within_allocated_pages_helper,
+            lookup_stack_read_len,
+            lookup_stack_read_value,
+            lookup_stack_read_offset,
+        })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for MemoryFillConfig {
+    fn opcode(&self, _meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        constant!(bn_to_field(
+            &(BigUint::from(OpcodeClass::MemoryFill as u64) << OPCODE_CLASS_SHIFT)
+        ))
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step_info: &StepStatus,
+        entry: &EventTableEntry,
+    ) -> Result<(), Error> {
+        match &entry.step_info {
+            StepInfo::MemoryFill {
+                offset, value, len, ..
+            } => {
+                self.offset.assign(ctx, *offset as u64)?;
+                self.value.assign(ctx, *value as u64)?;
+                self.len.assign(ctx, *len as u64)?;
+                self.len_inv
+                    .assign(ctx, F::from(*len as u64).invert().unwrap_or(F::zero()))?;
+                self.is_empty.assign(ctx, *len == 0)?;
+
+                self.dst_blocks.assign(ctx, *offset as u64, *len as u64)?;
+
+                self.address_within_allocated_pages_helper.assign(
+                    ctx,
+                    F::from(
+                        step_info.current.allocated_memory_pages as u64 * WASM_PAGE_SIZE
+                            - (*offset as u64 + *len as u64),
+                    ),
+                )?;
+
+                self.lookup_stack_read_len.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_stack_read(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(1 as u64),
+                        BigUint::from(step_info.current.sp + 1),
+                        BigUint::from(VarType::I32 as u16),
+                        BigUint::from(*len),
+                    ),
+                )?;
+
+                self.lookup_stack_read_value.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_stack_read(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(2 as u64),
+                        BigUint::from(step_info.current.sp + 2),
+                        BigUint::from(VarType::I32 as u16),
+                        BigUint::from(*value),
+                    ),
+                )?;
+
+                self.lookup_stack_read_offset.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_stack_read(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(3 as u64),
+                        BigUint::from(step_info.current.sp + 3),
+                        BigUint::from(VarType::I32 as u16),
+                        BigUint::from(*offset),
+                    ),
+                )?;
+
+                Ok(())
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        let is_not_empty = constant_from!(1) - self.is_empty.expr(meta);
+
+        Some(constant_from!(3) + constant_from!(2) * self.dst_blocks.blocks(meta, is_not_empty))
+    }
+
+    fn assigned_extra_mops(
+        &self,
+        _ctx: &mut Context<'_, F>,
+        _step: &StepStatus,
+        entry: &EventTableEntry,
+    ) -> u64 {
+        match &entry.step_info {
+            StepInfo::MemoryFill {
+                pre_block_values, ..
+            } => 2 * pre_block_values.len() as u64,
+            _ => unreachable!(),
+        }
+    }
+
+    fn sp_diff(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(3))
+    }
+
+    fn mtable_lookup(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        item: MLookupItem,
+        common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        match item {
+            MLookupItem::First => Some(MemoryTableLookupEncode::encode_stack_read(
+                common_config.eid(meta),
+                constant_from!(1),
+                common_config.sp(meta) + constant_from!(1),
+                constant_from!(VarType::I32 as u64),
+                self.len.expr(meta),
+            )),
+            MLookupItem::Second => Some(MemoryTableLookupEncode::encode_stack_read(
+                common_config.eid(meta),
+                constant_from!(2),
+                common_config.sp(meta) + constant_from!(2),
+                constant_from!(VarType::I32 as u64),
+                self.value.expr(meta),
+            )),
+            MLookupItem::Third => Some(MemoryTableLookupEncode::encode_stack_read(
+                common_config.eid(meta),
+                constant_from!(3),
+                common_config.sp(meta) + constant_from!(3),
+                constant_from!(VarType::I32 as u64),
+                self.offset.expr(meta),
+            )),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::test_circuit_bulk_memory;
+
+    #[test]
+    fn test_memory_fill_ok() {
+        let textual_repr = r#"
+                (module
+                    (memory $0 1)
+                    (func (export "test")
+                      (i32.const 0) (i32.const 0xff) (i32.const 0) (memory.fill)
+                      (i32.const 3) (i32.const 0x12) (i32.const 4) (memory.fill)
+                      (i32.const 5) (i32.const 0x34) (i32.const 30) (memory.fill)
+                      (i32.const 65535) (i32.const 0x56) (i32.const 1) (memory.fill)
+                    )
+                   )
+                "#;
+
+        test_circuit_bulk_memory(textual_repr).unwrap()
+    }
+}
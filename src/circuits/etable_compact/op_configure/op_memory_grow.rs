@@ -42,7 +42,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for MemoryGrowConfigBuilder {
         let lookup_stack_read = common.alloc_mtable_lookup();
         let lookup_stack_write = common.alloc_mtable_lookup();
 
-        let maximal_memory_pages = common.config.circuit_configure.maximal_memory_pages;
+        let maximal_memory_pages = common.config.image_configure.maximal_memory_pages;
 
         constraint_builder.push(
             "memory_grow: return value",
@@ -62,7 +62,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for MemoryGrowConfigBuilder {
                     (current_memory_size.expr(meta)
                         + grow_size.expr(meta)
                         + current_maximal_diff.expr(meta)
-                        - constant_from!(maximal_memory_pages))
+                        - maximal_memory_pages.expr(meta))
                         * success.expr(meta),
                 ]
             }),
@@ -1,34 +1,24 @@
+use super::keep_values::KeepValuesCell;
 use super::*;
 
-use crate::{
-    circuits::{
-        jtable::expression::JtableLookupEntryEncode,
-        mtable_compact::encode::MemoryTableLookupEncode,
-        utils::{bn_to_field, Context},
-    },
-    constant,
-};
+use crate::circuits::{jtable::expression::JtableLookupEntryEncode, utils::Context};
 use halo2_proofs::{
     arithmetic::FieldExt,
     plonk::{Error, Expression, VirtualCells},
 };
 use num_bigint::ToBigUint;
 use specs::{
-    encode::frame_table::encode_frame_table_entry,
+    encode::{frame_table::encode_frame_table_entry, opcode::encode_return},
     etable::EventTableEntry,
-    itable::{OpcodeClass, OPCODE_ARG0_SHIFT, OPCODE_ARG1_SHIFT, OPCODE_CLASS_SHIFT},
-    mtable::VarType,
     step::StepInfo,
 };
 
+const MAX_KEEP: usize = OpcodeClass::Return.max_keep();
+
 pub struct ReturnConfig {
-    keep: BitCell,
+    keep: KeepValuesCell,
     drop: CommonRangeCell,
-    vtype: CommonRangeCell,
-    value: U64Cell,
     return_lookup: JTableLookupCell,
-    mtable_lookup_stack_read: MTableLookupCell,
-    mtable_lookup_stack_write: MTableLookupCell,
 }
 
 pub struct ReturnConfigBuilder {}
@@ -36,37 +26,29 @@ pub struct ReturnConfigBuilder {}
 impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ReturnConfigBuilder {
     fn configure(
         common: &mut EventTableCellAllocator<F>,
-        _constraint_builder: &mut ConstraintBuilder<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
     ) -> Box<dyn EventTableOpcodeConfig<F>> {
         let drop = common.alloc_common_range_value();
-        let keep = common.alloc_bit_value();
-        let vtype = common.alloc_common_range_value();
-        let value = common.alloc_u64();
+        let keep = KeepValuesCell::configure(common, constraint_builder, MAX_KEEP, 1, drop, |_| {
+            constant_from!(1)
+        });
         let return_lookup = common.alloc_jtable_lookup();
-        let mtable_lookup_stack_read = common.alloc_mtable_lookup();
-        let mtable_lookup_stack_write = common.alloc_mtable_lookup();
 
         Box::new(ReturnConfig {
             keep,
             drop,
-            vtype,
-            value,
             return_lookup,
-            mtable_lookup_stack_read,
-            mtable_lookup_stack_write,
         })
     }
 }
 
 impl<F: FieldExt> EventTableOpcodeConfig<F> for ReturnConfig {
     fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
-        constant!(bn_to_field(
-            &(BigUint::from(OpcodeClass::Return as u64) << OPCODE_CLASS_SHIFT)
-        )) + self.drop.expr(meta)
-            * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG0_SHIFT)))
-            + self.keep.expr(meta)
-                * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG1_SHIFT)))
-            + self.vtype.expr(meta)
+        encode_return(
+            self.drop.expr(meta),
+            self.keep.keep(meta),
+            self.keep.keep_types(meta),
+        )
     }
 
     fn assign(
@@ -76,53 +58,19 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ReturnConfig {
         entry: &EventTableEntry,
     ) -> Result<(), Error> {
         match &entry.step_info {
-            StepInfo::Return {
-                drop,
-                keep,
-                keep_values,
-                ..
-            } => {
-                assert!(keep.len() <= 1);
+            StepInfo::Return { drop, .. } => {
                 assert!(*drop < 1 << 16);
-                assert_eq!(keep.len(), keep_values.len());
 
                 self.drop.assign(ctx, F::from(*drop as u64))?;
-
-                if keep_values.len() == 0 {
-                    self.keep.assign(ctx, false)?;
-                } else {
-                    let vtype = VarType::from(keep[0]);
-                    self.keep.assign(ctx, true)?;
-                    self.vtype.assign(ctx, F::from(vtype as u64))?;
-                    self.value.assign(ctx, keep_values[0])?;
-                    self.mtable_lookup_stack_read.assign(
-                        ctx,
-                        &MemoryTableLookupEncode::encode_stack_read(
-                            BigUint::from(entry.eid),
-                            BigUint::from(1 as u64),
-                            BigUint::from(entry.sp + 1),
-                            BigUint::from(vtype as u16),
-                            BigUint::from(keep_values[0]),
-                        ),
-                    )?;
-                    self.mtable_lookup_stack_write.assign(
-                        ctx,
-                        &MemoryTableLookupEncode::encode_stack_write(
-                            BigUint::from(entry.eid),
-                            BigUint::from(2 as u64),
-                            BigUint::from(entry.sp + drop + 1),
-                            BigUint::from(vtype as u16),
-                            BigUint::from(keep_values[0]),
-                        ),
-                    )?;
-                }
+                self.keep.assign(ctx, entry, true, 1)?;
 
                 self.return_lookup.assign(
                     ctx,
                     &encode_frame_table_entry(
                         step.current.last_jump_eid.to_biguint().unwrap(),
                         step.next.last_jump_eid.to_biguint().unwrap(),
-                        step.current.fid.to_biguint().unwrap(),
+                        step.current.callee_fid.to_biguint().unwrap(),
+                        step.next.callee_fid.to_biguint().unwrap(),
                         step.next.fid.to_biguint().unwrap(),
                         step.next.iid.to_biguint().unwrap(),
                     ),
@@ -139,7 +87,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ReturnConfig {
     }
 
     fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(constant_from!(2) * self.keep.expr(meta))
+        Some(self.keep.mops(meta))
     }
 
     fn assigned_extra_mops(
@@ -149,14 +97,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ReturnConfig {
         entry: &EventTableEntry,
     ) -> u64 {
         match &entry.step_info {
-            StepInfo::Return { keep, .. } => {
-                if keep.len() > 0 {
-                    assert!(keep.len() == 1);
-                    2
-                } else {
-                    0
-                }
-            }
+            StepInfo::Return { keep, .. } => 2 * keep.len() as u64,
             _ => unreachable!(),
         }
     }
@@ -173,6 +114,14 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ReturnConfig {
         Some(common_config.next_last_jump_eid(meta))
     }
 
+    fn next_callee_fid(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        Some(common_config.next_callee_fid(meta))
+    }
+
     fn next_fid(
         &self,
         meta: &mut VirtualCells<'_, F>,
@@ -197,7 +146,8 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ReturnConfig {
         Some(JumpTableConfig::encode_lookup(
             common_config.last_jump_eid(meta),
             common_config.next_last_jump_eid(meta),
-            common_config.fid(meta),
+            common_config.callee_fid(meta),
+            common_config.next_callee_fid(meta),
             common_config.next_fid(meta),
             common_config.next_iid(meta),
         ))
@@ -209,30 +159,10 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ReturnConfig {
         item: MLookupItem,
         common: &EventTableCommonConfig<F>,
     ) -> Option<Expression<F>> {
-        match item {
-            MLookupItem::First => Some(
-                self.keep.expr(meta)
-                    * MemoryTableLookupEncode::encode_stack_read(
-                        common.eid(meta),
-                        constant_from!(1),
-                        common.sp(meta) + constant_from!(1),
-                        self.vtype.expr(meta),
-                        self.value.expr(meta),
-                    ),
-            ),
-            MLookupItem::Second => Some(
-                self.keep.expr(meta)
-                    * MemoryTableLookupEncode::encode_stack_write(
-                        common.eid(meta),
-                        constant_from!(2),
-                        common.sp(meta) + self.drop.expr(meta) + constant_from!(1),
-                        self.vtype.expr(meta),
-                        self.value.expr(meta),
-                    ),
-            ),
-            MLookupItem::Third => None,
-            _ => None,
-        }
+        let drop = self.drop.expr(meta);
+
+        self.keep
+            .mtable_lookup(meta, item as usize, common, constant_from!(1), 1, drop)
     }
 }
 
@@ -282,4 +212,55 @@ mod tests {
 
         test_circuit_noexternal(textual_repr).unwrap();
     }
+
+    #[test]
+    fn test_return_multi_value_ok() {
+        let textual_repr = r#"
+            (module
+                (func $multi (result i64 i32 i64)
+                  (i32.const 0)
+                  (i64.const 1)
+                  (i32.const 2)
+                  (i64.const 3)
+                  return
+                )
+
+                (func (export "test")
+                  (call $multi)
+                  drop
+                  drop
+                  drop
+                )
+               )
+            "#;
+
+        test_circuit_noexternal(textual_repr).unwrap();
+    }
+
+    #[test]
+    fn test_return_keep_moved_ok() {
+        let textual_repr = r#"
+            (module
+                (func $multi (result i32 i64 i32 i64 i32)
+                  (i32.const 0)
+                  (i64.const 1)
+                  (i32.const 2)
+                  (i64.const 3)
+                  (i32.const 4)
+                  return
+                )
+
+                (func (export "test")
+                  (call $multi)
+                  drop
+                  drop
+                  drop
+                  drop
+                  drop
+                )
+               )
+            "#;
+
+        test_circuit_noexternal(textual_repr).unwrap();
+    }
 }
@@ -0,0 +1,175 @@
+use super::keep_values::KeepValuesCell;
+use super::*;
+use crate::circuits::utils::Context;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Error, Expression, VirtualCells},
+};
+use specs::{encode::opcode::encode_return_call, etable::EventTableEntry, step::StepInfo};
+
+const MAX_KEEP: usize = OpcodeClass::ReturnCall.max_keep();
+
+pub struct ReturnCallConfig {
+    index: CommonRangeCell,
+    keep: KeepValuesCell,
+    drop: CommonRangeCell,
+}
+
+pub struct ReturnCallConfigBuilder {}
+
+impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ReturnCallConfigBuilder {
+    fn configure(
+        common: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let index = common.alloc_common_range_value();
+        let drop = common.alloc_common_range_value();
+        let keep = KeepValuesCell::configure(common, constraint_builder, MAX_KEEP, 1, drop, |_| {
+            constant_from!(1)
+        });
+
+        Box::new(ReturnCallConfig { index, keep, drop })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for ReturnCallConfig {
+    fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        encode_return_call(
+            self.drop.expr(meta),
+            self.keep.keep(meta),
+            self.keep.keep_types(meta),
+            self.index.expr(meta),
+        )
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        _step: &StepStatus,
+        entry: &EventTableEntry,
+    ) -> Result<(), Error> {
+        match &entry.step_info {
+            StepInfo::ReturnCall { index, drop, .. } => {
+                assert!(*drop < 1 << 16);
+
+                self.index.assign(ctx, F::from(*index as u64))?;
+                self.drop.assign(ctx, F::from(*drop as u64))?;
+                self.keep.assign(ctx, entry, true, 1)?;
+
+                Ok(())
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    fn sp_diff(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(self.drop.expr(meta))
+    }
+
+    fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(self.keep.mops(meta))
+    }
+
+    fn assigned_extra_mops(
+        &self,
+        _ctx: &mut Context<'_, F>,
+        _step: &StepStatus,
+        entry: &EventTableEntry,
+    ) -> u64 {
+        match &entry.step_info {
+            StepInfo::ReturnCall { keep, .. } => 2 * keep.len() as u64,
+            _ => unreachable!(),
+        }
+    }
+
+    fn next_fid(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        _common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        Some(self.index.expr(meta))
+    }
+
+    fn next_iid(
+        &self,
+        _meta: &mut VirtualCells<'_, F>,
+        _common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        Some(constant_from!(0))
+    }
+
+    fn mtable_lookup(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        item: MLookupItem,
+        common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        let drop = self.drop.expr(meta);
+
+        self.keep.mtable_lookup(
+            meta,
+            item as usize,
+            common_config,
+            constant_from!(1),
+            1,
+            drop,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::test_circuit_tail_call;
+
+    #[test]
+    fn test_return_call() {
+        let textual_repr = r#"
+        (module
+            (func $foo (param i32) (result i32)
+              (local.get 0)
+            )
+            (func $bar (param i32 i64) (result i32)
+              (local i32)
+              (i32.const 1)
+              (local.get 0)
+              return_call $foo
+            )
+            (func (export "test")
+              (i32.const 0)
+              (i64.const 0)
+              call $bar
+              drop
+            )
+           )
+        "#;
+
+        test_circuit_tail_call(textual_repr).unwrap()
+    }
+
+    #[test]
+    fn test_return_call_recursion() {
+        let textual_repr = r#"
+        (module
+            (func $count (param i32 i64) (result i64)
+              (if (result i64) (i32.eqz (local.get 0))
+                (then (local.get 1))
+                (else
+                  (i32.sub (local.get 0) (i32.const 1))
+                  (i64.add (local.get 1) (i64.const 1))
+                  return_call $count
+                )
+              )
+            )
+            (func (export "test") (result i64)
+              (i32.const 100)
+              (i64.const 0)
+              return_call $count
+            )
+           )
+        "#;
+
+        test_circuit_tail_call(textual_repr).unwrap()
+    }
+}
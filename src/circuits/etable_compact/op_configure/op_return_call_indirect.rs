@@ -0,0 +1,268 @@
+use super::keep_values::KeepValuesCell;
+use super::table::TableLayoutCell;
+use super::*;
+use crate::{
+    circuits::{mtable_compact::encode::MemoryTableLookupEncode, utils::Context},
+    constant,
+};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Error, Expression, VirtualCells},
+};
+use specs::{
+    encode::{br_table::encode_func_ref, opcode::encode_return_call_indirect},
+    etable::EventTableEntry,
+    mtable::VarType,
+    step::StepInfo,
+};
+
+const MAX_KEEP: usize = OpcodeClass::ReturnCallIndirect.max_keep();
+
+pub struct ReturnCallIndirectConfig {
+    type_index: CommonRangeCell,
+    func_index: CommonRangeCell,
+    offset: CommonRangeCell,
+    offset_helper: CommonRangeCell,
+    table: TableLayoutCell,
+    keep: KeepValuesCell,
+    drop: CommonRangeCell,
+    stack_read_lookup: MTableLookupCell,
+    elem_read_lookup: MTableLookupCell,
+}
+
+pub struct ReturnCallIndirectConfigBuilder {}
+
+impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ReturnCallIndirectConfigBuilder {
+    fn configure(
+        common: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let type_index = common.alloc_common_range_value();
+        let offset = common.alloc_common_range_value();
+        let offset_helper = common.alloc_common_range_value();
+        let func_index = common.alloc_common_range_value();
+        let drop = common.alloc_common_range_value();
+        let table = TableLayoutCell::alloc(common);
+
+        let stack_read_lookup = common.alloc_mtable_lookup();
+        let keep = KeepValuesCell::configure(common, constraint_builder, MAX_KEEP, 2, drop, |_| {
+            constant_from!(1)
+        });
+        let elem_read_lookup = common.alloc_mtable_lookup();
+
+        let capacity = table.capacity;
+
+        constraint_builder.push(
+            "return_call_indirect: offset < capacity",
+            Box::new(move |meta| {
+                vec![
+                    offset.expr(meta) + offset_helper.expr(meta) + constant_from!(1)
+                        - capacity.expr(meta),
+                ]
+            }),
+        );
+
+        Box::new(ReturnCallIndirectConfig {
+            type_index,
+            func_index,
+            offset,
+            offset_helper,
+            table,
+            keep,
+            drop,
+            stack_read_lookup,
+            elem_read_lookup,
+        })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for ReturnCallIndirectConfig {
+    fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        encode_return_call_indirect(
+            self.drop.expr(meta),
+            self.keep.keep(meta),
+            self.keep.keep_types(meta),
+            self.type_index.expr(meta),
+            self.table.table_idx.expr(meta),
+        )
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step_info: &StepStatus,
+        entry: &EventTableEntry,
+    ) -> Result<(), Error> {
+        match &entry.step_info {
+            StepInfo::ReturnCallIndirect {
+                table_index,
+                type_index,
+                offset,
+                func_index,
+                drop,
+                keep,
+                ..
+            } => {
+                assert!(*drop < 1 << 16);
+
+                let layout = &step_info.table_layouts[*table_index as usize];
+
+                self.table.assign(ctx, layout)?;
+                self.type_index.assign(ctx, F::from(*type_index as u64))?;
+                self.offset.assign(ctx, F::from(*offset as u64))?;
+                self.offset_helper
+                    .assign(ctx, F::from((layout.capacity - 1 - *offset) as u64))?;
+                self.func_index.assign(ctx, F::from(*func_index as u64))?;
+                self.drop.assign(ctx, F::from(*drop as u64))?;
+
+                self.stack_read_lookup.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_stack_read(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(1 as u64),
+                        BigUint::from(step_info.current.sp + 1),
+                        BigUint::from(VarType::I32 as u16),
+                        BigUint::from(*offset),
+                    ),
+                )?;
+
+                self.keep.assign(ctx, entry, true, 2)?;
+
+                self.elem_read_lookup.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_table_read(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(2 + 2 * keep.len() as u64),
+                        BigUint::from(layout.elem_offset(*offset)),
+                        encode_func_ref(BigUint::from(*type_index), BigUint::from(*func_index)),
+                    ),
+                )?;
+
+                Ok(())
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    fn sp_diff(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant!(F::one()) + self.drop.expr(meta))
+    }
+
+    fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(2) + self.keep.mops(meta))
+    }
+
+    fn assigned_extra_mops(
+        &self,
+        _ctx: &mut Context<'_, F>,
+        _step: &StepStatus,
+        entry: &EventTableEntry,
+    ) -> u64 {
+        match &entry.step_info {
+            StepInfo::ReturnCallIndirect { keep, .. } => 2 * keep.len() as u64,
+            _ => unreachable!(),
+        }
+    }
+
+    fn mtable_lookup(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        item: MLookupItem,
+        common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        match item {
+            MLookupItem::First => Some(MemoryTableLookupEncode::encode_stack_read(
+                common_config.eid(meta),
+                constant_from!(1),
+                common_config.sp(meta) + constant_from!(1),
+                constant_from!(VarType::I32),
+                self.offset.expr(meta),
+            )),
+            MLookupItem::Six => {
+                // The element is read after the kept values are moved.
+                let offset = self.offset.expr(meta);
+
+                Some(MemoryTableLookupEncode::encode_table_read(
+                    common_config.eid(meta),
+                    constant_from!(2) + self.keep.mops(meta),
+                    self.table.elem_offset(meta, offset),
+                    encode_func_ref(self.type_index.expr(meta), self.func_index.expr(meta)),
+                ))
+            }
+            _ => {
+                let drop = self.drop.expr(meta);
+
+                self.keep.mtable_lookup(
+                    meta,
+                    item as usize - 1,
+                    common_config,
+                    constant_from!(1),
+                    2,
+                    drop,
+                )
+            }
+        }
+    }
+
+    fn brtable_lookup(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        _common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        Some(self.table.encode(meta))
+    }
+
+    fn next_fid(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        _common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        Some(self.func_index.expr(meta))
+    }
+
+    fn next_iid(
+        &self,
+        _meta: &mut VirtualCells<'_, F>,
+        _common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        Some(constant_from!(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::test_circuit_tail_call;
+
+    #[test]
+    fn test_return_call_indirect() {
+        let textual_repr = r#"
+            (module
+                (type (;0;) (func (param i32 i32) (result i32)))
+                (func (;0;) (type 0) (param i32 i32) (result i32)
+                    local.get 0
+                    local.get 1
+                    i32.add)
+                (func (;1;) (type 0) (param i32 i32) (result i32)
+                    local.get 0
+                    local.get 1
+                    i32.sub)
+                (func (;2;) (param i64) (result i32)
+                    i32.const 1
+                    i32.const 2
+                    i32.const 1
+                    return_call_indirect (type 0)
+                )
+                (func (;3;) (result i32)
+                    i64.const 0
+                    call 2)
+                (table (;0;) 2 2 funcref)
+                (export "test" (func 3))
+                (elem (;0;) (i32.const 0) func 0 1)
+            )
+        "#;
+
+        test_circuit_tail_call(textual_repr).unwrap()
+    }
+}
@@ -30,6 +30,7 @@ pub struct StoreConfig {
     store_end_block_inner_offset: CommonRangeCell,
     store_end_block_inner_offset_helper: CommonRangeCell,
 
+    // Holds the distance past the allocated memory if the store traps.
     load_value1: U64OnU8Cell,
     load_value2: U64OnU8Cell,
     store_value1: U64OnU8Cell,
@@ -58,6 +59,8 @@ pub struct StoreConfig {
     lookup_pow: PowTableLookupCell,
 
     address_within_allocated_pages_helper: CommonRangeCell,
+
+    is_trap: BitCell,
 }
 
 pub struct StoreConfigBuilder {}
@@ -107,6 +110,8 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for StoreConfigBuilder {
         let current_memory_page_size = common.allocated_memory_pages_cell();
         let address_within_allocated_pages_helper = common.alloc_common_range_value();
 
+        let is_trap = common.alloc_bit_value();
+
         constraint_builder.push(
             "op_store start end offset range",
             Box::new(move |meta| {
@@ -143,9 +148,10 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for StoreConfigBuilder {
             "op_store start store_base",
             Box::new(move |meta| {
                 vec![
-                    store_base.expr(meta) + opcode_store_offset.expr(meta)
+                    (store_base.expr(meta) + opcode_store_offset.expr(meta)
                         - store_start_block_index.expr(meta) * constant_from!(8)
-                        - store_start_block_inner_offset.expr(meta),
+                        - store_start_block_inner_offset.expr(meta))
+                        * (constant_from!(1) - is_trap.expr(meta)),
                 ]
             }),
         );
@@ -248,7 +254,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for StoreConfigBuilder {
                             * mask_bits[i as usize + 8].expr(meta);
                 }
 
-                vec![acc]
+                vec![acc * (constant_from!(1) - is_trap.expr(meta))]
             }),
         );
 
@@ -275,7 +281,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for StoreConfigBuilder {
                             * (constant_from!(1) - mask_bits[i as usize + 8].expr(meta));
                 }
 
-                vec![acc]
+                vec![acc * (constant_from!(1) - is_trap.expr(meta))]
             }),
         );
 
@@ -292,7 +298,27 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for StoreConfigBuilder {
                         + opcode_store_offset.expr(meta)
                         + len
                         + address_within_allocated_pages_helper.expr(meta)
-                        - current_memory_page_size.expr(meta) * constant_from!(WASM_PAGE_SIZE)),
+                        - current_memory_page_size.expr(meta) * constant_from!(WASM_PAGE_SIZE))
+                        * (constant_from!(1) - is_trap.expr(meta)),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_store out of bounds",
+            Box::new(move |meta| {
+                let len = constant_from!(1)
+                    + is_two_bytes.expr(meta) * constant_from!(1)
+                    + is_four_bytes.expr(meta) * constant_from!(3)
+                    + is_eight_bytes.expr(meta) * constant_from!(7);
+
+                // The last byte is at or after the end of the allocated memory
+                vec![
+                    (store_base.expr(meta) + opcode_store_offset.expr(meta) + len
+                        - constant_from!(1)
+                        - load_value1.expr(meta)
+                        - current_memory_page_size.expr(meta) * constant_from!(WASM_PAGE_SIZE))
+                        * is_trap.expr(meta),
                 ]
             }),
         );
@@ -328,6 +354,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for StoreConfigBuilder {
             load_value1,
             load_value2,
             address_within_allocated_pages_helper,
+            is_trap,
         })
     }
 }
@@ -498,6 +525,84 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for StoreConfig {
                 Ok(())
             }
 
+            StepInfo::StoreOutOfBounds {
+                vtype,
+                store_size,
+                offset,
+                raw_address,
+                value,
+            } => {
+                self.opcode_store_offset
+                    .assign(ctx, F::from(offset as u64))?;
+
+                // No memory block is accessed, the block cells describe [0, len).
+                let len = store_size.byte_size();
+
+                self.store_start_block_inner_offset_helper
+                    .assign(ctx, F::from(7))?;
+                self.store_end_block_inner_offset
+                    .assign(ctx, F::from(len - 1))?;
+                self.store_end_block_inner_offset_helper
+                    .assign(ctx, F::from(8 - len))?;
+
+                let bits = bits_of_offset_len(0, len);
+                for i in 0..16 {
+                    self.mask_bits[i].assign(ctx, (bits >> i) & 1 == 1)?;
+                }
+                self.offset_modulus.assign(ctx, 1)?;
+                self.store_raw_value.assign(ctx, value)?;
+                self.store_base.assign(ctx, raw_address.into())?;
+                self.store_wrapped_value.assign(
+                    ctx,
+                    F::from(if len == 8 {
+                        value
+                    } else {
+                        value & ((1u64 << (len * 8)) - 1)
+                    }),
+                )?;
+
+                self.is_one_byte.assign(ctx, len == 1)?;
+                self.is_two_bytes.assign(ctx, len == 2)?;
+                self.is_four_bytes.assign(ctx, len == 4)?;
+                self.is_eight_bytes.assign(ctx, len == 8)?;
+                self.vtype.assign(ctx, F::from(vtype as u64))?;
+
+                self.lookup_stack_read_val.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_stack_read(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(1 as u64),
+                        BigUint::from(step_info.current.sp + 1),
+                        BigUint::from(vtype as u64),
+                        BigUint::from(value),
+                    ),
+                )?;
+
+                self.lookup_stack_read_pos.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_stack_read(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(2 as u64),
+                        BigUint::from(step_info.current.sp + 2),
+                        BigUint::from(VarType::I32 as u16),
+                        BigUint::from(raw_address),
+                    ),
+                )?;
+
+                self.is_trap.assign(ctx, true)?;
+                self.load_value1.assign(
+                    ctx,
+                    raw_address as u64 + offset as u64 + len
+                        - 1
+                        - step_info.current.allocated_memory_pages as u64 * WASM_PAGE_SIZE,
+                )?;
+
+                self.lookup_offset_len_bits.assign(ctx, 0, len)?;
+                self.lookup_pow.assign(ctx, 0)?;
+
+                Ok(())
+            }
+
             _ => unreachable!(),
         }
     }
@@ -509,7 +614,11 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for StoreConfig {
     fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
         let cross_block =
             self.store_end_block_index.expr(meta) - self.store_start_block_index.expr(meta);
-        Some(constant_from!(4) + cross_block * constant_from!(2))
+        Some(
+            (constant_from!(4) + cross_block * constant_from!(2))
+                * (constant_from!(1) - self.is_trap.expr(meta))
+                + constant_from!(2) * self.is_trap.expr(meta),
+        )
     }
 
     fn assigned_extra_mops(
@@ -532,6 +641,8 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for StoreConfig {
                     0
                 }
             }
+            // Only the value and the address are read
+            StepInfo::StoreOutOfBounds { .. } => 2,
             _ => unreachable!(),
         }
     }
@@ -544,6 +655,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for StoreConfig {
     ) -> Option<Expression<F>> {
         let cross_block =
             self.store_end_block_index.expr(meta) - self.store_start_block_index.expr(meta);
+        let no_trap = constant_from!(1) - self.is_trap.expr(meta);
 
         match item {
             MLookupItem::First => Some(MemoryTableLookupEncode::encode_stack_read(
@@ -560,20 +672,24 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for StoreConfig {
                 constant_from!(VarType::I32),
                 self.store_base.expr(meta),
             )),
-            MLookupItem::Third => Some(MemoryTableLookupEncode::encode_memory_load(
-                common_config.eid(meta),
-                constant_from!(3),
-                self.store_start_block_index.expr(meta),
-                constant_from!(VarType::I64),
-                self.load_value1.expr(meta),
-            )),
-            MLookupItem::Fourth => Some(MemoryTableLookupEncode::encode_memory_store(
-                common_config.eid(meta),
-                constant_from!(4),
-                self.store_start_block_index.expr(meta),
-                constant_from!(VarType::I64),
-                self.store_value1.expr(meta),
-            )),
+            MLookupItem::Third => Some(
+                MemoryTableLookupEncode::encode_memory_load(
+                    common_config.eid(meta),
+                    constant_from!(3),
+                    self.store_start_block_index.expr(meta),
+                    constant_from!(VarType::I64),
+                    self.load_value1.expr(meta),
+                ) * no_trap.clone(),
+            ),
+            MLookupItem::Fourth => Some(
+                MemoryTableLookupEncode::encode_memory_store(
+                    common_config.eid(meta),
+                    constant_from!(4),
+                    self.store_start_block_index.expr(meta),
+                    constant_from!(VarType::I64),
+                    self.store_value1.expr(meta),
+                ) * no_trap.clone(),
+            ),
             MLookupItem::Fifth => Some(
                 MemoryTableLookupEncode::encode_memory_load(
                     common_config.eid(meta),
@@ -581,7 +697,8 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for StoreConfig {
                     self.store_end_block_index.expr(meta),
                     constant_from!(VarType::I64),
                     self.load_value2.expr(meta),
-                ) * cross_block.clone(),
+                ) * cross_block.clone()
+                    * no_trap.clone(),
             ),
             MLookupItem::Six => Some(
                 MemoryTableLookupEncode::encode_memory_store(
@@ -590,15 +707,25 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for StoreConfig {
                     self.store_end_block_index.expr(meta),
                     constant_from!(VarType::I64),
                     self.store_value2.expr(meta),
-                ) * cross_block.clone(),
+                ) * cross_block.clone()
+                    * no_trap,
             ),
         }
     }
+
+    fn is_trap(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(self.is_trap.expr(meta))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::test::test_circuit_noexternal;
+    use crate::test::{test_circuit_noexternal, test_circuit_noexternal_patched, StepPatch};
+    use specs::{
+        itable::Opcode,
+        mtable::{MemoryStoreSize, VarType},
+        step::StepInfo,
+    };
 
     #[test]
     fn test_store_normal() {
@@ -677,4 +804,63 @@ mod tests {
 
         test_circuit_noexternal(textual_repr).unwrap();
     }
+
+    #[test]
+    fn test_store_out_of_bounds_trap() {
+        let textual_repr = r#"
+                (module
+                    (memory $0 1)
+                    (func (export "test")
+                      (i32.const 65534)
+                      (i32.const 0x12345678)
+                      (i32.store offset=0)
+                    )
+                   )
+                "#;
+
+        // The wasmi tracer does not emit the trapping store, i32.add stands in.
+        test_circuit_noexternal_patched(
+            textual_repr,
+            &[("(i32.store offset=0)", "(i32.add) (drop)")],
+            &[],
+            |_, entry| match entry.step_info {
+                StepInfo::I32BinOp { left, right, .. } => StepPatch::Replace(
+                    Opcode::Store {
+                        offset: 0,
+                        vtype: VarType::I32,
+                        size: MemoryStoreSize::Byte32,
+                    },
+                    StepInfo::StoreOutOfBounds {
+                        vtype: VarType::I32,
+                        store_size: MemoryStoreSize::Byte32,
+                        offset: 0,
+                        raw_address: left as u32,
+                        value: right as u32 as u64,
+                    },
+                ),
+                _ => StepPatch::Keep,
+            },
+        )
+        .unwrap();
+    }
+
+    // The block index of the address is beyond 2^(K - 1) with the default K.
+    #[test]
+    fn test_store_high_address() {
+        let textual_repr = r#"
+                (module
+                    (memory $0 17)
+                    (func (export "test")
+                      (i32.const 0x100008)
+                      (i64.const 0x432134214)
+                      (i64.store offset=0)
+                      (i32.const 0x100008)
+                      (i64.load offset=0)
+                      (drop)
+                    )
+                   )
+                "#;
+
+        test_circuit_noexternal(textual_repr).unwrap();
+    }
 }
@@ -0,0 +1,338 @@
+use super::table::TableLayoutCell;
+use super::*;
+use crate::{
+    circuits::{mtable_compact::encode::MemoryTableLookupEncode, utils::Context},
+    constant,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Error, Expression, VirtualCells},
+};
+use specs::{
+    encode::opcode::encode_table_op, etable::EventTableEntry, itable::OpcodeClass, mtable::VarType,
+    step::StepInfo,
+};
+
+/*
+ * Slots beyond the size of a table are null, so growing it only writes the
+ * new size. Growing with a non-null init value is not supported, the runtime
+ * rejects such executions before they reach the circuit.
+ */
+pub struct TableGrowConfig {
+    table: TableLayoutCell,
+    delta: U64Cell,
+    init: U64Cell,
+    size: CommonRangeCell,
+    success: BitCell,
+    capacity_diff: U64Cell,
+    result: U64Cell,
+    lookup_stack_read_delta: MTableLookupCell,
+    lookup_stack_read_init: MTableLookupCell,
+    lookup_size_read: MTableLookupCell,
+    lookup_size_write: MTableLookupCell,
+    lookup_stack_write: MTableLookupCell,
+}
+
+pub struct TableGrowConfigBuilder {}
+
+impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for TableGrowConfigBuilder {
+    fn configure(
+        common: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let table = TableLayoutCell::alloc(common);
+        let delta = common.alloc_u64();
+        let init = common.alloc_u64();
+        let size = common.alloc_common_range_value();
+        let success = common.alloc_bit_value();
+        let capacity_diff = common.alloc_u64();
+        let result = common.alloc_u64();
+
+        let lookup_stack_read_delta = common.alloc_mtable_lookup();
+        let lookup_stack_read_init = common.alloc_mtable_lookup();
+        let lookup_size_read = common.alloc_mtable_lookup();
+        let lookup_size_write = common.alloc_mtable_lookup();
+        let lookup_stack_write = common.alloc_mtable_lookup();
+
+        let capacity = table.capacity;
+
+        constraint_builder.push(
+            "table_grow: init is null",
+            Box::new(move |meta| vec![init.expr(meta)]),
+        );
+
+        constraint_builder.push(
+            "table_grow: succeed iff the new size fits in the capacity",
+            Box::new(move |meta| {
+                vec![
+                    success.expr(meta)
+                        * (size.expr(meta) + delta.expr(meta) + capacity_diff.expr(meta)
+                            - capacity.expr(meta)),
+                    (constant_from!(1) - success.expr(meta))
+                        * (size.expr(meta) + delta.expr(meta)
+                            - capacity.expr(meta)
+                            - constant_from!(1)
+                            - capacity_diff.expr(meta)),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "table_grow: return value",
+            Box::new(move |meta| {
+                vec![
+                    (constant_from!(1) - success.expr(meta))
+                        * (result.expr(meta) - constant_from!(u32::MAX)),
+                    success.expr(meta) * (result.expr(meta) - size.expr(meta)),
+                ]
+            }),
+        );
+
+        Box::new(TableGrowConfig {
+            table,
+            delta,
+            init,
+            size,
+            success,
+            capacity_diff,
+            result,
+            lookup_stack_read_delta,
+            lookup_stack_read_init,
+            lookup_size_read,
+            lookup_size_write,
+            lookup_stack_write,
+        })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for TableGrowConfig {
+    fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        encode_table_op(OpcodeClass::TableGrow, self.table.table_idx.expr(meta))
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step_info: &StepStatus,
+        entry: &EventTableEntry,
+    ) -> Result<(), Error> {
+        match &entry.step_info {
+            StepInfo::TableGrow {
+                table_index,
+                delta,
+                init,
+                size,
+                result,
+            } => {
+                let layout = &step_info.table_layouts[*table_index as usize];
+                let success = *result != -1;
+                let new_size = *size as u64 + *delta as u64;
+
+                self.table.assign(ctx, layout)?;
+                self.delta.assign(ctx, *delta as u64)?;
+                self.init.assign(ctx, *init)?;
+                self.size.assign(ctx, F::from(*size as u64))?;
+                self.success.assign(ctx, success)?;
+                self.result.assign(ctx, *result as u32 as u64)?;
+                if success {
+                    self.capacity_diff
+                        .assign(ctx, layout.capacity as u64 - new_size)?;
+                } else {
+                    self.capacity_diff
+                        .assign(ctx, new_size - layout.capacity as u64 - 1)?;
+                }
+
+                self.lookup_stack_read_delta.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_stack_read(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(1 as u64),
+                        BigUint::from(step_info.current.sp + 1),
+                        BigUint::from(VarType::I32 as u16),
+                        BigUint::from(*delta),
+                    ),
+                )?;
+
+                self.lookup_stack_read_init.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_stack_read(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(2 as u64),
+                        BigUint::from(step_info.current.sp + 2),
+                        BigUint::from(VarType::I64 as u16),
+                        BigUint::from(*init),
+                    ),
+                )?;
+
+                self.lookup_size_read.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_table_read(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(3 as u64),
+                        BigUint::from(layout.base),
+                        BigUint::from(*size),
+                    ),
+                )?;
+
+                if success {
+                    self.lookup_size_write.assign(
+                        ctx,
+                        &MemoryTableLookupEncode::encode_table_write(
+                            BigUint::from(step_info.current.eid),
+                            BigUint::from(4 as u64),
+                            BigUint::from(layout.base),
+                            BigUint::from(new_size),
+                        ),
+                    )?;
+                }
+
+                self.lookup_stack_write.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_stack_write(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(4 + success as u64),
+                        BigUint::from(step_info.current.sp + 2),
+                        BigUint::from(VarType::I32 as u16),
+                        BigUint::from(*result as u32),
+                    ),
+                )?;
+
+                Ok(())
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    fn sp_diff(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant!(F::one()))
+    }
+
+    fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(4) + self.success.expr(meta))
+    }
+
+    fn assigned_extra_mops(
+        &self,
+        _ctx: &mut Context<'_, F>,
+        _step: &StepStatus,
+        entry: &EventTableEntry,
+    ) -> u64 {
+        match &entry.step_info {
+            StepInfo::TableGrow { result, .. } => (*result != -1) as u64,
+            _ => unreachable!(),
+        }
+    }
+
+    fn mtable_lookup(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        item: MLookupItem,
+        common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        match item {
+            MLookupItem::First => Some(MemoryTableLookupEncode::encode_stack_read(
+                common_config.eid(meta),
+                constant_from!(1),
+                common_config.sp(meta) + constant_from!(1),
+                constant_from!(VarType::I32),
+                self.delta.expr(meta),
+            )),
+            MLookupItem::Second => Some(MemoryTableLookupEncode::encode_stack_read(
+                common_config.eid(meta),
+                constant_from!(2),
+                common_config.sp(meta) + constant_from!(2),
+                constant_from!(VarType::I64),
+                self.init.expr(meta),
+            )),
+            MLookupItem::Third => Some(MemoryTableLookupEncode::encode_table_read(
+                common_config.eid(meta),
+                constant_from!(3),
+                self.table.size_offset(meta),
+                self.size.expr(meta),
+            )),
+            MLookupItem::Fourth => Some(
+                self.success.expr(meta)
+                    * MemoryTableLookupEncode::encode_table_write(
+                        common_config.eid(meta),
+                        constant_from!(4),
+                        self.table.size_offset(meta),
+                        self.size.expr(meta) + self.delta.expr(meta),
+                    ),
+            ),
+            MLookupItem::Fifth => Some(MemoryTableLookupEncode::encode_stack_write(
+                common_config.eid(meta),
+                constant_from!(4) + self.success.expr(meta),
+                common_config.sp(meta) + constant_from!(2),
+                constant_from!(VarType::I32),
+                self.result.expr(meta),
+            )),
+            _ => None,
+        }
+    }
+
+    fn brtable_lookup(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        _common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        Some(self.table.encode(meta))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::{run_noexternal, test_circuit_noexternal};
+
+    #[test]
+    fn test_table_grow() {
+        let textual_repr = r#"
+                (module
+                    (table 1 3 funcref)
+
+                    (func (export "test")
+                        (table.grow 0 (ref.null func) (i32.const 2))
+                        (drop)
+                        (table.get 0 (i32.const 2))
+                        (drop)
+                    )
+                   )
+                "#;
+
+        test_circuit_noexternal(textual_repr).unwrap()
+    }
+
+    #[test]
+    fn test_table_grow_fail() {
+        let textual_repr = r#"
+                (module
+                    (table 1 2 funcref)
+
+                    (func (export "test")
+                        (table.grow 0 (ref.null func) (i32.const 2))
+                        (drop)
+                    )
+                   )
+                "#;
+
+        test_circuit_noexternal(textual_repr).unwrap()
+    }
+
+    #[test]
+    fn test_table_grow_non_null_rejected() {
+        let textual_repr = r#"
+                (module
+                    (table 1 3 funcref)
+                    (func $f)
+                    (elem declare func $f)
+
+                    (func (export "test")
+                        (table.grow 0 (ref.func $f) (i32.const 1))
+                        (drop)
+                    )
+                   )
+                "#;
+
+        assert!(run_noexternal(textual_repr).is_err());
+    }
+}
@@ -0,0 +1,225 @@
+use super::table::TableLayoutCell;
+use super::*;
+use crate::circuits::{mtable_compact::encode::MemoryTableLookupEncode, utils::Context};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Error, Expression, VirtualCells},
+};
+use specs::{
+    encode::opcode::encode_table_op, etable::EventTableEntry, itable::OpcodeClass, mtable::VarType,
+    step::StepInfo,
+};
+
+pub struct TableSetConfig {
+    table: TableLayoutCell,
+    index: CommonRangeCell,
+    index_helper: CommonRangeCell,
+    size: CommonRangeCell,
+    value: U64Cell,
+    lookup_stack_read_value: MTableLookupCell,
+    lookup_stack_read_index: MTableLookupCell,
+    lookup_size_read: MTableLookupCell,
+    lookup_elem_write: MTableLookupCell,
+}
+
+pub struct TableSetConfigBuilder {}
+
+impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for TableSetConfigBuilder {
+    fn configure(
+        common: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let table = TableLayoutCell::alloc(common);
+        let index = common.alloc_common_range_value();
+        let index_helper = common.alloc_common_range_value();
+        let size = common.alloc_common_range_value();
+        let value = common.alloc_u64();
+
+        let lookup_stack_read_value = common.alloc_mtable_lookup();
+        let lookup_stack_read_index = common.alloc_mtable_lookup();
+        let lookup_size_read = common.alloc_mtable_lookup();
+        let lookup_elem_write = common.alloc_mtable_lookup();
+
+        constraint_builder.push(
+            "table_set: index < size",
+            Box::new(move |meta| {
+                vec![
+                    index.expr(meta) + index_helper.expr(meta) + constant_from!(1)
+                        - size.expr(meta),
+                ]
+            }),
+        );
+
+        Box::new(TableSetConfig {
+            table,
+            index,
+            index_helper,
+            size,
+            value,
+            lookup_stack_read_value,
+            lookup_stack_read_index,
+            lookup_size_read,
+            lookup_elem_write,
+        })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for TableSetConfig {
+    fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        encode_table_op(OpcodeClass::TableSet, self.table.table_idx.expr(meta))
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step_info: &StepStatus,
+        entry: &EventTableEntry,
+    ) -> Result<(), Error> {
+        match &entry.step_info {
+            StepInfo::TableSet {
+                table_index,
+                index,
+                size,
+                value,
+            } => {
+                let layout = &step_info.table_layouts[*table_index as usize];
+
+                self.table.assign(ctx, layout)?;
+                self.index.assign(ctx, F::from(*index as u64))?;
+                self.index_helper
+                    .assign(ctx, F::from((*size - 1 - *index) as u64))?;
+                self.size.assign(ctx, F::from(*size as u64))?;
+                self.value.assign(ctx, *value)?;
+
+                self.lookup_stack_read_value.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_stack_read(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(1 as u64),
+                        BigUint::from(step_info.current.sp + 1),
+                        BigUint::from(VarType::I64 as u16),
+                        BigUint::from(*value),
+                    ),
+                )?;
+
+                self.lookup_stack_read_index.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_stack_read(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(2 as u64),
+                        BigUint::from(step_info.current.sp + 2),
+                        BigUint::from(VarType::I32 as u16),
+                        BigUint::from(*index),
+                    ),
+                )?;
+
+                self.lookup_size_read.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_table_read(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(3 as u64),
+                        BigUint::from(layout.base),
+                        BigUint::from(*size),
+                    ),
+                )?;
+
+                self.lookup_elem_write.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_table_write(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(4 as u64),
+                        BigUint::from(layout.elem_offset(*index)),
+                        BigUint::from(*value),
+                    ),
+                )?;
+
+                Ok(())
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    fn sp_diff(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(2))
+    }
+
+    fn mops(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(4))
+    }
+
+    fn mtable_lookup(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        item: MLookupItem,
+        common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        match item {
+            MLookupItem::First => Some(MemoryTableLookupEncode::encode_stack_read(
+                common_config.eid(meta),
+                constant_from!(1),
+                common_config.sp(meta) + constant_from!(1),
+                constant_from!(VarType::I64),
+                self.value.expr(meta),
+            )),
+            MLookupItem::Second => Some(MemoryTableLookupEncode::encode_stack_read(
+                common_config.eid(meta),
+                constant_from!(2),
+                common_config.sp(meta) + constant_from!(2),
+                constant_from!(VarType::I32),
+                self.index.expr(meta),
+            )),
+            MLookupItem::Third => Some(MemoryTableLookupEncode::encode_table_read(
+                common_config.eid(meta),
+                constant_from!(3),
+                self.table.size_offset(meta),
+                self.size.expr(meta),
+            )),
+            MLookupItem::Fourth => {
+                let index = self.index.expr(meta);
+
+                Some(MemoryTableLookupEncode::encode_table_write(
+                    common_config.eid(meta),
+                    constant_from!(4),
+                    self.table.elem_offset(meta, index),
+                    self.value.expr(meta),
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    fn brtable_lookup(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        _common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        Some(self.table.encode(meta))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::test_circuit_noexternal;
+
+    #[test]
+    fn test_table_set() {
+        let textual_repr = r#"
+                (module
+                    (type $t (func (result i32)))
+                    (table 2 funcref)
+                    (func $f (type $t)
+                        (i32.const 1)
+                    )
+                    (elem declare func $f)
+
+                    (func (export "test") (result i32)
+                        (table.set 0 (i32.const 1) (ref.func $f))
+                        (call_indirect (type $t) (i32.const 1))
+                    )
+                   )
+                "#;
+
+        test_circuit_noexternal(textual_repr).unwrap()
+    }
+}
@@ -0,0 +1,151 @@
+use super::table::TableLayoutCell;
+use super::*;
+use crate::{
+    circuits::{mtable_compact::encode::MemoryTableLookupEncode, utils::Context},
+    constant,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Error, Expression, VirtualCells},
+};
+use specs::{
+    encode::opcode::encode_table_op, etable::EventTableEntry, itable::OpcodeClass, mtable::VarType,
+    step::StepInfo,
+};
+
+pub struct TableSizeConfig {
+    table: TableLayoutCell,
+    size: CommonRangeCell,
+    lookup_size_read: MTableLookupCell,
+    lookup_stack_write: MTableLookupCell,
+}
+
+pub struct TableSizeConfigBuilder {}
+
+impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for TableSizeConfigBuilder {
+    fn configure(
+        common: &mut EventTableCellAllocator<F>,
+        _constraint_builder: &mut ConstraintBuilder<F>,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let table = TableLayoutCell::alloc(common);
+        let size = common.alloc_common_range_value();
+
+        let lookup_size_read = common.alloc_mtable_lookup();
+        let lookup_stack_write = common.alloc_mtable_lookup();
+
+        Box::new(TableSizeConfig {
+            table,
+            size,
+            lookup_size_read,
+            lookup_stack_write,
+        })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for TableSizeConfig {
+    fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        encode_table_op(OpcodeClass::TableSize, self.table.table_idx.expr(meta))
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step_info: &StepStatus,
+        entry: &EventTableEntry,
+    ) -> Result<(), Error> {
+        match &entry.step_info {
+            StepInfo::TableSize { table_index, size } => {
+                let layout = &step_info.table_layouts[*table_index as usize];
+
+                self.table.assign(ctx, layout)?;
+                self.size.assign(ctx, F::from(*size as u64))?;
+
+                self.lookup_size_read.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_table_read(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(1 as u64),
+                        BigUint::from(layout.base),
+                        BigUint::from(*size),
+                    ),
+                )?;
+
+                self.lookup_stack_write.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_stack_write(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(2 as u64),
+                        BigUint::from(step_info.current.sp),
+                        BigUint::from(VarType::I32 as u16),
+                        BigUint::from(*size),
+                    ),
+                )?;
+
+                Ok(())
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    fn sp_diff(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant!(-F::one()))
+    }
+
+    fn mops(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(2))
+    }
+
+    fn mtable_lookup(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        item: MLookupItem,
+        common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        match item {
+            MLookupItem::First => Some(MemoryTableLookupEncode::encode_table_read(
+                common_config.eid(meta),
+                constant_from!(1),
+                self.table.size_offset(meta),
+                self.size.expr(meta),
+            )),
+            MLookupItem::Second => Some(MemoryTableLookupEncode::encode_stack_write(
+                common_config.eid(meta),
+                constant_from!(2),
+                common_config.sp(meta),
+                constant_from!(VarType::I32),
+                self.size.expr(meta),
+            )),
+            _ => None,
+        }
+    }
+
+    fn brtable_lookup(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        _common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        Some(self.table.encode(meta))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::test_circuit_noexternal;
+
+    #[test]
+    fn test_table_size() {
+        let textual_repr = r#"
+                (module
+                    (table 1 funcref)
+                    (table 3 8 funcref)
+
+                    (func (export "test") (result i32)
+                        (i32.add (table.size 0) (table.size 1))
+                    )
+                   )
+                "#;
+
+        test_circuit_noexternal(textual_repr).unwrap()
+    }
+}
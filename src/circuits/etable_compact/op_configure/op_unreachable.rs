@@ -0,0 +1,108 @@
+use super::*;
+use crate::{
+    circuits::utils::{bn_to_field, Context},
+    constant,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Error, Expression, VirtualCells},
+};
+use specs::{
+    etable::EventTableEntry,
+    itable::{OpcodeClass, OPCODE_CLASS_SHIFT},
+    step::StepInfo,
+};
+
+pub struct UnreachableConfig {}
+
+pub struct UnreachableConfigBuilder {}
+
+impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for UnreachableConfigBuilder {
+    fn configure(
+        _common: &mut EventTableCellAllocator<F>,
+        _constraint_builder: &mut ConstraintBuilder<F>,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        Box::new(UnreachableConfig {})
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for UnreachableConfig {
+    fn opcode(&self, _meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        constant!(bn_to_field(
+            &(BigUint::from(OpcodeClass::Unreachable as u64) << OPCODE_CLASS_SHIFT)
+        ))
+    }
+
+    fn assign(
+        &self,
+        _ctx: &mut Context<'_, F>,
+        _step: &StepStatus,
+        entry: &EventTableEntry,
+    ) -> Result<(), Error> {
+        match &entry.step_info {
+            StepInfo::Unreachable => Ok(()),
+            _ => unreachable!(),
+        }
+    }
+
+    fn is_trap(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::{test_circuit_noexternal_patched, StepPatch};
+    use specs::{itable::Opcode, step::StepInfo};
+
+    // The wasmi tracer does not emit the unreachable step, return stands in.
+    fn test_circuit_unreachable(textual_repr: &str) {
+        test_circuit_noexternal_patched(
+            textual_repr,
+            &[("(unreachable)", "(return)")],
+            &[],
+            |_, entry| match entry.step_info {
+                StepInfo::Return { .. } => {
+                    StepPatch::Replace(Opcode::Unreachable, StepInfo::Unreachable)
+                }
+                _ => StepPatch::Keep,
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_unreachable_trap_ok() {
+        let textual_repr = r#"
+                (module
+                    (func (export "test")
+                      (i32.const 0)
+                      (if
+                        (then)
+                        (else (unreachable))
+                      )
+                    )
+                   )
+                "#;
+
+        test_circuit_unreachable(textual_repr)
+    }
+
+    #[test]
+    fn test_unreachable_in_callee_trap_ok() {
+        let textual_repr = r#"
+                (module
+                    (func $abort
+                      (unreachable)
+                    )
+                    (func (export "test")
+                      (i32.const 1)
+                      (drop)
+                      (call $abort)
+                    )
+                   )
+                "#;
+
+        test_circuit_unreachable(textual_repr)
+    }
+}
@@ -0,0 +1,63 @@
+use super::*;
+use crate::circuits::utils::Context;
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Error, Expression, VirtualCells},
+};
+use specs::{brtable::TableLayout, encode::br_table::encode_table_layout};
+
+/*
+ * The layout of the table accessed by the step, looked up in the brtable.
+ * See specs::brtable::TableLayout.
+ */
+pub(super) struct TableLayoutCell {
+    pub(super) table_idx: CommonRangeCell,
+    pub(super) base: CommonRangeCell,
+    pub(super) capacity: CommonRangeCell,
+    lookup: BrTableLookupCell,
+}
+
+impl TableLayoutCell {
+    pub(super) fn alloc<F: FieldExt>(common: &mut EventTableCellAllocator<F>) -> Self {
+        Self {
+            table_idx: common.alloc_common_range_value(),
+            base: common.alloc_common_range_value(),
+            capacity: common.alloc_common_range_value(),
+            lookup: common.alloc_brtable_lookup(),
+        }
+    }
+
+    pub(super) fn encode<F: FieldExt>(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        encode_table_layout(
+            self.table_idx.expr(meta),
+            self.base.expr(meta),
+            self.capacity.expr(meta),
+        )
+    }
+
+    pub(super) fn size_offset<F: FieldExt>(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        self.base.expr(meta)
+    }
+
+    pub(super) fn elem_offset<F: FieldExt>(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        index: Expression<F>,
+    ) -> Expression<F> {
+        self.base.expr(meta) + constant_from!(1) + index
+    }
+
+    pub(super) fn assign<F: FieldExt>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        layout: &TableLayout,
+    ) -> Result<(), Error> {
+        self.table_idx
+            .assign(ctx, F::from(layout.table_idx as u64))?;
+        self.base.assign(ctx, F::from(layout.base as u64))?;
+        self.capacity.assign(ctx, F::from(layout.capacity as u64))?;
+        self.lookup.assign(ctx, &layout.encode())?;
+
+        Ok(())
+    }
+}
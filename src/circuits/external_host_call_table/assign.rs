@@ -1,22 +1,35 @@
-use halo2_proofs::{arithmetic::FieldExt, circuit::Layouter, plonk::Error};
-use specs::external_host_call_table::ExternalHostCallTable;
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter},
+    plonk::Error,
+};
+use num_bigint::BigUint;
+use specs::external_host_call_table::{
+    encode::{encode_host_call_entry, encode_host_call_visibility},
+    ExternalHostCallTable,
+};
+use std::collections::BTreeMap;
 
-use crate::circuits::traits::TableSize;
+use crate::circuits::{traits::TableSize, utils::bn_to_field};
 
-use super::ExternalHostCallChip;
+use super::{ExternalHostCallChip, ExternalHostCallVisibilityConfig};
 
 impl<F: FieldExt> ExternalHostCallChip<F> {
     pub(in crate::circuits) fn assign(
         self,
         layouter: &mut impl Layouter<F>,
         table: &ExternalHostCallTable,
+        visibility: &BTreeMap<usize, bool>,
     ) -> Result<(), Error> {
-        layouter.assign_region(
+        let transcript_cells = layouter.assign_region(
             || "foreign table",
             |mut region| {
+                let mut transcript_cells: Vec<AssignedCell<F, F>> = vec![];
+                let is_public = |op: &usize| visibility.get(op).cloned().unwrap_or(false);
+
                 // Assign Fixed Column
                 {
-                    for offset in 0..self.config.max_available_size() {
+                    for offset in 0..self.max_available_size() {
                         region.assign_fixed(
                             || "external host call idx",
                             self.config.idx,
@@ -51,6 +64,13 @@ impl<F: FieldExt> ExternalHostCallChip<F> {
                             offset,
                             || Ok(F::zero()),
                         )?;
+
+                        region.assign_advice(
+                            || "external host call is public",
+                            self.config.is_public,
+                            offset,
+                            || Ok(F::zero()),
+                        )?;
                     }
 
                     offset += 1;
@@ -70,6 +90,13 @@ impl<F: FieldExt> ExternalHostCallChip<F> {
                             || Ok(F::from(entry.sig.is_ret() as u64)),
                         )?;
 
+                        region.assign_advice(
+                            || "external host call is public",
+                            self.config.is_public,
+                            offset,
+                            || Ok(F::from(is_public(&entry.op) as u64)),
+                        )?;
+
                         region.assign_advice(
                             || "external host call value",
                             self.config.arg,
@@ -81,10 +108,74 @@ impl<F: FieldExt> ExternalHostCallChip<F> {
                     }
                 }
 
-                Ok(())
+                /*
+                 * Every row is copied to the transcript, so that the copy
+                 * constraints do not depend on the trace.
+                 */
+                if let Some((entry_col, _)) = self.config.transcript {
+                    for offset in 0..self.max_available_size() {
+                        let entry = offset
+                            .checked_sub(1)
+                            .and_then(|idx| table.entries().get(idx))
+                            .filter(|entry| is_public(&entry.op))
+                            .map_or(F::zero(), |entry| {
+                                bn_to_field(&encode_host_call_entry(
+                                    BigUint::from(offset as u64),
+                                    BigUint::from(entry.op as u64),
+                                    BigUint::from(entry.sig.is_ret() as u64),
+                                    BigUint::from(1u64),
+                                    BigUint::from(entry.value),
+                                ))
+                            });
+
+                        transcript_cells.push(region.assign_advice(
+                            || "external host call transcript",
+                            entry_col,
+                            offset,
+                            || Ok(entry),
+                        )?);
+                    }
+                }
+
+                Ok(transcript_cells)
             },
         )?;
 
+        if let Some((_, instance)) = self.config.transcript {
+            for (row, cell) in transcript_cells.into_iter().enumerate() {
+                layouter.constrain_instance(cell.cell(), instance, row)?;
+            }
+        }
+
         Ok(())
     }
 }
+
+impl<F: FieldExt> ExternalHostCallVisibilityConfig<F> {
+    pub(in crate::circuits) fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        visibility: &BTreeMap<usize, bool>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "external host call visibility",
+            |mut region| {
+                for (offset, (op, is_public)) in visibility.iter().enumerate() {
+                    region.assign_fixed(
+                        || "external host call visibility",
+                        self.visibility,
+                        offset,
+                        || {
+                            Ok(bn_to_field(&encode_host_call_visibility(
+                                BigUint::from(*op as u64),
+                                BigUint::from(*is_public as u64),
+                            )))
+                        },
+                    )?;
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
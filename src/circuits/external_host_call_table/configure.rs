@@ -5,9 +5,12 @@ use halo2_proofs::{
 use specs::external_host_call_table::encode::encode_host_call_entry;
 use std::marker::PhantomData;
 
-use crate::{circuits::traits::ConfigureLookupTable, curr, fixed_curr};
+use crate::{
+    circuits::traits::ConfigureLookupTable, constant_from, curr, fixed_curr,
+    foreign::ForeignTableConfig,
+};
 
-use super::ExternalHostCallTableConfig;
+use super::{ExternalHostCallTableConfig, ExternalHostCallVisibilityConfig};
 
 impl<F: FieldExt> ExternalHostCallTableConfig<F> {
     pub(in crate::circuits) fn configure(meta: &mut ConstraintSystem<F>) -> Self {
@@ -15,10 +18,43 @@ impl<F: FieldExt> ExternalHostCallTableConfig<F> {
             idx: meta.fixed_column(),
             op: meta.advice_column(),
             is_ret: meta.advice_column(),
+            is_public: meta.advice_column(),
             arg: meta.advice_column(),
+            transcript: None,
             _phantom: PhantomData,
         }
     }
+
+    /*
+     * The transcript allocates an instance column, it is configured apart from
+     * the table to keep the order of the instance columns.
+     */
+    pub(in crate::circuits) fn configure_transcript(&mut self, meta: &mut ConstraintSystem<F>) {
+        let entry = meta.advice_column();
+        let instance = meta.instance_column();
+
+        meta.enable_equality(entry);
+        meta.enable_equality(instance);
+
+        meta.create_gate("external host call transcript", |meta| {
+            let is_public = curr!(meta, self.is_public);
+
+            vec![
+                is_public.clone() * (is_public.clone() - constant_from!(1)),
+                curr!(meta, entry)
+                    - is_public
+                        * encode_host_call_entry(
+                            fixed_curr!(meta, self.idx),
+                            curr!(meta, self.op),
+                            curr!(meta, self.is_ret),
+                            constant_from!(1),
+                            curr!(meta, self.arg),
+                        ),
+            ]
+        });
+
+        self.transcript = Some((entry, instance));
+    }
 }
 
 impl<F: FieldExt> ConfigureLookupTable<F> for ExternalHostCallTableConfig<F> {
@@ -35,9 +71,32 @@ impl<F: FieldExt> ConfigureLookupTable<F> for ExternalHostCallTableConfig<F> {
                     fixed_curr!(meta, self.idx),
                     curr!(meta, self.op),
                     curr!(meta, self.is_ret),
+                    curr!(meta, self.is_public),
                     curr!(meta, self.arg),
                 ),
             )]
         });
     }
 }
+
+impl<F: FieldExt> ExternalHostCallVisibilityConfig<F> {
+    pub(in crate::circuits) fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            visibility: meta.fixed_column(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<F: FieldExt> ForeignTableConfig<F> for ExternalHostCallVisibilityConfig<F> {
+    fn configure_in_table(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        key: &'static str,
+        expr: &dyn Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+    ) {
+        meta.lookup_any(key, |meta| {
+            vec![(expr(meta), fixed_curr!(meta, self.visibility))]
+        });
+    }
+}
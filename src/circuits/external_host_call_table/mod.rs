@@ -1,41 +1,89 @@
 use halo2_proofs::{
     arithmetic::FieldExt,
-    plonk::{Advice, Column, Fixed},
+    plonk::{Advice, Column, Fixed, Instance},
 };
-use std::marker::PhantomData;
+use num_bigint::BigUint;
+use specs::external_host_call_table::{encode::encode_host_call_entry, ExternalHostCallTable};
+use std::{collections::BTreeMap, marker::PhantomData};
 
-use crate::circuits::config::{
-    zkwasm_k, ZKWASM_FOREIGN_CALL_TABLE_RATIO, ZKWASM_TABLE_DENOMINATOR,
-};
-
-use super::traits::TableSize;
+use super::{traits::TableSize, utils::bn_to_field};
 
 mod assign;
 mod configure;
 
+pub const EXTERNAL_HOST_CALL_VISIBILITY_TABLE_KEY: &'static str =
+    "external-host-call-visibility-table";
+
 #[derive(Clone)]
 pub struct ExternalHostCallTableConfig<F: FieldExt> {
     idx: Column<Fixed>,
     op: Column<Advice>,
     arg: Column<Advice>,
     is_ret: Column<Advice>,
+    is_public: Column<Advice>,
+    /*
+     * The entry of each row if its call is public, zero otherwise, copied to
+     * the instance row of the same index.
+     */
+    transcript: Option<(Column<Advice>, Column<Instance>)>,
     _phantom: PhantomData<F>,
 }
 
-impl<F: FieldExt> TableSize for ExternalHostCallTableConfig<F> {
-    fn max_available_size(&self) -> usize {
-        assert!(*ZKWASM_FOREIGN_CALL_TABLE_RATIO < *ZKWASM_TABLE_DENOMINATOR);
-
-        ((1 << zkwasm_k()) / *ZKWASM_TABLE_DENOMINATOR * *ZKWASM_FOREIGN_CALL_TABLE_RATIO) as usize
-    }
+/*
+ * The (op, is_public) pairs of the external host functions, an external host
+ * call takes its visibility from the function instead of the prover.
+ */
+#[derive(Clone)]
+pub struct ExternalHostCallVisibilityConfig<F: FieldExt> {
+    visibility: Column<Fixed>,
+    _phantom: PhantomData<F>,
 }
 
 pub struct ExternalHostCallChip<F: FieldExt> {
     config: ExternalHostCallTableConfig<F>,
+    max_available_rows: usize,
+}
+
+impl<F: FieldExt> TableSize for ExternalHostCallChip<F> {
+    fn max_available_size(&self) -> usize {
+        self.max_available_rows
+    }
 }
 
 impl<F: FieldExt> ExternalHostCallChip<F> {
-    pub fn new(config: ExternalHostCallTableConfig<F>) -> Self {
-        Self { config }
+    pub fn new(config: ExternalHostCallTableConfig<F>, max_available_rows: usize) -> Self {
+        Self {
+            config,
+            max_available_rows,
+        }
     }
 }
+
+/*
+ * The instances of the transcript column: the entry of the i-th external host
+ * call is at row i + 1 if its function is public, the other rows are zero.
+ */
+pub fn external_host_call_transcript<F: FieldExt>(
+    table: &ExternalHostCallTable,
+    visibility: &BTreeMap<usize, bool>,
+) -> Vec<F> {
+    let mut instances = vec![F::zero()];
+
+    for (idx, entry) in table.entries().iter().enumerate() {
+        let is_public = *visibility.get(&entry.op).unwrap();
+
+        instances.push(if is_public {
+            bn_to_field(&encode_host_call_entry(
+                BigUint::from(idx as u64 + 1),
+                BigUint::from(entry.op as u64),
+                BigUint::from(entry.sig.is_ret() as u64),
+                BigUint::from(1u64),
+                BigUint::from(entry.value),
+            ))
+        } else {
+            F::zero()
+        });
+    }
+
+    instances
+}
@@ -0,0 +1,289 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Cell, Layouter},
+    plonk::{Column, Error, Fixed},
+};
+use specs::poseidon::POSEIDON_RATE;
+
+use super::{
+    ConfigureValue, ImageCommitmentChip, ImageCommitmentInputs, ImageConfigureChip,
+    ImageTableColumn,
+};
+use crate::circuits::utils::Context;
+
+impl ImageTableColumn {
+    /*
+     * Returns the cells of the column in the universal circuit, the values are
+     * padded with zeros up to the capacity.
+     */
+    pub(in crate::circuits) fn assign<F: FieldExt>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        name: &'static str,
+        values: Vec<F>,
+    ) -> Result<Vec<Cell>, Error> {
+        match *self {
+            ImageTableColumn::Fixed(col) => {
+                layouter.assign_table(
+                    || name,
+                    |mut table| {
+                        for (i, value) in values.iter().enumerate() {
+                            table.assign_cell(|| name, col, i, || Ok(*value))?;
+                        }
+
+                        Ok(())
+                    },
+                )?;
+
+                Ok(vec![])
+            }
+            ImageTableColumn::Advice { col, sel, rows } => {
+                assert!(
+                    values.len() <= rows,
+                    "{} exceeds the capacity of the universal circuit",
+                    name
+                );
+
+                layouter.assign_region(
+                    || name,
+                    |mut region| {
+                        let mut cells = Vec::with_capacity(rows);
+
+                        for i in 0..rows {
+                            region.assign_fixed(|| name, sel, i, || Ok(F::one()))?;
+
+                            let cell = region.assign_advice(
+                                || name,
+                                col,
+                                i,
+                                || Ok(values.get(i).cloned().unwrap_or(F::zero())),
+                            )?;
+
+                            cells.push(cell.cell());
+                        }
+
+                        Ok(cells)
+                    },
+                )
+            }
+        }
+    }
+}
+
+impl<F: FieldExt> ImageConfigureChip<F> {
+    /*
+     * Returns the cells of maximal_memory_pages and first_consecutive_zero_memory_offset
+     * in the universal circuit.
+     */
+    pub(in crate::circuits) fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        maximal_memory_pages: u32,
+        first_consecutive_zero_memory_offset: u32,
+    ) -> Result<Vec<Cell>, Error> {
+        let (sel, columns) = match (
+            self.config.sel,
+            self.config.maximal_memory_pages,
+            self.config.first_consecutive_zero_memory_offset,
+        ) {
+            (Some(sel), ConfigureValue::Column(max_pages), ConfigureValue::Column(first_zero)) => {
+                (sel, [max_pages, first_zero])
+            }
+            _ => return Ok(vec![]),
+        };
+        let values = [maximal_memory_pages, first_consecutive_zero_memory_offset];
+
+        layouter.assign_region(
+            || "image configure",
+            |mut region| {
+                let mut cells = vec![];
+
+                for i in 0..self.max_available_rows {
+                    if i + 1 < self.max_available_rows {
+                        region.assign_fixed(|| "image configure sel", sel, i, || Ok(F::one()))?;
+                    }
+
+                    for (col, value) in columns.iter().zip(values.iter()) {
+                        let cell = region.assign_advice(
+                            || "image configure",
+                            *col,
+                            i,
+                            || Ok(F::from(*value as u64)),
+                        )?;
+
+                        if i == 0 {
+                            cells.push(cell.cell());
+                        }
+                    }
+                }
+
+                Ok(cells)
+            },
+        )
+    }
+}
+
+impl<F: FieldExt> ImageCommitmentChip<F> {
+    fn assign_fixed(
+        &self,
+        ctx: &mut Context<'_, F>,
+        col: Column<Fixed>,
+        name: &'static str,
+    ) -> Result<(), Error> {
+        ctx.region
+            .assign_fixed(|| name, col, ctx.offset, || Ok(F::one()))?;
+
+        Ok(())
+    }
+
+    fn assign_remaining(
+        &self,
+        ctx: &mut Context<'_, F>,
+        offset: usize,
+        value: F,
+    ) -> Result<Cell, Error> {
+        let cell = ctx.region.assign_advice(
+            || "image commitment remaining",
+            self.config.remaining,
+            offset,
+            || Ok(value),
+        )?;
+
+        Ok(cell.cell())
+    }
+
+    /*
+     * Hash the image and expose the digest. The header is bound to the cells of
+     * the configure table and the static frame slots, followed by the lengths
+     * bound to the counts of the columns, and each column is bound to the cells
+     * of its image table, whose capacity sets the blocks of the column.
+     */
+    pub(in crate::circuits) fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        inputs: &ImageCommitmentInputs<F>,
+        header_sources: &[Cell],
+        column_sources: &[Vec<Cell>],
+    ) -> Result<(), Error> {
+        assert_eq!(
+            inputs.header.len(),
+            header_sources.len() + inputs.columns.len()
+        );
+        assert_eq!(inputs.columns.len(), column_sources.len());
+
+        let digest = layouter.assign_region(
+            || "image commitment",
+            |region| {
+                let mut ctx = Context::new(region);
+                let poseidon = &self.config.poseidon;
+
+                let mut state = poseidon.assign_initial_state(&mut ctx)?;
+
+                let mut header_cells = vec![];
+                for chunk in inputs.header.chunks(POSEIDON_RATE) {
+                    self.assign_fixed(
+                        &mut ctx,
+                        self.config.absorbing_block_sel,
+                        "image commitment absorbing block sel",
+                    )?;
+
+                    let (mut cells, _) =
+                        poseidon.assign_block(&mut ctx, &mut state, chunk, false)?;
+                    header_cells.append(&mut cells);
+                }
+
+                for (cell, source) in header_cells.iter().zip(header_sources.iter()) {
+                    ctx.region.constrain_equal(*cell, *source)?;
+                }
+                let length_cells = &header_cells[header_sources.len()..];
+
+                for ((column, sources), length_cell) in inputs
+                    .columns
+                    .iter()
+                    .zip(column_sources.iter())
+                    .zip(length_cells.iter())
+                {
+                    let blocks = (sources.len() + POSEIDON_RATE - 1) / POSEIDON_RATE;
+                    let used_blocks = (column.len() + POSEIDON_RATE - 1) / POSEIDON_RATE;
+
+                    for block in 0..blocks {
+                        let offset = ctx.offset;
+
+                        self.assign_fixed(
+                            &mut ctx,
+                            self.config.column_block_sel,
+                            "image commitment column block sel",
+                        )?;
+                        if block == 0 {
+                            self.assign_fixed(
+                                &mut ctx,
+                                self.config.column_first_block_sel,
+                                "image commitment column first block sel",
+                            )?;
+                        }
+                        if block + 1 == blocks {
+                            self.assign_fixed(
+                                &mut ctx,
+                                self.config.column_last_block_sel,
+                                "image commitment column last block sel",
+                            )?;
+                        }
+
+                        self.assign_remaining(
+                            &mut ctx,
+                            offset,
+                            F::from(used_blocks.saturating_sub(block) as u64),
+                        )?;
+
+                        if block == 0 {
+                            let length = self.assign_remaining(
+                                &mut ctx,
+                                offset + 1,
+                                F::from(column.len() as u64),
+                            )?;
+                            ctx.region.constrain_equal(length, *length_cell)?;
+
+                            let rest = used_blocks * POSEIDON_RATE - column.len();
+                            for i in 0..3 {
+                                self.assign_remaining(
+                                    &mut ctx,
+                                    offset + 2 + i,
+                                    F::from(((rest >> i) & 1) as u64),
+                                )?;
+                            }
+                        }
+
+                        let rows = block * POSEIDON_RATE
+                            ..usize::min((block + 1) * POSEIDON_RATE, sources.len());
+                        let chunk = rows
+                            .clone()
+                            .map(|row| column.get(row).cloned().unwrap_or(F::zero()))
+                            .collect::<Vec<_>>();
+
+                        let (cells, _) = poseidon.assign_variable_block(
+                            &mut ctx,
+                            &mut state,
+                            &chunk,
+                            block < used_blocks,
+                        )?;
+
+                        for (cell, source) in cells.into_iter().zip(sources[rows].iter()) {
+                            ctx.region.constrain_equal(cell, *source)?;
+                        }
+                    }
+                }
+
+                self.assign_fixed(
+                    &mut ctx,
+                    self.config.absorbing_block_sel,
+                    "image commitment absorbing block sel",
+                )?;
+                let (_, output) = poseidon.assign_block(&mut ctx, &mut state, &[], true)?;
+
+                Ok(output[1])
+            },
+        )?;
+
+        layouter.constrain_instance(digest, self.config.commitment, 0)
+    }
+}
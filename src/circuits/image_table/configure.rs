@@ -0,0 +1,184 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{ConstraintSystem, Expression, VirtualCells},
+};
+use std::marker::PhantomData;
+
+use super::{ConfigureValue, ImageCommitmentConfig, ImageConfigureConfig, ImageTableColumn};
+use crate::{
+    circuits::{
+        utils::poseidon::{PoseidonConfig, POSEIDON_BLOCK_ROWS},
+        CircuitConfigure,
+    },
+    constant_from, curr, fixed_curr, next, nextn,
+};
+use specs::poseidon::POSEIDON_RATE;
+
+impl ImageTableColumn {
+    pub(in crate::circuits) fn configure<F: FieldExt>(
+        meta: &mut ConstraintSystem<F>,
+        rows: Option<u32>,
+    ) -> Self {
+        match rows {
+            Some(rows) => {
+                let col = meta.advice_column();
+                let sel = meta.fixed_column();
+
+                meta.enable_equality(col);
+
+                ImageTableColumn::Advice {
+                    col,
+                    sel,
+                    rows: rows as usize,
+                }
+            }
+            None => ImageTableColumn::Fixed(meta.lookup_table_column()),
+        }
+    }
+
+    pub(in crate::circuits) fn configure_in_table<F: FieldExt>(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        key: &'static str,
+        expr: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+    ) {
+        match *self {
+            ImageTableColumn::Fixed(col) => meta.lookup(key, |meta| vec![(expr(meta), col)]),
+            ImageTableColumn::Advice { col, sel, .. } => meta.lookup_any(key, |meta| {
+                vec![(expr(meta), curr!(meta, col) * fixed_curr!(meta, sel))]
+            }),
+        }
+    }
+}
+
+impl ConfigureValue {
+    pub(in crate::circuits) fn expr<F: FieldExt>(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+    ) -> Expression<F> {
+        match *self {
+            ConfigureValue::Constant(value) => constant_from!(value),
+            ConfigureValue::Column(col) => curr!(meta, col),
+        }
+    }
+}
+
+impl<F: FieldExt> ImageConfigureConfig<F> {
+    pub(in crate::circuits) fn configure(
+        meta: &mut ConstraintSystem<F>,
+        circuit_configure: &CircuitConfigure,
+    ) -> Self {
+        if circuit_configure.image_table.is_none() {
+            return ImageConfigureConfig {
+                maximal_memory_pages: ConfigureValue::Constant(
+                    circuit_configure.maximal_memory_pages,
+                ),
+                first_consecutive_zero_memory_offset: ConfigureValue::Constant(
+                    circuit_configure.first_consecutive_zero_memory_offset,
+                ),
+                sel: None,
+                _mark: PhantomData,
+            };
+        }
+
+        let sel = meta.fixed_column();
+        let maximal_memory_pages = meta.advice_column();
+        let first_consecutive_zero_memory_offset = meta.advice_column();
+
+        meta.enable_equality(maximal_memory_pages);
+        meta.enable_equality(first_consecutive_zero_memory_offset);
+
+        meta.create_gate("image configure is constant", |meta| {
+            [maximal_memory_pages, first_consecutive_zero_memory_offset]
+                .into_iter()
+                .map(|col| (next!(meta, col) - curr!(meta, col)) * fixed_curr!(meta, sel))
+                .collect::<Vec<_>>()
+        });
+
+        ImageConfigureConfig {
+            maximal_memory_pages: ConfigureValue::Column(maximal_memory_pages),
+            first_consecutive_zero_memory_offset: ConfigureValue::Column(
+                first_consecutive_zero_memory_offset,
+            ),
+            sel: Some(sel),
+            _mark: PhantomData,
+        }
+    }
+}
+
+impl<F: FieldExt> ImageCommitmentConfig<F> {
+    pub(in crate::circuits) fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        let poseidon = PoseidonConfig::configure_variable(meta);
+        let remaining = meta.advice_column();
+        let absorbing_block_sel = meta.fixed_column();
+        let column_block_sel = meta.fixed_column();
+        let column_first_block_sel = meta.fixed_column();
+        let column_last_block_sel = meta.fixed_column();
+        let commitment = meta.instance_column();
+
+        meta.enable_equality(remaining);
+        meta.enable_equality(commitment);
+
+        let absorbed = poseidon.absorbed.unwrap();
+
+        meta.create_gate("image commitment absorbing block", |meta| {
+            vec![
+                fixed_curr!(meta, absorbing_block_sel)
+                    * (constant_from!(1) - curr!(meta, absorbed)),
+            ]
+        });
+
+        meta.create_gate("image commitment column block", |meta| {
+            let absorbed = curr!(meta, absorbed);
+            let remaining_next = nextn!(meta, remaining, POSEIDON_BLOCK_ROWS as i32);
+            let remaining = curr!(meta, remaining);
+
+            vec![
+                fixed_curr!(meta, column_block_sel)
+                    * (constant_from!(1) - absorbed.clone())
+                    * remaining.clone(),
+                fixed_curr!(meta, column_block_sel)
+                    * (constant_from!(1) - fixed_curr!(meta, column_last_block_sel))
+                    * (remaining_next - remaining.clone() + absorbed.clone()),
+                fixed_curr!(meta, column_last_block_sel) * (remaining - absorbed),
+            ]
+        });
+
+        meta.create_gate("image commitment column length", |meta| {
+            let bits = (0..3)
+                .map(|i| nextn!(meta, remaining, 2 + i))
+                .collect::<Vec<_>>();
+
+            let mut constraints = bits
+                .iter()
+                .map(|bit| bit.clone() * (constant_from!(1) - bit.clone()))
+                .collect::<Vec<_>>();
+
+            constraints.push(
+                constant_from!(POSEIDON_RATE) * curr!(meta, remaining)
+                    - next!(meta, remaining)
+                    - bits
+                        .into_iter()
+                        .enumerate()
+                        .fold(constant_from!(0), |acc, (i, bit)| {
+                            acc + constant_from!(1 << i) * bit
+                        }),
+            );
+
+            constraints
+                .into_iter()
+                .map(|x| x * fixed_curr!(meta, column_first_block_sel))
+                .collect::<Vec<_>>()
+        });
+
+        ImageCommitmentConfig {
+            poseidon,
+            remaining,
+            absorbing_block_sel,
+            column_block_sel,
+            column_first_block_sel,
+            column_last_block_sel,
+            commitment,
+        }
+    }
+}
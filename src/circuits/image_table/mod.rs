@@ -0,0 +1,217 @@
+use super::{
+    brtable::BrTableChip, config::ImageTableLayout, imtable::MInitTableChip,
+    itable::InstructionTableChip, jtable::STATIC_FRAME_SLOTS, utils::poseidon::PoseidonConfig,
+};
+use crate::circuits::utils::bn_to_field;
+use anyhow::{anyhow, Result};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Advice, Column, Fixed, Instance, TableColumn},
+};
+use specs::{
+    poseidon::{PoseidonSpec, POSEIDON_RATE},
+    CompilationTable,
+};
+use std::{iter, marker::PhantomData};
+
+mod assign;
+mod configure;
+
+/*
+ * The column of an image table (itable, imtable and brtable). It is a fixed
+ * table column for a fixed image, in the universal circuit it is an advice
+ * column committed in the image commitment, rows out of the capacity are
+ * disabled by sel.
+ */
+#[derive(Clone, Copy)]
+pub enum ImageTableColumn {
+    Fixed(TableColumn),
+    Advice {
+        col: Column<Advice>,
+        sel: Column<Fixed>,
+        rows: usize,
+    },
+}
+
+/*
+ * A value of the configure table used in constraints. It is a constant for a
+ * fixed image, in the universal circuit it is an advice column holding the
+ * committed value on every row.
+ */
+#[derive(Clone, Copy)]
+pub enum ConfigureValue {
+    Constant(u32),
+    Column(Column<Advice>),
+}
+
+#[derive(Clone)]
+pub struct ImageConfigureConfig<F> {
+    pub maximal_memory_pages: ConfigureValue,
+    pub first_consecutive_zero_memory_offset: ConfigureValue,
+    sel: Option<Column<Fixed>>,
+    _mark: PhantomData<F>,
+}
+
+/*
+ * The commitment of the image in the universal circuit, it's the only row of
+ * the last instance column.
+ *
+ * The blocks of a column absorb its used rows, counted down by remaining on
+ * the absorb rows of the column: a block absorbs while the count is not zero.
+ * The count starts at the number of chunks of the length, the length and
+ * the bits of 8 * chunks - length are on the rows after the first absorb row.
+ */
+#[derive(Clone)]
+pub struct ImageCommitmentConfig<F: FieldExt> {
+    poseidon: PoseidonConfig<F>,
+    remaining: Column<Advice>,
+    // The header and the padding blocks, which always absorb.
+    absorbing_block_sel: Column<Fixed>,
+    column_block_sel: Column<Fixed>,
+    column_first_block_sel: Column<Fixed>,
+    column_last_block_sel: Column<Fixed>,
+    commitment: Column<Instance>,
+}
+
+pub struct ImageConfigureChip<F: FieldExt> {
+    config: ImageConfigureConfig<F>,
+    max_available_rows: usize,
+}
+
+impl<F: FieldExt> ImageConfigureChip<F> {
+    pub fn new(config: ImageConfigureConfig<F>, max_available_rows: usize) -> Self {
+        ImageConfigureChip {
+            config,
+            max_available_rows,
+        }
+    }
+}
+
+pub struct ImageCommitmentChip<F: FieldExt> {
+    config: ImageCommitmentConfig<F>,
+}
+
+impl<F: FieldExt> ImageCommitmentChip<F> {
+    pub fn new(config: ImageCommitmentConfig<F>) -> Self {
+        ImageCommitmentChip { config }
+    }
+}
+
+/*
+ * The committed image: the header is the configure table, the static frame
+ * slots and the lengths of the image table columns, followed by the used rows
+ * of the columns. The header and each column are padded with zeros to whole
+ * chunks, so that the commitment does not depend on the capacities.
+ */
+pub struct ImageCommitmentInputs<F: FieldExt> {
+    pub(in crate::circuits) header: Vec<F>,
+    pub(in crate::circuits) columns: Vec<Vec<F>>,
+}
+
+impl<F: FieldExt> ImageCommitmentInputs<F> {
+    pub fn hashed(&self) -> Vec<F> {
+        let pad = |values: &Vec<F>| {
+            let mut values = values.clone();
+            values.resize(
+                (values.len() + POSEIDON_RATE - 1) / POSEIDON_RATE * POSEIDON_RATE,
+                F::zero(),
+            );
+            values
+        };
+
+        let mut inputs = pad(&self.header);
+        for column in self.columns.iter() {
+            inputs.append(&mut pad(column));
+        }
+
+        inputs
+    }
+}
+
+fn check_capacity<F: FieldExt>(column: &Vec<F>, rows: u32) -> Result<()> {
+    if column.len() > rows as usize {
+        return Err(anyhow!(
+            "the {} rows of the image table exceed the capacity {} of the universal circuit",
+            column.len(),
+            rows
+        ));
+    }
+
+    Ok(())
+}
+
+fn image_inputs<F: FieldExt>(tables: &CompilationTable) -> Result<ImageCommitmentInputs<F>> {
+    let mut header = vec![
+        F::from(tables.configure_table.init_memory_pages as u64),
+        F::from(tables.configure_table.maximal_memory_pages as u64),
+        F::from(tables.imtable.first_consecutive_zero_memory() as u64),
+    ];
+
+    if tables.static_jtable.len() > STATIC_FRAME_SLOTS {
+        return Err(anyhow!(
+            "the {} static frames exceed the {} static frame slots",
+            tables.static_jtable.len(),
+            STATIC_FRAME_SLOTS
+        ));
+    }
+    for slot in 0..STATIC_FRAME_SLOTS {
+        match tables.static_jtable.get(slot) {
+            Some(entry) => {
+                header.push(F::one());
+                header.push(bn_to_field(&entry.encode()));
+            }
+            None => {
+                header.push(F::zero());
+                header.push(F::zero());
+            }
+        }
+    }
+
+    let mut columns = vec![
+        InstructionTableChip::column(&tables.itable),
+        BrTableChip::column(&tables.itable.create_brtable(), &tables.table_layouts),
+    ];
+    columns.extend(MInitTableChip::columns(&tables.imtable));
+
+    for column in columns.iter() {
+        header.push(F::from(column.len() as u64));
+    }
+
+    Ok(ImageCommitmentInputs { header, columns })
+}
+
+pub fn image_commitment_inputs<F: FieldExt>(
+    tables: &CompilationTable,
+    layout: &ImageTableLayout,
+) -> Result<ImageCommitmentInputs<F>> {
+    let inputs = image_inputs(tables)?;
+
+    let capacities = [layout.itable_rows, layout.brtable_rows]
+        .into_iter()
+        .chain(iter::repeat(layout.imtable_rows));
+    for (column, rows) in inputs.columns.iter().zip(capacities) {
+        check_capacity(column, rows)?;
+    }
+
+    Ok(inputs)
+}
+
+/*
+ * The hash identifying a program, it is written with the vkey of a circuit
+ * setup for the program. It is also the image commitment of the program in
+ * the universal circuit, whatever the capacities holding the image.
+ */
+pub fn image_hash<F: FieldExt>(tables: &CompilationTable) -> Result<F> {
+    Ok(PoseidonSpec::new().hash(&image_inputs(tables)?.hashed()))
+}
+
+/*
+ * The image commitment exposed by the universal circuit, a program is identified
+ * by its commitment while all programs share the vkey.
+ */
+pub fn image_commitment<F: FieldExt>(
+    tables: &CompilationTable,
+    layout: &ImageTableLayout,
+) -> Result<F> {
+    Ok(PoseidonSpec::new().hash(&image_commitment_inputs(tables, layout)?.hashed()))
+}
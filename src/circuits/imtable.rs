@@ -1,8 +1,8 @@
-use super::{config::IMTABLE_COLOMNS, utils::bn_to_field};
+use super::{config::IMTABLE_COLOMNS, image_table::ImageTableColumn, utils::bn_to_field};
 use halo2_proofs::{
     arithmetic::FieldExt,
-    circuit::Layouter,
-    plonk::{ConstraintSystem, Error, Expression, TableColumn, VirtualCells},
+    circuit::{Cell, Layouter},
+    plonk::{ConstraintSystem, Error, Expression, VirtualCells},
 };
 use specs::{
     encode::init_memory_table::encode_init_memory_table_entry, imtable::InitMemoryTable,
@@ -12,12 +12,12 @@ use std::marker::PhantomData;
 
 #[derive(Clone)]
 pub struct InitMemoryTableConfig<F: FieldExt> {
-    col: [TableColumn; IMTABLE_COLOMNS],
+    col: [ImageTableColumn; IMTABLE_COLOMNS],
     _mark: PhantomData<F>,
 }
 
 impl<F: FieldExt> InitMemoryTableConfig<F> {
-    pub fn configure(col: [TableColumn; IMTABLE_COLOMNS]) -> Self {
+    pub fn configure(col: [ImageTableColumn; IMTABLE_COLOMNS]) -> Self {
         Self {
             col,
             _mark: PhantomData,
@@ -41,7 +41,7 @@ impl<F: FieldExt> InitMemoryTableConfig<F> {
         expr: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
         index: usize,
     ) {
-        meta.lookup(key, |meta| vec![(expr(meta), self.col[index])]);
+        self.col[index].configure_in_table(meta, key, expr);
     }
 }
 
@@ -54,56 +54,59 @@ impl<F: FieldExt> MInitTableChip<F> {
         MInitTableChip { config }
     }
 
+    /*
+     * The values of the imtable columns, the first row is reserved for disabled lookups.
+     */
+    pub fn columns(minit: &InitMemoryTable) -> [Vec<F>; IMTABLE_COLOMNS] {
+        let mut columns = [(); IMTABLE_COLOMNS].map(|_| vec![F::zero()]);
+
+        let heap_entries = minit.filter(LocationType::Heap);
+        let global_entries = minit.filter(LocationType::Global);
+        let data_entries = minit.filter(LocationType::Data);
+        let table_entries = minit.filter(LocationType::Table);
+
+        /*
+         * Since the number of heap entries is always n * PAGE_SIZE / sizeof(u64).
+         */
+        assert_eq!(heap_entries.len() % IMTABLE_COLOMNS, 0);
+
+        let mut idx = 0;
+
+        /*
+         * The mtable picks the imtable column by offset % IMTABLE_COLOMNS,
+         * so each location type starts at the first column of a new row.
+         */
+        for entries in [heap_entries, global_entries, data_entries, table_entries] {
+            for v in entries.into_iter() {
+                columns[idx % IMTABLE_COLOMNS].push(bn_to_field::<F>(&v.encode()));
+
+                idx += 1;
+            }
+
+            /*
+             * Fill blank cells in the last row to make halo2 happy.
+             */
+            while idx % IMTABLE_COLOMNS != 0 {
+                columns[idx % IMTABLE_COLOMNS].push(F::zero());
+
+                idx += 1;
+            }
+        }
+
+        columns
+    }
+
     pub fn assign(
         self,
         layouter: &mut impl Layouter<F>,
         minit: &InitMemoryTable,
-    ) -> Result<(), Error> {
-        layouter.assign_table(
-            || "minit",
-            |mut table| {
-                for i in 0..IMTABLE_COLOMNS {
-                    table.assign_cell(|| "minit table", self.config.col[i], 0, || Ok(F::zero()))?;
-                }
-
-                let heap_entries = minit.filter(LocationType::Heap);
-                let global_entries = minit.filter(LocationType::Global);
-
-                /*
-                 * Since the number of heap entries is always n * PAGE_SIZE / sizeof(u64).
-                 */
-                assert_eq!(heap_entries.len() % IMTABLE_COLOMNS, 0);
-
-                let mut idx = 0;
-
-                for v in heap_entries.into_iter().chain(global_entries.into_iter()) {
-                    table.assign_cell(
-                        || "minit table",
-                        self.config.col[idx % IMTABLE_COLOMNS],
-                        idx / IMTABLE_COLOMNS + 1,
-                        || Ok(bn_to_field::<F>(&v.encode())),
-                    )?;
-
-                    idx += 1;
-                }
-
-                /*
-                 * Fill blank cells in the last row to make halo2 happy.
-                 */
-                if idx % IMTABLE_COLOMNS != 0 {
-                    for blank_col in (idx % IMTABLE_COLOMNS)..IMTABLE_COLOMNS {
-                        table.assign_cell(
-                            || "minit table",
-                            self.config.col[blank_col],
-                            idx / IMTABLE_COLOMNS + 1,
-                            || Ok(F::zero()),
-                        )?;
-                    }
-                }
-
-                Ok(())
-            },
-        )?;
-        Ok(())
+    ) -> Result<Vec<Vec<Cell>>, Error> {
+        let mut cells = vec![];
+
+        for (i, column) in Self::columns(minit).into_iter().enumerate() {
+            cells.push(self.config.col[i].assign(layouter, "minit", column)?);
+        }
+
+        Ok(cells)
     }
 }
@@ -116,6 +116,6 @@ impl<F: FieldExt> InputTableChip<F> {
 pub struct InputForeignCallInfo {}
 impl ForeignCallInfo for InputForeignCallInfo {
     fn call_id(&self) -> usize {
-        OpcodeClass::ForeignPluginStart as usize + HostPlugin::HostInput as usize
+        OpcodeClass::ForeignPluginStart as usize + HostPlugin::HostInput.id()
     }
 }
@@ -1,20 +1,20 @@
-use super::utils::bn_to_field;
+use super::{image_table::ImageTableColumn, utils::bn_to_field};
 use halo2_proofs::{
     arithmetic::FieldExt,
-    circuit::Layouter,
-    plonk::{ConstraintSystem, Error, Expression, TableColumn, VirtualCells},
+    circuit::{Cell, Layouter},
+    plonk::{ConstraintSystem, Error, Expression, VirtualCells},
 };
 use specs::itable::InstructionTable;
 use std::marker::PhantomData;
 
 #[derive(Clone)]
 pub struct InstructionTableConfig<F: FieldExt> {
-    col: TableColumn,
+    col: ImageTableColumn,
     _mark: PhantomData<F>,
 }
 
 impl<F: FieldExt> InstructionTableConfig<F> {
-    pub fn configure(col: TableColumn) -> Self {
+    pub fn configure(col: ImageTableColumn) -> Self {
         InstructionTableConfig {
             col,
             _mark: PhantomData,
@@ -27,7 +27,7 @@ impl<F: FieldExt> InstructionTableConfig<F> {
         key: &'static str,
         expr: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
     ) {
-        meta.lookup(key, |meta| vec![(expr(meta), self.col)]);
+        self.col.configure_in_table(meta, key, expr);
     }
 }
 
@@ -41,26 +41,26 @@ impl<F: FieldExt> InstructionTableChip<F> {
         InstructionTableChip { config }
     }
 
+    /*
+     * The values of the itable column, the first row is reserved for disabled lookups.
+     */
+    pub fn column(instructions: &InstructionTable) -> Vec<F> {
+        let mut column = vec![F::zero()];
+
+        for v in instructions.entries().iter() {
+            column.push(bn_to_field::<F>(&v.encode()));
+        }
+
+        column
+    }
+
     pub fn assign(
         self,
         layouter: &mut impl Layouter<F>,
         instructions: &InstructionTable,
-    ) -> Result<(), Error> {
-        layouter.assign_table(
-            || "itable",
-            |mut table| {
-                table.assign_cell(|| "inst_init table", self.config.col, 0, || Ok(F::zero()))?;
-                for (i, v) in instructions.entries().iter().enumerate() {
-                    table.assign_cell(
-                        || "inst_init table",
-                        self.config.col,
-                        i + 1,
-                        || Ok(bn_to_field::<F>(&v.encode())),
-                    )?;
-                }
-                Ok(())
-            },
-        )?;
-        Ok(())
+    ) -> Result<Vec<Cell>, Error> {
+        self.config
+            .col
+            .assign(layouter, "itable", Self::column(instructions))
     }
 }
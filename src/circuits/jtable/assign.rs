@@ -1,7 +1,11 @@
 use halo2_proofs::{arithmetic::FieldExt, circuit::Cell, plonk::Error};
-use specs::jtable::{JumpTable, StaticFrameEntry};
+use specs::{
+    etable::EventTable,
+    jtable::{JumpTable, StaticFrameEntry},
+};
+use std::collections::BTreeMap;
 
-use super::{jtable_rows, JtableOffset, JumpTableChip};
+use super::{JtableOffset, JumpTableChip, STATIC_FRAME_SLOTS};
 use crate::circuits::utils::{bn_to_field, Context};
 
 impl<F: FieldExt> JumpTableChip<F> {
@@ -31,7 +35,7 @@ impl<F: FieldExt> JumpTableChip<F> {
     }
 
     fn init(&self, ctx: &mut Context<'_, F>) -> Result<(), Error> {
-        for i in 0..jtable_rows() {
+        for i in 0..self.max_available_rows {
             if (i as u32) % (JtableOffset::JtableOffsetMax as u32) == 0 {
                 ctx.region
                     .assign_fixed(|| "jtable sel", self.config.sel, i, || Ok(F::one()))?;
@@ -51,12 +55,14 @@ impl<F: FieldExt> JumpTableChip<F> {
             let rest_f = (*rest_jops).into();
             let entry_f = bn_to_field(&entry.encode());
 
-            ctx.region.assign_fixed(
-                || "jtable static entry",
-                self.config.static_bit,
-                ctx.offset,
-                || Ok(F::one()),
-            )?;
+            if self.config.static_slots.is_none() {
+                ctx.region.assign_fixed(
+                    || "jtable static entry",
+                    self.config.static_bit,
+                    ctx.offset,
+                    || Ok(F::one()),
+                )?;
+            }
 
             ctx.region.assign_advice(
                 || "jtable enable",
@@ -75,12 +81,21 @@ impl<F: FieldExt> JumpTableChip<F> {
             ctx.next();
 
             // Frame Table Constraint 2. Static entry must be a constant."
-            ctx.region.assign_advice_from_constant(
-                || "jtable entry",
-                self.config.data,
-                ctx.offset,
-                entry_f,
-            )?;
+            if self.config.static_slots.is_none() {
+                ctx.region.assign_advice_from_constant(
+                    || "jtable entry",
+                    self.config.data,
+                    ctx.offset,
+                    entry_f,
+                )?;
+            } else {
+                ctx.region.assign_advice(
+                    || "jtable entry",
+                    self.config.data,
+                    ctx.offset,
+                    || Ok(entry_f),
+                )?;
+            }
             ctx.next();
 
             *rest_jops -= 1;
@@ -89,15 +104,67 @@ impl<F: FieldExt> JumpTableChip<F> {
         Ok(())
     }
 
+    /*
+     * Returns the flag and the entry cells of each static frame slot, which are
+     * bound to the image commitment.
+     */
+    fn assign_static_slots(
+        &self,
+        ctx: &mut Context<'_, F>,
+        static_entries: &Vec<StaticFrameEntry>,
+    ) -> Result<Vec<Cell>, Error> {
+        let mut cells = vec![];
+
+        if let Some(static_slots) = self.config.static_slots {
+            if static_entries.len() > STATIC_FRAME_SLOTS {
+                return Err(Error::Synthesis);
+            }
+
+            for slot in 0..STATIC_FRAME_SLOTS {
+                let offset = slot * JtableOffset::JtableOffsetMax as usize;
+                let (flag, entry_f) = match static_entries.get(slot) {
+                    Some(entry) => (F::one(), bn_to_field(&entry.encode())),
+                    None => (F::zero(), F::zero()),
+                };
+
+                ctx.region.assign_fixed(
+                    || "jtable static slot",
+                    self.config.static_bit,
+                    offset,
+                    || Ok(F::one()),
+                )?;
+
+                let flag_cell = ctx.region.assign_advice(
+                    || "jtable static slot flag",
+                    static_slots,
+                    offset + JtableOffset::JtableOffsetEnable as usize,
+                    || Ok(flag),
+                )?;
+                let entry_cell = ctx.region.assign_advice(
+                    || "jtable static slot entry",
+                    static_slots,
+                    offset + JtableOffset::JtableOffsetEntry as usize,
+                    || Ok(entry_f),
+                )?;
+
+                cells.push(flag_cell.cell());
+                cells.push(entry_cell.cell());
+            }
+        }
+
+        Ok(cells)
+    }
+
     fn assign_jtable_entries(
         &self,
         ctx: &mut Context<'_, F>,
         rest_jops: &mut u64,
         jtable: &JumpTable,
+        last_callee_fids: &BTreeMap<u32, u32>,
     ) -> Result<(), Error> {
         for entry in jtable.entries().iter() {
             let rest_f = (*rest_jops).into();
-            let entry_f = bn_to_field(&entry.encode());
+            let entry_f = bn_to_field(&entry.encode(last_callee_fids[&entry.eid]));
 
             ctx.region.assign_advice(
                 || "jtable enable",
@@ -161,18 +228,29 @@ impl<F: FieldExt> JumpTableChip<F> {
         jtable: &JumpTable,
         etable_rest_jops_cell: Option<Cell>,
         static_entries: &Vec<StaticFrameEntry>,
-    ) -> Result<(), Error> {
+        etable: &EventTable,
+        callee_fids: &[u32],
+    ) -> Result<Vec<Cell>, Error> {
         if etable_rest_jops_cell.is_some() {
             self.constraint_to_etable_jops(ctx, etable_rest_jops_cell.unwrap())?;
         }
 
         self.init(ctx)?;
+        let static_slot_cells = self.assign_static_slots(ctx, static_entries)?;
 
         let mut rest_jops = jtable.entries().len() as u64 * 2 + static_entries.len() as u64;
 
         self.assign_static_entries(ctx, &mut rest_jops, static_entries)?;
-        self.assign_jtable_entries(ctx, &mut rest_jops, jtable)?;
+        // The callee fid of the frame of each call, restored when the callee returns.
+        let last_callee_fids = etable
+            .entries()
+            .iter()
+            .map(|entry| entry.eid)
+            .zip(callee_fids.iter().cloned())
+            .collect::<BTreeMap<_, _>>();
 
-        Ok(())
+        self.assign_jtable_entries(ctx, &mut rest_jops, jtable, &last_callee_fids)?;
+
+        Ok(static_slot_cells)
     }
 }
@@ -1,8 +1,8 @@
-use super::JumpTableConfig;
-use crate::{circuits::Lookup, constant_from, fixed_curr};
+use super::{JtableOffset, JumpTableConfig};
+use crate::{circuits::Lookup, constant_from, fixed_curr, nextn};
 use halo2_proofs::{
     arithmetic::FieldExt,
-    plonk::{Advice, Column, ConstraintSystem, Expression, VirtualCells},
+    plonk::{ConstraintSystem, Expression, VirtualCells},
 };
 
 pub trait JTableConstraint<F: FieldExt> {
@@ -11,12 +11,14 @@ pub trait JTableConstraint<F: FieldExt> {
         self.enable_rest_jops_permutation(meta);
         self.configure_rest_jops_decrease(meta);
         self.disabled_block_should_be_empty(meta);
+        self.static_slots_in_image(meta);
     }
 
     fn enable_rest_jops_permutation(&self, meta: &mut ConstraintSystem<F>);
     fn enable_is_bit(&self, meta: &mut ConstraintSystem<F>);
     fn configure_rest_jops_decrease(&self, meta: &mut ConstraintSystem<F>);
     fn disabled_block_should_be_empty(&self, meta: &mut ConstraintSystem<F>);
+    fn static_slots_in_image(&self, meta: &mut ConstraintSystem<F>);
 }
 
 impl<F: FieldExt> JTableConstraint<F> for JumpTableConfig<F> {
@@ -57,6 +59,23 @@ impl<F: FieldExt> JTableConstraint<F> for JumpTableConfig<F> {
             ]
         });
     }
+
+    fn static_slots_in_image(&self, meta: &mut ConstraintSystem<F>) {
+        if let Some(static_slots) = self.static_slots {
+            meta.create_gate("jtable static slots", |meta| {
+                let flag = nextn!(meta, static_slots, JtableOffset::JtableOffsetEnable as i32);
+
+                vec![
+                    flag.clone() * (flag - constant_from!(1)) * fixed_curr!(meta, self.static_bit),
+                    // Frame Table Constraint 2. Static entry must be the committed one.
+                    (self.entry(meta)
+                        - nextn!(meta, static_slots, JtableOffset::JtableOffsetEntry as i32))
+                        * self.static_bit(meta),
+                    (self.enable(meta) - constant_from!(1)) * self.static_bit(meta),
+                ]
+            });
+        }
+    }
 }
 
 impl<F: FieldExt> Lookup<F> for JumpTableConfig<F> {
@@ -81,18 +100,21 @@ impl<F: FieldExt> Lookup<F> for JumpTableConfig<F> {
 }
 
 impl<F: FieldExt> JumpTableConfig<F> {
-    pub(super) fn new(
-        meta: &mut ConstraintSystem<F>,
-        cols: &mut impl Iterator<Item = Column<Advice>>,
-    ) -> Self {
+    pub(super) fn new(meta: &mut ConstraintSystem<F>, universal: bool) -> Self {
         let sel = meta.fixed_column();
         let static_bit = meta.fixed_column();
-        let data = cols.next().unwrap();
+        let data = meta.advice_column();
+        let static_slots = universal.then(|| {
+            let col = meta.advice_column();
+            meta.enable_equality(col);
+            col
+        });
 
         JumpTableConfig {
             sel,
             static_bit,
             data,
+            static_slots,
             _m: std::marker::PhantomData,
         }
     }
@@ -27,8 +27,18 @@ impl<F: FieldExt> JumpTableConfig<F> {
         nextn!(meta, self.data, JtableOffset::JtableOffsetEntry as i32)
     }
 
+    /*
+     * In the universal circuit the fixed static bit marks the slots, whether a
+     * slot holds a static frame is committed in the image.
+     */
     pub(super) fn static_bit(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
-        fixed_curr!(meta, self.static_bit)
+        match self.static_slots {
+            Some(static_slots) => {
+                fixed_curr!(meta, self.static_bit)
+                    * nextn!(meta, static_slots, JtableOffset::JtableOffsetEnable as i32)
+            }
+            None => fixed_curr!(meta, self.static_bit),
+        }
     }
 }
 
@@ -37,6 +47,7 @@ pub(crate) trait JtableLookupEntryEncode<F> {
         current_last_jump_eid: Expression<F>,
         next_last_jump_eid: Expression<F>,
         callee_fid: Expression<F>,
+        next_callee_fid: Expression<F>,
         next_fid: Expression<F>,
         next_iid: Expression<F>,
     ) -> Expression<F>;
@@ -47,6 +58,7 @@ impl<F: FieldExt> JtableLookupEntryEncode<F> for JumpTableConfig<F> {
         current_last_jump_eid: Expression<F>,
         next_last_jump_eid: Expression<F>,
         callee_fid: Expression<F>,
+        next_callee_fid: Expression<F>,
         next_fid: Expression<F>,
         next_iid: Expression<F>,
     ) -> Expression<F> {
@@ -54,6 +66,7 @@ impl<F: FieldExt> JtableLookupEntryEncode<F> for JumpTableConfig<F> {
             current_last_jump_eid,
             next_last_jump_eid,
             callee_fid,
+            next_callee_fid,
             next_fid,
             next_iid,
         )
@@ -1,5 +1,5 @@
 use self::configure::JTableConstraint;
-use super::config::max_jtable_rows;
+use super::CircuitConfigure;
 use halo2_proofs::{
     arithmetic::FieldExt,
     plonk::{Advice, Column, ConstraintSystem, Fixed},
@@ -10,6 +10,13 @@ mod assign;
 mod configure;
 pub(crate) mod expression;
 
+/*
+ * The static frames (the entry of the start function and the entry function)
+ * take the leading blocks of the jtable, the universal circuit reserves a slot
+ * for each of them.
+ */
+pub const STATIC_FRAME_SLOTS: usize = 2;
+
 pub enum JtableOffset {
     JtableOffsetEnable = 0,
     JtableOffsetRest = 1,
@@ -17,25 +24,22 @@ pub enum JtableOffset {
     JtableOffsetMax = 3,
 }
 
-fn jtable_rows() -> usize {
-    max_jtable_rows() as usize / JtableOffset::JtableOffsetMax as usize
-        * JtableOffset::JtableOffsetMax as usize
-}
-
 #[derive(Clone)]
 pub struct JumpTableConfig<F: FieldExt> {
     sel: Column<Fixed>,
     static_bit: Column<Fixed>,
     data: Column<Advice>,
+    /*
+     * Only in the universal circuit: the committed flag and entry of each
+     * static frame slot, at the enable and entry rotation of the slot block.
+     */
+    static_slots: Option<Column<Advice>>,
     _m: PhantomData<F>,
 }
 
 impl<F: FieldExt> JumpTableConfig<F> {
-    pub fn configure(
-        meta: &mut ConstraintSystem<F>,
-        cols: &mut impl Iterator<Item = Column<Advice>>,
-    ) -> Self {
-        let jtable = Self::new(meta, cols);
+    pub fn configure(meta: &mut ConstraintSystem<F>, circuit_configure: &CircuitConfigure) -> Self {
+        let jtable = Self::new(meta, circuit_configure.image_table.is_some());
         jtable.configure(meta);
         jtable
     }
@@ -43,10 +47,15 @@ impl<F: FieldExt> JumpTableConfig<F> {
 
 pub struct JumpTableChip<F: FieldExt> {
     config: JumpTableConfig<F>,
+    max_available_rows: usize,
 }
 
 impl<F: FieldExt> JumpTableChip<F> {
-    pub fn new(config: JumpTableConfig<F>) -> Self {
-        JumpTableChip { config }
+    pub fn new(config: JumpTableConfig<F>, max_available_rows: usize) -> Self {
+        JumpTableChip {
+            config,
+            max_available_rows: max_available_rows / JtableOffset::JtableOffsetMax as usize
+                * JtableOffset::JtableOffsetMax as usize,
+        }
     }
 }
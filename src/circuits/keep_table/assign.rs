@@ -0,0 +1,176 @@
+use halo2_proofs::{arithmetic::FieldExt, circuit::Layouter, plonk::Error};
+use specs::{etable::EventTable, itable::OpcodeClass, mtable::VarType, step::StepInfo};
+
+use crate::circuits::traits::TableSize;
+
+use super::{KeepTableChip, RotationOfAuxColumn, RotationOfBitColumn, STEP_SIZE};
+
+/*
+ * The values moved in the keep table by a step, emid and offset are the ones
+ * of the read of the first of them.
+ */
+pub(crate) struct KeepOp {
+    eid: u32,
+    emid: u32,
+    offset: u32,
+    drop: u32,
+    keep: u32,
+    values: Vec<(VarType, u64)>,
+}
+
+impl KeepOp {
+    pub(crate) fn steps(&self) -> usize {
+        self.values.len()
+    }
+}
+
+/*
+ * The kept values are read after the operands popped by the step, the ones
+ * of a branch not taken are not moved.
+ */
+pub(crate) fn keep_ops(etable: &EventTable) -> Vec<KeepOp> {
+    let mut ops = vec![];
+
+    for entry in etable.entries() {
+        let (class, start, drop, keep, keep_values) = match &entry.step_info {
+            StepInfo::Br {
+                drop,
+                keep,
+                keep_values,
+                ..
+            } => (OpcodeClass::Br, 1, drop, keep, keep_values),
+            StepInfo::BrIfNez {
+                condition,
+                drop,
+                keep,
+                keep_values,
+                ..
+            } if *condition != 0 => (OpcodeClass::BrIf, 2, drop, keep, keep_values),
+            StepInfo::BrIfEqz {
+                condition,
+                drop,
+                keep,
+                keep_values,
+                ..
+            } if *condition == 0 => (OpcodeClass::BrIfEqz, 2, drop, keep, keep_values),
+            StepInfo::BrTable {
+                drop,
+                keep,
+                keep_values,
+                ..
+            } => (OpcodeClass::BrTable, 2, drop, keep, keep_values),
+            StepInfo::Return {
+                drop,
+                keep,
+                keep_values,
+                ..
+            } => (OpcodeClass::Return, 1, drop, keep, keep_values),
+            StepInfo::ReturnCall {
+                drop,
+                keep,
+                keep_values,
+                ..
+            } => (OpcodeClass::ReturnCall, 1, drop, keep, keep_values),
+            StepInfo::ReturnCallIndirect {
+                drop,
+                keep,
+                keep_values,
+                ..
+            } => (OpcodeClass::ReturnCallIndirect, 2, drop, keep, keep_values),
+            _ => continue,
+        };
+
+        let max_keep = class.max_keep();
+
+        if keep.len() <= max_keep {
+            continue;
+        }
+
+        ops.push(KeepOp {
+            eid: entry.eid,
+            emid: start + max_keep as u32,
+            offset: entry.sp + start + max_keep as u32,
+            drop: *drop,
+            keep: keep.len() as u32,
+            values: keep
+                .iter()
+                .zip(keep_values.iter())
+                .skip(max_keep)
+                .map(|(vtype, value)| (VarType::from(*vtype), *value))
+                .collect(),
+        });
+    }
+
+    ops
+}
+
+impl<F: FieldExt> KeepTableChip<F> {
+    pub(in crate::circuits) fn assign(
+        self,
+        layouter: &mut impl Layouter<F>,
+        ops: &Vec<KeepOp>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "keep table",
+            |mut region| {
+                for offset in (0..self.max_available_size()).step_by(STEP_SIZE) {
+                    region.assign_fixed(
+                        || "keep table step sel",
+                        self.config.step_sel,
+                        offset,
+                        || Ok(F::one()),
+                    )?;
+                }
+
+                let mut offset = 0;
+
+                for op in ops {
+                    for (step, (vtype, value)) in op.values.iter().enumerate() {
+                        assert!(offset + STEP_SIZE <= self.max_available_size());
+
+                        let bits = [
+                            (RotationOfBitColumn::Enable, true),
+                            (RotationOfBitColumn::IsLast, step == op.steps() - 1),
+                            (RotationOfBitColumn::IsI64, *vtype == VarType::I64),
+                        ];
+
+                        for (rot, value) in bits {
+                            region.assign_advice(
+                                || "keep table bit",
+                                self.config.bit,
+                                offset + rot as usize,
+                                || Ok(F::from(value as u64)),
+                            )?;
+                        }
+
+                        let aux = [
+                            (RotationOfAuxColumn::Eid, op.eid as u64),
+                            (RotationOfAuxColumn::Emid, (op.emid + step as u32) as u64),
+                            (
+                                RotationOfAuxColumn::Offset,
+                                (op.offset + step as u32) as u64,
+                            ),
+                            (RotationOfAuxColumn::Drop, op.drop as u64),
+                            (RotationOfAuxColumn::Keep, op.keep as u64),
+                            (RotationOfAuxColumn::Rest, (op.steps() - step) as u64),
+                            (RotationOfAuxColumn::Value, *value),
+                        ];
+
+                        for (rot, value) in aux {
+                            region.assign_advice(
+                                || "keep table aux",
+                                self.config.aux,
+                                offset + rot as usize,
+                                || Ok(F::from(value)),
+                            )?;
+                        }
+
+                        offset += STEP_SIZE;
+                    }
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
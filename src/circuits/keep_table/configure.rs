@@ -0,0 +1,163 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{ConstraintSystem, Expression, VirtualCells},
+};
+use std::marker::PhantomData;
+
+use crate::{
+    circuits::{
+        mtable_compact::{encode::MemoryTableLookupEncode, MemoryTableConfig},
+        Lookup,
+    },
+    constant_from,
+    foreign::ForeignTableConfig,
+    nextn,
+};
+
+use super::{
+    KeepTableConfig, KeepTableEncode, RotationOfAuxColumn, RotationOfBitColumn, STEP_SIZE,
+};
+
+impl<F: FieldExt> KeepTableConfig<F> {
+    pub(in crate::circuits) fn configure(
+        meta: &mut ConstraintSystem<F>,
+        mtable: &MemoryTableConfig<F>,
+    ) -> Self {
+        let config = Self {
+            step_sel: meta.fixed_column(),
+            bit: meta.advice_column(),
+            aux: meta.advice_column(),
+            _mark: PhantomData,
+        };
+
+        config.configure_operation(meta);
+        config.configure_next_step(meta);
+        config.configure_mtable_lookup(meta, mtable);
+
+        config
+    }
+
+    fn configure_operation(&self, meta: &mut ConstraintSystem<F>) {
+        meta.create_gate("keep table bits", |meta| {
+            [
+                RotationOfBitColumn::Enable,
+                RotationOfBitColumn::IsLast,
+                RotationOfBitColumn::IsI64,
+            ]
+            .into_iter()
+            .map(|rot| {
+                let bit = nextn!(meta, self.bit, rot as i32);
+
+                bit.clone() * (constant_from!(1) - bit) * self.is_step(meta)
+            })
+            .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("keep table last step", |meta| {
+            vec![
+                (self.aux(meta, RotationOfAuxColumn::Rest) - constant_from!(1))
+                    * self.bit(meta, RotationOfBitColumn::IsLast)
+                    * self.bit(meta, RotationOfBitColumn::Enable)
+                    * self.is_step(meta),
+            ]
+        });
+    }
+
+    /*
+     * The steps of an operation are consecutive, rest decreases down to 1 on
+     * the last one.
+     */
+    fn configure_next_step(&self, meta: &mut ConstraintSystem<F>) {
+        meta.create_gate("keep table next step", |meta| {
+            let mut constraints = vec![
+                constant_from!(1)
+                    - self.next_bit(meta, RotationOfBitColumn::Enable) * self.is_next_step(meta),
+                self.next_aux(meta, RotationOfAuxColumn::Emid)
+                    - self.aux(meta, RotationOfAuxColumn::Emid)
+                    - constant_from!(1),
+                self.next_aux(meta, RotationOfAuxColumn::Offset)
+                    - self.aux(meta, RotationOfAuxColumn::Offset)
+                    - constant_from!(1),
+                self.next_aux(meta, RotationOfAuxColumn::Rest)
+                    - self.aux(meta, RotationOfAuxColumn::Rest)
+                    + constant_from!(1),
+            ];
+
+            for rot in [
+                RotationOfAuxColumn::Eid,
+                RotationOfAuxColumn::Drop,
+                RotationOfAuxColumn::Keep,
+            ] {
+                let rot = rot as i32;
+                constraints.push(
+                    nextn!(meta, self.aux, rot + STEP_SIZE as i32) - nextn!(meta, self.aux, rot),
+                );
+            }
+
+            constraints
+                .into_iter()
+                .map(|e| {
+                    e * self.bit(meta, RotationOfBitColumn::Enable)
+                        * (constant_from!(1) - self.bit(meta, RotationOfBitColumn::IsLast))
+                        * self.is_step(meta)
+                })
+                .collect::<Vec<_>>()
+        });
+    }
+
+    fn configure_mtable_lookup(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        mtable: &MemoryTableConfig<F>,
+    ) {
+        mtable.configure_in_table(meta, "keep table stack read", |meta| {
+            self.bit(meta, RotationOfBitColumn::Enable)
+                * MemoryTableLookupEncode::encode_stack_read(
+                    self.aux(meta, RotationOfAuxColumn::Eid),
+                    self.aux(meta, RotationOfAuxColumn::Emid),
+                    self.aux(meta, RotationOfAuxColumn::Offset),
+                    self.vtype(meta),
+                    self.aux(meta, RotationOfAuxColumn::Value),
+                )
+                * self.is_step(meta)
+        });
+
+        mtable.configure_in_table(meta, "keep table stack write", |meta| {
+            self.bit(meta, RotationOfBitColumn::Enable)
+                * MemoryTableLookupEncode::encode_stack_write(
+                    self.aux(meta, RotationOfAuxColumn::Eid),
+                    self.aux(meta, RotationOfAuxColumn::Emid)
+                        + self.aux(meta, RotationOfAuxColumn::Keep),
+                    self.aux(meta, RotationOfAuxColumn::Offset)
+                        + self.aux(meta, RotationOfAuxColumn::Drop),
+                    self.vtype(meta),
+                    self.aux(meta, RotationOfAuxColumn::Value),
+                )
+                * self.is_step(meta)
+        });
+    }
+}
+
+impl<F: FieldExt> ForeignTableConfig<F> for KeepTableConfig<F> {
+    fn configure_in_table(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        key: &'static str,
+        expr: &dyn Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+    ) {
+        meta.lookup_any(key, |meta| {
+            vec![(
+                expr(meta),
+                KeepTableEncode::encode_for_lookup(
+                    self.aux(meta, RotationOfAuxColumn::Eid),
+                    self.aux(meta, RotationOfAuxColumn::Emid),
+                    self.aux(meta, RotationOfAuxColumn::Offset),
+                    self.aux(meta, RotationOfAuxColumn::Drop),
+                    self.aux(meta, RotationOfAuxColumn::Keep),
+                    self.aux(meta, RotationOfAuxColumn::Rest),
+                ) * self.bit(meta, RotationOfBitColumn::Enable)
+                    * self.is_step(meta),
+            )]
+        });
+    }
+}
@@ -0,0 +1,54 @@
+use super::*;
+use crate::{constant_from, fixed_curr, nextn};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Expression, VirtualCells},
+    poly::Rotation,
+};
+use specs::mtable::VarType;
+
+impl<F: FieldExt> KeepTableConfig<F> {
+    pub(super) fn is_step(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        fixed_curr!(meta, self.step_sel)
+    }
+
+    pub(super) fn is_next_step(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        meta.query_fixed(self.step_sel, Rotation(STEP_SIZE as i32))
+    }
+
+    pub(super) fn bit(
+        &self,
+        meta: &mut VirtualCells<F>,
+        rot: RotationOfBitColumn,
+    ) -> Expression<F> {
+        nextn!(meta, self.bit, rot as i32)
+    }
+
+    pub(super) fn next_bit(
+        &self,
+        meta: &mut VirtualCells<F>,
+        rot: RotationOfBitColumn,
+    ) -> Expression<F> {
+        nextn!(meta, self.bit, rot as i32 + STEP_SIZE as i32)
+    }
+
+    pub(super) fn aux(
+        &self,
+        meta: &mut VirtualCells<F>,
+        rot: RotationOfAuxColumn,
+    ) -> Expression<F> {
+        nextn!(meta, self.aux, rot as i32)
+    }
+
+    pub(super) fn next_aux(
+        &self,
+        meta: &mut VirtualCells<F>,
+        rot: RotationOfAuxColumn,
+    ) -> Expression<F> {
+        nextn!(meta, self.aux, rot as i32 + STEP_SIZE as i32)
+    }
+
+    pub(super) fn vtype(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        constant_from!(VarType::I32 as u64) + self.bit(meta, RotationOfBitColumn::IsI64)
+    }
+}
@@ -0,0 +1,101 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Advice, Column, Fixed},
+};
+use num_bigint::BigUint;
+use specs::encode::FromBn;
+use std::marker::PhantomData;
+
+use super::traits::TableSize;
+
+pub(crate) mod assign;
+mod configure;
+mod expression;
+
+pub const KEEP_TABLE_KEY: &'static str = "keep-table";
+
+pub const STEP_SIZE: usize = 8;
+
+pub(crate) enum RotationOfBitColumn {
+    Enable = 0,
+    IsLast,
+    IsI64,
+}
+
+/*
+ * Eid, Drop and Keep are the same on all the steps of an operation, Rest is
+ * the number of moved values from the step to the last one.
+ */
+pub(crate) enum RotationOfAuxColumn {
+    Eid = 0,
+    Emid,
+    Offset,
+    Drop,
+    Keep,
+    Rest,
+    Value,
+}
+
+/*
+ * A branch, return or tail call keeping more values than the slots of its
+ * etable step (see OpcodeClass::max_keep) moves the others in the keep
+ * table, a step of STEP_SIZE rows for each of them, ordered from the top of
+ * the stack. The etable looks up the first step of each operation moving
+ * values.
+ *
+ * The value of a step is read at (emid, offset) and written at
+ * (emid + keep, offset + drop). Its vtype is not encoded in the opcode, it is
+ * the one of the stack entry it reads, which the mtable binds to the last
+ * write of the entry.
+ */
+#[derive(Clone)]
+pub struct KeepTableConfig<F: FieldExt> {
+    step_sel: Column<Fixed>,
+
+    // See enum RotationOfBitColumn
+    bit: Column<Advice>,
+    // See enum RotationOfAuxColumn
+    aux: Column<Advice>,
+
+    _mark: PhantomData<F>,
+}
+
+pub struct KeepTableChip<F: FieldExt> {
+    config: KeepTableConfig<F>,
+    max_available_rows: usize,
+}
+
+impl<F: FieldExt> TableSize for KeepTableChip<F> {
+    fn max_available_size(&self) -> usize {
+        self.max_available_rows
+    }
+}
+
+impl<F: FieldExt> KeepTableChip<F> {
+    pub fn new(config: KeepTableConfig<F>, max_available_rows: usize) -> Self {
+        Self {
+            config,
+            max_available_rows: max_available_rows / STEP_SIZE * STEP_SIZE,
+        }
+    }
+}
+
+pub(crate) struct KeepTableEncode;
+
+impl KeepTableEncode {
+    pub(crate) fn encode_for_lookup<T: FromBn>(
+        eid: T,
+        emid: T,
+        offset: T,
+        drop: T,
+        keep: T,
+        rest: T,
+    ) -> T {
+        eid * T::from_bn(&(BigUint::from(1u64) << 160))
+            + emid * T::from_bn(&(BigUint::from(1u64) << 128))
+            + offset * T::from_bn(&(BigUint::from(1u64) << 96))
+            + drop * T::from_bn(&(BigUint::from(1u64) << 64))
+            + keep * T::from_bn(&(BigUint::from(1u64) << 32))
+            + rest
+    }
+}
@@ -1,27 +1,96 @@
 use self::{
     brtable::{BrTableChip, BrTableConfig},
-    config::{IMTABLE_COLOMNS, VAR_COLUMNS},
+    bulk_memory_table::{
+        assign::bulk_memory_ops, BulkMemoryTableChip, BulkMemoryTableConfig, BULK_MEMORY_TABLE_KEY,
+        STEP_SIZE as BULK_MEMORY_STEP_SIZE,
+    },
+    config::{CircuitLayout, ImageTableLayout, IMTABLE_COLOMNS},
     etable_compact::{EventTableChip, EventTableConfig},
-    external_host_call_table::{ExternalHostCallChip, ExternalHostCallTableConfig},
-    jtable::{JumpTableChip, JumpTableConfig},
-    mtable_compact::{MemoryTableChip, MemoryTableConfig},
+    external_host_call_table::{
+        ExternalHostCallChip, ExternalHostCallTableConfig, ExternalHostCallVisibilityConfig,
+        EXTERNAL_HOST_CALL_VISIBILITY_TABLE_KEY,
+    },
+    image_table::{
+        image_commitment_inputs, ImageCommitmentChip, ImageCommitmentConfig, ImageConfigureChip,
+        ImageConfigureConfig, ImageTableColumn,
+    },
+    jtable::{JtableOffset, JumpTableChip, JumpTableConfig},
+    keep_table::{
+        assign::keep_ops, KeepTableChip, KeepTableConfig, KEEP_TABLE_KEY,
+        STEP_SIZE as KEEP_STEP_SIZE,
+    },
+    mtable_compact::{
+        configure::STEP_SIZE as MTABLE_STEP_SIZE, MemoryTableChip, MemoryTableConfig,
+    },
 };
 use crate::{
     circuits::{
-        config::zkwasm_k,
         imtable::{InitMemoryTableConfig, MInitTableChip},
         itable::{InstructionTableChip, InstructionTableConfig},
         rtable::{RangeTableChip, RangeTableConfig},
         utils::Context,
     },
     foreign::{
+        keccak_helper::{
+            circuits::{
+                assign::{keccak_permutations, KeccakHelperTableChip},
+                KeccakHelperTableConfig, BLOCK_LINES as KECCAK_BLOCK_LINES,
+            },
+            KECCAK_FOREIGN_TABLE_KEY,
+        },
+        kv_helper::{
+            circuits::{
+                assign::KvHelperTableChip, KvHelperTableConfig, BLOCK_LINES as KV_BLOCK_LINES,
+            },
+            KV_FOREIGN_TABLE_KEY,
+        },
+        poseidon_helper::{
+            circuits::{
+                assign::{poseidon_blocks, PoseidonHelperTableChip},
+                PoseidonHelperTableConfig, BLOCK_LINES as POSEIDON_BLOCK_LINES,
+            },
+            POSEIDON_FOREIGN_TABLE_KEY,
+        },
+        registry::{PluginRegistry, PluginTableConfig},
+        secp256k1_helper::{
+            circuits::{
+                assign::{secp256k1_signatures, Secp256k1HelperTableChip},
+                Secp256k1HelperTableConfig, BLOCK_LINES as SECP256K1_BLOCK_LINES,
+            },
+            SECP256K1_FOREIGN_TABLE_KEY,
+        },
+        sha256_compress_helper::{
+            circuits::{
+                assign::{
+                    sha256_compress_helper_calls, sha256_compressions,
+                    Sha256CompressHelperTableChip,
+                },
+                Sha256CompressHelperTableConfig, BLOCK_LINES as SHA256_COMPRESS_BLOCK_LINES,
+            },
+            SHA256_COMPRESS_FOREIGN_TABLE_KEY,
+        },
         sha256_helper::{
-            circuits::{assign::Sha256HelperTableChip, Sha256HelperTableConfig},
+            circuits::{
+                assign::{sha256_helper_calls, Sha256HelperTableChip},
+                Sha256HelperTableConfig, BLOCK_LINES as SHA256_BLOCK_LINES,
+            },
             SHA256_FOREIGN_TABLE_KEY,
         },
-        wasm_input_helper::circuits::{
-            assign::WasmInputHelperTableChip, WasmInputHelperTableConfig,
-            WASM_INPUT_FOREIGN_TABLE_KEY,
+        u256_helper::{
+            circuits::{
+                assign::{u256_operations, U256HelperTableChip},
+                U256HelperTableConfig, BLOCK_LINES as U256_BLOCK_LINES,
+            },
+            U256_FOREIGN_TABLE_KEY,
+        },
+        wasm_input_helper::{
+            circuits::{
+                assign::{WasmInputHelperTableChip, WasmPrivateInputHelperTableChip},
+                WasmInputHelperTableConfig, WasmPrivateInputHelperTableConfig,
+                WASM_INPUT_FOREIGN_TABLE_KEY, WASM_OUTPUT_FOREIGN_TABLE_KEY,
+                WASM_PRIVATE_INPUT_FOREIGN_TABLE_KEY,
+            },
+            wasm_outputs,
         },
         ForeignTableConfig,
     },
@@ -40,37 +109,124 @@ use halo2_proofs::{
 };
 use num_bigint::BigUint;
 use rand::rngs::OsRng;
-use specs::{host_function::HostPlugin, itable::OpcodeClassPlain, ExecutionTable, Tables};
+use specs::{
+    host_function::HostPlugin,
+    itable::{OpcodeClass, OpcodeClassPlain},
+    ExecutionTable, Tables,
+};
 use std::{
+    any::Any,
+    cell::RefCell,
     collections::{BTreeMap, BTreeSet},
     fs::File,
     io::{Cursor, Read},
     marker::PhantomData,
     path::PathBuf,
+    rc::Rc,
 };
+use strum::IntoEnumIterator;
 
 pub mod brtable;
+pub mod bulk_memory_table;
 pub mod config;
 pub mod etable_compact;
-mod external_host_call_table;
+pub mod external_host_call_table;
+pub mod image_table;
 pub mod imtable;
 pub mod itable;
 pub mod jtable;
+pub mod keep_table;
 pub mod mtable_compact;
 pub mod rtable;
 mod traits;
 pub mod utils;
 
-#[derive(Clone)]
+/*
+ * The configuration of the circuit derived from the image, it is part of
+ * the TestCircuitParams.
+ */
+#[derive(Default, Clone)]
 pub struct CircuitConfigure {
     pub initial_memory_pages: u32,
     pub maximal_memory_pages: u32,
     pub first_consecutive_zero_memory_offset: u32,
     pub opcode_selector: BTreeSet<OpcodeClassPlain>,
+    pub trap_mode: bool,
+    pub image_table: Option<ImageTableLayout>,
+    /*
+     * The visibility of the external host functions by op. The calls of a
+     * public function are exposed in the transcript instance column, every
+     * external host call must be listed once a function is public.
+     */
+    pub external_host_call_visibility: BTreeMap<usize, bool>,
+    /*
+     * The capacity of the private input commitment, the private inputs are
+     * committed in an instance column if it is set.
+     */
+    pub private_input_commitment: Option<usize>,
 }
 
-#[thread_local]
-static mut CIRCUIT_CONFIGURE: Option<CircuitConfigure> = None;
+impl CircuitConfigure {
+    /*
+     * The universal circuit derives nothing from the image: every opcode class
+     * and plugin is enabled, and the memory configure is committed in the image
+     * commitment along with the image tables.
+     */
+    pub fn universal(image_table: ImageTableLayout, trap_mode: bool) -> Self {
+        let mut opcode_selector = OpcodeClass::iter()
+            .filter(|class| *class != OpcodeClass::ForeignPluginStart)
+            .map(|class| OpcodeClassPlain(class as usize))
+            .collect::<BTreeSet<_>>();
+
+        for plugin in
+            HostPlugin::iter().filter(|plugin| !matches!(plugin, HostPlugin::Registered(_)))
+        {
+            opcode_selector.insert(OpcodeClassPlain(
+                OpcodeClass::ForeignPluginStart as usize + plugin.id(),
+            ));
+        }
+
+        CircuitConfigure {
+            initial_memory_pages: 0,
+            maximal_memory_pages: 0,
+            first_consecutive_zero_memory_offset: 0,
+            opcode_selector,
+            trap_mode,
+            image_table: Some(image_table),
+            external_host_call_visibility: BTreeMap::new(),
+            private_input_commitment: None,
+        }
+    }
+
+    // Whether the circuit configures the helper table of the plugin.
+    pub fn enables_plugin(&self, plugin: HostPlugin) -> bool {
+        self.opcode_selector.contains(&OpcodeClassPlain(
+            OpcodeClass::ForeignPluginStart as usize + plugin.id(),
+        ))
+    }
+
+    // Whether the circuit configures the bulk memory table, wasm_private_input_bytes
+    // writes the guest memory through it.
+    pub fn enables_bulk_memory(&self) -> bool {
+        [
+            OpcodeClass::MemoryFill,
+            OpcodeClass::MemoryCopy,
+            OpcodeClass::MemoryInit,
+        ]
+        .into_iter()
+        .any(|class| {
+            self.opcode_selector
+                .contains(&OpcodeClassPlain(class as usize))
+        }) || self.enables_plugin(HostPlugin::HostInput)
+    }
+
+    // Whether the circuit exposes the calls of public external host functions.
+    pub fn publishes_external_host_calls(&self) -> bool {
+        self.external_host_call_visibility
+            .values()
+            .any(|is_public| *is_public)
+    }
+}
 
 #[derive(Clone)]
 pub struct TestCircuitConfig<F: FieldExt> {
@@ -81,21 +237,57 @@ pub struct TestCircuitConfig<F: FieldExt> {
     jtable: JumpTableConfig<F>,
     etable: EventTableConfig<F>,
     brtable: BrTableConfig<F>,
+    bulk_memory_table: Option<BulkMemoryTableConfig<F>>,
+    keep_table: KeepTableConfig<F>,
     external_host_call_table: ExternalHostCallTableConfig<F>,
+    external_host_call_visibility_table: Option<ExternalHostCallVisibilityConfig<F>>,
     wasm_input_helper_table: WasmInputHelperTableConfig<F>,
-    sha256_helper_table: Sha256HelperTableConfig<F>,
+    wasm_private_input_helper_table: Option<WasmPrivateInputHelperTableConfig<F>>,
+    sha256_helper_table: Option<Sha256HelperTableConfig<F>>,
+    keccak_helper_table: Option<KeccakHelperTableConfig<F>>,
+    poseidon_helper_table: Option<PoseidonHelperTableConfig<F>>,
+    sha256_compress_helper_table: Option<Sha256CompressHelperTableConfig<F>>,
+    secp256k1_helper_table: Option<Secp256k1HelperTableConfig<F>>,
+    u256_helper_table: Option<U256HelperTableConfig<F>>,
+    kv_helper_table: Option<KvHelperTableConfig<F>>,
+    plugin_tables: Vec<(HostPlugin, Rc<dyn PluginTableConfig<F>>)>,
+    image_configure: ImageConfigureConfig<F>,
+    image_commitment: Option<ImageCommitmentConfig<F>>,
 }
 
 #[derive(Default, Clone)]
 pub struct TestCircuit<F: FieldExt> {
     pub tables: Tables,
+    pub layout: CircuitLayout,
+    pub circuit_configure: CircuitConfigure,
+    pub plugins: PluginRegistry<F>,
+    // The private inputs committed in the private input commitment mode.
+    pub private_inputs: Vec<u64>,
     _data: PhantomData<F>,
 }
 
 impl<F: FieldExt> TestCircuit<F> {
     pub fn new(tables: Tables) -> Self {
-        unsafe {
-            CIRCUIT_CONFIGURE = Some(CircuitConfigure {
+        Self::new_with_layout(tables, CircuitLayout::default())
+    }
+
+    pub fn new_with_layout(tables: Tables, layout: CircuitLayout) -> Self {
+        Self::new_with_mode(tables, layout, false)
+    }
+
+    /*
+     * The proof attests that the execution trapped, the trap point (see
+     * EventTable::trap_point) is the only row of its instance column, which
+     * follows the outputs, the kv roots and the private input commitment.
+     */
+    pub fn new_trapped(tables: Tables) -> Self {
+        Self::new_with_mode(tables, CircuitLayout::default(), true)
+    }
+
+    fn new_with_mode(tables: Tables, layout: CircuitLayout, trap_mode: bool) -> Self {
+        let circuit_configure = match layout.image_table {
+            Some(image_table) => CircuitConfigure::universal(image_table, trap_mode),
+            None => CircuitConfigure {
                 first_consecutive_zero_memory_offset: tables
                     .compilation_tables
                     .imtable
@@ -106,31 +298,227 @@ impl<F: FieldExt> TestCircuit<F> {
                     .configure_table
                     .maximal_memory_pages,
                 opcode_selector: tables.compilation_tables.itable.opcode_class(),
-            });
-        }
+                trap_mode,
+                image_table: None,
+                external_host_call_visibility: BTreeMap::new(),
+                private_input_commitment: None,
+            },
+        };
 
         TestCircuit {
             tables,
+            layout,
+            circuit_configure,
+            plugins: PluginRegistry::new(),
+            private_inputs: vec![],
             _data: PhantomData,
         }
     }
+
+    /*
+     * The registered plugins take part in the circuit, the universal circuit
+     * enables all of them.
+     */
+    pub fn with_plugins(mut self, plugins: PluginRegistry<F>) -> Self {
+        if self.circuit_configure.image_table.is_some() {
+            for (plugin, _) in plugins.plugins() {
+                self.circuit_configure
+                    .opcode_selector
+                    .insert(OpcodeClassPlain(
+                        OpcodeClass::ForeignPluginStart as usize + plugin.id(),
+                    ));
+            }
+        }
+
+        TestCircuit { plugins, ..self }
+    }
+
+    /*
+     * The calls of the public external host functions are exposed in the
+     * transcript instance column (see external_host_call_transcript).
+     */
+    pub fn with_external_host_call_visibility(
+        mut self,
+        external_host_call_visibility: BTreeMap<usize, bool>,
+    ) -> Self {
+        self.circuit_configure.external_host_call_visibility = external_host_call_visibility;
+
+        self
+    }
+
+    /*
+     * Tune the layout to the tables of the circuit, the rows of an etable step
+     * are found out by configuring the circuit on a scratch constraint system.
+     */
+    pub fn fit_layout(&self) -> anyhow::Result<CircuitLayout> {
+        let config = Self::configure_with_params(&mut ConstraintSystem::default(), self.params());
+
+        self.layout.fit(&self.tables, config.etable.step_size())
+    }
+
+    /*
+     * The private inputs passed to the runtime are committed in the private
+     * input commitment (see private_input_commitment), the private inputs
+     * consumed by wasm_input and wasm_private_input_bytes are looked up in the
+     * committed inputs.
+     */
+    pub fn with_private_input_commitment(
+        mut self,
+        capacity: usize,
+        private_inputs: Vec<u64>,
+    ) -> Self {
+        self.circuit_configure.private_input_commitment = Some(capacity);
+
+        TestCircuit {
+            private_inputs,
+            ..self
+        }
+    }
 }
 
-impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
-    type Config = TestCircuitConfig<F>;
+impl CircuitLayout {
+    /*
+     * Tune the ratios of the layout to the table sizes of a trace, the trace
+     * must fit in the circuit with the K of the layout. The rows of an etable
+     * step depend on the enabled opcode classes, see TestCircuit::fit_layout.
+     */
+    pub fn fit(self, tables: &Tables, etable_step_size: usize) -> anyhow::Result<Self> {
+        let layout = self;
+        let etable = &tables.execution_tables.etable;
 
-    type FloorPlanner = SimpleFloorPlanner;
+        Ok(CircuitLayout {
+            etable_ratio: layout.fit_ratio((etable.entries().len() + 1) * etable_step_size)?,
+            mtable_ratio: layout.fit_ratio(
+                (tables.execution_tables.mtable.entries().len() + 1) * MTABLE_STEP_SIZE as usize,
+            )?,
+            jtable_ratio: layout.fit_ratio(
+                (tables.compilation_tables.static_jtable.len()
+                    + tables.execution_tables.jtable.entries().len()
+                    + 1)
+                    * JtableOffset::JtableOffsetMax as usize,
+            )?,
+            foreign_call_table_ratio: layout
+                .fit_ratio(etable.filter_external_host_call_table().entries().len() + 1)?,
+            bulk_memory_ratio: layout.fit_ratio(
+                bulk_memory_ops(etable, &tables.execution_tables.host_memory_accesses, &[])
+                    .iter()
+                    .map(|op| op.steps())
+                    .sum::<usize>()
+                    * BULK_MEMORY_STEP_SIZE,
+            )?,
+            keep_ratio: layout.fit_ratio(
+                keep_ops(etable).iter().map(|op| op.steps()).sum::<usize>() * KEEP_STEP_SIZE,
+            )?,
+            sha256_ratio: layout.fit_ratio(
+                (etable.filter_foreign_entries(HostPlugin::Sha256).len()
+                    + sha256_compress_helper_calls(
+                        &etable.filter_foreign_entries(HostPlugin::Sha256Compress),
+                    )
+                    .len())
+                    * SHA256_BLOCK_LINES,
+            )?,
+            keccak_ratio: layout.fit_ratio(
+                keccak_permutations(&etable.filter_foreign_entries(HostPlugin::Keccak)).len()
+                    * KECCAK_BLOCK_LINES,
+            )?,
+            poseidon_ratio: layout.fit_ratio(
+                poseidon_blocks(&etable.filter_foreign_entries(HostPlugin::Poseidon)).len()
+                    * POSEIDON_BLOCK_LINES,
+            )?,
+            sha256_compress_ratio: layout.fit_ratio(
+                sha256_compressions(&etable.filter_foreign_entries(HostPlugin::Sha256Compress))
+                    .len()
+                    * SHA256_COMPRESS_BLOCK_LINES,
+            )?,
+            secp256k1_ratio: layout.fit_ratio(
+                secp256k1_signatures(&etable.filter_foreign_entries(HostPlugin::Secp256k1)).len()
+                    * SECP256K1_BLOCK_LINES,
+            )?,
+            u256_ratio: layout.fit_ratio(
+                u256_operations(&etable.filter_foreign_entries(HostPlugin::U256)).len()
+                    * U256_BLOCK_LINES,
+            )?,
+            kv_ratio: layout
+                .fit_ratio(etable.filter_foreign_entries(HostPlugin::Kv).len() * KV_BLOCK_LINES)?,
+            ..layout
+        })
+    }
 
-    fn without_witnesses(&self) -> Self {
-        TestCircuit::new(Tables {
-            compilation_tables: self.tables.compilation_tables.clone(),
-            execution_tables: ExecutionTable::default(),
+    // Tune plugin_ratio to the largest helper table of the registered plugins.
+    pub fn fit_plugins<F: FieldExt>(
+        self,
+        tables: &Tables,
+        plugins: &PluginRegistry<F>,
+    ) -> anyhow::Result<Self> {
+        let etable = &tables.execution_tables.etable;
+
+        Ok(CircuitLayout {
+            plugin_ratio: self.fit_ratio(
+                plugins
+                    .plugins()
+                    .map(|(plugin, registered)| {
+                        registered.rows(&etable.filter_foreign_entries(plugin))
+                    })
+                    .max()
+                    .unwrap_or(0),
+            )?,
+            ..self
         })
     }
+}
 
-    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-        let circuit_configure = unsafe { CIRCUIT_CONFIGURE.clone().unwrap() };
+/*
+ * The params of TestCircuit: the circuit configure and the registered plugins.
+ *
+ * halo2 configures a circuit by Circuit::configure, without an instance of it.
+ * The halo2 calls configuring a TestCircuit, e.g. keygen, create_proof or
+ * reading a vkey, run within with_circuit_params, which exposes the params to
+ * Circuit::configure on the current thread for the duration of the call.
+ *
+ * This is a workaround for the halo2 we depend on, whose Circuit trait has no
+ * params, and should go once it has. The params are a thread local stack: a
+ * nested call configures with its own params and restores the outer ones when
+ * it returns, and a call moving the configuration to another thread does not
+ * see them. Configuring a TestCircuit out of with_circuit_params panics.
+ */
+pub type TestCircuitParams<F> = (CircuitConfigure, PluginRegistry<F>);
+
+thread_local! {
+    static CONFIGURING_PARAMS: RefCell<Vec<Rc<dyn Any>>> = RefCell::new(vec![]);
+}
+
+pub fn with_circuit_params<F: FieldExt, T>(
+    params: TestCircuitParams<F>,
+    f: impl FnOnce() -> T,
+) -> T {
+    struct PopOnDrop;
+
+    impl Drop for PopOnDrop {
+        fn drop(&mut self) {
+            CONFIGURING_PARAMS.with(|params| params.borrow_mut().pop());
+        }
+    }
+
+    CONFIGURING_PARAMS.with(|configuring| configuring.borrow_mut().push(Rc::new(params)));
+    let _guard = PopOnDrop;
+
+    f()
+}
 
+impl<F: FieldExt> TestCircuit<F> {
+    pub fn params(&self) -> TestCircuitParams<F> {
+        (self.circuit_configure.clone(), self.plugins.clone())
+    }
+
+    // Run a halo2 call configuring the circuit, see with_circuit_params.
+    pub fn with_params<T>(&self, f: impl FnOnce() -> T) -> T {
+        with_circuit_params(self.params(), f)
+    }
+
+    fn configure_with_params(
+        meta: &mut ConstraintSystem<F>,
+        (circuit_configure, plugins): TestCircuitParams<F>,
+    ) -> TestCircuitConfig<F> {
         /*
          * Allocate a column to enable assign_advice_from_constant.
          */
@@ -140,21 +528,26 @@ impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
             meta.enable_equality(constants);
         }
 
-        let mut cols = [(); VAR_COLUMNS].map(|_| meta.advice_column()).into_iter();
+        let image_table = circuit_configure.image_table;
 
         let rtable = RangeTableConfig::configure([0; 7].map(|_| meta.lookup_table_column()));
-        let itable = InstructionTableConfig::configure(meta.lookup_table_column());
-        let imtable = InitMemoryTableConfig::configure(
-            [0; IMTABLE_COLOMNS].map(|_| meta.lookup_table_column()),
-        );
-        let mtable =
-            MemoryTableConfig::configure(meta, &mut cols, &rtable, &imtable, &circuit_configure);
-        let jtable = JumpTableConfig::configure(meta, &mut cols);
-        let brtable = BrTableConfig::configure(meta.lookup_table_column());
-        let external_host_call_table = ExternalHostCallTableConfig::configure(meta);
+        let itable = InstructionTableConfig::configure(ImageTableColumn::configure(
+            meta,
+            image_table.map(|layout| layout.itable_rows),
+        ));
+        let imtable = InitMemoryTableConfig::configure([0; IMTABLE_COLOMNS].map(|_| {
+            ImageTableColumn::configure(meta, image_table.map(|layout| layout.imtable_rows))
+        }));
+        let image_configure = ImageConfigureConfig::configure(meta, &circuit_configure);
+        let mtable = MemoryTableConfig::configure(meta, &rtable, &imtable, &image_configure);
+        let jtable = JumpTableConfig::configure(meta, &circuit_configure);
+        let brtable = BrTableConfig::configure(ImageTableColumn::configure(
+            meta,
+            image_table.map(|layout| layout.brtable_rows),
+        ));
+        let mut external_host_call_table = ExternalHostCallTableConfig::configure(meta);
 
         let wasm_input_helper_table = WasmInputHelperTableConfig::configure(meta, &rtable);
-        let sha256_helper_table = Sha256HelperTableConfig::configure(meta, &rtable);
 
         let mut foreign_tables = BTreeMap::<&'static str, Box<dyn ForeignTableConfig<_>>>::new();
         foreign_tables.insert(
@@ -162,14 +555,222 @@ impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
             Box::new(wasm_input_helper_table.clone()),
         );
         foreign_tables.insert(
-            SHA256_FOREIGN_TABLE_KEY,
-            Box::new(sha256_helper_table.clone()),
+            WASM_OUTPUT_FOREIGN_TABLE_KEY,
+            Box::new(wasm_input_helper_table.output_table()),
         );
 
+        /*
+         * The block values written by the bulk memory operations are
+         * constrained in the bulk memory table, which is only configured for
+         * an image using them.
+         */
+        let bulk_memory_table = if circuit_configure.enables_bulk_memory() {
+            let bulk_memory_table = BulkMemoryTableConfig::configure(meta, &rtable, &mtable);
+
+            foreign_tables.insert(BULK_MEMORY_TABLE_KEY, Box::new(bulk_memory_table.clone()));
+
+            Some(bulk_memory_table)
+        } else {
+            None
+        };
+
+        // The values kept beyond the slots of a step are moved in the keep table.
+        let keep_table = KeepTableConfig::configure(meta, &mtable);
+        foreign_tables.insert(KEEP_TABLE_KEY, Box::new(keep_table.clone()));
+
+        /*
+         * The keccak and poseidon helper tables take many columns, each of them
+         * is only configured for an image calling its plugin.
+         */
+        let keccak_helper_table = if circuit_configure
+            .opcode_selector
+            .contains(&OpcodeClassPlain(
+                OpcodeClass::ForeignPluginStart as usize + HostPlugin::Keccak.id(),
+            )) {
+            let keccak_helper_table = KeccakHelperTableConfig::configure(meta);
+
+            foreign_tables.insert(
+                KECCAK_FOREIGN_TABLE_KEY,
+                Box::new(keccak_helper_table.clone()),
+            );
+
+            Some(keccak_helper_table)
+        } else {
+            None
+        };
+
+        let poseidon_helper_table =
+            if circuit_configure
+                .opcode_selector
+                .contains(&OpcodeClassPlain(
+                    OpcodeClass::ForeignPluginStart as usize + HostPlugin::Poseidon.id(),
+                ))
+            {
+                let poseidon_helper_table = PoseidonHelperTableConfig::configure(meta, &rtable);
+
+                foreign_tables.insert(
+                    POSEIDON_FOREIGN_TABLE_KEY,
+                    Box::new(poseidon_helper_table.clone()),
+                );
+
+                Some(poseidon_helper_table)
+            } else {
+                None
+            };
+
+        /*
+         * The sha256 helper table is looked up by the sha256 plugin and by the
+         * rounds of the compression table, it is only configured for an image
+         * calling one of them.
+         */
+        let sha256_helper_table =
+            if [HostPlugin::Sha256, HostPlugin::Sha256Compress]
+                .iter()
+                .any(|plugin| {
+                    circuit_configure
+                        .opcode_selector
+                        .contains(&OpcodeClassPlain(
+                            OpcodeClass::ForeignPluginStart as usize + plugin.id(),
+                        ))
+                })
+            {
+                let sha256_helper_table = Sha256HelperTableConfig::configure(meta, &rtable);
+
+                foreign_tables.insert(
+                    SHA256_FOREIGN_TABLE_KEY,
+                    Box::new(sha256_helper_table.clone()),
+                );
+
+                Some(sha256_helper_table)
+            } else {
+                None
+            };
+
+        let sha256_compress_helper_table =
+            if circuit_configure
+                .opcode_selector
+                .contains(&OpcodeClassPlain(
+                    OpcodeClass::ForeignPluginStart as usize + HostPlugin::Sha256Compress.id(),
+                ))
+            {
+                let sha256_compress_helper_table = Sha256CompressHelperTableConfig::configure(
+                    meta,
+                    &rtable,
+                    sha256_helper_table.as_ref().unwrap(),
+                );
+
+                foreign_tables.insert(
+                    SHA256_COMPRESS_FOREIGN_TABLE_KEY,
+                    Box::new(sha256_compress_helper_table.clone()),
+                );
+
+                Some(sha256_compress_helper_table)
+            } else {
+                None
+            };
+
+        let secp256k1_helper_table =
+            if circuit_configure
+                .opcode_selector
+                .contains(&OpcodeClassPlain(
+                    OpcodeClass::ForeignPluginStart as usize + HostPlugin::Secp256k1.id(),
+                ))
+            {
+                let secp256k1_helper_table = Secp256k1HelperTableConfig::configure(meta, &rtable);
+
+                foreign_tables.insert(
+                    SECP256K1_FOREIGN_TABLE_KEY,
+                    Box::new(secp256k1_helper_table.clone()),
+                );
+
+                Some(secp256k1_helper_table)
+            } else {
+                None
+            };
+
+        let u256_helper_table = if circuit_configure
+            .opcode_selector
+            .contains(&OpcodeClassPlain(
+                OpcodeClass::ForeignPluginStart as usize + HostPlugin::U256.id(),
+            )) {
+            let u256_helper_table = U256HelperTableConfig::configure(meta, &rtable);
+
+            foreign_tables.insert(U256_FOREIGN_TABLE_KEY, Box::new(u256_helper_table.clone()));
+
+            Some(u256_helper_table)
+        } else {
+            None
+        };
+
+        /*
+         * The kv helper table exposes the roots of the store, its instance
+         * column comes after the input and output columns and before the columns
+         * of etable.
+         */
+        let kv_helper_table = if circuit_configure.enables_plugin(HostPlugin::Kv) {
+            let kv_helper_table = KvHelperTableConfig::configure(meta);
+
+            foreign_tables.insert(KV_FOREIGN_TABLE_KEY, Box::new(kv_helper_table.clone()));
+
+            Some(kv_helper_table)
+        } else {
+            None
+        };
+
+        /*
+         * The private input commitment follows the kv roots, the table is only
+         * configured in the private input commitment mode.
+         */
+        let wasm_private_input_helper_table =
+            if let Some(capacity) = circuit_configure.private_input_commitment {
+                let wasm_private_input_helper_table =
+                    WasmPrivateInputHelperTableConfig::configure(meta, capacity);
+
+                foreign_tables.insert(
+                    WASM_PRIVATE_INPUT_FOREIGN_TABLE_KEY,
+                    Box::new(wasm_private_input_helper_table.clone()),
+                );
+
+                // The private inputs written by wasm_private_input_bytes.
+                if let Some(bulk_memory_table) = bulk_memory_table.as_ref() {
+                    bulk_memory_table
+                        .configure_private_input_lookup(meta, &wasm_private_input_helper_table);
+                }
+
+                Some(wasm_private_input_helper_table)
+            } else {
+                None
+            };
+
+        let external_host_call_visibility_table =
+            if circuit_configure.publishes_external_host_calls() {
+                let external_host_call_visibility_table =
+                    ExternalHostCallVisibilityConfig::configure(meta);
+
+                foreign_tables.insert(
+                    EXTERNAL_HOST_CALL_VISIBILITY_TABLE_KEY,
+                    Box::new(external_host_call_visibility_table.clone()),
+                );
+
+                Some(external_host_call_visibility_table)
+            } else {
+                None
+            };
+
+        let mut plugin_tables = vec![];
+        for (plugin, registered) in plugins.plugins() {
+            if circuit_configure.enables_plugin(plugin) {
+                let plugin_table = registered.configure(meta, &rtable);
+
+                foreign_tables.insert(registered.table_key(), Box::new(plugin_table.clone()));
+                plugin_tables.push((plugin, plugin_table));
+            }
+        }
+
         let etable = EventTableConfig::configure(
             meta,
-            &mut cols,
             &circuit_configure,
+            &image_configure,
             &rtable,
             &itable,
             &mtable,
@@ -177,10 +778,24 @@ impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
             &brtable,
             &external_host_call_table,
             &foreign_tables,
+            &plugins,
             &circuit_configure.opcode_selector,
         );
 
-        Self::Config {
+        /*
+         * The transcript of the public external host calls comes after the
+         * trap point and before the image commitment.
+         */
+        if circuit_configure.publishes_external_host_calls() {
+            external_host_call_table.configure_transcript(meta);
+        }
+
+        /*
+         * Configured after etable, the image commitment is the last instance column.
+         */
+        let image_commitment = image_table.map(|_| ImageCommitmentConfig::configure(meta));
+
+        TestCircuitConfig {
             rtable,
             itable,
             imtable,
@@ -188,11 +803,64 @@ impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
             jtable,
             etable,
             brtable,
+            bulk_memory_table,
+            keep_table,
             external_host_call_table,
+            external_host_call_visibility_table,
             wasm_input_helper_table,
+            wasm_private_input_helper_table,
             sha256_helper_table,
+            keccak_helper_table,
+            poseidon_helper_table,
+            sha256_compress_helper_table,
+            secp256k1_helper_table,
+            u256_helper_table,
+            kv_helper_table,
+            plugin_tables,
+            image_configure,
+            image_commitment,
         }
     }
+}
+
+impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+    type Config = TestCircuitConfig<F>;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        let circuit = TestCircuit::new_with_mode(
+            Tables {
+                compilation_tables: self.tables.compilation_tables.clone(),
+                execution_tables: ExecutionTable::default(),
+            },
+            self.layout,
+            self.circuit_configure.trap_mode,
+        )
+        .with_plugins(self.plugins.clone())
+        .with_external_host_call_visibility(
+            self.circuit_configure.external_host_call_visibility.clone(),
+        );
+
+        match self.circuit_configure.private_input_commitment {
+            Some(capacity) => circuit.with_private_input_commitment(capacity, vec![]),
+            None => circuit,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let params = CONFIGURING_PARAMS
+            .with(|params| params.borrow().last().cloned())
+            .expect("TestCircuit is configured out of with_circuit_params");
+
+        Self::configure_with_params(
+            meta,
+            params
+                .downcast_ref::<TestCircuitParams<F>>()
+                .expect("the params are not the ones of the field")
+                .clone(),
+        )
+    }
 
     fn synthesize(
         &self,
@@ -202,26 +870,149 @@ impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
         let rchip = RangeTableChip::new(config.rtable);
         let ichip = InstructionTableChip::new(config.itable);
         let imchip = MInitTableChip::new(config.imtable);
-        let mchip = MemoryTableChip::new(config.mtable);
-        let jchip = JumpTableChip::new(config.jtable);
-        let echip = EventTableChip::new(config.etable);
+        let mchip = MemoryTableChip::new(config.mtable, self.layout.max_mtable_rows() as usize);
+        let jchip = JumpTableChip::new(config.jtable, self.layout.max_jtable_rows() as usize);
+        let echip = EventTableChip::new(config.etable, self.layout.max_etable_rows() as usize);
         let brchip = BrTableChip::new(config.brtable);
-        let external_host_call_chip = ExternalHostCallChip::new(config.external_host_call_table);
+        let external_host_call_chip = ExternalHostCallChip::new(
+            config.external_host_call_table,
+            self.layout.max_foreign_call_table_rows() as usize,
+        );
         let wasm_input_chip = WasmInputHelperTableChip::new(config.wasm_input_helper_table);
-        let sha256chip = Sha256HelperTableChip::new(config.sha256_helper_table);
+        let image_configure_chip = ImageConfigureChip::new(
+            config.image_configure,
+            usize::max(
+                self.layout.max_etable_rows() as usize,
+                self.layout.max_mtable_rows() as usize,
+            ),
+        );
 
         rchip.init(&mut layouter)?;
         wasm_input_chip.init(&mut layouter)?;
-        sha256chip.init(&mut layouter)?;
 
-        sha256chip.assign(
+        let sha256_compress_entries = self
+            .tables
+            .execution_tables
+            .etable
+            .filter_foreign_entries(HostPlugin::Sha256Compress);
+
+        if let Some(sha256_helper_table) = config.sha256_helper_table {
+            let sha256chip = Sha256HelperTableChip::new(
+                sha256_helper_table,
+                self.layout.max_sha256_rows() as usize,
+            );
+
+            sha256chip.init(&mut layouter)?;
+            sha256chip.assign(
+                &mut layouter,
+                &sha256_helper_calls(
+                    &self
+                        .tables
+                        .execution_tables
+                        .etable
+                        .filter_foreign_entries(HostPlugin::Sha256),
+                )
+                .into_iter()
+                .chain(sha256_compress_helper_calls(&sha256_compress_entries))
+                .collect(),
+            )?;
+        }
+        if let Some(sha256_compress_helper_table) = config.sha256_compress_helper_table {
+            Sha256CompressHelperTableChip::new(
+                sha256_compress_helper_table,
+                self.layout.max_sha256_compress_rows() as usize,
+            )
+            .assign(&mut layouter, &sha256_compress_entries)?;
+        }
+        if let Some(keccak_helper_table) = config.keccak_helper_table {
+            KeccakHelperTableChip::new(keccak_helper_table, self.layout.max_keccak_rows() as usize)
+                .assign(
+                    &mut layouter,
+                    &self
+                        .tables
+                        .execution_tables
+                        .etable
+                        .filter_foreign_entries(HostPlugin::Keccak),
+                )?;
+        }
+        if let Some(poseidon_helper_table) = config.poseidon_helper_table {
+            PoseidonHelperTableChip::new(
+                poseidon_helper_table,
+                self.layout.max_poseidon_rows() as usize,
+            )
+            .assign(
+                &mut layouter,
+                &self
+                    .tables
+                    .execution_tables
+                    .etable
+                    .filter_foreign_entries(HostPlugin::Poseidon),
+            )?;
+        }
+        if let Some(secp256k1_helper_table) = config.secp256k1_helper_table {
+            Secp256k1HelperTableChip::new(
+                secp256k1_helper_table,
+                self.layout.max_secp256k1_rows() as usize,
+            )
+            .assign(
+                &mut layouter,
+                &self
+                    .tables
+                    .execution_tables
+                    .etable
+                    .filter_foreign_entries(HostPlugin::Secp256k1),
+            )?;
+        }
+        if let Some(u256_helper_table) = config.u256_helper_table {
+            U256HelperTableChip::new(u256_helper_table, self.layout.max_u256_rows() as usize)
+                .assign(
+                    &mut layouter,
+                    &self
+                        .tables
+                        .execution_tables
+                        .etable
+                        .filter_foreign_entries(HostPlugin::U256),
+                )?;
+        }
+        if let Some(bulk_memory_table) = config.bulk_memory_table {
+            BulkMemoryTableChip::new(
+                bulk_memory_table,
+                self.layout.max_bulk_memory_rows() as usize,
+            )
+            .assign(
+                &mut layouter,
+                &bulk_memory_ops(
+                    &self.tables.execution_tables.etable,
+                    &self.tables.execution_tables.host_memory_accesses,
+                    &self.private_inputs,
+                ),
+            )?;
+        }
+        KeepTableChip::new(config.keep_table, self.layout.max_keep_rows() as usize).assign(
             &mut layouter,
-            &self
+            &keep_ops(&self.tables.execution_tables.etable),
+        )?;
+        if let Some(kv_helper_table) = config.kv_helper_table {
+            KvHelperTableChip::new(kv_helper_table, self.layout.max_kv_rows() as usize)
+                .assign(&mut layouter, &self.tables.execution_tables.kvtable)?;
+        }
+        for (plugin, plugin_table) in config.plugin_tables {
+            let entries = self
                 .tables
                 .execution_tables
                 .etable
-                .filter_foreign_entries(HostPlugin::Sha256),
-        )?;
+                .filter_foreign_entries(plugin);
+
+            layouter.assign_region(
+                || "registered plugin assign",
+                |region| {
+                    let mut ctx = Context::new(region);
+
+                    plugin_table.assign(&mut ctx, self.layout.max_plugin_rows() as usize, &entries)
+                },
+            )?;
+        }
+
         wasm_input_chip.assign(
             &mut layouter,
             &self
@@ -230,16 +1021,24 @@ impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
                 .etable
                 .filter_foreign_entries(HostPlugin::HostInput),
         )?;
+        if let Some(wasm_private_input_helper_table) = config.wasm_private_input_helper_table {
+            WasmPrivateInputHelperTableChip::new(wasm_private_input_helper_table)
+                .assign(&mut layouter, &self.private_inputs)?;
+        }
 
-        ichip.assign(&mut layouter, &self.tables.compilation_tables.itable)?;
-        brchip.assign(
+        let itable_cells = ichip.assign(&mut layouter, &self.tables.compilation_tables.itable)?;
+        let brtable_cells = brchip.assign(
             &mut layouter,
             &self.tables.compilation_tables.itable.create_brtable(),
-            &self.tables.compilation_tables.elem_table,
+            &self.tables.compilation_tables.table_layouts,
         )?;
-        if self.tables.compilation_tables.imtable.entries().len() > 0 {
-            imchip.assign(&mut layouter, &self.tables.compilation_tables.imtable)?;
-        }
+        let imtable_cells = if self.circuit_configure.image_table.is_some()
+            || self.tables.compilation_tables.imtable.entries().len() > 0
+        {
+            imchip.assign(&mut layouter, &self.tables.compilation_tables.imtable)?
+        } else {
+            vec![]
+        };
 
         external_host_call_chip.assign(
             &mut layouter,
@@ -248,18 +1047,36 @@ impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
                 .execution_tables
                 .etable
                 .filter_external_host_call_table(),
+            &self.circuit_configure.external_host_call_visibility,
         )?;
 
-        layouter.assign_region(
+        if let Some(external_host_call_visibility_table) =
+            &config.external_host_call_visibility_table
+        {
+            external_host_call_visibility_table.assign(
+                &mut layouter,
+                &self.circuit_configure.external_host_call_visibility,
+            )?;
+        }
+
+        let callee_fids = self
+            .tables
+            .execution_tables
+            .etable
+            .callee_fids(&self.tables.compilation_tables.static_jtable);
+
+        let (init_memory_pages_cell, static_slot_cells) = layouter.assign_region(
             || "jtable mtable etable",
             |region| {
                 let mut ctx = Context::new(region);
 
-                let (rest_mops_cell, rest_jops_cell) = {
+                let (rest_mops_cell, rest_jops_cell, init_memory_pages_cell) = {
                     echip.assign(
                         &mut ctx,
                         &self.tables.execution_tables.etable,
                         self.tables.compilation_tables.configure_table,
+                        &self.tables.compilation_tables.table_layouts,
+                        &callee_fids,
                     )?
                 };
 
@@ -275,17 +1092,53 @@ impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
                 )?;
 
                 ctx.reset();
-                jchip.assign(
+                let static_slot_cells = jchip.assign(
                     &mut ctx,
                     &self.tables.execution_tables.jtable,
                     rest_jops_cell,
                     &self.tables.compilation_tables.static_jtable,
+                    &self.tables.execution_tables.etable,
+                    &callee_fids,
                 )?;
 
-                Ok(())
+                Ok((init_memory_pages_cell, static_slot_cells))
             },
         )?;
 
+        let configure_cells = image_configure_chip.assign(
+            &mut layouter,
+            self.tables
+                .compilation_tables
+                .configure_table
+                .maximal_memory_pages,
+            self.tables
+                .compilation_tables
+                .imtable
+                .first_consecutive_zero_memory(),
+        )?;
+
+        if let (Some(image_commitment), Some(image_table)) =
+            (config.image_commitment, self.circuit_configure.image_table)
+        {
+            let header_sources = init_memory_pages_cell
+                .into_iter()
+                .chain(configure_cells)
+                .chain(static_slot_cells)
+                .collect::<Vec<_>>();
+            let column_sources = vec![itable_cells, brtable_cells]
+                .into_iter()
+                .chain(imtable_cells)
+                .collect::<Vec<_>>();
+
+            ImageCommitmentChip::new(image_commitment).assign(
+                &mut layouter,
+                &image_commitment_inputs(&self.tables.compilation_tables, &image_table)
+                    .map_err(|_| Error::Synthesis)?,
+                &header_sources,
+                &column_sources,
+            )?;
+        }
+
         Ok(())
     }
 }
@@ -318,7 +1171,7 @@ impl ZkWasmCircuitBuilder {
         TestCircuit::new(self.tables.clone())
     }
 
-    fn prepare_param(&self) -> Params<G1Affine> {
+    fn prepare_param(&self, k: u32) -> Params<G1Affine> {
         let path = PathBuf::from(PARAMS);
 
         if path.exists() {
@@ -329,8 +1182,8 @@ impl ZkWasmCircuitBuilder {
             Params::<G1Affine>::read(Cursor::new(buf)).unwrap()
         } else {
             // Initialize the polynomial commitment parameters
-            let timer = start_timer!(|| format!("build params with K = {}", zkwasm_k()));
-            let params: Params<G1Affine> = Params::<G1Affine>::unsafe_setup::<Bn256>(zkwasm_k());
+            let timer = start_timer!(|| format!("build params with K = {}", k));
+            let params: Params<G1Affine> = Params::<G1Affine>::unsafe_setup::<Bn256>(k);
             end_timer!(timer);
 
             let mut fd = File::create(path.as_path()).unwrap();
@@ -346,7 +1199,9 @@ impl ZkWasmCircuitBuilder {
         params: &Params<G1Affine>,
     ) -> VerifyingKey<G1Affine> {
         let timer = start_timer!(|| "build vk");
-        let vk = keygen_vk(params, circuit).expect("keygen_vk should not fail");
+        let vk = circuit
+            .with_params(|| keygen_vk(params, circuit))
+            .expect("keygen_vk should not fail");
         end_timer!(timer);
 
         vk
@@ -359,7 +1214,9 @@ impl ZkWasmCircuitBuilder {
         vk: VerifyingKey<G1Affine>,
     ) -> ProvingKey<G1Affine> {
         let timer = start_timer!(|| "build pk");
-        let pk = keygen_pk(&params, vk, circuit).expect("keygen_pk should not fail");
+        let pk = circuit
+            .with_params(|| keygen_pk(&params, vk, circuit))
+            .expect("keygen_pk should not fail");
         end_timer!(timer);
         pk
     }
@@ -369,20 +1226,23 @@ impl ZkWasmCircuitBuilder {
         circuits: &[TestCircuit<Fr>],
         params: &Params<G1Affine>,
         pk: &ProvingKey<G1Affine>,
-        public_inputs: &Vec<Fr>,
+        instances: &Vec<Vec<Fr>>,
     ) -> Vec<u8> {
         let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
 
         let timer = start_timer!(|| "create proof");
-        create_proof(
-            params,
-            pk,
-            circuits,
-            &[&[public_inputs]],
-            OsRng,
-            &mut transcript,
-        )
-        .expect("proof generation should not fail");
+        circuits[0]
+            .with_params(|| {
+                create_proof(
+                    params,
+                    pk,
+                    circuits,
+                    &[&instances.iter().map(|x| &x[..]).collect::<Vec<_>>()[..]],
+                    OsRng,
+                    &mut transcript,
+                )
+            })
+            .expect("proof generation should not fail");
         end_timer!(timer);
 
         transcript.finalize()
@@ -393,9 +1253,9 @@ impl ZkWasmCircuitBuilder {
         vk: &VerifyingKey<G1Affine>,
         params: &Params<G1Affine>,
         proof: &Vec<u8>,
-        public_inputs: &Vec<Fr>,
+        instances: &Vec<Vec<Fr>>,
     ) {
-        let public_inputs_size = public_inputs.len();
+        let public_inputs_size = instances.iter().map(|x| x.len()).max().unwrap();
 
         let params_verifier: ParamsVerifier<Bn256> = params.verifier(public_inputs_size).unwrap();
 
@@ -407,7 +1267,7 @@ impl ZkWasmCircuitBuilder {
             &params_verifier,
             vk,
             strategy,
-            &[&[public_inputs]],
+            &[&instances.iter().map(|x| &x[..]).collect::<Vec<_>>()[..]],
             &mut transcript,
         )
         .unwrap();
@@ -417,13 +1277,21 @@ impl ZkWasmCircuitBuilder {
     pub fn bench(&self, public_inputs: Vec<Fr>) {
         let circuit: TestCircuit<Fr> = self.build_circuit::<Fr>();
 
-        let params = self.prepare_param();
+        let params = self.prepare_param(circuit.layout.k);
 
         let vk = self.prepare_vk(&circuit, &params);
         let pk = self.prepare_pk(&circuit, &params, vk);
 
-        let proof = self.create_proof(&[circuit], &params, &pk, &public_inputs);
+        let instances = vec![
+            public_inputs,
+            wasm_outputs(&self.tables.execution_tables.etable)
+                .into_iter()
+                .map(|v| Fr::from(v))
+                .collect(),
+        ];
+
+        let proof = self.create_proof(&[circuit], &params, &pk, &instances);
 
-        self.verify_check(pk.get_vk(), &params, &proof, &public_inputs);
+        self.verify_check(pk.get_vk(), &params, &proof, &instances);
     }
 }
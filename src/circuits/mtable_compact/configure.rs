@@ -1,12 +1,9 @@
 use super::*;
 use crate::{
-    circuits::{config::IMTABLE_COLOMNS, CircuitConfigure, Lookup},
+    circuits::{config::IMTABLE_COLOMNS, image_table::ImageConfigureConfig, Lookup},
     constant_from, curr, fixed_curr, nextn,
 };
-use halo2_proofs::{
-    arithmetic::FieldExt,
-    plonk::{Advice, Column, ConstraintSystem},
-};
+use halo2_proofs::{arithmetic::FieldExt, plonk::ConstraintSystem};
 use specs::{
     encode::memory_table::encode_memory_table_entry,
     mtable::{AccessType, LocationType},
@@ -20,7 +17,7 @@ pub trait MemoryTableConstriants<F: FieldExt> {
         meta: &mut ConstraintSystem<F>,
         rtable: &RangeTableConfig<F>,
         imtable: &InitMemoryTableConfig<F>,
-        configure: &CircuitConfigure,
+        configure: &ImageConfigureConfig<F>,
     ) {
         self.configure_enable_as_bit(meta, rtable);
         self.configure_rest_mops_decrease(meta, rtable);
@@ -67,14 +64,17 @@ pub trait MemoryTableConstriants<F: FieldExt> {
         &self,
         meta: &mut ConstraintSystem<F>,
         rtable: &RangeTableConfig<F>,
-        configure: &CircuitConfigure,
+        configure: &ImageConfigureConfig<F>,
     );
 }
 
 impl<F: FieldExt> MemoryTableConstriants<F> for MemoryTableConfig<F> {
     fn configure_encode_range(&self, meta: &mut ConstraintSystem<F>, rtable: &RangeTableConfig<F>) {
         rtable.configure_in_common_range(meta, "mtable encode in common range", |meta| {
-            curr!(meta, self.aux) * self.is_enabled_line(meta)
+            (
+                curr!(meta, self.aux) * self.is_enabled_line(meta),
+                curr!(meta, self.aux_high) * self.is_enabled_line(meta),
+            )
         })
     }
 
@@ -118,14 +118,27 @@ impl<F: FieldExt> MemoryTableConstriants<F> for MemoryTableConfig<F> {
             .collect::<Vec<_>>()
         });
 
-        rtable.configure_in_common_range(meta, "mtable configure_index_sort", |meta| {
-            (curr!(meta, self.index.data) - nextn!(meta, self.index.data, -STEP_SIZE))
-                * curr!(meta, self.aux)
-                * self.is_enabled_following_block(meta)
+        /*
+         * The sorting diffs follow the indexes in the index column, they are
+         * range checked along with the indexes.
+         */
+        meta.create_gate("mtable configure_index_sort diff", |meta| {
+            (0..RotationOfIndexColumn::MAX as i32)
+                .map(|rot| {
+                    (self.index_diff(meta, rot)
+                        - (nextn!(meta, self.index.data, rot)
+                            - nextn!(meta, self.index.data, rot - STEP_SIZE))
+                            * nextn!(meta, self.aux, rot))
+                        * self.is_enabled_following_block(meta)
+                })
+                .collect::<Vec<_>>()
         });
 
         rtable.configure_in_common_range(meta, "mtable configure_index_sort", |meta| {
-            curr!(meta, self.index.data) * self.is_enabled_line(meta)
+            (
+                curr!(meta, self.index.data) * self.is_enabled_line(meta),
+                curr!(meta, self.index_high) * self.is_enabled_line(meta),
+            )
         });
     }
 
@@ -210,11 +223,15 @@ impl<F: FieldExt> MemoryTableConstriants<F> for MemoryTableConfig<F> {
             vec![
                 (self.ltype(meta) - constant_from!(LocationType::Stack))
                     * (self.ltype(meta) - constant_from!(LocationType::Heap))
-                    * (self.ltype(meta) - constant_from!(LocationType::Global)),
+                    * (self.ltype(meta) - constant_from!(LocationType::Global))
+                    * (self.ltype(meta) - constant_from!(LocationType::Data))
+                    * (self.ltype(meta) - constant_from!(LocationType::Table)),
                 (self.is_stack(meta)) * (self.ltype(meta) - constant_from!(LocationType::Stack)),
                 (constant_from!(1) - self.is_stack(meta))
                     * (self.ltype(meta) - constant_from!(LocationType::Heap))
-                    * (self.ltype(meta) - constant_from!(LocationType::Global)),
+                    * (self.ltype(meta) - constant_from!(LocationType::Global))
+                    * (self.ltype(meta) - constant_from!(LocationType::Data))
+                    * (self.ltype(meta) - constant_from!(LocationType::Table)),
                 (self.ltype(meta) - constant_from!(LocationType::Heap))
                     * (self.ltype(meta) - constant_from!(LocationType::Stack))
                     * self.same_offset(meta)
@@ -251,8 +268,7 @@ impl<F: FieldExt> MemoryTableConstriants<F> for MemoryTableConfig<F> {
 
         meta.create_gate("mtable stack first line must be write", |meta| {
             vec![
-                (self.ltype(meta) - constant_from!(LocationType::Heap))
-                    * (self.ltype(meta) - constant_from!(LocationType::Global))
+                self.is_stack(meta)
                     * (constant_from!(1) - self.same_offset(meta))
                     * (self.atype(meta) - constant_from!(AccessType::Write.into_index())),
             ]
@@ -292,7 +308,8 @@ impl<F: FieldExt> MemoryTableConstriants<F> for MemoryTableConfig<F> {
         meta.create_gate("mtable heap and stack are mutable", |meta| {
             vec![
                 (constant_from!(1) - self.is_mutable(meta))
-                    * (self.ltype(meta) - constant_from!(LocationType::Global)),
+                    * (self.ltype(meta) - constant_from!(LocationType::Global))
+                    * (self.ltype(meta) - constant_from!(LocationType::Data)),
             ]
             .into_iter()
             .map(|e| e * self.is_enabled_block(meta))
@@ -371,7 +388,7 @@ impl<F: FieldExt> MemoryTableConstriants<F> for MemoryTableConfig<F> {
         &self,
         meta: &mut ConstraintSystem<F>,
         _rtable: &RangeTableConfig<F>,
-        configure: &CircuitConfigure,
+        configure: &ImageConfigureConfig<F>,
     ) {
         meta.create_gate("mtable lazy init", |meta| {
             vec![
@@ -379,7 +396,7 @@ impl<F: FieldExt> MemoryTableConstriants<F> for MemoryTableConfig<F> {
                     * self.is_lazy_init(meta)
                     * (self.offset(meta)
                         - self.range_in_lazy_init_diff(meta)
-                        - constant_from!(configure.first_consecutive_zero_memory_offset))
+                        - configure.first_consecutive_zero_memory_offset.expr(meta))
                     * self.is_enabled_block(meta),
                 /*
                  * lazy init value must be 0
@@ -415,19 +432,18 @@ impl<F: FieldExt> Lookup<F> for MemoryTableConfig<F> {
 }
 
 impl<F: FieldExt> MemoryTableConfig<F> {
-    pub(super) fn new(
-        meta: &mut ConstraintSystem<F>,
-        cols: &mut impl Iterator<Item = Column<Advice>>,
-    ) -> Self {
+    pub(super) fn new(meta: &mut ConstraintSystem<F>) -> Self {
         let sel = meta.fixed_column();
         let following_block_sel = meta.fixed_column();
         let block_first_line_sel = meta.fixed_column();
-        let bit = cols.next().unwrap();
-        let index = RowDiffConfig::configure("mtable index", meta, cols, STEP_SIZE, |meta| {
+        let bit = meta.advice_column();
+        let index = RowDiffConfig::configure("mtable index", meta, STEP_SIZE, |meta| {
             fixed_curr!(meta, following_block_sel)
         });
-        let aux = cols.next().unwrap();
-        let bytes = cols.next().unwrap();
+        let aux = meta.advice_column();
+        let index_high = meta.advice_column();
+        let aux_high = meta.advice_column();
+        let bytes = meta.advice_column();
 
         MemoryTableConfig {
             sel,
@@ -436,6 +452,8 @@ impl<F: FieldExt> MemoryTableConfig<F> {
             bit,
             index,
             aux,
+            index_high,
+            aux_high,
             bytes,
         }
     }
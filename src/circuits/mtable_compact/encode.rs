@@ -1,7 +1,7 @@
 use num_bigint::BigUint;
 use specs::{
     encode::{memory_table::encode_memory_table_entry, FromBn},
-    mtable::{AccessType, LocationType},
+    mtable::{AccessType, LocationType, VarType},
 };
 
 pub(crate) struct MemoryTableLookupEncode;
@@ -124,4 +124,52 @@ impl MemoryTableLookupEncode {
             value,
         )
     }
+
+    pub(crate) fn encode_table_read<T: FromBn>(eid: T, emid: T, offset: T, value: T) -> T {
+        MemoryTableLookupEncode::encode_for_lookup(
+            eid,
+            emid,
+            offset,
+            T::from_bn(&BigUint::from(LocationType::Table as u64)),
+            T::from_bn(&BigUint::from(AccessType::Read.into_index())),
+            T::from_bn(&BigUint::from(VarType::I64 as u64)),
+            value,
+        )
+    }
+
+    pub(crate) fn encode_table_write<T: FromBn>(eid: T, emid: T, offset: T, value: T) -> T {
+        MemoryTableLookupEncode::encode_for_lookup(
+            eid,
+            emid,
+            offset,
+            T::from_bn(&BigUint::from(LocationType::Table as u64)),
+            T::from_bn(&BigUint::from(AccessType::Write.into_index())),
+            T::from_bn(&BigUint::from(VarType::I64 as u64)),
+            value,
+        )
+    }
+
+    pub(crate) fn encode_data_read<T: FromBn>(eid: T, emid: T, offset: T, value: T) -> T {
+        MemoryTableLookupEncode::encode_for_lookup(
+            eid,
+            emid,
+            offset,
+            T::from_bn(&BigUint::from(LocationType::Data as u64)),
+            T::from_bn(&BigUint::from(AccessType::Read.into_index())),
+            T::from_bn(&BigUint::from(VarType::I64 as u64)),
+            value,
+        )
+    }
+
+    pub(crate) fn encode_data_write<T: FromBn>(eid: T, emid: T, offset: T, value: T) -> T {
+        MemoryTableLookupEncode::encode_for_lookup(
+            eid,
+            emid,
+            offset,
+            T::from_bn(&BigUint::from(LocationType::Data as u64)),
+            T::from_bn(&BigUint::from(AccessType::Write.into_index())),
+            T::from_bn(&BigUint::from(VarType::I64 as u64)),
+            value,
+        )
+    }
 }
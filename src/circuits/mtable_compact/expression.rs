@@ -63,6 +63,14 @@ impl<F: FieldExt> MemoryTableConfig<F> {
         nextn!(meta, self.index.data, RotationOfIndexColumn::EMID as i32)
     }
 
+    pub(super) fn index_diff(&self, meta: &mut VirtualCells<F>, rot: i32) -> Expression<F> {
+        nextn!(
+            meta,
+            self.index.data,
+            RotationOfIndexColumn::MAX as i32 + rot
+        )
+    }
+
     pub(super) fn atype(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
         nextn!(meta, self.aux, RotationOfAuxColumn::Atype as i32)
     }
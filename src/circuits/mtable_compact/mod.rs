@@ -1,10 +1,9 @@
 use self::configure::MemoryTableConstriants;
 use super::{
-    config::max_mtable_rows,
+    image_table::ImageConfigureConfig,
     imtable::InitMemoryTableConfig,
     rtable::RangeTableConfig,
     utils::{row_diff::RowDiffConfig, Context},
-    CircuitConfigure,
 };
 use crate::circuits::{mtable_compact::configure::STEP_SIZE, IMTABLE_COLOMNS};
 use halo2_proofs::{
@@ -14,10 +13,6 @@ use halo2_proofs::{
 };
 use specs::mtable::{AccessType, InitType, LocationType, MTable, MemoryTableEntry, VarType};
 
-fn mtable_rows() -> usize {
-    max_mtable_rows() as usize / STEP_SIZE as usize * STEP_SIZE as usize
-}
-
 pub mod configure;
 pub(crate) mod encode;
 pub mod expression;
@@ -60,12 +55,18 @@ pub struct MemoryTableConfig<F: FieldExt> {
     // See enum RotationOfBitColumn
     pub(crate) bit: Column<Advice>,
 
-    // See enum RotationOfIndexColumn
+    // See enum RotationOfIndexColumn, the sorting diffs of the indexes
+    // follow them from RotationOfIndexColumn::MAX.
     pub(crate) index: RowDiffConfig<F>,
 
     // See enum RotationOfBitColumn
     pub(crate) aux: Column<Advice>,
 
+    // High u16 limbs of the values in common range,
+    // see RangeTableConfig::configure_in_common_range
+    pub(crate) index_high: Column<Advice>,
+    pub(crate) aux_high: Column<Advice>,
+
     // Rotation:
     // 0..8 bytes
     pub(crate) bytes: Column<Advice>,
@@ -74,12 +75,11 @@ pub struct MemoryTableConfig<F: FieldExt> {
 impl<F: FieldExt> MemoryTableConfig<F> {
     pub fn configure(
         meta: &mut ConstraintSystem<F>,
-        cols: &mut impl Iterator<Item = Column<Advice>>,
         rtable: &RangeTableConfig<F>,
         imtable: &InitMemoryTableConfig<F>,
-        configure: &CircuitConfigure,
+        configure: &ImageConfigureConfig<F>,
     ) -> Self {
-        let mtconfig = Self::new(meta, cols);
+        let mtconfig = Self::new(meta);
         meta.enable_equality(mtconfig.aux);
         mtconfig.configure(meta, rtable, imtable, configure);
         mtconfig
@@ -88,11 +88,15 @@ impl<F: FieldExt> MemoryTableConfig<F> {
 
 pub struct MemoryTableChip<F: FieldExt> {
     config: MemoryTableConfig<F>,
+    max_available_rows: usize,
 }
 
 impl<F: FieldExt> MemoryTableChip<F> {
-    pub fn new(config: MemoryTableConfig<F>) -> Self {
-        MemoryTableChip { config }
+    pub fn new(config: MemoryTableConfig<F>, max_available_rows: usize) -> Self {
+        MemoryTableChip {
+            config,
+            max_available_rows: max_available_rows / STEP_SIZE as usize * STEP_SIZE as usize,
+        }
     }
 
     pub fn assign(
@@ -102,9 +106,9 @@ impl<F: FieldExt> MemoryTableChip<F> {
         etable_rest_mops_cell: Option<Cell>,
         consecutive_zero_offset: u32,
     ) -> Result<(), Error> {
-        assert_eq!(mtable_rows() % (STEP_SIZE as usize), 0);
+        assert_eq!(self.max_available_rows % (STEP_SIZE as usize), 0);
 
-        for i in 0..mtable_rows() {
+        for i in 0..self.max_available_rows {
             ctx.region
                 .assign_fixed(|| "mtable sel", self.config.sel, i, || Ok(F::one()))?;
 
@@ -144,6 +148,7 @@ impl<F: FieldExt> MemoryTableChip<F> {
             .fold(0, |acc, e| acc + if e.atype.is_init() { 0 } else { 1 });
 
         let mut last_entry: Option<&MemoryTableEntry> = None;
+        let mut last_index_diffs = [0u64; 4];
         for (index, entry) in mtable.entries().iter().enumerate() {
             macro_rules! assign_advice {
                 ($key: expr, $offset: expr, $column: ident, $value: expr) => {
@@ -203,9 +208,7 @@ impl<F: FieldExt> MemoryTableChip<F> {
                     F::from(entry.is_mutable)
                 );
 
-                if (entry.ltype == LocationType::Heap || entry.ltype == LocationType::Global)
-                    && entry.atype.is_positive_init()
-                {
+                if entry.ltype != LocationType::Stack && entry.atype.is_positive_init() {
                     assign_advice!(
                         "vtype imtable selector",
                         RotationOfBitColumn::IMTableSelectorStart as i32
@@ -216,6 +219,34 @@ impl<F: FieldExt> MemoryTableChip<F> {
                 }
             }
 
+            let mut same_ltype = false;
+            let mut same_offset = false;
+            let mut same_eid = false;
+
+            if let Some(last_entry) = last_entry {
+                same_ltype = last_entry.ltype == entry.ltype;
+                same_offset = last_entry.offset == entry.offset && same_ltype;
+                same_eid = last_entry.eid == entry.eid && same_offset;
+            }
+
+            let index_diffs = last_entry.map_or([0; 4], |last_entry| {
+                [
+                    (entry.ltype as u64, last_entry.ltype as u64, true),
+                    (entry.offset as u64, last_entry.offset as u64, same_ltype),
+                    (entry.eid as u64, last_entry.eid as u64, same_offset),
+                    (entry.emid as u64, last_entry.emid as u64, same_eid),
+                ]
+                .map(
+                    |(value, last_value, is_sorted)| {
+                        if is_sorted {
+                            value - last_value
+                        } else {
+                            0
+                        }
+                    },
+                )
+            });
+
             // index column
             {
                 assign_row_diff!(RotationOfIndexColumn::LTYPE, ltype);
@@ -223,28 +254,22 @@ impl<F: FieldExt> MemoryTableChip<F> {
                 assign_row_diff!(RotationOfIndexColumn::EID, eid);
                 assign_row_diff!(RotationOfIndexColumn::EMID, emid);
 
-                for i in (RotationOfIndexColumn::MAX as i32)..STEP_SIZE {
+                for (rot, (diff, last_diff)) in
+                    index_diffs.into_iter().zip(last_index_diffs).enumerate()
+                {
                     self.config.index.assign(
                         ctx,
-                        Some(index * STEP_SIZE as usize + i as usize),
-                        F::zero(),
-                        F::zero(),
+                        Some(
+                            index * STEP_SIZE as usize + RotationOfIndexColumn::MAX as usize + rot,
+                        ),
+                        F::from(diff),
+                        F::from(diff) - F::from(last_diff),
                     )?;
                 }
             }
 
             // aux column
             {
-                let mut same_ltype = false;
-                let mut same_offset = false;
-                let mut same_eid = false;
-
-                if let Some(last_entry) = last_entry {
-                    same_ltype = last_entry.ltype == entry.ltype;
-                    same_offset = last_entry.offset == entry.offset && same_ltype;
-                    same_eid = last_entry.eid == entry.eid && same_offset;
-                }
-
                 assign_advice!(
                     "constant 1",
                     RotationOfAuxColumn::ConstantOne,
@@ -281,6 +306,12 @@ impl<F: FieldExt> MemoryTableChip<F> {
                     aux,
                     F::from(mops)
                 );
+                assign_advice!(
+                    "rest mops high",
+                    RotationOfAuxColumn::RestMops,
+                    aux_high,
+                    F::from(mops >> 16)
+                );
 
                 if let AccessType::Init(InitType::Lazy) = entry.atype {
                     assert!(entry.offset >= consecutive_zero_offset);
@@ -291,6 +322,12 @@ impl<F: FieldExt> MemoryTableChip<F> {
                         aux,
                         F::from((entry.offset - consecutive_zero_offset) as u64)
                     );
+                    assign_advice!(
+                        "lazy init helper high",
+                        RotationOfAuxColumn::RangeInLazyInitDiff,
+                        aux_high,
+                        F::from(((entry.offset - consecutive_zero_offset) >> 16) as u64)
+                    );
 
                     assign_advice!(
                         "lazy init helper",
@@ -301,6 +338,26 @@ impl<F: FieldExt> MemoryTableChip<F> {
                 }
             }
 
+            // high limbs of the index column and of the sorting diffs
+            {
+                let index = [
+                    entry.ltype as u64,
+                    entry.offset as u64,
+                    entry.eid as u64,
+                    entry.emid as u64,
+                ];
+
+                for (rot, (value, diff)) in index.into_iter().zip(index_diffs).enumerate() {
+                    assign_advice!("index high", rot, index_high, F::from(value >> 16));
+                    assign_advice!(
+                        "index diff high",
+                        RotationOfIndexColumn::MAX as usize + rot,
+                        index_high,
+                        F::from(diff >> 16)
+                    );
+                }
+            }
+
             // bytes column
             {
                 let mut bytes = Vec::from(entry.value.to_le_bytes());
@@ -315,29 +372,28 @@ impl<F: FieldExt> MemoryTableChip<F> {
             }
 
             last_entry = Some(entry);
+            last_index_diffs = index_diffs;
             ctx.offset += STEP_SIZE as usize;
         }
 
         if let Some(last_entry) = last_entry {
-            self.config
-                .index
-                .assign(ctx, None, F::zero(), -F::from(last_entry.ltype as u64))?;
-            ctx.offset += 1;
-            self.config
-                .index
-                .assign(ctx, None, F::zero(), -F::from(last_entry.offset as u64))?;
-            ctx.offset += 1;
-            self.config
-                .index
-                .assign(ctx, None, F::zero(), -F::from(last_entry.eid as u64))?;
-            ctx.offset += 1;
-            self.config
-                .index
-                .assign(ctx, None, F::zero(), -F::from(last_entry.emid as u64))?;
-            ctx.offset += 1;
+            for last_value in [
+                last_entry.ltype as u64,
+                last_entry.offset as u64,
+                last_entry.eid as u64,
+                last_entry.emid as u64,
+            ]
+            .into_iter()
+            .chain(last_index_diffs)
+            {
+                self.config
+                    .index
+                    .assign(ctx, None, F::zero(), -F::from(last_value))?;
+                ctx.offset += 1;
+            }
         }
 
-        for i in ctx.offset..max_mtable_rows() as usize {
+        for i in ctx.offset..self.max_available_rows {
             self.config
                 .index
                 .assign(ctx, Some(i), F::zero(), F::zero())?;
@@ -1,6 +1,6 @@
-use super::config::zkwasm_k;
 use super::config::POW_TABLE_LIMIT;
 use super::utils::bn_to_field;
+use super::utils::field_to_bn;
 use crate::constant_from;
 use crate::traits::circuits::bit_range_table::BitRangeTable;
 use halo2_proofs::arithmetic::FieldExt;
@@ -49,13 +49,25 @@ impl<F: FieldExt> RangeTableConfig<F> {
         }
     }
 
+    /*
+     * The common range is [0, 2^32), so that addresses and step indexes don't depend on K.
+     * expr returns the value and its high u16 limb, the high limb is witnessed by the
+     * caller, see common_range_high_limb.
+     */
     pub fn configure_in_common_range(
         &self,
         meta: &mut ConstraintSystem<F>,
         key: &'static str,
-        expr: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        expr: impl Fn(&mut VirtualCells<'_, F>) -> (Expression<F>, Expression<F>),
     ) {
-        meta.lookup(key, |meta| vec![(expr(meta), self.u16_col)]);
+        meta.lookup(key, |meta| {
+            let (value, high) = expr(meta);
+            vec![(value - high * constant_from!(1u64 << 16), self.u16_col)]
+        });
+        meta.lookup(key, |meta| {
+            let (_, high) = expr(meta);
+            vec![(high, self.u16_col)]
+        });
     }
 
     pub fn configure_in_u16_range(
@@ -141,6 +153,13 @@ pub fn pow_table_encode<F: FieldExt>(
     modulus * constant_from!(1u64 << 16) + power
 }
 
+pub fn common_range_high_limb<F: FieldExt>(value: &F) -> F {
+    let value = field_to_bn(value);
+    assert!(value < BigUint::from(1u64) << 32);
+
+    bn_to_field(&(value >> 16))
+}
+
 pub fn bits_of_offset_len(offset: u64, len: u64) -> u64 {
     let bits = (1 << len) - 1;
     bits << offset
@@ -167,9 +186,9 @@ impl<F: FieldExt> RangeTableChip<F> {
 
     pub fn init(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
         layouter.assign_table(
-            || "common range table",
+            || "u16 range table",
             |mut table| {
-                for i in 0..(1 << (zkwasm_k() - 1)) {
+                for i in 0..(1 << 16) {
                     table.assign_cell(
                         || "range table",
                         self.config.u16_col,
@@ -17,12 +17,11 @@ pub struct BitValueConfig<F: FieldExt> {
 impl<F: FieldExt> BitValueConfig<F> {
     pub fn configure(
         meta: &mut ConstraintSystem<F>,
-        cols: &mut impl Iterator<Item = Column<Advice>>,
         rtable: &RangeTableConfig<F>,
         enable: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
     ) -> Self {
-        let bits_le = [(); 16].map(|_| cols.next().unwrap());
-        let value = cols.next().unwrap();
+        let bits_le = [(); 16].map(|_| meta.advice_column());
+        let value = meta.advice_column();
 
         for i in 0..16 {
             rtable.configure_in_u4_range(meta, "bits repr", |meta| {
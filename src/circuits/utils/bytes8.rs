@@ -16,12 +16,11 @@ pub struct Bytes8Config<F: FieldExt> {
 impl<F: FieldExt> Bytes8Config<F> {
     pub fn configure(
         meta: &mut ConstraintSystem<F>,
-        cols: &mut impl Iterator<Item = Column<Advice>>,
         rtable: &RangeTableConfig<F>,
         enable: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
     ) -> Self {
-        let bytes_le = [0; 8].map(|_| cols.next().unwrap());
-        let value = cols.next().unwrap();
+        let bytes_le = [0; 8].map(|_| meta.advice_column());
+        let value = meta.advice_column();
 
         for byte in bytes_le.iter() {
             rtable.configure_in_u8_range(meta, "byte", |meta| {
@@ -6,6 +6,7 @@ use num_bigint::BigUint;
 
 pub mod bitvalue;
 pub mod bytes8;
+pub mod poseidon;
 pub mod row_diff;
 pub mod u16;
 pub mod u32;
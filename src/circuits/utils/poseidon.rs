@@ -0,0 +1,444 @@
+use super::Context;
+use crate::{constant, constant_from, curr, fixed_curr, next, nextn, prev};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::Cell,
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, VirtualCells},
+};
+use specs::poseidon::{PoseidonRound, PoseidonSpec, POSEIDON_RATE, POSEIDON_ROUNDS, POSEIDON_T};
+
+/*
+ * A block absorbs a chunk of POSEIDON_RATE inputs, it starts with the absorb row
+ * holding the chunk, followed by a row per round and the output row.
+ */
+pub const POSEIDON_BLOCK_ROWS: usize = POSEIDON_ROUNDS + 2;
+
+/*
+ * PoseidonConfig constrains the sponge of PoseidonSpec, see PoseidonSpec::hash.
+ *
+ * The variable length config also lets a block skip its chunk: a block not
+ * absorbing has a zero chunk and outputs the state of the previous block, so
+ * a sequence of blocks hashes the chunks of the absorbing blocks only.
+ */
+#[derive(Clone)]
+pub struct PoseidonConfig<F: FieldExt> {
+    pub(crate) state: [Column<Advice>; POSEIDON_T],
+    // Whether the block absorbs its chunk, on the absorb row.
+    pub(crate) absorbed: Option<Column<Advice>>,
+    round_constants: [Column<Fixed>; POSEIDON_T],
+    // The sparse matrix of the partial rounds.
+    sparse_row: [Column<Fixed>; POSEIDON_T],
+    sparse_col_hat: [Column<Fixed>; POSEIDON_RATE],
+    absorb: Column<Fixed>,
+    full_round: Column<Fixed>,
+    pre_sparse_round: Column<Fixed>,
+    partial_round: Column<Fixed>,
+    last_round: Column<Fixed>,
+    spec: PoseidonSpec<F>,
+}
+
+fn sbox<F: FieldExt>(x: Expression<F>) -> Expression<F> {
+    x.clone() * x.clone() * x.clone() * x.clone() * x
+}
+
+impl<F: FieldExt> PoseidonConfig<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        Self::_configure(meta, false)
+    }
+
+    pub fn configure_variable(meta: &mut ConstraintSystem<F>) -> Self {
+        Self::_configure(meta, true)
+    }
+
+    fn _configure(meta: &mut ConstraintSystem<F>, variable: bool) -> Self {
+        let state = [0; POSEIDON_T].map(|_| meta.advice_column());
+        let absorbed = if variable {
+            Some(meta.advice_column())
+        } else {
+            None
+        };
+        let round_constants = [0; POSEIDON_T].map(|_| meta.fixed_column());
+        let sparse_row = [0; POSEIDON_T].map(|_| meta.fixed_column());
+        let sparse_col_hat = [0; POSEIDON_RATE].map(|_| meta.fixed_column());
+        let absorb = meta.fixed_column();
+        let full_round = meta.fixed_column();
+        let pre_sparse_round = meta.fixed_column();
+        let partial_round = meta.fixed_column();
+        let last_round = meta.fixed_column();
+
+        for col in state.iter() {
+            meta.enable_equality(*col);
+        }
+
+        let spec = PoseidonSpec::<F>::new();
+        let absorb_constants = spec.absorb_constants();
+        let mds = spec.mds();
+        let pre_sparse_mds = spec.pre_sparse_mds();
+
+        meta.create_gate("poseidon absorb", |meta| {
+            let mut constraints = vec![curr!(meta, state[0])];
+
+            for i in 0..POSEIDON_T {
+                constraints.push(
+                    next!(meta, state[i])
+                        - prev!(meta, state[i])
+                        - curr!(meta, state[i])
+                        - constant!(absorb_constants[i]),
+                );
+            }
+
+            if let Some(absorbed) = absorbed {
+                let absorbed = curr!(meta, absorbed);
+
+                constraints.push(absorbed.clone() * (constant_from!(1) - absorbed.clone()));
+                for i in 1..POSEIDON_T {
+                    constraints
+                        .push((constant_from!(1) - absorbed.clone()) * curr!(meta, state[i]));
+                }
+            }
+
+            constraints
+                .into_iter()
+                .map(|x| x * fixed_curr!(meta, absorb))
+                .collect::<Vec<_>>()
+        });
+
+        /*
+         * The next state of a full round, M * (sbox(state) + C).
+         */
+        let full_round_state =
+            |meta: &mut VirtualCells<'_, F>, matrix: &[[F; POSEIDON_T]; POSEIDON_T]| {
+                let sboxed = (0..POSEIDON_T)
+                    .map(|j| sbox(curr!(meta, state[j])) + fixed_curr!(meta, round_constants[j]))
+                    .collect::<Vec<_>>();
+
+                matrix
+                    .iter()
+                    .map(|row| {
+                        sboxed
+                            .iter()
+                            .zip(row.iter())
+                            .fold(constant_from!(0), |acc, (x, m)| {
+                                acc + constant!(*m) * x.clone()
+                            })
+                    })
+                    .collect::<Vec<_>>()
+            };
+
+        meta.create_gate("poseidon full round", |meta| {
+            full_round_state(meta, &mds)
+                .into_iter()
+                .enumerate()
+                .map(|(i, x)| fixed_curr!(meta, full_round) * (next!(meta, state[i]) - x))
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("poseidon pre-sparse round", |meta| {
+            full_round_state(meta, &pre_sparse_mds)
+                .into_iter()
+                .enumerate()
+                .map(|(i, x)| fixed_curr!(meta, pre_sparse_round) * (next!(meta, state[i]) - x))
+                .collect::<Vec<_>>()
+        });
+
+        /*
+         * A block not absorbing outputs the state before its absorb row, the
+         * last round is POSEIDON_ROUNDS rows after the absorb row.
+         */
+        meta.create_gate("poseidon last round", |meta| {
+            let computed = full_round_state(meta, &mds);
+
+            computed
+                .into_iter()
+                .enumerate()
+                .map(|(i, x)| {
+                    let expected = match absorbed {
+                        Some(absorbed) => {
+                            let absorbed = nextn!(meta, absorbed, -(POSEIDON_ROUNDS as i32));
+
+                            absorbed.clone() * x
+                                + (constant_from!(1) - absorbed)
+                                    * nextn!(meta, state[i], -(POSEIDON_ROUNDS as i32) - 1)
+                        }
+                        None => x,
+                    };
+
+                    fixed_curr!(meta, last_round) * (next!(meta, state[i]) - expected)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("poseidon partial round", |meta| {
+            let mut sboxed = (0..POSEIDON_T)
+                .map(|j| curr!(meta, state[j]))
+                .collect::<Vec<_>>();
+            sboxed[0] = sbox(sboxed[0].clone()) + fixed_curr!(meta, round_constants[0]);
+
+            let mut constraints = vec![
+                next!(meta, state[0])
+                    - sboxed
+                        .iter()
+                        .enumerate()
+                        .fold(constant_from!(0), |acc, (j, x)| {
+                            acc + fixed_curr!(meta, sparse_row[j]) * x.clone()
+                        }),
+            ];
+
+            for i in 1..POSEIDON_T {
+                constraints.push(
+                    next!(meta, state[i])
+                        - fixed_curr!(meta, sparse_col_hat[i - 1]) * sboxed[0].clone()
+                        - sboxed[i].clone(),
+                );
+            }
+
+            constraints
+                .into_iter()
+                .map(|x| x * fixed_curr!(meta, partial_round))
+                .collect::<Vec<_>>()
+        });
+
+        PoseidonConfig {
+            state,
+            absorbed,
+            round_constants,
+            sparse_row,
+            sparse_col_hat,
+            absorb,
+            full_round,
+            pre_sparse_round,
+            partial_round,
+            last_round,
+            spec,
+        }
+    }
+
+    pub fn rows(inputs: usize) -> usize {
+        1 + (inputs / POSEIDON_RATE + 1) * POSEIDON_BLOCK_ROWS
+    }
+
+    /*
+     * Assigns the initial state of the sponge, the row before the first block.
+     */
+    pub fn assign_initial_state(&self, ctx: &mut Context<'_, F>) -> Result<[F; POSEIDON_T], Error> {
+        let state = PoseidonSpec::<F>::initial_state();
+
+        for i in 0..POSEIDON_T {
+            ctx.region.assign_advice_from_constant(
+                || "poseidon init",
+                self.state[i],
+                ctx.offset,
+                state[i],
+            )?;
+        }
+        ctx.next();
+
+        Ok(state)
+    }
+
+    /*
+     * Returns the cells of the inputs and the cell of the digest, the caller
+     * binds the input cells to their sources.
+     */
+    pub fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        inputs: &[F],
+    ) -> Result<(Vec<Cell>, Cell), Error> {
+        let mut state = self.assign_initial_state(ctx)?;
+        let full_chunks = inputs.len() / POSEIDON_RATE * POSEIDON_RATE;
+
+        let mut input_cells = vec![];
+
+        for chunk in inputs[..full_chunks].chunks(POSEIDON_RATE) {
+            let (mut cells, _) = self.assign_block(ctx, &mut state, chunk, false)?;
+
+            input_cells.append(&mut cells);
+        }
+
+        let (mut cells, output) =
+            self.assign_block(ctx, &mut state, &inputs[full_chunks..], true)?;
+        input_cells.append(&mut cells);
+
+        Ok((input_cells, output[1]))
+    }
+
+    /*
+     * Assigns a block absorbing the chunk from the state of the previous row,
+     * the chunk is padded with zeros, after a one if padded is set. Returns the
+     * cells of the chunk and the cells of the output state.
+     */
+    pub fn assign_block(
+        &self,
+        ctx: &mut Context<'_, F>,
+        state: &mut [F; POSEIDON_T],
+        chunk: &[F],
+        padded: bool,
+    ) -> Result<(Vec<Cell>, Vec<Cell>), Error> {
+        self.assign_block_with_absorbed(ctx, state, chunk, padded, true)
+    }
+
+    /*
+     * Assigns a block of the variable length config, a block not absorbing
+     * must have a zero chunk and keeps the state.
+     */
+    pub fn assign_variable_block(
+        &self,
+        ctx: &mut Context<'_, F>,
+        state: &mut [F; POSEIDON_T],
+        chunk: &[F],
+        absorbed: bool,
+    ) -> Result<(Vec<Cell>, Vec<Cell>), Error> {
+        assert!(self.absorbed.is_some());
+
+        self.assign_block_with_absorbed(ctx, state, chunk, false, absorbed)
+    }
+
+    fn assign_block_with_absorbed(
+        &self,
+        ctx: &mut Context<'_, F>,
+        state: &mut [F; POSEIDON_T],
+        chunk: &[F],
+        padded: bool,
+        absorbed: bool,
+    ) -> Result<(Vec<Cell>, Vec<Cell>), Error> {
+        assert!(chunk.len() + padded as usize <= POSEIDON_RATE);
+
+        let mut input_cells = vec![];
+        let mut round_state = *state;
+
+        ctx.region.assign_fixed(
+            || "poseidon absorb",
+            self.absorb,
+            ctx.offset,
+            || Ok(F::one()),
+        )?;
+        ctx.region.assign_advice(
+            || "poseidon absorb",
+            self.state[0],
+            ctx.offset,
+            || Ok(F::zero()),
+        )?;
+
+        if let Some(col) = self.absorbed {
+            ctx.region.assign_advice(
+                || "poseidon absorbed",
+                col,
+                ctx.offset,
+                || Ok(F::from(absorbed as u64)),
+            )?;
+        }
+
+        for i in 0..POSEIDON_RATE {
+            match chunk.get(i) {
+                Some(input) => {
+                    let cell = ctx.region.assign_advice(
+                        || "poseidon absorb",
+                        self.state[i + 1],
+                        ctx.offset,
+                        || Ok(*input),
+                    )?;
+
+                    input_cells.push(cell.cell());
+                    round_state[i + 1] = round_state[i + 1] + *input;
+                }
+                None => {
+                    let padding = if padded && i == chunk.len() {
+                        F::one()
+                    } else {
+                        F::zero()
+                    };
+
+                    ctx.region.assign_advice_from_constant(
+                        || "poseidon padding",
+                        self.state[i + 1],
+                        ctx.offset,
+                        padding,
+                    )?;
+                    round_state[i + 1] = round_state[i + 1] + padding;
+                }
+            }
+        }
+        ctx.next();
+
+        for (word, constant) in round_state
+            .iter_mut()
+            .zip(self.spec.absorb_constants().iter())
+        {
+            *word = *word + *constant;
+        }
+
+        for round in 0..POSEIDON_ROUNDS {
+            let selector = match PoseidonSpec::<F>::round_kind(round) {
+                _ if round + 1 == POSEIDON_ROUNDS => self.last_round,
+                PoseidonRound::Full => self.full_round,
+                PoseidonRound::PreSparse => self.pre_sparse_round,
+                PoseidonRound::Partial => self.partial_round,
+            };
+
+            ctx.region
+                .assign_fixed(|| "poseidon round", selector, ctx.offset, || Ok(F::one()))?;
+
+            let round_constants = self.spec.round_constants(round);
+            for i in 0..POSEIDON_T {
+                ctx.region.assign_fixed(
+                    || "poseidon round constant",
+                    self.round_constants[i],
+                    ctx.offset,
+                    || Ok(round_constants[i]),
+                )?;
+
+                ctx.region.assign_advice(
+                    || "poseidon state",
+                    self.state[i],
+                    ctx.offset,
+                    || Ok(round_state[i]),
+                )?;
+            }
+
+            if PoseidonSpec::<F>::round_kind(round) == PoseidonRound::Partial {
+                let (row, col_hat) = self.spec.sparse_mds(round);
+
+                for i in 0..POSEIDON_T {
+                    ctx.region.assign_fixed(
+                        || "poseidon sparse row",
+                        self.sparse_row[i],
+                        ctx.offset,
+                        || Ok(row[i]),
+                    )?;
+                }
+
+                for i in 0..POSEIDON_RATE {
+                    ctx.region.assign_fixed(
+                        || "poseidon sparse col",
+                        self.sparse_col_hat[i],
+                        ctx.offset,
+                        || Ok(col_hat[i]),
+                    )?;
+                }
+            }
+            ctx.next();
+
+            round_state = self.spec.round(round, &round_state);
+        }
+
+        if absorbed {
+            *state = round_state;
+        }
+
+        let mut output_cells = vec![];
+        for i in 0..POSEIDON_T {
+            let cell = ctx.region.assign_advice(
+                || "poseidon output",
+                self.state[i],
+                ctx.offset,
+                || Ok(state[i]),
+            )?;
+
+            output_cells.push(cell.cell());
+        }
+        ctx.next();
+
+        Ok((input_cells, output_cells))
+    }
+}
@@ -19,13 +19,12 @@ impl<F: FieldExt> RowDiffConfig<F> {
     pub fn configure(
         key: &'static str,
         meta: &mut ConstraintSystem<F>,
-        cols: &mut impl Iterator<Item = Column<Advice>>,
         distance: i32,
         enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
     ) -> Self {
-        let data = cols.next().unwrap();
-        let same = cols.next().unwrap();
-        let inv = cols.next().unwrap();
+        let data = meta.advice_column();
+        let same = meta.advice_column();
+        let inv = meta.advice_column();
 
         meta.enable_equality(same);
 
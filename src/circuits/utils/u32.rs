@@ -16,12 +16,11 @@ pub struct U32Config<F: FieldExt> {
 impl<F: FieldExt> U32Config<F> {
     pub fn configure(
         meta: &mut ConstraintSystem<F>,
-        cols: &mut impl Iterator<Item = Column<Advice>>,
         rtable: &RangeTableConfig<F>,
         enable: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
     ) -> Self {
-        let u16_le = [0; 2].map(|_| cols.next().unwrap());
-        let value = cols.next().unwrap();
+        let u16_le = [0; 2].map(|_| meta.advice_column());
+        let value = meta.advice_column();
 
         for u16_i in u16_le.iter() {
             rtable.configure_in_u16_range(meta, "u16", |meta| {
@@ -15,11 +15,10 @@ pub struct U8Config<F: FieldExt> {
 impl<F: FieldExt> U8Config<F> {
     pub fn configure(
         meta: &mut ConstraintSystem<F>,
-        cols: &mut impl Iterator<Item = Column<Advice>>,
         rtable: &RangeTableConfig<F>,
         enable: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
     ) -> Self {
-        let value = cols.next().unwrap();
+        let value = meta.advice_column();
 
         rtable.configure_in_u8_range(meta, "u8", |meta| curr!(meta, value.clone()) * enable(meta));
         Self {
@@ -2,13 +2,13 @@ use clap::{App, AppSettings};
 use log::info;
 use std::{fs, path::PathBuf};
 
-use crate::circuits::config::{set_zkwasm_k, MIN_K};
+use crate::circuits::config::{CircuitLayout, MIN_K};
 
 use super::{
     command::CommandBuilder,
     exec::{
-        build_circuit_without_witness, exec_aggregate_create_proof, exec_create_proof, exec_setup,
-        exec_solidity_aggregate_proof, exec_verify_aggregate_proof, exec_verify_proof,
+        exec_aggregate_create_proof, exec_create_proof, exec_fit_layout, exec_setup,
+        exec_solidity_aggregate_proof, exec_verify_aggregate_proof, exec_verify_proof, load_layout,
     },
 };
 
@@ -54,18 +54,12 @@ pub trait AppBuilder: CommandBuilder {
         let top_matches = command.get_matches();
 
         let zkwasm_k = Self::parse_zkwasm_k_arg(&top_matches).unwrap_or(MIN_K);
-        set_zkwasm_k(zkwasm_k);
 
         let wasm_file_path = Self::parse_zkwasm_file_arg(&top_matches);
         let wasm_binary = fs::read(&wasm_file_path).unwrap();
 
         let function_name = Self::parse_function_name(&top_matches);
 
-        /*
-         * FIXME: trigger CIRCUIT_CONFIGURE initialization.
-         */
-        build_circuit_without_witness(&wasm_binary, &function_name);
-
         let md5 = format!("{:X}", md5::compute(&wasm_binary));
 
         let output_dir =
@@ -73,7 +67,26 @@ pub trait AppBuilder: CommandBuilder {
         fs::create_dir_all(&output_dir).unwrap();
 
         match top_matches.subcommand() {
-            Some(("setup", _)) => {
+            Some(("setup", sub_matches)) => {
+                let layout = match Self::parse_layout_arg(&sub_matches) {
+                    None => CircuitLayout::new(zkwasm_k),
+                    Some(layout) if layout == "auto" => {
+                        let public_inputs: Vec<u64> = Self::parse_single_public_arg(&sub_matches);
+                        let private_inputs: Vec<u64> = Self::parse_single_private_arg(&sub_matches);
+
+                        exec_fit_layout(
+                            zkwasm_k,
+                            &wasm_binary,
+                            &function_name,
+                            &public_inputs,
+                            &private_inputs,
+                        )
+                        .unwrap()
+                    }
+                    Some(layout) if layout == "universal" => CircuitLayout::new_universal(zkwasm_k),
+                    Some(path) => load_layout(zkwasm_k, &PathBuf::from(path)).unwrap(),
+                };
+
                 exec_setup(
                     zkwasm_k,
                     Self::AGGREGATE_K,
@@ -81,6 +94,7 @@ pub trait AppBuilder: CommandBuilder {
                     &wasm_binary,
                     &function_name,
                     &output_dir,
+                    layout,
                 );
             }
             Some(("single-prove", sub_matches)) => {
@@ -103,16 +117,21 @@ pub trait AppBuilder: CommandBuilder {
             Some(("single-verify", sub_matches)) => {
                 let proof_path: PathBuf = Self::parse_proof_path_arg(&sub_matches);
                 let public_inputs: Vec<u64> = Self::parse_single_public_arg(&sub_matches);
+                let outputs: Vec<u64> = Self::parse_single_output_arg(&sub_matches);
 
                 assert!(public_inputs.len() <= Self::MAX_PUBLIC_INPUT_SIZE);
 
                 exec_verify_proof(
                     Self::NAME,
                     zkwasm_k,
+                    &wasm_binary,
+                    &function_name,
                     &output_dir,
                     &proof_path,
                     &public_inputs,
-                );
+                    &outputs,
+                )
+                .unwrap();
             }
             Some(("aggregate-prove", sub_matches)) => {
                 let public_inputs: Vec<Vec<u64>> = Self::parse_aggregate_public_args(&sub_matches);
@@ -143,12 +162,17 @@ pub trait AppBuilder: CommandBuilder {
                 let instances_path: PathBuf = Self::parse_aggregate_instance(&sub_matches);
 
                 exec_verify_aggregate_proof(
+                    Self::NAME,
+                    zkwasm_k,
                     Self::AGGREGATE_K as u32,
+                    &wasm_binary,
+                    &function_name,
                     &output_dir,
                     &proof_path,
                     &instances_path,
                     Self::N_PROOFS,
-                );
+                )
+                .unwrap();
             }
 
             Some(("solidity-aggregate-verifier", sub_matches)) => {
@@ -78,12 +78,24 @@ pub trait ArgBuilder {
         .takes_value(false)
     }
 
+    fn layout_arg<'a>() -> Arg<'a> {
+        arg!(
+            --layout [LAYOUT] "Path of the circuit layout file, 'auto' to fit the layout to the trace of the given inputs, or 'universal' to setup a vkey shared by all images fitting in the universal circuit.\nThe default layout is used if not supplied."
+        )
+    }
+    fn parse_layout_arg(matches: &ArgMatches) -> Option<String> {
+        matches.get_one::<String>("layout").cloned()
+    }
+
     fn single_public_arg<'a>() -> Arg<'a>;
     fn parse_single_public_arg(matches: &ArgMatches) -> Vec<u64>;
 
     fn aggregate_public_args<'a>() -> Arg<'a>;
     fn parse_aggregate_public_args(matches: &ArgMatches) -> Vec<Vec<u64>>;
 
+    fn single_output_arg<'a>() -> Arg<'a>;
+    fn parse_single_output_arg(matches: &ArgMatches) -> Vec<u64>;
+
     fn single_private_arg<'a>() -> Arg<'a>;
     fn parse_single_private_arg(matches: &ArgMatches) -> Vec<u64>;
 
@@ -4,7 +4,11 @@ use super::args::ArgBuilder;
 
 pub trait CommandBuilder: ArgBuilder {
     fn append_setup_subcommand(app: App) -> App {
-        let command = Command::new("setup").arg(Self::output_path_arg());
+        let command = Command::new("setup")
+            .arg(Self::output_path_arg())
+            .arg(Self::layout_arg())
+            .arg(Self::single_public_arg())
+            .arg(Self::single_private_arg());
 
         app.subcommand(command)
     }
@@ -21,6 +25,7 @@ pub trait CommandBuilder: ArgBuilder {
     fn append_verify_single_proof_subcommand(app: App) -> App {
         let command = Command::new("single-verify")
             .arg(Self::single_public_arg())
+            .arg(Self::single_output_arg())
             .arg(Self::proof_path_arg());
 
         app.subcommand(command)
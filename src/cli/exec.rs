@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{anyhow, ensure, Result};
 use halo2_proofs::{
     dev::MockProver,
     pairing::bn256::{Bn256, Fr, G1Affine},
@@ -15,29 +15,201 @@ use halo2aggregator_s::{
     transcript::{poseidon::PoseidonRead, sha256::ShaRead},
 };
 use log::info;
-use specs::{ExecutionTable, Tables};
-use std::path::PathBuf;
+use num_bigint::BigUint;
+use sha2::Digest;
+use specs::{
+    host_function::HostPlugin, kvtable::KvTable, CompilationTable, ExecutionTable, Tables,
+};
+use std::{fs, path::PathBuf};
 use wasmi::ImportsBuilder;
 
 use crate::{
-    circuits::{TestCircuit, ZkWasmCircuitBuilder},
+    circuits::{
+        config::CircuitLayout,
+        image_table::{image_commitment, image_hash},
+        utils::{bn_to_field, field_to_bn},
+        with_circuit_params, TestCircuit,
+    },
     foreign::{
-        require_helper::register_require_foreign, sha256_helper::runtime::register_sha256_foreign,
-        wasm_input_helper::runtime::register_wasm_input_foreign,
+        keccak_helper::runtime::register_keccak_foreign,
+        kv_helper::{
+            kv_roots,
+            runtime::{kv_table, register_kv_foreign},
+            KvTree,
+        },
+        poseidon_helper::runtime::register_poseidon_foreign,
+        require_helper::register_require_foreign,
+        secp256k1_helper::runtime::register_secp256k1_foreign,
+        sha256_compress_helper::runtime::register_sha256_compress_foreign,
+        sha256_helper::runtime::register_sha256_foreign,
+        u256_helper::runtime::register_u256_foreign,
+        wasm_input_helper::{runtime::register_wasm_input_foreign, wasm_outputs},
     },
     runtime::{host::host_env::HostEnv, wasmi_interpreter::Execution, WasmInterpreter},
 };
 
 const AGGREGATE_PREFIX: &'static str = "aggregate-circuit";
 
-pub fn build_circuit_without_witness(
-    wasm_binary: &Vec<u8>,
-    function_name: &str,
-) -> TestCircuit<Fr> {
+fn layout_path(prefix: &'static str, output_dir: &PathBuf) -> PathBuf {
+    output_dir.join(format!("{}.{}.layout.json", prefix, 0))
+}
+
+/*
+ * Reads the layout at `path`, which must be the one of a circuit of size
+ * `zkwasm_k`.
+ */
+pub fn load_layout(zkwasm_k: u32, path: &PathBuf) -> Result<CircuitLayout> {
+    let layout = CircuitLayout::read(path)?;
+
+    ensure!(
+        layout.k == zkwasm_k,
+        "the layout {:?} is for K = {}, not {}",
+        path,
+        layout.k,
+        zkwasm_k
+    );
+
+    Ok(layout)
+}
+
+fn read_layout(zkwasm_k: u32, prefix: &'static str, output_dir: &PathBuf) -> Result<CircuitLayout> {
+    load_layout(zkwasm_k, &layout_path(prefix, output_dir))
+}
+
+/*
+ * The instances of a proof: the public inputs and the outputs, followed by the
+ * roots of the kv store if the circuit serves kv calls and the image
+ * commitment in the universal circuit.
+ */
+fn circuit_instances(
+    circuit: &TestCircuit<Fr>,
+    public_inputs: &Vec<u64>,
+    outputs: &Vec<u64>,
+    kv_roots: Vec<Fr>,
+) -> Result<Vec<Vec<Fr>>> {
+    let mut instances = vec![
+        public_inputs.iter().map(|v| Fr::from(*v)).collect(),
+        outputs.iter().map(|v| Fr::from(*v)).collect(),
+    ];
+
+    if circuit.circuit_configure.enables_plugin(HostPlugin::Kv) {
+        instances.push(kv_roots);
+    }
+
+    if let Some(image_table) = circuit.layout.image_table {
+        instances.push(vec![image_commitment(
+            &circuit.tables.compilation_tables,
+            &image_table,
+        )?]);
+    }
+
+    Ok(instances)
+}
+
+fn kv_roots_path(prefix: &'static str, output_dir: &PathBuf) -> PathBuf {
+    output_dir.join(format!("{}.{}.kv.roots", prefix, 0))
+}
+
+/*
+ * The roots of the kv store are written along with the proof, one hex line
+ * per root, the verifier reads them as instances.
+ */
+fn write_kv_roots(prefix: &'static str, output_dir: &PathBuf, kvtable: &KvTable) {
+    fs::write(
+        kv_roots_path(prefix, output_dir),
+        format!(
+            "{}\n{}\n",
+            kvtable.old_root.to_str_radix(16),
+            kvtable.new_root.to_str_radix(16)
+        ),
+    )
+    .unwrap();
+}
+
+fn read_kv_roots(prefix: &'static str, output_dir: &PathBuf) -> Vec<Fr> {
+    fs::read_to_string(kv_roots_path(prefix, output_dir))
+        .unwrap()
+        .lines()
+        .map(|root| bn_to_field(&BigUint::parse_bytes(root.as_bytes(), 16).unwrap()))
+        .collect()
+}
+
+fn image_hash_path(prefix: &'static str, output_dir: &PathBuf) -> PathBuf {
+    output_dir.join(format!("{}.{}.image.hash", prefix, 0))
+}
+
+fn vkey_path(prefix: &'static str, output_dir: &PathBuf) -> PathBuf {
+    output_dir.join(format!("{}.{}.vkey.data", prefix, 0))
+}
+
+/*
+ * The image hash in hex, followed by the sha256 of the vkey file it is written
+ * with, so that the hash can't be paired with another vkey.
+ */
+fn image_hash_record(
+    tables: &CompilationTable,
+    prefix: &'static str,
+    output_dir: &PathBuf,
+) -> Result<(String, String)> {
+    let path = vkey_path(prefix, output_dir);
+    let vkey = fs::read(&path).map_err(|e| anyhow!("failed to read the vkey {:?}: {}", path, e))?;
+
+    Ok((
+        field_to_bn(&image_hash::<Fr>(tables)?).to_str_radix(16),
+        hex::encode(sha2::Sha256::digest(&vkey)),
+    ))
+}
+
+fn write_image_hash(tables: &CompilationTable, prefix: &'static str, output_dir: &PathBuf) {
+    let (image_hash, vkey_hash) = image_hash_record(tables, prefix, output_dir).unwrap();
+    let path = image_hash_path(prefix, output_dir);
+
+    info!("Write image hash {} to {:?}", image_hash, path);
+    fs::write(&path, format!("{}\n{}\n", image_hash, vkey_hash)).unwrap();
+}
+
+/*
+ * Check that the image hash written with the vkey is the hash of the given wasm
+ * image, and that the vkey is the one it was written with.
+ */
+fn check_image_hash(
+    prefix: &'static str,
+    output_dir: &PathBuf,
+    tables: &CompilationTable,
+) -> Result<()> {
+    let path = image_hash_path(prefix, output_dir);
+    let expected = fs::read_to_string(&path)
+        .map_err(|e| anyhow!("failed to read the image hash {:?}: {}", path, e))?;
+    let mut expected = expected.lines();
+    let (image_hash, vkey_hash) = image_hash_record(tables, prefix, output_dir)?;
+
+    ensure!(
+        expected.next() == Some(image_hash.as_str()),
+        "the wasm image hash {} does not match the image hash of the setup",
+        image_hash
+    );
+    ensure!(
+        expected.next() == Some(vkey_hash.as_str()),
+        "the vkey {:?} is not the one the image hash was written with",
+        vkey_path(prefix, output_dir)
+    );
+
+    info!("Image hash {} matched", image_hash);
+
+    Ok(())
+}
+
+fn compile_image(wasm_binary: &Vec<u8>, function_name: &str) -> CompilationTable {
     let module = wasmi::Module::from_buffer(wasm_binary).expect("failed to load wasm");
 
     let mut env = HostEnv::new();
     register_sha256_foreign(&mut env);
+    register_keccak_foreign(&mut env);
+    register_poseidon_foreign(&mut env);
+    register_sha256_compress_foreign(&mut env);
+    register_secp256k1_foreign(&mut env);
+    register_u256_foreign(&mut env);
+    register_kv_foreign(&mut env, KvTree::new());
     register_wasm_input_foreign(&mut env, vec![], vec![]);
     register_require_foreign(&mut env);
     env.finalize();
@@ -46,6 +218,7 @@ pub fn build_circuit_without_witness(
     let compiler = WasmInterpreter::new();
     let compiled_module = compiler
         .compile(
+            wasm_binary,
             &module,
             &imports,
             &env.function_description_table(),
@@ -53,14 +226,21 @@ pub fn build_circuit_without_witness(
         )
         .expect("file cannot be complied");
 
-    let builder = ZkWasmCircuitBuilder {
-        tables: Tables {
-            compilation_tables: compiled_module.tables,
+    compiled_module.tables
+}
+
+pub fn build_circuit_without_witness(
+    wasm_binary: &Vec<u8>,
+    function_name: &str,
+    layout: CircuitLayout,
+) -> TestCircuit<Fr> {
+    TestCircuit::new_with_layout(
+        Tables {
+            compilation_tables: compile_image(wasm_binary, function_name),
             execution_tables: ExecutionTable::default(),
         },
-    };
-
-    builder.build_circuit::<Fr>()
+        layout,
+    )
 }
 
 fn build_circuit_with_witness(
@@ -68,11 +248,19 @@ fn build_circuit_with_witness(
     function_name: &str,
     public_inputs: &Vec<u64>,
     private_inputs: &Vec<u64>,
+    layout: CircuitLayout,
 ) -> Result<TestCircuit<Fr>> {
     let module = wasmi::Module::from_buffer(wasm_binary).expect("failed to load wasm");
 
     let mut env = HostEnv::new();
     register_sha256_foreign(&mut env);
+    register_keccak_foreign(&mut env);
+    register_poseidon_foreign(&mut env);
+    register_sha256_compress_foreign(&mut env);
+    register_secp256k1_foreign(&mut env);
+    register_u256_foreign(&mut env);
+    // The kv store of the cli starts empty.
+    register_kv_foreign(&mut env, KvTree::new());
     register_wasm_input_foreign(&mut env, public_inputs.clone(), private_inputs.clone());
     register_require_foreign(&mut env);
     env.finalize();
@@ -81,6 +269,7 @@ fn build_circuit_with_witness(
     let compiler = WasmInterpreter::new();
     let compiled_module = compiler
         .compile(
+            wasm_binary,
             &module,
             &imports,
             &env.function_description_table(),
@@ -88,13 +277,41 @@ fn build_circuit_with_witness(
         )
         .expect("file cannot be complied");
 
-    let execution_result = compiled_module.run(&mut env)?;
+    let mut execution_result = compiled_module.run(&mut env)?;
 
-    let builder = ZkWasmCircuitBuilder {
-        tables: execution_result.tables,
-    };
+    if let Some(kvtable) = kv_table(&env) {
+        execution_result.tables.execution_tables.kvtable = kvtable;
+    }
 
-    Ok(builder.build_circuit())
+    if let Some(trap) = execution_result.trap {
+        return Err(anyhow!("execution trapped: {:?}", trap));
+    }
+
+    Ok(TestCircuit::new_with_layout(
+        execution_result.tables,
+        layout,
+    ))
+}
+
+/*
+ * Fit the circuit layout to the trace of the given inputs.
+ */
+pub fn exec_fit_layout(
+    zkwasm_k: u32,
+    wasm_binary: &Vec<u8>,
+    function_name: &str,
+    public_inputs: &Vec<u64>,
+    private_inputs: &Vec<u64>,
+) -> Result<CircuitLayout> {
+    let circuit = build_circuit_with_witness(
+        wasm_binary,
+        function_name,
+        public_inputs,
+        private_inputs,
+        CircuitLayout::new(zkwasm_k),
+    )?;
+
+    circuit.fit_layout()
 }
 
 pub fn exec_setup(
@@ -104,8 +321,9 @@ pub fn exec_setup(
     wasm_binary: &Vec<u8>,
     entry: &str,
     output_dir: &PathBuf,
+    layout: CircuitLayout,
 ) {
-    let circuit = build_circuit_without_witness(wasm_binary, entry);
+    let circuit = build_circuit_without_witness(wasm_binary, entry, layout);
 
     info!("Setup Params and VerifyingKey");
 
@@ -124,7 +342,7 @@ pub fn exec_setup(
 
     // Setup ZkWasm Vkey
     {
-        let vk_path = &output_dir.join(format!("{}.{}.vkey.data", prefix, 0));
+        let vk_path = &vkey_path(prefix, output_dir);
 
         if vk_path.exists() {
             info!("Found Verifying at {:?}", vk_path);
@@ -132,7 +350,20 @@ pub fn exec_setup(
             info!("Create Verifying to {:?}", vk_path);
         }
 
-        load_or_build_vkey::<Bn256, _>(&params, &circuit, Some(vk_path));
+        circuit.with_params(|| load_or_build_vkey::<Bn256, _>(&params, &circuit, Some(vk_path)));
+
+        // The layout is part of the circuit, save it along with the vkey
+        let layout_path = layout_path(prefix, output_dir);
+        info!("Write circuit layout {:?} to {:?}", layout, layout_path);
+        layout.write(&layout_path);
+
+        /*
+         * The image hash identifies the wasm image the vkey is setup for, the
+         * universal circuit exposes it as the image commitment instead.
+         */
+        if !layout.is_universal() {
+            write_image_hash(&circuit.tables.compilation_tables, prefix, output_dir);
+        }
     }
 
     // Setup Aggregate Circuit Params
@@ -161,24 +392,29 @@ pub fn exec_create_proof(
     public_inputs: &Vec<u64>,
     private_inputs: &Vec<u64>,
 ) -> Result<()> {
-    let circuit =
-        build_circuit_with_witness(wasm_binary, function_name, public_inputs, private_inputs)?;
-    let instances = vec![public_inputs
-        .iter()
-        .map(|v| Fr::from(*v))
-        .collect::<Vec<_>>()
-        .clone()];
+    let layout = read_layout(zkwasm_k, prefix, output_dir)?;
+    let circuit = build_circuit_with_witness(
+        wasm_binary,
+        function_name,
+        public_inputs,
+        private_inputs,
+        layout,
+    )?;
+    let outputs = wasm_outputs(&circuit.tables.execution_tables.etable);
+    let instances = circuit_instances(
+        &circuit,
+        public_inputs,
+        &outputs,
+        kv_roots(&circuit.tables.execution_tables.kvtable),
+    )?;
 
     if true {
         info!("Mock test...");
 
         circuit.tables.write_json(Some(output_dir.clone()));
 
-        let prover = MockProver::run(
-            zkwasm_k,
-            &circuit,
-            vec![public_inputs.into_iter().map(|v| Fr::from(*v)).collect()],
-        )?;
+        let prover =
+            circuit.with_params(|| MockProver::run(zkwasm_k, &circuit, instances.clone()))?;
 
         assert_eq!(prover.verify(), Ok(()));
 
@@ -190,22 +426,26 @@ pub fn exec_create_proof(
         Some(&output_dir.join(format!("K{}.params", zkwasm_k))),
     );
 
-    let vkey = load_vkey::<Bn256, TestCircuit<_>>(
-        &params,
-        &output_dir.join(format!("{}.{}.vkey.data", prefix, 0)),
-    );
+    circuit.with_params(|| {
+        let vkey = load_vkey::<Bn256, TestCircuit<_>>(&params, &vkey_path(prefix, output_dir));
 
-    load_or_create_proof::<Bn256, _>(
-        &params,
-        vkey,
-        circuit.clone(),
-        &instances.iter().map(|x| &x[..]).collect::<Vec<_>>(),
-        Some(&output_dir.join(format!("{}.{}.transcript.data", prefix, 0))),
-        TranscriptHash::Poseidon,
-        false,
-    );
+        load_or_create_proof::<Bn256, _>(
+            &params,
+            vkey,
+            circuit.clone(),
+            &instances.iter().map(|x| &x[..]).collect::<Vec<_>>(),
+            Some(&output_dir.join(format!("{}.{}.transcript.data", prefix, 0))),
+            TranscriptHash::Poseidon,
+            false,
+        )
+    });
+
+    if circuit.circuit_configure.enables_plugin(HostPlugin::Kv) {
+        write_kv_roots(prefix, output_dir, &circuit.tables.execution_tables.kvtable);
+    }
 
     info!("Proof has been created.");
+    info!("Outputs: {:?}", outputs);
 
     Ok(())
 }
@@ -213,27 +453,36 @@ pub fn exec_create_proof(
 pub fn exec_verify_proof(
     prefix: &'static str,
     zkwasm_k: u32,
+    wasm_binary: &Vec<u8>,
+    function_name: &str,
     output_dir: &PathBuf,
     proof_path: &PathBuf,
     public_inputs: &Vec<u64>,
-) {
-    let public_inputs_size = public_inputs.len();
+    outputs: &Vec<u64>,
+) -> Result<()> {
+    let layout = read_layout(zkwasm_k, prefix, output_dir)?;
+    let circuit = build_circuit_without_witness(wasm_binary, function_name, layout);
 
-    let instances = vec![public_inputs
-        .iter()
-        .map(|v| Fr::from(*v))
-        .collect::<Vec<_>>()
-        .clone()];
+    if !layout.is_universal() {
+        check_image_hash(prefix, output_dir, &circuit.tables.compilation_tables)?;
+    }
+
+    let kv_roots = if circuit.circuit_configure.enables_plugin(HostPlugin::Kv) {
+        read_kv_roots(prefix, output_dir)
+    } else {
+        vec![]
+    };
+    let instances = circuit_instances(&circuit, public_inputs, outputs, kv_roots)?;
+    let public_inputs_size = instances.iter().map(|x| x.len()).max().unwrap();
 
     let params = load_or_build_unsafe_params::<Bn256>(
         zkwasm_k,
         Some(&output_dir.join(format!("K{}.params", zkwasm_k))),
     );
 
-    let vkey = load_vkey::<Bn256, TestCircuit<_>>(
-        &params,
-        &output_dir.join(format!("{}.{}.vkey.data", prefix, 0)),
-    );
+    let vkey = circuit.with_params(|| {
+        load_vkey::<Bn256, TestCircuit<_>>(&params, &vkey_path(prefix, output_dir))
+    });
 
     let proof = load_proof(proof_path);
 
@@ -247,9 +496,11 @@ pub fn exec_verify_proof(
         &[&instances.iter().map(|x| &x[..]).collect::<Vec<_>>()[..]],
         &mut PoseidonRead::init(&proof[..]),
     )
-    .unwrap();
+    .map_err(|e| anyhow!("failed to verify the proof: {:?}", e))?;
 
     info!("Verifing proof passed");
+
+    Ok(())
 }
 
 pub fn exec_aggregate_create_proof(
@@ -264,13 +515,21 @@ pub fn exec_aggregate_create_proof(
 ) {
     assert_eq!(public_inputs.len(), private_inputs.len());
 
+    let layout = read_layout(zkwasm_k, prefix, output_dir).unwrap();
+
     let (circuits, instances) = public_inputs.iter().zip(private_inputs.iter()).fold(
         (vec![], vec![]),
         |(mut circuits, mut instances), (public, private)| {
             let circuit =
-                build_circuit_with_witness(&wasm_binary, &function_name, &public, &private)
+                build_circuit_with_witness(&wasm_binary, &function_name, &public, &private, layout)
                     .unwrap();
-            let instance = vec![public.iter().map(|v| Fr::from(*v)).collect()];
+            let instance = circuit_instances(
+                &circuit,
+                public,
+                &wasm_outputs(&circuit.tables.execution_tables.etable),
+                kv_roots(&circuit.tables.execution_tables.kvtable),
+            )
+            .unwrap();
 
             circuits.push(circuit);
             instances.push(instance);
@@ -279,17 +538,23 @@ pub fn exec_aggregate_create_proof(
         },
     );
 
-    let (aggregate_circuit, aggregate_instances) = run_circuit_unsafe_full_pass::<Bn256, _>(
-        &output_dir.as_path(),
-        prefix,
-        zkwasm_k,
-        circuits,
-        instances,
-        TranscriptHash::Poseidon,
-        vec![],
-        false,
-    )
-    .unwrap();
+    let compilation_tables = circuits[0].tables.compilation_tables.clone();
+
+    // The circuits share the image, hence the params
+    let (aggregate_circuit, aggregate_instances) =
+        with_circuit_params(circuits[0].params(), || {
+            run_circuit_unsafe_full_pass::<Bn256, _>(
+                &output_dir.as_path(),
+                prefix,
+                zkwasm_k,
+                circuits,
+                instances,
+                TranscriptHash::Poseidon,
+                vec![],
+                false,
+            )
+        })
+        .unwrap();
 
     run_circuit_unsafe_full_pass::<Bn256, _>(
         &output_dir.as_path(),
@@ -301,15 +566,38 @@ pub fn exec_aggregate_create_proof(
         vec![],
         true,
     );
+
+    // The aggregate vkey is derived from the vkey of the image
+    if !layout.is_universal() {
+        write_image_hash(&compilation_tables, AGGREGATE_PREFIX, output_dir);
+    }
 }
 
 pub fn exec_verify_aggregate_proof(
+    prefix: &'static str,
+    zkwasm_k: u32,
     aggregate_k: u32,
+    wasm_binary: &Vec<u8>,
+    function_name: &str,
     output_dir: &PathBuf,
     proof_path: &PathBuf,
     instances_path: &PathBuf,
     n_proofs: usize,
-) {
+) -> Result<()> {
+    /*
+     * The aggregate instances only commit to the instances of the proofs, so
+     * the image commitment of a universal proof can't be checked from them.
+     */
+    ensure!(
+        !read_layout(zkwasm_k, prefix, output_dir)?.is_universal(),
+        "an aggregate proof of the universal circuit does not identify its image, verify the proofs instead"
+    );
+    check_image_hash(
+        AGGREGATE_PREFIX,
+        output_dir,
+        &compile_image(wasm_binary, function_name),
+    )?;
+
     let params = load_or_build_unsafe_params::<Bn256>(
         aggregate_k,
         Some(&output_dir.join(format!("K{}.params", aggregate_k))),
@@ -318,7 +606,7 @@ pub fn exec_verify_aggregate_proof(
     let proof = load_proof(&proof_path.as_path());
     let vkey = load_vkey::<Bn256, AggregatorCircuit<G1Affine>>(
         &params,
-        &output_dir.join(format!("{}.{}.vkey.data", AGGREGATE_PREFIX, 0)),
+        &vkey_path(AGGREGATE_PREFIX, output_dir),
     );
 
     let public_inputs_size: u32 = 6 + 3 * n_proofs as u32;
@@ -336,9 +624,11 @@ pub fn exec_verify_aggregate_proof(
         &[&instances.iter().map(|x| &x[..]).collect::<Vec<_>>()[..]],
         &mut ShaRead::<_, _, _, sha2::Sha256>::init(&proof[..]),
     )
-    .unwrap();
+    .map_err(|e| anyhow!("failed to verify the aggregate proof: {:?}", e))?;
+
+    info!("Verifing Aggregate Proof Passed.");
 
-    info!("Verifing Aggregate Proof Passed.")
+    Ok(())
 }
 
 const SOLIDITY_VERIFY_STEPS: usize = 2;
@@ -375,7 +665,7 @@ pub fn exec_solidity_aggregate_proof(
 
         let vkey = load_vkey::<Bn256, AggregatorCircuit<G1Affine>>(
             &params,
-            &output_dir.join(format!("{}.{}.vkey.data", AGGREGATE_PREFIX, 0)),
+            &vkey_path(AGGREGATE_PREFIX, output_dir),
         );
 
         let instances = load_instance::<Bn256>(&[public_inputs_size as u32], &instances_path);
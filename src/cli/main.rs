@@ -84,6 +84,24 @@ impl ArgBuilder for SampleApp {
         vec![inputs]
     }
 
+    fn single_output_arg<'a>() -> Arg<'a> {
+        Arg::new("wasm_output")
+            .long("output")
+            .value_parser(value_parser!(String))
+            .action(ArgAction::Append)
+            .help("Expected outputs of your wasm program of format value:type where type=i64|bytes|bytes-packed")
+            .min_values(0)
+    }
+    fn parse_single_output_arg(matches: &ArgMatches) -> Vec<u64> {
+        let outputs: Vec<&str> = matches
+            .get_many("wasm_output")
+            .unwrap_or_default()
+            .map(|v: &String| v.as_str())
+            .collect();
+
+        parse_args(outputs.into())
+    }
+
     fn single_private_arg<'a>() -> Arg<'a> {
         Arg::new("private")
             .long("private")
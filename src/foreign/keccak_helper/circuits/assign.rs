@@ -0,0 +1,286 @@
+use super::{KeccakHelperEncode, KeccakHelperTableConfig, BLOCK_LINES, SEGMENT_LINES};
+use crate::circuits::utils::bn_to_field;
+use crate::foreign::keccak_helper::{
+    keccak_round, KeccakHelperOp, KECCAK_LANES, KECCAK_ROUNDS, ROUND_CONSTANTS,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Region},
+    plonk::{Advice, Column, Error},
+};
+use num_bigint::BigUint;
+use specs::{etable::EventTableEntry, host_function::HostPlugin, step::StepInfo};
+
+pub struct KeccakHelperTableChip<F: FieldExt> {
+    pub(crate) config: KeccakHelperTableConfig<F>,
+    max_available_rows: usize,
+}
+
+/*
+ * The input states of the permutations called by the lanes of a trace, a
+ * lane never given is 0.
+ */
+pub(crate) fn keccak_permutations(entries: &Vec<EventTableEntry>) -> Vec<[u64; KECCAK_LANES]> {
+    let mut permutations = vec![];
+
+    for entry in entries {
+        if let StepInfo::CallHost {
+            plugin,
+            function_name,
+            args,
+            ..
+        } = &entry.step_info
+        {
+            assert_eq!(*plugin, HostPlugin::Keccak);
+
+            let index = args[0] as usize;
+            let permutation = index / KECCAK_LANES;
+
+            if permutations.len() <= permutation {
+                permutations.resize(permutation + 1, [0; KECCAK_LANES]);
+            }
+
+            if KeccakHelperOp::from(function_name) == KeccakHelperOp::Input {
+                permutations[permutation][index % KECCAK_LANES] = args[1];
+            }
+        } else {
+            unreachable!()
+        }
+    }
+
+    permutations
+}
+
+impl<F: FieldExt> KeccakHelperTableChip<F> {
+    pub fn new(config: KeccakHelperTableConfig<F>, max_available_rows: usize) -> Self {
+        Self {
+            config,
+            max_available_rows,
+        }
+    }
+
+    fn assign_bits(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        cols: &[Column<Advice>],
+        lanes: &[u64],
+    ) -> Result<(), Error> {
+        for (col, lane) in cols.iter().zip(lanes.iter()) {
+            for z in 0..SEGMENT_LINES {
+                region.assign_advice(
+                    || "keccak helper bit",
+                    *col,
+                    offset + z,
+                    || Ok(F::from((lane >> z) & 1)),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn assign_lanes(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lanes: &[u64; KECCAK_LANES],
+    ) -> Result<(), Error> {
+        self.assign_bits(region, offset, &self.config.state, lanes)?;
+
+        for (i, lane) in lanes.iter().enumerate() {
+            for z in 0..SEGMENT_LINES {
+                region.assign_advice(
+                    || "keccak helper lane accumulator",
+                    self.config.pi[i],
+                    offset + z,
+                    || Ok(F::from(lane >> z)),
+                )?;
+            }
+
+            region.assign_advice(
+                || "keccak helper lane",
+                self.config.lane,
+                offset + i,
+                || Ok(F::from(*lane)),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn assign_fixed(&self, region: &mut Region<'_, F>, block: usize) -> Result<(), Error> {
+        for line in 0..BLOCK_LINES {
+            let offset = block * BLOCK_LINES + line;
+            let segment = line / SEGMENT_LINES;
+            let z = line % SEGMENT_LINES;
+            let is_input = segment == 0;
+            let is_output = segment == KECCAK_ROUNDS + 1;
+
+            region.assign_fixed(
+                || "keccak helper sel",
+                self.config.sel,
+                offset,
+                || Ok(F::one()),
+            )?;
+
+            if line == 0 {
+                region.assign_fixed(
+                    || "keccak helper block first line sel",
+                    self.config.block_first_line_sel,
+                    offset,
+                    || Ok(F::one()),
+                )?;
+            }
+
+            if is_input {
+                region.assign_fixed(
+                    || "keccak helper input sel",
+                    self.config.input_sel,
+                    offset,
+                    || Ok(F::one()),
+                )?;
+            }
+
+            if !is_input && !is_output {
+                region.assign_fixed(
+                    || "keccak helper round sel",
+                    self.config.round_sel,
+                    offset,
+                    || Ok(F::one()),
+                )?;
+                region.assign_fixed(
+                    || "keccak helper rc",
+                    self.config.rc,
+                    offset,
+                    || Ok(F::from((ROUND_CONSTANTS[segment - 1] >> z) & 1)),
+                )?;
+
+                for (r, col) in self.config.wrap_sel.iter() {
+                    if z < *r as usize {
+                        region.assign_fixed(
+                            || "keccak helper wrap sel",
+                            *col,
+                            offset,
+                            || Ok(F::one()),
+                        )?;
+                    }
+                }
+            }
+
+            if is_input || is_output {
+                let op = if is_input {
+                    KeccakHelperOp::Input
+                } else {
+                    KeccakHelperOp::Output
+                };
+
+                region.assign_fixed(
+                    || "keccak helper lane acc sel",
+                    self.config.lane_acc_sel,
+                    offset,
+                    || Ok(F::one()),
+                )?;
+
+                if z < SEGMENT_LINES - 1 {
+                    region.assign_fixed(
+                        || "keccak helper lane acc shift",
+                        self.config.lane_acc_shift,
+                        offset,
+                        || Ok(F::one()),
+                    )?;
+                }
+
+                if z == 0 {
+                    region.assign_fixed(
+                        || "keccak helper lane first line sel",
+                        self.config.lane_first_line_sel,
+                        offset,
+                        || Ok(F::one()),
+                    )?;
+                }
+
+                if z < KECCAK_LANES {
+                    region.assign_fixed(
+                        || "keccak helper lane sel",
+                        self.config.lane_sel,
+                        offset,
+                        || Ok(F::one()),
+                    )?;
+                    region.assign_fixed(
+                        || "keccak helper lane prefix",
+                        self.config.lane_prefix,
+                        offset,
+                        || {
+                            Ok(bn_to_field(&KeccakHelperEncode::encode_lane_prefix::<
+                                BigUint,
+                            >(
+                                op, (block * KECCAK_LANES + z) as u64
+                            )))
+                        },
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        entries: &Vec<EventTableEntry>,
+    ) -> Result<(), Error> {
+        let permutations = keccak_permutations(entries);
+
+        assert!(permutations.len() * BLOCK_LINES <= self.max_available_rows);
+
+        layouter.assign_region(
+            || "keccak helper assign",
+            |mut region| {
+                for block in 0..self.max_available_rows / BLOCK_LINES {
+                    self.assign_fixed(&mut region, block)?;
+                }
+
+                for (block, input) in permutations.iter().enumerate() {
+                    let offset = block * BLOCK_LINES;
+
+                    for line in 0..BLOCK_LINES {
+                        region.assign_advice(
+                            || "keccak helper enable",
+                            self.config.enable,
+                            offset + line,
+                            || Ok(F::one()),
+                        )?;
+                    }
+
+                    self.assign_lanes(&mut region, offset, input)?;
+
+                    let mut state = *input;
+                    for round in 0..KECCAK_ROUNDS {
+                        let offset = offset + (round + 1) * SEGMENT_LINES;
+                        let (witness, next) = keccak_round(state, round);
+
+                        self.assign_bits(&mut region, offset, &self.config.state, &witness.state)?;
+                        self.assign_bits(&mut region, offset, &self.config.c, &witness.c)?;
+                        self.assign_bits(&mut region, offset, &self.config.d, &witness.d)?;
+                        self.assign_bits(&mut region, offset, &self.config.theta, &witness.theta)?;
+                        self.assign_bits(&mut region, offset, &self.config.pi, &witness.pi)?;
+
+                        state = next;
+                    }
+
+                    self.assign_lanes(
+                        &mut region,
+                        offset + (KECCAK_ROUNDS + 1) * SEGMENT_LINES,
+                        &state,
+                    )?;
+                }
+
+                Ok(())
+            },
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,191 @@
+use super::{KeccakHelperTableConfig, SEGMENT_LINES};
+use crate::foreign::keccak_helper::{lane_index, KECCAK_LANES, RHO_OFFSETS};
+use crate::{constant_from, curr, fixed_curr, next, nextn, prev};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Advice, Column, ConstraintSystem, Expression, VirtualCells},
+};
+
+fn xor<F: FieldExt>(a: Expression<F>, b: Expression<F>) -> Expression<F> {
+    a.clone() + b.clone() - constant_from!(2) * a * b
+}
+
+impl<F: FieldExt> KeccakHelperTableConfig<F> {
+    /*
+     * The bit z - r of a lane in the segment, the lines z < r wrap around to
+     * the end of the segment.
+     */
+    fn rotated_expr(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        col: Column<Advice>,
+        r: u32,
+    ) -> Expression<F> {
+        if r == 0 {
+            curr!(meta, col)
+        } else {
+            let wrap = fixed_curr!(meta, *self.wrap_sel.get(&r).unwrap());
+
+            wrap.clone() * nextn!(meta, col, SEGMENT_LINES as i32 - r as i32)
+                + (constant_from!(1) - wrap) * nextn!(meta, col, -(r as i32))
+        }
+    }
+
+    pub fn _configure(&self, meta: &mut ConstraintSystem<F>) {
+        meta.create_gate("keccak helper enable", |meta| {
+            let enable = curr!(meta, self.enable);
+
+            vec![
+                fixed_curr!(meta, self.sel) * enable.clone() * (constant_from!(1) - enable.clone()),
+                fixed_curr!(meta, self.sel)
+                    * (constant_from!(1) - fixed_curr!(meta, self.block_first_line_sel))
+                    * (enable - prev!(meta, self.enable)),
+            ]
+        });
+
+        meta.create_gate("keccak helper input", |meta| {
+            let mut constraints = vec![];
+
+            for lane in self.state {
+                let bit = curr!(meta, lane);
+
+                constraints.push(bit.clone() * (constant_from!(1) - bit.clone()));
+                constraints.push(bit - nextn!(meta, lane, SEGMENT_LINES as i32));
+            }
+
+            constraints
+                .into_iter()
+                .map(|x| x * fixed_curr!(meta, self.input_sel) * curr!(meta, self.enable))
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("keccak helper theta", |meta| {
+            let mut constraints = vec![];
+
+            for x in 0..5 {
+                let sum = (0..5)
+                    .map(|y| curr!(meta, self.state[lane_index(x, y)]))
+                    .reduce(|acc, expr| acc + expr)
+                    .unwrap();
+                let c = curr!(meta, self.c[x]);
+                let even = sum - c.clone();
+
+                constraints.push(c.clone() * (constant_from!(1) - c));
+                constraints.push(
+                    even.clone() * (even.clone() - constant_from!(2)) * (even - constant_from!(4)),
+                );
+            }
+
+            for x in 0..5 {
+                let d = xor(
+                    curr!(meta, self.c[(x + 4) % 5]),
+                    self.rotated_expr(meta, self.c[(x + 1) % 5], 1),
+                );
+
+                constraints.push(curr!(meta, self.d[x]) - d);
+            }
+
+            for x in 0..5 {
+                for y in 0..5 {
+                    let theta = xor(
+                        curr!(meta, self.state[lane_index(x, y)]),
+                        curr!(meta, self.d[x]),
+                    );
+
+                    constraints.push(curr!(meta, self.theta[lane_index(x, y)]) - theta);
+                }
+            }
+
+            constraints
+                .into_iter()
+                .map(|x| x * fixed_curr!(meta, self.round_sel) * curr!(meta, self.enable))
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("keccak helper rho and pi", |meta| {
+            let mut constraints = vec![];
+
+            for x in 0..5 {
+                for y in 0..5 {
+                    let rotated =
+                        self.rotated_expr(meta, self.theta[lane_index(x, y)], RHO_OFFSETS[x][y]);
+
+                    constraints
+                        .push(curr!(meta, self.pi[lane_index(y, (2 * x + 3 * y) % 5)]) - rotated);
+                }
+            }
+
+            constraints
+                .into_iter()
+                .map(|x| x * fixed_curr!(meta, self.round_sel) * curr!(meta, self.enable))
+                .collect::<Vec<_>>()
+        });
+
+        /*
+         * The state of the next segment is chi of pi, iota flips the bits of
+         * the lane (0, 0) where the round constant is set.
+         */
+        meta.create_gate("keccak helper chi and iota", |meta| {
+            let mut constraints = vec![];
+
+            for x in 0..5 {
+                for y in 0..5 {
+                    let b = curr!(meta, self.pi[lane_index(x, y)]);
+                    let b1 = curr!(meta, self.pi[lane_index((x + 1) % 5, y)]);
+                    let b2 = curr!(meta, self.pi[lane_index((x + 2) % 5, y)]);
+                    let chi = xor(b, (constant_from!(1) - b1) * b2);
+                    let next = nextn!(meta, self.state[lane_index(x, y)], SEGMENT_LINES as i32);
+
+                    let constraint = fixed_curr!(meta, self.round_sel) * (next - chi.clone());
+
+                    if x == 0 && y == 0 {
+                        constraints.push(
+                            constraint
+                                + fixed_curr!(meta, self.rc)
+                                    * (constant_from!(2) * chi - constant_from!(1)),
+                        );
+                    } else {
+                        constraints.push(constraint);
+                    }
+                }
+            }
+
+            constraints
+                .into_iter()
+                .map(|x| x * curr!(meta, self.enable))
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("keccak helper lane accumulator", |meta| {
+            let mut constraints = vec![];
+
+            for i in 0..KECCAK_LANES {
+                constraints.push(
+                    curr!(meta, self.pi[i])
+                        - curr!(meta, self.state[i])
+                        - constant_from!(2)
+                            * fixed_curr!(meta, self.lane_acc_shift)
+                            * next!(meta, self.pi[i]),
+                );
+            }
+
+            constraints
+                .into_iter()
+                .map(|x| x * fixed_curr!(meta, self.lane_acc_sel) * curr!(meta, self.enable))
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("keccak helper lanes", |meta| {
+            let mut constraints = vec![];
+
+            for i in 0..KECCAK_LANES {
+                constraints.push(nextn!(meta, self.lane, i as i32) - curr!(meta, self.pi[i]));
+            }
+
+            constraints
+                .into_iter()
+                .map(|x| x * fixed_curr!(meta, self.lane_first_line_sel) * curr!(meta, self.enable))
+                .collect::<Vec<_>>()
+        });
+    }
+}
@@ -0,0 +1,127 @@
+use super::{KeccakHelperOp, KECCAK_LANES, KECCAK_ROUNDS, RHO_OFFSETS};
+use crate::{curr, fixed_curr, foreign::ForeignTableConfig};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Advice, Column, ConstraintSystem, Expression, Fixed, VirtualCells},
+};
+use num_bigint::BigUint;
+use specs::encode::FromBn;
+use std::{collections::BTreeMap, marker::PhantomData};
+
+pub mod assign;
+pub mod config;
+
+/*
+ * A permutation is laid out in segments of 64 lines, the line z of a segment
+ * holds the bit z of every lane: the input segment, one segment per round and
+ * the output segment.
+ */
+pub(crate) const SEGMENT_LINES: usize = 64;
+const SEGMENTS: usize = KECCAK_ROUNDS + 2;
+pub(crate) const BLOCK_LINES: usize = SEGMENTS * SEGMENT_LINES;
+
+const OP_SHIFT: usize = 96;
+const INDEX_SHIFT: usize = 64;
+
+pub struct KeccakHelperEncode();
+
+impl KeccakHelperEncode {
+    pub(super) fn encode_lane<T: FromBn>(op: T, index: T, value: T) -> T {
+        op * T::from_bn(&(BigUint::from(1u64) << OP_SHIFT))
+            + index * T::from_bn(&(BigUint::from(1u64) << INDEX_SHIFT))
+            + value
+    }
+
+    pub(super) fn encode_lane_prefix<T: FromBn>(op: KeccakHelperOp, index: u64) -> T {
+        Self::encode_lane(
+            T::from_bn(&BigUint::from(op as u64)),
+            T::from_bn(&BigUint::from(index)),
+            T::from_bn(&BigUint::from(0u64)),
+        )
+    }
+}
+
+#[derive(Clone)]
+pub struct KeccakHelperTableConfig<F: FieldExt> {
+    sel: Column<Fixed>,
+    block_first_line_sel: Column<Fixed>,
+    input_sel: Column<Fixed>,
+    round_sel: Column<Fixed>,
+    // The bits of the round constant.
+    rc: Column<Fixed>,
+    lane_acc_sel: Column<Fixed>,
+    lane_acc_shift: Column<Fixed>,
+    lane_first_line_sel: Column<Fixed>,
+    lane_sel: Column<Fixed>,
+    lane_prefix: Column<Fixed>,
+    // For each rotation r, enabled on the lines z < r which wrap around the segment.
+    wrap_sel: BTreeMap<u32, Column<Fixed>>,
+
+    enable: Column<Advice>,
+    state: [Column<Advice>; KECCAK_LANES],
+    c: [Column<Advice>; 5],
+    d: [Column<Advice>; 5],
+    theta: [Column<Advice>; KECCAK_LANES],
+    // The accumulators of the lanes in the input and output segments.
+    pi: [Column<Advice>; KECCAK_LANES],
+    lane: Column<Advice>,
+
+    mark: PhantomData<F>,
+}
+
+impl<F: FieldExt> KeccakHelperTableConfig<F> {
+    fn new(meta: &mut ConstraintSystem<F>) -> Self {
+        let mut wrap_sel = BTreeMap::new();
+        for r in RHO_OFFSETS.iter().flatten().chain([1u32].iter()) {
+            if *r != 0 && !wrap_sel.contains_key(r) {
+                wrap_sel.insert(*r, meta.fixed_column());
+            }
+        }
+
+        Self {
+            sel: meta.fixed_column(),
+            block_first_line_sel: meta.fixed_column(),
+            input_sel: meta.fixed_column(),
+            round_sel: meta.fixed_column(),
+            rc: meta.fixed_column(),
+            lane_acc_sel: meta.fixed_column(),
+            lane_acc_shift: meta.fixed_column(),
+            lane_first_line_sel: meta.fixed_column(),
+            lane_sel: meta.fixed_column(),
+            lane_prefix: meta.fixed_column(),
+            wrap_sel,
+            enable: meta.advice_column(),
+            state: [(); KECCAK_LANES].map(|_| meta.advice_column()),
+            c: [(); 5].map(|_| meta.advice_column()),
+            d: [(); 5].map(|_| meta.advice_column()),
+            theta: [(); KECCAK_LANES].map(|_| meta.advice_column()),
+            pi: [(); KECCAK_LANES].map(|_| meta.advice_column()),
+            lane: meta.advice_column(),
+            mark: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        let config = Self::new(meta);
+        config._configure(meta);
+        config
+    }
+}
+
+impl<F: FieldExt> ForeignTableConfig<F> for KeccakHelperTableConfig<F> {
+    fn configure_in_table(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        key: &'static str,
+        expr: &dyn Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+    ) {
+        meta.lookup_any(key, |meta| {
+            vec![(
+                expr(meta),
+                fixed_curr!(meta, self.lane_sel)
+                    * curr!(meta, self.enable)
+                    * (fixed_curr!(meta, self.lane_prefix) + curr!(meta, self.lane)),
+            )]
+        });
+    }
+}
@@ -1 +1,141 @@
+use strum_macros::EnumIter;
+
+pub mod circuits;
+pub mod etable_op_configure;
+pub mod runtime;
 pub mod test;
+
+pub const KECCAK_FOREIGN_TABLE_KEY: &'static str = "keccak-helper-table";
+pub const KECCAK_FOREIGN_FUNCTION_NAME_INPUT: &'static str = "zkwasm_keccak_f_input";
+pub const KECCAK_FOREIGN_FUNCTION_NAME_OUTPUT: &'static str = "zkwasm_keccak_f_output";
+
+pub const KECCAK_LANES: usize = 25;
+pub const KECCAK_ROUNDS: usize = 24;
+
+/*
+ * A guest calls keccak-f[1600] lane by lane: the lane (x, y) of the k-th
+ * permutation has the index k * 25 + x + 5 * y, the input lanes of a
+ * permutation must be given before its output lanes are read.
+ */
+#[derive(Clone, Copy, EnumIter, PartialEq)]
+pub enum KeccakHelperOp {
+    Input = 1,
+    Output = 2,
+}
+
+impl From<&String> for KeccakHelperOp {
+    fn from(function_name: &String) -> Self {
+        match function_name.as_str() {
+            KECCAK_FOREIGN_FUNCTION_NAME_INPUT => KeccakHelperOp::Input,
+            KECCAK_FOREIGN_FUNCTION_NAME_OUTPUT => KeccakHelperOp::Output,
+            _ => unreachable!(),
+        }
+    }
+}
+
+pub(crate) const ROUND_CONSTANTS: [u64; KECCAK_ROUNDS] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+// Indexed by [x][y].
+pub(crate) const RHO_OFFSETS: [[u32; 5]; 5] = [
+    [0, 36, 3, 41, 18],
+    [1, 44, 10, 45, 2],
+    [62, 6, 43, 15, 61],
+    [28, 55, 25, 21, 56],
+    [27, 20, 39, 8, 14],
+];
+
+pub(crate) fn lane_index(x: usize, y: usize) -> usize {
+    x + 5 * y
+}
+
+/*
+ * The intermediate states of a round, the circuit holds each of them.
+ */
+pub(crate) struct KeccakRound {
+    pub(crate) state: [u64; KECCAK_LANES],
+    pub(crate) c: [u64; 5],
+    pub(crate) d: [u64; 5],
+    pub(crate) theta: [u64; KECCAK_LANES],
+    pub(crate) pi: [u64; KECCAK_LANES],
+}
+
+pub(crate) fn keccak_round(
+    state: [u64; KECCAK_LANES],
+    round: usize,
+) -> (KeccakRound, [u64; KECCAK_LANES]) {
+    let mut c = [0u64; 5];
+    for x in 0..5 {
+        for y in 0..5 {
+            c[x] ^= state[lane_index(x, y)];
+        }
+    }
+
+    let mut d = [0u64; 5];
+    for x in 0..5 {
+        d[x] = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+    }
+
+    let mut theta = [0u64; KECCAK_LANES];
+    for x in 0..5 {
+        for y in 0..5 {
+            theta[lane_index(x, y)] = state[lane_index(x, y)] ^ d[x];
+        }
+    }
+
+    let mut pi = [0u64; KECCAK_LANES];
+    for x in 0..5 {
+        for y in 0..5 {
+            pi[lane_index(y, (2 * x + 3 * y) % 5)] =
+                theta[lane_index(x, y)].rotate_left(RHO_OFFSETS[x][y]);
+        }
+    }
+
+    let mut next = [0u64; KECCAK_LANES];
+    for x in 0..5 {
+        for y in 0..5 {
+            next[lane_index(x, y)] = pi[lane_index(x, y)]
+                ^ (!pi[lane_index((x + 1) % 5, y)] & pi[lane_index((x + 2) % 5, y)]);
+        }
+    }
+    next[0] ^= ROUND_CONSTANTS[round];
+
+    (
+        KeccakRound {
+            state,
+            c,
+            d,
+            theta,
+            pi,
+        },
+        next,
+    )
+}
+
+pub fn keccak_f(state: [u64; KECCAK_LANES]) -> [u64; KECCAK_LANES] {
+    (0..KECCAK_ROUNDS).fold(state, |state, round| keccak_round(state, round).1)
+}
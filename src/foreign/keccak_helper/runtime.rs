@@ -0,0 +1,81 @@
+use std::rc::Rc;
+
+use specs::{host_function::HostPlugin, types::ValueType};
+use wasmi::{RuntimeArgs, RuntimeValue};
+
+use crate::runtime::host::{host_env::HostEnv, ForeignContext};
+
+use super::{
+    keccak_f, KeccakHelperOp, KECCAK_FOREIGN_FUNCTION_NAME_INPUT,
+    KECCAK_FOREIGN_FUNCTION_NAME_OUTPUT, KECCAK_LANES,
+};
+
+struct Context {
+    inputs: Vec<[u64; KECCAK_LANES]>,
+}
+
+impl Context {
+    fn new() -> Self {
+        Context { inputs: vec![] }
+    }
+
+    fn permutation(&mut self, index: usize) -> &mut [u64; KECCAK_LANES] {
+        let permutation = index / KECCAK_LANES;
+
+        if self.inputs.len() <= permutation {
+            self.inputs.resize(permutation + 1, [0; KECCAK_LANES]);
+        }
+
+        &mut self.inputs[permutation]
+    }
+}
+
+impl ForeignContext for Context {}
+
+fn input(context: &mut Context, args: RuntimeArgs) -> Option<RuntimeValue> {
+    let index: u32 = args.nth(0);
+    let value: u64 = args.nth(1);
+
+    context.permutation(index as usize)[index as usize % KECCAK_LANES] = value;
+
+    None
+}
+
+fn output(context: &mut Context, args: RuntimeArgs) -> Option<RuntimeValue> {
+    let index: u32 = args.nth(0);
+
+    let res = keccak_f(*context.permutation(index as usize))[index as usize % KECCAK_LANES];
+
+    Some(RuntimeValue::I64(res as i64))
+}
+
+pub fn register_keccak_foreign(env: &mut HostEnv) {
+    env.internal_env
+        .register_plugin(HostPlugin::Keccak, Box::new(Context::new()));
+
+    env.internal_env.register_function(
+        KECCAK_FOREIGN_FUNCTION_NAME_INPUT,
+        specs::host_function::Signature {
+            params: vec![ValueType::I32, ValueType::I64],
+            return_type: None,
+        },
+        HostPlugin::Keccak,
+        KeccakHelperOp::Input as usize,
+        Rc::new(|context: &mut dyn ForeignContext, args: RuntimeArgs| {
+            input(context.downcast_mut::<Context>().unwrap(), args)
+        }),
+    );
+
+    env.internal_env.register_function(
+        KECCAK_FOREIGN_FUNCTION_NAME_OUTPUT,
+        specs::host_function::Signature {
+            params: vec![ValueType::I32],
+            return_type: Some(specs::types::ValueType::I64),
+        },
+        HostPlugin::Keccak,
+        KeccakHelperOp::Output as usize,
+        Rc::new(|context: &mut dyn ForeignContext, args: RuntimeArgs| {
+            output(context.downcast_mut::<Context>().unwrap(), args)
+        }),
+    );
+}
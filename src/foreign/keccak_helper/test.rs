@@ -1,14 +1,18 @@
 #[cfg(test)]
 pub(crate) mod tests {
     use crate::{
-        foreign::wasm_input_helper::runtime::register_wasm_input_foreign,
-        runtime::host::host_env::HostEnv, test::test_circuit_with_env,
+        circuits::config::CircuitLayout,
+        foreign::{
+            keccak_helper::runtime::register_keccak_foreign,
+            wasm_input_helper::runtime::register_wasm_input_foreign,
+        },
+        runtime::{host::host_env::HostEnv, wasmi_interpreter::Execution, WasmInterpreter},
+        test::{test_circuit_with_env, test_circuit_with_env_and_layout},
     };
 
-    use crate::circuits::config::set_zkwasm_k;
     use halo2_proofs::pairing::bn256::Fr as Fp;
-    use rusty_fork::rusty_fork_test;
     use std::fs;
+    use wasmi::ImportsBuilder;
 
     pub(crate) fn prepare_inputs() -> (Vec<u64>, Vec<u64>) {
         let msg = "abcdef";
@@ -30,24 +34,124 @@ pub(crate) mod tests {
         (public_inputs, private_inputs)
     }
 
+    #[test]
+    fn test_keccak() {
+        let (public_inputs, private_inputs) = prepare_inputs();
+
+        let wasm = fs::read("wasm/keccak.wasm").unwrap();
+
+        let mut env = HostEnv::new();
+        register_wasm_input_foreign(&mut env, public_inputs.clone(), private_inputs.clone());
+        env.finalize();
+
+        test_circuit_with_env_and_layout(
+            env,
+            wasm,
+            "keccak_digest",
+            public_inputs.into_iter().map(|v| Fp::from(v)).collect(),
+            CircuitLayout::new(19),
+        )
+        .unwrap();
+    }
+
     /*
-     *  FORK the test since it modifies global variable zkwasm_k.
+     * Keccak-256 of a message shorter than the rate (136 bytes), the single
+     * block is permuted by the keccak plugin.
      */
-    rusty_fork_test! {
-        #[test]
-        fn test_keccak() {
-            set_zkwasm_k(19);
+    const KECCAK_HELPER_WAT: &str = r#"
+        (module
+          (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+          (import "env" "zkwasm_keccak_f_input" (func $keccak_f_input (param i32 i64)))
+          (import "env" "zkwasm_keccak_f_output" (func $keccak_f_output (param i32) (result i64)))
+          (memory 1)
+          (func (export "keccak_digest")
+            (local $len i32) (local $i i32) (local $lane i64)
+            (local.set $len (i32.wrap_i64 (call $wasm_input (i32.const 0))))
+            (block $done
+              (loop $read
+                (br_if $done (i32.ge_u (local.get $i) (local.get $len)))
+                (i32.store8 (local.get $i) (i32.wrap_i64 (call $wasm_input (i32.const 0))))
+                (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                (br $read)))
+            (i32.store8 (local.get $len) (i32.xor (i32.load8_u (local.get $len)) (i32.const 0x01)))
+            (i32.store8 (i32.const 135) (i32.xor (i32.load8_u (i32.const 135)) (i32.const 0x80)))
+            (local.set $i (i32.const 0))
+            (loop $absorb
+              (call $keccak_f_input (local.get $i) (i64.load (i32.shl (local.get $i) (i32.const 3))))
+              (local.set $i (i32.add (local.get $i) (i32.const 1)))
+              (br_if $absorb (i32.lt_u (local.get $i) (i32.const 25))))
+            (local.set $i (i32.const 0))
+            (loop $squeeze
+              (if (i32.eqz (i32.and (local.get $i) (i32.const 7)))
+                (then (local.set $lane (call $keccak_f_output (i32.shr_u (local.get $i) (i32.const 3))))))
+              (if (i64.ne
+                    (i64.and (local.get $lane) (i64.const 0xff))
+                    (call $wasm_input (i32.const 1)))
+                (then unreachable))
+              (local.set $lane (i64.shr_u (local.get $lane) (i64.const 8)))
+              (local.set $i (i32.add (local.get $i) (i32.const 1)))
+              (br_if $squeeze (i32.lt_u (local.get $i) (i32.const 32))))))
+    "#;
+
+    pub(crate) fn etable_rows(mut env: HostEnv, wasm: &Vec<u8>, function_name: &str) -> usize {
+        let module = wasmi::Module::from_buffer(wasm).expect("failed to load wasm");
+        let imports = ImportsBuilder::new().with_resolver("env", &env);
+
+        let compiled_module = WasmInterpreter::new()
+            .compile(
+                wasm,
+                &module,
+                &imports,
+                &env.function_description_table(),
+                function_name,
+            )
+            .unwrap();
+
+        compiled_module
+            .run(&mut env)
+            .unwrap()
+            .tables
+            .execution_tables
+            .etable
+            .entries()
+            .len()
+    }
+
+    #[test]
+    fn test_keccak_helper() {
+        let (public_inputs, private_inputs) = prepare_inputs();
 
-            let (public_inputs, private_inputs) = prepare_inputs();
+        let wasm = wabt::wat2wasm(KECCAK_HELPER_WAT).expect("failed to parse wat");
 
-            let wasm = fs::read("wasm/keccak.wasm").unwrap();
+        let mut env = HostEnv::new();
+        register_keccak_foreign(&mut env);
+        register_wasm_input_foreign(&mut env, public_inputs.clone(), private_inputs.clone());
+        env.finalize();
 
-            let mut env = HostEnv::new();
-            register_wasm_input_foreign(&mut env, public_inputs.clone(), private_inputs.clone());
-            env.finalize();
+        let execution_result = test_circuit_with_env(
+            env,
+            wasm,
+            "keccak_digest",
+            public_inputs.iter().map(|v| Fp::from(*v)).collect(),
+        )
+        .unwrap();
+        let helper_rows = execution_result
+            .tables
+            .execution_tables
+            .etable
+            .entries()
+            .len();
 
+        let mut env = HostEnv::new();
+        register_wasm_input_foreign(&mut env, public_inputs, private_inputs);
+        env.finalize();
+        let pure_rows = etable_rows(env, &fs::read("wasm/keccak.wasm").unwrap(), "keccak_digest");
 
-            test_circuit_with_env(env, wasm, "keccak_digest", public_inputs.into_iter().map(|v| Fp::from(v)).collect()).unwrap();
-        }
+        assert!(
+            helper_rows * 10 < pure_rows,
+            "the keccak plugin takes {} etable rows, the pure wasm version {}",
+            helper_rows,
+            pure_rows
+        );
     }
 }
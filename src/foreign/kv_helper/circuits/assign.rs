@@ -0,0 +1,216 @@
+use super::{KvHelperEncode, KvHelperTableConfig, BLOCK_LINES};
+use crate::circuits::utils::{bn_to_field, Context};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Cell, Layouter},
+    plonk::{Advice, Column, Error},
+};
+use num_bigint::BigUint;
+use specs::{
+    kvtable::{KvAccess, KvTable, KV_TREE_DEPTH},
+    poseidon::PoseidonSpec,
+};
+
+pub struct KvHelperTableChip<F: FieldExt> {
+    pub(crate) config: KvHelperTableConfig<F>,
+    max_available_rows: usize,
+    spec: PoseidonSpec<F>,
+}
+
+impl<F: FieldExt> KvHelperTableChip<F> {
+    pub fn new(config: KvHelperTableConfig<F>, max_available_rows: usize) -> Self {
+        Self {
+            config,
+            max_available_rows,
+            spec: PoseidonSpec::new(),
+        }
+    }
+
+    fn assign_advice(
+        &self,
+        ctx: &mut Context<'_, F>,
+        offset: usize,
+        col: Column<Advice>,
+        value: F,
+    ) -> Result<Cell, Error> {
+        let cell = ctx
+            .region
+            .assign_advice(|| "kv helper advice", col, offset, || Ok(value))?;
+
+        Ok(cell.cell())
+    }
+
+    /*
+     * Assigns an access, a disabled block hashes a path of zeros and keeps the
+     * root. Returns the cells of the root before and after the block.
+     */
+    fn assign_access(
+        &self,
+        ctx: &mut Context<'_, F>,
+        block: usize,
+        access: Option<&KvAccess>,
+        root: &mut F,
+    ) -> Result<(Cell, Cell), Error> {
+        let offset = block * BLOCK_LINES;
+
+        ctx.region.assign_fixed(
+            || "kv helper block first line sel",
+            self.config.block_first_line_sel,
+            offset,
+            || Ok(F::one()),
+        )?;
+        ctx.region.assign_fixed(
+            || "kv helper index prefix",
+            self.config.index_prefix,
+            offset,
+            || {
+                Ok(bn_to_field(&KvHelperEncode::encode_call(
+                    BigUint::from(0u64),
+                    BigUint::from(block),
+                    BigUint::from(0u64),
+                    BigUint::from(0u64),
+                )))
+            },
+        )?;
+        for height in 0..KV_TREE_DEPTH {
+            ctx.region.assign_fixed(
+                || "kv helper level sel",
+                self.config.level_sel,
+                offset + height,
+                || Ok(F::one()),
+            )?;
+        }
+
+        let (is_set, key, old_value, new_value, siblings) = match access {
+            Some(access) => (
+                access.is_set,
+                access.key,
+                access.old_value,
+                access.new_value,
+                access.siblings.iter().map(bn_to_field).collect(),
+            ),
+            None => (false, 0, 0, 0, vec![F::zero(); KV_TREE_DEPTH]),
+        };
+
+        self.assign_advice(
+            ctx,
+            offset,
+            self.config.enable,
+            F::from(access.is_some() as u64),
+        )?;
+        self.assign_advice(ctx, offset, self.config.is_set, F::from(is_set as u64))?;
+        self.assign_advice(ctx, offset, self.config.key, F::from(key))?;
+
+        // The nodes on the path and the children of their parents.
+        let mut paths = vec![];
+        for (col, value) in [(self.config.old, old_value), (self.config.new, new_value)] {
+            let mut node = F::from(value);
+            let mut children = vec![];
+
+            self.assign_advice(ctx, offset, col, node)?;
+
+            for height in 0..KV_TREE_DEPTH {
+                let (left, right) = if (key >> height) & 1 == 1 {
+                    (siblings[height], node)
+                } else {
+                    (node, siblings[height])
+                };
+
+                node = self.spec.hash(&[left, right]);
+
+                let parent_cell = self.assign_advice(ctx, offset + height + 1, col, node)?;
+                children.push((left, right, parent_cell));
+            }
+
+            paths.push((node, children));
+        }
+
+        for height in 0..KV_TREE_DEPTH {
+            self.assign_advice(
+                ctx,
+                offset + height,
+                self.config.bit,
+                F::from((key >> height) & 1),
+            )?;
+            self.assign_advice(ctx, offset + height, self.config.sibling, siblings[height])?;
+        }
+
+        /*
+         * The parents are hashed along the block, each hash is bound to the
+         * children on the line of the node and to the parent on the next line.
+         */
+        ctx.offset = offset;
+        for ((_, children), (left_col, right_col)) in paths.iter().zip([
+            (self.config.old_left, self.config.old_right),
+            (self.config.new_left, self.config.new_right),
+        ]) {
+            for (height, (left, right, parent_cell)) in children.iter().enumerate() {
+                let left_cell = self.assign_advice(ctx, offset + height, left_col, *left)?;
+                let right_cell = self.assign_advice(ctx, offset + height, right_col, *right)?;
+
+                let (cells, digest) = self.config.poseidon.assign(ctx, &[*left, *right])?;
+
+                ctx.region.constrain_equal(cells[0], left_cell)?;
+                ctx.region.constrain_equal(cells[1], right_cell)?;
+                ctx.region.constrain_equal(digest, *parent_cell)?;
+            }
+        }
+        assert_eq!(ctx.offset, offset + BLOCK_LINES);
+
+        let root_in = *root;
+        if access.is_some() {
+            assert_eq!(paths[0].0, root_in);
+
+            *root = paths[1].0;
+        }
+
+        Ok((
+            self.assign_advice(ctx, offset, self.config.root_in, root_in)?,
+            self.assign_advice(ctx, offset, self.config.root_out, *root)?,
+        ))
+    }
+
+    pub fn assign(&self, layouter: &mut impl Layouter<F>, kvtable: &KvTable) -> Result<(), Error> {
+        let max_blocks = self.max_available_rows / BLOCK_LINES;
+
+        assert!(kvtable.accesses.len() <= max_blocks);
+
+        let (old_root, new_root) = layouter.assign_region(
+            || "kv helper assign",
+            |region| {
+                let mut ctx = Context::new(region);
+                let mut root = bn_to_field(&kvtable.old_root);
+
+                // Without a block, the root is kept by a single cell.
+                if max_blocks == 0 {
+                    let cell = self.assign_advice(&mut ctx, 0, self.config.root_in, root)?;
+
+                    return Ok((cell, cell));
+                }
+
+                let mut roots: Vec<(Cell, Cell)> = vec![];
+                for block in 0..max_blocks {
+                    let (root_in, root_out) = self.assign_access(
+                        &mut ctx,
+                        block,
+                        kvtable.accesses.get(block),
+                        &mut root,
+                    )?;
+
+                    if let Some((_, last_root_out)) = roots.last() {
+                        ctx.region.constrain_equal(*last_root_out, root_in)?;
+                    }
+
+                    roots.push((root_in, root_out));
+                }
+
+                Ok((roots.first().unwrap().0, roots.last().unwrap().1))
+            },
+        )?;
+
+        layouter.constrain_instance(old_root, self.config.roots, 0)?;
+        layouter.constrain_instance(new_root, self.config.roots, 1)?;
+
+        Ok(())
+    }
+}
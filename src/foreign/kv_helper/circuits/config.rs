@@ -0,0 +1,65 @@
+use super::KvHelperTableConfig;
+use crate::{constant_from, curr, fixed_curr, nextn};
+use halo2_proofs::{arithmetic::FieldExt, plonk::ConstraintSystem};
+use specs::kvtable::KV_TREE_DEPTH;
+
+impl<F: FieldExt> KvHelperTableConfig<F> {
+    pub fn _configure(&self, meta: &mut ConstraintSystem<F>) {
+        /*
+         * The children of the parent of a node are the node and its sibling,
+         * swapped if the bit of the key is set. The parents are bound to the
+         * nodes of the next line by copy constraints.
+         */
+        meta.create_gate("kv helper level", |meta| {
+            let bit = curr!(meta, self.bit);
+            let sibling = curr!(meta, self.sibling);
+            let mut constraints = vec![bit.clone() * (constant_from!(1) - bit.clone())];
+
+            for (node, left, right) in [
+                (self.old, self.old_left, self.old_right),
+                (self.new, self.new_left, self.new_right),
+            ] {
+                let node = curr!(meta, node);
+
+                constraints.push(
+                    curr!(meta, left)
+                        - node.clone()
+                        - bit.clone() * (sibling.clone() - node.clone()),
+                );
+                constraints.push(
+                    curr!(meta, right) - sibling.clone() - bit.clone() * (node - sibling.clone()),
+                );
+            }
+
+            constraints
+                .into_iter()
+                .map(|x| x * fixed_curr!(meta, self.level_sel))
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("kv helper access", |meta| {
+            let enable = curr!(meta, self.enable);
+            let is_set = curr!(meta, self.is_set);
+            let root_in = curr!(meta, self.root_in);
+
+            let key = (0..KV_TREE_DEPTH).fold(constant_from!(0), |acc, height| {
+                acc + nextn!(meta, self.bit, height as i32) * constant_from!(1u64 << height)
+            });
+
+            vec![
+                enable.clone() * (constant_from!(1) - enable.clone()),
+                is_set.clone() * (constant_from!(1) - is_set.clone()),
+                curr!(meta, self.key) - key,
+                // A get leaves the leaf unchanged.
+                (constant_from!(1) - is_set) * (curr!(meta, self.new) - curr!(meta, self.old)),
+                enable.clone() * (nextn!(meta, self.old, KV_TREE_DEPTH as i32) - root_in.clone()),
+                curr!(meta, self.root_out)
+                    - root_in.clone()
+                    - enable * (nextn!(meta, self.new, KV_TREE_DEPTH as i32) - root_in),
+            ]
+            .into_iter()
+            .map(|x| x * fixed_curr!(meta, self.block_first_line_sel))
+            .collect::<Vec<_>>()
+        });
+    }
+}
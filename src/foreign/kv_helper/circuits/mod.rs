@@ -0,0 +1,158 @@
+use super::KvHelperOp;
+use crate::{
+    circuits::utils::poseidon::{PoseidonConfig, POSEIDON_BLOCK_ROWS},
+    constant_from, curr, fixed_curr,
+    foreign::ForeignTableConfig,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Advice, Column, ConstraintSystem, Expression, Fixed, Instance, VirtualCells},
+};
+use num_bigint::BigUint;
+use specs::{encode::FromBn, kvtable::KV_TREE_DEPTH};
+use std::marker::PhantomData;
+
+pub mod assign;
+pub mod config;
+
+/*
+ * An access takes a block of lines: the line h < KV_TREE_DEPTH holds the node
+ * of height h on the path of the key before and after the access, its sibling
+ * and the children of its parent, the line KV_TREE_DEPTH holds the roots. The
+ * parents are hashed by PoseidonConfig along the block, the old path first.
+ */
+pub(crate) const BLOCK_LINES: usize = 2 * KV_TREE_DEPTH * HASH_ROWS;
+
+// The rows of PoseidonConfig hashing two nodes, see PoseidonConfig::rows.
+pub(crate) const HASH_ROWS: usize = 1 + POSEIDON_BLOCK_ROWS;
+
+const OP_SHIFT: usize = 192;
+const INDEX_SHIFT: usize = 128;
+const KEY_SHIFT: usize = 64;
+
+pub struct KvHelperEncode();
+
+impl KvHelperEncode {
+    pub(super) fn encode_call<T: FromBn>(op: T, index: T, key: T, value: T) -> T {
+        op * T::from_bn(&(BigUint::from(1u64) << OP_SHIFT))
+            + index * T::from_bn(&(BigUint::from(1u64) << INDEX_SHIFT))
+            + key * T::from_bn(&(BigUint::from(1u64) << KEY_SHIFT))
+            + value
+    }
+}
+
+/*
+ * The roots are chained from the old root instance through the blocks to the
+ * new root instance, a disabled block keeps the root.
+ */
+#[derive(Clone)]
+pub struct KvHelperTableConfig<F: FieldExt> {
+    block_first_line_sel: Column<Fixed>,
+    // Enabled on the lines of the path below the root.
+    level_sel: Column<Fixed>,
+    // The index of the block shifted to its place in the encoding of a call.
+    index_prefix: Column<Fixed>,
+
+    enable: Column<Advice>,
+    is_set: Column<Advice>,
+    key: Column<Advice>,
+    bit: Column<Advice>,
+    sibling: Column<Advice>,
+    old: Column<Advice>,
+    new: Column<Advice>,
+    old_left: Column<Advice>,
+    old_right: Column<Advice>,
+    new_left: Column<Advice>,
+    new_right: Column<Advice>,
+    root_in: Column<Advice>,
+    root_out: Column<Advice>,
+
+    // The old root then the new root.
+    roots: Column<Instance>,
+    poseidon: PoseidonConfig<F>,
+    mark: PhantomData<F>,
+}
+
+impl<F: FieldExt> KvHelperTableConfig<F> {
+    fn new(meta: &mut ConstraintSystem<F>) -> Self {
+        let mut equality_column = || {
+            let col = meta.advice_column();
+            meta.enable_equality(col);
+            col
+        };
+
+        let old = equality_column();
+        let new = equality_column();
+        let old_left = equality_column();
+        let old_right = equality_column();
+        let new_left = equality_column();
+        let new_right = equality_column();
+        let root_in = equality_column();
+        let root_out = equality_column();
+
+        let roots = meta.instance_column();
+        meta.enable_equality(roots);
+
+        Self {
+            block_first_line_sel: meta.fixed_column(),
+            level_sel: meta.fixed_column(),
+            index_prefix: meta.fixed_column(),
+            enable: meta.advice_column(),
+            is_set: meta.advice_column(),
+            key: meta.advice_column(),
+            bit: meta.advice_column(),
+            sibling: meta.advice_column(),
+            old,
+            new,
+            old_left,
+            old_right,
+            new_left,
+            new_right,
+            root_in,
+            root_out,
+            roots,
+            poseidon: PoseidonConfig::configure(meta),
+            mark: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        let config = Self::new(meta);
+        config._configure(meta);
+        config
+    }
+
+    // The value read by a get or written by a set.
+    fn value(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        curr!(meta, self.old)
+            + curr!(meta, self.is_set) * (curr!(meta, self.new) - curr!(meta, self.old))
+    }
+}
+
+impl<F: FieldExt> ForeignTableConfig<F> for KvHelperTableConfig<F> {
+    fn configure_in_table(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        key: &'static str,
+        expr: &dyn Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+    ) {
+        meta.lookup_any(key, |meta| {
+            let op = constant_from!(KvHelperOp::Get)
+                + curr!(meta, self.is_set)
+                    * constant_from!(KvHelperOp::Set as u64 - KvHelperOp::Get as u64);
+
+            vec![(
+                expr(meta),
+                fixed_curr!(meta, self.block_first_line_sel)
+                    * curr!(meta, self.enable)
+                    * (fixed_curr!(meta, self.index_prefix)
+                        + KvHelperEncode::encode_call(
+                            op,
+                            constant_from!(0),
+                            curr!(meta, self.key),
+                            self.value(meta),
+                        )),
+            )]
+        });
+    }
+}
@@ -0,0 +1,245 @@
+use super::{circuits::KvHelperEncode, KvHelperOp, KV_FOREIGN_TABLE_KEY};
+use crate::{
+    circuits::{
+        etable_compact::{
+            op_configure::{
+                BitCell, ConstraintBuilder, EventTableCellAllocator, EventTableOpcodeConfig,
+                MTableLookupCell, U64Cell,
+            },
+            EventTableCommonConfig, MLookupItem, StepStatus,
+        },
+        mtable_compact::encode::MemoryTableLookupEncode,
+        utils::{bn_to_field, Context},
+    },
+    constant_from, constant_from_bn,
+    foreign::{EventTableForeignCallConfigBuilder, ForeignCallInfo},
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Error, Expression, VirtualCells},
+};
+use num_bigint::BigUint;
+use specs::step::StepInfo;
+use specs::{
+    etable::EventTableEntry,
+    itable::{OpcodeClass, OPCODE_CLASS_SHIFT},
+};
+use specs::{host_function::HostPlugin, mtable::VarType};
+
+pub struct ETableKvHelperTableConfig {
+    foreign_call_id: u64,
+
+    index: U64Cell,
+    key: U64Cell,
+    value: U64Cell,
+    is_set: BitCell,
+
+    lookup_stack_read_index: MTableLookupCell,
+    lookup_stack_read_key: MTableLookupCell,
+    lookup_stack_value: MTableLookupCell,
+}
+
+pub struct KvForeignCallInfo {}
+impl ForeignCallInfo for KvForeignCallInfo {
+    fn call_id(&self) -> usize {
+        OpcodeClass::ForeignPluginStart as usize + HostPlugin::Kv.id()
+    }
+}
+pub struct ETableKvHelperTableConfigBuilder {}
+
+impl<F: FieldExt> EventTableForeignCallConfigBuilder<F> for ETableKvHelperTableConfigBuilder {
+    fn configure(
+        common: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+        info: &impl ForeignCallInfo,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let index = common.alloc_u64();
+        let key = common.alloc_u64();
+        let value = common.alloc_u64();
+
+        let is_set = common.alloc_bit_value();
+
+        let lookup_stack_read_index = common.alloc_mtable_lookup();
+        let lookup_stack_read_key = common.alloc_mtable_lookup();
+        let lookup_stack_value = common.alloc_mtable_lookup();
+
+        constraint_builder.lookup(
+            KV_FOREIGN_TABLE_KEY,
+            "kv helper table lookup",
+            Box::new(move |meta| {
+                let op = constant_from!(KvHelperOp::Get)
+                    + is_set.expr(meta)
+                        * constant_from!(KvHelperOp::Set as u64 - KvHelperOp::Get as u64);
+
+                KvHelperEncode::encode_call(op, index.expr(meta), key.expr(meta), value.expr(meta))
+            }),
+        );
+
+        Box::new(ETableKvHelperTableConfig {
+            foreign_call_id: info.call_id() as u64,
+            index,
+            key,
+            value,
+            is_set,
+            lookup_stack_read_index,
+            lookup_stack_read_key,
+            lookup_stack_value,
+        })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for ETableKvHelperTableConfig {
+    fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        let pick_one = constant_from!(KvHelperOp::Get)
+            + self.is_set.expr(meta)
+                * constant_from!(KvHelperOp::Set as u64 - KvHelperOp::Get as u64);
+
+        constant_from_bn!(&(BigUint::from(self.foreign_call_id) << OPCODE_CLASS_SHIFT)) + pick_one
+    }
+
+    fn mops(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(3))
+    }
+
+    fn assigned_extra_mops(
+        &self,
+        _ctx: &mut Context<'_, F>,
+        _step: &StepStatus,
+        _entry: &EventTableEntry,
+    ) -> u64 {
+        3
+    }
+
+    /*
+     * Get pops the index and the key and pushes the value, set pops the
+     * index, the key and the value.
+     */
+    fn mtable_lookup(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        item: MLookupItem,
+        common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        match item {
+            MLookupItem::First => Some(MemoryTableLookupEncode::encode_stack_read(
+                common_config.eid(meta),
+                constant_from!(1),
+                common_config.sp(meta) + constant_from!(2) + self.is_set.expr(meta),
+                constant_from!(VarType::I32),
+                self.index.expr(meta),
+            )),
+            MLookupItem::Second => Some(MemoryTableLookupEncode::encode_stack_read(
+                common_config.eid(meta),
+                constant_from!(2),
+                common_config.sp(meta) + constant_from!(1) + self.is_set.expr(meta),
+                constant_from!(VarType::I64),
+                self.key.expr(meta),
+            )),
+            MLookupItem::Third => Some(
+                self.is_set.expr(meta)
+                    * MemoryTableLookupEncode::encode_stack_read(
+                        common_config.eid(meta),
+                        constant_from!(3),
+                        common_config.sp(meta) + constant_from!(1),
+                        constant_from!(VarType::I64),
+                        self.value.expr(meta),
+                    )
+                    + (constant_from!(1) - self.is_set.expr(meta))
+                        * MemoryTableLookupEncode::encode_stack_write(
+                            common_config.eid(meta),
+                            constant_from!(3),
+                            common_config.sp(meta) + constant_from!(2),
+                            constant_from!(VarType::I64),
+                            self.value.expr(meta),
+                        ),
+            ),
+            _ => None,
+        }
+    }
+
+    fn sp_diff(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(1) + constant_from!(2) * self.is_set.expr(meta))
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step_info: &StepStatus,
+        entry: &EventTableEntry,
+    ) -> Result<(), Error> {
+        match &entry.step_info {
+            StepInfo::CallHost {
+                plugin,
+                function_name,
+                args,
+                ret_val,
+                ..
+            } => {
+                assert_eq!(*plugin, HostPlugin::Kv);
+
+                let index = args[0];
+                let key = args[1];
+
+                self.index.assign(ctx, index)?;
+                self.lookup_stack_read_index.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_stack_read(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(1u64),
+                        BigUint::from(step_info.current.sp + args.len() as u32),
+                        BigUint::from(VarType::I32 as u64),
+                        BigUint::from(index),
+                    ),
+                )?;
+
+                self.key.assign(ctx, key)?;
+                self.lookup_stack_read_key.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_stack_read(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(2u64),
+                        BigUint::from(step_info.current.sp + args.len() as u32 - 1),
+                        BigUint::from(VarType::I64 as u64),
+                        BigUint::from(key),
+                    ),
+                )?;
+
+                match KvHelperOp::from(function_name) {
+                    KvHelperOp::Get => {
+                        let value = ret_val.unwrap();
+
+                        self.value.assign(ctx, value)?;
+                        self.lookup_stack_value.assign(
+                            ctx,
+                            &MemoryTableLookupEncode::encode_stack_write(
+                                BigUint::from(step_info.current.eid),
+                                BigUint::from(3u64),
+                                BigUint::from(step_info.current.sp + 2),
+                                BigUint::from(VarType::I64 as u64),
+                                BigUint::from(value),
+                            ),
+                        )?;
+                    }
+                    KvHelperOp::Set => {
+                        let value = args[2];
+
+                        self.is_set.assign(ctx, true)?;
+                        self.value.assign(ctx, value)?;
+                        self.lookup_stack_value.assign(
+                            ctx,
+                            &MemoryTableLookupEncode::encode_stack_read(
+                                BigUint::from(step_info.current.eid),
+                                BigUint::from(3u64),
+                                BigUint::from(step_info.current.sp + 1),
+                                BigUint::from(VarType::I64 as u64),
+                                BigUint::from(value),
+                            ),
+                        )?;
+                    }
+                }
+            }
+            _ => unreachable!(),
+        };
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+use crate::circuits::utils::bn_to_field;
+use halo2_proofs::arithmetic::FieldExt;
+use specs::{
+    kvtable::{KvTable, KV_TREE_DEPTH},
+    poseidon::PoseidonSpec,
+};
+use std::collections::HashMap;
+use strum_macros::EnumIter;
+
+pub mod circuits;
+pub mod etable_op_configure;
+pub mod runtime;
+pub mod test;
+
+pub const KV_FOREIGN_TABLE_KEY: &'static str = "kv-helper-table";
+pub const KV_FOREIGN_FUNCTION_NAME_GET: &'static str = "zkwasm_kv_get";
+pub const KV_FOREIGN_FUNCTION_NAME_SET: &'static str = "zkwasm_kv_set";
+
+/*
+ * The first argument of every call is its index in the sequence of kv calls,
+ * followed by the key and, for set, the value.
+ */
+#[derive(Clone, Copy, Debug, EnumIter, PartialEq)]
+pub enum KvHelperOp {
+    Get = 1,
+    Set = 2,
+}
+
+impl From<&String> for KvHelperOp {
+    fn from(function_name: &String) -> Self {
+        match function_name.as_str() {
+            KV_FOREIGN_FUNCTION_NAME_GET => KvHelperOp::Get,
+            KV_FOREIGN_FUNCTION_NAME_SET => KvHelperOp::Set,
+            _ => unreachable!(),
+        }
+    }
+}
+
+// The instance of the old root and the new root of the kv table.
+pub fn kv_roots<F: FieldExt>(kvtable: &KvTable) -> Vec<F> {
+    vec![
+        bn_to_field(&kvtable.old_root),
+        bn_to_field(&kvtable.new_root),
+    ]
+}
+
+/*
+ * The parent of a node on the path of a key, the bit of the key at the height
+ * of the node tells whether the node is the right child.
+ */
+pub(crate) fn kv_parent<F: FieldExt>(spec: &PoseidonSpec<F>, bit: bool, node: F, sibling: F) -> F {
+    if bit {
+        spec.hash(&[sibling, node])
+    } else {
+        spec.hash(&[node, sibling])
+    }
+}
+
+/*
+ * The sparse Merkle tree of the key-value store: the leaf of a key holds its
+ * value and a node is the hash of its children. Only the nodes differing from
+ * the tree of zeros are kept.
+ */
+pub struct KvTree<F: FieldExt> {
+    spec: PoseidonSpec<F>,
+    // The node of each height in the tree of zeros.
+    defaults: Vec<F>,
+    // The nodes by height and position, the position of a node is key >> height.
+    nodes: HashMap<(usize, u64), F>,
+    values: HashMap<u64, u64>,
+}
+
+impl<F: FieldExt> KvTree<F> {
+    pub fn new() -> Self {
+        let spec = PoseidonSpec::new();
+        let mut defaults = vec![F::zero()];
+
+        for height in 0..KV_TREE_DEPTH {
+            defaults.push(spec.hash(&[defaults[height], defaults[height]]));
+        }
+
+        KvTree {
+            spec,
+            defaults,
+            nodes: HashMap::new(),
+            values: HashMap::new(),
+        }
+    }
+
+    fn node(&self, height: usize, position: u64) -> F {
+        self.nodes
+            .get(&(height, position))
+            .cloned()
+            .unwrap_or(self.defaults[height])
+    }
+
+    pub fn root(&self) -> F {
+        self.node(KV_TREE_DEPTH, 0)
+    }
+
+    pub fn get(&self, key: u64) -> u64 {
+        self.values.get(&key).cloned().unwrap_or(0)
+    }
+
+    // The siblings of the path of the key from the leaf up.
+    pub fn siblings(&self, key: u64) -> Vec<F> {
+        (0..KV_TREE_DEPTH)
+            .map(|height| self.node(height, (key >> height) ^ 1))
+            .collect()
+    }
+
+    pub fn set(&mut self, key: u64, value: u64) {
+        let mut node = F::from(value);
+
+        self.values.insert(key, value);
+        self.nodes.insert((0, key), node);
+
+        for height in 0..KV_TREE_DEPTH {
+            let position = key >> height;
+
+            node = kv_parent(
+                &self.spec,
+                position & 1 == 1,
+                node,
+                self.node(height, position ^ 1),
+            );
+            self.nodes.insert((height + 1, position >> 1), node);
+        }
+    }
+}
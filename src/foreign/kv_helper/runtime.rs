@@ -0,0 +1,142 @@
+use std::rc::Rc;
+
+use halo2_proofs::pairing::bn256::Fr;
+use specs::{
+    host_function::HostPlugin,
+    kvtable::{KvAccess, KvTable},
+    types::ValueType,
+};
+use wasmi::{RuntimeArgs, RuntimeValue};
+
+use crate::{
+    circuits::utils::field_to_bn,
+    runtime::host::{host_env::HostEnv, ForeignContext},
+};
+
+use super::{KvHelperOp, KvTree, KV_FOREIGN_FUNCTION_NAME_GET, KV_FOREIGN_FUNCTION_NAME_SET};
+
+struct Context {
+    tree: KvTree<Fr>,
+    // The number of kv calls, the index expected from the next call.
+    calls: u32,
+    table: KvTable,
+}
+
+impl Context {
+    fn new(tree: KvTree<Fr>) -> Self {
+        let root = field_to_bn(&tree.root());
+
+        Context {
+            tree,
+            calls: 0,
+            table: KvTable {
+                old_root: root.clone(),
+                new_root: root,
+                accesses: vec![],
+            },
+        }
+    }
+
+    fn check_index(&mut self, args: &RuntimeArgs) {
+        let index: u32 = args.nth(0);
+
+        assert_eq!(index, self.calls, "kv calls must be indexed in order");
+
+        self.calls += 1;
+    }
+
+    // Records the access along with the path of the key in the tree before it.
+    fn access(&mut self, is_set: bool, key: u64, new_value: u64) {
+        let siblings = self.tree.siblings(key).iter().map(field_to_bn).collect();
+        let old_value = self.tree.get(key);
+
+        if is_set {
+            self.tree.set(key, new_value);
+        }
+
+        self.table.new_root = field_to_bn(&self.tree.root());
+        self.table.accesses.push(KvAccess {
+            is_set,
+            key,
+            old_value,
+            new_value,
+            siblings,
+        });
+    }
+}
+
+impl ForeignContext for Context {}
+
+fn get(context: &mut Context, args: RuntimeArgs) -> Option<RuntimeValue> {
+    context.check_index(&args);
+
+    let key: u64 = args.nth(1);
+    let value = context.tree.get(key);
+
+    context.access(false, key, value);
+
+    Some(RuntimeValue::I64(value as i64))
+}
+
+fn set(context: &mut Context, args: RuntimeArgs) -> Option<RuntimeValue> {
+    context.check_index(&args);
+
+    let key: u64 = args.nth(1);
+    let value: u64 = args.nth(2);
+
+    context.access(true, key, value);
+
+    None
+}
+
+/*
+ * The kv calls are served from the tree, the tree after the execution is
+ * committed by the new root of the kv table.
+ */
+pub fn register_kv_foreign(env: &mut HostEnv, tree: KvTree<Fr>) {
+    env.internal_env
+        .register_plugin(HostPlugin::Kv, Box::new(Context::new(tree)));
+
+    env.internal_env.register_function(
+        KV_FOREIGN_FUNCTION_NAME_GET,
+        specs::host_function::Signature {
+            params: vec![ValueType::I32, ValueType::I64],
+            return_type: Some(specs::types::ValueType::I64),
+        },
+        HostPlugin::Kv,
+        KvHelperOp::Get as usize,
+        Rc::new(|context: &mut dyn ForeignContext, args: RuntimeArgs| {
+            get(context.downcast_mut::<Context>().unwrap(), args)
+        }),
+    );
+
+    env.internal_env.register_function(
+        KV_FOREIGN_FUNCTION_NAME_SET,
+        specs::host_function::Signature {
+            params: vec![ValueType::I32, ValueType::I64, ValueType::I64],
+            return_type: None,
+        },
+        HostPlugin::Kv,
+        KvHelperOp::Set as usize,
+        Rc::new(|context: &mut dyn ForeignContext, args: RuntimeArgs| {
+            set(context.downcast_mut::<Context>().unwrap(), args)
+        }),
+    );
+}
+
+/*
+ * The kv table of the execution run with the env, None if the env serves no
+ * kv calls.
+ */
+pub fn kv_table(env: &HostEnv) -> Option<KvTable> {
+    env.internal_env
+        .plugin_context(HostPlugin::Kv)
+        .map(|context| {
+            context
+                .borrow()
+                .downcast_ref::<Context>()
+                .unwrap()
+                .table
+                .clone()
+        })
+}
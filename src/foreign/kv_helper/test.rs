@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        circuits::utils::field_to_bn,
+        foreign::{
+            kv_helper::{kv_parent, runtime::register_kv_foreign, KvTree},
+            wasm_input_helper::runtime::register_wasm_input_foreign,
+        },
+        runtime::host::host_env::HostEnv,
+        test::test_circuit_with_env,
+    };
+
+    use halo2_proofs::pairing::bn256::Fr as Fp;
+    use specs::poseidon::PoseidonSpec;
+
+    /*
+     * Sets the key 1 to the first public input and reads it back, then checks
+     * the key 5 of the initial store against the second public input.
+     */
+    const KV_HELPER_WAT: &str = r#"
+        (module
+          (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+          (import "env" "zkwasm_kv_get" (func $kv_get (param i32 i64) (result i64)))
+          (import "env" "zkwasm_kv_set" (func $kv_set (param i32 i64 i64)))
+          (func (export "kv")
+            (local $value i64)
+            (local.set $value (call $wasm_input (i32.const 1)))
+            (call $kv_set (i32.const 0) (i64.const 1) (local.get $value))
+            (if (i64.ne (call $kv_get (i32.const 1) (i64.const 1)) (local.get $value))
+              (then unreachable))
+            (if (i64.ne (call $kv_get (i32.const 2) (i64.const 5)) (call $wasm_input (i32.const 1)))
+              (then unreachable))))
+    "#;
+
+    #[test]
+    fn test_kv_tree() {
+        let spec = PoseidonSpec::<Fp>::new();
+        let mut tree = KvTree::<Fp>::new();
+        let empty_root = tree.root();
+        let key = (1 << 40) + 3;
+
+        tree.set(key, 42);
+        assert_eq!(tree.get(key), 42);
+        assert_eq!(tree.get(key + 1), 0);
+
+        let root = tree
+            .siblings(key)
+            .iter()
+            .enumerate()
+            .fold(Fp::from(42), |node, (height, sibling)| {
+                kv_parent(&spec, (key >> height) & 1 == 1, node, *sibling)
+            });
+        assert_eq!(root, tree.root());
+
+        tree.set(key, 0);
+        assert_eq!(tree.root(), empty_root);
+    }
+
+    #[test]
+    fn test_kv_helper() {
+        let public_inputs = vec![0xdead_beef_0000_0001, 42];
+
+        let mut tree = KvTree::new();
+        tree.set(5, 42);
+
+        let mut expected = KvTree::<Fp>::new();
+        expected.set(5, 42);
+        expected.set(1, public_inputs[0]);
+
+        let wasm = wabt::wat2wasm(KV_HELPER_WAT).expect("failed to parse wat");
+
+        let mut env = HostEnv::new();
+        register_kv_foreign(&mut env, tree);
+        register_wasm_input_foreign(&mut env, public_inputs.clone(), vec![]);
+        env.finalize();
+
+        let execution_result = test_circuit_with_env(
+            env,
+            wasm,
+            "kv",
+            public_inputs.into_iter().map(|v| Fp::from(v)).collect(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            execution_result.tables.execution_tables.kvtable.new_root,
+            field_to_bn(&expected.root())
+        );
+    }
+}
@@ -8,8 +8,14 @@ use crate::circuits::etable_compact::op_configure::{
 };
 
 pub mod keccak_helper;
-pub mod sha256_helper;
+pub mod kv_helper;
+pub mod poseidon_helper;
+pub mod registry;
 pub mod require_helper;
+pub mod secp256k1_helper;
+pub mod sha256_compress_helper;
+pub mod sha256_helper;
+pub mod u256_helper;
 pub mod wasm_input_helper;
 
 pub trait ForeignCallInfo {
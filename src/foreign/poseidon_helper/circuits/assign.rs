@@ -0,0 +1,375 @@
+use super::{
+    PoseidonHelperTableConfig, ABSORB_LINE, BLOCK_LINES, FINALIZE_SLOT_START, LIMB_BYTES, NEW_SLOT,
+    PUSH_SLOT_START, W_START,
+};
+use crate::circuits::utils::Context;
+use crate::foreign::poseidon_helper::{
+    chunk_from_limbs, field_to_limbs, PoseidonHelperOp, POSEIDON_CHUNK_LIMBS, POSEIDON_LIMBS,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::Layouter,
+    plonk::{Column, Error, Fixed},
+};
+use specs::{
+    etable::EventTableEntry,
+    host_function::HostPlugin,
+    poseidon::{PoseidonSpec, POSEIDON_RATE, POSEIDON_T},
+    step::StepInfo,
+};
+
+pub struct PoseidonHelperTableChip<F: FieldExt> {
+    pub(crate) config: PoseidonHelperTableConfig<F>,
+    max_available_rows: usize,
+}
+
+/*
+ * The calls absorbed by a block, new is false if the block continues the hash
+ * of the previous block.
+ */
+pub(crate) struct PoseidonBlock {
+    new: bool,
+    pushes: Vec<u64>,
+    finalized: usize,
+}
+
+pub(crate) fn poseidon_blocks(entries: &Vec<EventTableEntry>) -> Vec<PoseidonBlock> {
+    let mut blocks: Vec<PoseidonBlock> = vec![];
+
+    for entry in entries {
+        if let StepInfo::CallHost {
+            plugin,
+            function_name,
+            args,
+            ..
+        } = &entry.step_info
+        {
+            assert_eq!(*plugin, HostPlugin::Poseidon);
+
+            match PoseidonHelperOp::from(function_name) {
+                PoseidonHelperOp::New => blocks.push(PoseidonBlock {
+                    new: true,
+                    pushes: vec![],
+                    finalized: 0,
+                }),
+                PoseidonHelperOp::Push => {
+                    if blocks.last().unwrap().pushes.len() == POSEIDON_CHUNK_LIMBS {
+                        blocks.push(PoseidonBlock {
+                            new: false,
+                            pushes: vec![],
+                            finalized: 0,
+                        });
+                    }
+
+                    blocks.last_mut().unwrap().pushes.push(args[1]);
+                }
+                PoseidonHelperOp::Finalize => {
+                    let last = blocks.last().unwrap();
+
+                    // The padding of a full chunk is absorbed by the next block.
+                    if last.finalized == 0 && last.pushes.len() == POSEIDON_CHUNK_LIMBS {
+                        blocks.push(PoseidonBlock {
+                            new: false,
+                            pushes: vec![],
+                            finalized: 0,
+                        });
+                    }
+
+                    blocks.last_mut().unwrap().finalized += 1
+                }
+            }
+        } else {
+            unreachable!()
+        }
+    }
+
+    blocks
+}
+
+impl<F: FieldExt> PoseidonHelperTableChip<F> {
+    pub fn new(config: PoseidonHelperTableConfig<F>, max_available_rows: usize) -> Self {
+        Self {
+            config,
+            max_available_rows,
+        }
+    }
+
+    fn assign_fixed(&self, ctx: &mut Context<'_, F>, block: usize) -> Result<(), Error> {
+        let modulus = field_to_limbs(&-F::one());
+
+        for line in 0..BLOCK_LINES {
+            let offset = block * BLOCK_LINES + line;
+            let mut enable = |name: &'static str, col: Column<Fixed>, value: F| {
+                ctx.region.assign_fixed(|| name, col, offset, || Ok(value))
+            };
+
+            enable("poseidon helper sel", self.config.sel, F::one())?;
+
+            if line == NEW_SLOT {
+                enable(
+                    "poseidon helper block first line sel",
+                    self.config.block_first_line_sel,
+                    F::one(),
+                )?;
+
+                if block == 0 {
+                    enable(
+                        "poseidon helper first block sel",
+                        self.config.first_block_sel,
+                        F::one(),
+                    )?;
+                }
+            }
+
+            let op = if line == NEW_SLOT {
+                Some(PoseidonHelperOp::New)
+            } else if line < FINALIZE_SLOT_START {
+                Some(PoseidonHelperOp::Push)
+            } else if line < W_START {
+                Some(PoseidonHelperOp::Finalize)
+            } else {
+                None
+            };
+
+            if let Some(op) = op {
+                enable("poseidon helper slot sel", self.config.slot_sel, F::one())?;
+                enable(
+                    "poseidon helper slot op",
+                    self.config.slot_op,
+                    F::from(op as u64),
+                )?;
+            }
+
+            if line == ABSORB_LINE {
+                enable("poseidon helper chunk sel", self.config.chunk_sel, F::one())?;
+            }
+
+            if op == Some(PoseidonHelperOp::Push) {
+                enable("poseidon helper push sel", self.config.push_sel, F::one())?;
+            }
+
+            if (line >= PUSH_SLOT_START && line < FINALIZE_SLOT_START - 1)
+                || (line >= FINALIZE_SLOT_START && line < W_START - 1)
+            {
+                enable(
+                    "poseidon helper prefix sel",
+                    self.config.prefix_sel,
+                    F::one(),
+                )?;
+            }
+
+            if op == Some(PoseidonHelperOp::Finalize) {
+                enable(
+                    "poseidon helper finalize sel",
+                    self.config.finalize_sel,
+                    F::one(),
+                )?;
+                enable(
+                    "poseidon helper modulus limb",
+                    self.config.modulus_limb,
+                    F::from(modulus[line - FINALIZE_SLOT_START]),
+                )?;
+            }
+
+            if line == FINALIZE_SLOT_START {
+                enable(
+                    "poseidon helper finalize first sel",
+                    self.config.finalize_first_sel,
+                    F::one(),
+                )?;
+            }
+
+            if line == W_START {
+                enable(
+                    "poseidon helper carry end sel",
+                    self.config.carry_end_sel,
+                    F::one(),
+                )?;
+            }
+
+            if line >= FINALIZE_SLOT_START && line < W_START + POSEIDON_LIMBS {
+                enable("poseidon helper limb sel", self.config.limb_sel, F::one())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn assign_limb_bytes(
+        &self,
+        ctx: &mut Context<'_, F>,
+        offset: usize,
+        limb: u64,
+    ) -> Result<(), Error> {
+        for k in 0..LIMB_BYTES {
+            ctx.region.assign_advice(
+                || "poseidon helper limb bytes",
+                self.config.limb_bytes[k].0,
+                offset,
+                || Ok(F::from((limb >> (8 * k)) & 0xff)),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn assign_block(
+        &self,
+        ctx: &mut Context<'_, F>,
+        block: usize,
+        calls: &PoseidonBlock,
+        idx: &mut u64,
+        state: &mut [F; POSEIDON_T],
+    ) -> Result<(), Error> {
+        let offset = block * BLOCK_LINES;
+
+        if calls.new {
+            *state = PoseidonSpec::initial_state();
+        }
+
+        for i in 0..POSEIDON_T {
+            ctx.region.assign_advice(
+                || "poseidon helper init",
+                self.config.poseidon.state[i],
+                offset,
+                || Ok(state[i]),
+            )?;
+        }
+
+        let chunk = chunk_from_limbs::<F>(&calls.pushes);
+        let finalized = calls.finalized > 0;
+        let elements = if finalized {
+            calls.pushes.len() / POSEIDON_LIMBS
+        } else {
+            POSEIDON_RATE
+        };
+
+        ctx.offset = offset + ABSORB_LINE;
+        self.config
+            .poseidon
+            .assign_block(ctx, state, &chunk[..elements], finalized)?;
+
+        /*
+         * The digest limbs are assigned on the finalize slots of every block,
+         * they are only looked up from the slots used.
+         */
+        let digest = field_to_limbs(&state[1]);
+        let modulus = field_to_limbs(&-F::one());
+        let mut w = [0u64; POSEIDON_LIMBS];
+        // The carries of digest + w are the borrows of p - 1 - digest.
+        let mut carry = [0u64; POSEIDON_LIMBS + 1];
+
+        for j in 0..POSEIDON_LIMBS {
+            let (diff, b0) = modulus[j].overflowing_sub(digest[j]);
+            let (diff, b1) = diff.overflowing_sub(carry[j]);
+
+            w[j] = diff;
+            carry[j + 1] = (b0 || b1) as u64;
+        }
+        assert_eq!(carry[POSEIDON_LIMBS], 0);
+
+        for line in 0..BLOCK_LINES {
+            let (used, value) = if line == NEW_SLOT {
+                (calls.new, 0)
+            } else if line < FINALIZE_SLOT_START {
+                let push = line - PUSH_SLOT_START;
+
+                (
+                    push < calls.pushes.len(),
+                    *calls.pushes.get(push).unwrap_or(&0),
+                )
+            } else if line < W_START {
+                let limb = line - FINALIZE_SLOT_START;
+
+                (limb < calls.finalized, digest[limb])
+            } else {
+                (false, 0)
+            };
+
+            ctx.region.assign_advice(
+                || "poseidon helper used",
+                self.config.used.0,
+                offset + line,
+                || Ok(F::from(used as u64)),
+            )?;
+            ctx.region.assign_advice(
+                || "poseidon helper idx",
+                self.config.idx,
+                offset + line,
+                || Ok(F::from(*idx)),
+            )?;
+            ctx.region.assign_advice(
+                || "poseidon helper value",
+                self.config.value,
+                offset + line,
+                || Ok(F::from(value)),
+            )?;
+            ctx.region.assign_advice(
+                || "poseidon helper carry",
+                self.config.carry,
+                offset + line,
+                || {
+                    Ok(if line >= FINALIZE_SLOT_START && line <= W_START {
+                        F::from(carry[line - FINALIZE_SLOT_START])
+                    } else {
+                        F::zero()
+                    })
+                },
+            )?;
+
+            *idx += used as u64;
+        }
+
+        for j in 0..POSEIDON_LIMBS {
+            self.assign_limb_bytes(ctx, offset + FINALIZE_SLOT_START + j, digest[j])?;
+            self.assign_limb_bytes(ctx, offset + W_START + j, w[j])?;
+        }
+
+        Ok(())
+    }
+
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        entries: &Vec<EventTableEntry>,
+    ) -> Result<(), Error> {
+        let blocks = poseidon_blocks(entries);
+        let max_blocks = self.max_available_rows / BLOCK_LINES;
+
+        assert!(blocks.len() <= max_blocks);
+
+        layouter.assign_region(
+            || "poseidon helper assign",
+            |region| {
+                let mut ctx = Context::new(region);
+                let mut idx = 0;
+                let mut state = PoseidonSpec::initial_state();
+
+                /*
+                 * The fixed columns of PoseidonConfig are assigned with its
+                 * blocks, the blocks after the calls hash nothing.
+                 */
+                let padding = PoseidonBlock {
+                    new: true,
+                    pushes: vec![],
+                    finalized: 0,
+                };
+
+                for block in 0..max_blocks {
+                    self.assign_fixed(&mut ctx, block)?;
+                    self.assign_block(
+                        &mut ctx,
+                        block,
+                        blocks.get(block).unwrap_or(&padding),
+                        &mut idx,
+                        &mut state,
+                    )?;
+                }
+
+                Ok(())
+            },
+        )?;
+
+        Ok(())
+    }
+}
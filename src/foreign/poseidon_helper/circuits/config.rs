@@ -0,0 +1,201 @@
+use super::{
+    PoseidonHelperTableConfig, ABSORB_LINE, BLOCK_LINES, FINALIZE_SLOT_START, LIMB_BYTES,
+    OUTPUT_LINE, W_START,
+};
+use crate::circuits::utils::bn_to_field;
+use crate::foreign::poseidon_helper::POSEIDON_LIMBS;
+use crate::{constant, constant_from, constant_from_bn, curr, fixed_curr, next, nextn, prev};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Advice, Column, ConstraintSystem, Expression, VirtualCells},
+};
+use num_bigint::BigUint;
+use specs::poseidon::{PoseidonSpec, POSEIDON_RATE};
+
+fn shift<F: FieldExt>(bits: usize) -> Expression<F> {
+    constant_from_bn!(&(BigUint::from(1u64) << bits))
+}
+
+/*
+ * The little-endian composition of the cells of a column from the rotation start.
+ */
+fn compose<F: FieldExt>(
+    meta: &mut VirtualCells<'_, F>,
+    col: Column<Advice>,
+    start: i32,
+    len: usize,
+    width: usize,
+) -> Expression<F> {
+    (0..len)
+        .map(|i| nextn!(meta, col, start + i as i32) * shift(i * width))
+        .reduce(|acc, expr| acc + expr)
+        .unwrap()
+}
+
+impl<F: FieldExt> PoseidonHelperTableConfig<F> {
+    fn limb_of_bytes(&self, meta: &mut VirtualCells<'_, F>, rotation: i32) -> Expression<F> {
+        (0..LIMB_BYTES)
+            .map(|k| nextn!(meta, self.limb_bytes[k].0, rotation) * shift(k * 8))
+            .reduce(|acc, expr| acc + expr)
+            .unwrap()
+    }
+
+    pub fn _configure(&self, meta: &mut ConstraintSystem<F>) {
+        meta.create_gate("poseidon helper slot", |meta| {
+            vec![
+                fixed_curr!(meta, self.sel)
+                    * (constant_from!(1) - fixed_curr!(meta, self.slot_sel))
+                    * curr!(meta, self.used.0),
+            ]
+        });
+
+        /*
+         * The index of a slot is the number of used slots before it, the first
+         * block starts with a new.
+         */
+        meta.create_gate("poseidon helper idx", |meta| {
+            vec![
+                fixed_curr!(meta, self.sel)
+                    * (constant_from!(1) - fixed_curr!(meta, self.first_block_sel))
+                    * (curr!(meta, self.idx) - prev!(meta, self.idx) - prev!(meta, self.used.0)),
+                fixed_curr!(meta, self.first_block_sel) * curr!(meta, self.idx),
+                fixed_curr!(meta, self.first_block_sel)
+                    * (constant_from!(1) - curr!(meta, self.used.0)),
+            ]
+        });
+
+        meta.create_gate("poseidon helper used slots", |meta| {
+            vec![
+                fixed_curr!(meta, self.push_sel)
+                    * (constant_from!(1) - curr!(meta, self.used.0))
+                    * curr!(meta, self.value),
+                fixed_curr!(meta, self.prefix_sel)
+                    * (constant_from!(1) - curr!(meta, self.used.0))
+                    * next!(meta, self.used.0),
+                fixed_curr!(meta, self.block_first_line_sel) * curr!(meta, self.value),
+            ]
+        });
+
+        /*
+         * A block with a new starts from the initial state of the sponge, a
+         * block without a new continues the previous block, which must have
+         * pushed a full chunk without finalizing.
+         */
+        meta.create_gate("poseidon helper init", |meta| {
+            let is_new = curr!(meta, self.used.0);
+            let mut constraints = vec![];
+
+            for (i, initial) in PoseidonSpec::<F>::initial_state().into_iter().enumerate() {
+                constraints.push(
+                    curr!(meta, self.poseidon.state[i])
+                        - is_new.clone() * constant!(initial)
+                        - (constant_from!(1) - is_new.clone())
+                            * prev!(meta, self.poseidon.state[i]),
+                );
+            }
+
+            constraints.push(
+                (constant_from!(1) - is_new.clone())
+                    * (constant_from!(1)
+                        - nextn!(
+                            meta,
+                            self.used.0,
+                            (FINALIZE_SLOT_START - 1) as i32 - BLOCK_LINES as i32
+                        )),
+            );
+            constraints.push(
+                (constant_from!(1) - is_new)
+                    * nextn!(
+                        meta,
+                        self.used.0,
+                        FINALIZE_SLOT_START as i32 - BLOCK_LINES as i32
+                    ),
+            );
+
+            constraints
+                .into_iter()
+                .map(|x| x * fixed_curr!(meta, self.block_first_line_sel))
+                .collect::<Vec<_>>()
+        });
+
+        /*
+         * The elements are pushed as whole limbs. A finalized block absorbs the
+         * padding one after its last element, so it can't push a full chunk,
+         * which is finalized by a continuation block pushing nothing.
+         */
+        meta.create_gate("poseidon helper chunk", |meta| {
+            let finalized = nextn!(
+                meta,
+                self.used.0,
+                (FINALIZE_SLOT_START - ABSORB_LINE) as i32
+            );
+            let used = |meta: &mut VirtualCells<'_, F>, limb: usize| {
+                nextn!(meta, self.used.0, limb as i32)
+            };
+
+            let mut constraints =
+                vec![finalized.clone() * used(meta, POSEIDON_RATE * POSEIDON_LIMBS - 1)];
+
+            for e in 0..POSEIDON_RATE {
+                let first = used(meta, e * POSEIDON_LIMBS);
+                let prev_last = if e == 0 {
+                    constant_from!(1)
+                } else {
+                    used(meta, e * POSEIDON_LIMBS - 1)
+                };
+                let padding = finalized.clone() * prev_last * (constant_from!(1) - first.clone());
+
+                constraints.push(first - used(meta, (e + 1) * POSEIDON_LIMBS - 1));
+                constraints.push(
+                    curr!(meta, self.poseidon.state[e + 1])
+                        - compose(
+                            meta,
+                            self.value,
+                            (e * POSEIDON_LIMBS) as i32,
+                            POSEIDON_LIMBS,
+                            64,
+                        )
+                        - padding,
+                );
+            }
+
+            constraints
+                .into_iter()
+                .map(|x| x * fixed_curr!(meta, self.chunk_sel))
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("poseidon helper digest", |meta| {
+            vec![
+                fixed_curr!(meta, self.finalize_first_sel)
+                    * (compose(meta, self.value, 0, POSEIDON_LIMBS, 64)
+                        - nextn!(
+                            meta,
+                            self.poseidon.state[1],
+                            (OUTPUT_LINE - FINALIZE_SLOT_START) as i32
+                        )),
+                fixed_curr!(meta, self.finalize_first_sel) * curr!(meta, self.carry),
+            ]
+        });
+
+        /*
+         * digest + w = p - 1 limb by limb, w being the limbs of the bytes on the
+         * rows after the finalize slots, so the digest is canonical.
+         */
+        meta.create_gate("poseidon helper canonical digest", |meta| {
+            let carry = curr!(meta, self.carry);
+            let w = self.limb_of_bytes(meta, (W_START - FINALIZE_SLOT_START) as i32);
+
+            vec![
+                fixed_curr!(meta, self.finalize_sel)
+                    * (curr!(meta, self.value) - self.limb_of_bytes(meta, 0)),
+                fixed_curr!(meta, self.finalize_sel)
+                    * (curr!(meta, self.value) + w + carry.clone()
+                        - fixed_curr!(meta, self.modulus_limb)
+                        - shift(64) * next!(meta, self.carry)),
+                fixed_curr!(meta, self.finalize_sel) * carry.clone() * (constant_from!(1) - carry),
+                fixed_curr!(meta, self.carry_end_sel) * curr!(meta, self.carry),
+            ]
+        });
+    }
+}
@@ -0,0 +1,143 @@
+use super::{POSEIDON_CHUNK_LIMBS, POSEIDON_LIMBS};
+use crate::{
+    circuits::utils::poseidon::{PoseidonConfig, POSEIDON_BLOCK_ROWS},
+    curr, fixed_curr,
+    foreign::ForeignTableConfig,
+    traits::circuits::bit_range_table::{BitColumn, BitRangeTable, U8Column},
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Advice, Column, ConstraintSystem, Expression, Fixed, VirtualCells},
+};
+use num_bigint::BigUint;
+use specs::encode::FromBn;
+use std::marker::PhantomData;
+
+pub mod assign;
+pub mod config;
+
+/*
+ * A block is the init row followed by a block of PoseidonConfig absorbing a
+ * chunk. The rows of a block are also the slots of the calls:
+ *   row 0:            new, the init row
+ *   rows 1 ..= 32:    push, the limb j of the element e at the row 1 + 4e + j
+ *   rows 33 ..= 36:   finalize, the limbs of the digest
+ *   rows 37 ..= 40:   the limbs of p - 1 - digest, proving the digest canonical
+ * A block without new continues the hash of the previous block.
+ */
+pub(crate) const BLOCK_LINES: usize = POSEIDON_BLOCK_ROWS + 1;
+
+pub(crate) const NEW_SLOT: usize = 0;
+pub(crate) const PUSH_SLOT_START: usize = 1;
+pub(crate) const FINALIZE_SLOT_START: usize = PUSH_SLOT_START + POSEIDON_CHUNK_LIMBS;
+pub(crate) const W_START: usize = FINALIZE_SLOT_START + POSEIDON_LIMBS;
+pub(crate) const ABSORB_LINE: usize = 1;
+pub(crate) const OUTPUT_LINE: usize = BLOCK_LINES - 1;
+
+pub(crate) const LIMB_BYTES: usize = 8;
+
+const OP_SHIFT: usize = 96;
+const INDEX_SHIFT: usize = 64;
+
+pub struct PoseidonHelperEncode();
+
+impl PoseidonHelperEncode {
+    pub(super) fn encode_call<T: FromBn>(op: T, index: T, value: T) -> T {
+        op * T::from_bn(&(BigUint::from(1u64) << OP_SHIFT))
+            + index * T::from_bn(&(BigUint::from(1u64) << INDEX_SHIFT))
+            + value
+    }
+}
+
+#[derive(Clone)]
+pub struct PoseidonHelperTableConfig<F: FieldExt> {
+    sel: Column<Fixed>,
+    block_first_line_sel: Column<Fixed>,
+    first_block_sel: Column<Fixed>,
+    slot_sel: Column<Fixed>,
+    // The op of the slot.
+    slot_op: Column<Fixed>,
+    chunk_sel: Column<Fixed>,
+    push_sel: Column<Fixed>,
+    // Enabled on the slots followed by a slot of the same op.
+    prefix_sel: Column<Fixed>,
+    finalize_sel: Column<Fixed>,
+    finalize_first_sel: Column<Fixed>,
+    carry_end_sel: Column<Fixed>,
+    // The limbs of p - 1 on the finalize slots.
+    modulus_limb: Column<Fixed>,
+    // Enabled on the rows holding the bytes of a limb.
+    limb_sel: Column<Fixed>,
+
+    used: BitColumn,
+    idx: Column<Advice>,
+    value: Column<Advice>,
+    carry: Column<Advice>,
+    limb_bytes: [U8Column; LIMB_BYTES],
+
+    poseidon: PoseidonConfig<F>,
+    mark: PhantomData<F>,
+}
+
+impl<F: FieldExt> PoseidonHelperTableConfig<F> {
+    fn new(meta: &mut ConstraintSystem<F>, rtable: &impl BitRangeTable<F>) -> Self {
+        let sel = meta.fixed_column();
+        let limb_sel = meta.fixed_column();
+        let used = rtable.bit_column(meta, "poseidon helper used", |meta| fixed_curr!(meta, sel));
+        let limb_bytes = [0; LIMB_BYTES].map(|_| {
+            rtable.u8_column(meta, "poseidon helper limb bytes", |meta| {
+                fixed_curr!(meta, limb_sel)
+            })
+        });
+
+        Self {
+            sel,
+            block_first_line_sel: meta.fixed_column(),
+            first_block_sel: meta.fixed_column(),
+            slot_sel: meta.fixed_column(),
+            slot_op: meta.fixed_column(),
+            chunk_sel: meta.fixed_column(),
+            push_sel: meta.fixed_column(),
+            prefix_sel: meta.fixed_column(),
+            finalize_sel: meta.fixed_column(),
+            finalize_first_sel: meta.fixed_column(),
+            carry_end_sel: meta.fixed_column(),
+            modulus_limb: meta.fixed_column(),
+            limb_sel,
+            used,
+            idx: meta.advice_column(),
+            value: meta.advice_column(),
+            carry: meta.advice_column(),
+            limb_bytes,
+            poseidon: PoseidonConfig::configure(meta),
+            mark: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, rtable: &impl BitRangeTable<F>) -> Self {
+        let config = Self::new(meta, rtable);
+        config._configure(meta);
+        config
+    }
+}
+
+impl<F: FieldExt> ForeignTableConfig<F> for PoseidonHelperTableConfig<F> {
+    fn configure_in_table(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        key: &'static str,
+        expr: &dyn Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+    ) {
+        meta.lookup_any(key, |meta| {
+            vec![(
+                expr(meta),
+                curr!(meta, self.used.0)
+                    * PoseidonHelperEncode::encode_call(
+                        fixed_curr!(meta, self.slot_op),
+                        curr!(meta, self.idx),
+                        curr!(meta, self.value),
+                    ),
+            )]
+        });
+    }
+}
@@ -0,0 +1,250 @@
+use super::{circuits::PoseidonHelperEncode, PoseidonHelperOp, POSEIDON_FOREIGN_TABLE_KEY};
+use crate::{
+    circuits::{
+        etable_compact::{
+            op_configure::{
+                BitCell, ConstraintBuilder, EventTableCellAllocator, EventTableOpcodeConfig,
+                MTableLookupCell, U64Cell,
+            },
+            EventTableCommonConfig, MLookupItem, StepStatus,
+        },
+        mtable_compact::encode::MemoryTableLookupEncode,
+        utils::{bn_to_field, Context},
+    },
+    constant_from, constant_from_bn,
+    foreign::{EventTableForeignCallConfigBuilder, ForeignCallInfo},
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Error, Expression, VirtualCells},
+};
+use num_bigint::BigUint;
+use specs::step::StepInfo;
+use specs::{
+    etable::EventTableEntry,
+    itable::{OpcodeClass, OPCODE_CLASS_SHIFT},
+};
+use specs::{host_function::HostPlugin, mtable::VarType};
+
+pub struct ETablePoseidonHelperTableConfig {
+    foreign_call_id: u64,
+
+    index: U64Cell,
+    value: U64Cell,
+    is_new: BitCell,
+    is_push: BitCell,
+    is_finalize: BitCell,
+
+    lookup_stack_read_index: MTableLookupCell,
+    lookup_stack_value: MTableLookupCell,
+}
+
+pub struct PoseidonForeignCallInfo {}
+impl ForeignCallInfo for PoseidonForeignCallInfo {
+    fn call_id(&self) -> usize {
+        OpcodeClass::ForeignPluginStart as usize + HostPlugin::Poseidon.id()
+    }
+}
+pub struct ETablePoseidonHelperTableConfigBuilder {}
+
+impl<F: FieldExt> EventTableForeignCallConfigBuilder<F> for ETablePoseidonHelperTableConfigBuilder {
+    fn configure(
+        common: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+        info: &impl ForeignCallInfo,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let index = common.alloc_u64();
+        let value = common.alloc_u64();
+
+        let is_new = common.alloc_bit_value();
+        let is_push = common.alloc_bit_value();
+        let is_finalize = common.alloc_bit_value();
+
+        let lookup_stack_read_index = common.alloc_mtable_lookup();
+        let lookup_stack_value = common.alloc_mtable_lookup();
+
+        constraint_builder.push(
+            "poseidonhelper: is one of ops",
+            Box::new(move |meta| {
+                vec![
+                    is_new.expr(meta) + is_push.expr(meta) + is_finalize.expr(meta)
+                        - constant_from!(1),
+                ]
+            }),
+        );
+
+        constraint_builder.lookup(
+            POSEIDON_FOREIGN_TABLE_KEY,
+            "poseidon helper table lookup",
+            Box::new(move |meta| {
+                let op = is_new.expr(meta) * constant_from!(PoseidonHelperOp::New)
+                    + is_push.expr(meta) * constant_from!(PoseidonHelperOp::Push)
+                    + is_finalize.expr(meta) * constant_from!(PoseidonHelperOp::Finalize);
+
+                PoseidonHelperEncode::encode_call(op, index.expr(meta), value.expr(meta))
+            }),
+        );
+
+        Box::new(ETablePoseidonHelperTableConfig {
+            foreign_call_id: info.call_id() as u64,
+            index,
+            value,
+            is_new,
+            is_push,
+            is_finalize,
+            lookup_stack_read_index,
+            lookup_stack_value,
+        })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for ETablePoseidonHelperTableConfig {
+    fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        let pick_one = self.is_new.expr(meta) * constant_from!(PoseidonHelperOp::New)
+            + self.is_push.expr(meta) * constant_from!(PoseidonHelperOp::Push)
+            + self.is_finalize.expr(meta) * constant_from!(PoseidonHelperOp::Finalize);
+
+        constant_from_bn!(&(BigUint::from(self.foreign_call_id) << OPCODE_CLASS_SHIFT)) + pick_one
+    }
+
+    fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(1) + self.is_push.expr(meta) + self.is_finalize.expr(meta))
+    }
+
+    fn assigned_extra_mops(
+        &self,
+        _ctx: &mut Context<'_, F>,
+        _step: &StepStatus,
+        entry: &EventTableEntry,
+    ) -> u64 {
+        match &entry.step_info {
+            StepInfo::CallHost { function_name, .. } => {
+                if PoseidonHelperOp::from(function_name) == PoseidonHelperOp::New {
+                    1
+                } else {
+                    2
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /*
+     * New pops the index, push pops the index and the value, finalize pops the
+     * index and pushes a limb of the digest.
+     */
+    fn mtable_lookup(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        item: MLookupItem,
+        common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        match item {
+            MLookupItem::First => Some(MemoryTableLookupEncode::encode_stack_read(
+                common_config.eid(meta),
+                constant_from!(1),
+                common_config.sp(meta) + constant_from!(1) + self.is_push.expr(meta),
+                constant_from!(VarType::I32),
+                self.index.expr(meta),
+            )),
+            MLookupItem::Second => Some(
+                self.is_push.expr(meta)
+                    * MemoryTableLookupEncode::encode_stack_read(
+                        common_config.eid(meta),
+                        constant_from!(2),
+                        common_config.sp(meta) + constant_from!(1),
+                        constant_from!(VarType::I64),
+                        self.value.expr(meta),
+                    )
+                    + self.is_finalize.expr(meta)
+                        * MemoryTableLookupEncode::encode_stack_write(
+                            common_config.eid(meta),
+                            constant_from!(2),
+                            common_config.sp(meta) + constant_from!(1),
+                            constant_from!(VarType::I64),
+                            self.value.expr(meta),
+                        ),
+            ),
+            _ => None,
+        }
+    }
+
+    fn sp_diff(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(self.is_new.expr(meta) + constant_from!(2) * self.is_push.expr(meta))
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step_info: &StepStatus,
+        entry: &EventTableEntry,
+    ) -> Result<(), Error> {
+        match &entry.step_info {
+            StepInfo::CallHost {
+                plugin,
+                function_name,
+                args,
+                ret_val,
+                ..
+            } => {
+                assert_eq!(*plugin, HostPlugin::Poseidon);
+
+                let index = args[0];
+
+                self.index.assign(ctx, index)?;
+                self.lookup_stack_read_index.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_stack_read(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(1u64),
+                        BigUint::from(step_info.current.sp + args.len() as u32),
+                        BigUint::from(VarType::I32 as u64),
+                        BigUint::from(index),
+                    ),
+                )?;
+
+                let op = PoseidonHelperOp::from(function_name);
+
+                match op {
+                    PoseidonHelperOp::New => self.is_new.assign(ctx, true)?,
+                    PoseidonHelperOp::Push => self.is_push.assign(ctx, true)?,
+                    PoseidonHelperOp::Finalize => self.is_finalize.assign(ctx, true)?,
+                }
+
+                if op == PoseidonHelperOp::New {
+                    self.value.assign(ctx, 0)?;
+                } else if op == PoseidonHelperOp::Finalize {
+                    let value = ret_val.unwrap();
+
+                    self.value.assign(ctx, value)?;
+                    self.lookup_stack_value.assign(
+                        ctx,
+                        &MemoryTableLookupEncode::encode_stack_write(
+                            BigUint::from(step_info.current.eid),
+                            BigUint::from(2u64),
+                            BigUint::from(step_info.current.sp + 1),
+                            BigUint::from(VarType::I64 as u64),
+                            BigUint::from(value),
+                        ),
+                    )?;
+                } else {
+                    let value = args[1];
+
+                    self.value.assign(ctx, value)?;
+                    self.lookup_stack_value.assign(
+                        ctx,
+                        &MemoryTableLookupEncode::encode_stack_read(
+                            BigUint::from(step_info.current.eid),
+                            BigUint::from(2u64),
+                            BigUint::from(step_info.current.sp + 1),
+                            BigUint::from(VarType::I64 as u64),
+                            BigUint::from(value),
+                        ),
+                    )?;
+                }
+            }
+            _ => unreachable!(),
+        };
+        Ok(())
+    }
+}
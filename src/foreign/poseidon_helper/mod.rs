@@ -0,0 +1,76 @@
+use crate::circuits::utils::{bn_to_field, field_to_bn};
+use halo2_proofs::arithmetic::FieldExt;
+use num_bigint::BigUint;
+use specs::poseidon::POSEIDON_RATE;
+use strum_macros::EnumIter;
+
+pub mod circuits;
+pub mod etable_op_configure;
+pub mod runtime;
+pub mod test;
+
+pub const POSEIDON_FOREIGN_TABLE_KEY: &'static str = "poseidon-helper-table";
+pub const POSEIDON_FOREIGN_FUNCTION_NAME_NEW: &'static str = "zkwasm_poseidon_new";
+pub const POSEIDON_FOREIGN_FUNCTION_NAME_PUSH: &'static str = "zkwasm_poseidon_push";
+pub const POSEIDON_FOREIGN_FUNCTION_NAME_FINALIZE: &'static str = "zkwasm_poseidon_finalize";
+
+// A field element is pushed and squeezed as 4 little-endian u64 limbs.
+pub const POSEIDON_LIMBS: usize = 4;
+pub(crate) const POSEIDON_CHUNK_LIMBS: usize = POSEIDON_RATE * POSEIDON_LIMBS;
+
+/*
+ * A hash is a call to new, the limbs of the elements pushed in order and up to
+ * 4 calls to finalize returning the limbs of the digest, which is the digest of
+ * PoseidonSpec::hash over the elements. The first argument of every call is its
+ * index in the sequence of poseidon calls, starting from 0.
+ */
+#[derive(Clone, Copy, EnumIter, PartialEq)]
+pub enum PoseidonHelperOp {
+    New = 1,
+    Push = 2,
+    Finalize = 3,
+}
+
+impl From<&String> for PoseidonHelperOp {
+    fn from(function_name: &String) -> Self {
+        match function_name.as_str() {
+            POSEIDON_FOREIGN_FUNCTION_NAME_NEW => PoseidonHelperOp::New,
+            POSEIDON_FOREIGN_FUNCTION_NAME_PUSH => PoseidonHelperOp::Push,
+            POSEIDON_FOREIGN_FUNCTION_NAME_FINALIZE => PoseidonHelperOp::Finalize,
+            _ => unreachable!(),
+        }
+    }
+}
+
+pub(crate) fn limbs_to_field<F: FieldExt>(limbs: &[u64]) -> F {
+    limbs.iter().enumerate().fold(F::zero(), |acc, (i, limb)| {
+        acc + F::from(*limb) * bn_to_field::<F>(&(BigUint::from(1u64) << (64 * i)))
+    })
+}
+
+pub(crate) fn field_to_limbs<F: FieldExt>(value: &F) -> [u64; POSEIDON_LIMBS] {
+    let mut limbs = [0; POSEIDON_LIMBS];
+
+    for (i, limb) in field_to_bn(value).to_u64_digits().into_iter().enumerate() {
+        limbs[i] = limb;
+    }
+
+    limbs
+}
+
+/*
+ * The field elements of a chunk from its pushed limbs, the missing limbs are 0.
+ */
+pub(crate) fn chunk_from_limbs<F: FieldExt>(limbs: &[u64]) -> [F; POSEIDON_RATE] {
+    assert!(limbs.len() <= POSEIDON_CHUNK_LIMBS);
+
+    let mut padded = limbs.to_vec();
+    padded.resize(POSEIDON_CHUNK_LIMBS, 0);
+
+    let mut chunk = [F::zero(); POSEIDON_RATE];
+    for (i, element) in padded.chunks(POSEIDON_LIMBS).enumerate() {
+        chunk[i] = limbs_to_field(element);
+    }
+
+    chunk
+}
@@ -0,0 +1,172 @@
+use std::rc::Rc;
+
+use halo2_proofs::pairing::bn256::Fr;
+use specs::{
+    host_function::HostPlugin,
+    poseidon::{PoseidonSpec, POSEIDON_T},
+    types::ValueType,
+};
+use wasmi::{RuntimeArgs, RuntimeValue};
+
+use crate::runtime::host::{host_env::HostEnv, ForeignContext};
+
+use super::{
+    chunk_from_limbs, field_to_limbs, PoseidonHelperOp, POSEIDON_CHUNK_LIMBS,
+    POSEIDON_FOREIGN_FUNCTION_NAME_FINALIZE, POSEIDON_FOREIGN_FUNCTION_NAME_NEW,
+    POSEIDON_FOREIGN_FUNCTION_NAME_PUSH, POSEIDON_LIMBS,
+};
+
+struct Context {
+    spec: PoseidonSpec<Fr>,
+    // The number of poseidon calls, the index expected from the next call.
+    calls: u32,
+    state: [Fr; POSEIDON_T],
+    // The limbs pushed since the last permutation.
+    pending: Vec<u64>,
+    digest: Option<[u64; POSEIDON_LIMBS]>,
+    squeezed: usize,
+}
+
+impl Context {
+    fn new() -> Self {
+        Context {
+            spec: PoseidonSpec::new(),
+            calls: 0,
+            state: PoseidonSpec::initial_state(),
+            pending: vec![],
+            digest: None,
+            squeezed: 0,
+        }
+    }
+
+    fn check_index(&mut self, args: &RuntimeArgs) {
+        let index: u32 = args.nth(0);
+
+        assert_eq!(index, self.calls, "poseidon calls must be indexed in order");
+
+        self.calls += 1;
+    }
+
+    fn absorb(&mut self) {
+        self.spec
+            .absorb(&mut self.state, &chunk_from_limbs::<Fr>(&self.pending));
+        self.pending.clear();
+    }
+
+    /*
+     * Absorbs the pushed elements followed by the padding one.
+     */
+    fn absorb_last(&mut self) {
+        assert!(
+            self.pending.len() % POSEIDON_LIMBS == 0,
+            "poseidon elements are pushed as {} limbs",
+            POSEIDON_LIMBS
+        );
+
+        let elements = self.pending.len() / POSEIDON_LIMBS;
+        let mut chunk = chunk_from_limbs::<Fr>(&self.pending)[..elements].to_vec();
+        chunk.push(Fr::from(1u64));
+
+        self.spec.absorb(&mut self.state, &chunk);
+        self.pending.clear();
+    }
+}
+
+impl ForeignContext for Context {}
+
+fn new(context: &mut Context, args: RuntimeArgs) -> Option<RuntimeValue> {
+    context.check_index(&args);
+
+    context.state = PoseidonSpec::initial_state();
+    context.pending.clear();
+    context.digest = None;
+    context.squeezed = 0;
+
+    None
+}
+
+fn push(context: &mut Context, args: RuntimeArgs) -> Option<RuntimeValue> {
+    context.check_index(&args);
+
+    assert!(
+        context.digest.is_none(),
+        "poseidon push after finalize requires a new hash"
+    );
+
+    if context.pending.len() == POSEIDON_CHUNK_LIMBS {
+        context.absorb();
+    }
+
+    context.pending.push(args.nth(1));
+
+    None
+}
+
+fn finalize(context: &mut Context, args: RuntimeArgs) -> Option<RuntimeValue> {
+    context.check_index(&args);
+
+    if context.digest.is_none() {
+        // A full chunk is absorbed before the chunk of the padding.
+        if context.pending.len() == POSEIDON_CHUNK_LIMBS {
+            context.absorb();
+        }
+
+        context.absorb_last();
+        context.digest = Some(field_to_limbs(&context.state[1]));
+    }
+
+    assert!(
+        context.squeezed < POSEIDON_LIMBS,
+        "poseidon digest has {} limbs",
+        POSEIDON_LIMBS
+    );
+
+    let res = context.digest.unwrap()[context.squeezed];
+    context.squeezed += 1;
+
+    Some(RuntimeValue::I64(res as i64))
+}
+
+pub fn register_poseidon_foreign(env: &mut HostEnv) {
+    env.internal_env
+        .register_plugin(HostPlugin::Poseidon, Box::new(Context::new()));
+
+    env.internal_env.register_function(
+        POSEIDON_FOREIGN_FUNCTION_NAME_NEW,
+        specs::host_function::Signature {
+            params: vec![ValueType::I32],
+            return_type: None,
+        },
+        HostPlugin::Poseidon,
+        PoseidonHelperOp::New as usize,
+        Rc::new(|context: &mut dyn ForeignContext, args: RuntimeArgs| {
+            new(context.downcast_mut::<Context>().unwrap(), args)
+        }),
+    );
+
+    env.internal_env.register_function(
+        POSEIDON_FOREIGN_FUNCTION_NAME_PUSH,
+        specs::host_function::Signature {
+            params: vec![ValueType::I32, ValueType::I64],
+            return_type: None,
+        },
+        HostPlugin::Poseidon,
+        PoseidonHelperOp::Push as usize,
+        Rc::new(|context: &mut dyn ForeignContext, args: RuntimeArgs| {
+            push(context.downcast_mut::<Context>().unwrap(), args)
+        }),
+    );
+
+    env.internal_env.register_function(
+        POSEIDON_FOREIGN_FUNCTION_NAME_FINALIZE,
+        specs::host_function::Signature {
+            params: vec![ValueType::I32],
+            return_type: Some(specs::types::ValueType::I64),
+        },
+        HostPlugin::Poseidon,
+        PoseidonHelperOp::Finalize as usize,
+        Rc::new(|context: &mut dyn ForeignContext, args: RuntimeArgs| {
+            finalize(context.downcast_mut::<Context>().unwrap(), args)
+        }),
+    );
+}
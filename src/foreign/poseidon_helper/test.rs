@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        foreign::{
+            poseidon_helper::{field_to_limbs, runtime::register_poseidon_foreign},
+            wasm_input_helper::runtime::register_wasm_input_foreign,
+        },
+        runtime::host::host_env::HostEnv,
+        test::test_circuit_with_env,
+    };
+
+    use halo2_proofs::pairing::bn256::Fr as Fp;
+    use poseidon::Poseidon;
+    use specs::poseidon::{
+        POSEIDON_FULL_ROUNDS, POSEIDON_PARTIAL_ROUNDS, POSEIDON_RATE, POSEIDON_T,
+    };
+
+    /*
+     * Hashes the n field elements given as private inputs, 4 limbs each, and
+     * checks the limbs of the digest against the public inputs.
+     */
+    const POSEIDON_HELPER_WAT: &str = r#"
+        (module
+          (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+          (import "env" "zkwasm_poseidon_new" (func $poseidon_new (param i32)))
+          (import "env" "zkwasm_poseidon_push" (func $poseidon_push (param i32 i64)))
+          (import "env" "zkwasm_poseidon_finalize" (func $poseidon_finalize (param i32) (result i64)))
+          (func (export "poseidon_digest")
+            (local $call i32) (local $n i64) (local $i i64)
+            (local.set $n (call $wasm_input (i32.const 0)))
+            (call $poseidon_new (local.get $call))
+            (local.set $call (i32.add (local.get $call) (i32.const 1)))
+            (block $done
+              (loop $absorb
+                (br_if $done (i64.ge_u (local.get $i) (i64.mul (local.get $n) (i64.const 4))))
+                (call $poseidon_push (local.get $call) (call $wasm_input (i32.const 0)))
+                (local.set $call (i32.add (local.get $call) (i32.const 1)))
+                (local.set $i (i64.add (local.get $i) (i64.const 1)))
+                (br $absorb)))
+            (local.set $i (i64.const 0))
+            (loop $squeeze
+              (if (i64.ne
+                    (call $poseidon_finalize (local.get $call))
+                    (call $wasm_input (i32.const 1)))
+                (then unreachable))
+              (local.set $call (i32.add (local.get $call) (i32.const 1)))
+              (local.set $i (i64.add (local.get $i) (i64.const 1)))
+              (br_if $squeeze (i64.lt_u (local.get $i) (i64.const 4))))))
+    "#;
+
+    fn test_poseidon_helper(n: u64) {
+        let inputs = (1..=n)
+            .map(|i| Fp::from(i) * Fp::from(0x1234_5678_9abc_def0) * Fp::from(u64::MAX))
+            .collect::<Vec<_>>();
+
+        let mut private_inputs = vec![n];
+        for input in inputs.iter() {
+            private_inputs.append(&mut field_to_limbs(input).to_vec());
+        }
+        // The digest of the poseidon crate, which the plugin must agree with.
+        let mut hasher = Poseidon::<Fp, POSEIDON_T, POSEIDON_RATE>::new(
+            POSEIDON_FULL_ROUNDS,
+            POSEIDON_PARTIAL_ROUNDS,
+        );
+        hasher.update(&inputs);
+        let public_inputs = field_to_limbs(&hasher.squeeze()).to_vec();
+
+        let wasm = wabt::wat2wasm(POSEIDON_HELPER_WAT).expect("failed to parse wat");
+
+        let mut env = HostEnv::new();
+        register_poseidon_foreign(&mut env);
+        register_wasm_input_foreign(&mut env, public_inputs.clone(), private_inputs);
+        env.finalize();
+
+        test_circuit_with_env(
+            env,
+            wasm,
+            "poseidon_digest",
+            public_inputs.into_iter().map(|v| Fp::from(v)).collect(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_poseidon_helper_single_chunk() {
+        test_poseidon_helper(3);
+    }
+
+    // The padding after a full chunk is absorbed by a continuation block.
+    #[test]
+    fn test_poseidon_helper_full_chunk() {
+        test_poseidon_helper(8);
+    }
+
+    // The elements after the first chunk are absorbed by a continuation block.
+    #[test]
+    fn test_poseidon_helper_multiple_chunks() {
+        test_poseidon_helper(19);
+    }
+}
@@ -0,0 +1,555 @@
+use super::{EventTableForeignCallConfigBuilder, ForeignCallInfo, ForeignTableConfig};
+use crate::{
+    circuits::{
+        etable_compact::op_configure::{
+            ConstraintBuilder, EventTableCellAllocator, EventTableOpcodeConfig,
+        },
+        rtable::RangeTableConfig,
+        utils::Context,
+    },
+    runtime::host::host_env::HostEnv,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{ConstraintSystem, Error, Expression, VirtualCells},
+};
+use specs::{etable::EventTableEntry, host_function::HostPlugin, itable::OpcodeClass};
+use std::{collections::BTreeMap, rc::Rc, sync::Arc};
+
+/*
+ * A circuit-backed host plugin defined out of the crate. The plugin bundles
+ * the runtime callbacks of its host functions, its helper table, the etable
+ * config of its calls and the assignment of the helper table.
+ */
+pub trait CircuitPlugin<F: FieldExt>: Send + Sync + 'static {
+    type TableConfig: ForeignTableConfig<F> + Clone + 'static;
+    type ETableConfigBuilder: EventTableForeignCallConfigBuilder<F>;
+
+    // The name of the plugin in the registry, the ids are allocated by names.
+    fn name(&self) -> &'static str;
+
+    // The key of the helper table, which the etable config looks up.
+    fn table_key(&self) -> &'static str;
+
+    /*
+     * Registers the context and the host functions of the plugin in the
+     * environment, under the id allocated by the registry.
+     */
+    fn register_runtime(&self, env: &mut HostEnv, plugin: HostPlugin);
+
+    fn configure(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        rtable: &RangeTableConfig<F>,
+    ) -> Self::TableConfig;
+
+    // The rows of the helper table taken by the calls of the plugin.
+    fn rows(&self, entries: &[EventTableEntry]) -> usize;
+
+    fn assign(
+        &self,
+        config: &Self::TableConfig,
+        ctx: &mut Context<'_, F>,
+        max_available_rows: usize,
+        entries: &[EventTableEntry],
+    ) -> Result<(), Error>;
+}
+
+pub struct RegisteredForeignCallInfo {
+    pub plugin: HostPlugin,
+}
+
+impl ForeignCallInfo for RegisteredForeignCallInfo {
+    fn call_id(&self) -> usize {
+        OpcodeClass::ForeignPluginStart as usize + self.plugin.id()
+    }
+}
+
+pub(crate) trait PluginTableConfig<F: FieldExt>: ForeignTableConfig<F> {
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        max_available_rows: usize,
+        entries: &[EventTableEntry],
+    ) -> Result<(), Error>;
+}
+
+impl<F: FieldExt> ForeignTableConfig<F> for Rc<dyn PluginTableConfig<F>> {
+    fn configure_in_table(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        key: &'static str,
+        expr: &dyn Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+    ) {
+        (**self).configure_in_table(meta, key, expr)
+    }
+}
+
+struct PluginTable<F: FieldExt, P: CircuitPlugin<F>> {
+    plugin: Arc<P>,
+    config: P::TableConfig,
+}
+
+impl<F: FieldExt, P: CircuitPlugin<F>> ForeignTableConfig<F> for PluginTable<F, P> {
+    fn configure_in_table(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        key: &'static str,
+        expr: &dyn Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+    ) {
+        self.config.configure_in_table(meta, key, expr)
+    }
+}
+
+impl<F: FieldExt, P: CircuitPlugin<F>> PluginTableConfig<F> for PluginTable<F, P> {
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        max_available_rows: usize,
+        entries: &[EventTableEntry],
+    ) -> Result<(), Error> {
+        self.plugin
+            .assign(&self.config, ctx, max_available_rows, entries)
+    }
+}
+
+// The object safe view of a CircuitPlugin kept in the registry.
+pub(crate) trait RegisteredPlugin<F: FieldExt>: Send + Sync {
+    fn table_key(&self) -> &'static str;
+
+    fn register_runtime(&self, env: &mut HostEnv, plugin: HostPlugin);
+
+    fn configure(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        rtable: &RangeTableConfig<F>,
+    ) -> Rc<dyn PluginTableConfig<F>>;
+
+    fn configure_etable(
+        &self,
+        common: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+        plugin: HostPlugin,
+    ) -> Box<dyn EventTableOpcodeConfig<F>>;
+
+    fn rows(&self, entries: &[EventTableEntry]) -> usize;
+}
+
+impl<F: FieldExt, P: CircuitPlugin<F>> RegisteredPlugin<F> for Arc<P> {
+    fn table_key(&self) -> &'static str {
+        CircuitPlugin::table_key(self.as_ref())
+    }
+
+    fn register_runtime(&self, env: &mut HostEnv, plugin: HostPlugin) {
+        CircuitPlugin::register_runtime(self.as_ref(), env, plugin)
+    }
+
+    fn configure(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        rtable: &RangeTableConfig<F>,
+    ) -> Rc<dyn PluginTableConfig<F>> {
+        Rc::new(PluginTable {
+            plugin: self.clone(),
+            config: CircuitPlugin::configure(self.as_ref(), meta, rtable),
+        })
+    }
+
+    fn configure_etable(
+        &self,
+        common: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+        plugin: HostPlugin,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        <P::ETableConfigBuilder as EventTableForeignCallConfigBuilder<F>>::configure(
+            common,
+            constraint_builder,
+            &RegisteredForeignCallInfo { plugin },
+        )
+    }
+
+    fn rows(&self, entries: &[EventTableEntry]) -> usize {
+        CircuitPlugin::rows(self.as_ref(), entries)
+    }
+}
+
+/*
+ * The registry of the plugins out of the crate. A plugin takes the id
+ * HostPlugin::Registered(i) where i is the rank of its name, so the ids do not
+ * depend on the order of the registrations.
+ */
+pub struct PluginRegistry<F: FieldExt> {
+    plugins: BTreeMap<&'static str, Arc<dyn RegisteredPlugin<F>>>,
+}
+
+impl<F: FieldExt> Default for PluginRegistry<F> {
+    fn default() -> Self {
+        PluginRegistry {
+            plugins: BTreeMap::new(),
+        }
+    }
+}
+
+impl<F: FieldExt> Clone for PluginRegistry<F> {
+    fn clone(&self) -> Self {
+        PluginRegistry {
+            plugins: self.plugins.clone(),
+        }
+    }
+}
+
+impl<F: FieldExt> PluginRegistry<F> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register<P: CircuitPlugin<F>>(&mut self, plugin: P) {
+        let name = plugin.name();
+
+        // The plugin is shared with the helper table it configures.
+        self.plugins
+            .insert(name, Arc::new(Arc::new(plugin)))
+            .map(|_| panic!("conflicting plugin name {}", name));
+    }
+
+    pub(crate) fn plugins(
+        &self,
+    ) -> impl Iterator<Item = (HostPlugin, &Arc<dyn RegisteredPlugin<F>>)> {
+        self.plugins
+            .values()
+            .enumerate()
+            .map(|(index, plugin)| (HostPlugin::Registered(index), plugin))
+    }
+
+    // Registers the runtime of every plugin under its id.
+    pub fn register_runtime(&self, env: &mut HostEnv) {
+        for (id, plugin) in self.plugins() {
+            plugin.register_runtime(env, id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CircuitPlugin, PluginRegistry};
+    use crate::{
+        circuits::{
+            etable_compact::{
+                op_configure::{
+                    ConstraintBuilder, EventTableCellAllocator, EventTableOpcodeConfig,
+                    MTableLookupCell, U64Cell,
+                },
+                EventTableCommonConfig, MLookupItem, StepStatus,
+            },
+            mtable_compact::encode::MemoryTableLookupEncode,
+            rtable::RangeTableConfig,
+            utils::{bn_to_field, Context},
+        },
+        constant_from, constant_from_bn, curr, fixed_curr,
+        foreign::{EventTableForeignCallConfigBuilder, ForeignCallInfo, ForeignTableConfig},
+        runtime::host::{host_env::HostEnv, ForeignContext},
+        test::test_circuit_with_plugins,
+    };
+    use halo2_proofs::{
+        arithmetic::FieldExt,
+        pairing::bn256::Fr as Fp,
+        plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, VirtualCells},
+    };
+    use num_bigint::BigUint;
+    use specs::{
+        etable::EventTableEntry,
+        host_function::{HostPlugin, Signature},
+        itable::OPCODE_CLASS_SHIFT,
+        mtable::VarType,
+        step::StepInfo,
+        types::ValueType,
+    };
+    use std::{marker::PhantomData, rc::Rc};
+
+    const DOUBLE_FOREIGN_TABLE_KEY: &'static str = "double-helper-table";
+
+    // A plugin out of the crate doubling an i64 through a table of (input, output).
+    struct DoublePlugin;
+
+    struct DoubleContext;
+    impl ForeignContext for DoubleContext {}
+
+    fn encode<T: Clone + std::ops::Add<Output = T> + std::ops::Mul<Output = T>>(
+        input: T,
+        output: T,
+        shift: T,
+    ) -> T {
+        input + output * shift
+    }
+
+    #[derive(Clone)]
+    struct DoubleTableConfig<F: FieldExt> {
+        sel: Column<Fixed>,
+        input: Column<Advice>,
+        output: Column<Advice>,
+        _mark: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> ForeignTableConfig<F> for DoubleTableConfig<F> {
+        fn configure_in_table(
+            &self,
+            meta: &mut ConstraintSystem<F>,
+            key: &'static str,
+            expr: &dyn Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+        ) {
+            meta.lookup_any(key, |meta| {
+                vec![(
+                    expr(meta),
+                    fixed_curr!(meta, self.sel)
+                        * encode(
+                            curr!(meta, self.input),
+                            curr!(meta, self.output),
+                            constant_from_bn!(&(BigUint::from(1u64) << 64)),
+                        ),
+                )]
+            });
+        }
+    }
+
+    struct DoubleConfig {
+        foreign_call_id: u64,
+        input: U64Cell,
+        output: U64Cell,
+        lookup_stack_read: MTableLookupCell,
+        lookup_stack_write: MTableLookupCell,
+    }
+
+    struct DoubleConfigBuilder;
+
+    impl<F: FieldExt> EventTableForeignCallConfigBuilder<F> for DoubleConfigBuilder {
+        fn configure(
+            common: &mut EventTableCellAllocator<F>,
+            constraint_builder: &mut ConstraintBuilder<F>,
+            info: &impl ForeignCallInfo,
+        ) -> Box<dyn EventTableOpcodeConfig<F>> {
+            let input = common.alloc_u64();
+            let output = common.alloc_u64();
+            let lookup_stack_read = common.alloc_mtable_lookup();
+            let lookup_stack_write = common.alloc_mtable_lookup();
+
+            constraint_builder.lookup(
+                DOUBLE_FOREIGN_TABLE_KEY,
+                "double helper table lookup",
+                Box::new(move |meta| {
+                    encode(
+                        input.expr(meta),
+                        output.expr(meta),
+                        constant_from_bn!(&(BigUint::from(1u64) << 64)),
+                    )
+                }),
+            );
+
+            Box::new(DoubleConfig {
+                foreign_call_id: info.call_id() as u64,
+                input,
+                output,
+                lookup_stack_read,
+                lookup_stack_write,
+            })
+        }
+    }
+
+    impl<F: FieldExt> EventTableOpcodeConfig<F> for DoubleConfig {
+        fn opcode(&self, _meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+            constant_from_bn!(&(BigUint::from(self.foreign_call_id) << OPCODE_CLASS_SHIFT))
+        }
+
+        fn assign(
+            &self,
+            ctx: &mut Context<'_, F>,
+            step_info: &StepStatus,
+            entry: &EventTableEntry,
+        ) -> Result<(), Error> {
+            match &entry.step_info {
+                StepInfo::CallHost { args, ret_val, .. } => {
+                    self.input.assign(ctx, args[0])?;
+                    self.output.assign(ctx, ret_val.unwrap())?;
+
+                    self.lookup_stack_read.assign(
+                        ctx,
+                        &MemoryTableLookupEncode::encode_stack_read(
+                            BigUint::from(step_info.current.eid),
+                            BigUint::from(1u64),
+                            BigUint::from(step_info.current.sp + 1),
+                            BigUint::from(VarType::I64 as u64),
+                            BigUint::from(args[0]),
+                        ),
+                    )?;
+                    self.lookup_stack_write.assign(
+                        ctx,
+                        &MemoryTableLookupEncode::encode_stack_write(
+                            BigUint::from(step_info.current.eid),
+                            BigUint::from(2u64),
+                            BigUint::from(step_info.current.sp + 1),
+                            BigUint::from(VarType::I64 as u64),
+                            BigUint::from(ret_val.unwrap()),
+                        ),
+                    )?;
+
+                    Ok(())
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        fn mops(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+            Some(constant_from!(2))
+        }
+
+        fn assigned_extra_mops(
+            &self,
+            _ctx: &mut Context<'_, F>,
+            _step: &StepStatus,
+            _entry: &EventTableEntry,
+        ) -> u64 {
+            2
+        }
+
+        fn mtable_lookup(
+            &self,
+            meta: &mut VirtualCells<'_, F>,
+            item: MLookupItem,
+            common_config: &EventTableCommonConfig<F>,
+        ) -> Option<Expression<F>> {
+            match item {
+                MLookupItem::First => Some(MemoryTableLookupEncode::encode_stack_read(
+                    common_config.eid(meta),
+                    constant_from!(1),
+                    common_config.sp(meta) + constant_from!(1),
+                    constant_from!(VarType::I64),
+                    self.input.expr(meta),
+                )),
+                MLookupItem::Second => Some(MemoryTableLookupEncode::encode_stack_write(
+                    common_config.eid(meta),
+                    constant_from!(2),
+                    common_config.sp(meta) + constant_from!(1),
+                    constant_from!(VarType::I64),
+                    self.output.expr(meta),
+                )),
+                _ => None,
+            }
+        }
+    }
+
+    impl<F: FieldExt> CircuitPlugin<F> for DoublePlugin {
+        type TableConfig = DoubleTableConfig<F>;
+        type ETableConfigBuilder = DoubleConfigBuilder;
+
+        fn name(&self) -> &'static str {
+            "double"
+        }
+
+        fn table_key(&self) -> &'static str {
+            DOUBLE_FOREIGN_TABLE_KEY
+        }
+
+        fn register_runtime(&self, env: &mut HostEnv, plugin: HostPlugin) {
+            env.internal_env
+                .register_plugin(plugin, Box::new(DoubleContext));
+            env.internal_env.register_function(
+                "zkwasm_double",
+                Signature {
+                    params: vec![ValueType::I64],
+                    return_type: Some(ValueType::I64),
+                },
+                plugin,
+                0,
+                Rc::new(
+                    |_context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
+                        let input: i64 = args.nth(0);
+
+                        Some(wasmi::RuntimeValue::I64(input.checked_mul(2).unwrap()))
+                    },
+                ),
+            );
+        }
+
+        fn configure(
+            &self,
+            meta: &mut ConstraintSystem<F>,
+            _rtable: &RangeTableConfig<F>,
+        ) -> Self::TableConfig {
+            let config = DoubleTableConfig {
+                sel: meta.fixed_column(),
+                input: meta.advice_column(),
+                output: meta.advice_column(),
+                _mark: PhantomData,
+            };
+
+            meta.create_gate("double helper table", |meta| {
+                vec![
+                    fixed_curr!(meta, config.sel)
+                        * (curr!(meta, config.output)
+                            - curr!(meta, config.input) * constant_from!(2)),
+                ]
+            });
+
+            config
+        }
+
+        fn rows(&self, entries: &[EventTableEntry]) -> usize {
+            entries.len()
+        }
+
+        fn assign(
+            &self,
+            config: &Self::TableConfig,
+            ctx: &mut Context<'_, F>,
+            max_available_rows: usize,
+            entries: &[EventTableEntry],
+        ) -> Result<(), Error> {
+            assert!(entries.len() <= max_available_rows);
+
+            for (offset, entry) in entries.iter().enumerate() {
+                if let StepInfo::CallHost { args, ret_val, .. } = &entry.step_info {
+                    ctx.region.assign_fixed(
+                        || "double helper sel",
+                        config.sel,
+                        offset,
+                        || Ok(F::one()),
+                    )?;
+                    ctx.region.assign_advice(
+                        || "double helper input",
+                        config.input,
+                        offset,
+                        || Ok(bn_to_field(&BigUint::from(args[0]))),
+                    )?;
+                    ctx.region.assign_advice(
+                        || "double helper output",
+                        config.output,
+                        offset,
+                        || Ok(bn_to_field(&BigUint::from(ret_val.unwrap()))),
+                    )?;
+                }
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_registered_plugin() {
+        let textual_repr = r#"
+            (module
+              (import "env" "zkwasm_double" (func $double (param i64) (result i64)))
+              (func (export "test")
+                (if (i64.ne (call $double (i64.const 21)) (i64.const 42))
+                  (then unreachable))))
+        "#;
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+
+        let mut plugins = PluginRegistry::<Fp>::new();
+        plugins.register(DoublePlugin);
+
+        let mut env = HostEnv::new();
+        plugins.register_runtime(&mut env);
+        env.finalize();
+
+        test_circuit_with_plugins(env, plugins, wasm, "test", vec![]).unwrap();
+    }
+}
@@ -28,7 +28,7 @@ use specs::{host_function::HostPlugin, mtable::VarType};
 pub struct RequireForeignCallInfo {}
 impl ForeignCallInfo for RequireForeignCallInfo {
     fn call_id(&self) -> usize {
-        OpcodeClass::ForeignPluginStart as usize + HostPlugin::Require as usize
+        OpcodeClass::ForeignPluginStart as usize + HostPlugin::Require.id()
     }
 }
 
@@ -66,8 +66,9 @@ impl<F: FieldExt> EventTableForeignCallConfigBuilder<F> for ETableRequireHelperT
 impl<F: FieldExt> EventTableOpcodeConfig<F> for ETableRequireHelperTableConfig {
     fn opcode(&self, _meta: &mut VirtualCells<'_, F>) -> Expression<F> {
         constant!(bn_to_field(
-            &(BigUint::from(OpcodeClass::ForeignPluginStart as u64 + HostPlugin::Require as u64)
-                << OPCODE_CLASS_SHIFT)
+            &(BigUint::from(
+                OpcodeClass::ForeignPluginStart as u64 + HostPlugin::Require.id() as u64
+            ) << OPCODE_CLASS_SHIFT)
         ))
     }
 
@@ -0,0 +1,384 @@
+use super::{
+    program::{Modulus, Operand, VerificationProgram},
+    Secp256k1HelperEncode, Secp256k1HelperTableConfig, BLOCK_LINES, CARRIES, CARRY_OFFSET_SHIFT,
+    LIMB_PARTS, OP_LINES, SLOTS,
+};
+use crate::circuits::utils::bn_to_field;
+use crate::foreign::secp256k1_helper::{
+    biguint_to_limbs, Secp256k1HelperOp, SECP256K1_INPUT_LIMBS, SECP256K1_LIMBS,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Cell, Layouter, Region},
+    plonk::{Advice, Column, Error, Fixed},
+};
+use num_bigint::{BigInt, BigUint};
+use num_traits::Zero;
+use specs::{etable::EventTableEntry, host_function::HostPlugin, step::StepInfo};
+
+pub struct Secp256k1HelperTableChip<F: FieldExt> {
+    pub(crate) config: Secp256k1HelperTableConfig<F>,
+    max_available_rows: usize,
+}
+
+/*
+ * The input limbs of the verifications called by a trace, a limb never given
+ * is 0.
+ */
+pub(crate) fn secp256k1_signatures(
+    entries: &Vec<EventTableEntry>,
+) -> Vec<[u64; SECP256K1_INPUT_LIMBS]> {
+    let mut signatures = vec![];
+
+    for entry in entries {
+        if let StepInfo::CallHost {
+            plugin,
+            function_name,
+            args,
+            ..
+        } = &entry.step_info
+        {
+            assert_eq!(*plugin, HostPlugin::Secp256k1);
+
+            let index = args[0] as usize;
+            let op = Secp256k1HelperOp::from(function_name);
+            let signature = match op {
+                Secp256k1HelperOp::Input => index / SECP256K1_INPUT_LIMBS,
+                Secp256k1HelperOp::Verify => index,
+            };
+
+            if signatures.len() <= signature {
+                signatures.resize(signature + 1, [0; SECP256K1_INPUT_LIMBS]);
+            }
+
+            if op == Secp256k1HelperOp::Input {
+                signatures[signature][index % SECP256K1_INPUT_LIMBS] = args[1];
+            }
+        } else {
+            unreachable!()
+        }
+    }
+
+    signatures
+}
+
+impl<F: FieldExt> Secp256k1HelperTableChip<F> {
+    pub fn new(config: Secp256k1HelperTableConfig<F>, max_available_rows: usize) -> Self {
+        Self {
+            config,
+            max_available_rows,
+        }
+    }
+
+    fn assign_fixed(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        col: Column<Fixed>,
+        value: F,
+    ) -> Result<(), Error> {
+        region.assign_fixed(|| "secp256k1 helper fixed", col, offset, || Ok(value))?;
+
+        Ok(())
+    }
+
+    fn assign_advice(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        col: Column<Advice>,
+        value: F,
+    ) -> Result<Cell, Error> {
+        let cell = region.assign_advice(|| "secp256k1 helper advice", col, offset, || Ok(value))?;
+
+        Ok(cell.cell())
+    }
+
+    /*
+     * Assigns the limbs of a value with their u16 parts on the lines of the op
+     * starting at offset, returns the cells of the limbs.
+     */
+    fn assign_limbs(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        col: Column<Advice>,
+        parts: &[Column<Advice>; LIMB_PARTS],
+        value: &BigUint,
+    ) -> Result<Vec<Cell>, Error> {
+        let mut cells = vec![];
+
+        for (i, limb) in biguint_to_limbs(value).iter().enumerate() {
+            cells.push(self.assign_advice(region, offset + i, col, F::from(*limb))?);
+
+            for (j, part) in parts.iter().enumerate() {
+                self.assign_advice(
+                    region,
+                    offset + i,
+                    *part,
+                    F::from((*limb >> (16 * j)) & 0xffff),
+                )?;
+            }
+        }
+
+        Ok(cells)
+    }
+
+    /*
+     * The carries of a * b + c - q * m - d = 0, see the mul mod gate, shifted
+     * by 2^CARRY_OFFSET_SHIFT.
+     */
+    fn carries([a, b, c, d]: &[BigUint; 4], q: &BigUint, m: &BigUint) -> Vec<BigUint> {
+        let [a, b, c, d, q, m] = [a, b, c, d, q, m].map(|v| biguint_to_limbs(v).map(BigInt::from));
+        let mut carry = BigInt::zero();
+        let mut carries = vec![];
+
+        for k in 0..CARRIES {
+            let mut sum = carry.clone();
+
+            for i in 0..SECP256K1_LIMBS {
+                if k >= i && k - i < SECP256K1_LIMBS {
+                    sum += &a[i] * &b[k - i] - &q[i] * &m[k - i];
+                }
+            }
+
+            if k < SECP256K1_LIMBS {
+                sum += &c[k] - &d[k];
+            }
+
+            carry = sum >> 64;
+            carries.push(
+                (&carry + (BigInt::from(1u64) << CARRY_OFFSET_SHIFT))
+                    .to_biguint()
+                    .unwrap(),
+            );
+        }
+
+        carries
+    }
+
+    fn assign_op(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        modulus: Modulus,
+        values: &[BigUint; 4],
+        enable: bool,
+    ) -> Result<Vec<Vec<Cell>>, Error> {
+        let m = modulus.value();
+
+        self.assign_fixed(region, offset, self.config.op_sel, F::one())?;
+        if modulus == Modulus::Order {
+            self.assign_fixed(region, offset, self.config.is_order, F::one())?;
+        }
+
+        let mut cells = vec![];
+        for i in 0..4 {
+            cells.push(self.assign_limbs(
+                region,
+                offset,
+                self.config.operands[i],
+                &self.config.parts[i],
+                &values[i],
+            )?);
+        }
+
+        // A disabled op is assigned with 0.
+        let (q, e, carries) = if enable {
+            let [a, b, c, d] = values;
+            let q = (a * b + c - d) / &m;
+            let e = &m - 1u64 - d;
+            let carries = Self::carries(values, &q, &m);
+
+            (q, e, carries)
+        } else {
+            (
+                BigUint::zero(),
+                BigUint::zero(),
+                vec![BigUint::zero(); CARRIES],
+            )
+        };
+
+        self.assign_limbs(region, offset, self.config.q, &self.config.parts[4], &q)?;
+        self.assign_limbs(region, offset, self.config.e, &self.config.parts[5], &e)?;
+
+        for line in 0..OP_LINES {
+            for (i, col) in self.config.carries.iter().enumerate() {
+                let carry = carries
+                    .get(line * 2 + i)
+                    .cloned()
+                    .unwrap_or(BigUint::zero());
+
+                for (j, part) in col.iter().enumerate() {
+                    self.assign_advice(
+                        region,
+                        offset + line,
+                        *part,
+                        bn_to_field(&((&carry >> (16 * j)) & BigUint::from(0xffffu64))),
+                    )?;
+                }
+            }
+        }
+
+        // d + e + 1 = m limb by limb.
+        let mut e_carry = 1u128;
+        let d = biguint_to_limbs(&values[3]);
+        let e = biguint_to_limbs(&e);
+        for line in 0..OP_LINES {
+            e_carry = (d[line] as u128 + e[line] as u128 + e_carry) >> 64;
+
+            self.assign_advice(
+                region,
+                offset + line,
+                self.config.e_carry.0,
+                F::from(e_carry as u64),
+            )?;
+        }
+
+        Ok(cells)
+    }
+
+    /*
+     * Assigns a verification, a disabled block holds the ops of the program
+     * with 0. The variables are copied in any case, so that the permutation
+     * does not depend on the trace.
+     */
+    fn assign_verification(
+        &self,
+        region: &mut Region<'_, F>,
+        block: usize,
+        program: &VerificationProgram,
+        input: Option<&[u64; SECP256K1_INPUT_LIMBS]>,
+    ) -> Result<(), Error> {
+        let offset = block * BLOCK_LINES;
+        let enable = input.is_some();
+
+        for line in 0..BLOCK_LINES {
+            self.assign_fixed(region, offset + line, self.config.sel, F::one())?;
+            self.assign_advice(
+                region,
+                offset + line,
+                self.config.enable,
+                if enable { F::one() } else { F::zero() },
+            )?;
+        }
+        self.assign_fixed(region, offset, self.config.block_first_line_sel, F::one())?;
+
+        let mut vars: Vec<Option<Vec<Cell>>> = vec![None; program.values.len()];
+
+        for (i, op) in program.ops.iter().enumerate() {
+            let op_offset = offset + i * OP_LINES;
+            let values = [0, 1, 2, 3].map(|j| {
+                if enable {
+                    program.value(&op.operands[j])
+                } else {
+                    BigUint::zero()
+                }
+            });
+
+            let cells = self.assign_op(region, op_offset, op.modulus, &values, enable)?;
+
+            for (j, (operand, cells)) in op.operands.iter().zip(cells.into_iter()).enumerate() {
+                match operand {
+                    Operand::Const(value) => {
+                        for (line, limb) in biguint_to_limbs(value).iter().enumerate() {
+                            self.assign_fixed(
+                                region,
+                                op_offset + line,
+                                self.config.constant_sel[j],
+                                F::one(),
+                            )?;
+                            self.assign_fixed(
+                                region,
+                                op_offset + line,
+                                self.config.constant[j],
+                                F::from(*limb),
+                            )?;
+                        }
+                    }
+                    Operand::Var(var) => match &vars[*var] {
+                        Some(home) => {
+                            for (cell, home) in cells.iter().zip(home.iter()) {
+                                region.constrain_equal(*cell, *home)?;
+                            }
+                        }
+                        None => vars[*var] = Some(cells),
+                    },
+                }
+            }
+        }
+
+        for line in 0..SLOTS {
+            let (op, index) = if line < SECP256K1_INPUT_LIMBS {
+                (
+                    Secp256k1HelperOp::Input,
+                    block * SECP256K1_INPUT_LIMBS + line,
+                )
+            } else {
+                (Secp256k1HelperOp::Verify, block)
+            };
+
+            self.assign_fixed(region, offset + line, self.config.slot_sel, F::one())?;
+            self.assign_fixed(
+                region,
+                offset + line,
+                self.config.slot_prefix,
+                bn_to_field(&Secp256k1HelperEncode::encode_limb_prefix::<BigUint>(
+                    op,
+                    index as u64,
+                )),
+            )?;
+
+            if line < SECP256K1_INPUT_LIMBS {
+                let limb = input.map_or(0, |input| input[line]);
+                let cell =
+                    self.assign_advice(region, offset + line, self.config.slot, F::from(limb))?;
+                let var = program.inputs[line / SECP256K1_LIMBS];
+
+                region
+                    .constrain_equal(cell, vars[var].as_ref().unwrap()[line % SECP256K1_LIMBS])?;
+            } else {
+                self.assign_advice(region, offset + line, self.config.slot, F::zero())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        entries: &Vec<EventTableEntry>,
+    ) -> Result<(), Error> {
+        let signatures = secp256k1_signatures(entries);
+
+        assert!(signatures.len() * BLOCK_LINES <= self.max_available_rows);
+
+        let programs = signatures
+            .iter()
+            .map(|input| VerificationProgram::verification(input))
+            .collect::<Vec<_>>();
+        let padding = VerificationProgram::verification(&[0; SECP256K1_INPUT_LIMBS]);
+
+        layouter.assign_region(
+            || "secp256k1 helper assign",
+            |mut region| {
+                for block in 0..self.max_available_rows / BLOCK_LINES {
+                    match programs.get(block) {
+                        Some(program) => self.assign_verification(
+                            &mut region,
+                            block,
+                            program,
+                            Some(&signatures[block]),
+                        )?,
+                        None => self.assign_verification(&mut region, block, &padding, None)?,
+                    }
+                }
+
+                Ok(())
+            },
+        )?;
+
+        Ok(())
+    }
+}
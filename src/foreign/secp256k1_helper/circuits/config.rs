@@ -0,0 +1,151 @@
+use super::{Secp256k1HelperTableConfig, CARRIES, CARRY_OFFSET_SHIFT};
+use crate::circuits::utils::bn_to_field;
+use crate::foreign::secp256k1_helper::{
+    biguint_to_limbs, field_modulus, group_order, SECP256K1_LIMBS,
+};
+use crate::{constant_from, constant_from_bn, curr, fixed_curr, nextn, prev};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{ConstraintSystem, Expression, VirtualCells},
+};
+use num_bigint::BigUint;
+
+fn limb_carry<F: FieldExt>() -> Expression<F> {
+    constant_from_bn!(&(BigUint::from(1u64) << 64))
+}
+
+impl<F: FieldExt> Secp256k1HelperTableConfig<F> {
+    // The limb i of the modulus of the op starting on the current line.
+    fn modulus_limb(&self, meta: &mut VirtualCells<'_, F>, i: usize) -> Expression<F> {
+        let p = BigUint::from(biguint_to_limbs(&field_modulus())[i]);
+        let n = BigUint::from(biguint_to_limbs(&group_order())[i]);
+
+        constant_from_bn!(&p)
+            + fixed_curr!(meta, self.is_order) * (constant_from_bn!(&n) - constant_from_bn!(&p))
+    }
+
+    // The carry k of the product of the op starting on the current line.
+    fn carry(&self, meta: &mut VirtualCells<'_, F>, k: usize) -> Expression<F> {
+        let shifted =
+            self.carries[k % 2]
+                .iter()
+                .enumerate()
+                .fold(constant_from!(0), |acc, (i, part)| {
+                    acc + nextn!(meta, *part, (k / 2) as i32)
+                        * constant_from_bn!(&(BigUint::from(1u64) << (16 * i)))
+                });
+
+        shifted - constant_from_bn!(&(BigUint::from(1u64) << CARRY_OFFSET_SHIFT))
+    }
+
+    pub fn _configure(&self, meta: &mut ConstraintSystem<F>) {
+        meta.create_gate("secp256k1 helper enable", |meta| {
+            let enable = curr!(meta, self.enable);
+
+            vec![
+                fixed_curr!(meta, self.sel) * enable.clone() * (constant_from!(1) - enable.clone()),
+                fixed_curr!(meta, self.sel)
+                    * (constant_from!(1) - fixed_curr!(meta, self.block_first_line_sel))
+                    * (enable - prev!(meta, self.enable)),
+            ]
+        });
+
+        meta.create_gate("secp256k1 helper limbs", |meta| {
+            self.operands
+                .iter()
+                .chain([self.q, self.e].iter())
+                .zip(self.parts.iter())
+                .map(|(limb, parts)| {
+                    let sum = parts
+                        .iter()
+                        .enumerate()
+                        .fold(constant_from!(0), |acc, (i, part)| {
+                            acc + curr!(meta, *part) * constant_from!(1u64 << (16 * i))
+                        });
+
+                    fixed_curr!(meta, self.sel) * (curr!(meta, *limb) - sum)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("secp256k1 helper constants", |meta| {
+            (0..4)
+                .map(|i| {
+                    fixed_curr!(meta, self.constant_sel[i])
+                        * curr!(meta, self.enable)
+                        * (curr!(meta, self.operands[i]) - fixed_curr!(meta, self.constant[i]))
+                })
+                .collect::<Vec<_>>()
+        });
+
+        /*
+         * a * b + c - q * m - d = 0 limb by limb: the limb k of the product
+         * plus the carry k - 1 is the carry k shifted by 64 bits, the carries
+         * are small enough for the sums not to wrap around the field.
+         */
+        meta.create_gate("secp256k1 helper mul mod", |meta| {
+            let [a, b, c, d] = self.operands;
+            let mut constraints = vec![];
+
+            for k in 0..2 * SECP256K1_LIMBS - 1 {
+                let mut sum = constant_from!(0);
+
+                for i in 0..SECP256K1_LIMBS {
+                    if k >= i && k - i < SECP256K1_LIMBS {
+                        let j = k - i;
+
+                        sum = sum + nextn!(meta, a, i as i32) * nextn!(meta, b, j as i32)
+                            - nextn!(meta, self.q, i as i32) * self.modulus_limb(meta, j);
+                    }
+                }
+
+                if k < SECP256K1_LIMBS {
+                    sum = sum + nextn!(meta, c, k as i32) - nextn!(meta, d, k as i32);
+                }
+
+                if k > 0 {
+                    sum = sum + self.carry(meta, k - 1);
+                }
+
+                if k < CARRIES {
+                    sum = sum - self.carry(meta, k) * limb_carry();
+                }
+
+                constraints.push(sum);
+            }
+
+            constraints
+                .into_iter()
+                .map(|x| x * fixed_curr!(meta, self.op_sel) * curr!(meta, self.enable))
+                .collect::<Vec<_>>()
+        });
+
+        // d + e = m - 1 limb by limb, m is odd.
+        meta.create_gate("secp256k1 helper canonical", |meta| {
+            let mut constraints = vec![];
+
+            for i in 0..SECP256K1_LIMBS {
+                let mut sum = nextn!(meta, self.operands[3], i as i32)
+                    + nextn!(meta, self.e, i as i32)
+                    - self.modulus_limb(meta, i);
+
+                if i == 0 {
+                    sum = sum + constant_from!(1);
+                } else {
+                    sum = sum + nextn!(meta, self.e_carry.0, i as i32 - 1);
+                }
+
+                if i < SECP256K1_LIMBS - 1 {
+                    sum = sum - nextn!(meta, self.e_carry.0, i as i32) * limb_carry();
+                }
+
+                constraints.push(sum);
+            }
+
+            constraints
+                .into_iter()
+                .map(|x| x * fixed_curr!(meta, self.op_sel) * curr!(meta, self.enable))
+                .collect::<Vec<_>>()
+        });
+    }
+}
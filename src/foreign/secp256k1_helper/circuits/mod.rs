@@ -0,0 +1,159 @@
+use self::program::VERIFICATION_OPS;
+use super::{Secp256k1HelperOp, SECP256K1_INPUT_LIMBS, SECP256K1_LIMBS};
+use crate::{
+    circuits::rtable::RangeTableConfig,
+    curr, fixed_curr,
+    foreign::ForeignTableConfig,
+    traits::circuits::bit_range_table::{BitColumn, BitRangeTable},
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Advice, Column, ConstraintSystem, Expression, Fixed, VirtualCells},
+};
+use num_bigint::BigUint;
+use specs::encode::FromBn;
+use std::marker::PhantomData;
+
+pub mod assign;
+pub mod config;
+pub mod program;
+
+/*
+ * An op takes a line per u64 limb: the line i holds the limb i of each
+ * operand. A verification takes a block of lines running the ops of its
+ * program, its input limbs then the verify call are looked up on the first
+ * lines.
+ */
+pub(crate) const OP_LINES: usize = SECP256K1_LIMBS;
+pub(crate) const BLOCK_LINES: usize = VERIFICATION_OPS * OP_LINES;
+pub(crate) const SLOTS: usize = SECP256K1_INPUT_LIMBS + 1;
+
+// The u16 parts of a limb.
+pub(crate) const LIMB_PARTS: usize = 4;
+/*
+ * A carry of the product is less than 2^67 in absolute value, it is shifted
+ * by CARRY_OFFSET and held by 5 u16 parts.
+ */
+pub(crate) const CARRY_PARTS: usize = 5;
+pub(crate) const CARRY_OFFSET_SHIFT: usize = 68;
+// The carries of the 7 limbs of the product, the last one is 0.
+pub(crate) const CARRIES: usize = 2 * SECP256K1_LIMBS - 2;
+
+const OP_SHIFT: usize = 96;
+const INDEX_SHIFT: usize = 64;
+
+pub struct Secp256k1HelperEncode();
+
+impl Secp256k1HelperEncode {
+    pub(super) fn encode_limb<T: FromBn>(op: T, index: T, value: T) -> T {
+        op * T::from_bn(&(BigUint::from(1u64) << OP_SHIFT))
+            + index * T::from_bn(&(BigUint::from(1u64) << INDEX_SHIFT))
+            + value
+    }
+
+    pub(super) fn encode_limb_prefix<T: FromBn>(op: Secp256k1HelperOp, index: u64) -> T {
+        Self::encode_limb(
+            T::from_bn(&BigUint::from(op as u64)),
+            T::from_bn(&BigUint::from(index)),
+            T::from_bn(&BigUint::from(0u64)),
+        )
+    }
+}
+
+#[derive(Clone)]
+pub struct Secp256k1HelperTableConfig<F: FieldExt> {
+    sel: Column<Fixed>,
+    block_first_line_sel: Column<Fixed>,
+    op_sel: Column<Fixed>,
+    // The op is modulo the group order instead of the field modulus.
+    is_order: Column<Fixed>,
+    // The constant limbs of the operands [a, b, c, d].
+    constant_sel: [Column<Fixed>; 4],
+    constant: [Column<Fixed>; 4],
+    slot_sel: Column<Fixed>,
+    slot_prefix: Column<Fixed>,
+
+    enable: Column<Advice>,
+    // The limbs of the operands [a, b, c, d], copied between the ops.
+    operands: [Column<Advice>; 4],
+    q: Column<Advice>,
+    // d + e = m - 1, so that d < m.
+    e: Column<Advice>,
+    // The u16 parts of the limbs of a, b, c, d, q and e.
+    parts: [[Column<Advice>; LIMB_PARTS]; 6],
+    carries: [[Column<Advice>; CARRY_PARTS]; 2],
+    e_carry: BitColumn,
+    slot: Column<Advice>,
+
+    mark: PhantomData<F>,
+}
+
+impl<F: FieldExt> Secp256k1HelperTableConfig<F> {
+    fn new(meta: &mut ConstraintSystem<F>, rtable: &RangeTableConfig<F>) -> Self {
+        let sel = meta.fixed_column();
+
+        let mut u16_column = || {
+            let col = meta.advice_column();
+            rtable.configure_in_u16_range(meta, "secp256k1 helper u16 part", |meta| {
+                fixed_curr!(meta, sel) * curr!(meta, col)
+            });
+            col
+        };
+        let parts = [0; 6].map(|_| [0; LIMB_PARTS].map(|_| u16_column()));
+        let carries = [0; 2].map(|_| [0; CARRY_PARTS].map(|_| u16_column()));
+
+        let e_carry = rtable.bit_column(meta, "secp256k1 helper e carry", |meta| {
+            fixed_curr!(meta, sel)
+        });
+
+        let operands = [0; 4].map(|_| meta.advice_column());
+        let slot = meta.advice_column();
+        for col in operands.iter().chain([slot].iter()) {
+            meta.enable_equality(*col);
+        }
+
+        Self {
+            sel,
+            block_first_line_sel: meta.fixed_column(),
+            op_sel: meta.fixed_column(),
+            is_order: meta.fixed_column(),
+            constant_sel: [0; 4].map(|_| meta.fixed_column()),
+            constant: [0; 4].map(|_| meta.fixed_column()),
+            slot_sel: meta.fixed_column(),
+            slot_prefix: meta.fixed_column(),
+            enable: meta.advice_column(),
+            operands,
+            q: meta.advice_column(),
+            e: meta.advice_column(),
+            parts,
+            carries,
+            e_carry,
+            slot,
+            mark: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, rtable: &RangeTableConfig<F>) -> Self {
+        let config = Self::new(meta, rtable);
+        config._configure(meta);
+        config
+    }
+}
+
+impl<F: FieldExt> ForeignTableConfig<F> for Secp256k1HelperTableConfig<F> {
+    fn configure_in_table(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        key: &'static str,
+        expr: &dyn Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+    ) {
+        meta.lookup_any(key, |meta| {
+            vec![(
+                expr(meta),
+                fixed_curr!(meta, self.slot_sel)
+                    * curr!(meta, self.enable)
+                    * (fixed_curr!(meta, self.slot_prefix) + curr!(meta, self.slot)),
+            )]
+        });
+    }
+}
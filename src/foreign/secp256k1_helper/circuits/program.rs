@@ -0,0 +1,359 @@
+use crate::foreign::secp256k1_helper::{
+    field_modulus, generator, group_order, inverse_mod, limbs_to_biguint, neg_mod, offset_point,
+    point_add, Point, CURVE_B, SECP256K1_INPUTS, SECP256K1_INPUT_LIMBS, SECP256K1_LIMBS,
+};
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+// The bits of the scalars u1 and u2.
+const SCALAR_BITS: usize = 256;
+
+const SETUP_OPS: usize = 12;
+const POINT_DOUBLE_OPS: usize = 8;
+const POINT_ADD_OPS: usize = 8;
+const SELECT_OPS: usize = 4;
+// The booleans and the composition of a bit of u1 and of u2, then the double and add.
+const BIT_OPS: usize = 4 + POINT_DOUBLE_OPS + 2 * (POINT_ADD_OPS + SELECT_OPS);
+const FINAL_OPS: usize = POINT_ADD_OPS + 1;
+
+pub(crate) const VERIFICATION_OPS: usize = SETUP_OPS + SCALAR_BITS * BIT_OPS + FINAL_OPS;
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Modulus {
+    // The modulus p of the base field.
+    Field,
+    // The order n of the group.
+    Order,
+}
+
+impl Modulus {
+    pub(crate) fn value(&self) -> BigUint {
+        match self {
+            Modulus::Field => field_modulus(),
+            Modulus::Order => group_order(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) enum Operand {
+    Const(BigUint),
+    Var(usize),
+}
+
+/*
+ * An op of the helper table constrains a * b + c = q * m + d with d < m, the
+ * operands are [a, b, c, d].
+ */
+pub(crate) struct MulModOp {
+    pub(crate) modulus: Modulus,
+    pub(crate) operands: [Operand; 4],
+}
+
+type AffinePoint = (Operand, Operand);
+
+/*
+ * The verification of a signature as a sequence of ops. A variable is held by
+ * the cells of the first operand taking it and copied to its other operands,
+ * the ops only depend on the values of the variables.
+ */
+pub(crate) struct VerificationProgram {
+    pub(crate) ops: Vec<MulModOp>,
+    pub(crate) values: Vec<BigUint>,
+    // The variables of the hash, r, s and the public key, in the order of the input limbs.
+    pub(crate) inputs: Vec<usize>,
+}
+
+impl VerificationProgram {
+    pub(crate) fn value(&self, operand: &Operand) -> BigUint {
+        match operand {
+            Operand::Const(value) => value.clone(),
+            Operand::Var(var) => self.values[*var].clone(),
+        }
+    }
+
+    fn var(&mut self, value: BigUint) -> Operand {
+        self.values.push(value);
+        Operand::Var(self.values.len() - 1)
+    }
+
+    fn var_index(operand: &Operand) -> usize {
+        match operand {
+            Operand::Var(var) => *var,
+            Operand::Const(_) => unreachable!(),
+        }
+    }
+
+    fn constrain(&mut self, modulus: Modulus, a: &Operand, b: &Operand, c: &Operand, d: &Operand) {
+        self.ops.push(MulModOp {
+            modulus,
+            operands: [a.clone(), b.clone(), c.clone(), d.clone()],
+        })
+    }
+
+    // d = a * b + c mod m
+    fn mul_add(&mut self, modulus: Modulus, a: &Operand, b: &Operand, c: &Operand) -> Operand {
+        let d = (self.value(a) * self.value(b) + self.value(c)) % modulus.value();
+        let d = self.var(d);
+
+        self.constrain(modulus, a, b, c, &d);
+
+        d
+    }
+
+    fn mul(&mut self, modulus: Modulus, a: &Operand, b: &Operand) -> Operand {
+        self.mul_add(modulus, a, b, &Operand::Const(BigUint::zero()))
+    }
+
+    // c - a mod m
+    fn sub(&mut self, modulus: Modulus, c: &Operand, a: &Operand) -> Operand {
+        let minus_one = Operand::Const(modulus.value() - 1u64);
+
+        self.mul_add(modulus, a, &minus_one, c)
+    }
+
+    fn neg(&mut self, modulus: Modulus, a: &Operand) -> Operand {
+        self.sub(modulus, &Operand::Const(BigUint::zero()), a)
+    }
+
+    // Constrains a = b mod m for a canonical b.
+    fn canonical_to(&mut self, modulus: Modulus, a: &Operand, b: &Operand) {
+        self.constrain(
+            modulus,
+            a,
+            &Operand::Const(BigUint::one()),
+            &Operand::Const(BigUint::zero()),
+            b,
+        )
+    }
+
+    // Constrains a < m.
+    fn canonical(&mut self, modulus: Modulus, a: &Operand) {
+        self.canonical_to(modulus, a, a)
+    }
+
+    // Constrains a to have an inverse modulo m, which is returned.
+    fn inverse(&mut self, modulus: Modulus, a: &Operand) -> Operand {
+        let inv = inverse_mod(&self.value(a), &modulus.value());
+        let inv = self.var(inv);
+
+        self.constrain(
+            modulus,
+            a,
+            &inv,
+            &Operand::Const(BigUint::zero()),
+            &Operand::Const(BigUint::one()),
+        );
+
+        inv
+    }
+
+    fn bit(&mut self, value: bool) -> Operand {
+        let bit = self.var(if value {
+            BigUint::one()
+        } else {
+            BigUint::zero()
+        });
+
+        // bit * bit = bit and bit < n
+        self.constrain(
+            Modulus::Order,
+            &bit,
+            &bit,
+            &Operand::Const(BigUint::zero()),
+            &bit,
+        );
+
+        bit
+    }
+
+    // acc * 2 + bit mod n, the last bit of a scalar constrains its composition.
+    fn compose_bit(&mut self, acc: &Operand, bit: &Operand, scalar: Option<&Operand>) -> Operand {
+        let two = Operand::Const(BigUint::from(2u64));
+
+        match scalar {
+            Some(scalar) => {
+                self.constrain(Modulus::Order, acc, &two, bit, scalar);
+                scalar.clone()
+            }
+            None => self.mul_add(Modulus::Order, acc, &two, bit),
+        }
+    }
+
+    /*
+     * lambda = 3x^2 / 2y is constrained by y * w = 3 / 2 and lambda = x^2 * w,
+     * y is not 0 on the curve.
+     */
+    fn double(&mut self, (x, y): &AffinePoint) -> AffinePoint {
+        let p = field_modulus();
+        let three_halves = BigUint::from(3u64) * inverse_mod(&BigUint::from(2u64), &p) % &p;
+
+        let xx = self.mul(Modulus::Field, x, x);
+        let w = BigUint::from(3u64) * inverse_mod(&(self.value(y) * 2u64), &p) % &p;
+        let w = self.var(w);
+        self.constrain(
+            Modulus::Field,
+            y,
+            &w,
+            &Operand::Const(BigUint::zero()),
+            &Operand::Const(three_halves),
+        );
+        let lambda = self.mul(Modulus::Field, &xx, &w);
+
+        let minus_two = Operand::Const(&p - 2u64);
+        let minus_2x = self.mul(Modulus::Field, x, &minus_two);
+        let x3 = self.mul_add(Modulus::Field, &lambda, &lambda, &minus_2x);
+
+        self.finish_point(&lambda, x, y, x3)
+    }
+
+    /*
+     * Adds the addend of a different x, the difference of the x has an inverse
+     * and lambda * (x2 - x1) + y1 = y2. The addend is canonical and its negated
+     * x is given.
+     */
+    fn add(
+        &mut self,
+        (x1, y1): &AffinePoint,
+        (x2, y2): &AffinePoint,
+        minus_x2: &Operand,
+    ) -> AffinePoint {
+        let p = field_modulus();
+
+        let dx = self.sub(Modulus::Field, x2, x1);
+        let dx_inv = self.inverse(Modulus::Field, &dx);
+        let lambda = (self.value(y2) + neg_mod(&self.value(y1), &p)) * self.value(&dx_inv) % &p;
+        let lambda = self.var(lambda);
+        self.constrain(Modulus::Field, &dx, &lambda, y1, y2);
+
+        let minus_sum = self.sub(Modulus::Field, minus_x2, x1);
+        let x3 = self.mul_add(Modulus::Field, &lambda, &lambda, &minus_sum);
+
+        self.finish_point(&lambda, x1, y1, x3)
+    }
+
+    // y3 = lambda * (x1 - x3) - y1
+    fn finish_point(
+        &mut self,
+        lambda: &Operand,
+        x1: &Operand,
+        y1: &Operand,
+        x3: Operand,
+    ) -> AffinePoint {
+        let dx = self.sub(Modulus::Field, x1, &x3);
+        let minus_y1 = self.neg(Modulus::Field, y1);
+        let y3 = self.mul_add(Modulus::Field, lambda, &dx, &minus_y1);
+
+        (x3, y3)
+    }
+
+    // bit ? t : p, as p + bit * (t - p)
+    fn select(&mut self, bit: &Operand, t: &AffinePoint, p: &AffinePoint) -> AffinePoint {
+        let dx = self.sub(Modulus::Field, &t.0, &p.0);
+        let x = self.mul_add(Modulus::Field, bit, &dx, &p.0);
+        let dy = self.sub(Modulus::Field, &t.1, &p.1);
+        let y = self.mul_add(Modulus::Field, bit, &dy, &p.1);
+
+        (x, y)
+    }
+
+    fn const_point(point: &Point) -> (AffinePoint, Operand) {
+        (
+            (
+                Operand::Const(point.x.clone()),
+                Operand::Const(point.y.clone()),
+            ),
+            Operand::Const(neg_mod(&point.x, &field_modulus())),
+        )
+    }
+
+    /*
+     * R = u1 * G + u2 * Q with u1 = hash / s and u2 = r / s modulo n, the
+     * signature is valid if the x of R is r modulo n. R is accumulated from
+     * the offset point H by double and add on the bits of u1 and u2, then
+     * -2^256 * H is added.
+     */
+    pub(crate) fn verification(input: &[u64; SECP256K1_INPUT_LIMBS]) -> Self {
+        let mut program = VerificationProgram {
+            ops: vec![],
+            values: vec![],
+            inputs: vec![],
+        };
+
+        let [hash, r, s, qx, qy] = [0, 1, 2, 3, 4].map(|i| {
+            program.var(limbs_to_biguint(
+                &input[i * SECP256K1_LIMBS..(i + 1) * SECP256K1_LIMBS],
+            ))
+        });
+        program.inputs = [&hash, &r, &s, &qx, &qy]
+            .iter()
+            .map(|input| Self::var_index(input))
+            .collect();
+        assert_eq!(program.inputs.len(), SECP256K1_INPUTS);
+
+        program.canonical(Modulus::Order, &r);
+        program.inverse(Modulus::Order, &r);
+        program.canonical(Modulus::Order, &s);
+        let s_inv = program.inverse(Modulus::Order, &s);
+        let u1 = program.mul(Modulus::Order, &hash, &s_inv);
+        let u2 = program.mul(Modulus::Order, &r, &s_inv);
+
+        program.canonical(Modulus::Field, &qx);
+        program.canonical(Modulus::Field, &qy);
+        let yy = program.mul(Modulus::Field, &qy, &qy);
+        let xx = program.mul(Modulus::Field, &qx, &qx);
+        program.constrain(
+            Modulus::Field,
+            &xx,
+            &qx,
+            &Operand::Const(BigUint::from(CURVE_B)),
+            &yy,
+        );
+        let minus_qx = program.neg(Modulus::Field, &qx);
+        assert_eq!(program.ops.len(), SETUP_OPS);
+
+        let (g, minus_gx) = Self::const_point(&generator());
+        let q = (qx, qy);
+        let (u1_value, u2_value) = (program.value(&u1), program.value(&u2));
+
+        let mut acc = Self::const_point(&offset_point()).0;
+        let mut acc_u1 = Operand::Const(BigUint::zero());
+        let mut acc_u2 = Operand::Const(BigUint::zero());
+
+        for i in (0..SCALAR_BITS).rev() {
+            let (scalar_u1, scalar_u2) = if i == 0 {
+                (Some(&u1), Some(&u2))
+            } else {
+                (None, None)
+            };
+
+            let bit_u1 = program.bit(u1_value.bit(i as u64));
+            let bit_u2 = program.bit(u2_value.bit(i as u64));
+            acc_u1 = program.compose_bit(&acc_u1, &bit_u1, scalar_u1);
+            acc_u2 = program.compose_bit(&acc_u2, &bit_u2, scalar_u2);
+
+            acc = program.double(&acc);
+            let t = program.add(&acc, &g, &minus_gx);
+            acc = program.select(&bit_u1, &t, &acc);
+            let t = program.add(&acc, &q, &minus_qx);
+            acc = program.select(&bit_u2, &t, &acc);
+        }
+
+        let mut offset = Some(offset_point());
+        for _ in 0..SCALAR_BITS {
+            offset = point_add(&offset, &offset);
+        }
+        let offset = offset.unwrap();
+        let (correction, minus_correction_x) = Self::const_point(&Point {
+            x: offset.x,
+            y: neg_mod(&offset.y, &field_modulus()),
+        });
+
+        let (rx, _) = program.add(&acc, &correction, &minus_correction_x);
+        program.canonical_to(Modulus::Order, &rx, &r);
+
+        assert_eq!(program.ops.len(), VERIFICATION_OPS);
+
+        program
+    }
+}
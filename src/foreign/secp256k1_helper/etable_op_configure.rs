@@ -0,0 +1,210 @@
+use super::{
+    circuits::Secp256k1HelperEncode, Secp256k1HelperOp, SECP256K1_FOREIGN_FUNCTION_NAME_INPUT,
+    SECP256K1_FOREIGN_TABLE_KEY,
+};
+use crate::{
+    circuits::{
+        etable_compact::{
+            op_configure::{
+                BitCell, ConstraintBuilder, EventTableCellAllocator, EventTableOpcodeConfig,
+                MTableLookupCell, U64Cell,
+            },
+            EventTableCommonConfig, MLookupItem, StepStatus,
+        },
+        mtable_compact::encode::MemoryTableLookupEncode,
+        utils::{bn_to_field, Context},
+    },
+    constant_from, constant_from_bn,
+    foreign::{EventTableForeignCallConfigBuilder, ForeignCallInfo},
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Error, Expression, VirtualCells},
+};
+use num_bigint::BigUint;
+use specs::step::StepInfo;
+use specs::{
+    etable::EventTableEntry,
+    itable::{OpcodeClass, OPCODE_CLASS_SHIFT},
+};
+use specs::{host_function::HostPlugin, mtable::VarType};
+
+pub struct ETableSecp256k1HelperTableConfig {
+    foreign_call_id: u64,
+
+    index: U64Cell,
+    value: U64Cell,
+    is_input: BitCell,
+    is_verify: BitCell,
+
+    lookup_stack_read_index: MTableLookupCell,
+    lookup_stack_value: MTableLookupCell,
+}
+
+pub struct Secp256k1ForeignCallInfo {}
+impl ForeignCallInfo for Secp256k1ForeignCallInfo {
+    fn call_id(&self) -> usize {
+        OpcodeClass::ForeignPluginStart as usize + HostPlugin::Secp256k1.id()
+    }
+}
+pub struct ETableSecp256k1HelperTableConfigBuilder {}
+
+impl<F: FieldExt> EventTableForeignCallConfigBuilder<F>
+    for ETableSecp256k1HelperTableConfigBuilder
+{
+    fn configure(
+        common: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+        info: &impl ForeignCallInfo,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let index = common.alloc_u64();
+        let value = common.alloc_u64();
+
+        let is_input = common.alloc_bit_value();
+        let is_verify = common.alloc_bit_value();
+
+        let lookup_stack_read_index = common.alloc_mtable_lookup();
+        let lookup_stack_value = common.alloc_mtable_lookup();
+
+        constraint_builder.push(
+            "secp256k1helper: is one of ops",
+            Box::new(move |meta| {
+                vec![is_input.expr(meta) + is_verify.expr(meta) - constant_from!(1)]
+            }),
+        );
+
+        constraint_builder.lookup(
+            SECP256K1_FOREIGN_TABLE_KEY,
+            "secp256k1 helper table lookup",
+            Box::new(move |meta| {
+                let op = is_input.expr(meta) * constant_from!(Secp256k1HelperOp::Input)
+                    + is_verify.expr(meta) * constant_from!(Secp256k1HelperOp::Verify);
+
+                Secp256k1HelperEncode::encode_limb(op, index.expr(meta), value.expr(meta))
+            }),
+        );
+
+        Box::new(ETableSecp256k1HelperTableConfig {
+            foreign_call_id: info.call_id() as u64,
+            index,
+            value,
+            is_input,
+            is_verify,
+            lookup_stack_read_index,
+            lookup_stack_value,
+        })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for ETableSecp256k1HelperTableConfig {
+    fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        let pick_one = self.is_input.expr(meta) * constant_from!(Secp256k1HelperOp::Input)
+            + self.is_verify.expr(meta) * constant_from!(Secp256k1HelperOp::Verify);
+
+        constant_from_bn!(&(BigUint::from(self.foreign_call_id) << OPCODE_CLASS_SHIFT)) + pick_one
+    }
+
+    fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(1) + self.is_input.expr(meta))
+    }
+
+    fn assigned_extra_mops(
+        &self,
+        _ctx: &mut Context<'_, F>,
+        _step: &StepStatus,
+        entry: &EventTableEntry,
+    ) -> u64 {
+        match &entry.step_info {
+            StepInfo::CallHost { args, .. } => args.len() as u64,
+            _ => unreachable!(),
+        }
+    }
+
+    /*
+     * The input pops the index and the limb, the verify pops the index.
+     */
+    fn mtable_lookup(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        item: MLookupItem,
+        common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        match item {
+            MLookupItem::First => Some(MemoryTableLookupEncode::encode_stack_read(
+                common_config.eid(meta),
+                constant_from!(1),
+                common_config.sp(meta) + constant_from!(1) + self.is_input.expr(meta),
+                constant_from!(VarType::I32),
+                self.index.expr(meta),
+            )),
+            MLookupItem::Second => Some(
+                self.is_input.expr(meta)
+                    * MemoryTableLookupEncode::encode_stack_read(
+                        common_config.eid(meta),
+                        constant_from!(2),
+                        common_config.sp(meta) + constant_from!(1),
+                        constant_from!(VarType::I64),
+                        self.value.expr(meta),
+                    ),
+            ),
+            _ => None,
+        }
+    }
+
+    fn sp_diff(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(1) + self.is_input.expr(meta))
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step_info: &StepStatus,
+        entry: &EventTableEntry,
+    ) -> Result<(), Error> {
+        match &entry.step_info {
+            StepInfo::CallHost {
+                plugin,
+                function_name,
+                args,
+                ..
+            } => {
+                assert_eq!(*plugin, HostPlugin::Secp256k1);
+
+                let index = args[0];
+
+                self.index.assign(ctx, index)?;
+                self.lookup_stack_read_index.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_stack_read(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(1u64),
+                        BigUint::from(step_info.current.sp + args.len() as u32),
+                        BigUint::from(VarType::I32 as u64),
+                        BigUint::from(index),
+                    ),
+                )?;
+
+                if function_name == SECP256K1_FOREIGN_FUNCTION_NAME_INPUT {
+                    let value = args[1];
+
+                    self.is_input.assign(ctx, true)?;
+                    self.value.assign(ctx, value)?;
+                    self.lookup_stack_value.assign(
+                        ctx,
+                        &MemoryTableLookupEncode::encode_stack_read(
+                            BigUint::from(step_info.current.eid),
+                            BigUint::from(2u64),
+                            BigUint::from(step_info.current.sp + 1),
+                            BigUint::from(VarType::I64 as u64),
+                            BigUint::from(value),
+                        ),
+                    )?;
+                } else {
+                    self.is_verify.assign(ctx, true)?;
+                }
+            }
+            _ => unreachable!(),
+        };
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+use strum_macros::EnumIter;
+
+pub mod circuits;
+pub mod etable_op_configure;
+pub mod runtime;
+pub mod test;
+
+pub const SECP256K1_FOREIGN_TABLE_KEY: &'static str = "secp256k1-helper-table";
+pub const SECP256K1_FOREIGN_FUNCTION_NAME_INPUT: &'static str = "zkwasm_secp256k1_input";
+pub const SECP256K1_FOREIGN_FUNCTION_NAME_VERIFY: &'static str = "zkwasm_secp256k1_verify";
+
+// The u64 limbs of a 256-bit integer.
+pub const SECP256K1_LIMBS: usize = 4;
+// The hash, r, s and the x and y of the public key.
+pub const SECP256K1_INPUTS: usize = 5;
+pub const SECP256K1_INPUT_LIMBS: usize = SECP256K1_INPUTS * SECP256K1_LIMBS;
+
+/*
+ * A guest verifies a signature limb by limb: the k-th verification takes the
+ * little endian u64 limbs of the hash, r, s and the x and y of the public key
+ * at the indices k * 20 + 0..20, then zkwasm_secp256k1_verify(k) traps unless
+ * the signature is valid. The limbs of a verification must be given before it
+ * is verified, and the verifications are numbered from 0 without gaps: an
+ * index past the next verification traps.
+ */
+#[derive(Clone, Copy, EnumIter, PartialEq)]
+pub enum Secp256k1HelperOp {
+    Input = 1,
+    Verify = 2,
+}
+
+impl From<&String> for Secp256k1HelperOp {
+    fn from(function_name: &String) -> Self {
+        match function_name.as_str() {
+            SECP256K1_FOREIGN_FUNCTION_NAME_INPUT => Secp256k1HelperOp::Input,
+            SECP256K1_FOREIGN_FUNCTION_NAME_VERIFY => Secp256k1HelperOp::Verify,
+            _ => unreachable!(),
+        }
+    }
+}
+
+fn from_hex(hex: &str) -> BigUint {
+    BigUint::parse_bytes(hex.as_bytes(), 16).unwrap()
+}
+
+pub(crate) fn field_modulus() -> BigUint {
+    from_hex("fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f")
+}
+
+pub(crate) fn group_order() -> BigUint {
+    from_hex("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141")
+}
+
+pub(crate) const CURVE_B: u64 = 7;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Point {
+    pub(crate) x: BigUint,
+    pub(crate) y: BigUint,
+}
+
+pub(crate) fn generator() -> Point {
+    Point {
+        x: from_hex("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"),
+        y: from_hex("483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8"),
+    }
+}
+
+pub(crate) fn limbs_to_biguint(limbs: &[u64]) -> BigUint {
+    limbs.iter().rev().fold(BigUint::zero(), |acc, limb| {
+        (acc << 64) + BigUint::from(*limb)
+    })
+}
+
+pub(crate) fn biguint_to_limbs(value: &BigUint) -> [u64; SECP256K1_LIMBS] {
+    let mut limbs = [0; SECP256K1_LIMBS];
+
+    for (i, limb) in value.to_u64_digits().into_iter().enumerate() {
+        limbs[i] = limb;
+    }
+
+    limbs
+}
+
+// The inverse of a modulo the prime m, 0 has the inverse 0.
+pub(crate) fn inverse_mod(a: &BigUint, m: &BigUint) -> BigUint {
+    a.modpow(&(m - 2u64), m)
+}
+
+pub(crate) fn neg_mod(a: &BigUint, m: &BigUint) -> BigUint {
+    (m - a % m) % m
+}
+
+pub(crate) fn is_on_curve(point: &Point) -> bool {
+    let p = field_modulus();
+
+    point.x < p
+        && point.y < p
+        && (&point.y * &point.y) % &p == (&point.x * &point.x * &point.x + CURVE_B) % &p
+}
+
+// The sum of two points, None is the point at infinity.
+pub(crate) fn point_add(a: &Option<Point>, b: &Option<Point>) -> Option<Point> {
+    let p = field_modulus();
+
+    match (a, b) {
+        (None, _) => b.clone(),
+        (_, None) => a.clone(),
+        (Some(a), Some(b)) => {
+            let lambda = if a.x == b.x {
+                if (&a.y + &b.y) % &p == BigUint::zero() {
+                    return None;
+                }
+
+                &a.x * &a.x * 3u64 * inverse_mod(&(&a.y * 2u64), &p) % &p
+            } else {
+                (&b.y + neg_mod(&a.y, &p)) * inverse_mod(&(&b.x + neg_mod(&a.x, &p)), &p) % &p
+            };
+
+            let x = (&lambda * &lambda + neg_mod(&(&a.x + &b.x), &p)) % &p;
+            let y = (&lambda * (&a.x + neg_mod(&x, &p)) + neg_mod(&a.y, &p)) % &p;
+
+            Some(Point { x, y })
+        }
+    }
+}
+
+pub(crate) fn point_mul(k: &BigUint, point: &Option<Point>) -> Option<Point> {
+    let mut acc = None;
+
+    for i in (0..k.bits()).rev() {
+        acc = point_add(&acc, &acc);
+
+        if k.bit(i) {
+            acc = point_add(&acc, point);
+        }
+    }
+
+    acc
+}
+
+/*
+ * The point of the least x, its discrete log to the generator is unknown.
+ * The circuit accumulates the scalar multiplication from it so that the
+ * incomplete addition never meets the point at infinity.
+ */
+pub(crate) fn offset_point() -> Point {
+    let p = field_modulus();
+    let mut x = BigUint::one();
+
+    loop {
+        let y2 = (&x * &x * &x + CURVE_B) % &p;
+        let y = y2.modpow(&((&p + 1u64) >> 2), &p);
+
+        if (&y * &y) % &p == y2 {
+            return Point { x, y };
+        }
+
+        x += 1u64;
+    }
+}
+
+pub(crate) struct Secp256k1Signature {
+    pub(crate) hash: BigUint,
+    pub(crate) r: BigUint,
+    pub(crate) s: BigUint,
+    pub(crate) public_key: Point,
+}
+
+impl Secp256k1Signature {
+    pub(crate) fn from_limbs(input: &[u64; SECP256K1_INPUT_LIMBS]) -> Self {
+        let [hash, r, s, x, y] = [0, 1, 2, 3, 4]
+            .map(|i| limbs_to_biguint(&input[i * SECP256K1_LIMBS..(i + 1) * SECP256K1_LIMBS]));
+
+        Secp256k1Signature {
+            hash,
+            r,
+            s,
+            public_key: Point { x, y },
+        }
+    }
+
+    /*
+     * The hash is the big endian integer of the digest, it is not reduced
+     * before the verification.
+     */
+    pub(crate) fn verify(&self) -> bool {
+        let n = group_order();
+
+        if self.r.is_zero() || self.r >= n || self.s.is_zero() || self.s >= n {
+            return false;
+        }
+
+        if !is_on_curve(&self.public_key) {
+            return false;
+        }
+
+        let s_inv = inverse_mod(&self.s, &n);
+        let u1 = &self.hash * &s_inv % &n;
+        let u2 = &self.r * &s_inv % &n;
+
+        match point_add(
+            &point_mul(&u1, &Some(generator())),
+            &point_mul(&u2, &Some(self.public_key.clone())),
+        ) {
+            Some(point) => point.x % &n == self.r,
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::rc::Rc;
+
+use specs::{host_function::HostPlugin, types::ValueType};
+use wasmi::{RuntimeArgs, RuntimeValue, Trap};
+
+use crate::runtime::host::{host_env::HostEnv, ForeignContext, HostFunctionError};
+
+use super::{
+    Secp256k1HelperOp, Secp256k1Signature, SECP256K1_FOREIGN_FUNCTION_NAME_INPUT,
+    SECP256K1_FOREIGN_FUNCTION_NAME_VERIFY, SECP256K1_INPUT_LIMBS,
+};
+
+struct Context {
+    inputs: Vec<[u64; SECP256K1_INPUT_LIMBS]>,
+}
+
+impl Context {
+    fn new() -> Self {
+        Context { inputs: vec![] }
+    }
+
+    // The verifications are dense, a guest starts at most the next one.
+    fn signature(&mut self, signature: usize) -> Result<&mut [u64; SECP256K1_INPUT_LIMBS], Trap> {
+        if signature == self.inputs.len() {
+            self.inputs.push([0; SECP256K1_INPUT_LIMBS]);
+        }
+
+        let next = self.inputs.len();
+
+        self.inputs.get_mut(signature).ok_or_else(|| {
+            HostFunctionError::trap(format!(
+                "secp256k1 verification {} skips the verification {}",
+                signature, next
+            ))
+        })
+    }
+}
+
+impl ForeignContext for Context {}
+
+fn input(context: &mut Context, args: RuntimeArgs) -> Result<Option<RuntimeValue>, Trap> {
+    let index: u32 = args.nth(0);
+    let limb: u64 = args.nth(1);
+    let index = index as usize;
+
+    context.signature(index / SECP256K1_INPUT_LIMBS)?[index % SECP256K1_INPUT_LIMBS] = limb;
+
+    Ok(None)
+}
+
+fn verify(context: &mut Context, args: RuntimeArgs) -> Result<Option<RuntimeValue>, Trap> {
+    let index: u32 = args.nth(0);
+
+    if !Secp256k1Signature::from_limbs(context.signature(index as usize)?).verify() {
+        return Err(HostFunctionError::trap(format!(
+            "secp256k1 verification {} is invalid",
+            index
+        )));
+    }
+
+    Ok(None)
+}
+
+pub fn register_secp256k1_foreign(env: &mut HostEnv) {
+    env.internal_env
+        .register_plugin(HostPlugin::Secp256k1, Box::new(Context::new()));
+
+    env.internal_env.register_fallible_function(
+        SECP256K1_FOREIGN_FUNCTION_NAME_INPUT,
+        specs::host_function::Signature {
+            params: vec![ValueType::I32, ValueType::I64],
+            return_type: None,
+        },
+        HostPlugin::Secp256k1,
+        Secp256k1HelperOp::Input as usize,
+        Rc::new(|context: &mut dyn ForeignContext, args: RuntimeArgs| {
+            input(context.downcast_mut::<Context>().unwrap(), args)
+        }),
+    );
+
+    env.internal_env.register_fallible_function(
+        SECP256K1_FOREIGN_FUNCTION_NAME_VERIFY,
+        specs::host_function::Signature {
+            params: vec![ValueType::I32],
+            return_type: None,
+        },
+        HostPlugin::Secp256k1,
+        Secp256k1HelperOp::Verify as usize,
+        Rc::new(|context: &mut dyn ForeignContext, args: RuntimeArgs| {
+            verify(context.downcast_mut::<Context>().unwrap(), args)
+        }),
+    );
+}
@@ -0,0 +1,141 @@
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::{
+        circuits::config::CircuitLayout,
+        foreign::{
+            secp256k1_helper::{
+                biguint_to_limbs, generator, group_order, inverse_mod, point_mul,
+                runtime::register_secp256k1_foreign, Secp256k1Signature, SECP256K1_INPUT_LIMBS,
+                SECP256K1_LIMBS,
+            },
+            wasm_input_helper::runtime::register_wasm_input_foreign,
+        },
+        runtime::host::host_env::HostEnv,
+        test::test_circuit_with_env_and_layout,
+    };
+
+    use halo2_proofs::pairing::bn256::Fr as Fp;
+    use num_bigint::BigUint;
+
+    /*
+     * Verifies the signature whose limbs are the public inputs.
+     */
+    pub(crate) const SECP256K1_WAT: &str = r#"
+        (module
+          (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+          (import "env" "zkwasm_secp256k1_input" (func $secp256k1_input (param i32 i64)))
+          (import "env" "zkwasm_secp256k1_verify" (func $secp256k1_verify (param i32)))
+          (func (export "secp256k1_verify")
+            (local $i i32)
+            (loop $input
+              (call $secp256k1_input (local.get $i) (call $wasm_input (i32.const 1)))
+              (local.set $i (i32.add (local.get $i) (i32.const 1)))
+              (br_if $input (i32.lt_u (local.get $i) (i32.const 20))))
+            (call $secp256k1_verify (i32.const 0))))
+    "#;
+
+    // The input limbs of the signature of the hash by the private key with the nonce.
+    pub(crate) fn sign(
+        private_key: &BigUint,
+        nonce: &BigUint,
+        hash: &BigUint,
+    ) -> [u64; SECP256K1_INPUT_LIMBS] {
+        let n = group_order();
+        let r = point_mul(nonce, &Some(generator())).unwrap().x % &n;
+        let s = inverse_mod(nonce, &n) * (hash + &r * private_key) % &n;
+        let public_key = point_mul(private_key, &Some(generator())).unwrap();
+
+        let mut input = [0; SECP256K1_INPUT_LIMBS];
+        for (i, value) in [hash, &r, &s, &public_key.x, &public_key.y]
+            .iter()
+            .enumerate()
+        {
+            input[i * SECP256K1_LIMBS..(i + 1) * SECP256K1_LIMBS]
+                .copy_from_slice(&biguint_to_limbs(value));
+        }
+
+        input
+    }
+
+    fn signature() -> [u64; SECP256K1_INPUT_LIMBS] {
+        sign(
+            &BigUint::parse_bytes(
+                b"4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318",
+                16,
+            )
+            .unwrap(),
+            &BigUint::parse_bytes(
+                b"2b5f2bd42a9b5e8b8c7d7e2f4c1a6d3e9f0b1c2d3e4f5a6b7c8d9e0f1a2b3c4d",
+                16,
+            )
+            .unwrap(),
+            &BigUint::parse_bytes(
+                b"c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+                16,
+            )
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_secp256k1_verify() {
+        let mut input = signature();
+        assert!(Secp256k1Signature::from_limbs(&input).verify());
+
+        input[0] ^= 1;
+        assert!(!Secp256k1Signature::from_limbs(&input).verify());
+    }
+
+    #[test]
+    fn test_secp256k1_helper() {
+        let public_inputs = signature().to_vec();
+
+        let wasm = wabt::wat2wasm(SECP256K1_WAT).expect("failed to parse wat");
+
+        let mut env = HostEnv::new();
+        register_secp256k1_foreign(&mut env);
+        register_wasm_input_foreign(&mut env, public_inputs.clone(), vec![]);
+        env.finalize();
+
+        test_circuit_with_env_and_layout(
+            env,
+            wasm,
+            "secp256k1_verify",
+            public_inputs.into_iter().map(|v| Fp::from(v)).collect(),
+            CircuitLayout {
+                secp256k1_ratio: 2,
+                ..CircuitLayout::default()
+            },
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_secp256k1_helper_skipping_verification() {
+        let wasm = wabt::wat2wasm(
+            r#"
+            (module
+              (import "env" "zkwasm_secp256k1_input" (func $secp256k1_input (param i32 i64)))
+              (func (export "secp256k1_input")
+                (call $secp256k1_input (i32.const 0xffffffff) (i64.const 0))))
+            "#,
+        )
+        .expect("failed to parse wat");
+
+        let mut env = HostEnv::new();
+        register_secp256k1_foreign(&mut env);
+        env.finalize();
+
+        assert!(test_circuit_with_env_and_layout(
+            env,
+            wasm,
+            "secp256k1_input",
+            vec![],
+            CircuitLayout {
+                secp256k1_ratio: 2,
+                ..CircuitLayout::default()
+            },
+        )
+        .is_err());
+    }
+}
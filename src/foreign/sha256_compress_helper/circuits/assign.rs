@@ -0,0 +1,317 @@
+use super::{
+    Sha256CompressHelperEncode, Sha256CompressHelperTableConfig, BLOCK_LINES, OUTPUT_LINE_START,
+    ROUND_LINE_START, SCHEDULE_LINE_START, SLOTS,
+};
+use crate::circuits::utils::bn_to_field;
+use crate::foreign::sha256_compress_helper::{
+    ch, lsigma0, lsigma1, maj, sha256_compression, ssigma0, ssigma1, Sha256CompressHelperOp,
+    Sha256Compression, ROUND_CONSTANTS, SHA256_COMPRESS_INPUT_WORDS, SHA256_ROUNDS,
+    SHA256_STATE_WORDS,
+};
+use crate::foreign::sha256_helper::Sha256HelperOp;
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Region},
+    plonk::{Advice, Column, Error},
+};
+use num_bigint::BigUint;
+use specs::{etable::EventTableEntry, host_function::HostPlugin, step::StepInfo};
+
+pub struct Sha256CompressHelperTableChip<F: FieldExt> {
+    pub(crate) config: Sha256CompressHelperTableConfig<F>,
+    max_available_rows: usize,
+}
+
+/*
+ * The inputs of the compressions called by the words of a trace, a word
+ * never given is 0.
+ */
+pub(crate) fn sha256_compressions(
+    entries: &Vec<EventTableEntry>,
+) -> Vec<[u32; SHA256_COMPRESS_INPUT_WORDS]> {
+    let mut compressions = vec![];
+
+    for entry in entries {
+        if let StepInfo::CallHost {
+            plugin,
+            function_name,
+            args,
+            ..
+        } = &entry.step_info
+        {
+            assert_eq!(*plugin, HostPlugin::Sha256Compress);
+
+            let index = args[0] as usize;
+            let op = Sha256CompressHelperOp::from(function_name);
+            let compression = match op {
+                Sha256CompressHelperOp::Input => index / SHA256_COMPRESS_INPUT_WORDS,
+                Sha256CompressHelperOp::Output => index / SHA256_STATE_WORDS,
+            };
+
+            if compressions.len() <= compression {
+                compressions.resize(compression + 1, [0; SHA256_COMPRESS_INPUT_WORDS]);
+            }
+
+            if op == Sha256CompressHelperOp::Input {
+                compressions[compression][index % SHA256_COMPRESS_INPUT_WORDS] = args[1] as u32;
+            }
+        } else {
+            unreachable!()
+        }
+    }
+
+    compressions
+}
+
+/*
+ * The calls of the sha256 helper table looked up by the compressions of a trace.
+ */
+pub(crate) fn sha256_compress_helper_calls(
+    entries: &Vec<EventTableEntry>,
+) -> Vec<(Sha256HelperOp, Vec<u32>, u32)> {
+    sha256_compressions(entries)
+        .iter()
+        .flat_map(|input| sha256_compression(input).helper_calls())
+        .collect()
+}
+
+impl<F: FieldExt> Sha256CompressHelperTableChip<F> {
+    pub fn new(config: Sha256CompressHelperTableConfig<F>, max_available_rows: usize) -> Self {
+        Self {
+            config,
+            max_available_rows,
+        }
+    }
+
+    fn assign_word(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        col: Column<Advice>,
+        value: u32,
+    ) -> Result<(), Error> {
+        region.assign_advice(
+            || "sha256 compress helper word",
+            col,
+            offset,
+            || Ok(F::from(value as u64)),
+        )?;
+
+        Ok(())
+    }
+
+    /*
+     * Assigns a word of the a, e or w column with its u16 limbs.
+     */
+    fn assign_limbed_word(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        word: usize,
+        value: u32,
+    ) -> Result<(), Error> {
+        let col = [self.config.a, self.config.e, self.config.w][word];
+
+        self.assign_word(region, offset, col, value)?;
+        self.assign_word(region, offset, self.config.limbs[word][0], value & 0xffff)?;
+        self.assign_word(region, offset, self.config.limbs[word][1], value >> 16)?;
+
+        Ok(())
+    }
+
+    fn assign_fixed(&self, region: &mut Region<'_, F>, block: usize) -> Result<(), Error> {
+        for line in 0..BLOCK_LINES {
+            let offset = block * BLOCK_LINES + line;
+
+            region.assign_fixed(
+                || "sha256 compress helper sel",
+                self.config.sel,
+                offset,
+                || Ok(F::one()),
+            )?;
+
+            if line == 0 {
+                region.assign_fixed(
+                    || "sha256 compress helper block first line sel",
+                    self.config.block_first_line_sel,
+                    offset,
+                    || Ok(F::one()),
+                )?;
+            }
+
+            if line >= ROUND_LINE_START && line < OUTPUT_LINE_START {
+                region.assign_fixed(
+                    || "sha256 compress helper round sel",
+                    self.config.round_sel,
+                    offset,
+                    || Ok(F::one()),
+                )?;
+                region.assign_fixed(
+                    || "sha256 compress helper round constant",
+                    self.config.round_constant,
+                    offset,
+                    || Ok(F::from(ROUND_CONSTANTS[line - ROUND_LINE_START] as u64)),
+                )?;
+            }
+
+            if line >= SCHEDULE_LINE_START && line < OUTPUT_LINE_START {
+                region.assign_fixed(
+                    || "sha256 compress helper schedule sel",
+                    self.config.schedule_sel,
+                    offset,
+                    || Ok(F::one()),
+                )?;
+            }
+
+            if line < SLOTS {
+                let (op, index) = if line < SHA256_COMPRESS_INPUT_WORDS {
+                    (
+                        Sha256CompressHelperOp::Input,
+                        block * SHA256_COMPRESS_INPUT_WORDS + line,
+                    )
+                } else {
+                    (
+                        Sha256CompressHelperOp::Output,
+                        block * SHA256_STATE_WORDS + line - SHA256_COMPRESS_INPUT_WORDS,
+                    )
+                };
+
+                region.assign_fixed(
+                    || "sha256 compress helper slot sel",
+                    self.config.slot_sel,
+                    offset,
+                    || Ok(F::one()),
+                )?;
+                region.assign_fixed(
+                    || "sha256 compress helper slot prefix",
+                    self.config.slot_prefix,
+                    offset,
+                    || {
+                        Ok(bn_to_field(
+                            &Sha256CompressHelperEncode::encode_word_prefix::<BigUint>(
+                                op,
+                                index as u64,
+                            ),
+                        ))
+                    },
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn assign_compression(
+        &self,
+        region: &mut Region<'_, F>,
+        block: usize,
+        input: &[u32; SHA256_COMPRESS_INPUT_WORDS],
+    ) -> Result<(), Error> {
+        let offset = block * BLOCK_LINES;
+        let Sha256Compression { w, a, e, output } = sha256_compression(input);
+
+        for line in 0..BLOCK_LINES {
+            region.assign_advice(
+                || "sha256 compress helper enable",
+                self.config.enable,
+                offset + line,
+                || Ok(F::one()),
+            )?;
+        }
+
+        for r in 0..OUTPUT_LINE_START {
+            self.assign_limbed_word(region, offset + r, 0, a[r])?;
+            self.assign_limbed_word(region, offset + r, 1, e[r])?;
+        }
+
+        for t in 0..SHA256_ROUNDS {
+            let r = ROUND_LINE_START + t;
+            let s1 = lsigma1(e[r - 1]);
+            let ch = ch(e[r - 1], e[r - 2], e[r - 3]);
+            let s0 = lsigma0(a[r - 1]);
+            let maj = maj(a[r - 1], a[r - 2], a[r - 3]);
+            let t1 =
+                e[r - 4] as u64 + s1 as u64 + ch as u64 + ROUND_CONSTANTS[t] as u64 + w[t] as u64;
+            let t2 = s0 as u64 + maj as u64;
+
+            self.assign_limbed_word(region, offset + r, 2, w[t])?;
+            self.assign_word(region, offset + r, self.config.lsigma1, s1)?;
+            self.assign_word(region, offset + r, self.config.ch, ch)?;
+            self.assign_word(region, offset + r, self.config.lsigma0, s0)?;
+            self.assign_word(region, offset + r, self.config.maj, maj)?;
+            self.assign_word(
+                region,
+                offset + r,
+                self.config.carry_e.0,
+                ((a[r - 4] as u64 + t1) >> 32) as u32,
+            )?;
+            self.assign_word(
+                region,
+                offset + r,
+                self.config.carry_a.0,
+                ((t1 + t2) >> 32) as u32,
+            )?;
+
+            if r >= SCHEDULE_LINE_START {
+                let sigma0 = ssigma0(w[t - 15]);
+                let sigma1 = ssigma1(w[t - 2]);
+                let sum = sigma1 as u64 + w[t - 7] as u64 + sigma0 as u64 + w[t - 16] as u64;
+
+                self.assign_word(region, offset + r, self.config.ssigma0, sigma0)?;
+                self.assign_word(region, offset + r, self.config.ssigma1, sigma1)?;
+                self.assign_word(
+                    region,
+                    offset + r,
+                    self.config.carry_w.0,
+                    (sum >> 32) as u32,
+                )?;
+            }
+        }
+
+        for i in 0..SHA256_STATE_WORDS {
+            let line = OUTPUT_LINE_START + i;
+            let last = if i < 4 { a } else { e }[OUTPUT_LINE_START - 1 - i % 4];
+
+            self.assign_limbed_word(region, offset + line, 2, output[i])?;
+            self.assign_word(
+                region,
+                offset + line,
+                self.config.carry_w.0,
+                ((input[i] as u64 + last as u64) >> 32) as u32,
+            )?;
+        }
+
+        for (i, value) in input.iter().chain(output.iter()).enumerate() {
+            self.assign_word(region, offset + i, self.config.slot, *value)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        entries: &Vec<EventTableEntry>,
+    ) -> Result<(), Error> {
+        let compressions = sha256_compressions(entries);
+
+        assert!(compressions.len() * BLOCK_LINES <= self.max_available_rows);
+
+        layouter.assign_region(
+            || "sha256 compress helper assign",
+            |mut region| {
+                for block in 0..self.max_available_rows / BLOCK_LINES {
+                    self.assign_fixed(&mut region, block)?;
+                }
+
+                for (block, input) in compressions.iter().enumerate() {
+                    self.assign_compression(&mut region, block, input)?;
+                }
+
+                Ok(())
+            },
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,236 @@
+use super::{Sha256CompressHelperTableConfig, OUTPUT_LINE_START, ROUND_LINE_START};
+use crate::circuits::utils::bn_to_field;
+use crate::foreign::{
+    sha256_compress_helper::{SHA256_BLOCK_WORDS, SHA256_STATE_WORDS},
+    sha256_helper::{
+        circuits::{Sha256HelperTableConfig, Sha2HelperEncode},
+        Sha256HelperOp, SHA256_FOREIGN_TABLE_KEY,
+    },
+    ForeignTableConfig,
+};
+use crate::{constant_from, constant_from_bn, curr, fixed_curr, nextn, prev};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Advice, Column, ConstraintSystem, Expression, Fixed, VirtualCells},
+};
+use num_bigint::BigUint;
+
+fn word_carry<F: FieldExt>() -> Expression<F> {
+    constant_from_bn!(&(BigUint::from(1u64) << 32))
+}
+
+impl<F: FieldExt> Sha256CompressHelperTableConfig<F> {
+    /*
+     * Looks up a call of the sha256 helper table on the lines enabled by
+     * sel, the helper table constrains the bitwise functions on u4 limbs.
+     */
+    fn configure_helper_call(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        sha256_helper_table: &Sha256HelperTableConfig<F>,
+        sel: Column<Fixed>,
+        op: Sha256HelperOp,
+        args: &dyn Fn(&mut VirtualCells<'_, F>) -> Vec<Expression<F>>,
+        ret: Column<Advice>,
+    ) {
+        sha256_helper_table.configure_in_table(meta, SHA256_FOREIGN_TABLE_KEY, &|meta| {
+            fixed_curr!(meta, sel)
+                * curr!(meta, self.enable)
+                * Sha2HelperEncode::encode_opcode_expr(
+                    constant_from!(op),
+                    args(meta),
+                    curr!(meta, ret),
+                )
+        });
+    }
+
+    pub fn _configure(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        sha256_helper_table: &Sha256HelperTableConfig<F>,
+    ) {
+        meta.create_gate("sha256 compress helper enable", |meta| {
+            let enable = curr!(meta, self.enable);
+
+            vec![
+                fixed_curr!(meta, self.sel) * enable.clone() * (constant_from!(1) - enable.clone()),
+                fixed_curr!(meta, self.sel)
+                    * (constant_from!(1) - fixed_curr!(meta, self.block_first_line_sel))
+                    * (enable - prev!(meta, self.enable)),
+            ]
+        });
+
+        meta.create_gate("sha256 compress helper limbs", |meta| {
+            [self.a, self.e, self.w]
+                .iter()
+                .zip(self.limbs.iter())
+                .map(|(word, limbs)| {
+                    fixed_curr!(meta, self.sel)
+                        * (curr!(meta, *word)
+                            - curr!(meta, limbs[0])
+                            - curr!(meta, limbs[1]) * constant_from!(1u64 << 16))
+                })
+                .collect::<Vec<_>>()
+        });
+
+        /*
+         * e = d + t1 and a = t1 + t2 modulo 2^32, where
+         * t1 = h + Σ1(e) + Ch(e, f, g) + K + W and t2 = Σ0(a) + Maj(a, b, c).
+         */
+        meta.create_gate("sha256 compress helper round", |meta| {
+            let d = nextn!(meta, self.a, -4);
+            let h = nextn!(meta, self.e, -4);
+
+            let t1 = h
+                + curr!(meta, self.lsigma1)
+                + curr!(meta, self.ch)
+                + fixed_curr!(meta, self.round_constant)
+                + curr!(meta, self.w);
+            let t2 = curr!(meta, self.lsigma0) + curr!(meta, self.maj);
+
+            vec![
+                curr!(meta, self.e) + curr!(meta, self.carry_e.0) * word_carry() - d - t1.clone(),
+                curr!(meta, self.a) + curr!(meta, self.carry_a.0) * word_carry() - t1 - t2,
+            ]
+            .into_iter()
+            .map(|x| x * fixed_curr!(meta, self.round_sel) * curr!(meta, self.enable))
+            .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("sha256 compress helper schedule", |meta| {
+            vec![
+                fixed_curr!(meta, self.schedule_sel)
+                    * curr!(meta, self.enable)
+                    * (curr!(meta, self.w) + curr!(meta, self.carry_w.0) * word_carry()
+                        - curr!(meta, self.ssigma1)
+                        - nextn!(meta, self.w, -7)
+                        - curr!(meta, self.ssigma0)
+                        - nextn!(meta, self.w, -16)),
+            ]
+        });
+
+        /*
+         * The output words are the initial state plus the final state, the
+         * state words are read from the first lines of the block.
+         */
+        meta.create_gate("sha256 compress helper output", |meta| {
+            let mut constraints = vec![];
+
+            for i in 0..SHA256_STATE_WORDS {
+                let col = if i < 4 { self.a } else { self.e };
+                let line = OUTPUT_LINE_START + i;
+
+                constraints.push(
+                    nextn!(meta, self.w, line as i32)
+                        + nextn!(meta, self.carry_w.0, line as i32) * word_carry()
+                        - nextn!(meta, col, 3 - (i % 4) as i32)
+                        - nextn!(meta, col, (OUTPUT_LINE_START - 1 - i % 4) as i32),
+                );
+            }
+
+            constraints
+                .into_iter()
+                .map(|x| {
+                    x * fixed_curr!(meta, self.block_first_line_sel) * curr!(meta, self.enable)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("sha256 compress helper slots", |meta| {
+            let mut constraints = vec![];
+
+            for i in 0..SHA256_STATE_WORDS {
+                let col = if i < 4 { self.a } else { self.e };
+
+                constraints.push(
+                    nextn!(meta, self.slot, i as i32) - nextn!(meta, col, 3 - (i % 4) as i32),
+                );
+            }
+
+            for t in 0..SHA256_BLOCK_WORDS {
+                constraints.push(
+                    nextn!(meta, self.slot, (SHA256_STATE_WORDS + t) as i32)
+                        - nextn!(meta, self.w, (ROUND_LINE_START + t) as i32),
+                );
+            }
+
+            for i in 0..SHA256_STATE_WORDS {
+                constraints.push(
+                    nextn!(
+                        meta,
+                        self.slot,
+                        (SHA256_STATE_WORDS + SHA256_BLOCK_WORDS + i) as i32
+                    ) - nextn!(meta, self.w, (OUTPUT_LINE_START + i) as i32),
+                );
+            }
+
+            constraints
+                .into_iter()
+                .map(|x| {
+                    x * fixed_curr!(meta, self.block_first_line_sel) * curr!(meta, self.enable)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        self.configure_helper_call(
+            meta,
+            sha256_helper_table,
+            self.round_sel,
+            Sha256HelperOp::LSigma1,
+            &|meta| vec![nextn!(meta, self.e, -1)],
+            self.lsigma1,
+        );
+        self.configure_helper_call(
+            meta,
+            sha256_helper_table,
+            self.round_sel,
+            Sha256HelperOp::Ch,
+            &|meta| {
+                vec![
+                    nextn!(meta, self.e, -1),
+                    nextn!(meta, self.e, -2),
+                    nextn!(meta, self.e, -3),
+                ]
+            },
+            self.ch,
+        );
+        self.configure_helper_call(
+            meta,
+            sha256_helper_table,
+            self.round_sel,
+            Sha256HelperOp::LSigma0,
+            &|meta| vec![nextn!(meta, self.a, -1)],
+            self.lsigma0,
+        );
+        self.configure_helper_call(
+            meta,
+            sha256_helper_table,
+            self.round_sel,
+            Sha256HelperOp::Maj,
+            &|meta| {
+                vec![
+                    nextn!(meta, self.a, -1),
+                    nextn!(meta, self.a, -2),
+                    nextn!(meta, self.a, -3),
+                ]
+            },
+            self.maj,
+        );
+        self.configure_helper_call(
+            meta,
+            sha256_helper_table,
+            self.schedule_sel,
+            Sha256HelperOp::SSigma0,
+            &|meta| vec![nextn!(meta, self.w, -15)],
+            self.ssigma0,
+        );
+        self.configure_helper_call(
+            meta,
+            sha256_helper_table,
+            self.schedule_sel,
+            Sha256HelperOp::SSigma1,
+            &|meta| vec![nextn!(meta, self.w, -2)],
+            self.ssigma1,
+        );
+    }
+}
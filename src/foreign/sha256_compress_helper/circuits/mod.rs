@@ -0,0 +1,159 @@
+use super::{Sha256CompressHelperOp, SHA256_BLOCK_WORDS, SHA256_ROUNDS, SHA256_STATE_WORDS};
+use crate::{
+    circuits::rtable::RangeTableConfig,
+    curr, fixed_curr,
+    foreign::{sha256_helper::circuits::Sha256HelperTableConfig, ForeignTableConfig},
+    traits::circuits::bit_range_table::{BitRangeTable, U4Column},
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Advice, Column, ConstraintSystem, Expression, Fixed, VirtualCells},
+};
+use num_bigint::BigUint;
+use specs::encode::FromBn;
+use std::marker::PhantomData;
+
+pub mod assign;
+pub mod config;
+
+/*
+ * A compression takes a block of lines: the initial state on the lines 0..4,
+ * a line per round and the output words on the last 8 lines. The a and e
+ * words of the round t are on the line t + 4, so that the state of the round
+ * is read from the 4 previous lines.
+ */
+pub(crate) const ROUND_LINE_START: usize = 4;
+pub(crate) const SCHEDULE_LINE_START: usize = ROUND_LINE_START + SHA256_BLOCK_WORDS;
+pub(crate) const OUTPUT_LINE_START: usize = ROUND_LINE_START + SHA256_ROUNDS;
+pub(crate) const BLOCK_LINES: usize = OUTPUT_LINE_START + SHA256_STATE_WORDS;
+
+// The input words then the output words of a compression are looked up on the first lines.
+pub(crate) const SLOTS: usize = SHA256_STATE_WORDS + SHA256_BLOCK_WORDS + SHA256_STATE_WORDS;
+
+const OP_SHIFT: usize = 96;
+const INDEX_SHIFT: usize = 64;
+
+pub struct Sha256CompressHelperEncode();
+
+impl Sha256CompressHelperEncode {
+    pub(super) fn encode_word<T: FromBn>(op: T, index: T, value: T) -> T {
+        op * T::from_bn(&(BigUint::from(1u64) << OP_SHIFT))
+            + index * T::from_bn(&(BigUint::from(1u64) << INDEX_SHIFT))
+            + value
+    }
+
+    pub(super) fn encode_word_prefix<T: FromBn>(op: Sha256CompressHelperOp, index: u64) -> T {
+        Self::encode_word(
+            T::from_bn(&BigUint::from(op as u64)),
+            T::from_bn(&BigUint::from(index)),
+            T::from_bn(&BigUint::from(0u64)),
+        )
+    }
+}
+
+#[derive(Clone)]
+pub struct Sha256CompressHelperTableConfig<F: FieldExt> {
+    sel: Column<Fixed>,
+    block_first_line_sel: Column<Fixed>,
+    round_sel: Column<Fixed>,
+    schedule_sel: Column<Fixed>,
+    round_constant: Column<Fixed>,
+    slot_sel: Column<Fixed>,
+    slot_prefix: Column<Fixed>,
+
+    enable: Column<Advice>,
+    a: Column<Advice>,
+    e: Column<Advice>,
+    // The message schedule, the output words on the output lines.
+    w: Column<Advice>,
+    // The u16 limbs of a, e and w.
+    limbs: [[Column<Advice>; 2]; 3],
+    lsigma0: Column<Advice>,
+    lsigma1: Column<Advice>,
+    ch: Column<Advice>,
+    maj: Column<Advice>,
+    ssigma0: Column<Advice>,
+    ssigma1: Column<Advice>,
+    carry_a: U4Column,
+    carry_e: U4Column,
+    // The carry of the message schedule and of the output words.
+    carry_w: U4Column,
+    slot: Column<Advice>,
+
+    mark: PhantomData<F>,
+}
+
+impl<F: FieldExt> Sha256CompressHelperTableConfig<F> {
+    fn new(meta: &mut ConstraintSystem<F>, rtable: &RangeTableConfig<F>) -> Self {
+        let sel = meta.fixed_column();
+
+        let limbs = [0; 3].map(|_| {
+            [0; 2].map(|_| {
+                let limb = meta.advice_column();
+                rtable.configure_in_u16_range(meta, "sha256 compress helper limb", |meta| {
+                    fixed_curr!(meta, sel) * curr!(meta, limb)
+                });
+                limb
+            })
+        });
+        let [carry_a, carry_e, carry_w] = [0; 3].map(|_| {
+            rtable.u4_column(meta, "sha256 compress helper carry", |meta| {
+                fixed_curr!(meta, sel)
+            })
+        });
+
+        Self {
+            sel,
+            block_first_line_sel: meta.fixed_column(),
+            round_sel: meta.fixed_column(),
+            schedule_sel: meta.fixed_column(),
+            round_constant: meta.fixed_column(),
+            slot_sel: meta.fixed_column(),
+            slot_prefix: meta.fixed_column(),
+            enable: meta.advice_column(),
+            a: meta.advice_column(),
+            e: meta.advice_column(),
+            w: meta.advice_column(),
+            limbs,
+            lsigma0: meta.advice_column(),
+            lsigma1: meta.advice_column(),
+            ch: meta.advice_column(),
+            maj: meta.advice_column(),
+            ssigma0: meta.advice_column(),
+            ssigma1: meta.advice_column(),
+            carry_a,
+            carry_e,
+            carry_w,
+            slot: meta.advice_column(),
+            mark: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        rtable: &RangeTableConfig<F>,
+        sha256_helper_table: &Sha256HelperTableConfig<F>,
+    ) -> Self {
+        let config = Self::new(meta, rtable);
+        config._configure(meta, sha256_helper_table);
+        config
+    }
+}
+
+impl<F: FieldExt> ForeignTableConfig<F> for Sha256CompressHelperTableConfig<F> {
+    fn configure_in_table(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        key: &'static str,
+        expr: &dyn Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+    ) {
+        meta.lookup_any(key, |meta| {
+            vec![(
+                expr(meta),
+                fixed_curr!(meta, self.slot_sel)
+                    * curr!(meta, self.enable)
+                    * (fixed_curr!(meta, self.slot_prefix) + curr!(meta, self.slot)),
+            )]
+        });
+    }
+}
@@ -0,0 +1,157 @@
+use crate::foreign::sha256_helper::Sha256HelperOp;
+use strum_macros::EnumIter;
+
+pub mod circuits;
+pub mod etable_op_configure;
+pub mod runtime;
+pub mod test;
+
+pub const SHA256_COMPRESS_FOREIGN_TABLE_KEY: &'static str = "sha256-compress-helper-table";
+pub const SHA256_COMPRESS_FOREIGN_FUNCTION_NAME_INPUT: &'static str =
+    "zkwasm_sha256_compress_input";
+pub const SHA256_COMPRESS_FOREIGN_FUNCTION_NAME_OUTPUT: &'static str =
+    "zkwasm_sha256_compress_output";
+
+pub const SHA256_STATE_WORDS: usize = 8;
+pub const SHA256_BLOCK_WORDS: usize = 16;
+pub const SHA256_COMPRESS_INPUT_WORDS: usize = SHA256_STATE_WORDS + SHA256_BLOCK_WORDS;
+pub const SHA256_ROUNDS: usize = 64;
+
+/*
+ * A guest compresses a block word by word: the k-th compression takes the
+ * state words at the indices k * 24 + 0..8 and the message words at the
+ * indices k * 24 + 8..24, its output words have the indices k * 8 + 0..8. The
+ * input words of a compression must be given before its output words are read.
+ */
+#[derive(Clone, Copy, EnumIter, PartialEq)]
+pub enum Sha256CompressHelperOp {
+    Input = 1,
+    Output = 2,
+}
+
+impl From<&String> for Sha256CompressHelperOp {
+    fn from(function_name: &String) -> Self {
+        match function_name.as_str() {
+            SHA256_COMPRESS_FOREIGN_FUNCTION_NAME_INPUT => Sha256CompressHelperOp::Input,
+            SHA256_COMPRESS_FOREIGN_FUNCTION_NAME_OUTPUT => Sha256CompressHelperOp::Output,
+            _ => unreachable!(),
+        }
+    }
+}
+
+pub(crate) const ROUND_CONSTANTS: [u32; SHA256_ROUNDS] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+pub(crate) fn lsigma0(x: u32) -> u32 {
+    x.rotate_right(2) ^ x.rotate_right(13) ^ x.rotate_right(22)
+}
+
+pub(crate) fn lsigma1(x: u32) -> u32 {
+    x.rotate_right(6) ^ x.rotate_right(11) ^ x.rotate_right(25)
+}
+
+pub(crate) fn ssigma0(x: u32) -> u32 {
+    x.rotate_right(7) ^ x.rotate_right(18) ^ (x >> 3)
+}
+
+pub(crate) fn ssigma1(x: u32) -> u32 {
+    x.rotate_right(17) ^ x.rotate_right(19) ^ (x >> 10)
+}
+
+pub(crate) fn ch(x: u32, y: u32, z: u32) -> u32 {
+    z ^ (x & (y ^ z))
+}
+
+pub(crate) fn maj(x: u32, y: u32, z: u32) -> u32 {
+    (x & y) ^ (z & (x ^ y))
+}
+
+/*
+ * The intermediate values of a compression, the circuit holds each of them.
+ * The a and e words of the round t are at t + 4, preceded by the initial
+ * state: a[0..4] = [h3, h2, h1, h0] and e[0..4] = [h7, h6, h5, h4].
+ */
+pub(crate) struct Sha256Compression {
+    pub(crate) w: [u32; SHA256_ROUNDS],
+    pub(crate) a: [u32; SHA256_ROUNDS + 4],
+    pub(crate) e: [u32; SHA256_ROUNDS + 4],
+    pub(crate) output: [u32; SHA256_STATE_WORDS],
+}
+
+pub(crate) fn sha256_compression(input: &[u32; SHA256_COMPRESS_INPUT_WORDS]) -> Sha256Compression {
+    let mut w = [0u32; SHA256_ROUNDS];
+    w[..SHA256_BLOCK_WORDS].copy_from_slice(&input[SHA256_STATE_WORDS..]);
+    for t in SHA256_BLOCK_WORDS..SHA256_ROUNDS {
+        w[t] = ssigma1(w[t - 2])
+            .wrapping_add(w[t - 7])
+            .wrapping_add(ssigma0(w[t - 15]))
+            .wrapping_add(w[t - 16]);
+    }
+
+    let mut a = [0u32; SHA256_ROUNDS + 4];
+    let mut e = [0u32; SHA256_ROUNDS + 4];
+    for i in 0..4 {
+        a[i] = input[3 - i];
+        e[i] = input[7 - i];
+    }
+
+    for t in 0..SHA256_ROUNDS {
+        let r = t + 4;
+        let t1 = e[r - 4]
+            .wrapping_add(lsigma1(e[r - 1]))
+            .wrapping_add(ch(e[r - 1], e[r - 2], e[r - 3]))
+            .wrapping_add(ROUND_CONSTANTS[t])
+            .wrapping_add(w[t]);
+        let t2 = lsigma0(a[r - 1]).wrapping_add(maj(a[r - 1], a[r - 2], a[r - 3]));
+
+        e[r] = a[r - 4].wrapping_add(t1);
+        a[r] = t1.wrapping_add(t2);
+    }
+
+    let mut output = [0u32; SHA256_STATE_WORDS];
+    for i in 0..4 {
+        output[i] = input[i].wrapping_add(a[SHA256_ROUNDS + 3 - i]);
+        output[i + 4] = input[i + 4].wrapping_add(e[SHA256_ROUNDS + 3 - i]);
+    }
+
+    Sha256Compression { w, a, e, output }
+}
+
+impl Sha256Compression {
+    /*
+     * The calls of the sha256 helper table looked up by the compression: the
+     * two sigmas of the message schedule and the four functions of a round.
+     */
+    pub(crate) fn helper_calls(&self) -> Vec<(Sha256HelperOp, Vec<u32>, u32)> {
+        let mut calls = vec![];
+
+        for t in SHA256_BLOCK_WORDS..SHA256_ROUNDS {
+            let x = self.w[t - 15];
+            calls.push((Sha256HelperOp::SSigma0, vec![x], ssigma0(x)));
+
+            let x = self.w[t - 2];
+            calls.push((Sha256HelperOp::SSigma1, vec![x], ssigma1(x)));
+        }
+
+        for t in 0..SHA256_ROUNDS {
+            let r = t + 4;
+            let (a, b, c) = (self.a[r - 1], self.a[r - 2], self.a[r - 3]);
+            let (e, f, g) = (self.e[r - 1], self.e[r - 2], self.e[r - 3]);
+
+            calls.push((Sha256HelperOp::LSigma1, vec![e], lsigma1(e)));
+            calls.push((Sha256HelperOp::Ch, vec![e, f, g], ch(e, f, g)));
+            calls.push((Sha256HelperOp::LSigma0, vec![a], lsigma0(a)));
+            calls.push((Sha256HelperOp::Maj, vec![a, b, c], maj(a, b, c)));
+        }
+
+        calls
+    }
+}
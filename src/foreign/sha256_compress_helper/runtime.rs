@@ -0,0 +1,84 @@
+use std::rc::Rc;
+
+use specs::{host_function::HostPlugin, types::ValueType};
+use wasmi::{RuntimeArgs, RuntimeValue};
+
+use crate::runtime::host::{host_env::HostEnv, ForeignContext};
+
+use super::{
+    sha256_compression, Sha256CompressHelperOp, SHA256_COMPRESS_FOREIGN_FUNCTION_NAME_INPUT,
+    SHA256_COMPRESS_FOREIGN_FUNCTION_NAME_OUTPUT, SHA256_COMPRESS_INPUT_WORDS, SHA256_STATE_WORDS,
+};
+
+struct Context {
+    inputs: Vec<[u32; SHA256_COMPRESS_INPUT_WORDS]>,
+}
+
+impl Context {
+    fn new() -> Self {
+        Context { inputs: vec![] }
+    }
+
+    fn compression(&mut self, compression: usize) -> &mut [u32; SHA256_COMPRESS_INPUT_WORDS] {
+        if self.inputs.len() <= compression {
+            self.inputs
+                .resize(compression + 1, [0; SHA256_COMPRESS_INPUT_WORDS]);
+        }
+
+        &mut self.inputs[compression]
+    }
+}
+
+impl ForeignContext for Context {}
+
+fn input(context: &mut Context, args: RuntimeArgs) -> Option<RuntimeValue> {
+    let index: u32 = args.nth(0);
+    let value: u32 = args.nth(1);
+    let index = index as usize;
+
+    context.compression(index / SHA256_COMPRESS_INPUT_WORDS)[index % SHA256_COMPRESS_INPUT_WORDS] =
+        value;
+
+    None
+}
+
+fn output(context: &mut Context, args: RuntimeArgs) -> Option<RuntimeValue> {
+    let index: u32 = args.nth(0);
+    let index = index as usize;
+
+    let res = sha256_compression(context.compression(index / SHA256_STATE_WORDS)).output
+        [index % SHA256_STATE_WORDS];
+
+    Some(RuntimeValue::I32(res as i32))
+}
+
+pub fn register_sha256_compress_foreign(env: &mut HostEnv) {
+    env.internal_env
+        .register_plugin(HostPlugin::Sha256Compress, Box::new(Context::new()));
+
+    env.internal_env.register_function(
+        SHA256_COMPRESS_FOREIGN_FUNCTION_NAME_INPUT,
+        specs::host_function::Signature {
+            params: vec![ValueType::I32, ValueType::I32],
+            return_type: None,
+        },
+        HostPlugin::Sha256Compress,
+        Sha256CompressHelperOp::Input as usize,
+        Rc::new(|context: &mut dyn ForeignContext, args: RuntimeArgs| {
+            input(context.downcast_mut::<Context>().unwrap(), args)
+        }),
+    );
+
+    env.internal_env.register_function(
+        SHA256_COMPRESS_FOREIGN_FUNCTION_NAME_OUTPUT,
+        specs::host_function::Signature {
+            params: vec![ValueType::I32],
+            return_type: Some(specs::types::ValueType::I32),
+        },
+        HostPlugin::Sha256Compress,
+        Sha256CompressHelperOp::Output as usize,
+        Rc::new(|context: &mut dyn ForeignContext, args: RuntimeArgs| {
+            output(context.downcast_mut::<Context>().unwrap(), args)
+        }),
+    );
+}
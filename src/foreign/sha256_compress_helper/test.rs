@@ -0,0 +1,116 @@
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::{
+        foreign::{
+            keccak_helper::test::tests::etable_rows,
+            sha256_compress_helper::runtime::register_sha256_compress_foreign,
+            sha256_helper::{runtime::register_sha256_foreign, test::tests::prepare_inputs},
+            wasm_input_helper::runtime::register_wasm_input_foreign,
+        },
+        runtime::host::host_env::HostEnv,
+        test::test_circuit_with_env,
+    };
+
+    use halo2_proofs::pairing::bn256::Fr as Fp;
+    use std::fs;
+
+    /*
+     * SHA-256 of a message shorter than 56 bytes, the single padded block is
+     * compressed by the sha256 compress plugin from the initial hash value
+     * stored at 64.
+     */
+    pub(crate) const SHA256_COMPRESS_WAT: &str = r#"
+        (module
+          (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+          (import "env" "zkwasm_sha256_compress_input" (func $compress_input (param i32 i32)))
+          (import "env" "zkwasm_sha256_compress_output" (func $compress_output (param i32) (result i32)))
+          (memory 1)
+          (data (i32.const 64)
+            "\67\e6\09\6a\85\ae\67\bb\72\f3\6e\3c\3a\f5\4f\a5"
+            "\7f\52\0e\51\8c\68\05\9b\ab\d9\83\1f\19\cd\e0\5b")
+          (func $load_be (param $p i32) (result i32)
+            (i32.or
+              (i32.or
+                (i32.shl (i32.load8_u (local.get $p)) (i32.const 24))
+                (i32.shl (i32.load8_u offset=1 (local.get $p)) (i32.const 16)))
+              (i32.or
+                (i32.shl (i32.load8_u offset=2 (local.get $p)) (i32.const 8))
+                (i32.load8_u offset=3 (local.get $p)))))
+          (func (export "sha256_digest")
+            (local $len i32) (local $i i32) (local $word i32)
+            (local.set $len (i32.wrap_i64 (call $wasm_input (i32.const 0))))
+            (block $done
+              (loop $read
+                (br_if $done (i32.ge_u (local.get $i) (local.get $len)))
+                (i32.store8 (local.get $i) (i32.wrap_i64 (call $wasm_input (i32.const 0))))
+                (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                (br $read)))
+            (i32.store8 (local.get $len) (i32.const 0x80))
+            (i32.store8 (i32.const 62) (i32.shr_u (local.get $len) (i32.const 5)))
+            (i32.store8 (i32.const 63) (i32.shl (local.get $len) (i32.const 3)))
+            (local.set $i (i32.const 0))
+            (loop $state
+              (call $compress_input
+                (local.get $i)
+                (i32.load offset=64 (i32.shl (local.get $i) (i32.const 2))))
+              (local.set $i (i32.add (local.get $i) (i32.const 1)))
+              (br_if $state (i32.lt_u (local.get $i) (i32.const 8))))
+            (local.set $i (i32.const 0))
+            (loop $block
+              (call $compress_input
+                (i32.add (local.get $i) (i32.const 8))
+                (call $load_be (i32.shl (local.get $i) (i32.const 2))))
+              (local.set $i (i32.add (local.get $i) (i32.const 1)))
+              (br_if $block (i32.lt_u (local.get $i) (i32.const 16))))
+            (local.set $i (i32.const 0))
+            (loop $squeeze
+              (if (i32.eqz (i32.and (local.get $i) (i32.const 3)))
+                (then (local.set $word (call $compress_output (i32.shr_u (local.get $i) (i32.const 2))))))
+              (if (i64.ne
+                    (i64.extend_i32_u (i32.shr_u (local.get $word) (i32.const 24)))
+                    (call $wasm_input (i32.const 1)))
+                (then unreachable))
+              (local.set $word (i32.shl (local.get $word) (i32.const 8)))
+              (local.set $i (i32.add (local.get $i) (i32.const 1)))
+              (br_if $squeeze (i32.lt_u (local.get $i) (i32.const 32))))))
+    "#;
+
+    #[test]
+    fn test_sha256_compress_helper() {
+        let (public_inputs, private_inputs) = prepare_inputs();
+
+        let wasm = wabt::wat2wasm(SHA256_COMPRESS_WAT).expect("failed to parse wat");
+
+        let mut env = HostEnv::new();
+        register_sha256_compress_foreign(&mut env);
+        register_wasm_input_foreign(&mut env, public_inputs.clone(), private_inputs.clone());
+        env.finalize();
+
+        let execution_result = test_circuit_with_env(
+            env,
+            wasm,
+            "sha256_digest",
+            public_inputs.iter().map(|v| Fp::from(*v)).collect(),
+        )
+        .unwrap();
+        let compress_rows = execution_result
+            .tables
+            .execution_tables
+            .etable
+            .entries()
+            .len();
+
+        let mut env = HostEnv::new();
+        register_sha256_foreign(&mut env);
+        register_wasm_input_foreign(&mut env, public_inputs, private_inputs);
+        env.finalize();
+        let helper_rows = etable_rows(env, &fs::read("wasm/sha256.wasm").unwrap(), "sha256_digest");
+
+        assert!(
+            compress_rows * 2 < helper_rows,
+            "the sha256 compress plugin takes {} etable rows, the sha256 helper version {}",
+            compress_rows,
+            helper_rows
+        );
+    }
+}
@@ -1,25 +1,60 @@
 use super::{Sha256HelperTableConfig, Sha2HelperEncode, BLOCK_LINES, OP_ARGS_NUM};
-use crate::{circuits::config::max_sha256_rows, foreign::sha256_helper::Sha256HelperOp};
+use crate::foreign::sha256_helper::Sha256HelperOp;
 use halo2_proofs::{arithmetic::FieldExt, circuit::Layouter, plonk::Error};
 use specs::{etable::EventTableEntry, host_function::HostPlugin, step::StepInfo};
 
 pub struct Sha256HelperTableChip<F: FieldExt> {
     pub(crate) config: Sha256HelperTableConfig<F>,
+    max_available_rows: usize,
+}
+
+/*
+ * The op, the args and the result of each sha256 helper call of a trace.
+ */
+pub(crate) fn sha256_helper_calls(
+    entries: &Vec<EventTableEntry>,
+) -> Vec<(Sha256HelperOp, Vec<u32>, u32)> {
+    entries
+        .iter()
+        .map(|entry| {
+            if let StepInfo::CallHost {
+                plugin,
+                function_name,
+                args,
+                ret_val,
+                ..
+            } = &entry.step_info
+            {
+                assert_eq!(*plugin, HostPlugin::Sha256);
+
+                (
+                    Sha256HelperOp::from(function_name),
+                    args.iter().map(|arg| *arg as u32).collect(),
+                    ret_val.unwrap() as u32,
+                )
+            } else {
+                unreachable!()
+            }
+        })
+        .collect()
 }
 
 impl<F: FieldExt> Sha256HelperTableChip<F> {
-    pub fn new(config: Sha256HelperTableConfig<F>) -> Self {
-        Self { config }
+    pub fn new(config: Sha256HelperTableConfig<F>, max_available_rows: usize) -> Self {
+        Self {
+            config,
+            max_available_rows,
+        }
     }
     pub fn assign(
         &self,
         layouter: &mut impl Layouter<F>,
-        entry: &Vec<EventTableEntry>,
+        calls: &Vec<(Sha256HelperOp, Vec<u32>, u32)>,
     ) -> Result<(), Error> {
         layouter.assign_region(
             || "sha256 helper assign",
             |mut region| {
-                for i in 0..max_sha256_rows() as usize {
+                for i in 0..self.max_available_rows {
                     region.assign_fixed(
                         || "sha256 helper sel",
                         self.config.sel,
@@ -37,98 +72,83 @@ impl<F: FieldExt> Sha256HelperTableChip<F> {
                 }
 
                 // op args ret
-                for (block_i, step) in entry.iter().enumerate() {
-                    if let StepInfo::CallHost {
-                        plugin,
-                        function_name,
-                        args,
-                        ret_val,
-                        ..
-                    } = &step.step_info
-                    {
-                        assert_eq!(*plugin, HostPlugin::Sha256);
-
-                        let offset = block_i * BLOCK_LINES;
-                        let op = Sha256HelperOp::from(function_name);
-                        let args: Vec<u32> = args.iter().map(|arg| *arg as u32).collect();
-                        let ret = ret_val.unwrap() as u32;
-
-                        for i in 0..BLOCK_LINES {
-                            region.assign_advice(
-                                || "sha256 helper table",
-                                self.config.op.0,
-                                offset + i,
-                                || Ok(F::from(op as u64)),
-                            )?;
-                        }
+                for (block_i, (op, args, ret)) in calls.iter().enumerate() {
+                    let offset = block_i * BLOCK_LINES;
+                    let (op, ret) = (*op, *ret);
 
+                    for i in 0..BLOCK_LINES {
                         region.assign_advice(
-                            || "sha256 helper opcode",
-                            self.config.aux.0,
-                            offset,
-                            || Ok(Sha2HelperEncode::encode_opcode_f(op, &args, ret)),
+                            || "sha256 helper table",
+                            self.config.op.0,
+                            offset + i,
+                            || Ok(F::from(op as u64)),
                         )?;
+                    }
 
-                        region.assign_advice(
-                            || "sha256 helper enable",
-                            self.config.op_bit.0,
-                            offset,
-                            || Ok(F::from(1u64)),
-                        )?;
+                    region.assign_advice(
+                        || "sha256 helper opcode",
+                        self.config.aux.0,
+                        offset,
+                        || Ok(Sha2HelperEncode::encode_opcode_f(op, &args, ret)),
+                    )?;
 
-                        region.assign_advice(
-                            || "sha256 helper op bit",
-                            self.config.op_bit.0,
-                            offset + (op as usize),
-                            || Ok(F::from(1u64)),
-                        )?;
+                    region.assign_advice(
+                        || "sha256 helper enable",
+                        self.config.op_bit.0,
+                        offset,
+                        || Ok(F::from(1u64)),
+                    )?;
 
-                        let start = match op {
-                            Sha256HelperOp::SSigma0
-                            | Sha256HelperOp::SSigma1
-                            | Sha256HelperOp::LSigma0
-                            | Sha256HelperOp::LSigma1 => 0,
-                            Sha256HelperOp::Ch | Sha256HelperOp::Maj => 1,
-                        };
-
-                        for (arg_i, arg) in args.iter().enumerate() {
-                            for i in 0..8 {
-                                region.assign_advice(
-                                    || "sha256 helper args",
-                                    self.config.args[arg_i + start].0,
-                                    offset + i,
-                                    || Ok(F::from((arg >> (i * 4)) as u64 & 0xfu64)),
-                                )?;
-                            }
-                        }
+                    region.assign_advice(
+                        || "sha256 helper op bit",
+                        self.config.op_bit.0,
+                        offset + (op as usize),
+                        || Ok(F::from(1u64)),
+                    )?;
 
+                    let start = match op {
+                        Sha256HelperOp::SSigma0
+                        | Sha256HelperOp::SSigma1
+                        | Sha256HelperOp::LSigma0
+                        | Sha256HelperOp::LSigma1 => 0,
+                        Sha256HelperOp::Ch | Sha256HelperOp::Maj => 1,
+                    };
+
+                    for (arg_i, arg) in args.iter().enumerate() {
                         for i in 0..8 {
                             region.assign_advice(
-                                || "sha256 helper ret",
-                                self.config.args[OP_ARGS_NUM - 1].0,
+                                || "sha256 helper args",
+                                self.config.args[arg_i + start].0,
                                 offset + i,
-                                || Ok(F::from((ret >> (i * 4)) as u64 & 0xfu64)),
+                                || Ok(F::from((arg >> (i * 4)) as u64 & 0xfu64)),
                             )?;
                         }
+                    }
 
-                        match op {
-                            Sha256HelperOp::Ch => self.assign_ch(&mut region, offset, &args)?,
-                            Sha256HelperOp::Maj => self.assign_maj(&mut region, offset, &args)?,
-                            Sha256HelperOp::LSigma0 => {
-                                self.assign_lsigma0(&mut region, offset, &args)?
-                            }
-                            Sha256HelperOp::LSigma1 => {
-                                self.assign_lsigma1(&mut region, offset, &args)?
-                            }
-                            Sha256HelperOp::SSigma0 => {
-                                self.assign_ssigma0(&mut region, offset, &args)?
-                            }
-                            Sha256HelperOp::SSigma1 => {
-                                self.assign_ssigma1(&mut region, offset, &args)?
-                            }
+                    for i in 0..8 {
+                        region.assign_advice(
+                            || "sha256 helper ret",
+                            self.config.args[OP_ARGS_NUM - 1].0,
+                            offset + i,
+                            || Ok(F::from((ret >> (i * 4)) as u64 & 0xfu64)),
+                        )?;
+                    }
+
+                    match op {
+                        Sha256HelperOp::Ch => self.assign_ch(&mut region, offset, args)?,
+                        Sha256HelperOp::Maj => self.assign_maj(&mut region, offset, args)?,
+                        Sha256HelperOp::LSigma0 => {
+                            self.assign_lsigma0(&mut region, offset, args)?
+                        }
+                        Sha256HelperOp::LSigma1 => {
+                            self.assign_lsigma1(&mut region, offset, args)?
+                        }
+                        Sha256HelperOp::SSigma0 => {
+                            self.assign_ssigma0(&mut region, offset, args)?
+                        }
+                        Sha256HelperOp::SSigma1 => {
+                            self.assign_ssigma1(&mut region, offset, args)?
                         }
-                    } else {
-                        unreachable!()
                     }
                 }
 
@@ -18,12 +18,12 @@ pub mod expr;
 pub mod ops;
 
 const OP_ARGS_NUM: usize = 5;
-const BLOCK_LINES: usize = 10;
+pub(crate) const BLOCK_LINES: usize = 10;
 
 pub struct Sha2HelperEncode();
 
 impl Sha2HelperEncode {
-    pub(super) fn encode_opcode_expr<F: FieldExt>(
+    pub(crate) fn encode_opcode_expr<F: FieldExt>(
         op: Expression<F>,
         args: Vec<Expression<F>>,
         ret: Expression<F>,
@@ -58,7 +58,7 @@ pub struct ETableSha256HelperTableConfig {
 pub struct Sha256ForeignCallInfo {}
 impl ForeignCallInfo for Sha256ForeignCallInfo {
     fn call_id(&self) -> usize {
-        OpcodeClass::ForeignPluginStart as usize + HostPlugin::Sha256 as usize
+        OpcodeClass::ForeignPluginStart as usize + HostPlugin::Sha256.id()
     }
 }
 pub struct ETableSha256HelperTableConfigBuilder {}
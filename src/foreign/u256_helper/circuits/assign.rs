@@ -0,0 +1,363 @@
+use super::{
+    U256HelperEncode, U256HelperTableConfig, ARITH_LINES, BLOCK_LINES, CARRIES, CARRY_OFFSET_SHIFT,
+    LIMB_PARTS, OUTPUT_OPS,
+};
+use crate::circuits::utils::bn_to_field;
+use crate::foreign::u256_helper::{
+    biguint_to_limbs, limbs_to_biguint, u256_operation, U256HelperOp, U256_INPUT_LIMBS, U256_LIMBS,
+    U256_OUTPUT_LIMBS,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Region},
+    plonk::{Advice, Column, Error, Fixed},
+};
+use num_bigint::{BigInt, BigUint};
+use num_traits::{One, Zero};
+use specs::{etable::EventTableEntry, host_function::HostPlugin, step::StepInfo};
+
+pub struct U256HelperTableChip<F: FieldExt> {
+    pub(crate) config: U256HelperTableConfig<F>,
+    max_available_rows: usize,
+}
+
+/*
+ * The input limbs and the op of the operations called by a trace, a limb never
+ * given is 0 and an operation whose output is never read is an add.
+ */
+pub(crate) fn u256_operations(
+    entries: &Vec<EventTableEntry>,
+) -> Vec<([u64; U256_INPUT_LIMBS], U256HelperOp)> {
+    let mut operations = vec![];
+
+    for entry in entries {
+        if let StepInfo::CallHost {
+            plugin,
+            function_name,
+            args,
+            ..
+        } = &entry.step_info
+        {
+            assert_eq!(*plugin, HostPlugin::U256);
+
+            let index = args[0] as usize;
+            let op = U256HelperOp::from(function_name);
+            let operation = match op {
+                U256HelperOp::Input => index / U256_INPUT_LIMBS,
+                _ => index / U256_OUTPUT_LIMBS,
+            };
+
+            if operations.len() <= operation {
+                operations.resize(operation + 1, ([0; U256_INPUT_LIMBS], U256HelperOp::Add));
+            }
+
+            match op {
+                U256HelperOp::Input => operations[operation].0[index % U256_INPUT_LIMBS] = args[1],
+                _ => operations[operation].1 = op,
+            }
+        } else {
+            unreachable!()
+        }
+    }
+
+    operations
+}
+
+/*
+ * The values of x, y, z, s, t and w of an operation, see the bindings of the
+ * config.
+ */
+fn arith(op: U256HelperOp, input: &[u64; U256_INPUT_LIMBS]) -> [BigUint; 6] {
+    let [a, b, m] =
+        [0, 1, 2].map(|i| limbs_to_biguint(&input[i * U256_LIMBS..(i + 1) * U256_LIMBS]));
+    let out = limbs_to_biguint(&u256_operation(op, input).unwrap());
+    let low = &out % (BigUint::one() << 256);
+    let high = &out >> 256;
+
+    match op {
+        U256HelperOp::Add => [a, BigUint::one(), b, BigUint::zero(), BigUint::zero(), out],
+        U256HelperOp::Sub => [
+            low,
+            BigUint::one(),
+            b,
+            BigUint::zero(),
+            BigUint::zero(),
+            a + (high << 256),
+        ],
+        U256HelperOp::Mul => [a, b, BigUint::zero(), BigUint::zero(), BigUint::zero(), out],
+        U256HelperOp::DivMod => [a, BigUint::one(), BigUint::zero(), low, b, high],
+        U256HelperOp::MulMod => {
+            let q = (&a * &b) / &m;
+
+            [a, b, BigUint::zero(), q, m, low]
+        }
+        U256HelperOp::Input => unreachable!(),
+    }
+}
+
+impl<F: FieldExt> U256HelperTableChip<F> {
+    pub fn new(config: U256HelperTableConfig<F>, max_available_rows: usize) -> Self {
+        Self {
+            config,
+            max_available_rows,
+        }
+    }
+
+    fn assign_fixed(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        col: Column<Fixed>,
+        value: F,
+    ) -> Result<(), Error> {
+        region.assign_fixed(|| "u256 helper fixed", col, offset, || Ok(value))?;
+
+        Ok(())
+    }
+
+    fn assign_advice(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        col: Column<Advice>,
+        value: F,
+    ) -> Result<(), Error> {
+        region.assign_advice(|| "u256 helper advice", col, offset, || Ok(value))?;
+
+        Ok(())
+    }
+
+    /*
+     * Assigns the limbs of a value with their u16 parts on the lines of the
+     * block starting at offset, the limbs past the value are 0.
+     */
+    fn assign_limbs(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        col: Column<Advice>,
+        parts: &[Column<Advice>; LIMB_PARTS],
+        value: &BigUint,
+    ) -> Result<(), Error> {
+        let limbs = biguint_to_limbs::<ARITH_LINES>(value);
+
+        for line in 0..BLOCK_LINES {
+            let limb = limbs.get(line).cloned().unwrap_or(0);
+
+            self.assign_advice(region, offset + line, col, F::from(limb))?;
+
+            for (j, part) in parts.iter().enumerate() {
+                self.assign_advice(
+                    region,
+                    offset + line,
+                    *part,
+                    F::from((limb >> (16 * j)) & 0xffff),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // The carries of x * y + z - s * t - w = 0, shifted by 2^CARRY_OFFSET_SHIFT.
+    fn carries([x, y, z, s, t, w]: &[BigUint; 6]) -> Vec<BigUint> {
+        let [x, y, z, s, t, w] =
+            [x, y, z, s, t, w].map(|v| biguint_to_limbs::<ARITH_LINES>(v).map(BigInt::from));
+        let mut carry = BigInt::zero();
+        let mut carries = vec![];
+
+        for k in 0..CARRIES {
+            let mut sum = carry.clone();
+
+            for i in 0..ARITH_LINES {
+                if k >= i && k - i < U256_LIMBS {
+                    if i < U256_LIMBS {
+                        sum += &x[i] * &y[k - i];
+                    }
+                    sum -= &s[i] * &t[k - i];
+                }
+            }
+
+            if k < U256_LIMBS {
+                sum += &z[k];
+            }
+
+            if k < ARITH_LINES {
+                sum -= &w[k];
+            }
+
+            carry = sum >> 64;
+            carries.push(
+                (&carry + (BigInt::from(1u64) << CARRY_OFFSET_SHIFT))
+                    .to_biguint()
+                    .unwrap(),
+            );
+        }
+
+        carries
+    }
+
+    // Assigns an operation, a disabled block holds 0.
+    fn assign_operation(
+        &self,
+        region: &mut Region<'_, F>,
+        block: usize,
+        operation: Option<&([u64; U256_INPUT_LIMBS], U256HelperOp)>,
+    ) -> Result<(), Error> {
+        let offset = block * BLOCK_LINES;
+
+        for line in 0..BLOCK_LINES {
+            self.assign_fixed(region, offset + line, self.config.sel, F::one())?;
+
+            let (prefix_op, index) = if line < U256_INPUT_LIMBS {
+                (Some(U256HelperOp::Input), block * U256_INPUT_LIMBS + line)
+            } else {
+                self.assign_fixed(region, offset + line, self.config.output_sel, F::one())?;
+
+                (None, block * U256_OUTPUT_LIMBS + line - U256_INPUT_LIMBS)
+            };
+
+            self.assign_fixed(
+                region,
+                offset + line,
+                self.config.slot_prefix,
+                bn_to_field(&U256HelperEncode::encode_limb_prefix::<BigUint>(
+                    prefix_op,
+                    index as u64,
+                )),
+            )?;
+
+            self.assign_advice(
+                region,
+                offset + line,
+                self.config.enable,
+                if operation.is_some() {
+                    F::one()
+                } else {
+                    F::zero()
+                },
+            )?;
+
+            for (op, col) in OUTPUT_OPS.iter().zip(self.config.ops.iter()) {
+                let value = operation.map_or(false, |(_, operation_op)| operation_op == op);
+
+                self.assign_advice(
+                    region,
+                    offset + line,
+                    *col,
+                    if value { F::one() } else { F::zero() },
+                )?;
+            }
+        }
+        self.assign_fixed(region, offset, self.config.block_first_line_sel, F::one())?;
+
+        let (slots, values, carries) = match operation {
+            Some((input, op)) => {
+                let mut slots = input.to_vec();
+                slots.append(&mut u256_operation(*op, input).unwrap().to_vec());
+
+                let values = arith(*op, input);
+                let carries = Self::carries(&values);
+
+                (slots, values, carries)
+            }
+            None => (
+                vec![0; BLOCK_LINES],
+                [0; 6].map(|_| BigUint::zero()),
+                vec![BigUint::zero(); CARRIES],
+            ),
+        };
+
+        for (line, slot) in slots.iter().enumerate() {
+            self.assign_advice(region, offset + line, self.config.slot, F::from(*slot))?;
+        }
+
+        let [_, _, _, _, t, w] = &values;
+        let is_less_than = operation.map_or(false, |(_, op)| {
+            *op == U256HelperOp::DivMod || *op == U256HelperOp::MulMod
+        });
+        let e = if is_less_than {
+            t - 1u64 - w
+        } else {
+            BigUint::zero()
+        };
+
+        for (i, (col, value)) in [
+            self.config.x,
+            self.config.y,
+            self.config.z,
+            self.config.s,
+            self.config.t,
+            self.config.w,
+            self.config.e,
+        ]
+        .iter()
+        .zip(values.iter().chain([e.clone()].iter()))
+        .enumerate()
+        {
+            self.assign_limbs(region, offset, *col, &self.config.parts[i], value)?;
+        }
+
+        for line in 0..BLOCK_LINES {
+            for (i, col) in self.config.carries.iter().enumerate() {
+                let carry = carries
+                    .get(line * 2 + i)
+                    .cloned()
+                    .unwrap_or(BigUint::zero());
+
+                for (j, part) in col.iter().enumerate() {
+                    self.assign_advice(
+                        region,
+                        offset + line,
+                        *part,
+                        bn_to_field(&((&carry >> (16 * j)) & BigUint::from(0xffffu64))),
+                    )?;
+                }
+            }
+        }
+
+        // w + e + 1 = t limb by limb.
+        let mut e_carry = if is_less_than { 1u128 } else { 0 };
+        let w = biguint_to_limbs::<U256_LIMBS>(w);
+        let e = biguint_to_limbs::<U256_LIMBS>(&e);
+        for line in 0..BLOCK_LINES {
+            if is_less_than && line < U256_LIMBS {
+                e_carry = (w[line] as u128 + e[line] as u128 + e_carry) >> 64;
+            } else {
+                e_carry = 0;
+            }
+
+            self.assign_advice(
+                region,
+                offset + line,
+                self.config.e_carry.0,
+                F::from(e_carry as u64),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        entries: &Vec<EventTableEntry>,
+    ) -> Result<(), Error> {
+        let operations = u256_operations(entries);
+
+        assert!(operations.len() * BLOCK_LINES <= self.max_available_rows);
+
+        layouter.assign_region(
+            || "u256 helper assign",
+            |mut region| {
+                for block in 0..self.max_available_rows / BLOCK_LINES {
+                    self.assign_operation(&mut region, block, operations.get(block))?;
+                }
+
+                Ok(())
+            },
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,314 @@
+use super::{U256HelperTableConfig, ARITH_LINES, CARRIES, CARRY_OFFSET_SHIFT, OUTPUT_OPS};
+use crate::circuits::utils::bn_to_field;
+use crate::foreign::u256_helper::{U256HelperOp, U256_LIMBS};
+use crate::{constant_from, constant_from_bn, curr, fixed_curr, nextn, prev};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Advice, Column, ConstraintSystem, Expression, VirtualCells},
+};
+use num_bigint::BigUint;
+
+fn limb_carry<F: FieldExt>() -> Expression<F> {
+    constant_from_bn!(&(BigUint::from(1u64) << 64))
+}
+
+// The slots of the operation.
+#[derive(Clone, Copy)]
+enum Operand {
+    In0,
+    In1,
+    In2,
+    Out,
+}
+
+// The value an op binds to a limb of x, y, z, s, t or w.
+enum Binding {
+    Zero,
+    One,
+    Slot(Operand, usize),
+}
+
+#[derive(Clone, Copy)]
+enum Role {
+    X,
+    Y,
+    Z,
+    S,
+    T,
+    W,
+}
+
+/*
+ * The limb i of a role bound by an op, None if it is free. The limbs 4..8 of
+ * x, y, z and t are never read.
+ */
+fn binding(op: U256HelperOp, role: Role, i: usize) -> Option<Binding> {
+    use Binding::*;
+    use Operand::*;
+    use U256HelperOp::*;
+
+    let low = |operand| {
+        if i < U256_LIMBS {
+            Slot(operand, i)
+        } else {
+            Zero
+        }
+    };
+    let one = if i == 0 { One } else { Zero };
+
+    let binding = match role {
+        Role::X => match op {
+            Sub => Slot(Out, i),
+            _ => Slot(In0, i),
+        },
+        Role::Y => match op {
+            Mul | MulMod => Slot(In1, i),
+            _ => one,
+        },
+        Role::Z => match op {
+            Add | Sub => Slot(In1, i),
+            _ => Zero,
+        },
+        Role::S => match op {
+            DivMod => low(Out),
+            MulMod => return None,
+            _ => Zero,
+        },
+        Role::T => match op {
+            DivMod => Slot(In1, i),
+            MulMod => Slot(In2, i),
+            _ => Zero,
+        },
+        Role::W => match op {
+            Add | Mul => Slot(Out, i),
+            Sub if i == U256_LIMBS => Slot(Out, i),
+            Sub => low(In0),
+            DivMod if i < U256_LIMBS => Slot(Out, U256_LIMBS + i),
+            DivMod => Zero,
+            MulMod => low(Out),
+            Input => unreachable!(),
+        },
+    };
+
+    Some(binding)
+}
+
+impl<F: FieldExt> U256HelperTableConfig<F> {
+    // The carry k of the operation starting on the current line.
+    fn carry(&self, meta: &mut VirtualCells<'_, F>, k: usize) -> Expression<F> {
+        let shifted =
+            self.carries[k % 2]
+                .iter()
+                .enumerate()
+                .fold(constant_from!(0), |acc, (i, part)| {
+                    acc + nextn!(meta, *part, (k / 2) as i32)
+                        * constant_from_bn!(&(BigUint::from(1u64) << (16 * i)))
+                });
+
+        shifted - constant_from_bn!(&(BigUint::from(1u64) << CARRY_OFFSET_SHIFT))
+    }
+
+    fn slot(&self, meta: &mut VirtualCells<'_, F>, operand: Operand, i: usize) -> Expression<F> {
+        let line = match operand {
+            Operand::In0 => i,
+            Operand::In1 => U256_LIMBS + i,
+            Operand::In2 => 2 * U256_LIMBS + i,
+            Operand::Out => 3 * U256_LIMBS + i,
+        };
+
+        nextn!(meta, self.slot, line as i32)
+    }
+
+    /*
+     * The limbs bound by an op:
+     *   add: a * 1 + b = 0 * 0 + out
+     *   sub: out * 1 + b = 0 * 0 + a + borrow * 2^256
+     *   mul: a * b + 0 = 0 * 0 + out
+     *   divmod: a * 1 + 0 = quotient * b + remainder
+     *   mulmod: a * b + 0 = q * m + out
+     * The output limbs not bound to an operand are 0.
+     */
+    fn bindings(&self, op: U256HelperOp) -> Vec<(Column<Advice>, usize, Binding)> {
+        let mut bindings = vec![];
+
+        for (col, role, lines) in [
+            (self.x, Role::X, U256_LIMBS),
+            (self.y, Role::Y, U256_LIMBS),
+            (self.z, Role::Z, U256_LIMBS),
+            (self.s, Role::S, ARITH_LINES),
+            (self.t, Role::T, U256_LIMBS),
+            (self.w, Role::W, ARITH_LINES),
+        ] {
+            for i in 0..lines {
+                if let Some(binding) = binding(op, role, i) {
+                    bindings.push((col, i, binding));
+                }
+            }
+        }
+
+        let unbound_output = match op {
+            U256HelperOp::Sub => U256_LIMBS + 1..ARITH_LINES,
+            U256HelperOp::MulMod => U256_LIMBS..ARITH_LINES,
+            _ => 0..0,
+        };
+        for i in unbound_output {
+            bindings.push((self.slot, 3 * U256_LIMBS + i, Binding::Zero));
+        }
+
+        bindings
+    }
+
+    pub fn _configure(&self, meta: &mut ConstraintSystem<F>) {
+        meta.create_gate("u256 helper enable", |meta| {
+            let mut constraints = vec![];
+
+            for col in [self.enable].iter().chain(self.ops.iter()) {
+                let value = curr!(meta, *col);
+
+                constraints.push(
+                    fixed_curr!(meta, self.sel)
+                        * value.clone()
+                        * (constant_from!(1) - value.clone()),
+                );
+                constraints.push(
+                    fixed_curr!(meta, self.sel)
+                        * (constant_from!(1) - fixed_curr!(meta, self.block_first_line_sel))
+                        * (value - prev!(meta, *col)),
+                );
+            }
+
+            let ops = self
+                .ops
+                .iter()
+                .fold(constant_from!(0), |acc, col| acc + curr!(meta, *col));
+            constraints.push(fixed_curr!(meta, self.sel) * (ops - curr!(meta, self.enable)));
+
+            constraints
+        });
+
+        meta.create_gate("u256 helper limbs", |meta| {
+            [self.x, self.y, self.z, self.s, self.t, self.w, self.e]
+                .iter()
+                .zip(self.parts.iter())
+                .map(|(limb, parts)| {
+                    let sum = parts
+                        .iter()
+                        .enumerate()
+                        .fold(constant_from!(0), |acc, (i, part)| {
+                            acc + curr!(meta, *part) * constant_from!(1u64 << (16 * i))
+                        });
+
+                    fixed_curr!(meta, self.sel) * (curr!(meta, *limb) - sum)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("u256 helper bindings", |meta| {
+            let mut constraints = vec![];
+
+            for (op, op_col) in OUTPUT_OPS.iter().zip(self.ops.iter()) {
+                for (col, line, binding) in self.bindings(*op) {
+                    let value = match binding {
+                        Binding::Zero => constant_from!(0),
+                        Binding::One => constant_from!(1),
+                        Binding::Slot(operand, i) => self.slot(meta, operand, i),
+                    };
+
+                    constraints.push(
+                        fixed_curr!(meta, self.block_first_line_sel)
+                            * curr!(meta, *op_col)
+                            * (nextn!(meta, col, line as i32) - value),
+                    );
+                }
+            }
+
+            constraints
+        });
+
+        /*
+         * x * y + z - s * t - w = 0 limb by limb: the limb k plus the carry
+         * k - 1 is the carry k shifted by 64 bits, the carries are small
+         * enough for the sums not to wrap around the field.
+         */
+        meta.create_gate("u256 helper arith", |meta| {
+            let mut constraints = vec![];
+
+            for k in 0..CARRIES + 1 {
+                let mut sum = constant_from!(0);
+
+                for i in 0..ARITH_LINES {
+                    if k >= i && k - i < U256_LIMBS {
+                        let j = k - i;
+
+                        if i < U256_LIMBS {
+                            sum = sum
+                                + nextn!(meta, self.x, i as i32) * nextn!(meta, self.y, j as i32);
+                        }
+                        sum = sum - nextn!(meta, self.s, i as i32) * nextn!(meta, self.t, j as i32);
+                    }
+                }
+
+                if k < U256_LIMBS {
+                    sum = sum + nextn!(meta, self.z, k as i32);
+                }
+
+                if k < ARITH_LINES {
+                    sum = sum - nextn!(meta, self.w, k as i32);
+                }
+
+                if k > 0 {
+                    sum = sum + self.carry(meta, k - 1);
+                }
+
+                if k < CARRIES {
+                    sum = sum - self.carry(meta, k) * limb_carry();
+                }
+
+                constraints.push(sum);
+            }
+
+            constraints
+                .into_iter()
+                .map(|x| {
+                    x * fixed_curr!(meta, self.block_first_line_sel) * curr!(meta, self.enable)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        // w + e = t - 1 limb by limb for divmod and mulmod.
+        meta.create_gate("u256 helper less than", |meta| {
+            let mut constraints = vec![];
+
+            for i in 0..U256_LIMBS {
+                let mut sum = nextn!(meta, self.w, i as i32) + nextn!(meta, self.e, i as i32)
+                    - nextn!(meta, self.t, i as i32);
+
+                if i == 0 {
+                    sum = sum + constant_from!(1);
+                } else {
+                    sum = sum + nextn!(meta, self.e_carry.0, i as i32 - 1);
+                }
+
+                if i < U256_LIMBS - 1 {
+                    sum = sum - nextn!(meta, self.e_carry.0, i as i32) * limb_carry();
+                }
+
+                constraints.push(sum);
+            }
+
+            let is_less_than = [U256HelperOp::DivMod, U256HelperOp::MulMod]
+                .iter()
+                .map(|op| {
+                    let i = OUTPUT_OPS.iter().position(|x| x == op).unwrap();
+                    curr!(meta, self.ops[i])
+                })
+                .reduce(|acc, x| acc + x)
+                .unwrap();
+
+            constraints
+                .into_iter()
+                .map(|x| x * fixed_curr!(meta, self.block_first_line_sel) * is_less_than.clone())
+                .collect::<Vec<_>>()
+        });
+    }
+}
@@ -0,0 +1,175 @@
+use super::{U256HelperOp, U256_INPUT_LIMBS, U256_LIMBS, U256_OUTPUT_LIMBS};
+use crate::{
+    circuits::{rtable::RangeTableConfig, utils::bn_to_field},
+    constant_from, constant_from_bn, curr, fixed_curr,
+    foreign::ForeignTableConfig,
+    traits::circuits::bit_range_table::{BitColumn, BitRangeTable},
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Advice, Column, ConstraintSystem, Expression, Fixed, VirtualCells},
+};
+use num_bigint::BigUint;
+use specs::encode::FromBn;
+use std::marker::PhantomData;
+
+pub mod assign;
+pub mod config;
+
+/*
+ * An operation takes a block of lines: its input limbs then its output limbs
+ * are looked up on the slot of the lines. The arithmetic takes the first
+ * ARITH_LINES lines, the line i holds the limb i of each operand.
+ */
+pub(crate) const BLOCK_LINES: usize = U256_INPUT_LIMBS + U256_OUTPUT_LIMBS;
+pub(crate) const ARITH_LINES: usize = U256_OUTPUT_LIMBS;
+
+// The u16 parts of a limb.
+pub(crate) const LIMB_PARTS: usize = 4;
+/*
+ * A carry of the product is less than 2^67 in absolute value, it is shifted
+ * by CARRY_OFFSET and held by 5 u16 parts.
+ */
+pub(crate) const CARRY_PARTS: usize = 5;
+pub(crate) const CARRY_OFFSET_SHIFT: usize = 68;
+// The carries of the 11 limbs of s * t, the last one is 0.
+pub(crate) const CARRIES: usize = ARITH_LINES + U256_LIMBS - 2;
+
+// The ops reading the output, in the order of the op columns.
+pub(crate) const OUTPUT_OPS: [U256HelperOp; 5] = [
+    U256HelperOp::Add,
+    U256HelperOp::Sub,
+    U256HelperOp::Mul,
+    U256HelperOp::DivMod,
+    U256HelperOp::MulMod,
+];
+
+const OP_SHIFT: usize = 96;
+const INDEX_SHIFT: usize = 64;
+
+pub struct U256HelperEncode();
+
+impl U256HelperEncode {
+    pub(super) fn encode_limb<T: FromBn>(op: T, index: T, value: T) -> T {
+        op * T::from_bn(&(BigUint::from(1u64) << OP_SHIFT))
+            + index * T::from_bn(&(BigUint::from(1u64) << INDEX_SHIFT))
+            + value
+    }
+
+    // The prefix of an output limb leaves the op to the op columns.
+    pub(super) fn encode_limb_prefix<T: FromBn>(op: Option<U256HelperOp>, index: u64) -> T {
+        Self::encode_limb(
+            T::from_bn(&BigUint::from(op.map_or(0, |op| op as u64))),
+            T::from_bn(&BigUint::from(index)),
+            T::from_bn(&BigUint::from(0u64)),
+        )
+    }
+}
+
+/*
+ * The operation is x * y + z = s * t + w on the arithmetic lines, x, y, z and
+ * t are 4 limbs, s and w are 8 limbs. The op columns bind them to the slots.
+ */
+#[derive(Clone)]
+pub struct U256HelperTableConfig<F: FieldExt> {
+    sel: Column<Fixed>,
+    block_first_line_sel: Column<Fixed>,
+    output_sel: Column<Fixed>,
+    slot_prefix: Column<Fixed>,
+
+    enable: Column<Advice>,
+    // Whether the block runs the op of OUTPUT_OPS.
+    ops: [Column<Advice>; 5],
+    slot: Column<Advice>,
+    x: Column<Advice>,
+    y: Column<Advice>,
+    z: Column<Advice>,
+    s: Column<Advice>,
+    t: Column<Advice>,
+    w: Column<Advice>,
+    // w + e = t - 1 for divmod and mulmod, so that w < t.
+    e: Column<Advice>,
+    // The u16 parts of the limbs of x, y, z, s, t, w and e.
+    parts: [[Column<Advice>; LIMB_PARTS]; 7],
+    carries: [[Column<Advice>; CARRY_PARTS]; 2],
+    e_carry: BitColumn,
+
+    mark: PhantomData<F>,
+}
+
+impl<F: FieldExt> U256HelperTableConfig<F> {
+    fn new(meta: &mut ConstraintSystem<F>, rtable: &RangeTableConfig<F>) -> Self {
+        let sel = meta.fixed_column();
+
+        let mut u16_column = || {
+            let col = meta.advice_column();
+            rtable.configure_in_u16_range(meta, "u256 helper u16 part", |meta| {
+                fixed_curr!(meta, sel) * curr!(meta, col)
+            });
+            col
+        };
+        let parts = [0; 7].map(|_| [0; LIMB_PARTS].map(|_| u16_column()));
+        let carries = [0; 2].map(|_| [0; CARRY_PARTS].map(|_| u16_column()));
+
+        let e_carry = rtable.bit_column(meta, "u256 helper e carry", |meta| fixed_curr!(meta, sel));
+
+        Self {
+            sel,
+            block_first_line_sel: meta.fixed_column(),
+            output_sel: meta.fixed_column(),
+            slot_prefix: meta.fixed_column(),
+            enable: meta.advice_column(),
+            ops: [0; 5].map(|_| meta.advice_column()),
+            slot: meta.advice_column(),
+            x: meta.advice_column(),
+            y: meta.advice_column(),
+            z: meta.advice_column(),
+            s: meta.advice_column(),
+            t: meta.advice_column(),
+            w: meta.advice_column(),
+            e: meta.advice_column(),
+            parts,
+            carries,
+            e_carry,
+            mark: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, rtable: &RangeTableConfig<F>) -> Self {
+        let config = Self::new(meta, rtable);
+        config._configure(meta);
+        config
+    }
+
+    // The op of the current line.
+    fn op(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        OUTPUT_OPS
+            .iter()
+            .zip(self.ops.iter())
+            .fold(constant_from!(0), |acc, (op, col)| {
+                acc + curr!(meta, *col) * constant_from!(*op as u64)
+            })
+    }
+}
+
+impl<F: FieldExt> ForeignTableConfig<F> for U256HelperTableConfig<F> {
+    fn configure_in_table(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        key: &'static str,
+        expr: &dyn Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+    ) {
+        meta.lookup_any(key, |meta| {
+            vec![(
+                expr(meta),
+                fixed_curr!(meta, self.sel)
+                    * curr!(meta, self.enable)
+                    * (fixed_curr!(meta, self.slot_prefix)
+                        + fixed_curr!(meta, self.output_sel)
+                            * self.op(meta)
+                            * constant_from_bn!(&(BigUint::from(1u64) << OP_SHIFT))
+                        + curr!(meta, self.slot)),
+            )]
+        });
+    }
+}
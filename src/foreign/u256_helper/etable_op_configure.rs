@@ -0,0 +1,241 @@
+use super::{
+    circuits::{U256HelperEncode, OUTPUT_OPS},
+    U256HelperOp, U256_FOREIGN_FUNCTION_NAME_INPUT, U256_FOREIGN_TABLE_KEY,
+};
+use crate::{
+    circuits::{
+        etable_compact::{
+            op_configure::{
+                BitCell, ConstraintBuilder, EventTableCellAllocator, EventTableOpcodeConfig,
+                MTableLookupCell, U64Cell,
+            },
+            EventTableCommonConfig, MLookupItem, StepStatus,
+        },
+        mtable_compact::encode::MemoryTableLookupEncode,
+        utils::{bn_to_field, Context},
+    },
+    constant_from, constant_from_bn,
+    foreign::{EventTableForeignCallConfigBuilder, ForeignCallInfo},
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Error, Expression, VirtualCells},
+};
+use num_bigint::BigUint;
+use specs::step::StepInfo;
+use specs::{
+    etable::EventTableEntry,
+    itable::{OpcodeClass, OPCODE_CLASS_SHIFT},
+};
+use specs::{host_function::HostPlugin, mtable::VarType};
+
+pub struct ETableU256HelperTableConfig {
+    foreign_call_id: u64,
+
+    index: U64Cell,
+    value: U64Cell,
+    is_input: BitCell,
+    // Whether the call reads the output of the op of OUTPUT_OPS.
+    is_ops: [BitCell; 5],
+
+    lookup_stack_read_index: MTableLookupCell,
+    lookup_stack_value: MTableLookupCell,
+}
+
+pub struct U256ForeignCallInfo {}
+impl ForeignCallInfo for U256ForeignCallInfo {
+    fn call_id(&self) -> usize {
+        OpcodeClass::ForeignPluginStart as usize + HostPlugin::U256.id()
+    }
+}
+pub struct ETableU256HelperTableConfigBuilder {}
+
+impl<F: FieldExt> EventTableForeignCallConfigBuilder<F> for ETableU256HelperTableConfigBuilder {
+    fn configure(
+        common: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+        info: &impl ForeignCallInfo,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let index = common.alloc_u64();
+        let value = common.alloc_u64();
+
+        let is_input = common.alloc_bit_value();
+        let is_ops = [0; 5].map(|_| common.alloc_bit_value());
+
+        let lookup_stack_read_index = common.alloc_mtable_lookup();
+        let lookup_stack_value = common.alloc_mtable_lookup();
+
+        constraint_builder.push(
+            "u256helper: is one of ops",
+            Box::new(move |meta| {
+                vec![
+                    is_ops
+                        .iter()
+                        .fold(is_input.expr(meta), |acc, is_op| acc + is_op.expr(meta))
+                        - constant_from!(1),
+                ]
+            }),
+        );
+
+        constraint_builder.lookup(
+            U256_FOREIGN_TABLE_KEY,
+            "u256 helper table lookup",
+            Box::new(move |meta| {
+                let op = OUTPUT_OPS.iter().zip(is_ops.iter()).fold(
+                    is_input.expr(meta) * constant_from!(U256HelperOp::Input),
+                    |acc, (op, is_op)| acc + is_op.expr(meta) * constant_from!(*op),
+                );
+
+                U256HelperEncode::encode_limb(op, index.expr(meta), value.expr(meta))
+            }),
+        );
+
+        Box::new(ETableU256HelperTableConfig {
+            foreign_call_id: info.call_id() as u64,
+            index,
+            value,
+            is_input,
+            is_ops,
+            lookup_stack_read_index,
+            lookup_stack_value,
+        })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for ETableU256HelperTableConfig {
+    fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        let pick_one = OUTPUT_OPS.iter().zip(self.is_ops.iter()).fold(
+            self.is_input.expr(meta) * constant_from!(U256HelperOp::Input),
+            |acc, (op, is_op)| acc + is_op.expr(meta) * constant_from!(*op),
+        );
+
+        constant_from_bn!(&(BigUint::from(self.foreign_call_id) << OPCODE_CLASS_SHIFT)) + pick_one
+    }
+
+    fn mops(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(2))
+    }
+
+    fn assigned_extra_mops(
+        &self,
+        _ctx: &mut Context<'_, F>,
+        _step: &StepStatus,
+        _entry: &EventTableEntry,
+    ) -> u64 {
+        2
+    }
+
+    /*
+     * The input pops the index and the limb, an output pops the index and
+     * pushes the limb.
+     */
+    fn mtable_lookup(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        item: MLookupItem,
+        common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        match item {
+            MLookupItem::First => Some(MemoryTableLookupEncode::encode_stack_read(
+                common_config.eid(meta),
+                constant_from!(1),
+                common_config.sp(meta) + constant_from!(1) + self.is_input.expr(meta),
+                constant_from!(VarType::I32),
+                self.index.expr(meta),
+            )),
+            MLookupItem::Second => Some(
+                self.is_input.expr(meta)
+                    * MemoryTableLookupEncode::encode_stack_read(
+                        common_config.eid(meta),
+                        constant_from!(2),
+                        common_config.sp(meta) + constant_from!(1),
+                        constant_from!(VarType::I64),
+                        self.value.expr(meta),
+                    )
+                    + (constant_from!(1) - self.is_input.expr(meta))
+                        * MemoryTableLookupEncode::encode_stack_write(
+                            common_config.eid(meta),
+                            constant_from!(2),
+                            common_config.sp(meta) + constant_from!(1),
+                            constant_from!(VarType::I64),
+                            self.value.expr(meta),
+                        ),
+            ),
+            _ => None,
+        }
+    }
+
+    fn sp_diff(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(2) * self.is_input.expr(meta))
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step_info: &StepStatus,
+        entry: &EventTableEntry,
+    ) -> Result<(), Error> {
+        match &entry.step_info {
+            StepInfo::CallHost {
+                plugin,
+                function_name,
+                args,
+                ret_val,
+                ..
+            } => {
+                assert_eq!(*plugin, HostPlugin::U256);
+
+                let index = args[0];
+
+                self.index.assign(ctx, index)?;
+                self.lookup_stack_read_index.assign(
+                    ctx,
+                    &MemoryTableLookupEncode::encode_stack_read(
+                        BigUint::from(step_info.current.eid),
+                        BigUint::from(1u64),
+                        BigUint::from(step_info.current.sp + args.len() as u32),
+                        BigUint::from(VarType::I32 as u64),
+                        BigUint::from(index),
+                    ),
+                )?;
+
+                if function_name == U256_FOREIGN_FUNCTION_NAME_INPUT {
+                    let value = args[1];
+
+                    self.is_input.assign(ctx, true)?;
+                    self.value.assign(ctx, value)?;
+                    self.lookup_stack_value.assign(
+                        ctx,
+                        &MemoryTableLookupEncode::encode_stack_read(
+                            BigUint::from(step_info.current.eid),
+                            BigUint::from(2u64),
+                            BigUint::from(step_info.current.sp + 1),
+                            BigUint::from(VarType::I64 as u64),
+                            BigUint::from(value),
+                        ),
+                    )?;
+                } else {
+                    let value = ret_val.unwrap();
+                    let op = U256HelperOp::from(function_name);
+
+                    for (output_op, is_op) in OUTPUT_OPS.iter().zip(self.is_ops.iter()) {
+                        is_op.assign(ctx, *output_op == op)?;
+                    }
+                    self.value.assign(ctx, value)?;
+                    self.lookup_stack_value.assign(
+                        ctx,
+                        &MemoryTableLookupEncode::encode_stack_write(
+                            BigUint::from(step_info.current.eid),
+                            BigUint::from(2u64),
+                            BigUint::from(step_info.current.sp + 1),
+                            BigUint::from(VarType::I64 as u64),
+                            BigUint::from(value),
+                        ),
+                    )?;
+                }
+            }
+            _ => unreachable!(),
+        };
+        Ok(())
+    }
+}
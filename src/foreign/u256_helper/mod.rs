@@ -0,0 +1,119 @@
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+use strum_macros::EnumIter;
+
+pub mod circuits;
+pub mod etable_op_configure;
+pub mod runtime;
+pub mod test;
+
+pub const U256_FOREIGN_TABLE_KEY: &'static str = "u256-helper-table";
+pub const U256_FOREIGN_FUNCTION_NAME_INPUT: &'static str = "zkwasm_u256_input";
+pub const U256_FOREIGN_FUNCTION_NAME_ADD: &'static str = "zkwasm_u256_add";
+pub const U256_FOREIGN_FUNCTION_NAME_SUB: &'static str = "zkwasm_u256_sub";
+pub const U256_FOREIGN_FUNCTION_NAME_MUL: &'static str = "zkwasm_u256_mul";
+pub const U256_FOREIGN_FUNCTION_NAME_DIVMOD: &'static str = "zkwasm_u256_divmod";
+pub const U256_FOREIGN_FUNCTION_NAME_MULMOD: &'static str = "zkwasm_u256_mulmod";
+
+// The u64 limbs of a u256.
+pub const U256_LIMBS: usize = 4;
+// The operands a, b and m.
+pub const U256_INPUT_LIMBS: usize = 3 * U256_LIMBS;
+pub const U256_OUTPUT_LIMBS: usize = 2 * U256_LIMBS;
+
+/*
+ * A guest runs an operation limb by limb: the k-th operation takes the little
+ * endian u64 limbs of a, b and m at the indices k * 12 + 0..12, then its output
+ * limbs are read at the indices k * 8 + 0..8 by the function of the operation:
+ *   add: a + b, the limb 4 is the carry.
+ *   sub: a - b modulo 2^256, the limb 4 is the borrow.
+ *   mul: the 512 bits of a * b.
+ *   divmod: the quotient then the remainder of a by b, traps if b is 0.
+ *   mulmod: a * b modulo m, traps if m is 0.
+ * The unused limbs of the output are 0. An input limb is given at most once,
+ * before the output limbs of its operation are read, all by the same function.
+ * The operations are numbered from 0 without gaps: an index past the next
+ * operation traps.
+ */
+#[derive(Clone, Copy, Debug, EnumIter, PartialEq)]
+pub enum U256HelperOp {
+    Input = 1,
+    Add = 2,
+    Sub = 3,
+    Mul = 4,
+    DivMod = 5,
+    MulMod = 6,
+}
+
+impl From<&String> for U256HelperOp {
+    fn from(function_name: &String) -> Self {
+        match function_name.as_str() {
+            U256_FOREIGN_FUNCTION_NAME_INPUT => U256HelperOp::Input,
+            U256_FOREIGN_FUNCTION_NAME_ADD => U256HelperOp::Add,
+            U256_FOREIGN_FUNCTION_NAME_SUB => U256HelperOp::Sub,
+            U256_FOREIGN_FUNCTION_NAME_MUL => U256HelperOp::Mul,
+            U256_FOREIGN_FUNCTION_NAME_DIVMOD => U256HelperOp::DivMod,
+            U256_FOREIGN_FUNCTION_NAME_MULMOD => U256HelperOp::MulMod,
+            _ => unreachable!(),
+        }
+    }
+}
+
+pub(crate) fn limbs_to_biguint(limbs: &[u64]) -> BigUint {
+    limbs.iter().rev().fold(BigUint::zero(), |acc, limb| {
+        (acc << 64) + BigUint::from(*limb)
+    })
+}
+
+pub(crate) fn biguint_to_limbs<const N: usize>(value: &BigUint) -> [u64; N] {
+    let mut limbs = [0; N];
+
+    for (i, limb) in value.to_u64_digits().into_iter().enumerate() {
+        limbs[i] = limb;
+    }
+
+    limbs
+}
+
+/*
+ * The output limbs of the operation on the input limbs, None if it divides
+ * by 0.
+ */
+pub(crate) fn u256_operation(
+    op: U256HelperOp,
+    input: &[u64; U256_INPUT_LIMBS],
+) -> Option<[u64; U256_OUTPUT_LIMBS]> {
+    let [a, b, m] =
+        [0, 1, 2].map(|i| limbs_to_biguint(&input[i * U256_LIMBS..(i + 1) * U256_LIMBS]));
+    let modulus = BigUint::one() << 256;
+
+    let output = match op {
+        U256HelperOp::Input => unreachable!(),
+        U256HelperOp::Add => a + b,
+        U256HelperOp::Sub => {
+            if a >= b {
+                a - b
+            } else {
+                // The borrow is the limb 4.
+                a + (&modulus << 1) - b
+            }
+        }
+        U256HelperOp::Mul => a * b,
+        U256HelperOp::DivMod => {
+            if b.is_zero() {
+                return None;
+            }
+
+            (&a % &b) * modulus + a / b
+        }
+        U256HelperOp::MulMod => {
+            if m.is_zero() {
+                return None;
+            }
+
+            a * b % m
+        }
+    };
+
+    Some(biguint_to_limbs(&output))
+}
@@ -0,0 +1,128 @@
+use std::rc::Rc;
+
+use specs::{host_function::HostPlugin, types::ValueType};
+use wasmi::{RuntimeArgs, RuntimeValue, Trap};
+
+use crate::runtime::host::{host_env::HostEnv, ForeignContext, HostFunctionError};
+
+use super::{
+    u256_operation, U256HelperOp, U256_FOREIGN_FUNCTION_NAME_ADD,
+    U256_FOREIGN_FUNCTION_NAME_DIVMOD, U256_FOREIGN_FUNCTION_NAME_INPUT,
+    U256_FOREIGN_FUNCTION_NAME_MUL, U256_FOREIGN_FUNCTION_NAME_MULMOD,
+    U256_FOREIGN_FUNCTION_NAME_SUB, U256_INPUT_LIMBS, U256_OUTPUT_LIMBS,
+};
+
+struct Context {
+    inputs: Vec<[u64; U256_INPUT_LIMBS]>,
+    // The function reading the output of an operation.
+    ops: Vec<Option<U256HelperOp>>,
+}
+
+impl Context {
+    fn new() -> Self {
+        Context {
+            inputs: vec![],
+            ops: vec![],
+        }
+    }
+
+    // The operations are dense, a guest starts at most the next one.
+    fn operation(&mut self, operation: usize) -> Result<&mut [u64; U256_INPUT_LIMBS], Trap> {
+        if operation == self.inputs.len() {
+            self.inputs.push([0; U256_INPUT_LIMBS]);
+            self.ops.push(None);
+        }
+
+        let next = self.inputs.len();
+
+        self.inputs.get_mut(operation).ok_or_else(|| {
+            HostFunctionError::trap(format!(
+                "u256 operation {} skips the operation {}",
+                operation, next
+            ))
+        })
+    }
+}
+
+impl ForeignContext for Context {}
+
+fn input(context: &mut Context, args: RuntimeArgs) -> Result<Option<RuntimeValue>, Trap> {
+    let index: u32 = args.nth(0);
+    let limb: u64 = args.nth(1);
+    let index = index as usize;
+
+    context.operation(index / U256_INPUT_LIMBS)?[index % U256_INPUT_LIMBS] = limb;
+
+    Ok(None)
+}
+
+fn output(
+    context: &mut Context,
+    op: U256HelperOp,
+    args: RuntimeArgs,
+) -> Result<Option<RuntimeValue>, Trap> {
+    let index: u32 = args.nth(0);
+    let index = index as usize;
+    let operation = index / U256_OUTPUT_LIMBS;
+
+    let input = *context.operation(operation)?;
+
+    match context.ops[operation] {
+        Some(prev) if prev != op => {
+            return Err(HostFunctionError::trap(format!(
+                "u256 operation {} is read by {:?} after {:?}",
+                operation, op, prev
+            )))
+        }
+        _ => context.ops[operation] = Some(op),
+    }
+
+    match u256_operation(op, &input) {
+        Some(output) => Ok(Some(RuntimeValue::I64(
+            output[index % U256_OUTPUT_LIMBS] as i64,
+        ))),
+        None => Err(HostFunctionError::trap(format!(
+            "u256 operation {} {:?} divides by 0",
+            operation, op
+        ))),
+    }
+}
+
+pub fn register_u256_foreign(env: &mut HostEnv) {
+    env.internal_env
+        .register_plugin(HostPlugin::U256, Box::new(Context::new()));
+
+    env.internal_env.register_fallible_function(
+        U256_FOREIGN_FUNCTION_NAME_INPUT,
+        specs::host_function::Signature {
+            params: vec![ValueType::I32, ValueType::I64],
+            return_type: None,
+        },
+        HostPlugin::U256,
+        U256HelperOp::Input as usize,
+        Rc::new(|context: &mut dyn ForeignContext, args: RuntimeArgs| {
+            input(context.downcast_mut::<Context>().unwrap(), args)
+        }),
+    );
+
+    for (function_name, op) in [
+        (U256_FOREIGN_FUNCTION_NAME_ADD, U256HelperOp::Add),
+        (U256_FOREIGN_FUNCTION_NAME_SUB, U256HelperOp::Sub),
+        (U256_FOREIGN_FUNCTION_NAME_MUL, U256HelperOp::Mul),
+        (U256_FOREIGN_FUNCTION_NAME_DIVMOD, U256HelperOp::DivMod),
+        (U256_FOREIGN_FUNCTION_NAME_MULMOD, U256HelperOp::MulMod),
+    ] {
+        env.internal_env.register_fallible_function(
+            function_name,
+            specs::host_function::Signature {
+                params: vec![ValueType::I32],
+                return_type: Some(specs::types::ValueType::I64),
+            },
+            HostPlugin::U256,
+            op as usize,
+            Rc::new(move |context: &mut dyn ForeignContext, args: RuntimeArgs| {
+                output(context.downcast_mut::<Context>().unwrap(), op, args)
+            }),
+        );
+    }
+}
@@ -0,0 +1,186 @@
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::{
+        foreign::{
+            u256_helper::{
+                biguint_to_limbs, runtime::register_u256_foreign, u256_operation, U256HelperOp,
+                U256_INPUT_LIMBS, U256_LIMBS,
+            },
+            wasm_input_helper::runtime::register_wasm_input_foreign,
+        },
+        runtime::host::host_env::HostEnv,
+        test::test_circuit_with_env,
+    };
+
+    use halo2_proofs::pairing::bn256::Fr as Fp;
+    use num_bigint::BigUint;
+
+    /*
+     * Runs the operation k of add, sub, mul, divmod and mulmod on the 12 input
+     * limbs given by the public inputs, then checks its 8 output limbs against
+     * the next public inputs.
+     */
+    pub(crate) const U256_WAT: &str = r#"
+        (module
+          (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+          (import "env" "zkwasm_u256_input" (func $u256_input (param i32 i64)))
+          (import "env" "zkwasm_u256_add" (func $u256_add (param i32) (result i64)))
+          (import "env" "zkwasm_u256_sub" (func $u256_sub (param i32) (result i64)))
+          (import "env" "zkwasm_u256_mul" (func $u256_mul (param i32) (result i64)))
+          (import "env" "zkwasm_u256_divmod" (func $u256_divmod (param i32) (result i64)))
+          (import "env" "zkwasm_u256_mulmod" (func $u256_mulmod (param i32) (result i64)))
+          (func $output (param $k i32) (param $index i32) (result i64)
+            (if (result i64) (i32.eq (local.get $k) (i32.const 0))
+              (then (call $u256_add (local.get $index)))
+              (else (if (result i64) (i32.eq (local.get $k) (i32.const 1))
+                (then (call $u256_sub (local.get $index)))
+                (else (if (result i64) (i32.eq (local.get $k) (i32.const 2))
+                  (then (call $u256_mul (local.get $index)))
+                  (else (if (result i64) (i32.eq (local.get $k) (i32.const 3))
+                    (then (call $u256_divmod (local.get $index)))
+                    (else (call $u256_mulmod (local.get $index)))))))))))
+          (func (export "u256")
+            (local $k i32) (local $i i32)
+            (loop $operation
+              (local.set $i (i32.const 0))
+              (loop $input
+                (call $u256_input
+                  (i32.add (i32.mul (local.get $k) (i32.const 12)) (local.get $i))
+                  (call $wasm_input (i32.const 1)))
+                (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                (br_if $input (i32.lt_u (local.get $i) (i32.const 12))))
+              (local.set $i (i32.const 0))
+              (loop $output
+                (if (i64.ne
+                      (call $output
+                        (local.get $k)
+                        (i32.add (i32.mul (local.get $k) (i32.const 8)) (local.get $i)))
+                      (call $wasm_input (i32.const 1)))
+                  (then unreachable))
+                (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                (br_if $output (i32.lt_u (local.get $i) (i32.const 8))))
+              (local.set $k (i32.add (local.get $k) (i32.const 1)))
+              (br_if $operation (i32.lt_u (local.get $k) (i32.const 5)))))
+    "#;
+
+    const OPS: [U256HelperOp; 5] = [
+        U256HelperOp::Add,
+        U256HelperOp::Sub,
+        U256HelperOp::Mul,
+        U256HelperOp::DivMod,
+        U256HelperOp::MulMod,
+    ];
+
+    fn input(a: &str, b: &str, m: &str) -> [u64; U256_INPUT_LIMBS] {
+        let mut input = [0; U256_INPUT_LIMBS];
+
+        for (i, value) in [a, b, m].iter().enumerate() {
+            input[i * U256_LIMBS..(i + 1) * U256_LIMBS].copy_from_slice(&biguint_to_limbs::<
+                U256_LIMBS,
+            >(
+                &BigUint::parse_bytes(value.as_bytes(), 16).unwrap(),
+            ));
+        }
+
+        input
+    }
+
+    #[test]
+    fn test_u256_operation() {
+        let a = "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
+        let input = input(a, "2", "0");
+
+        assert_eq!(
+            u256_operation(U256HelperOp::Add, &input),
+            Some([1, 0, 0, 0, 1, 0, 0, 0])
+        );
+        assert_eq!(
+            u256_operation(U256HelperOp::Sub, &input),
+            Some([u64::MAX - 2, u64::MAX, u64::MAX, u64::MAX, 0, 0, 0, 0])
+        );
+        assert_eq!(
+            u256_operation(U256HelperOp::Sub, &self::input("2", a, "0")),
+            Some([3, 0, 0, 0, 1, 0, 0, 0])
+        );
+        assert_eq!(
+            u256_operation(U256HelperOp::Mul, &input),
+            Some([u64::MAX - 1, u64::MAX, u64::MAX, u64::MAX, 1, 0, 0, 0])
+        );
+        assert_eq!(
+            u256_operation(U256HelperOp::DivMod, &input),
+            Some([u64::MAX, u64::MAX, u64::MAX, u64::MAX >> 1, 1, 0, 0, 0])
+        );
+        assert_eq!(u256_operation(U256HelperOp::MulMod, &input), None);
+    }
+
+    #[test]
+    fn test_u256_helper() {
+        let inputs = [
+            input(
+                "fedcba9876543210fedcba9876543210fedcba9876543210fedcba9876543210",
+                "8000000000000000000000000000000000000000000000000000000000000001",
+                "0",
+            ),
+            input(
+                "123456789abcdef",
+                "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+                "0",
+            ),
+            input(
+                "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+                "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+                "0",
+            ),
+            input(
+                "fedcba9876543210fedcba9876543210fedcba9876543210fedcba9876543210",
+                "1000000000000000f",
+                "0",
+            ),
+            input(
+                "fedcba9876543210fedcba9876543210fedcba9876543210fedcba9876543210",
+                "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+                "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+            ),
+        ];
+
+        let mut public_inputs = vec![];
+        for (op, input) in OPS.iter().zip(inputs.iter()) {
+            public_inputs.append(&mut input.to_vec());
+            public_inputs.append(&mut u256_operation(*op, input).unwrap().to_vec());
+        }
+
+        let wasm = wabt::wat2wasm(U256_WAT).expect("failed to parse wat");
+
+        let mut env = HostEnv::new();
+        register_u256_foreign(&mut env);
+        register_wasm_input_foreign(&mut env, public_inputs.clone(), vec![]);
+        env.finalize();
+
+        test_circuit_with_env(
+            env,
+            wasm,
+            "u256",
+            public_inputs.into_iter().map(|v| Fp::from(v)).collect(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_u256_helper_skipping_operation() {
+        let wasm = wabt::wat2wasm(
+            r#"
+            (module
+              (import "env" "zkwasm_u256_add" (func $u256_add (param i32) (result i64)))
+              (func (export "u256_add")
+                (drop (call $u256_add (i32.const 0xffffffff)))))
+            "#,
+        )
+        .expect("failed to parse wat");
+
+        let mut env = HostEnv::new();
+        register_u256_foreign(&mut env);
+        env.finalize();
+
+        assert!(test_circuit_with_env(env, wasm, "u256_add", vec![]).is_err());
+    }
+}
@@ -1,5 +1,8 @@
-use super::WasmInputHelperTableConfig;
-use crate::foreign::wasm_input_helper::circuits::ENABLE_LINES;
+use super::{WasmInputHelperTableConfig, WasmPrivateInputHelperTableConfig};
+use crate::{
+    circuits::utils::Context,
+    foreign::wasm_input_helper::{circuits::ENABLE_LINES, private_input_commitment_inputs},
+};
 use halo2_proofs::{arithmetic::FieldExt, circuit::Layouter, plonk::Error};
 use specs::{etable::EventTableEntry, host_function::HostPlugin, step::StepInfo};
 
@@ -80,3 +83,107 @@ impl<F: FieldExt> WasmInputHelperTableChip<F> {
         Ok(())
     }
 }
+
+pub struct WasmPrivateInputHelperTableChip<F: FieldExt> {
+    config: WasmPrivateInputHelperTableConfig<F>,
+}
+
+impl<F: FieldExt> WasmPrivateInputHelperTableChip<F> {
+    pub fn new(config: WasmPrivateInputHelperTableConfig<F>) -> Self {
+        Self { config }
+    }
+
+    /*
+     * Assigns the private inputs and exposes their commitment, the hashed
+     * inputs are bound to the rest cell of row 0 and the input cells.
+     */
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        private_inputs: &Vec<u64>,
+    ) -> Result<(), Error> {
+        let capacity = self.config.capacity;
+        let inputs = private_input_commitment_inputs::<F>(private_inputs, capacity)
+            .map_err(|_| Error::Synthesis)?;
+
+        let sources = layouter.assign_region(
+            || "wasm private input helper assign",
+            |mut region| {
+                let mut sources = vec![];
+
+                for i in 0..capacity {
+                    let value = private_inputs.get(i).cloned();
+
+                    region.assign_fixed(
+                        || "wasm private input sel",
+                        self.config.sel,
+                        i,
+                        || Ok(F::one()),
+                    )?;
+                    region.assign_fixed(
+                        || "wasm private input index",
+                        self.config.index,
+                        i,
+                        || Ok(F::from(i as u64)),
+                    )?;
+                    region.assign_advice(
+                        || "wasm private input enable",
+                        self.config.enable,
+                        i,
+                        || Ok(F::from(value.is_some() as u64)),
+                    )?;
+
+                    let rest = region.assign_advice(
+                        || "wasm private input rest",
+                        self.config.rest,
+                        i,
+                        || Ok(F::from(private_inputs.len().saturating_sub(i) as u64)),
+                    )?;
+                    if i == 0 {
+                        sources.push(rest.cell());
+                    }
+
+                    let input = region.assign_advice(
+                        || "wasm private input",
+                        self.config.input,
+                        i,
+                        || Ok(F::from(value.unwrap_or(0))),
+                    )?;
+                    sources.push(input.cell());
+                }
+
+                region.assign_advice_from_constant(
+                    || "wasm private input rest",
+                    self.config.rest,
+                    capacity,
+                    F::zero(),
+                )?;
+                region.assign_advice(
+                    || "wasm private input enable",
+                    self.config.enable,
+                    capacity,
+                    || Ok(F::zero()),
+                )?;
+
+                Ok(sources)
+            },
+        )?;
+
+        let digest = layouter.assign_region(
+            || "wasm private input commitment",
+            |region| {
+                let mut ctx = Context::new(region);
+
+                let (cells, digest) = self.config.poseidon.assign(&mut ctx, &inputs)?;
+
+                for (cell, source) in cells.into_iter().zip(sources.iter()) {
+                    ctx.region.constrain_equal(cell, *source)?;
+                }
+
+                Ok(digest)
+            },
+        )?;
+
+        layouter.constrain_instance(digest, self.config.commitment, 0)
+    }
+}
@@ -4,11 +4,14 @@ use halo2_proofs::{
 };
 
 use crate::{
-    constant_from, curr, fixed_curr, foreign::ForeignTableConfig, instance_curr,
+    circuits::utils::poseidon::PoseidonConfig, constant_from, curr, fixed_curr,
+    foreign::ForeignTableConfig, instance_curr, next,
     traits::circuits::bit_range_table::BitRangeTable,
 };
 
-use super::WasmInputHelperTableConfig;
+use super::{
+    WasmInputHelperTableConfig, WasmOutputHelperTableConfig, WasmPrivateInputHelperTableConfig,
+};
 
 impl<F: FieldExt> WasmInputHelperTableConfig<F> {
     pub fn configure(meta: &mut ConstraintSystem<F>, rtable: &impl BitRangeTable<F>) -> Self {
@@ -16,6 +19,8 @@ impl<F: FieldExt> WasmInputHelperTableConfig<F> {
         let index = meta.fixed_column();
         let input = meta.instance_column();
         let input_u8 = [(); 8].map(|_| meta.advice_column());
+        // The outputs are the second instance column.
+        let output = meta.instance_column();
 
         // constaint input to u64
         for i in input_u8 {
@@ -37,11 +42,64 @@ impl<F: FieldExt> WasmInputHelperTableConfig<F> {
             index,
             input,
             input_u8,
+            output,
+            _mark: std::marker::PhantomData,
+        }
+    }
+
+    pub fn output_table(&self) -> WasmOutputHelperTableConfig<F> {
+        WasmOutputHelperTableConfig {
+            enable: self.enable,
+            index: self.index,
+            output: self.output,
             _mark: std::marker::PhantomData,
         }
     }
 }
 
+impl<F: FieldExt> WasmPrivateInputHelperTableConfig<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>, capacity: usize) -> Self {
+        let sel = meta.fixed_column();
+        let index = meta.fixed_column();
+        let enable = meta.advice_column();
+        let rest = meta.advice_column();
+        let input = meta.advice_column();
+        let poseidon = PoseidonConfig::configure(meta);
+        let commitment = meta.instance_column();
+
+        meta.enable_equality(rest);
+        meta.enable_equality(input);
+        meta.enable_equality(commitment);
+
+        meta.create_gate("wasm private input table", |meta| {
+            let enable_expr = curr!(meta, enable);
+            let disable_expr = constant_from!(1) - enable_expr.clone();
+
+            vec![
+                enable_expr.clone() * disable_expr.clone(),
+                curr!(meta, rest) - next!(meta, rest) - enable_expr,
+                // The enabled rows are the first rows.
+                disable_expr.clone() * next!(meta, enable),
+                disable_expr * curr!(meta, input),
+            ]
+            .into_iter()
+            .map(|constraint| constraint * fixed_curr!(meta, sel))
+            .collect::<Vec<_>>()
+        });
+
+        WasmPrivateInputHelperTableConfig {
+            sel,
+            index,
+            enable,
+            rest,
+            input,
+            poseidon,
+            commitment,
+            capacity,
+        }
+    }
+}
+
 impl<F: FieldExt> ForeignTableConfig<F> for WasmInputHelperTableConfig<F> {
     fn configure_in_table(
         &self,
@@ -52,3 +110,25 @@ impl<F: FieldExt> ForeignTableConfig<F> for WasmInputHelperTableConfig<F> {
         meta.lookup_any(key, |meta| vec![(expr(meta), self.opcode_expr(meta))]);
     }
 }
+
+impl<F: FieldExt> ForeignTableConfig<F> for WasmOutputHelperTableConfig<F> {
+    fn configure_in_table(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        key: &'static str,
+        expr: &dyn Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+    ) {
+        meta.lookup_any(key, |meta| vec![(expr(meta), self.opcode_expr(meta))]);
+    }
+}
+
+impl<F: FieldExt> ForeignTableConfig<F> for WasmPrivateInputHelperTableConfig<F> {
+    fn configure_in_table(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        key: &'static str,
+        expr: &dyn Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+    ) {
+        meta.lookup_any(key, |meta| vec![(expr(meta), self.opcode_expr(meta))]);
+    }
+}
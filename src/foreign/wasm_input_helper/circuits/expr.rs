@@ -3,9 +3,12 @@ use halo2_proofs::{
     plonk::{Expression, VirtualCells},
 };
 
-use crate::{fixed_curr, instance_curr};
+use crate::{curr, fixed_curr, instance_curr};
 
-use super::{InputTableEncode, WasmInputHelperTableConfig};
+use super::{
+    InputTableEncode, WasmInputHelperTableConfig, WasmOutputHelperTableConfig,
+    WasmPrivateInputHelperTableConfig,
+};
 
 impl<F: FieldExt> WasmInputHelperTableConfig<F> {
     pub(super) fn opcode_expr(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
@@ -16,3 +19,24 @@ impl<F: FieldExt> WasmInputHelperTableConfig<F> {
             )
     }
 }
+
+impl<F: FieldExt> WasmOutputHelperTableConfig<F> {
+    pub(super) fn opcode_expr(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        fixed_curr!(meta, self.enable)
+            * InputTableEncode::encode_for_lookup(
+                fixed_curr!(meta, self.index),
+                instance_curr!(meta, self.output),
+            )
+    }
+}
+
+impl<F: FieldExt> WasmPrivateInputHelperTableConfig<F> {
+    pub(super) fn opcode_expr(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        fixed_curr!(meta, self.sel)
+            * curr!(meta, self.enable)
+            * InputTableEncode::encode_for_lookup(
+                fixed_curr!(meta, self.index),
+                curr!(meta, self.input),
+            )
+    }
+}
@@ -7,18 +7,22 @@ use halo2_proofs::{
 use num_bigint::BigUint;
 use specs::encode::FromBn;
 
+use crate::circuits::utils::poseidon::PoseidonConfig;
+
 pub mod assign;
 pub mod config;
 pub mod expr;
 
 pub const WASM_INPUT_FOREIGN_TABLE_KEY: &'static str = "wasm-input-helper-table";
+pub const WASM_OUTPUT_FOREIGN_TABLE_KEY: &'static str = "wasm-output-helper-table";
+pub const WASM_PRIVATE_INPUT_FOREIGN_TABLE_KEY: &'static str = "wasm-private-input-helper-table";
 const K: usize = 15;
 const ENABLE_LINES: usize = 1 << (K - 1);
 
 pub struct InputTableEncode {}
 
 impl InputTableEncode {
-    pub(super) fn encode_for_lookup<T: FromBn>(index: T, input: T) -> T {
+    pub(crate) fn encode_for_lookup<T: FromBn>(index: T, input: T) -> T {
         T::from_bn(&(BigUint::from(1u64) << 128))
             + index * T::from_bn(&(BigUint::from(1u64) << 64))
             + input
@@ -31,5 +35,37 @@ pub struct WasmInputHelperTableConfig<F: FieldExt> {
     index: Column<Fixed>,
     input: Column<Instance>,
     input_u8: [Column<Advice>; 8],
+    output: Column<Instance>,
     _mark: PhantomData<F>,
 }
+
+/*
+ * The outputs are looked up in the output column by their indexes, the
+ * values are u64 cells of etable so no decomposition is needed.
+ */
+#[derive(Clone)]
+pub struct WasmOutputHelperTableConfig<F: FieldExt> {
+    enable: Column<Fixed>,
+    index: Column<Fixed>,
+    output: Column<Instance>,
+    _mark: PhantomData<F>,
+}
+
+/*
+ * The private inputs committed in the private input commitment, the first
+ * rows up to the number of private inputs are enabled and the rest are zeros.
+ * rest counts down the enabled rows from the number of private inputs at row
+ * 0 to 0 at the row of the capacity, the commitment is the hash of the number
+ * of private inputs followed by the rows (see private_input_commitment).
+ */
+#[derive(Clone)]
+pub struct WasmPrivateInputHelperTableConfig<F: FieldExt> {
+    sel: Column<Fixed>,
+    index: Column<Fixed>,
+    enable: Column<Advice>,
+    rest: Column<Advice>,
+    input: Column<Advice>,
+    poseidon: PoseidonConfig<F>,
+    commitment: Column<Instance>,
+    capacity: usize,
+}
@@ -1,9 +1,11 @@
 use crate::{
     circuits::{
+        bulk_memory_table::{BulkMemoryOpKind, BulkMemoryTableEncode, BULK_MEMORY_TABLE_KEY},
         etable_compact::{
             op_configure::{
-                BitCell, ConstraintBuilder, EventTableCellAllocator, EventTableOpcodeConfig,
-                MTableLookupCell, U64Cell,
+                bulk_memory::BlockRangeCell, BitCell, CommonRangeCell, ConstraintBuilder,
+                EventTableCellAllocator, EventTableOpcodeConfig, MTableLookupCell, U64Cell,
+                UnlimitedCell,
             },
             EventTableCommonConfig, MLookupItem, StepStatus,
         },
@@ -11,7 +13,7 @@ use crate::{
         utils::{bn_to_field, Context},
     },
     constant, constant_from,
-    foreign::{EventTableForeignCallConfigBuilder, ForeignCallInfo},
+    foreign::ForeignCallInfo,
 };
 use halo2_proofs::{
     arithmetic::FieldExt,
@@ -20,45 +22,89 @@ use halo2_proofs::{
 use num_bigint::BigUint;
 use specs::step::StepInfo;
 use specs::{
+    configure_table::WASM_PAGE_SIZE,
     etable::EventTableEntry,
     itable::{OpcodeClass, OPCODE_CLASS_SHIFT},
 };
 use specs::{host_function::HostPlugin, mtable::VarType};
 
-use super::circuits::{InputTableEncode, WASM_INPUT_FOREIGN_TABLE_KEY};
+use super::{
+    circuits::{
+        InputTableEncode, WASM_INPUT_FOREIGN_TABLE_KEY, WASM_OUTPUT_FOREIGN_TABLE_KEY,
+        WASM_PRIVATE_INPUT_FOREIGN_TABLE_KEY,
+    },
+    WasmInputOp,
+};
 
 pub struct WasmInputForeignCallInfo {}
 impl ForeignCallInfo for WasmInputForeignCallInfo {
     fn call_id(&self) -> usize {
-        OpcodeClass::ForeignPluginStart as usize + HostPlugin::HostInput as usize
+        OpcodeClass::ForeignPluginStart as usize + HostPlugin::HostInput.id()
     }
 }
 
+/*
+ * wasm_input pops the flag of public and pushes the input,
+ * wasm_private_input_bytes pops ptr and len and writes the private inputs to
+ * the guest memory, wasm_output pops the output.
+ */
 pub struct ETableWasmInputHelperTableConfig {
     public: BitCell,
     value: U64Cell,
 
-    lookup_read_stack: MTableLookupCell,
-    lookup_write_stack: MTableLookupCell,
+    is_output: BitCell,
+
+    is_bytes: BitCell,
+    ptr: U64Cell,
+    len: U64Cell,
+    len_inv: UnlimitedCell,
+    is_empty: BitCell,
+    dst_blocks: BlockRangeCell,
+    address_within_allocated_pages_helper: CommonRangeCell,
+    // The private inputs loaded as bytes, 8 * limbs = len + padding.
+    limbs: CommonRangeCell,
+    limbs_padding: [BitCell; 3],
+
+    lookup_first_stack: MTableLookupCell,
+    lookup_second_stack: MTableLookupCell,
 }
 
 pub struct ETableWasmInputHelperTableConfigBuilder {}
 
-impl<F: FieldExt> EventTableForeignCallConfigBuilder<F>
-    for ETableWasmInputHelperTableConfigBuilder
-{
-    fn configure(
+impl ETableWasmInputHelperTableConfigBuilder {
+    /*
+     * The private inputs read by wasm_input are looked up in the private
+     * input table if the circuit commits the private inputs.
+     */
+    pub(crate) fn configure<F: FieldExt>(
         common: &mut EventTableCellAllocator<F>,
         constraint_builder: &mut ConstraintBuilder<F>,
-        _info: &impl ForeignCallInfo,
+        commits_private_inputs: bool,
     ) -> Box<dyn EventTableOpcodeConfig<F>> {
         let public = common.alloc_bit_value();
         let value = common.alloc_u64();
 
-        let lookup_read_stack = common.alloc_mtable_lookup();
-        let lookup_write_stack = common.alloc_mtable_lookup();
+        let is_output = common.alloc_bit_value();
+
+        let is_bytes = common.alloc_bit_value();
+        let ptr = common.alloc_u64();
+        let len = common.alloc_u64();
+        let len_inv = common.alloc_unlimited_value();
+        let is_empty = common.alloc_bit_value();
+        let dst_blocks = BlockRangeCell::alloc(common);
+
+        let current_memory_page_size = common.allocated_memory_pages_cell();
+        let address_within_allocated_pages_helper = common.alloc_common_range_value();
+        let limbs = common.alloc_common_range_value();
+        let limbs_padding = [0; 3].map(|_| common.alloc_bit_value());
 
+        let lookup_first_stack = common.alloc_mtable_lookup();
+        let lookup_second_stack = common.alloc_mtable_lookup();
+
+        let eid = common.eid_cell();
         let input_index = common.input_index_cell();
+        let output_index = common.output_index_cell();
+        let private_input_index = common.private_input_index_cell();
 
         constraint_builder.lookup(
             WASM_INPUT_FOREIGN_TABLE_KEY,
@@ -69,21 +115,125 @@ impl<F: FieldExt> EventTableForeignCallConfigBuilder<F>
             }),
         );
 
+        constraint_builder.lookup(
+            WASM_OUTPUT_FOREIGN_TABLE_KEY,
+            "lookup output table",
+            Box::new(move |meta| {
+                is_output.expr(meta)
+                    * InputTableEncode::encode_for_lookup(output_index.expr(meta), value.expr(meta))
+            }),
+        );
+
+        if commits_private_inputs {
+            constraint_builder.lookup(
+                WASM_PRIVATE_INPUT_FOREIGN_TABLE_KEY,
+                "lookup private input table",
+                Box::new(move |meta| {
+                    (constant_from!(1)
+                        - public.expr(meta)
+                        - is_output.expr(meta)
+                        - is_bytes.expr(meta))
+                        * InputTableEncode::encode_for_lookup(
+                            private_input_index.expr(meta),
+                            value.expr(meta),
+                        )
+                }),
+            );
+        }
+
+        constraint_builder.push(
+            "wasm input: output",
+            Box::new(move |meta| {
+                vec![
+                    is_output.expr(meta) * public.expr(meta),
+                    is_output.expr(meta) * is_bytes.expr(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "wasm input: private input bytes",
+            Box::new(move |meta| {
+                let is_not_empty = constant_from!(1) - is_empty.expr(meta);
+
+                let mut constraints = vec![
+                    // Only the private inputs are loaded as bytes.
+                    is_bytes.expr(meta) * public.expr(meta),
+                    is_empty.expr(meta) * len.expr(meta),
+                    len.expr(meta) * len_inv.expr(meta) - constant_from!(1) + is_empty.expr(meta),
+                    is_bytes.expr(meta)
+                        * (ptr.expr(meta)
+                            + len.expr(meta)
+                            + address_within_allocated_pages_helper.expr(meta)
+                            - current_memory_page_size.expr(meta) * constant_from!(WASM_PAGE_SIZE)),
+                    is_bytes.expr(meta)
+                        * (limbs.expr(meta) * constant_from!(8)
+                            - len.expr(meta)
+                            - limbs_padding[0].expr(meta)
+                            - limbs_padding[1].expr(meta) * constant_from!(2)
+                            - limbs_padding[2].expr(meta) * constant_from!(4)),
+                ];
+
+                for constraint in
+                    dst_blocks.constraints(meta, ptr.expr(meta), len.expr(meta), is_not_empty)
+                {
+                    constraints.push(is_bytes.expr(meta) * constraint);
+                }
+
+                constraints
+            }),
+        );
+
+        /*
+         * The written blocks are constrained in the bulk memory table, which
+         * keeps the bytes out of [ptr, ptr + len) in the first and last blocks
+         * and looks up the written private inputs in the committed ones if the
+         * circuit commits the private inputs.
+         */
+        constraint_builder.lookup(
+            BULK_MEMORY_TABLE_KEY,
+            "wasm input: bulk memory table",
+            Box::new(move |meta| {
+                is_bytes.expr(meta)
+                    * (constant_from!(1) - is_empty.expr(meta))
+                    * BulkMemoryTableEncode::encode_for_lookup(
+                        eid.expr(meta),
+                        constant_from!(BulkMemoryOpKind::PrivateInput),
+                        ptr.expr(meta),
+                        private_input_index.expr(meta) * constant_from!(8),
+                        len.expr(meta),
+                        constant_from!(0),
+                    )
+            }),
+        );
+
         Box::new(ETableWasmInputHelperTableConfig {
             public,
             value,
-            lookup_read_stack,
-            lookup_write_stack,
+            is_output,
+            is_bytes,
+            ptr,
+            len,
+            len_inv,
+            is_empty,
+            dst_blocks,
+            address_within_allocated_pages_helper,
+            limbs,
+            limbs_padding,
+            lookup_first_stack,
+            lookup_second_stack,
         })
     }
 }
 
 impl<F: FieldExt> EventTableOpcodeConfig<F> for ETableWasmInputHelperTableConfig {
-    fn opcode(&self, _meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+    fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
         constant!(bn_to_field(
-            &(BigUint::from(OpcodeClass::ForeignPluginStart as u64 + HostPlugin::HostInput as u64)
-                << OPCODE_CLASS_SHIFT)
-        ))
+            &(BigUint::from(
+                OpcodeClass::ForeignPluginStart as u64 + HostPlugin::HostInput.id() as u64
+            ) << OPCODE_CLASS_SHIFT)
+        )) + self.is_bytes.expr(meta) * constant_from!(WasmInputOp::PrivateInputBytes as u64)
+            + self.is_output.expr(meta) * constant_from!(WasmInputOp::Output as u64)
     }
 
     fn assign(
@@ -98,20 +248,99 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ETableWasmInputHelperTableConfig
                 args,
                 ret_val,
                 signature,
+                op_index_in_plugin,
                 ..
             } => {
+                assert_eq!(*plugin, HostPlugin::HostInput);
+
+                if *op_index_in_plugin == WasmInputOp::PrivateInputBytes as usize {
+                    let ptr = args[0];
+                    let len = args[1];
+
+                    assert_eq!(args.len(), 2);
+                    assert_eq!(*ret_val, None);
+
+                    self.is_bytes.assign(ctx, true)?;
+                    self.ptr.assign(ctx, ptr)?;
+                    self.len.assign(ctx, len)?;
+                    self.len_inv
+                        .assign(ctx, F::from(len).invert().unwrap_or(F::zero()))?;
+                    self.is_empty.assign(ctx, len == 0)?;
+                    self.dst_blocks.assign(ctx, ptr, len)?;
+                    self.address_within_allocated_pages_helper.assign(
+                        ctx,
+                        F::from(
+                            step_info.current.allocated_memory_pages as u64 * WASM_PAGE_SIZE
+                                - (ptr + len),
+                        ),
+                    )?;
+
+                    let limbs = (len + 7) / 8;
+                    let padding = limbs * 8 - len;
+                    self.limbs.assign(ctx, F::from(limbs))?;
+                    for (i, bit) in self.limbs_padding.iter().enumerate() {
+                        bit.assign(ctx, (padding >> i) & 1 == 1)?;
+                    }
+
+                    self.lookup_first_stack.assign(
+                        ctx,
+                        &MemoryTableLookupEncode::encode_stack_read(
+                            BigUint::from(step_info.current.eid),
+                            BigUint::from(1 as u64),
+                            BigUint::from(step_info.current.sp + 2),
+                            BigUint::from(VarType::I32 as u16),
+                            BigUint::from(ptr),
+                        ),
+                    )?;
+
+                    self.lookup_second_stack.assign(
+                        ctx,
+                        &MemoryTableLookupEncode::encode_stack_read(
+                            BigUint::from(step_info.current.eid),
+                            BigUint::from(2 as u64),
+                            BigUint::from(step_info.current.sp + 1),
+                            BigUint::from(VarType::I32 as u16),
+                            BigUint::from(len),
+                        ),
+                    )?;
+
+                    return Ok(());
+                }
+
+                if *op_index_in_plugin == WasmInputOp::Output as usize {
+                    assert_eq!(args.len(), 1);
+                    assert_eq!(*ret_val, None);
+
+                    self.is_output.assign(ctx, true)?;
+                    self.value.assign(ctx, args[0])?;
+                    self.is_empty.assign(ctx, true)?;
+
+                    self.lookup_first_stack.assign(
+                        ctx,
+                        &MemoryTableLookupEncode::encode_stack_read(
+                            BigUint::from(step_info.current.eid),
+                            BigUint::from(1 as u64),
+                            BigUint::from(step_info.current.sp + 1),
+                            BigUint::from(VarType::I64 as u16),
+                            BigUint::from(args[0]),
+                        ),
+                    )?;
+
+                    return Ok(());
+                }
+
                 let arg_type: VarType = (*signature.params.get(0).unwrap()).into();
                 let ret_type: VarType = signature.return_type.unwrap().into();
 
-                assert_eq!(*plugin, HostPlugin::HostInput);
                 assert_eq!(args.len(), 1);
                 assert_eq!(arg_type, VarType::I32);
                 assert_eq!(ret_type, VarType::I64);
 
                 self.public.assign(ctx, (*args.get(0).unwrap()) == 1)?;
                 self.value.assign(ctx, ret_val.unwrap())?;
+                self.is_empty.assign(ctx, true)?;
 
-                self.lookup_read_stack.assign(
+                self.lookup_first_stack.assign(
                     ctx,
                     &MemoryTableLookupEncode::encode_stack_read(
                         BigUint::from(step_info.current.eid),
@@ -122,7 +351,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ETableWasmInputHelperTableConfig
                     ),
                 )?;
 
-                self.lookup_write_stack.assign(
+                self.lookup_second_stack.assign(
                     ctx,
                     &MemoryTableLookupEncode::encode_stack_write(
                         BigUint::from(step_info.current.eid),
@@ -140,17 +369,47 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ETableWasmInputHelperTableConfig
         }
     }
 
-    fn mops(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(constant_from!(2))
+    fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        let is_not_empty = constant_from!(1) - self.is_empty.expr(meta);
+
+        Some(
+            constant_from!(2) - self.is_output.expr(meta)
+                + self.is_bytes.expr(meta)
+                    * constant_from!(2)
+                    * self.dst_blocks.blocks(meta, is_not_empty),
+        )
     }
 
     fn assigned_extra_mops(
         &self,
         _ctx: &mut Context<'_, F>,
         _step: &StepStatus,
-        _entry: &EventTableEntry,
+        entry: &EventTableEntry,
     ) -> u64 {
-        2
+        match &entry.step_info {
+            StepInfo::CallHost {
+                op_index_in_plugin,
+                args,
+                ret_val,
+                ..
+            } => {
+                // Each block of [ptr, ptr + len) is read then written, see GuestMemory::write.
+                let blocks = if *op_index_in_plugin == WasmInputOp::PrivateInputBytes as usize
+                    && args[1] != 0
+                {
+                    (args[0] + args[1] - 1) / 8 - args[0] / 8 + 1
+                } else {
+                    0
+                };
+
+                (args.len() + ret_val.is_some() as usize) as u64 + blocks * 2
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn sp_diff(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(2) * self.is_bytes.expr(meta) + self.is_output.expr(meta))
     }
 
     fn mtable_lookup(
@@ -159,21 +418,55 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ETableWasmInputHelperTableConfig
         item: MLookupItem,
         common_config: &EventTableCommonConfig<F>,
     ) -> Option<Expression<F>> {
+        let is_bytes = self.is_bytes.expr(meta);
+        let is_output = self.is_output.expr(meta);
+        let is_input = constant_from!(1) - is_bytes.clone() - is_output.clone();
+
         match item {
-            MLookupItem::First => Some(MemoryTableLookupEncode::encode_stack_read(
-                common_config.eid(meta),
-                constant_from!(1),
-                common_config.sp(meta) + constant_from!(1),
-                constant_from!(VarType::I32),
-                self.public.expr(meta),
-            )),
-            MLookupItem::Second => Some(MemoryTableLookupEncode::encode_stack_write(
-                common_config.eid(meta),
-                constant_from!(2),
-                common_config.sp(meta) + constant_from!(1),
-                constant_from!(VarType::I64),
-                self.value.expr(meta),
-            )),
+            MLookupItem::First => Some(
+                is_input.clone()
+                    * MemoryTableLookupEncode::encode_stack_read(
+                        common_config.eid(meta),
+                        constant_from!(1),
+                        common_config.sp(meta) + constant_from!(1),
+                        constant_from!(VarType::I32),
+                        self.public.expr(meta),
+                    )
+                    + is_bytes.clone()
+                        * MemoryTableLookupEncode::encode_stack_read(
+                            common_config.eid(meta),
+                            constant_from!(1),
+                            common_config.sp(meta) + constant_from!(2),
+                            constant_from!(VarType::I32),
+                            self.ptr.expr(meta),
+                        )
+                    + is_output
+                        * MemoryTableLookupEncode::encode_stack_read(
+                            common_config.eid(meta),
+                            constant_from!(1),
+                            common_config.sp(meta) + constant_from!(1),
+                            constant_from!(VarType::I64),
+                            self.value.expr(meta),
+                        ),
+            ),
+            MLookupItem::Second => Some(
+                is_input
+                    * MemoryTableLookupEncode::encode_stack_write(
+                        common_config.eid(meta),
+                        constant_from!(2),
+                        common_config.sp(meta) + constant_from!(1),
+                        constant_from!(VarType::I64),
+                        self.value.expr(meta),
+                    )
+                    + is_bytes
+                        * MemoryTableLookupEncode::encode_stack_read(
+                            common_config.eid(meta),
+                            constant_from!(2),
+                            common_config.sp(meta) + constant_from!(1),
+                            constant_from!(VarType::I32),
+                            self.len.expr(meta),
+                        ),
+            ),
             _ => None,
         }
     }
@@ -188,10 +481,71 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ETableWasmInputHelperTableConfig
 
     fn is_host_public_input(&self, _step: &StepStatus, entry: &EventTableEntry) -> bool {
         match &entry.step_info {
-            StepInfo::CallHost { plugin, args, .. } => {
+            StepInfo::CallHost {
+                plugin,
+                args,
+                op_index_in_plugin,
+                ..
+            } => {
                 assert_eq!(*plugin, HostPlugin::HostInput);
-                assert_eq!(args.len(), 1);
-                args[0] != 0
+
+                *op_index_in_plugin == WasmInputOp::Input as usize && args[0] != 0
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn output_index_increase(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        _common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        Some(self.is_output.expr(meta))
+    }
+
+    fn is_host_public_output(&self, _step: &StepStatus, entry: &EventTableEntry) -> bool {
+        match &entry.step_info {
+            StepInfo::CallHost {
+                plugin,
+                op_index_in_plugin,
+                ..
+            } => {
+                assert_eq!(*plugin, HostPlugin::HostInput);
+
+                *op_index_in_plugin == WasmInputOp::Output as usize
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn private_input_index_increase(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        _common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        Some(
+            constant_from!(1) - self.public.expr(meta) - self.is_output.expr(meta)
+                + self.is_bytes.expr(meta) * (self.limbs.expr(meta) - constant_from!(1)),
+        )
+    }
+
+    fn host_private_inputs(&self, _step: &StepStatus, entry: &EventTableEntry) -> u64 {
+        match &entry.step_info {
+            StepInfo::CallHost {
+                plugin,
+                args,
+                op_index_in_plugin,
+                ..
+            } => {
+                assert_eq!(*plugin, HostPlugin::HostInput);
+
+                if *op_index_in_plugin == WasmInputOp::PrivateInputBytes as usize {
+                    (args[1] + 7) / 8
+                } else if *op_index_in_plugin == WasmInputOp::Input as usize && args[0] == 0 {
+                    1
+                } else {
+                    0
+                }
             }
             _ => unreachable!(),
         }
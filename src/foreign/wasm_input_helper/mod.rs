@@ -1,4 +1,77 @@
+use anyhow::{anyhow, Result};
+use halo2_proofs::arithmetic::FieldExt;
+use specs::{
+    etable::EventTable, host_function::HostPlugin, poseidon::PoseidonSpec, step::StepInfo,
+};
+
 pub mod circuits;
 pub mod etable_op_configure;
 pub mod runtime;
 pub mod test;
+
+pub const WASM_INPUT_FUNCTION_NAME: &'static str = "wasm_input";
+pub const WASM_PRIVATE_INPUT_BYTES_FUNCTION_NAME: &'static str = "wasm_private_input_bytes";
+pub const WASM_OUTPUT_FUNCTION_NAME: &'static str = "wasm_output";
+
+// The op index of the functions within the plugin.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WasmInputOp {
+    Input = 0,
+    PrivateInputBytes = 1,
+    Output = 2,
+}
+
+/*
+ * The values passed to wasm_output in the trace, they are the instances of the
+ * output column following the public inputs.
+ */
+pub fn wasm_outputs(etable: &EventTable) -> Vec<u64> {
+    etable
+        .filter_foreign_entries(HostPlugin::HostInput)
+        .iter()
+        .filter_map(|entry| match &entry.step_info {
+            StepInfo::CallHost {
+                op_index_in_plugin,
+                args,
+                ..
+            } if *op_index_in_plugin == WasmInputOp::Output as usize => Some(args[0]),
+            _ => None,
+        })
+        .collect()
+}
+
+/*
+ * The hashed values of the private input commitment: the number of private
+ * inputs followed by the inputs padded with zeros up to the capacity.
+ */
+pub fn private_input_commitment_inputs<F: FieldExt>(
+    private_inputs: &Vec<u64>,
+    capacity: usize,
+) -> Result<Vec<F>> {
+    if private_inputs.len() > capacity {
+        return Err(anyhow!(
+            "the {} private inputs exceed the capacity {} of the private input commitment",
+            private_inputs.len(),
+            capacity
+        ));
+    }
+
+    let mut inputs = vec![F::from(private_inputs.len() as u64)];
+    for i in 0..capacity {
+        inputs.push(F::from(private_inputs.get(i).cloned().unwrap_or(0)));
+    }
+
+    Ok(inputs)
+}
+
+/*
+ * The commitment of the private inputs exposed in the private input
+ * commitment mode, it commits all the private inputs passed to the runtime
+ * whether they are consumed or not.
+ */
+pub fn private_input_commitment<F: FieldExt>(
+    private_inputs: &Vec<u64>,
+    capacity: usize,
+) -> Result<F> {
+    Ok(PoseidonSpec::new().hash(&private_input_commitment_inputs(private_inputs, capacity)?))
+}
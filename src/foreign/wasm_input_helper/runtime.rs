@@ -2,7 +2,11 @@ use std::rc::Rc;
 
 use specs::{host_function::HostPlugin, types::ValueType};
 
-use crate::runtime::host::{host_env::HostEnv, ForeignContext};
+use super::{
+    WasmInputOp, WASM_INPUT_FUNCTION_NAME, WASM_OUTPUT_FUNCTION_NAME,
+    WASM_PRIVATE_INPUT_BYTES_FUNCTION_NAME,
+};
+use crate::runtime::host::{host_env::HostEnv, ForeignContext, GuestMemory, HostFunctionError};
 
 struct Context {
     public_inputs: Vec<u64>,
@@ -51,19 +55,76 @@ pub fn register_wasm_input_foreign(
         },
     );
 
+    /*
+     * Loads len bytes of the private inputs into the guest memory from ptr,
+     * each input provides 8 bytes in little endian. Traps if the private inputs
+     * run out or the bytes are out of the guest memory.
+     */
+    let wasm_private_input_bytes = Rc::new(
+        |context: &mut dyn ForeignContext, memory: &mut GuestMemory, args: wasmi::RuntimeArgs| {
+            let context = context.downcast_mut::<Context>().unwrap();
+
+            let ptr: u32 = args.nth(0);
+            let len: u32 = args.nth(1);
+            let limbs = (len as usize + 7) / 8;
+
+            if limbs > context.private_inputs.len() {
+                return Err(HostFunctionError::trap(format!(
+                    "{} bytes are loaded from {} private inputs",
+                    len,
+                    context.private_inputs.len()
+                )));
+            }
+
+            let mut bytes = context
+                .private_inputs
+                .drain(..limbs)
+                .flat_map(|input| input.to_le_bytes())
+                .collect::<Vec<_>>();
+            bytes.truncate(len as usize);
+
+            memory.write(ptr, &bytes)?;
+
+            Ok(None)
+        },
+    );
+
     env.internal_env.register_plugin(
         HostPlugin::HostInput,
         Box::new(Context::new(public_inputs, private_inputs)),
     );
 
     env.internal_env.register_function(
-        "wasm_input",
+        WASM_INPUT_FUNCTION_NAME,
         specs::host_function::Signature {
             params: vec![ValueType::I32],
             return_type: Some(specs::types::ValueType::I64),
         },
         HostPlugin::HostInput,
-        0,
+        WasmInputOp::Input as usize,
         wasm_input,
     );
+
+    // The outputs are taken from the trace by wasm_outputs.
+    env.internal_env.register_function(
+        WASM_OUTPUT_FUNCTION_NAME,
+        specs::host_function::Signature {
+            params: vec![ValueType::I64],
+            return_type: None,
+        },
+        HostPlugin::HostInput,
+        WasmInputOp::Output as usize,
+        Rc::new(|_context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| None),
+    );
+
+    env.internal_env.register_memory_function(
+        WASM_PRIVATE_INPUT_BYTES_FUNCTION_NAME,
+        specs::host_function::Signature {
+            params: vec![ValueType::I32, ValueType::I32],
+            return_type: None,
+        },
+        HostPlugin::HostInput,
+        WasmInputOp::PrivateInputBytes as usize,
+        wasm_private_input_bytes,
+    );
 }
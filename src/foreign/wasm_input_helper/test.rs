@@ -2,7 +2,8 @@
 mod tests {
     use crate::{
         foreign::wasm_input_helper::runtime::register_wasm_input_foreign,
-        runtime::host::host_env::HostEnv, test::test_circuit_with_env,
+        runtime::host::host_env::HostEnv,
+        test::{test_circuit_with_env, test_circuit_with_private_input_commitment},
     };
 
     use halo2_proofs::pairing::bn256::Fr as Fp;
@@ -76,4 +77,195 @@ mod tests {
         )
         .unwrap();
     }
+
+    #[test]
+    fn test_foreign_wasm_output() {
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+            (import "env" "wasm_output" (func $wasm_output (param i64)))
+            (export "main" (func $main))
+            (func $main
+              (call $wasm_output (i64.add (call $wasm_input (i32.const 1)) (i64.const 1)))
+              (call $wasm_output (i64.const 0))))
+        "#;
+
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+        let public_inputs = vec![9];
+
+        let mut env = HostEnv::new();
+        register_wasm_input_foreign(&mut env, public_inputs.clone(), vec![]);
+        env.finalize();
+
+        test_circuit_with_env(
+            env,
+            wasm,
+            "main",
+            public_inputs.into_iter().map(|v| Fp::from(v)).collect(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_foreign_wasm_private_input_bytes() {
+        /*
+         * Loads 11 bytes from the offset 3, the bytes out of the range are
+         * kept in the written blocks.
+         */
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_private_input_bytes" (func $input_bytes (param i32 i32)))
+            (memory (;0;) 1 1)
+            (export "memory" (memory 0))
+            (func (export "main")
+              (i64.store (i32.const 0) (i64.const -1))
+              (i64.store (i32.const 8) (i64.const -1))
+              (call $input_bytes (i32.const 3) (i32.const 11))
+              (call $input_bytes (i32.const 16) (i32.const 0))
+              (if (i64.ne (i64.load (i32.const 0)) (i64.const 0x0504030201ffffff))
+                (then unreachable))
+              (if (i64.ne (i64.load (i32.const 8)) (i64.const 0xffff0b0a09080706))
+                (then unreachable))))
+        "#;
+
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+        let private_inputs = vec![0x0807060504030201, 0x0b0a09];
+
+        let mut env = HostEnv::new();
+        register_wasm_input_foreign(&mut env, vec![], private_inputs);
+        env.finalize();
+
+        test_circuit_with_env(env, wasm, "main", vec![]).unwrap();
+    }
+
+    #[test]
+    fn test_foreign_wasm_private_input_bytes_trap() {
+        // Out of the memory, overflowing u32, without any memory and out of private inputs.
+        for (memory, ptr, len) in [
+            (
+                "(memory (;0;) 1 1) (export \"memory\" (memory 0))",
+                0xffff,
+                2,
+            ),
+            (
+                "(memory (;0;) 1 1) (export \"memory\" (memory 0))",
+                0xffffffffu32,
+                2,
+            ),
+            ("", 0, 1),
+            ("(memory (;0;) 1 1) (export \"memory\" (memory 0))", 0, 9),
+        ] {
+            let textual_repr = format!(
+                r#"
+                (module
+                    (import "env" "wasm_private_input_bytes" (func $input_bytes (param i32 i32)))
+                    {}
+                    (func (export "main")
+                      (call $input_bytes (i32.const {}) (i32.const {}))))
+                "#,
+                memory, ptr, len
+            );
+
+            let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+            let mut env = HostEnv::new();
+            register_wasm_input_foreign(&mut env, vec![], vec![0]);
+            env.finalize();
+
+            assert!(test_circuit_with_env(env, wasm, "main", vec![]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_foreign_wasm_private_input_commitment() {
+        /*
+         * The bytes take the private inputs 2 and 3, the last private input
+         * is committed without being consumed.
+         */
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+            (import "env" "wasm_private_input_bytes" (func $input_bytes (param i32 i32)))
+            (memory (;0;) 1 1)
+            (export "memory" (memory 0))
+            (func (export "main")
+              (if (i64.ne (call $wasm_input (i32.const 0)) (i64.const 1))
+                (then unreachable))
+              (call $input_bytes (i32.const 0) (i32.const 9))
+              (if (i64.ne (call $wasm_input (i32.const 0)) (i64.const 4))
+                (then unreachable))))
+        "#;
+
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+        let private_inputs = vec![1, 2, 3, 4, 5];
+
+        let mut env = HostEnv::new();
+        register_wasm_input_foreign(&mut env, vec![], private_inputs.clone());
+        env.finalize();
+
+        test_circuit_with_private_input_commitment(env, wasm, "main", vec![], private_inputs, 8)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_foreign_wasm_private_input_bytes_commitment() {
+        /*
+         * Loads 3 bytes of the private input 0 from the offset 6, the 5
+         * padding bytes of the private input are committed but not written.
+         */
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_private_input_bytes" (func $input_bytes (param i32 i32)))
+            (memory (;0;) 1 1)
+            (export "memory" (memory 0))
+            (func (export "main")
+              (call $input_bytes (i32.const 6) (i32.const 3))
+              (if (i64.ne (i64.load (i32.const 0)) (i64.const 0x0706000000000000))
+                (then unreachable))
+              (if (i64.ne (i64.load (i32.const 8)) (i64.const 0x08))
+                (then unreachable))))
+        "#;
+
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+        let private_inputs = vec![0x0102030405080706];
+
+        let mut env = HostEnv::new();
+        register_wasm_input_foreign(&mut env, vec![], private_inputs.clone());
+        env.finalize();
+
+        test_circuit_with_private_input_commitment(env, wasm, "main", vec![], private_inputs, 8)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_foreign_wasm_private_input_commitment_capacity() {
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+            (func (export "main")
+              (drop (call $wasm_input (i32.const 0)))))
+        "#;
+
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+        let private_inputs = vec![1, 2, 3];
+
+        let mut env = HostEnv::new();
+        register_wasm_input_foreign(&mut env, vec![], private_inputs.clone());
+        env.finalize();
+
+        assert!(test_circuit_with_private_input_commitment(
+            env,
+            wasm,
+            "main",
+            vec![],
+            private_inputs,
+            2
+        )
+        .is_err());
+    }
 }
@@ -1,7 +1,6 @@
 #![deny(unused_imports)]
 #![deny(dead_code)]
 #![deny(unused_variables)]
-#![feature(thread_local)]
 
 pub mod circuits;
 pub mod cli;
@@ -12,8 +11,6 @@ pub mod traits;
 #[cfg(test)]
 pub mod test;
 
-#[macro_use]
-extern crate lazy_static;
 extern crate downcast_rs;
 
 // fn main() {
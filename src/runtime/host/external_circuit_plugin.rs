@@ -1,5 +1,9 @@
 use specs::external_host_call_table::ExternalHostCallSignature;
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+};
 use wasmi::{FuncInstance, ModuleImportResolver, RuntimeArgs, RuntimeValue};
 
 use super::{ForeignContext, ForeignPlugin, MatchForeignOpSignature};
@@ -7,6 +11,7 @@ use super::{ForeignContext, ForeignPlugin, MatchForeignOpSignature};
 pub(super) struct ForeignOp {
     pub op_index: usize,
     pub sig: ExternalHostCallSignature,
+    pub public: bool,
     pub plugin: Rc<ForeignPlugin>,
     pub cb: Rc<dyn Fn(&mut dyn ForeignContext, RuntimeArgs) -> Option<RuntimeValue>>,
 }
@@ -43,6 +48,30 @@ impl ExternalCircuitEnv {
         sig: ExternalHostCallSignature,
         plugin: Rc<ForeignPlugin>,
         cb: Rc<dyn Fn(&mut dyn ForeignContext, RuntimeArgs) -> Option<RuntimeValue>>,
+    ) {
+        self.insert_function(name, op_index, sig, false, plugin, cb)
+    }
+
+    /// Register a foreign function whose calls are exposed in the transcript instance
+    pub fn register_public_function(
+        &mut self,
+        name: &str,
+        op_index: usize,
+        sig: ExternalHostCallSignature,
+        plugin: Rc<ForeignPlugin>,
+        cb: Rc<dyn Fn(&mut dyn ForeignContext, RuntimeArgs) -> Option<RuntimeValue>>,
+    ) {
+        self.insert_function(name, op_index, sig, true, plugin, cb)
+    }
+
+    fn insert_function(
+        &mut self,
+        name: &str,
+        op_index: usize,
+        sig: ExternalHostCallSignature,
+        public: bool,
+        plugin: Rc<ForeignPlugin>,
+        cb: Rc<dyn Fn(&mut dyn ForeignContext, RuntimeArgs) -> Option<RuntimeValue>>,
     ) {
         assert!(!*self.finalized.borrow());
 
@@ -51,11 +80,20 @@ impl ExternalCircuitEnv {
             ForeignOp {
                 op_index,
                 sig,
+                public,
                 plugin,
                 cb,
             },
         );
     }
+
+    /// The visibility of the registered functions by op index
+    pub fn visibility(&self) -> BTreeMap<usize, bool> {
+        self.functions
+            .values()
+            .map(|function| (function.op_index, function.public))
+            .collect()
+    }
 }
 
 impl ModuleImportResolver for ExternalCircuitEnv {
@@ -1,13 +1,13 @@
 use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
-use specs::host_function::HostFunctionDesc;
-use wasmi::{Externals, ModuleImportResolver, RuntimeArgs, RuntimeValue, Trap};
+use specs::host_function::{HostFunctionDesc, HostMemoryAccess};
+use wasmi::{Externals, MemoryRef, ModuleImportResolver, RuntimeArgs, RuntimeValue, Trap};
 
 use crate::runtime::host::HostFunctionExecutionEnv;
 
 use super::{
     external_circuit_plugin::ExternalCircuitEnv, internal_circuit_plugin::InternalCircuitEnv,
-    HostFunction,
+    ForeignContext, GuestMemory, HostFunction,
 };
 
 pub struct HostEnv {
@@ -16,6 +16,10 @@ pub struct HostEnv {
 
     finalized: Rc<RefCell<bool>>,
     cached_lookup: Option<HashMap<usize, HostFunction>>,
+
+    memory: Option<MemoryRef>,
+    // The guest memory accesses of each host call, in the order of the calls.
+    memory_accesses: Vec<Vec<HostMemoryAccess>>,
 }
 
 impl HostEnv {
@@ -36,9 +40,20 @@ impl HostEnv {
             external_env: ExternalCircuitEnv::new(finalized.clone()),
             cached_lookup: None,
             finalized,
+            memory: None,
+            memory_accesses: vec![],
         }
     }
 
+    /// Attaches the linear memory of the guest, which the host functions access.
+    pub fn attach_memory(&mut self, memory: MemoryRef) {
+        self.memory = Some(memory);
+    }
+
+    pub(crate) fn take_memory_accesses(&mut self) -> Vec<Vec<HostMemoryAccess>> {
+        std::mem::take(&mut self.memory_accesses)
+    }
+
     /// Finalizes the host environment to allocate the index of registered objects.
     /// After finalizing the host env, registering any object causes a panic.
     pub fn finalize(&mut self) {
@@ -63,7 +78,17 @@ impl HostEnv {
                         },
                         execution_env: HostFunctionExecutionEnv {
                             ctx: op.plugin.ctx.clone(),
-                            cb: op.cb.clone(),
+                            cb: {
+                                let cb = op.cb.clone();
+
+                                Rc::new(
+                                    move |ctx: &mut dyn ForeignContext,
+                                          _memory: &mut GuestMemory,
+                                          args: RuntimeArgs| {
+                                        Ok(cb(ctx, args))
+                                    },
+                                )
+                            },
                         },
                     },
                 )
@@ -141,8 +166,12 @@ impl Externals for HostEnv {
                 let ctx = function.execution_env.ctx.clone();
                 let mut ctx = (*ctx).borrow_mut();
                 let ctx = ctx.as_mut();
+                let mut memory = GuestMemory::new(self.memory.clone());
+
+                let ret = (function.execution_env.cb)(ctx, &mut memory, args)?;
+                self.memory_accesses.push(memory.accesses);
 
-                Ok((function.execution_env.cb)(ctx, args))
+                Ok(ret)
             }
             None => unreachable!(),
         }
@@ -1,15 +1,21 @@
 use specs::host_function::{HostPlugin, Signature};
 use std::{cell::RefCell, collections::HashMap, rc::Rc};
-use wasmi::{FuncInstance, ModuleImportResolver, RuntimeArgs, RuntimeValue};
+use wasmi::{FuncInstance, ModuleImportResolver, RuntimeArgs, RuntimeValue, Trap};
 
-use super::{ForeignContext, ForeignPlugin};
+use super::{ForeignContext, ForeignPlugin, GuestMemory};
 
 pub(super) struct ForeignOp {
     pub index: Option<usize>,
     pub index_within_plugin: usize,
     pub sig: Signature,
     pub plugin: HostPlugin,
-    pub cb: Rc<dyn Fn(&mut dyn ForeignContext, RuntimeArgs) -> Option<RuntimeValue>>,
+    pub cb: Rc<
+        dyn Fn(
+            &mut dyn ForeignContext,
+            &mut GuestMemory,
+            RuntimeArgs,
+        ) -> Result<Option<RuntimeValue>, Trap>,
+    >,
 }
 
 pub struct InternalCircuitEnv {
@@ -36,6 +42,17 @@ impl InternalCircuitEnv {
         );
     }
 
+    /*
+     * The context of a registered plugin, a plugin keeping a witness besides
+     * the trace hands it over after the execution.
+     */
+    pub fn plugin_context(
+        &self,
+        plugin: HostPlugin,
+    ) -> Option<Rc<RefCell<Box<dyn ForeignContext>>>> {
+        self.plugins.get(&plugin).map(|plugin| plugin.ctx.clone())
+    }
+
     pub fn register_function(
         &mut self,
         function_name: &str,
@@ -43,6 +60,62 @@ impl InternalCircuitEnv {
         plugin: HostPlugin,
         index_within_plugin: usize,
         cb: Rc<dyn Fn(&mut dyn ForeignContext, RuntimeArgs) -> Option<RuntimeValue>>,
+    ) {
+        self.register_fallible_function(
+            function_name,
+            sig,
+            plugin,
+            index_within_plugin,
+            Rc::new(move |context: &mut dyn ForeignContext, args: RuntimeArgs| {
+                Ok(cb(context, args))
+            }),
+        )
+    }
+
+    /*
+     * Registers a host function trapping the guest on the arguments it rejects,
+     * see HostFunctionError.
+     */
+    pub fn register_fallible_function(
+        &mut self,
+        function_name: &str,
+        sig: Signature,
+        plugin: HostPlugin,
+        index_within_plugin: usize,
+        cb: Rc<dyn Fn(&mut dyn ForeignContext, RuntimeArgs) -> Result<Option<RuntimeValue>, Trap>>,
+    ) {
+        self.register_memory_function(
+            function_name,
+            sig,
+            plugin,
+            index_within_plugin,
+            Rc::new(
+                move |context: &mut dyn ForeignContext,
+                      _memory: &mut GuestMemory,
+                      args: RuntimeArgs| cb(context, args),
+            ),
+        )
+    }
+
+    /*
+     * Registers a host function accessing the guest memory, e.g. through a
+     * (ptr, len) pair of its arguments. The etable config of the plugin
+     * counts the recorded accesses in its mops. An access out of the guest
+     * memory traps, see GuestMemory.
+     */
+    pub fn register_memory_function(
+        &mut self,
+        function_name: &str,
+        sig: Signature,
+        plugin: HostPlugin,
+        index_within_plugin: usize,
+        cb: Rc<
+            dyn Fn(
+                &mut dyn ForeignContext,
+                &mut GuestMemory,
+                RuntimeArgs,
+            ) -> Result<Option<RuntimeValue>, Trap>,
+        >,
     ) {
         assert!(!*self.finalized.borrow());
 
@@ -1,7 +1,11 @@
 use downcast_rs::{impl_downcast, Downcast};
-use specs::{external_host_call_table::ExternalHostCallSignature, host_function::HostFunctionDesc};
-use std::{cell::RefCell, rc::Rc};
-use wasmi::{RuntimeArgs, RuntimeValue, Signature};
+use specs::{
+    external_host_call_table::ExternalHostCallSignature,
+    host_function::{HostFunctionDesc, HostMemoryAccess},
+    mtable::AccessType,
+};
+use std::{cell::RefCell, fmt, rc::Rc};
+use wasmi::{HostError, MemoryRef, RuntimeArgs, RuntimeValue, Signature, Trap, TrapKind};
 
 pub mod host_env;
 
@@ -46,14 +50,140 @@ impl MatchForeignOpSignature for ExternalHostCallSignature {
 pub trait ForeignContext: Downcast {}
 impl_downcast!(ForeignContext);
 
+/// The error of a host function rejecting its arguments, it traps the guest.
+#[derive(Debug)]
+pub struct HostFunctionError(String);
+
+impl HostFunctionError {
+    pub fn trap(message: String) -> Trap {
+        Trap::new(TrapKind::Host(Box::new(HostFunctionError(message))))
+    }
+}
+
+impl fmt::Display for HostFunctionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "host function error: {}", self.0)
+    }
+}
+
+impl HostError for HostFunctionError {}
+
 pub struct ForeignPlugin {
     ctx: Rc<RefCell<Box<dyn ForeignContext>>>,
 }
 
+/// The linear memory of the guest seen by a host function.
+///
+/// The u64 blocks touched by the host function are recorded, they become the
+/// heap accesses of the calling step in the memory table.
+pub struct GuestMemory {
+    memory: Option<MemoryRef>,
+    accesses: Vec<HostMemoryAccess>,
+}
+
+impl GuestMemory {
+    fn new(memory: Option<MemoryRef>) -> Self {
+        GuestMemory {
+            memory,
+            accesses: vec![],
+        }
+    }
+
+    fn memory(&self) -> Result<&MemoryRef, Trap> {
+        self.memory
+            .as_ref()
+            .ok_or_else(|| HostFunctionError::trap("the guest exports no memory".to_owned()))
+    }
+
+    fn blocks(ptr: u32, len: u32) -> Result<std::ops::Range<u32>, Trap> {
+        if len == 0 {
+            Ok(0..0)
+        } else {
+            let last = ptr.checked_add(len - 1).ok_or_else(|| {
+                HostFunctionError::trap(format!("{} bytes from {} overflow u32", len, ptr))
+            })?;
+
+            Ok(ptr / 8..last / 8 + 1)
+        }
+    }
+
+    fn out_of_bounds(ptr: u32, len: usize) -> Trap {
+        HostFunctionError::trap(format!(
+            "{} bytes from {} are out of the guest memory",
+            len, ptr
+        ))
+    }
+
+    fn block_value(&self, offset: u32) -> Result<u64, Trap> {
+        let bytes = self
+            .memory()?
+            .get(offset * 8, 8)
+            .map_err(|_| Self::out_of_bounds(offset * 8, 8))?;
+
+        Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn record(&mut self, offset: u32, atype: AccessType) -> Result<(), Trap> {
+        let value = self.block_value(offset)?;
+
+        self.accesses.push(HostMemoryAccess {
+            offset,
+            atype,
+            value,
+        });
+
+        Ok(())
+    }
+
+    /// Reads the bytes [ptr, ptr + len) of the guest memory, traps if they are
+    /// out of the memory.
+    pub fn read(&mut self, ptr: u32, len: u32) -> Result<Vec<u8>, Trap> {
+        for offset in Self::blocks(ptr, len)? {
+            self.record(offset, AccessType::Read)?;
+        }
+
+        self.memory()?
+            .get(ptr, len as usize)
+            .map_err(|_| Self::out_of_bounds(ptr, len as usize))
+    }
+
+    /// Writes the bytes to the guest memory from ptr, traps without writing if
+    /// they are out of the memory.
+    pub fn write(&mut self, ptr: u32, bytes: &[u8]) -> Result<(), Trap> {
+        let blocks = Self::blocks(ptr, bytes.len() as u32)?;
+        let pre_values = blocks
+            .clone()
+            .map(|offset| self.block_value(offset))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.memory()?
+            .set(ptr, bytes)
+            .map_err(|_| Self::out_of_bounds(ptr, bytes.len()))?;
+
+        // Each block is read before it is written, as the bulk memory instructions do.
+        for (offset, value) in blocks.zip(pre_values) {
+            self.accesses.push(HostMemoryAccess {
+                offset,
+                atype: AccessType::Read,
+                value,
+            });
+            self.record(offset, AccessType::Write)?;
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Clone)]
 struct HostFunctionExecutionEnv {
     ctx: Rc<RefCell<Box<dyn ForeignContext>>>,
-    cb: Rc<dyn Fn(&mut dyn ForeignContext, RuntimeArgs) -> Option<RuntimeValue>>,
+    cb: Rc<
+        dyn Fn(
+            &mut dyn ForeignContext,
+            &mut GuestMemory,
+            RuntimeArgs,
+        ) -> Result<Option<RuntimeValue>, Trap>,
+    >,
 }
 
 #[derive(Clone)]
@@ -1,9 +1,15 @@
 use std::{cell::RefCell, rc::Rc};
 
+use num_bigint::BigUint;
+
 use specs::{
-    etable::EventTableEntry,
+    brtable::TableLayout,
+    encode::br_table::encode_func_ref,
+    etable::{EventTable, EventTableEntry},
     external_host_call_table::ExternalHostCallSignature,
-    mtable::{AccessType, LocationType, MemoryTableEntry, VarType},
+    host_function::HostMemoryAccessTable,
+    itable::Opcode,
+    mtable::{AccessType, LocationType, MTable, MemoryTableEntry, VarType},
     step::StepInfo,
     CompilationTable, Tables,
 };
@@ -23,12 +29,40 @@ pub struct CompiledImage<I, T> {
 pub struct ExecutionResult<R> {
     pub tables: Tables,
     pub result: Option<R>,
+    pub trap: Option<wasmi::Trap>,
 }
 
 // TODO: use feature
 pub type WasmInterpreter = WasmiRuntime;
 
-pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<MemoryTableEntry> {
+pub fn mtable_of_etable(
+    etable: &EventTable,
+    host_memory_accesses: &HostMemoryAccessTable,
+    compilation_tables: &CompilationTable,
+) -> MTable {
+    let mentries = etable
+        .entries()
+        .iter()
+        .map(|eentry| {
+            memory_event_of_step(
+                eentry,
+                &compilation_tables.table_layouts,
+                host_memory_accesses,
+                &mut 1,
+            )
+        })
+        .collect::<Vec<Vec<_>>>()
+        .concat();
+
+    MTable::new(mentries, &compilation_tables.imtable)
+}
+
+pub fn memory_event_of_step(
+    event: &EventTableEntry,
+    table_layouts: &[TableLayout],
+    host_memory_accesses: &HostMemoryAccessTable,
+    emid: &mut u32,
+) -> Vec<MemoryTableEntry> {
     let eid = event.eid;
     let sp_before_execution = event.sp;
 
@@ -40,7 +74,6 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
             ..
         } => {
             assert_eq!(keep.len(), keep_values.len());
-            assert!(keep.len() <= 1);
 
             let mut sp = sp_before_execution + 1;
             let mut ops = vec![];
@@ -64,7 +97,7 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
             }
 
             sp += drop;
-            sp -= 1;
+            sp -= keep.len() as u32;
 
             {
                 for i in 0..keep.len() {
@@ -79,7 +112,7 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
                         value: keep_values[i],
                     });
 
-                    sp = sp - 1;
+                    sp = sp + 1;
                     *emid = (*emid).checked_add(1).unwrap();
                 }
             }
@@ -94,7 +127,6 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
             ..
         } => {
             assert_eq!(keep.len(), keep_values.len());
-            assert!(keep.len() <= 1);
 
             let mut sp = sp_before_execution + 1;
 
@@ -135,7 +167,7 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
             }
 
             sp += drop;
-            sp -= 1;
+            sp -= keep.len() as u32;
 
             {
                 for i in 0..keep.len() {
@@ -150,7 +182,7 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
                         value: keep_values[i],
                     });
 
-                    sp = sp - 1;
+                    sp = sp + 1;
                     *emid = (*emid).checked_add(1).unwrap();
                 }
             }
@@ -165,7 +197,6 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
             ..
         } => {
             assert_eq!(keep.len(), keep_values.len());
-            assert!(keep.len() <= 1);
 
             let mut sp = sp_before_execution + 1;
 
@@ -206,7 +237,7 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
             }
 
             sp += drop;
-            sp -= 1;
+            sp -= keep.len() as u32;
 
             {
                 for i in 0..keep.len() {
@@ -221,7 +252,7 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
                         value: keep_values[i],
                     });
 
-                    sp = sp - 1;
+                    sp = sp + 1;
                     *emid = (*emid).checked_add(1).unwrap();
                 }
             }
@@ -236,7 +267,6 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
             ..
         } => {
             assert_eq!(keep.len(), keep_values.len());
-            assert!(keep.len() <= 1);
 
             let mut sp = sp_before_execution + 1;
 
@@ -273,7 +303,7 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
             }
 
             sp += drop;
-            sp -= 1;
+            sp -= keep.len() as u32;
 
             {
                 for i in 0..keep.len() {
@@ -288,7 +318,7 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
                         value: keep_values[i],
                     });
 
-                    sp = sp - 1;
+                    sp = sp + 1;
                     *emid = (*emid).checked_add(1).unwrap();
                 }
             }
@@ -300,10 +330,16 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
             keep,
             drop_values,
             keep_values,
+        }
+        | StepInfo::ReturnCall {
+            drop,
+            keep,
+            drop_values,
+            keep_values,
+            ..
         } => {
             assert_eq!(*drop as usize, drop_values.len());
             assert_eq!(keep.len(), keep_values.len());
-            assert!(keep.len() <= 1);
 
             let mut sp = sp_before_execution + 1;
             let mut ops = vec![];
@@ -327,7 +363,7 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
             }
 
             sp += drop;
-            sp -= 1;
+            sp -= keep.len() as u32;
 
             {
                 for i in 0..keep.len() {
@@ -342,7 +378,7 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
                         value: keep_values[i],
                     });
 
-                    sp = sp - 1;
+                    sp = sp + 1;
                     *emid = (*emid).checked_add(1).unwrap();
                 }
             }
@@ -416,7 +452,12 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
         StepInfo::Call { index: _ } => {
             vec![]
         }
-        StepInfo::CallIndirect { offset, .. } => {
+        StepInfo::CallIndirect {
+            table_index,
+            type_index,
+            offset,
+            func_index,
+        } => {
             let stack_read = MemoryTableEntry {
                 eid,
                 emid: *emid,
@@ -429,7 +470,269 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
             };
             *emid = (*emid).checked_add(1).unwrap();
 
-            vec![stack_read]
+            let elem_read = mem_op_on_table(
+                eid,
+                emid,
+                table_layouts[*table_index as usize].elem_offset(*offset),
+                AccessType::Read,
+                func_ref(*type_index, *func_index),
+            );
+
+            vec![stack_read, elem_read]
+        }
+        StepInfo::ReturnCallIndirect {
+            table_index,
+            type_index,
+            offset,
+            func_index,
+            drop,
+            keep,
+            drop_values,
+            keep_values,
+        } => {
+            assert_eq!(*drop as usize, drop_values.len());
+            assert_eq!(keep.len(), keep_values.len());
+
+            let mut sp = sp_before_execution + 1;
+
+            let mut ops = vec![MemoryTableEntry {
+                eid,
+                emid: *emid,
+                offset: sp,
+                ltype: LocationType::Stack,
+                atype: AccessType::Read,
+                vtype: VarType::I32,
+                is_mutable: true,
+                value: *offset as u64,
+            }];
+
+            sp = sp + 1;
+            *emid = (*emid).checked_add(1).unwrap();
+
+            {
+                for i in 0..keep.len() {
+                    ops.push(MemoryTableEntry {
+                        eid,
+                        emid: *emid,
+                        offset: sp,
+                        ltype: LocationType::Stack,
+                        atype: AccessType::Read,
+                        vtype: keep[i].into(),
+                        is_mutable: true,
+                        value: keep_values[i],
+                    });
+
+                    sp = sp + 1;
+                    *emid = (*emid).checked_add(1).unwrap();
+                }
+            }
+
+            sp += drop;
+            sp -= keep.len() as u32;
+
+            {
+                for i in 0..keep.len() {
+                    ops.push(MemoryTableEntry {
+                        eid,
+                        emid: *emid,
+                        offset: sp,
+                        ltype: LocationType::Stack,
+                        atype: AccessType::Write,
+                        vtype: keep[i].into(),
+                        is_mutable: true,
+                        value: keep_values[i],
+                    });
+
+                    sp = sp + 1;
+                    *emid = (*emid).checked_add(1).unwrap();
+                }
+            }
+
+            ops.push(mem_op_on_table(
+                eid,
+                emid,
+                table_layouts[*table_index as usize].elem_offset(*offset),
+                AccessType::Read,
+                func_ref(*type_index, *func_index),
+            ));
+
+            ops
+        }
+        StepInfo::TableGet {
+            table_index,
+            index,
+            size,
+            value,
+        } => {
+            let layout = &table_layouts[*table_index as usize];
+
+            let mut ops = mem_op_from_stack_only_step(
+                sp_before_execution,
+                eid,
+                emid,
+                VarType::I32,
+                VarType::I32,
+                &[*index as u64],
+                &[],
+            );
+            ops.push(mem_op_on_table(
+                eid,
+                emid,
+                layout.base,
+                AccessType::Read,
+                *size as u64,
+            ));
+            ops.push(mem_op_on_table(
+                eid,
+                emid,
+                layout.elem_offset(*index),
+                AccessType::Read,
+                *value,
+            ));
+            ops.append(&mut mem_op_from_stack_only_step(
+                sp_before_execution + 1,
+                eid,
+                emid,
+                VarType::I64,
+                VarType::I64,
+                &[],
+                &[*value],
+            ));
+
+            ops
+        }
+        StepInfo::TableSet {
+            table_index,
+            index,
+            size,
+            value,
+        } => {
+            let layout = &table_layouts[*table_index as usize];
+
+            let mut ops = mem_op_from_stack_only_step(
+                sp_before_execution,
+                eid,
+                emid,
+                VarType::I64,
+                VarType::I64,
+                &[*value],
+                &[],
+            );
+            ops.append(&mut mem_op_from_stack_only_step(
+                sp_before_execution + 1,
+                eid,
+                emid,
+                VarType::I32,
+                VarType::I32,
+                &[*index as u64],
+                &[],
+            ));
+            ops.push(mem_op_on_table(
+                eid,
+                emid,
+                layout.base,
+                AccessType::Read,
+                *size as u64,
+            ));
+            ops.push(mem_op_on_table(
+                eid,
+                emid,
+                layout.elem_offset(*index),
+                AccessType::Write,
+                *value,
+            ));
+
+            ops
+        }
+        StepInfo::TableSize { table_index, size } => {
+            let mut ops = vec![mem_op_on_table(
+                eid,
+                emid,
+                table_layouts[*table_index as usize].base,
+                AccessType::Read,
+                *size as u64,
+            )];
+            ops.append(&mut mem_op_from_stack_only_step(
+                sp_before_execution,
+                eid,
+                emid,
+                VarType::I32,
+                VarType::I32,
+                &[],
+                &[*size as u64],
+            ));
+
+            ops
+        }
+        StepInfo::TableGrow {
+            table_index,
+            delta,
+            init,
+            size,
+            result,
+        } => {
+            let layout = &table_layouts[*table_index as usize];
+
+            let mut ops = mem_op_from_stack_only_step(
+                sp_before_execution,
+                eid,
+                emid,
+                VarType::I32,
+                VarType::I32,
+                &[*delta as u64],
+                &[],
+            );
+            ops.append(&mut mem_op_from_stack_only_step(
+                sp_before_execution + 1,
+                eid,
+                emid,
+                VarType::I64,
+                VarType::I64,
+                &[*init],
+                &[],
+            ));
+            ops.push(mem_op_on_table(
+                eid,
+                emid,
+                layout.base,
+                AccessType::Read,
+                *size as u64,
+            ));
+
+            if *result != -1 {
+                ops.push(mem_op_on_table(
+                    eid,
+                    emid,
+                    layout.base,
+                    AccessType::Write,
+                    (*size + *delta) as u64,
+                ));
+
+                // Slots beyond the size are null, only a non-null init has to be written.
+                if *init != 0 {
+                    for index in *size..*size + *delta {
+                        ops.push(mem_op_on_table(
+                            eid,
+                            emid,
+                            layout.elem_offset(index),
+                            AccessType::Write,
+                            *init,
+                        ));
+                    }
+                }
+            }
+
+            ops.append(&mut mem_op_from_stack_only_step(
+                sp_before_execution + 2,
+                eid,
+                emid,
+                VarType::I32,
+                VarType::I32,
+                &[],
+                &[*result as u32 as u64],
+            ));
+
+            ops
         }
         StepInfo::CallHost {
             args,
@@ -472,6 +775,22 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
                 *emid = (*emid).checked_add(1).unwrap();
             }
 
+            // The guest memory accessed by the host function follows the stack.
+            for access in host_memory_accesses.accesses(eid) {
+                mops.push(MemoryTableEntry {
+                    eid,
+                    emid: *emid,
+                    offset: access.offset,
+                    ltype: LocationType::Heap,
+                    atype: access.atype,
+                    vtype: VarType::I64,
+                    is_mutable: true,
+                    value: access.value,
+                });
+
+                *emid = (*emid).checked_add(1).unwrap();
+            }
+
             mops
         }
         StepInfo::ExternalHostCall { value, sig, .. } => match sig {
@@ -993,9 +1312,307 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
             &[*value as u32 as u64],
             &[*result as u64],
         ),
+        StepInfo::SignExtend {
+            vtype,
+            value,
+            result,
+            ..
+        } => mem_op_from_stack_only_step(
+            sp_before_execution,
+            eid,
+            emid,
+            *vtype,
+            *vtype,
+            &[*value],
+            &[*result],
+        ),
+
+        StepInfo::MemoryFill {
+            offset,
+            value,
+            len,
+            pre_block_values,
+            updated_block_values,
+        } => {
+            let mut ops = mem_op_from_stack_only_step(
+                sp_before_execution,
+                eid,
+                emid,
+                VarType::I32,
+                VarType::I32,
+                &[*len as u64, *value as u64, *offset as u64],
+                &[],
+            );
+
+            ops.append(&mut mem_op_from_bulk_memory_blocks(
+                eid,
+                emid,
+                None,
+                (
+                    *offset / 8,
+                    &pre_block_values[..],
+                    &updated_block_values[..],
+                ),
+            ));
+
+            ops
+        }
+        StepInfo::MemoryCopy {
+            dst,
+            src,
+            len,
+            src_block_values,
+            pre_block_values,
+            updated_block_values,
+        } => {
+            let mut ops = mem_op_from_stack_only_step(
+                sp_before_execution,
+                eid,
+                emid,
+                VarType::I32,
+                VarType::I32,
+                &[*len as u64, *src as u64, *dst as u64],
+                &[],
+            );
+
+            ops.append(&mut mem_op_from_bulk_memory_blocks(
+                eid,
+                emid,
+                Some((LocationType::Heap, *src / 8, &src_block_values[..])),
+                (*dst / 8, &pre_block_values[..], &updated_block_values[..]),
+            ));
+
+            ops
+        }
+        StepInfo::MemoryInit {
+            segment_len,
+            dst,
+            src,
+            len,
+            data_block_values,
+            pre_block_values,
+            updated_block_values,
+            ..
+        } => {
+            let segment_offset = match event.inst.opcode {
+                Opcode::MemoryInit { segment_offset, .. } => segment_offset,
+                _ => unreachable!(),
+            };
+
+            let mut ops = mem_op_from_stack_only_step(
+                sp_before_execution,
+                eid,
+                emid,
+                VarType::I32,
+                VarType::I32,
+                &[*len as u64, *src as u64, *dst as u64],
+                &[],
+            );
+
+            ops.push(mem_op_on_data(
+                eid,
+                emid,
+                segment_offset,
+                AccessType::Read,
+                *segment_len as u64,
+            ));
+
+            ops.append(&mut mem_op_from_bulk_memory_blocks(
+                eid,
+                emid,
+                Some((
+                    LocationType::Data,
+                    segment_offset + 1 + *src / 8,
+                    &data_block_values[..],
+                )),
+                (*dst / 8, &pre_block_values[..], &updated_block_values[..]),
+            ));
+
+            ops
+        }
+        StepInfo::DataDrop { .. } => {
+            let segment_offset = match event.inst.opcode {
+                Opcode::DataDrop { segment_offset, .. } => segment_offset,
+                _ => unreachable!(),
+            };
+
+            vec![mem_op_on_data(
+                eid,
+                emid,
+                segment_offset,
+                AccessType::Write,
+                0,
+            )]
+        }
+
+        StepInfo::Unreachable => vec![],
+        StepInfo::LoadOutOfBounds { raw_address, .. } => mem_op_from_stack_only_step(
+            sp_before_execution,
+            eid,
+            emid,
+            VarType::I32,
+            VarType::I32,
+            &[*raw_address as u64],
+            &[],
+        ),
+        StepInfo::StoreOutOfBounds {
+            vtype,
+            raw_address,
+            value,
+            ..
+        } => {
+            let mut ops = mem_op_from_stack_only_step(
+                sp_before_execution,
+                eid,
+                emid,
+                *vtype,
+                *vtype,
+                &[*value],
+                &[],
+            );
+
+            ops.append(&mut mem_op_from_stack_only_step(
+                sp_before_execution + 1,
+                eid,
+                emid,
+                VarType::I32,
+                VarType::I32,
+                &[*raw_address as u64],
+                &[],
+            ));
+
+            ops
+        }
+        StepInfo::I32BinOpTrap { left, right, .. } => mem_op_from_stack_only_step(
+            sp_before_execution,
+            eid,
+            emid,
+            VarType::I32,
+            VarType::I32,
+            &[*right as u32 as u64, *left as u32 as u64],
+            &[],
+        ),
+        StepInfo::I64BinOpTrap { left, right, .. } => mem_op_from_stack_only_step(
+            sp_before_execution,
+            eid,
+            emid,
+            VarType::I64,
+            VarType::I64,
+            &[*right as u64, *left as u64],
+            &[],
+        ),
     }
 }
 
+/*
+ * Reads the source blocks if any, then reads and writes each destination
+ * block, the operands are popped by the caller.
+ */
+fn mem_op_from_bulk_memory_blocks(
+    eid: u32,
+    emid: &mut u32,
+    src: Option<(LocationType, u32, &[u64])>,
+    (dst_start_block, pre_block_values, updated_block_values): (u32, &[u64], &[u64]),
+) -> Vec<MemoryTableEntry> {
+    assert_eq!(pre_block_values.len(), updated_block_values.len());
+
+    let mut mem_op = vec![];
+
+    if let Some((ltype, src_start_block, src_block_values)) = src {
+        for (i, value) in src_block_values.iter().enumerate() {
+            mem_op.push(MemoryTableEntry {
+                eid,
+                emid: *emid,
+                offset: src_start_block + i as u32,
+                ltype,
+                atype: AccessType::Read,
+                vtype: VarType::I64,
+                is_mutable: ltype == LocationType::Heap,
+                value: *value,
+            });
+            *emid = (*emid).checked_add(1).unwrap();
+        }
+    }
+
+    for (i, (pre_value, updated_value)) in pre_block_values
+        .iter()
+        .zip(updated_block_values.iter())
+        .enumerate()
+    {
+        for (atype, value) in [
+            (AccessType::Read, pre_value),
+            (AccessType::Write, updated_value),
+        ] {
+            mem_op.push(MemoryTableEntry {
+                eid,
+                emid: *emid,
+                offset: dst_start_block + i as u32,
+                ltype: LocationType::Heap,
+                atype,
+                vtype: VarType::I64,
+                is_mutable: true,
+                value: *value,
+            });
+            *emid = (*emid).checked_add(1).unwrap();
+        }
+    }
+
+    mem_op
+}
+
+fn func_ref(type_index: u32, func_index: u32) -> u64 {
+    u64::try_from(encode_func_ref(
+        BigUint::from(type_index),
+        BigUint::from(func_index),
+    ))
+    .unwrap()
+}
+
+// The length of a passive data segment, the only mutable block of LocationType::Data.
+fn mem_op_on_data(
+    eid: u32,
+    emid: &mut u32,
+    segment_offset: u32,
+    atype: AccessType,
+    value: u64,
+) -> MemoryTableEntry {
+    let entry = MemoryTableEntry {
+        eid,
+        emid: *emid,
+        offset: segment_offset,
+        ltype: LocationType::Data,
+        atype,
+        vtype: VarType::I64,
+        is_mutable: true,
+        value,
+    };
+    *emid = (*emid).checked_add(1).unwrap();
+
+    entry
+}
+
+fn mem_op_on_table(
+    eid: u32,
+    emid: &mut u32,
+    offset: u32,
+    atype: AccessType,
+    value: u64,
+) -> MemoryTableEntry {
+    let entry = MemoryTableEntry {
+        eid,
+        emid: *emid,
+        offset,
+        ltype: LocationType::Table,
+        atype,
+        vtype: VarType::I64,
+        is_mutable: true,
+        value,
+    };
+    *emid = (*emid).checked_add(1).unwrap();
+
+    entry
+}
+
 pub(crate) fn mem_op_from_stack_only_step(
     sp_before_execution: u32,
     eid: u32,
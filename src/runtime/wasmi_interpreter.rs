@@ -1,50 +1,117 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+};
 
-use crate::runtime::memory_event_of_step;
-use anyhow::Result;
+use crate::runtime::mtable_of_etable;
+use anyhow::{anyhow, bail, Result};
 use specs::{
-    host_function::HostFunctionDesc, jtable::StaticFrameEntry, mtable::MTable, CompilationTable,
-    ExecutionTable, Tables,
+    etable::EventTable,
+    host_function::{HostFunctionDesc, HostMemoryAccess, HostMemoryAccessTable},
+    imtable::{table_limits, tables_entries, InitMemoryTable},
+    jtable::StaticFrameEntry,
+    kvtable::KvTable,
+    step::StepInfo,
+    CompilationTable, ExecutionTable, Tables,
 };
-use wasmi::{Externals, ImportResolver, ModuleInstance, RuntimeValue};
+use wasmi::{ImportResolver, ModuleInstance, RuntimeValue, TrapKind};
 
-use super::{CompiledImage, ExecutionResult};
+use super::{host::host_env::HostEnv, CompiledImage, ExecutionResult};
 
 pub trait Execution<R> {
-    fn run<E: Externals>(self, externals: &mut E) -> Result<ExecutionResult<R>>;
+    fn run(self, env: &mut HostEnv) -> Result<ExecutionResult<R>>;
+}
+
+/*
+ * Each host call is a CallHost or an ExternalHostCall step, so the guest
+ * memory accesses recorded by the host env are matched with the steps in order.
+ */
+fn host_memory_access_table(
+    etable: &EventTable,
+    accesses: Vec<Vec<HostMemoryAccess>>,
+) -> HostMemoryAccessTable {
+    let mut accesses = accesses.into_iter();
+    let mut table = BTreeMap::new();
+
+    for entry in etable.entries() {
+        match &entry.step_info {
+            StepInfo::CallHost { .. } => {
+                let accesses = accesses.next().unwrap();
+
+                if !accesses.is_empty() {
+                    table.insert(entry.eid, accesses);
+                }
+            }
+            StepInfo::ExternalHostCall { .. } => assert!(accesses.next().unwrap().is_empty()),
+            _ => (),
+        }
+    }
+
+    assert!(accesses.next().is_none());
+
+    HostMemoryAccessTable::new(table)
 }
 
 impl Execution<RuntimeValue>
     for CompiledImage<wasmi::NotStartedModuleRef<'_>, wasmi::tracer::Tracer>
 {
-    fn run<E: Externals>(self, externals: &mut E) -> Result<ExecutionResult<RuntimeValue>> {
+    fn run(self, env: &mut HostEnv) -> Result<ExecutionResult<RuntimeValue>> {
+        if let Some(memory) = self
+            .instance
+            .not_started_instance()
+            .export_by_name("memory")
+            .and_then(|export| export.as_memory().cloned())
+        {
+            env.attach_memory(memory);
+        }
+
         let instance = self
             .instance
-            .run_start_tracer(externals, self.tracer.clone())
+            .run_start_tracer(env, self.tracer.clone())
             .unwrap();
 
-        let result =
-            instance.invoke_export_trace(&self.entry, &[], externals, self.tracer.clone())?;
+        // A trap still yields the tables, the trapping step is the last one.
+        let (result, trap) =
+            match instance.invoke_export_trace(&self.entry, &[], env, self.tracer.clone()) {
+                Ok(result) => (result, None),
+                Err(wasmi::Error::Trap(trap)) => {
+                    // A host function trapping is not a step the circuit proves.
+                    if let TrapKind::Host(error) = trap.kind() {
+                        bail!("{}", error);
+                    }
+
+                    (None, Some(trap))
+                }
+                Err(e) => return Err(e.into()),
+            };
 
         let execution_tables = {
             let tracer = self.tracer.borrow();
 
-            let mtable = {
-                let mentries = tracer
-                    .etable
-                    .entries()
-                    .iter()
-                    .map(|eentry| memory_event_of_step(eentry, &mut 1))
-                    .collect::<Vec<Vec<_>>>()
-                    .concat();
+            let etable = tracer.etable.clone();
+            let host_memory_accesses =
+                host_memory_access_table(&etable, env.take_memory_accesses());
+
+            // The circuit only grows tables with null slots, see TableGrowConfig.
+            if let Some(entry) = etable.entries().iter().find(|entry| match entry.step_info {
+                StepInfo::TableGrow { init, .. } => init != 0,
+                _ => false,
+            }) {
+                bail!(
+                    "table.grow with a non-null init value (eid {}) is not supported by the circuit",
+                    entry.eid
+                );
+            }
 
-                MTable::new(mentries, &self.tables.imtable)
-            };
+            let mtable = mtable_of_etable(&etable, &host_memory_accesses, &self.tables);
 
             ExecutionTable {
-                etable: tracer.etable.clone(),
+                etable,
                 mtable,
                 jtable: tracer.jtable.clone(),
+                host_memory_accesses,
+                kvtable: KvTable::default(),
             }
         };
 
@@ -54,6 +121,7 @@ impl Execution<RuntimeValue>
                 execution_tables,
             },
             result,
+            trap,
         })
     }
 }
@@ -65,8 +133,13 @@ impl WasmiRuntime {
         WasmiRuntime
     }
 
+    /*
+     * `wasm` is the binary `module` is loaded from, the tracer does not emit the
+     * tables so they are laid out from its table section.
+     */
     pub fn compile<'a, I: ImportResolver>(
         &self,
+        wasm: &[u8],
         module: &'a wasmi::Module,
         imports: &I,
         host_plugin_lookup: &HashMap<usize, HostFunctionDesc>,
@@ -109,8 +182,16 @@ impl WasmiRuntime {
         }
 
         let itable = tracer.borrow().itable.clone();
-        let imtable = tracer.borrow().imtable.finalized();
         let elem_table = tracer.borrow().elem_table.clone();
+        let (tables_entries, table_layouts) = tables_entries(
+            &table_limits(wasm).map_err(|e| anyhow!("failed to read the tables: {:?}", e))?,
+            &elem_table,
+        );
+        let imtable = {
+            let mut entries = tracer.borrow().imtable.finalized().entries().clone();
+            entries.extend(tables_entries);
+            InitMemoryTable::new(entries)
+        };
         let configure_table = tracer.borrow().configure_table.clone();
         let static_jtable = tracer.borrow().static_jtable_entries.clone();
 
@@ -120,6 +201,7 @@ impl WasmiRuntime {
                 itable,
                 imtable,
                 elem_table,
+                table_layouts,
                 configure_table,
                 static_jtable,
             },
@@ -1,13 +1,34 @@
 use crate::{
-    circuits::{config::zkwasm_k, TestCircuit},
+    circuits::{
+        config::CircuitLayout, external_host_call_table::external_host_call_transcript,
+        image_table::image_commitment, utils::bn_to_field, TestCircuit,
+    },
+    foreign::{
+        kv_helper::{kv_roots, runtime::kv_table},
+        registry::PluginRegistry,
+        wasm_input_helper::{private_input_commitment, wasm_outputs},
+    },
     runtime::{
-        host::host_env::HostEnv, wasmi_interpreter::Execution, ExecutionResult, WasmInterpreter,
+        host::host_env::HostEnv, mtable_of_etable, wasmi_interpreter::Execution, ExecutionResult,
+        WasmInterpreter,
     },
 };
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use halo2_proofs::{arithmetic::FieldExt, dev::MockProver, pairing::bn256::Fr as Fp};
-use specs::Tables;
+use num_bigint::BigUint;
+use specs::{
+    etable::{EventTable, EventTableEntry},
+    host_function::HostPlugin,
+    imtable::{passive_data_segments_entries, InitMemoryTable},
+    itable::{InstructionTable, InstructionTableEntry, Opcode},
+    jtable::{JumpTable, JumpTableEntry},
+    mtable::{AccessType, LocationType, VarType},
+    step::StepInfo,
+    types::ValueType,
+    CompilationTable, Tables,
+};
+use std::collections::BTreeMap;
 use wasmi::{ImportsBuilder, RuntimeValue};
 
 mod spec;
@@ -15,29 +36,58 @@ mod test_binary_search;
 mod test_fibonacci;
 mod test_sha256;
 mod test_start;
+mod test_universal;
 
-fn test_circuit(
-    mut env: HostEnv,
+fn execute(
+    env: &mut HostEnv,
     wasm: Vec<u8>,
     function_name: &str,
-    public_inputs: Vec<Fp>,
 ) -> Result<ExecutionResult<RuntimeValue>> {
     let module = wasmi::Module::from_buffer(&wasm).expect("failed to load wasm");
 
-    let imports = ImportsBuilder::new().with_resolver("env", &env);
+    let imports = ImportsBuilder::new().with_resolver("env", &*env);
 
     let compiler = WasmInterpreter::new();
     let compiled_module = compiler
         .compile(
+            &wasm,
             &module,
             &imports,
             &env.function_description_table(),
             function_name,
         )
         .unwrap();
-    let execution_result = compiled_module.run(&mut env)?;
+    let mut execution_result = compiled_module.run(env)?;
+
+    if let Some(kvtable) = kv_table(env) {
+        execution_result.tables.execution_tables.kvtable = kvtable;
+    }
+
+    if let Some(trap) = &execution_result.trap {
+        return Err(anyhow!("execution trapped: {:?}", trap));
+    }
+
+    Ok(execution_result)
+}
+
+fn test_circuit(
+    mut env: HostEnv,
+    plugins: PluginRegistry<Fp>,
+    wasm: Vec<u8>,
+    function_name: &str,
+    public_inputs: Vec<Fp>,
+    layout: CircuitLayout,
+) -> Result<ExecutionResult<RuntimeValue>> {
+    let execution_result = execute(&mut env, wasm, function_name)?;
 
-    run_test_circuit::<Fp>(execution_result.tables.clone(), public_inputs)?;
+    run_test_circuit_with_layout::<Fp>(
+        execution_result.tables.clone(),
+        plugins,
+        env.external_env.visibility(),
+        None,
+        public_inputs,
+        layout,
+    )?;
 
     Ok(execution_result)
 }
@@ -48,7 +98,55 @@ fn test_circuit_noexternal_function(textual_repr: &str, function_name: &str) ->
     let mut env = HostEnv::new();
     env.finalize();
 
-    test_circuit(env, wasm, function_name, vec![]).unwrap();
+    test_circuit(
+        env,
+        PluginRegistry::new(),
+        wasm,
+        function_name,
+        vec![],
+        CircuitLayout::default(),
+    )
+    .unwrap();
+
+    Ok(())
+}
+
+/*
+ * Compiles the image without running it, images using instructions the circuit
+ * does not constrain are rejected here.
+ */
+pub fn compile_noexternal(textual_repr: &str) -> Result<()> {
+    let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+    let mut env = HostEnv::new();
+    env.finalize();
+
+    let module = wasmi::Module::from_buffer(&wasm).expect("failed to load wasm");
+    let imports = ImportsBuilder::new().with_resolver("env", &env);
+
+    let compiler = WasmInterpreter::new();
+    compiler.compile(
+        &wasm,
+        &module,
+        &imports,
+        &env.function_description_table(),
+        "test",
+    )?;
+
+    Ok(())
+}
+
+/*
+ * Runs the image without proving it, executions with steps the circuit does
+ * not support are rejected here.
+ */
+pub fn run_noexternal(textual_repr: &str) -> Result<()> {
+    let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+    let mut env = HostEnv::new();
+    env.finalize();
+
+    execute(&mut env, wasm, "test")?;
 
     Ok(())
 }
@@ -59,21 +157,809 @@ pub fn test_circuit_noexternal(textual_repr: &str) -> Result<()> {
     Ok(())
 }
 
+/*
+ * The wasmi tracer does not emit the steps of some instructions supported by
+ * the circuit yet (see TODO.md). Their tests trace a program where each of
+ * these instructions is replaced by a stand-in with the same stack effect,
+ * and patch the steps of the stand-ins into the steps of the instructions.
+ */
+pub enum StepPatch {
+    Keep,
+    // A stand-in may take more than one step, it can't jump.
+    Remove,
+    // The opcode replaces the instruction of the step in the instruction table.
+    Replace(Opcode, StepInfo),
+}
+
+/*
+ * Proves the trace of `textual_repr` with each `(instruction, stand_in)` of
+ * `stand_ins` replaced, once `patch` visited its steps in order. The
+ * passive data segments are laid out in the init memory table, wasmi can't
+ * load modules declaring them.
+ *
+ * The trace ends at the first trapping step, it is then proven in trap mode.
+ */
+pub fn test_circuit_noexternal_patched(
+    textual_repr: &str,
+    stand_ins: &[(&str, &str)],
+    passive_data_segments: &[Vec<u8>],
+    mut patch: impl FnMut(&CompilationTable, &EventTableEntry) -> StepPatch,
+) -> Result<()> {
+    let textual_repr = stand_ins.iter().fold(
+        textual_repr.to_owned(),
+        |textual_repr, (instruction, stand_in)| textual_repr.replace(instruction, stand_in),
+    );
+    let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+    let mut env = HostEnv::new();
+    env.finalize();
+
+    let mut tables = execute(&mut env, wasm, "test")?.tables;
+
+    let (mut imtable_entries, _) = passive_data_segments_entries(passive_data_segments);
+    imtable_entries.extend(tables.compilation_tables.imtable.entries().iter().cloned());
+    tables.compilation_tables.imtable = InitMemoryTable::new(imtable_entries);
+
+    let mut opcodes = BTreeMap::<(u32, u32), Opcode>::new();
+    // From the eids of the trace to the eids of the patched trace
+    let mut eids = BTreeMap::<u32, u32>::new();
+    let mut entries = vec![];
+    let mut trap_eid = None;
+    let first_eid = tables
+        .execution_tables
+        .etable
+        .entries()
+        .first()
+        .map_or(0, |entry| entry.eid);
+
+    for entry in tables.execution_tables.etable.entries() {
+        match patch(&tables.compilation_tables, entry) {
+            StepPatch::Keep => entries.push(entry.clone()),
+            StepPatch::Remove => {
+                assert_eq!(entry.inst.opcode.jops(), 0);
+                continue;
+            }
+            StepPatch::Replace(opcode, step_info) => {
+                opcodes.insert((entry.inst.fid, entry.inst.iid), opcode.clone());
+
+                entries.push(EventTableEntry {
+                    inst: InstructionTableEntry {
+                        opcode,
+                        ..entry.inst.clone()
+                    },
+                    step_info,
+                    ..entry.clone()
+                });
+            }
+        }
+
+        eids.insert(entry.eid, first_eid + entries.len() as u32 - 1);
+
+        if entries.last().unwrap().step_info.is_trap() {
+            trap_eid = Some(entry.eid);
+            break;
+        }
+    }
+
+    let patched_eid = |eid: u32| eids.get(&eid).cloned().unwrap_or(eid);
+    for entry in entries.iter_mut() {
+        entry.eid = patched_eid(entry.eid);
+        entry.last_jump_eid = patched_eid(entry.last_jump_eid);
+    }
+    let mut jtable = JumpTable::default();
+    for entry in tables
+        .execution_tables
+        .jtable
+        .entries()
+        .iter()
+        .filter(|entry| trap_eid.map_or(true, |trap_eid| entry.eid <= trap_eid))
+    {
+        jtable.push(JumpTableEntry {
+            eid: patched_eid(entry.eid),
+            last_jump_eid: patched_eid(entry.last_jump_eid),
+            ..entry.clone()
+        });
+    }
+    tables.execution_tables.jtable = jtable;
+
+    // An instruction is either always patched or never.
+    for entry in entries.iter() {
+        if let Some(opcode) = opcodes.get(&(entry.inst.fid, entry.inst.iid)) {
+            let patched: BigUint = opcode.clone().into();
+            let traced: BigUint = entry.inst.opcode.clone().into();
+            assert_eq!(patched, traced);
+        }
+    }
+
+    tables.compilation_tables.itable = InstructionTable::new(
+        tables
+            .compilation_tables
+            .itable
+            .entries()
+            .iter()
+            .map(|entry| InstructionTableEntry {
+                opcode: opcodes
+                    .get(&(entry.fid, entry.iid))
+                    .cloned()
+                    .unwrap_or(entry.opcode.clone()),
+                ..entry.clone()
+            })
+            .collect(),
+    );
+    tables.execution_tables.etable = EventTable::new(entries);
+    tables.execution_tables.mtable = mtable_of_etable(
+        &tables.execution_tables.etable,
+        &tables.execution_tables.host_memory_accesses,
+        &tables.compilation_tables,
+    );
+
+    if trap_eid.is_some() {
+        run_test_circuit_trapped(tables)
+    } else {
+        run_test_circuit::<Fp>(tables, vec![])
+    }
+}
+
+// The bytes of a memory, in u64 blocks.
+#[derive(Default)]
+struct Blocks(BTreeMap<u32, u64>);
+
+impl Blocks {
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let mut blocks = Blocks::default();
+        for (address, byte) in bytes.iter().enumerate() {
+            blocks.write(address as u32, *byte);
+        }
+        blocks
+    }
+
+    fn read(&self, address: u32) -> u8 {
+        (self.0.get(&(address / 8)).cloned().unwrap_or(0) >> (address % 8 * 8)) as u8
+    }
+
+    fn write(&mut self, address: u32, byte: u8) {
+        let block = self.0.entry(address / 8).or_insert(0);
+        *block = (*block & !(0xff << (address % 8 * 8))) | ((byte as u64) << (address % 8 * 8));
+    }
+
+    // The blocks touched by [start, start + len), empty if len is 0.
+    fn values(&self, start: u32, len: u32) -> Vec<u64> {
+        if len == 0 {
+            return vec![];
+        }
+
+        (start / 8..=(start + len - 1) / 8)
+            .map(|block| self.0.get(&block).cloned().unwrap_or(0))
+            .collect()
+    }
+}
+
+enum BulkMemoryOp {
+    Fill,
+    Copy,
+    Init(usize),
+    DataDrop(usize),
+}
+
+/*
+ * The wasmi tracer does not emit the bulk memory steps and can't load passive
+ * data segments. The passive data segments `(data $name "...")`, each on its
+ * own line, are removed from the module. `(memory.fill)`, `(memory.copy)` and
+ * `(memory.init $name)` stand in as `(select) (drop)`, `(data.drop $name)` as
+ * `(block (br 0))`. Each of these instructions must run once, in the order of
+ * the text, their steps are computed on a model of the heap.
+ */
+pub fn test_circuit_bulk_memory(textual_repr: &str) -> Result<()> {
+    let mut stand_ins = vec![];
+    let mut segment_names = vec![];
+    let mut passive_data_segments = vec![];
+
+    for line in textual_repr.lines().map(|line| line.trim()) {
+        if let Some(segment) = line.strip_prefix("(data $") {
+            let (name, bytes) = segment.split_once(' ').unwrap();
+            let bytes = bytes.trim_end_matches(')').trim_matches('"');
+
+            let mut segment = vec![];
+            let mut chars = bytes.chars();
+            while let Some(c) = chars.next() {
+                if c == '\\' {
+                    let hex = chars.by_ref().take(2).collect::<String>();
+                    segment.push(u8::from_str_radix(&hex, 16).unwrap());
+                } else {
+                    segment.push(c as u8);
+                }
+            }
+
+            segment_names.push(format!("${}", name));
+            passive_data_segments.push(segment);
+            stand_ins.push((line, ""));
+        }
+    }
+
+    let segment_of = |instruction: &str| {
+        segment_names
+            .iter()
+            .position(|name| instruction.ends_with(&format!(" {})", name)))
+            .unwrap()
+    };
+
+    let mut ops = vec![];
+    for (position, _) in textual_repr.match_indices('(') {
+        let end = position + textual_repr[position..].find(')').unwrap() + 1;
+        let instruction = &textual_repr[position..end];
+
+        let (op, stand_in) = if instruction == "(memory.fill)" {
+            (BulkMemoryOp::Fill, "(select) (drop)")
+        } else if instruction == "(memory.copy)" {
+            (BulkMemoryOp::Copy, "(select) (drop)")
+        } else if instruction.starts_with("(memory.init ") {
+            (
+                BulkMemoryOp::Init(segment_of(instruction)),
+                "(select) (drop)",
+            )
+        } else if instruction.starts_with("(data.drop ") {
+            (
+                BulkMemoryOp::DataDrop(segment_of(instruction)),
+                "(block (br 0))",
+            )
+        } else {
+            continue;
+        };
+
+        ops.push(op);
+        stand_ins.push((instruction, stand_in));
+    }
+
+    let (_, segment_offsets) = passive_data_segments_entries(&passive_data_segments);
+    let mut segment_lens = passive_data_segments
+        .iter()
+        .map(|segment| segment.len() as u32)
+        .collect::<Vec<_>>();
+
+    let mut heap = None;
+    let mut ops = ops.into_iter();
+    let mut is_stand_in_drop = false;
+
+    test_circuit_noexternal_patched(
+        textual_repr,
+        &stand_ins,
+        &passive_data_segments,
+        |compilation_tables, entry| {
+            let heap = heap.get_or_insert_with(|| {
+                Blocks(
+                    compilation_tables
+                        .imtable
+                        .filter(LocationType::Heap)
+                        .into_iter()
+                        .map(|entry| (entry.offset, entry.value))
+                        .collect(),
+                )
+            });
+
+            match entry.step_info {
+                StepInfo::Select {
+                    val1, val2, cond, ..
+                } => {
+                    let (dst, operand, len) = (val1 as u32, val2 as u32, cond as u32);
+                    let pre_block_values = heap.values(dst, len);
+                    is_stand_in_drop = true;
+
+                    match ops.next().unwrap() {
+                        BulkMemoryOp::Fill => {
+                            for address in dst..dst + len {
+                                heap.write(address, operand as u8);
+                            }
+
+                            StepPatch::Replace(
+                                Opcode::MemoryFill,
+                                StepInfo::MemoryFill {
+                                    offset: dst,
+                                    value: operand,
+                                    len,
+                                    pre_block_values,
+                                    updated_block_values: heap.values(dst, len),
+                                },
+                            )
+                        }
+                        BulkMemoryOp::Copy => {
+                            let src = operand;
+                            let src_block_values = heap.values(src, len);
+                            let bytes = (src..src + len)
+                                .map(|address| heap.read(address))
+                                .collect::<Vec<_>>();
+                            for (address, byte) in (dst..dst + len).zip(bytes) {
+                                heap.write(address, byte);
+                            }
+
+                            StepPatch::Replace(
+                                Opcode::MemoryCopy,
+                                StepInfo::MemoryCopy {
+                                    dst,
+                                    src,
+                                    len,
+                                    src_block_values,
+                                    pre_block_values,
+                                    updated_block_values: heap.values(dst, len),
+                                },
+                            )
+                        }
+                        BulkMemoryOp::Init(segment) => {
+                            let src = operand;
+                            let data = Blocks::from_bytes(&passive_data_segments[segment]);
+                            for (address, src_address) in (dst..dst + len).zip(src..src + len) {
+                                heap.write(address, data.read(src_address));
+                            }
+
+                            StepPatch::Replace(
+                                Opcode::MemoryInit {
+                                    segment: segment as u32,
+                                    segment_offset: segment_offsets[segment],
+                                },
+                                StepInfo::MemoryInit {
+                                    segment: segment as u32,
+                                    segment_len: segment_lens[segment],
+                                    dst,
+                                    src,
+                                    len,
+                                    data_block_values: data.values(src, len),
+                                    pre_block_values,
+                                    updated_block_values: heap.values(dst, len),
+                                },
+                            )
+                        }
+                        BulkMemoryOp::DataDrop(_) => unreachable!(),
+                    }
+                }
+                StepInfo::Drop if is_stand_in_drop => {
+                    is_stand_in_drop = false;
+                    StepPatch::Remove
+                }
+                StepInfo::Br { .. } => match ops.next().unwrap() {
+                    BulkMemoryOp::DataDrop(segment) => {
+                        segment_lens[segment] = 0;
+
+                        StepPatch::Replace(
+                            Opcode::DataDrop {
+                                segment: segment as u32,
+                                segment_offset: segment_offsets[segment],
+                            },
+                            StepInfo::DataDrop {
+                                segment: segment as u32,
+                            },
+                        )
+                    }
+                    _ => unreachable!(),
+                },
+                _ => StepPatch::Keep,
+            }
+        },
+    )?;
+
+    assert!(ops.next().is_none());
+
+    Ok(())
+}
+
+/*
+ * The wasmi tracer does not emit the tail call steps and parity-wasm can't
+ * decode them. `return_call $f` and `return_call_indirect (type $t)`, each
+ * on its own line, stand in as the call followed by `return`. The steps of
+ * each pair are patched into the tail call step: the callee runs in the frame
+ * of the caller, whose values below the args of the call are dropped.
+ */
+pub fn test_circuit_tail_call(textual_repr: &str) -> Result<()> {
+    let textual_repr = textual_repr
+        .lines()
+        .map(|line| match line.trim().strip_prefix("return_call") {
+            Some(call) => format!("call{}\nreturn", call),
+            None => line.to_owned(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+    let mut env = HostEnv::new();
+    env.finalize();
+
+    let mut tables = execute(&mut env, wasm, "test")?.tables;
+    let traced = tables.execution_tables.etable.entries().clone();
+
+    let is_return_at = |fid: u32, iid: u32| {
+        tables
+            .compilation_tables
+            .itable
+            .entries()
+            .iter()
+            .any(|entry| {
+                entry.fid == fid
+                    && entry.iid == iid
+                    && matches!(entry.opcode, Opcode::Return { .. })
+            })
+    };
+
+    // From the index of the step of each stand-in call to the one of its return
+    let mut tail_calls = BTreeMap::<usize, usize>::new();
+    let mut calls = vec![];
+    for (index, entry) in traced.iter().enumerate() {
+        match entry.step_info {
+            StepInfo::Call { .. } | StepInfo::CallIndirect { .. } => calls.push(index),
+            StepInfo::Return { .. } => {
+                if let Some(call) = calls.pop() {
+                    let caller = &traced[call].inst;
+
+                    if is_return_at(caller.fid, caller.iid + 1) {
+                        assert_eq!(traced[index + 1].inst.fid, caller.fid);
+                        assert_eq!(traced[index + 1].inst.iid, caller.iid + 1);
+
+                        tail_calls.insert(call, index + 1);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // The value and the type of the last write on the stack entry before eid
+    let stack_entry = |eid: u32, offset: u32| {
+        let entry = tables
+            .execution_tables
+            .mtable
+            .entries()
+            .iter()
+            .filter(|entry| {
+                matches!(entry.ltype, LocationType::Stack)
+                    && matches!(entry.atype, AccessType::Write)
+                    && entry.offset == offset
+                    && entry.eid < eid
+            })
+            .max_by_key(|entry| (entry.eid, entry.emid))
+            .unwrap();
+
+        let vtype = match entry.vtype {
+            VarType::I32 => ValueType::I32,
+            VarType::I64 => ValueType::I64,
+        };
+
+        (vtype, entry.value)
+    };
+
+    let mut opcodes = BTreeMap::<(u32, u32), Opcode>::new();
+    // The callee of a tail call runs in the frame of the caller, at its last jump eid.
+    let mut frame_jump_eids = BTreeMap::<u32, u32>::new();
+    // The callee of a tail call is moved down by the values dropped from the frame.
+    let mut sp_shifts = vec![0];
+    let mut entries = vec![];
+
+    for (index, entry) in traced.iter().enumerate() {
+        let sp_shift = *sp_shifts.last().unwrap();
+
+        match &entry.step_info {
+            StepInfo::Call { .. } | StepInfo::CallIndirect { .. } => {
+                match tail_calls.get(&index) {
+                    Some(&ret) => {
+                        let (drop, results, drop_values) = match &traced[ret].step_info {
+                            StepInfo::Return {
+                                drop,
+                                keep,
+                                drop_values,
+                                ..
+                            } => (*drop, keep.len() as u32, drop_values.clone()),
+                            _ => unreachable!(),
+                        };
+                        // The args of an indirect call are below the offset of the element.
+                        let start = match entry.step_info {
+                            StepInfo::CallIndirect { .. } => 2,
+                            _ => 1,
+                        };
+                        let args = traced[ret].sp + results - entry.sp - (start - 1);
+                        let (keep, keep_values): (Vec<_>, Vec<_>) = (0..args)
+                            .map(|i| stack_entry(entry.eid, entry.sp + start + i))
+                            .unzip();
+
+                        let (opcode, step_info) = match (&entry.inst.opcode, &entry.step_info) {
+                            (Opcode::Call { index }, _) => (
+                                Opcode::ReturnCall {
+                                    drop,
+                                    keep: keep.clone(),
+                                    index: *index,
+                                },
+                                StepInfo::ReturnCall {
+                                    index: *index,
+                                    drop,
+                                    keep,
+                                    drop_values,
+                                    keep_values,
+                                },
+                            ),
+                            (
+                                Opcode::CallIndirect { type_idx },
+                                StepInfo::CallIndirect {
+                                    table_index,
+                                    type_index,
+                                    offset,
+                                    func_index,
+                                },
+                            ) => (
+                                Opcode::ReturnCallIndirect {
+                                    drop,
+                                    keep: keep.clone(),
+                                    type_idx: *type_idx,
+                                    table_idx: *table_index,
+                                },
+                                StepInfo::ReturnCallIndirect {
+                                    table_index: *table_index,
+                                    type_index: *type_index,
+                                    offset: *offset,
+                                    func_index: *func_index,
+                                    drop,
+                                    keep,
+                                    drop_values,
+                                    keep_values,
+                                },
+                            ),
+                            _ => unreachable!(),
+                        };
+
+                        let last_jump_eid = frame_jump_eids
+                            .get(&entry.last_jump_eid)
+                            .cloned()
+                            .unwrap_or(entry.last_jump_eid);
+                        frame_jump_eids.insert(entry.eid, last_jump_eid);
+                        sp_shifts.push(sp_shift + drop);
+
+                        let opcode = opcodes
+                            .entry((entry.inst.fid, entry.inst.iid))
+                            .or_insert(opcode)
+                            .clone();
+                        entries.push(EventTableEntry {
+                            sp: entry.sp + sp_shift,
+                            last_jump_eid,
+                            inst: InstructionTableEntry {
+                                opcode,
+                                ..entry.inst.clone()
+                            },
+                            step_info,
+                            ..entry.clone()
+                        });
+
+                        continue;
+                    }
+                    None => sp_shifts.push(sp_shift),
+                }
+            }
+            StepInfo::Return { .. } => {
+                sp_shifts.pop();
+            }
+            _ => {}
+        }
+
+        if tail_calls.values().any(|ret| *ret == index) {
+            continue;
+        }
+
+        entries.push(EventTableEntry {
+            sp: entry.sp + sp_shift,
+            last_jump_eid: frame_jump_eids
+                .get(&entry.last_jump_eid)
+                .cloned()
+                .unwrap_or(entry.last_jump_eid),
+            ..entry.clone()
+        });
+    }
+
+    let first_eid = traced.first().map_or(0, |entry| entry.eid);
+    let eids = entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| (entry.eid, first_eid + index as u32))
+        .collect::<BTreeMap<_, _>>();
+    let patched_eid = |eid: u32| eids.get(&eid).cloned().unwrap_or(eid);
+    for entry in entries.iter_mut() {
+        entry.eid = patched_eid(entry.eid);
+        entry.last_jump_eid = patched_eid(entry.last_jump_eid);
+    }
+
+    let mut jtable = JumpTable::default();
+    for entry in tables
+        .execution_tables
+        .jtable
+        .entries()
+        .iter()
+        .filter(|entry| !frame_jump_eids.contains_key(&entry.eid))
+    {
+        jtable.push(JumpTableEntry {
+            eid: patched_eid(entry.eid),
+            last_jump_eid: patched_eid(entry.last_jump_eid),
+            ..entry.clone()
+        });
+    }
+    tables.execution_tables.jtable = jtable;
+
+    tables.compilation_tables.itable = InstructionTable::new(
+        tables
+            .compilation_tables
+            .itable
+            .entries()
+            .iter()
+            .map(|entry| InstructionTableEntry {
+                opcode: opcodes
+                    .get(&(entry.fid, entry.iid))
+                    .cloned()
+                    .unwrap_or(entry.opcode.clone()),
+                ..entry.clone()
+            })
+            .collect(),
+    );
+    tables.execution_tables.etable = EventTable::new(entries);
+    tables.execution_tables.mtable = mtable_of_etable(
+        &tables.execution_tables.etable,
+        &tables.execution_tables.host_memory_accesses,
+        &tables.compilation_tables,
+    );
+
+    run_test_circuit::<Fp>(tables, vec![])
+}
+
+fn run_test_circuit_trapped(tables: Tables) -> Result<()> {
+    let trap_point = tables.execution_tables.etable.trap_point().unwrap();
+
+    let circuit = TestCircuit::<Fp>::new_trapped(tables);
+
+    let prover = circuit.with_params(|| {
+        MockProver::run(
+            circuit.layout.k,
+            &circuit,
+            vec![vec![], vec![], vec![bn_to_field(&trap_point)]],
+        )
+    })?;
+    assert_eq!(prover.verify(), Ok(()));
+
+    Ok(())
+}
+
 pub fn test_circuit_with_env(
     env: HostEnv,
     wasm: Vec<u8>,
     function_name: &str,
     public_inputs: Vec<Fp>,
 ) -> Result<ExecutionResult<RuntimeValue>> {
-    test_circuit(env, wasm, function_name, public_inputs)
+    test_circuit(
+        env,
+        PluginRegistry::new(),
+        wasm,
+        function_name,
+        public_inputs,
+        CircuitLayout::default(),
+    )
+}
+
+/*
+ * The runtime of the plugins must be registered in env, under the ids
+ * allocated by the registry.
+ */
+pub fn test_circuit_with_plugins(
+    env: HostEnv,
+    plugins: PluginRegistry<Fp>,
+    wasm: Vec<u8>,
+    function_name: &str,
+    public_inputs: Vec<Fp>,
+) -> Result<ExecutionResult<RuntimeValue>> {
+    test_circuit(
+        env,
+        plugins,
+        wasm,
+        function_name,
+        public_inputs,
+        CircuitLayout::default(),
+    )
+}
+
+pub fn test_circuit_with_env_and_layout(
+    env: HostEnv,
+    wasm: Vec<u8>,
+    function_name: &str,
+    public_inputs: Vec<Fp>,
+    layout: CircuitLayout,
+) -> Result<ExecutionResult<RuntimeValue>> {
+    test_circuit(
+        env,
+        PluginRegistry::new(),
+        wasm,
+        function_name,
+        public_inputs,
+        layout,
+    )
+}
+
+/*
+ * The private inputs must be the private inputs registered in env, they are
+ * committed with the capacity.
+ */
+pub fn test_circuit_with_private_input_commitment(
+    mut env: HostEnv,
+    wasm: Vec<u8>,
+    function_name: &str,
+    public_inputs: Vec<Fp>,
+    private_inputs: Vec<u64>,
+    capacity: usize,
+) -> Result<ExecutionResult<RuntimeValue>> {
+    let execution_result = execute(&mut env, wasm, function_name)?;
+
+    run_test_circuit_with_layout::<Fp>(
+        execution_result.tables.clone(),
+        PluginRegistry::new(),
+        env.external_env.visibility(),
+        Some((capacity, private_inputs)),
+        public_inputs,
+        CircuitLayout::default(),
+    )?;
+
+    Ok(execution_result)
 }
 
 pub fn run_test_circuit<F: FieldExt>(tables: Tables, public_inputs: Vec<F>) -> Result<()> {
+    run_test_circuit_with_layout(
+        tables,
+        PluginRegistry::new(),
+        BTreeMap::new(),
+        None,
+        public_inputs,
+        CircuitLayout::default(),
+    )
+}
+
+fn run_test_circuit_with_layout<F: FieldExt>(
+    tables: Tables,
+    plugins: PluginRegistry<F>,
+    external_host_call_visibility: BTreeMap<usize, bool>,
+    private_input_commitment: Option<(usize, Vec<u64>)>,
+    public_inputs: Vec<F>,
+    layout: CircuitLayout,
+) -> Result<()> {
     tables.write_json(None);
 
-    let circuit = TestCircuit::<F>::new(tables);
+    let layout = layout.fit_plugins(&tables, &plugins)?;
+    let mut circuit = TestCircuit::<F>::new_with_layout(tables, layout)
+        .with_plugins(plugins)
+        .with_external_host_call_visibility(external_host_call_visibility);
+    if let Some((capacity, private_inputs)) = private_input_commitment {
+        circuit = circuit.with_private_input_commitment(capacity, private_inputs);
+    }
+
+    let mut instances = vec![
+        public_inputs,
+        wasm_outputs(&circuit.tables.execution_tables.etable)
+            .into_iter()
+            .map(|v| F::from(v))
+            .collect(),
+    ];
+    if circuit.circuit_configure.enables_plugin(HostPlugin::Kv) {
+        instances.push(kv_roots(&circuit.tables.execution_tables.kvtable));
+    }
+    if let Some(capacity) = circuit.circuit_configure.private_input_commitment {
+        instances.push(vec![private_input_commitment(
+            &circuit.private_inputs,
+            capacity,
+        )?]);
+    }
+    if circuit.circuit_configure.publishes_external_host_calls() {
+        instances.push(external_host_call_transcript(
+            &circuit
+                .tables
+                .execution_tables
+                .etable
+                .filter_external_host_call_table(),
+            &circuit.circuit_configure.external_host_call_visibility,
+        ));
+    }
+    if let Some(image_table) = layout.image_table {
+        instances.push(vec![image_commitment(
+            &circuit.tables.compilation_tables,
+            &image_table,
+        )?]);
+    }
 
-    let prover = MockProver::run(zkwasm_k(), &circuit, vec![public_inputs])?;
+    let prover = circuit.with_params(|| MockProver::run(circuit.layout.k, &circuit, instances))?;
     assert_eq!(prover.verify(), Ok(()));
 
     Ok(())
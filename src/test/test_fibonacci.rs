@@ -91,8 +91,14 @@ fn build_test() -> Result<(ExecutionResult<RuntimeValue>, Vec<u64>, i32)> {
 
 mod tests {
     use super::*;
-    use crate::{circuits::ZkWasmCircuitBuilder, test::run_test_circuit};
-    use halo2_proofs::pairing::bn256::Fr as Fp;
+    use crate::{
+        circuits::{
+            config::{CircuitLayout, MIN_K},
+            TestCircuit, ZkWasmCircuitBuilder,
+        },
+        test::run_test_circuit,
+    };
+    use halo2_proofs::{dev::MockProver, pairing::bn256::Fr as Fp};
 
     #[test]
     fn test_fibonacci_mock() {
@@ -110,6 +116,65 @@ mod tests {
         .unwrap();
     }
 
+    #[test]
+    fn test_fibonacci_fit_layout() {
+        let (execution_result, public_inputs, _) = build_test().unwrap();
+
+        let layout = TestCircuit::<Fp>::new(execution_result.tables.clone())
+            .fit_layout()
+            .unwrap();
+        assert!(layout.etable_ratio <= CircuitLayout::default().etable_ratio);
+
+        let circuit = TestCircuit::<Fp>::new_with_layout(execution_result.tables, layout);
+        let prover = circuit
+            .with_params(|| {
+                MockProver::run(
+                    circuit.layout.k,
+                    &circuit,
+                    vec![
+                        public_inputs.into_iter().map(|v| Fp::from(v)).collect(),
+                        vec![],
+                    ],
+                )
+            })
+            .unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    /*
+     * The params of a circuit are scoped to its with_params call, a circuit
+     * of another K configured in a nested call does not leak into it.
+     */
+    #[test]
+    fn test_fibonacci_two_k_in_one_thread() {
+        let (execution_result, public_inputs, _) = build_test().unwrap();
+        let instances = vec![
+            public_inputs
+                .into_iter()
+                .map(|v| Fp::from(v))
+                .collect::<Vec<_>>(),
+            vec![],
+        ];
+
+        let circuit = TestCircuit::<Fp>::new(execution_result.tables.clone());
+        let larger_circuit = TestCircuit::<Fp>::new_with_layout(
+            execution_result.tables,
+            CircuitLayout::new(MIN_K + 1),
+        );
+
+        let (prover, larger_prover) = circuit.with_params(|| {
+            let larger_prover = larger_circuit.with_params(|| {
+                MockProver::run(larger_circuit.layout.k, &larger_circuit, instances.clone())
+            });
+            let prover = MockProver::run(circuit.layout.k, &circuit, instances.clone());
+
+            (prover, larger_prover)
+        });
+
+        assert_eq!(prover.unwrap().verify(), Ok(()));
+        assert_eq!(larger_prover.unwrap().verify(), Ok(()));
+    }
+
     #[test]
     fn test_fibonacci_full() {
         let (execution_result, public_inputs, expected_value) = build_test().unwrap();
@@ -3,6 +3,9 @@ mod tests {
     use crate::{
         circuits::ZkWasmCircuitBuilder,
         foreign::{
+            sha256_compress_helper::{
+                runtime::register_sha256_compress_foreign, test::tests::SHA256_COMPRESS_WAT,
+            },
             sha256_helper::{runtime::register_sha256_foreign, test::tests::prepare_inputs},
             wasm_input_helper::runtime::register_wasm_input_foreign,
         },
@@ -34,4 +37,31 @@ mod tests {
 
         builder.bench(public_inputs)
     }
+
+    /*
+     * The same digest as test_sha256_full, with the block compressed by the
+     * sha256 compress plugin instead of the guest calling the sha256 helpers.
+     */
+    #[test]
+    fn test_sha256_compress_full() {
+        let (public_inputs, private_inputs) = prepare_inputs();
+
+        let wasm = wabt::wat2wasm(SHA256_COMPRESS_WAT).expect("failed to parse wat");
+
+        let mut env = HostEnv::new();
+        register_wasm_input_foreign(&mut env, public_inputs.clone(), private_inputs.clone());
+        register_sha256_compress_foreign(&mut env);
+        env.finalize();
+
+        let public_inputs: Vec<Fp> = public_inputs.into_iter().map(|v| Fp::from(v)).collect();
+
+        let execution_result =
+            test_circuit_with_env(env, wasm, "sha256_digest", public_inputs.clone()).unwrap();
+
+        let builder = ZkWasmCircuitBuilder {
+            tables: execution_result.tables,
+        };
+
+        builder.bench(public_inputs)
+    }
 }
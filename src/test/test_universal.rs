@@ -0,0 +1,116 @@
+mod tests {
+    use crate::{
+        circuits::{
+            config::{CircuitLayout, ImageTableLayout},
+            image_table::image_commitment,
+        },
+        runtime::host::host_env::HostEnv,
+        test::{execute, test_circuit_with_env_and_layout},
+    };
+    use halo2_proofs::pairing::bn256::Fr as Fp;
+
+    fn test_universal_mock(textual_repr: &str) {
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+        let mut env = HostEnv::new();
+        env.finalize();
+
+        test_circuit_with_env_and_layout(
+            env,
+            wasm,
+            "zkmain",
+            vec![],
+            CircuitLayout::new_universal(18),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_universal_start_mock() {
+        let textual_repr = r#"
+        (module
+            (func (;0;)
+              i32.const 0
+              drop
+            )
+
+            (func (;1;)
+              i32.const 1
+              drop
+            )
+
+            (start 0)
+            (export "zkmain" (func 1))
+           )
+        "#;
+
+        test_universal_mock(textual_repr);
+    }
+
+    #[test]
+    fn test_universal_memory_mock() {
+        let textual_repr = r#"
+        (module
+            (memory $0 1 2)
+            (data (i32.const 0) "\01\02\03\04")
+
+            (func (export "zkmain")
+              (i32.store (i32.const 8) (i32.load (i32.const 0)))
+              (drop (memory.grow (i32.const 1)))
+            )
+           )
+        "#;
+
+        test_universal_mock(textual_repr);
+    }
+
+    // The commitment hashes the used rows only, so it doesn't depend on the capacities.
+    #[test]
+    fn test_universal_commitment_capacity() {
+        let textual_repr = r#"
+        (module
+            (memory $0 1 2)
+            (data (i32.const 0) "\01\02\03\04")
+
+            (func (export "zkmain")
+              (drop (i32.load (i32.const 0)))
+            )
+           )
+        "#;
+
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+        let mut env = HostEnv::new();
+        env.finalize();
+
+        let tables = execute(&mut env, wasm, "zkmain").unwrap().tables;
+
+        assert_eq!(
+            image_commitment::<Fp>(&tables.compilation_tables, &ImageTableLayout::new(18)).unwrap(),
+            image_commitment::<Fp>(&tables.compilation_tables, &ImageTableLayout::new(19)).unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_universal_commitment_exceeding_capacity() {
+        let textual_repr = r#"
+        (module
+            (func (export "zkmain")
+              (drop (i32.add (i32.const 1) (i32.const 2)))
+            )
+           )
+        "#;
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+        let mut env = HostEnv::new();
+        env.finalize();
+
+        let tables = execute(&mut env, wasm, "zkmain").unwrap().tables;
+        let layout = ImageTableLayout {
+            itable_rows: 2,
+            ..ImageTableLayout::new(18)
+        };
+
+        assert!(image_commitment::<Fp>(&tables.compilation_tables, &layout).is_err());
+    }
+}